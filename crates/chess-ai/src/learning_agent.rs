@@ -1,5 +1,5 @@
 use chess_core::ChessGame;
-use chess_engine::GameAnalyzer;
+use chess_engine::{GameAnalyzer, QualityCalibration};
 use chess_trainer::{TrainingSession, ExerciseDifficulty};
 use crate::playstyle::{PlayStyleAnalyzer, StyleCharacteristics};
 use crate::profile::PlayerProfile;
@@ -48,12 +48,14 @@ impl LearningAgent {
         self.game_history.push(game.clone());
         self.profile.increment_games_played();
 
-        // Analyze the game
-        let analyses = GameAnalyzer::analyze_game(&game);
+        // Analyze the game, judging moves against the player's own rating
+        let calibration = QualityCalibration::for_rating(self.profile.estimated_rating);
+        let analyses = GameAnalyzer::analyze_game_with(&game, &calibration);
         let weaknesses = GameAnalyzer::identify_weaknesses(&analyses);
+        tracing::debug!(user_id = self.profile.user_id, ?weaknesses, "analyzed game for player profile");
 
         // Analyze play style
-        let style_chars = PlayStyleAnalyzer::analyze_game(&game);
+        let style_chars = PlayStyleAnalyzer::analyze_game_with(&game, &calibration);
         self.profile.update_style(style_chars.clone());
 
         // Update profile with weaknesses
@@ -77,9 +79,10 @@ impl LearningAgent {
         // Analyze all games
         let mut all_weaknesses = Vec::new();
         let mut all_strengths = Vec::new();
+        let calibration = QualityCalibration::for_rating(self.profile.estimated_rating);
 
         for game in &games {
-            let analyses = GameAnalyzer::analyze_game(game);
+            let analyses = GameAnalyzer::analyze_game_with(game, &calibration);
             let weaknesses = GameAnalyzer::identify_weaknesses(&analyses);
             all_weaknesses.extend(weaknesses);
 
@@ -88,7 +91,7 @@ impl LearningAgent {
         }
 
         // Analyze aggregate play style
-        let style_chars = PlayStyleAnalyzer::analyze_multiple_games(&games);
+        let style_chars = PlayStyleAnalyzer::analyze_multiple_games(&games, &calibration);
         self.profile.update_style(style_chars);
 
         // Deduplicate and prioritize weaknesses
@@ -2,6 +2,6 @@ pub mod playstyle;
 pub mod learning_agent;
 pub mod profile;
 
-pub use playstyle::{PlayStyle, PlayStyleAnalyzer, StyleCharacteristics};
+pub use playstyle::{PlayStyle, PlayStyleAnalyzer, SacrificeStats, StyleCharacteristics};
 pub use learning_agent::{LearningAgent, AgentRecommendation};
 pub use profile::{PlayerProfile, SkillLevel};
@@ -1,9 +1,10 @@
+use chess::Color;
 use chess_core::ChessGame;
-use chess_engine::{GameAnalyzer, MoveAnalysis};
+use chess_engine::{GameAnalyzer, MoveAnalysis, QualityCalibration};
 use chess_core::MoveQuality;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PlayStyle {
     Aggressive,     // Prefers attacking, sacrifices
     Positional,     // Focuses on long-term advantages
@@ -12,6 +13,14 @@ pub enum PlayStyle {
     Balanced,       // Mix of all styles
 }
 
+chess_trainer::named_enum!(PlayStyle, "play style", {
+    PlayStyle::Aggressive => "Aggressive",
+    PlayStyle::Positional => "Positional",
+    PlayStyle::Tactical => "Tactical",
+    PlayStyle::Solid => "Solid",
+    PlayStyle::Balanced => "Balanced",
+});
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StyleCharacteristics {
     pub aggression_score: f32,      // 0.0 to 1.0
@@ -20,6 +29,61 @@ pub struct StyleCharacteristics {
     pub risk_taking_score: f32,     // 0.0 to 1.0
     pub accuracy_score: f32,        // 0.0 to 1.0
     pub primary_style: PlayStyle,
+    /// The player's moves that gave up material, tracked apart from the scores above
+    #[serde(default)]
+    pub sacrifices: SacrificeStats,
+}
+
+/// Side that played an analysed move. Games are analysed from the standard start,
+/// so White makes the even plies.
+fn mover(analysis: &MoveAnalysis) -> Color {
+    if analysis.move_number % 2 == 0 {
+        Color::White
+    } else {
+        Color::Black
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SacrificeStats {
+    pub moves_analyzed: u32,
+    pub sacrifices: u32,
+    /// Sacrifices the player's evaluation recovered from
+    pub sound: u32,
+}
+
+impl SacrificeStats {
+    fn from_analyses(analyses: &[MoveAnalysis], player_color: Color) -> Self {
+        let player_moves = analyses.iter().filter(|a| mover(a) == player_color);
+        let mut stats = Self::default();
+        for analysis in player_moves {
+            stats.moves_analyzed += 1;
+            if let Some(sacrifice) = analysis.sacrifice {
+                stats.sacrifices += 1;
+                stats.sound += sacrifice.sound as u32;
+            }
+        }
+        stats
+    }
+
+    /// Sacrifices per move played, 0.0 to 1.0
+    pub fn frequency(&self) -> f32 {
+        if self.moves_analyzed == 0 {
+            return 0.0;
+        }
+        self.sacrifices as f32 / self.moves_analyzed as f32
+    }
+
+    /// Share of sacrifices that paid off, or `None` before the first one
+    pub fn success_rate(&self) -> Option<f32> {
+        (self.sacrifices > 0).then(|| self.sound as f32 / self.sacrifices as f32)
+    }
+
+    fn add(&mut self, other: SacrificeStats) {
+        self.moves_analyzed += other.moves_analyzed;
+        self.sacrifices += other.sacrifices;
+        self.sound += other.sound;
+    }
 }
 
 impl StyleCharacteristics {
@@ -42,7 +106,12 @@ pub struct PlayStyleAnalyzer;
 
 impl PlayStyleAnalyzer {
     pub fn analyze_game(game: &ChessGame) -> StyleCharacteristics {
-        let analyses = GameAnalyzer::analyze_game(game);
+        Self::analyze_game_with(game, &QualityCalibration::default())
+    }
+
+    /// `analyze_game` judging moves by `calibration`, e.g. one for the player's rating
+    pub fn analyze_game_with(game: &ChessGame, calibration: &QualityCalibration) -> StyleCharacteristics {
+        let analyses = GameAnalyzer::analyze_game_with(game, calibration);
 
         let aggression_score = Self::calculate_aggression(&analyses);
         let tactical_score = Self::calculate_tactical_awareness(&analyses);
@@ -57,13 +126,14 @@ impl PlayStyleAnalyzer {
             risk_taking_score,
             accuracy_score,
             primary_style: PlayStyle::Balanced,
+            sacrifices: SacrificeStats::from_analyses(&analyses, game.player_color),
         };
 
         characteristics.primary_style = characteristics.determine_play_style();
         characteristics
     }
 
-    pub fn analyze_multiple_games(games: &[ChessGame]) -> StyleCharacteristics {
+    pub fn analyze_multiple_games(games: &[ChessGame], calibration: &QualityCalibration) -> StyleCharacteristics {
         if games.is_empty() {
             return Self::default_characteristics();
         }
@@ -73,14 +143,16 @@ impl PlayStyleAnalyzer {
         let mut total_positional = 0.0;
         let mut total_risk = 0.0;
         let mut total_accuracy = 0.0;
+        let mut sacrifices = SacrificeStats::default();
 
         for game in games {
-            let chars = Self::analyze_game(game);
+            let chars = Self::analyze_game_with(game, calibration);
             total_aggression += chars.aggression_score;
             total_tactical += chars.tactical_score;
             total_positional += chars.positional_score;
             total_risk += chars.risk_taking_score;
             total_accuracy += chars.accuracy_score;
+            sacrifices.add(chars.sacrifices);
         }
 
         let count = games.len() as f32;
@@ -91,6 +163,7 @@ impl PlayStyleAnalyzer {
             risk_taking_score: total_risk / count,
             accuracy_score: total_accuracy / count,
             primary_style: PlayStyle::Balanced,
+            sacrifices,
         };
 
         characteristics.primary_style = characteristics.determine_play_style();
@@ -102,10 +175,10 @@ impl PlayStyleAnalyzer {
             return 0.5;
         }
 
-        // Aggression based on sacrifices, checks, attacks
+        // Aggression based on sacrifices and attacks; a blunder is not a sacrifice
         let aggressive_moves = analyses.iter().filter(|a| {
-            a.centipawn_loss > 100 // Willing to sacrifice material
-                || a.evaluation_after - a.evaluation_before > 50 // Improved position
+            a.sacrifice.is_some() // Willing to sacrifice material
+                || a.evaluation_after.for_side(mover(a)) - a.evaluation_before.for_side(mover(a)) > 50 // Improved position
         }).count();
 
         (aggressive_moves as f32 / analyses.len() as f32).min(1.0)
@@ -171,6 +244,7 @@ impl PlayStyleAnalyzer {
             risk_taking_score: 0.5,
             accuracy_score: 0.5,
             primary_style: PlayStyle::Balanced,
+            sacrifices: SacrificeStats::default(),
         }
     }
 
@@ -198,7 +272,6 @@ impl PlayStyleAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chess::Color;
 
     #[test]
     fn test_default_characteristics() {
@@ -222,8 +295,30 @@ mod tests {
             risk_taking_score: 0.7,
             accuracy_score: 0.6,
             primary_style: PlayStyle::Balanced,
+            sacrifices: SacrificeStats::default(),
         };
 
         assert_eq!(aggressive_chars.determine_play_style(), PlayStyle::Aggressive);
     }
+
+    #[test]
+    fn test_sacrifices_are_not_blunders() {
+        // Legal's mate: 5.Nxe5 gives up the queen and mates two moves later
+        let mut game = ChessGame::new(Color::White);
+        for uci in ["e2e4", "e7e5", "g1f3", "d7d6", "f1c4", "c8g4", "b1c3", "g7g6", "f3e5", "g4d1", "c4f7", "e8e7", "c3d5"] {
+            game.make_move(chess_core::validate::parse_uci(uci).unwrap()).unwrap();
+        }
+        let chars = PlayStyleAnalyzer::analyze_game(&game);
+        assert_eq!(chars.sacrifices, SacrificeStats { moves_analyzed: 7, sacrifices: 1, sound: 1 });
+        assert_eq!(chars.sacrifices.success_rate(), Some(1.0));
+
+        let mut blunder = ChessGame::new(Color::White);
+        for uci in ["e2e4", "d7d5", "d1g4", "c8g4"] {
+            blunder.make_move(chess_core::validate::parse_uci(uci).unwrap()).unwrap();
+        }
+        let analyses = GameAnalyzer::analyze_game(&blunder);
+        assert!(analyses[2].centipawn_loss > 100);
+        assert!(analyses[2].sacrifice.is_none());
+        assert_eq!(PlayStyleAnalyzer::analyze_game(&blunder).sacrifices.sacrifices, 0);
+    }
 }
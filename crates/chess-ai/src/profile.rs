@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use crate::playstyle::{PlayStyle, StyleCharacteristics};
+use crate::playstyle::{PlayStyle, SacrificeStats, StyleCharacteristics};
 use chess_trainer::ExerciseDifficulty;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
@@ -60,6 +60,7 @@ impl PlayerProfile {
                 risk_taking_score: 0.5,
                 accuracy_score: 0.5,
                 primary_style: PlayStyle::Balanced,
+                sacrifices: SacrificeStats::default(),
             },
             games_played: 0,
             exercises_completed: 0,
@@ -0,0 +1,420 @@
+use chess_core::{MoveStyle, NotationPrefs, PieceLanguage, PieceStyle};
+use chess_engine::{Personality, MAX_DEPTH, SEARCH_DEPTH};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the optional config file in the app directory
+pub const CONFIG_FILE_NAME: &str = "config.json";
+pub const DEFAULT_BASE_URL: &str = "https://openrouter.ai/api/v1";
+/// Cheap, fast models tried in order for short replies
+pub const FAST_MODELS: &[&str] = &["anthropic/claude-3-haiku", "openai/gpt-4o-mini"];
+/// Stronger models tried in order for in-depth analysis
+pub const STRONG_MODELS: &[&str] = &["anthropic/claude-3.5-sonnet", "openai/gpt-4o"];
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+/// Unicode symbols in diagrams and the board widget's own pieces in the GUI
+pub const DEFAULT_PIECE_SET: &str = "standard";
+pub const DEFAULT_BOARD_THEME: &str = "brown";
+/// Centipawns an exercise answer may trail the stored solution by and still count
+pub const DEFAULT_SOLUTION_MARGIN: i32 = 30;
+/// Widest margin accepted; beyond this clearly worse moves would pass
+pub const MAX_SOLUTION_MARGIN: i32 = 300;
+
+const APP_DIR_NAME: &str = "tacticus";
+const DB_FILE_NAME: &str = "tacticus.db";
+
+/// Every config key, dotted the way the config file nests it
+pub const KEYS: &[&str] = &[
+    "db_path",
+    "log_level",
+    "engine.depth",
+    "engine.personality",
+    "engine.solution_margin",
+    "llm.api_key",
+    "llm.base_url",
+    "llm.model",
+    "notation.moves",
+    "notation.pieces",
+    "notation.language",
+    "notation.coordinates",
+    "theme.board_theme",
+    "theme.piece_set",
+];
+
+/// Environment variables read over the config file, as (variable, key)
+pub const ENV_VARS: &[(&str, &str)] = &[
+    ("TACTICUS_DB_PATH", "db_path"),
+    ("TACTICUS_LOG_LEVEL", "log_level"),
+    ("TACTICUS_ENGINE_DEPTH", "engine.depth"),
+    ("TACTICUS_ENGINE_PERSONALITY", "engine.personality"),
+    ("TACTICUS_SOLUTION_MARGIN", "engine.solution_margin"),
+    ("OPENROUTER_API_KEY", "llm.api_key"),
+    ("OPENROUTER_BASE_URL", "llm.base_url"),
+    ("TACTICUS_LLM_MODEL", "llm.model"),
+    ("TACTICUS_NOTATION_MOVES", "notation.moves"),
+    ("TACTICUS_NOTATION_PIECES", "notation.pieces"),
+    ("TACTICUS_NOTATION_LANGUAGE", "notation.language"),
+    ("TACTICUS_NOTATION_COORDINATES", "notation.coordinates"),
+    ("TACTICUS_BOARD_THEME", "theme.board_theme"),
+    ("TACTICUS_PIECE_SET", "theme.piece_set"),
+];
+
+/// Rows of the app's settings table read over everything else, as (setting, key).
+/// The database path can't come from the database, so it has no setting.
+pub const SETTING_KEYS: &[(&str, &str)] = &[
+    ("log_level", "log_level"),
+    ("engine_depth", "engine.depth"),
+    ("engine_personality", "engine.personality"),
+    ("solution_margin", "engine.solution_margin"),
+    ("api_key", "llm.api_key"),
+    ("llm_base_url", "llm.base_url"),
+    ("llm_model", "llm.model"),
+    ("notation_moves", "notation.moves"),
+    ("notation_pieces", "notation.pieces"),
+    ("notation_language", "notation.language"),
+    ("notation_coordinates", "notation.coordinates"),
+    ("board_theme", "theme.board_theme"),
+    ("piece_set", "theme.piece_set"),
+];
+
+/// A config file, variable or setting that couldn't be used
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("reading {}: {source}", path.display())]
+    Read { path: PathBuf, source: std::io::Error },
+    #[error("{} is not a JSON object of settings: {reason}", path.display())]
+    Parse { path: PathBuf, reason: String },
+    #[error("unknown config key '{0}'")]
+    UnknownKey(String),
+    #[error("invalid {key} '{value}' from {layer}: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        layer: Source,
+        reason: String,
+    },
+}
+
+/// Layer a value came from; later layers override earlier ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    #[default]
+    Default,
+    File,
+    Env,
+    Settings,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Source::Default => "defaults",
+            Source::File => "the config file",
+            Source::Env => "the environment",
+            Source::Settings => "settings",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineSettings {
+    /// Search depth for engine moves, 1 to `MAX_DEPTH`
+    pub depth: u32,
+    pub personality: Personality,
+    /// Centipawns, 0 to `MAX_SOLUTION_MARGIN`, the stored solution may trail the engine's best move by
+    /// with the engine still accepting alternative answers
+    pub solution_margin: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmSettings {
+    pub api_key: Option<String>,
+    /// OpenRouter-compatible endpoint, without the trailing `/chat/completions`
+    pub base_url: String,
+    /// Tried before the built-in models for every coach task without its own override
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeSettings {
+    pub board_theme: String,
+    pub piece_set: String,
+}
+
+/// Settings shared by the app's backends, resolved from built-in defaults, then
+/// `config.json` in the app directory, then environment variables (and a `.env`
+/// file), then the settings table
+///
+/// ```ignore
+/// let (mut config, problems) = Config::load();
+/// config.apply_settings(|setting| stored.get(setting).cloned());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+    pub db_path: PathBuf,
+    pub log_level: String,
+    pub engine: EngineSettings,
+    pub llm: LlmSettings,
+    /// How moves and boards are written for the user
+    pub notation: NotationPrefs,
+    pub theme: ThemeSettings,
+    /// Layer each key was last set from; keys missing here are defaults
+    #[serde(skip)]
+    sources: BTreeMap<&'static str, Source>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            db_path: Self::app_dir().join(DB_FILE_NAME),
+            log_level: DEFAULT_LOG_LEVEL.to_string(),
+            engine: EngineSettings {
+                depth: SEARCH_DEPTH,
+                personality: Personality::default(),
+                solution_margin: DEFAULT_SOLUTION_MARGIN,
+            },
+            llm: LlmSettings {
+                api_key: None,
+                base_url: DEFAULT_BASE_URL.to_string(),
+                model: None,
+            },
+            notation: NotationPrefs::default(),
+            theme: ThemeSettings {
+                board_theme: DEFAULT_BOARD_THEME.to_string(),
+                piece_set: DEFAULT_PIECE_SET.to_string(),
+            },
+            sources: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Folder holding the database, logs and `config.json`
+    pub fn app_dir() -> PathBuf {
+        dirs::data_local_dir().unwrap_or_else(|| PathBuf::from(".")).join(APP_DIR_NAME)
+    }
+
+    pub fn file_path() -> PathBuf {
+        Self::app_dir().join(CONFIG_FILE_NAME)
+    }
+
+    /// Defaults, the config file and the environment. Problems with single
+    /// values are returned alongside rather than discarding everything else.
+    pub fn load() -> (Self, Vec<ConfigError>) {
+        dotenv::dotenv().ok();
+        let mut config = Self::default();
+        let mut problems = config.apply_file(&Self::file_path());
+        problems.extend(config.apply_env(|var| std::env::var(var).ok()));
+        (config, problems)
+    }
+
+    /// Layer a config file over the current values; a missing file is not an error
+    pub fn apply_file(&mut self, path: &Path) -> Vec<ConfigError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return vec![],
+            Err(source) => return vec![ConfigError::Read { path: path.to_path_buf(), source }],
+        };
+        let root = match serde_json::from_str::<Value>(&text) {
+            Ok(Value::Object(root)) => root,
+            Ok(_) => {
+                let reason = "expected an object".to_string();
+                return vec![ConfigError::Parse { path: path.to_path_buf(), reason }];
+            }
+            Err(e) => return vec![ConfigError::Parse { path: path.to_path_buf(), reason: e.to_string() }],
+        };
+
+        let mut values = vec![];
+        flatten("", &Value::Object(root), &mut values);
+        values
+            .into_iter()
+            .filter_map(|(key, value)| self.set(&key, &value, Source::File).err())
+            .collect()
+    }
+
+    /// Layer environment variables from `ENV_VARS` over the current values
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Vec<ConfigError> {
+        self.apply_named(ENV_VARS, var, Source::Env)
+    }
+
+    /// Layer the app's stored settings from `SETTING_KEYS` over the current values
+    pub fn apply_settings(&mut self, setting: impl Fn(&str) -> Option<String>) -> Vec<ConfigError> {
+        self.apply_named(SETTING_KEYS, setting, Source::Settings)
+    }
+
+    fn apply_named(
+        &mut self,
+        names: &[(&str, &str)],
+        lookup: impl Fn(&str) -> Option<String>,
+        source: Source,
+    ) -> Vec<ConfigError> {
+        names
+            .iter()
+            .filter_map(|(name, key)| lookup(name).map(|value| (key, value)))
+            // An empty value leaves the key to the layers below, as a cleared setting should
+            .filter(|(_, value)| !value.trim().is_empty())
+            .filter_map(|(key, value)| self.set(key, &value, source).err())
+            .collect()
+    }
+
+    /// Set one dotted key from its text form, checking the value
+    pub fn set(&mut self, key: &str, value: &str, source: Source) -> Result<(), ConfigError> {
+        let key = *KEYS.iter().find(|k| **k == key).ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+        let value = value.trim();
+        let invalid = |reason: String| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            layer: source,
+            reason,
+        };
+
+        match key {
+            "db_path" => self.db_path = PathBuf::from(value),
+            "log_level" => self.log_level = value.to_lowercase(),
+            "engine.depth" => {
+                let depth = value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|d| (1..=MAX_DEPTH).contains(d))
+                    .ok_or_else(|| invalid(format!("expected a depth from 1 to {}", MAX_DEPTH)))?;
+                self.engine.depth = depth;
+            }
+            "engine.personality" => {
+                self.engine.personality = parse_name::<Personality>(value)
+                    .ok_or_else(|| invalid("expected balanced, aggressive or solid".to_string()))?;
+            }
+            "engine.solution_margin" => {
+                self.engine.solution_margin = value
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|m| (0..=MAX_SOLUTION_MARGIN).contains(m))
+                    .ok_or_else(|| invalid(format!("expected centipawns from 0 to {}", MAX_SOLUTION_MARGIN)))?;
+            }
+            "llm.api_key" => self.llm.api_key = Some(value.to_string()),
+            "llm.base_url" => {
+                if !value.starts_with("https://") && !value.starts_with("http://") {
+                    return Err(invalid("expected an http(s) URL".to_string()));
+                }
+                self.llm.base_url = value.trim_end_matches('/').to_string();
+            }
+            "llm.model" => self.llm.model = Some(value.to_string()),
+            "notation.moves" => {
+                self.notation.moves = parse_name::<MoveStyle>(value)
+                    .ok_or_else(|| invalid("expected san or long_algebraic".to_string()))?;
+            }
+            "notation.pieces" => {
+                self.notation.pieces = parse_name::<PieceStyle>(value)
+                    .ok_or_else(|| invalid("expected letters or figurine".to_string()))?;
+            }
+            "notation.language" => {
+                self.notation.language = parse_name::<PieceLanguage>(value)
+                    .ok_or_else(|| invalid("expected en, de, fr, es or it".to_string()))?;
+            }
+            "notation.coordinates" => {
+                self.notation.coordinates = value
+                    .to_lowercase()
+                    .parse::<bool>()
+                    .map_err(|_| invalid("expected true or false".to_string()))?;
+            }
+            "theme.board_theme" => self.theme.board_theme = value.to_string(),
+            "theme.piece_set" => self.theme.piece_set = value.to_string(),
+            _ => unreachable!("every key in KEYS is handled"),
+        }
+        self.sources.insert(key, source);
+        Ok(())
+    }
+
+    /// Layer `key`'s current value came from
+    pub fn source(&self, key: &str) -> Source {
+        self.sources.get(key).copied().unwrap_or_default()
+    }
+}
+
+/// A unit enum variant from its serialized name, any case
+fn parse_name<T: serde::de::DeserializeOwned>(value: &str) -> Option<T> {
+    serde_json::from_value(Value::String(value.to_lowercase())).ok()
+}
+
+/// Dotted keys and text values of a nested JSON object; arrays and nulls are skipped
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                let key = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+                flatten(&key, child, out);
+            }
+        }
+        Value::String(s) => out.push((prefix.to_string(), s.clone())),
+        Value::Number(n) => out.push((prefix.to_string(), n.to_string())),
+        Value::Bool(b) => out.push((prefix.to_string(), b.to_string())),
+        Value::Array(_) | Value::Null => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let dir = std::env::temp_dir().join(format!("tacticus-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join(CONFIG_FILE_NAME);
+        std::fs::write(
+            &file,
+            r#"{"db_path": "/data/t.db", "engine": {"depth": 2, "personality": "Solid"},
+                "theme": {"board_theme": "green"}, "log_level": "debug"}"#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        assert!(config.apply_file(&dir.join("missing.json")).is_empty());
+        assert!(config.apply_file(&file).is_empty());
+        assert_eq!(config.db_path, PathBuf::from("/data/t.db"));
+        assert_eq!(config.engine.depth, 2);
+        assert_eq!(config.engine.personality, Personality::Solid);
+        assert_eq!(config.source("theme.board_theme"), Source::File);
+        assert_eq!(config.source("theme.piece_set"), Source::Default);
+
+        let env: HashMap<&str, &str> =
+            [("TACTICUS_BOARD_THEME", "blue"), ("OPENROUTER_API_KEY", "from-env"), ("TACTICUS_LOG_LEVEL", " ")].into();
+        assert!(config.apply_env(|var| env.get(var).map(|v| v.to_string())).is_empty());
+        assert_eq!(config.theme.board_theme, "blue");
+        assert_eq!(config.log_level, "debug");
+
+        let settings: HashMap<&str, &str> = [("api_key", "from-settings"), ("board_theme", "grey")].into();
+        assert!(config.apply_settings(|key| settings.get(key).map(|v| v.to_string())).is_empty());
+        assert_eq!(config.llm.api_key.as_deref(), Some("from-settings"));
+        assert_eq!(config.theme.board_theme, "grey");
+        assert_eq!(config.engine.solution_margin, DEFAULT_SOLUTION_MARGIN);
+        assert!(config.set("engine.solution_margin", "80", Source::Settings).is_ok());
+        assert_eq!(config.engine.solution_margin, 80);
+        assert!(config.set("engine.solution_margin", "-5", Source::Settings).is_err());
+        assert_eq!(config.source("llm.api_key"), Source::Settings);
+        assert_eq!(config.notation, NotationPrefs::default());
+        let notation: HashMap<&str, &str> =
+            [("notation_moves", "Long_Algebraic"), ("notation_language", "de"), ("notation_coordinates", "false")].into();
+        assert!(config.apply_settings(|key| notation.get(key).map(|v| v.to_string())).is_empty());
+        assert_eq!(config.notation.moves, MoveStyle::LongAlgebraic);
+        assert_eq!(config.notation.language, PieceLanguage::De);
+        assert!(!config.notation.coordinates);
+        assert!(config.set("notation.pieces", "runes", Source::Settings).is_err());
+        assert_eq!(config.db_path, PathBuf::from("/data/t.db"));
+
+        // Bad values are reported and leave the lower layers in place
+        std::fs::write(&file, r#"{"engine": {"depth": 9}, "llm": {"base_url": "ftp://x"}, "colour": "red"}"#).unwrap();
+        let problems = config.apply_file(&file);
+        assert_eq!(problems.len(), 3);
+        assert!(problems.iter().any(|p| matches!(p, ConfigError::UnknownKey(key) if key == "colour")));
+        assert_eq!(config.engine.depth, 2);
+        assert_eq!(config.llm.base_url, DEFAULT_BASE_URL);
+
+        std::fs::write(&file, "[1, 2]").unwrap();
+        assert!(matches!(config.apply_file(&file).as_slice(), [ConfigError::Parse { .. }]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
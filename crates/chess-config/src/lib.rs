@@ -0,0 +1,7 @@
+pub mod config;
+
+pub use config::{
+    Config, ConfigError, EngineSettings, LlmSettings, Source, ThemeSettings, CONFIG_FILE_NAME, DEFAULT_BASE_URL,
+    DEFAULT_BOARD_THEME, DEFAULT_LOG_LEVEL, DEFAULT_PIECE_SET, DEFAULT_SOLUTION_MARGIN, ENV_VARS, FAST_MODELS, KEYS,
+    MAX_SOLUTION_MARGIN, SETTING_KEYS, STRONG_MODELS,
+};
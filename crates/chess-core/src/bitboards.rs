@@ -0,0 +1,125 @@
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, BitBoard, Board, Color, Piece,
+    Square, EMPTY,
+};
+
+/// Squares a `piece` of `color` on `square` attacks, with sliders stopped by `occupied`.
+/// Pawns attack their capture squares whether or not anything stands there.
+pub fn piece_attacks(piece: Piece, color: Color, square: Square, occupied: BitBoard) -> BitBoard {
+    match piece {
+        Piece::Pawn => get_pawn_attacks(square, color, !EMPTY),
+        Piece::Knight => get_knight_moves(square),
+        Piece::Bishop => get_bishop_moves(square, occupied),
+        Piece::Rook => get_rook_moves(square, occupied),
+        Piece::Queen => get_bishop_moves(square, occupied) | get_rook_moves(square, occupied),
+        Piece::King => get_king_moves(square),
+    }
+}
+
+/// Squares attacked by the piece on `square`; empty when the square is
+pub fn attacks_from(board: &Board, square: Square) -> BitBoard {
+    match (board.piece_on(square), board.color_on(square)) {
+        (Some(piece), Some(color)) => piece_attacks(piece, color, square, *board.combined()),
+        _ => EMPTY,
+    }
+}
+
+/// Every square at least one `color` piece attacks
+pub fn attack_map(board: &Board, color: Color) -> BitBoard {
+    (*board.color_combined(color)).fold(EMPTY, |map, square| map | attacks_from(board, square))
+}
+
+/// `by` pieces that attack `square`, whoever stands on it
+pub fn attackers_of(board: &Board, square: Square, by: Color) -> BitBoard {
+    let occupied = *board.combined();
+    let theirs = *board.color_combined(by);
+    let diagonal = *board.pieces(Piece::Bishop) | *board.pieces(Piece::Queen);
+    let straight = *board.pieces(Piece::Rook) | *board.pieces(Piece::Queen);
+
+    let attackers = (get_knight_moves(square) & *board.pieces(Piece::Knight))
+        | (get_king_moves(square) & *board.pieces(Piece::King))
+        | (get_bishop_moves(square, occupied) & diagonal)
+        | (get_rook_moves(square, occupied) & straight)
+        // A pawn of the other colour on `square` would capture exactly the squares our pawns attack from
+        | (get_pawn_attacks(square, !by, !EMPTY) & *board.pieces(Piece::Pawn));
+    attackers & theirs
+}
+
+/// `color` pieces pinned to their own king, whichever side is to move
+pub fn pinned(board: &Board, color: Color) -> BitBoard {
+    let king = board.king_square(color);
+    let occupied = *board.combined();
+    let enemies = *board.color_combined(!color);
+    let diagonal = (*board.pieces(Piece::Bishop) | *board.pieces(Piece::Queen)) & enemies;
+    let straight = (*board.pieces(Piece::Rook) | *board.pieces(Piece::Queen)) & enemies;
+    let pinners = (get_bishop_moves(king, EMPTY) & diagonal) | (get_rook_moves(king, EMPTY) & straight);
+
+    pinners.fold(EMPTY, |pinned, pinner| {
+        let blockers = between(king, pinner) & occupied;
+        if blockers.popcnt() == 1 && blockers & *board.color_combined(color) != EMPTY {
+            pinned | blockers
+        } else {
+            pinned
+        }
+    })
+}
+
+/// Squares strictly between `a` and `b` on a rank, file or diagonal; empty when they
+/// don't share one
+pub fn between(a: Square, b: Square) -> BitBoard {
+    chess::between(a, b)
+}
+
+/// The whole rank, file or diagonal through `a` and `b`, edge to edge; empty when they
+/// don't share one
+pub fn line(a: Square, b: Square) -> BitBoard {
+    chess::line(a, b)
+}
+
+/// Whether `c` lies on the line through `a` and `b`
+pub fn aligned(a: Square, b: Square, c: Square) -> bool {
+    line(a, b) & BitBoard::from_square(c) != EMPTY
+}
+
+pub fn contains(set: BitBoard, square: Square) -> bool {
+    set & BitBoard::from_square(square) != EMPTY
+}
+
+/// Squares in `set`, a1 first and h8 last
+pub fn squares(set: BitBoard) -> Vec<Square> {
+    set.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_attacks_pins_and_lines() {
+        // Bb5 pins the c6 knight and Qa5 the d2 pawn
+        let board = Board::from_str("r1b1kbnr/ppp2ppp/2n5/qB1pp3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 5").unwrap();
+
+        assert_eq!(squares(attackers_of(&board, Square::E5, Color::White)), vec![Square::F3]);
+        assert_eq!(squares(attackers_of(&board, Square::D4, Color::Black)), vec![Square::E5, Square::C6]);
+        assert!(contains(attacks_from(&board, Square::B5), Square::C6));
+        assert!(!contains(attacks_from(&board, Square::B5), Square::D7));
+        assert_eq!(attacks_from(&board, Square::E3), EMPTY);
+        // Pawns cover their capture squares even when empty
+        assert!(contains(attack_map(&board, Color::White), Square::D3));
+        assert!(!contains(attack_map(&board, Color::White), Square::D6));
+
+        assert_eq!(pinned(&board, Color::White), *board.pinned());
+        assert_eq!(squares(pinned(&board, Color::White)), vec![Square::D2]);
+        assert_eq!(squares(pinned(&board, Color::Black)), vec![Square::C6]);
+        let black_to_move = board.null_move().unwrap();
+        assert_eq!(pinned(&board, Color::Black), *black_to_move.pinned());
+
+        assert_eq!(squares(between(Square::B5, Square::E8)), vec![Square::C6, Square::D7]);
+        assert_eq!(between(Square::A1, Square::B3), EMPTY);
+        assert_eq!(line(Square::A1, Square::B3), EMPTY);
+        assert_eq!(line(Square::A1, Square::C3).popcnt(), 8);
+        assert!(aligned(Square::A1, Square::C3, Square::H8));
+        assert!(!aligned(Square::A1, Square::C3, Square::H1));
+    }
+}
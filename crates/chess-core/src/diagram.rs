@@ -0,0 +1,280 @@
+use chess::{Board, Color, Piece};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::notation::{NotationPrefs, PieceStyle};
+use crate::perspective::{screen_order, screen_position, square_at};
+
+const SQUARE_SIZE: u32 = 45;
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+
+/// Every piece, in the order piece sets list them
+pub const PIECE_KEYS: [&str; 12] = ["wK", "wQ", "wR", "wB", "wN", "wP", "bK", "bQ", "bR", "bB", "bN", "bP"];
+
+/// How pieces are drawn in a diagram
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PieceArt {
+    /// Unicode chess symbols, needing no assets
+    #[default]
+    Glyphs,
+    /// Image URLs (usually data URIs) keyed by `piece_key`. Missing pieces fall back to glyphs.
+    Images(HashMap<String, String>),
+}
+
+/// Square colours, piece art and labels for a rendered diagram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramStyle {
+    pub light_square: String,
+    pub dark_square: String,
+    pub pieces: PieceArt,
+    /// File letters along the bottom and rank numbers down the left
+    pub coordinates: bool,
+}
+
+impl Default for DiagramStyle {
+    fn default() -> Self {
+        DiagramStyle {
+            light_square: LIGHT_SQUARE.to_string(),
+            dark_square: DARK_SQUARE.to_string(),
+            pieces: PieceArt::Glyphs,
+            coordinates: false,
+        }
+    }
+}
+
+/// Asset name of a piece, e.g. "wK" or "bN", as piece set folders name their files
+pub fn piece_key(piece: Piece, color: Color) -> &'static str {
+    let index = match piece {
+        Piece::King => 0,
+        Piece::Queen => 1,
+        Piece::Rook => 2,
+        Piece::Bishop => 3,
+        Piece::Knight => 4,
+        Piece::Pawn => 5,
+    };
+    PIECE_KEYS[if color == Color::White { index } else { index + 6 }]
+}
+
+fn glyph(piece: Piece, color: Color) -> char {
+    match (color, piece) {
+        (Color::White, Piece::King) => '\u{2654}',
+        (Color::White, Piece::Queen) => '\u{2655}',
+        (Color::White, Piece::Rook) => '\u{2656}',
+        (Color::White, Piece::Bishop) => '\u{2657}',
+        (Color::White, Piece::Knight) => '\u{2658}',
+        (Color::White, Piece::Pawn) => '\u{2659}',
+        (Color::Black, Piece::King) => '\u{265A}',
+        (Color::Black, Piece::Queen) => '\u{265B}',
+        (Color::Black, Piece::Rook) => '\u{265C}',
+        (Color::Black, Piece::Bishop) => '\u{265D}',
+        (Color::Black, Piece::Knight) => '\u{265E}',
+        (Color::Black, Piece::Pawn) => '\u{265F}',
+    }
+}
+
+/// Render `board` as a standalone SVG diagram, seen from `orientation`'s side
+pub fn board_to_svg(board: &Board, orientation: Color) -> String {
+    board_to_svg_styled(board, orientation, &DiagramStyle::default())
+}
+
+/// Render `board` as a standalone SVG diagram with the given squares and pieces
+pub fn board_to_svg_styled(board: &Board, orientation: Color, style: &DiagramStyle) -> String {
+    let size = SQUARE_SIZE * 8;
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {size} {size}" width="{size}" height="{size}">"#
+    );
+
+    for square in screen_order(orientation) {
+        let (col, row) = screen_position(square, orientation);
+        let fill = if (square.get_rank().to_index() + square.get_file().to_index()) % 2 == 0 {
+            &style.dark_square
+        } else {
+            &style.light_square
+        };
+        let (x, y) = (col as u32 * SQUARE_SIZE, row as u32 * SQUARE_SIZE);
+
+        svg.push_str(&format!(
+            r#"<rect x="{x}" y="{y}" width="{SQUARE_SIZE}" height="{SQUARE_SIZE}" fill="{fill}"/>"#
+        ));
+
+        if style.coordinates {
+            // Labels take the other square colour so they show on either
+            let label = if *fill == style.dark_square { &style.light_square } else { &style.dark_square };
+            let name = square.to_string();
+            if col == 0 {
+                svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-size="10" fill="{label}">{}</text>"#,
+                    x + 2,
+                    y + 11,
+                    &name[1..]
+                ));
+            }
+            if row == 7 {
+                svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-size="10" text-anchor="end" fill="{label}">{}</text>"#,
+                    x + SQUARE_SIZE - 2,
+                    y + SQUARE_SIZE - 3,
+                    &name[..1]
+                ));
+            }
+        }
+
+        if let (Some(piece), Some(color)) = (board.piece_on(square), board.color_on(square)) {
+            let image = match &style.pieces {
+                PieceArt::Images(images) => images.get(piece_key(piece, color)),
+                PieceArt::Glyphs => None,
+            };
+            match image {
+                Some(href) => svg.push_str(&format!(
+                    r#"<image x="{x}" y="{y}" width="{SQUARE_SIZE}" height="{SQUARE_SIZE}" href="{}"/>"#,
+                    href.replace('"', "%22")
+                )),
+                None => svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-size="38" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    x + SQUARE_SIZE / 2,
+                    y + SQUARE_SIZE / 2,
+                    glyph(piece, color)
+                )),
+            }
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// `board` as plain text for terminals and logs, seen from `orientation`'s side. White
+/// pieces are upper case and Black's lower case in `prefs`'s language, or figurines;
+/// empty squares are dots.
+pub fn board_to_text(board: &Board, orientation: Color, prefs: &NotationPrefs) -> String {
+    let mut lines = Vec::with_capacity(9);
+    for row in 0..8 {
+        let mut cells = Vec::with_capacity(9);
+        let rank_square = square_at(0, row, orientation).expect("row is on the board");
+        if prefs.coordinates {
+            cells.push((rank_square.get_rank().to_index() + 1).to_string());
+        }
+        for col in 0..8 {
+            let square = square_at(col, row, orientation).expect("column is on the board");
+            cells.push(match (board.piece_on(square), board.color_on(square)) {
+                (Some(piece), Some(color)) => match prefs.pieces {
+                    PieceStyle::Figurine => glyph(piece, color).to_string(),
+                    PieceStyle::Letters if color == Color::White => prefs.language.board_letter(piece).to_string(),
+                    PieceStyle::Letters => prefs.language.board_letter(piece).to_lowercase(),
+                },
+                _ => ".".to_string(),
+            });
+        }
+        lines.push(cells.join(" "));
+    }
+    if prefs.coordinates {
+        let files: Vec<String> = (0..8)
+            .map(|col| square_at(col, 7, orientation).expect("column is on the board").to_string()[..1].to_string())
+            .collect();
+        lines.push(format!("  {}", files.join(" ")));
+    }
+    lines.join("\n")
+}
+
+/// Trim quoting and sentence punctuation around a word. `-` is kept since FENs use it.
+fn trim_word(word: &str) -> &str {
+    word.trim_matches(|c: char| matches!(c, '(' | ')' | '[' | ']' | ',' | '.' | ';' | ':' | '"' | '\'' | '`'))
+}
+
+fn looks_like_placement(word: &str) -> bool {
+    word.matches('/').count() == 7 && word.chars().all(|c| c == '/' || c.is_ascii_digit() || "prnbqkPRNBQK".contains(c))
+}
+
+/// FENs mentioned in free text, in order of appearance. A bare piece placement is
+/// completed with White to move.
+pub fn extract_fens(text: &str) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().map(trim_word).collect();
+    let mut fens: Vec<String> = Vec::new();
+
+    for (index, word) in words.iter().enumerate() {
+        if !looks_like_placement(word) {
+            continue;
+        }
+
+        // Prefer the longest run of following words that still parses
+        let available = (words.len() - index).min(6);
+        let fen = (1..=available).rev().find_map(|fields| {
+            let candidate = words[index..index + fields].join(" ");
+            let candidate = if fields == 1 { format!("{} w - - 0 1", candidate) } else { candidate };
+            Board::from_str(&candidate).ok().map(|_| candidate)
+        });
+
+        if let Some(fen) = fen {
+            if fens.last() != Some(&fen) {
+                fens.push(fen);
+            }
+        }
+    }
+
+    fens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_fens() {
+        let text = "Look at (FEN: rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1). \
+                    Later: 8/8/8/4k3/8/8/4K3/8, a basic ending.";
+
+        assert_eq!(
+            extract_fens(text),
+            vec![
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".to_string(),
+                "8/8/8/4k3/8/8/4K3/8 w - - 0 1".to_string(),
+            ]
+        );
+        assert!(extract_fens("no positions here 1/2/3").is_empty());
+    }
+
+    #[test]
+    fn test_svg_has_every_piece() {
+        let svg = board_to_svg(&Board::default(), Color::White);
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<rect").count(), 64);
+        assert_eq!(svg.matches("<text").count(), 32);
+    }
+
+    #[test]
+    fn test_styled_svg() {
+        let style = DiagramStyle {
+            light_square: "#eeeeee".to_string(),
+            dark_square: "#333333".to_string(),
+            pieces: PieceArt::Images(HashMap::from([("wK".to_string(), "data:image/png;base64,AAAA".to_string())])),
+            coordinates: false,
+        };
+        let svg = board_to_svg_styled(&Board::default(), Color::White, &style);
+        assert_eq!(svg.matches("fill=\"#333333\"").count(), 32);
+        assert_eq!(svg.matches("<image").count(), 1);
+        assert_eq!(svg.matches("<text").count(), 31);
+        let labelled = board_to_svg_styled(&Board::default(), Color::White, &DiagramStyle { coordinates: true, ..style });
+        assert_eq!(labelled.matches("<text").count(), 31 + 16);
+        assert_eq!(piece_key(Piece::Knight, Color::Black), "bN");
+    }
+
+    #[test]
+    fn test_text_board() {
+        let text = board_to_text(&Board::default(), Color::White, &NotationPrefs::default());
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "8 r n b q k b n r");
+        assert_eq!(lines[4], "4 . . . . . . . .");
+        assert_eq!(lines[8], "  a b c d e f g h");
+
+        let german = NotationPrefs {
+            coordinates: false,
+            language: crate::notation::PieceLanguage::De,
+            ..NotationPrefs::default()
+        };
+        let text = board_to_text(&Board::default(), Color::Black, &german);
+        assert_eq!(text.lines().count(), 8);
+        assert_eq!(text.lines().next(), Some("T S L K D L S T"));
+        assert_eq!(text.lines().nth(1), Some("B B B B B B B B"));
+    }
+}
@@ -8,6 +8,9 @@ pub enum ChessError {
     #[error("Game already finished")]
     GameFinished,
 
+    #[error("Invalid FEN: {0}")]
+    InvalidFen(String),
+
     #[error("Invalid position: {0}")]
     InvalidPosition(String),
 
@@ -17,6 +20,7 @@ pub enum ChessError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[cfg(feature = "json")]
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 }
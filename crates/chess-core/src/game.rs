@@ -1,9 +1,9 @@
 use chess::{Board, BoardStatus, ChessMove, Color};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "timestamps")]
 use chrono::{DateTime, Utc};
 use crate::error::{ChessError, Result};
 use crate::move_history::{MoveHistory, AnnotatedMove};
-use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum GameState {
@@ -81,7 +81,7 @@ impl<'de> Deserialize<'de> for GameState {
                             winner = Some(map.next_value::<String>()?);
                         }
                         _ => {
-                            let _ = map.next_value::<serde_json::Value>()?;
+                            let _ = map.next_value::<de::IgnoredAny>()?;
                         }
                     }
                 }
@@ -121,7 +121,9 @@ pub struct ChessGame {
     pub state: GameState,
     #[serde(serialize_with = "serialize_color", deserialize_with = "deserialize_color")]
     pub player_color: Color,
+    #[cfg(feature = "timestamps")]
     pub created_at: DateTime<Utc>,
+    #[cfg(feature = "timestamps")]
     pub finished_at: Option<DateTime<Utc>>,
 }
 
@@ -137,7 +139,7 @@ where
     D: serde::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    Board::from_str(&s).map_err(serde::de::Error::custom)
+    crate::validate::parse_fen(&s).map_err(serde::de::Error::custom)
 }
 
 fn serialize_color<S>(color: &Color, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -170,7 +172,9 @@ impl ChessGame {
             move_history: MoveHistory::new(),
             state: GameState::InProgress,
             player_color,
+            #[cfg(feature = "timestamps")]
             created_at: Utc::now(),
+            #[cfg(feature = "timestamps")]
             finished_at: None,
         }
     }
@@ -183,19 +187,23 @@ impl ChessGame {
             move_history: MoveHistory::new(),
             state,
             player_color,
+            #[cfg(feature = "timestamps")]
             created_at: Utc::now(),
+            #[cfg(feature = "timestamps")]
             finished_at: None,
         }
     }
 
     pub fn make_move(&mut self, chess_move: ChessMove) -> Result<()> {
         if self.state != GameState::InProgress {
+            tracing::debug!(%chess_move, "move rejected: game finished");
             return Err(ChessError::GameFinished);
         }
 
         let legal_moves: Vec<ChessMove> = chess::MoveGen::new_legal(&self.board).collect();
 
         if !legal_moves.contains(&chess_move) {
+            tracing::debug!(%chess_move, fen = %self.board, "move rejected: illegal");
             return Err(ChessError::InvalidMove(format!(
                 "Move {} is not legal in current position",
                 chess_move
@@ -206,6 +214,7 @@ impl ChessGame {
         self.move_history.add_move(AnnotatedMove::from_move(chess_move));
         self.state = Self::determine_state(&self.board);
 
+        #[cfg(feature = "timestamps")]
         if self.state != GameState::InProgress {
             self.finished_at = Some(Utc::now());
         }
@@ -242,8 +251,7 @@ impl ChessGame {
     }
 
     pub fn from_fen(fen: &str, player_color: Color) -> Result<Self> {
-        let board = Board::from_str(fen)
-            .map_err(|e| ChessError::ParseError(format!("Invalid FEN: {}", e)))?;
+        let board = crate::validate::parse_fen(fen)?;
         Ok(Self::from_board(board, player_color))
     }
 }
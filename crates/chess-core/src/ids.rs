@@ -0,0 +1,53 @@
+//! Globally unique row ids for data created on one device and merged into another.
+//! Ids are UUIDv7 strings from the `uuid` crate: a Unix millisecond timestamp followed
+//! by counter and random bits, so ids from one process sort in creation order.
+
+use uuid::{NoContext, Timestamp, Uuid};
+
+/// A new id stamped with `unix_ms`, e.g. a row's creation time when backfilling
+pub fn uid_at(unix_ms: u64) -> String {
+    let timestamp = Timestamp::from_unix(NoContext, unix_ms / 1000, (unix_ms % 1000) as u32 * 1_000_000);
+    Uuid::new_v7(timestamp).to_string()
+}
+
+/// A new id for a row created now. Later calls in this process always sort after earlier ones.
+pub fn new_uid() -> String {
+    Uuid::now_v7().to_string()
+}
+
+/// Whether `s` is a UUIDv7 in the lowercase hyphenated form `new_uid` produces
+pub fn is_uid(s: &str) -> bool {
+    Uuid::try_parse(s).is_ok_and(|uuid| uuid.get_version_num() == 7 && uuid.hyphenated().to_string() == s)
+}
+
+/// Creation time of a UUIDv7, in Unix milliseconds
+pub fn uid_timestamp_ms(uid: &str) -> Option<u64> {
+    if !is_uid(uid) {
+        return None;
+    }
+    let (secs, nanos) = Uuid::try_parse(uid).ok()?.get_timestamp()?.to_unix();
+    Some(secs * 1000 + u64::from(nanos) / 1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uid_layout() {
+        let uid = uid_at(0x0190_1234_5678);
+        assert!(uid.starts_with("01901234-5678-7"), "{}", uid);
+        assert!(is_uid(&uid));
+        assert_eq!(uid_timestamp_ms(&uid), Some(0x0190_1234_5678));
+        assert!(!is_uid("01901234-5678-4abc-bfff-ffffffffffff"));
+        assert!(!is_uid("not-a-uid"));
+    }
+
+    #[test]
+    fn test_new_uids_are_unique_and_ordered() {
+        let uids: Vec<String> = (0..5000).map(|_| new_uid()).collect();
+        assert!(uids.iter().all(|uid| is_uid(uid)));
+        assert!(uids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(uid_timestamp_ms(&uid_at(1_700_000_000_000)), Some(1_700_000_000_000));
+    }
+}
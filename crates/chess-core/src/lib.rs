@@ -1,12 +1,30 @@
 pub mod game;
+pub mod ids;
 pub mod position;
 pub mod move_history;
 pub mod error;
+pub mod notation;
+pub mod voice;
+pub mod diagram;
+pub mod validate;
+pub mod rng;
+pub mod openings;
+pub mod perspective;
+pub mod bitboards;
 
 pub use game::{ChessGame, GameState};
-pub use position::{Position, PositionAnalysis};
+pub use position::{GamePhase, Position, PositionAnalysis};
 pub use move_history::{MoveHistory, AnnotatedMove, MoveQuality};
 pub use error::{ChessError, Result};
+pub use ids::{is_uid, new_uid, uid_at, uid_timestamp_ms};
+pub use notation::{format_move, format_moves, move_label, move_number, to_san, to_spoken, MoveStyle, NotationPrefs, PieceLanguage, PieceStyle};
+pub use voice::{parse_spoken_move, SpokenMove};
+pub use diagram::{board_to_svg, board_to_svg_styled, board_to_text, extract_fens, piece_key, DiagramStyle, PieceArt, PIECE_KEYS};
+pub use validate::{parse_fen, parse_legal_uci, parse_move, parse_san, parse_uci};
+pub use rng::SeededRng;
+pub use perspective::{color_from_name, color_name, pieces_of, screen_order, screen_position, square_at, Side};
+pub use bitboards::{aligned, attack_map, attackers_of, attacks_from, between, line, pinned, piece_attacks};
+pub use openings::{deviation_ply, detect_opening, opening_by_key, position_key, Opening, OPENINGS};
 
 // Re-export commonly used chess types
 pub use chess::{Board, ChessMove, Color, Piece, Square, File, Rank};
@@ -33,28 +33,7 @@ where
 {
     let s = String::deserialize(deserializer)?;
     // Parse the UCI move string (e.g., "e2e4")
-    if s.len() >= 4 {
-        use std::str::FromStr;
-        let from = chess::Square::from_str(&s[0..2])
-            .map_err(|e| serde::de::Error::custom(format!("Invalid from square: {}", e)))?;
-        let to = chess::Square::from_str(&s[2..4])
-            .map_err(|e| serde::de::Error::custom(format!("Invalid to square: {}", e)))?;
-        let promotion = if s.len() == 5 {
-            let promo_char = s.chars().nth(4).unwrap();
-            Some(match promo_char.to_lowercase().next().unwrap() {
-                'q' => chess::Piece::Queen,
-                'r' => chess::Piece::Rook,
-                'b' => chess::Piece::Bishop,
-                'n' => chess::Piece::Knight,
-                _ => return Err(serde::de::Error::custom("Invalid promotion piece")),
-            })
-        } else {
-            None
-        };
-        Ok(ChessMove::new(from, to, promotion))
-    } else {
-        Err(serde::de::Error::custom("Move string too short"))
-    }
+    crate::validate::parse_uci(&s).map_err(serde::de::Error::custom)
 }
 
 impl AnnotatedMove {
@@ -0,0 +1,395 @@
+use chess::{Board, BoardStatus, ChessMove, MoveGen, Piece, Square};
+use serde::{Deserialize, Serialize};
+
+/// How a move is written out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveStyle {
+    /// Standard algebraic, e.g. "Nf3", "exd5"
+    #[default]
+    San,
+    /// Long algebraic with both squares, e.g. "Ng1-f3", "e4xd5"
+    LongAlgebraic,
+}
+
+/// How pieces are shown within a move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PieceStyle {
+    #[default]
+    Letters,
+    /// Unicode figurines, e.g. "♘f3", the same for both sides
+    Figurine,
+}
+
+/// Language piece letters are taken from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PieceLanguage {
+    #[default]
+    En,
+    De,
+    Fr,
+    Es,
+    It,
+}
+
+impl PieceLanguage {
+    pub const ALL: [PieceLanguage; 5] =
+        [PieceLanguage::En, PieceLanguage::De, PieceLanguage::Fr, PieceLanguage::Es, PieceLanguage::It];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PieceLanguage::En => "English",
+            PieceLanguage::De => "German",
+            PieceLanguage::Fr => "French",
+            PieceLanguage::Es => "Spanish",
+            PieceLanguage::It => "Italian",
+        }
+    }
+
+    /// Letter for `piece` in this language; empty for pawns, as in SAN
+    pub fn letter(self, piece: Piece) -> &'static str {
+        // King, queen, rook, bishop, knight
+        let letters = match self {
+            PieceLanguage::En => ["K", "Q", "R", "B", "N"],
+            PieceLanguage::De => ["K", "D", "T", "L", "S"],
+            PieceLanguage::Fr => ["R", "D", "T", "F", "C"],
+            PieceLanguage::Es | PieceLanguage::It => ["R", "D", "T", "A", "C"],
+        };
+        match piece {
+            Piece::Pawn => "",
+            Piece::King => letters[0],
+            Piece::Queen => letters[1],
+            Piece::Rook => letters[2],
+            Piece::Bishop => letters[3],
+            Piece::Knight => letters[4],
+        }
+    }
+
+    /// Letter for `piece` on a text board, where pawns need one too
+    pub fn board_letter(self, piece: Piece) -> &'static str {
+        match (self, piece) {
+            (PieceLanguage::De, Piece::Pawn) => "B",
+            (_, Piece::Pawn) => "P",
+            (language, piece) => language.letter(piece),
+        }
+    }
+}
+
+/// How moves and boards are written for the user. Parsing, PGN and the coach's own
+/// input stay in English SAN whatever these say.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotationPrefs {
+    pub moves: MoveStyle,
+    pub pieces: PieceStyle,
+    /// Ignored with figurines
+    pub language: PieceLanguage,
+    /// File and rank labels around boards
+    pub coordinates: bool,
+}
+
+impl Default for NotationPrefs {
+    fn default() -> Self {
+        NotationPrefs {
+            moves: MoveStyle::San,
+            pieces: PieceStyle::Letters,
+            language: PieceLanguage::En,
+            coordinates: true,
+        }
+    }
+}
+
+impl NotationPrefs {
+    /// Symbol for `piece` within a move; empty for pawns
+    pub fn piece_symbol(&self, piece: Piece) -> &'static str {
+        match (self.pieces, piece) {
+            (_, Piece::Pawn) => "",
+            (PieceStyle::Figurine, piece) => figurine(piece),
+            (PieceStyle::Letters, piece) => self.language.letter(piece),
+        }
+    }
+
+    /// Whether moves come out exactly as English SAN
+    pub fn is_standard(&self) -> bool {
+        self.moves == MoveStyle::San && self.pieces == PieceStyle::Letters && self.language == PieceLanguage::En
+    }
+
+    /// One sentence telling a writer how to write moves to match, e.g. for the coach
+    pub fn describe(&self) -> String {
+        let moves = match self.moves {
+            MoveStyle::San => "standard algebraic notation (Nf3, exd5)",
+            MoveStyle::LongAlgebraic => "long algebraic notation with both squares (Ng1-f3, e4xd5)",
+        };
+        let pieces = match self.pieces {
+            PieceStyle::Figurine => "figurines (\u{2654} \u{2655} \u{2656} \u{2657} \u{2658}) for the pieces".to_string(),
+            PieceStyle::Letters => {
+                let letters: Vec<&str> =
+                    [Piece::King, Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight].map(|p| self.language.letter(p)).to_vec();
+                format!("{} piece letters ({})", self.language.name(), letters.join(" "))
+            }
+        };
+        format!("Write moves in {} using {}.", moves, pieces)
+    }
+}
+
+/// Figurine for `piece`, drawn white as figurine notation does for both sides
+fn figurine(piece: Piece) -> &'static str {
+    match piece {
+        Piece::King => "\u{2654}",
+        Piece::Queen => "\u{2655}",
+        Piece::Rook => "\u{2656}",
+        Piece::Bishop => "\u{2657}",
+        Piece::Knight => "\u{2658}",
+        Piece::Pawn => "",
+    }
+}
+
+/// The pieces of a move needed to write it in SAN or read it aloud
+struct MoveDescription {
+    piece: Piece,
+    source: Square,
+    castle: Option<&'static str>,
+    disambiguation: String,
+    is_capture: bool,
+    destination: String,
+    promotion: Option<Piece>,
+    is_check: bool,
+    is_mate: bool,
+}
+
+pub fn piece_letter(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => "",
+        Piece::Knight => "N",
+        Piece::Bishop => "B",
+        Piece::Rook => "R",
+        Piece::Queen => "Q",
+        Piece::King => "K",
+    }
+}
+
+pub fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => "Pawn",
+        Piece::Knight => "Knight",
+        Piece::Bishop => "Bishop",
+        Piece::Rook => "Rook",
+        Piece::Queen => "Queen",
+        Piece::King => "King",
+    }
+}
+
+fn describe(board: &Board, chess_move: ChessMove) -> MoveDescription {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let piece = board.piece_on(source).unwrap_or(Piece::Pawn);
+
+    let file_diff = dest.get_file().to_index() as i32 - source.get_file().to_index() as i32;
+    let castle = match (piece, file_diff) {
+        (Piece::King, 2) => Some("kingside"),
+        (Piece::King, -2) => Some("queenside"),
+        _ => None,
+    };
+
+    // Pawns change file only when capturing, which also covers en passant
+    let is_capture = board.piece_on(dest).is_some() || (piece == Piece::Pawn && file_diff != 0);
+
+    let source_str = format!("{}", source);
+    let disambiguation = if piece == Piece::Pawn {
+        if is_capture { source_str[..1].to_string() } else { String::new() }
+    } else {
+        let rivals: Vec<ChessMove> = MoveGen::new_legal(board)
+            .filter(|m| m.get_dest() == dest && m.get_source() != source && board.piece_on(m.get_source()) == Some(piece))
+            .collect();
+        if rivals.is_empty() {
+            String::new()
+        } else if rivals.iter().all(|m| m.get_source().get_file() != source.get_file()) {
+            source_str[..1].to_string()
+        } else if rivals.iter().all(|m| m.get_source().get_rank() != source.get_rank()) {
+            source_str[1..].to_string()
+        } else {
+            source_str
+        }
+    };
+
+    let after = board.make_move_new(chess_move);
+    let is_mate = after.status() == BoardStatus::Checkmate;
+
+    MoveDescription {
+        piece,
+        source,
+        castle,
+        disambiguation,
+        is_capture,
+        destination: format!("{}", dest),
+        promotion: chess_move.get_promotion(),
+        is_check: after.checkers().popcnt() > 0,
+        is_mate,
+    }
+}
+
+/// Standard algebraic notation for a legal move, e.g. "Nf3", "exd5", "O-O", "e8=Q+"
+pub fn to_san(board: &Board, chess_move: ChessMove) -> String {
+    format_move(board, chess_move, &NotationPrefs::default())
+}
+
+/// A legal move written the way `prefs` asks, e.g. "Sf3" or "♘g1-f3"
+pub fn format_move(board: &Board, chess_move: ChessMove, prefs: &NotationPrefs) -> String {
+    let d = describe(board, chess_move);
+
+    let mut text = match d.castle {
+        Some("kingside") => "O-O".to_string(),
+        Some(_) => "O-O-O".to_string(),
+        None => {
+            let mut s = prefs.piece_symbol(d.piece).to_string();
+            match prefs.moves {
+                MoveStyle::San => s.push_str(&d.disambiguation),
+                MoveStyle::LongAlgebraic => s.push_str(&d.source.to_string()),
+            }
+            if d.is_capture {
+                s.push('x');
+            } else if prefs.moves == MoveStyle::LongAlgebraic {
+                s.push('-');
+            }
+            s.push_str(&d.destination);
+            if let Some(promotion) = d.promotion {
+                s.push('=');
+                s.push_str(prefs.piece_symbol(promotion));
+            }
+            s
+        }
+    };
+
+    if d.is_mate {
+        text.push('#');
+    } else if d.is_check {
+        text.push('+');
+    }
+    text
+}
+
+/// Moves played in turn from `board`, each written the way `prefs` asks; stops at
+/// the first illegal one
+pub fn format_moves(board: &Board, moves: &[ChessMove], prefs: &NotationPrefs) -> Vec<String> {
+    let mut board = *board;
+    let mut written = Vec::with_capacity(moves.len());
+    for &chess_move in moves {
+        if !board.legal(chess_move) {
+            break;
+        }
+        written.push(format_move(&board, chess_move, prefs));
+        board = board.make_move_new(chess_move);
+    }
+    written
+}
+
+/// Move number of the move `ply` half-moves after `initial_fen`, and whether White
+/// plays it, counting on from the FEN's fullmove number and side to move. A FEN
+/// that can't be read counts from move 1 with White.
+pub fn move_number(initial_fen: &str, ply: usize) -> (usize, bool) {
+    let mut fields = initial_fen.split_whitespace().skip(1);
+    let black_first = fields.next() == Some("b");
+    let fullmove = fields.nth(3).and_then(|n| n.parse::<usize>().ok()).unwrap_or(1).max(1);
+    let plies = (fullmove - 1) * 2 + usize::from(black_first) + ply;
+    (plies / 2 + 1, plies % 2 == 0)
+}
+
+/// "12." before a White move and "12..." before a Black one, for the move `ply`
+/// half-moves after `initial_fen`
+pub fn move_label(initial_fen: &str, ply: usize) -> String {
+    let (number, white) = move_number(initial_fen, ply);
+    format!("{}{}", number, if white { "." } else { "..." })
+}
+
+/// A move written out for speech, e.g. "Knight takes e5, check"
+pub fn to_spoken(board: &Board, chess_move: ChessMove) -> String {
+    let d = describe(board, chess_move);
+
+    let mut spoken = match d.castle {
+        Some(side) => format!("castles {}", side),
+        None => {
+            let mut parts = Vec::new();
+            if d.piece != Piece::Pawn {
+                parts.push(piece_name(d.piece).to_string());
+            }
+            if !d.disambiguation.is_empty() {
+                parts.push(d.disambiguation.clone());
+            }
+            if d.is_capture {
+                parts.push("takes".to_string());
+            }
+            parts.push(d.destination.clone());
+            let mut s = parts.join(" ");
+            if let Some(promotion) = d.promotion {
+                s.push_str(&format!(", promotes to {}", piece_name(promotion)));
+            }
+            s
+        }
+    };
+
+    if d.is_mate {
+        spoken.push_str(", checkmate");
+    } else if d.is_check {
+        spoken.push_str(", check");
+    }
+    spoken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_san_opening_moves() {
+        let board = Board::default();
+        assert_eq!(to_san(&board, ChessMove::from_str("e2e4").unwrap()), "e4");
+        assert_eq!(to_san(&board, ChessMove::from_str("g1f3").unwrap()), "Nf3");
+        assert_eq!(to_spoken(&board, ChessMove::from_str("g1f3").unwrap()), "Knight f3");
+    }
+
+    #[test]
+    fn test_move_numbers_follow_the_fen() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(move_label(start, 0), "1.");
+        assert_eq!(move_label(start, 3), "2...");
+        // An exercise from move 20 with Black to play
+        let midgame = "r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2N2N2/PP3PPP/R2QKB1R b KQ - 3 20";
+        assert_eq!(move_number(midgame, 0), (20, false));
+        assert_eq!(move_label(midgame, 1), "21.");
+        assert_eq!(move_label("not a fen", 2), "2.");
+    }
+
+    #[test]
+    fn test_checkmate_suffix() {
+        let board = Board::from_str("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+        let mate = ChessMove::from_str("h5f7").unwrap();
+        assert_eq!(to_san(&board, mate), "Qxf7#");
+        assert_eq!(to_spoken(&board, mate), "Queen takes f7, checkmate");
+    }
+
+    #[test]
+    fn test_notation_preferences() {
+        let board = Board::default();
+        let knight = ChessMove::from_str("g1f3").unwrap();
+        let german = NotationPrefs { language: PieceLanguage::De, ..NotationPrefs::default() };
+        assert_eq!(format_move(&board, knight, &german), "Sf3");
+        let long = NotationPrefs { moves: MoveStyle::LongAlgebraic, pieces: PieceStyle::Figurine, ..german };
+        assert_eq!(format_move(&board, knight, &long), "\u{2658}g1-f3");
+        assert!(!long.is_standard() && NotationPrefs::default().is_standard());
+
+        let board = Board::from_str("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+        let mate = ChessMove::from_str("h5f7").unwrap();
+        let french = NotationPrefs { moves: MoveStyle::LongAlgebraic, language: PieceLanguage::Fr, ..NotationPrefs::default() };
+        assert_eq!(format_move(&board, mate, &french), "Dh5xf7#");
+
+        let promotion = Board::from_str("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+        let queen = ChessMove::from_str("e7e8q").unwrap();
+        assert_eq!(format_move(&promotion, queen, &german), "e8=D");
+
+        let moves: Vec<ChessMove> = ["e2e4", "e7e5", "e1e3"].iter().map(|m| ChessMove::from_str(m).unwrap()).collect();
+        assert_eq!(format_moves(&Board::default(), &moves, &german), vec!["e4", "e5"]);
+    }
+}
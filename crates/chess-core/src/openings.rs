@@ -0,0 +1,217 @@
+use std::sync::OnceLock;
+
+use chess::Board;
+use serde::Serialize;
+
+use crate::validate::parse_legal_uci;
+
+/// A named opening and the moves that define it, in UCI from the standard start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Opening {
+    pub eco: &'static str,
+    pub name: &'static str,
+    pub moves: &'static [&'static str],
+}
+
+impl Opening {
+    /// Stable id of the line, e.g. "c60-ruy-lopez"; the name alone repeats across ECO codes
+    pub fn key(&self) -> String {
+        let mut key = self.eco.to_ascii_lowercase();
+        let mut gap = true;
+        for c in self.name.chars() {
+            if c.is_ascii_alphanumeric() {
+                if gap {
+                    key.push('-');
+                }
+                key.push(c.to_ascii_lowercase());
+                gap = false;
+            } else if c != '\'' {
+                gap = true;
+            }
+        }
+        key
+    }
+}
+
+/// The opening line with `key`
+pub fn opening_by_key(key: &str) -> Option<&'static Opening> {
+    OPENINGS.iter().find(|o| o.key() == key)
+}
+
+const fn opening(eco: &'static str, name: &'static str, moves: &'static [&'static str]) -> Opening {
+    Opening { eco, name, moves }
+}
+
+/// Common openings. Detection picks the longest line a game follows, so a
+/// variation only needs its own entry when it should be named separately.
+pub const OPENINGS: &[Opening] = &[
+    opening("A00", "Polish Opening", &["b2b4"]),
+    opening("A01", "Nimzo-Larsen Attack", &["b2b3"]),
+    opening("A04", "Reti Opening", &["g1f3"]),
+    opening("A10", "English Opening", &["c2c4"]),
+    opening("A40", "Queen's Pawn Game", &["d2d4"]),
+    opening("A45", "Indian Defence", &["d2d4", "g8f6"]),
+    opening("A45", "London System", &["d2d4", "g8f6", "c1f4"]),
+    opening("A56", "Benoni Defence", &["d2d4", "g8f6", "c2c4", "c7c5"]),
+    opening("A57", "Benko Gambit", &["d2d4", "g8f6", "c2c4", "c7c5", "d4d5", "b7b5"]),
+    opening("A80", "Dutch Defence", &["d2d4", "f7f5"]),
+    opening("B00", "King's Pawn Game", &["e2e4"]),
+    opening("B01", "Scandinavian Defence", &["e2e4", "d7d5"]),
+    opening("B02", "Alekhine's Defence", &["e2e4", "g8f6"]),
+    opening("B06", "Modern Defence", &["e2e4", "g7g6"]),
+    opening("B07", "Pirc Defence", &["e2e4", "d7d6", "d2d4", "g8f6"]),
+    opening("B10", "Caro-Kann Defence", &["e2e4", "c7c6"]),
+    opening("B20", "Sicilian Defence", &["e2e4", "c7c5"]),
+    opening(
+        "B90",
+        "Sicilian Defence: Najdorf Variation",
+        &["e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6", "b1c3", "a7a6"],
+    ),
+    opening("C00", "French Defence", &["e2e4", "e7e6"]),
+    opening("C20", "Open Game", &["e2e4", "e7e5"]),
+    opening("C23", "Bishop's Opening", &["e2e4", "e7e5", "f1c4"]),
+    opening("C25", "Vienna Game", &["e2e4", "e7e5", "b1c3"]),
+    opening("C30", "King's Gambit", &["e2e4", "e7e5", "f2f4"]),
+    opening("C40", "King's Knight Opening", &["e2e4", "e7e5", "g1f3"]),
+    opening("C41", "Philidor Defence", &["e2e4", "e7e5", "g1f3", "d7d6"]),
+    opening("C42", "Petrov's Defence", &["e2e4", "e7e5", "g1f3", "g8f6"]),
+    opening("C44", "Scotch Game", &["e2e4", "e7e5", "g1f3", "b8c6", "d2d4"]),
+    opening("C47", "Four Knights Game", &["e2e4", "e7e5", "g1f3", "b8c6", "b1c3", "g8f6"]),
+    opening("C50", "Italian Game", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4"]),
+    opening("C50", "Giuoco Piano", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"]),
+    opening("C51", "Evans Gambit", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "b2b4"]),
+    opening("C55", "Two Knights Defence", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"]),
+    opening("C60", "Ruy Lopez", &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"]),
+    opening("C65", "Ruy Lopez: Berlin Defence", &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "g8f6"]),
+    opening("C68", "Ruy Lopez: Exchange Variation", &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5c6"]),
+    opening("D00", "Queen's Pawn Game", &["d2d4", "d7d5"]),
+    opening("D00", "London System", &["d2d4", "d7d5", "c1f4"]),
+    opening("D06", "Queen's Gambit", &["d2d4", "d7d5", "c2c4"]),
+    opening("D10", "Slav Defence", &["d2d4", "d7d5", "c2c4", "c7c6"]),
+    opening("D20", "Queen's Gambit Accepted", &["d2d4", "d7d5", "c2c4", "d5c4"]),
+    opening("D30", "Queen's Gambit Declined", &["d2d4", "d7d5", "c2c4", "e7e6"]),
+    opening("D80", "Grunfeld Defence", &["d2d4", "g8f6", "c2c4", "g7g6", "b1c3", "d7d5"]),
+    opening("E01", "Catalan Opening", &["d2d4", "g8f6", "c2c4", "e7e6", "g2g3"]),
+    opening("E12", "Queen's Indian Defence", &["d2d4", "g8f6", "c2c4", "e7e6", "g1f3", "b7b6"]),
+    opening("E20", "Nimzo-Indian Defence", &["d2d4", "g8f6", "c2c4", "e7e6", "b1c3", "f8b4"]),
+    opening("E60", "King's Indian Defence", &["d2d4", "g8f6", "c2c4", "g7g6"]),
+];
+
+const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -";
+
+/// Key of a position for matching across move orders: piece placement, side to
+/// move and castling rights. The en passant square and move counters depend on
+/// the order the moves came in.
+pub fn position_key(board: &Board) -> String {
+    board.to_string().split_whitespace().take(3).collect::<Vec<_>>().join(" ")
+}
+
+/// The opening `moves` (UCI) reach, if the game began from the standard
+/// position. Openings are matched on the positions along the game rather than
+/// the move order, so a transposed line is named after the opening it reaches.
+/// Games set up from a custom FEN have no opening.
+pub fn detect_opening(initial_fen: &str, moves: &[String]) -> Option<&'static Opening> {
+    let path = path_keys(initial_fen, moves)?;
+    OPENINGS
+        .iter()
+        .zip(line_keys())
+        .filter(|(_, keys)| keys.last().is_some_and(|key| path.contains(key)))
+        .map(|(opening, _)| opening)
+        .max_by_key(|o| o.moves.len())
+}
+
+/// Index of the first of `moves` that leaves `opening` for good: the move after
+/// the last position the game shares with the line, in whatever order it got
+/// there. None when the game reaches the line's final position, never leaves
+/// the line before it ends, or starts from a custom position.
+pub fn deviation_ply(opening: &Opening, initial_fen: &str, moves: &[String]) -> Option<usize> {
+    let path = path_keys(initial_fen, moves)?;
+    let line = opening_keys(opening);
+    if line.last().is_some_and(|key| path.contains(key)) {
+        return None;
+    }
+    let deviation = path.iter().rposition(|key| line.contains(key)).map_or(0, |i| i + 1);
+    (deviation < path.len()).then_some(deviation)
+}
+
+/// Keys of the positions after each of `moves`, stopping at the first illegal
+/// one; none unless the game starts from the standard position
+fn path_keys(initial_fen: &str, moves: &[String]) -> Option<Vec<String>> {
+    let start: Vec<&str> = initial_fen.split_whitespace().take(4).collect();
+    if start.join(" ") != START_POSITION {
+        return None;
+    }
+    let mut board = Board::default();
+    let mut keys = Vec::with_capacity(moves.len());
+    for uci in moves {
+        let Ok(chess_move) = parse_legal_uci(&board, uci.trim()) else {
+            break;
+        };
+        board = board.make_move_new(chess_move);
+        keys.push(position_key(&board));
+    }
+    Some(keys)
+}
+
+/// Keys of the positions along every opening's line, in the order of `OPENINGS`
+fn line_keys() -> &'static [Vec<String>] {
+    static KEYS: OnceLock<Vec<Vec<String>>> = OnceLock::new();
+    KEYS.get_or_init(|| OPENINGS.iter().map(opening_keys).collect())
+}
+
+fn opening_keys(opening: &Opening) -> Vec<String> {
+    let moves: Vec<String> = opening.moves.iter().map(|m| m.to_string()).collect();
+    path_keys(START_POSITION, &moves).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    fn moves(uci: &str) -> Vec<String> {
+        uci.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_detects_longest_matching_line() {
+        let name = |uci: &str| detect_opening(START, &moves(uci)).map(|o| o.name);
+        assert_eq!(name("e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4"), Some("Ruy Lopez"));
+        assert_eq!(name("e2e4 e7e5 g1f3 b8c6 f1b5 g8f6 e1g1"), Some("Ruy Lopez: Berlin Defence"));
+        assert_eq!(name("d2d4 d7d5 c2c4 e7e6"), Some("Queen's Gambit Declined"));
+        assert_eq!(name("e2e4"), Some("King's Pawn Game"));
+        assert_eq!(name("h2h4"), None);
+        assert_eq!(name(""), None);
+
+        let custom = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+        assert!(detect_opening(custom, &moves("e2e4")).is_none());
+    }
+
+    #[test]
+    fn test_detects_transpositions() {
+        let name = |uci: &str| detect_opening(START, &moves(uci)).map(|o| o.name);
+        // The English move order reaching the Nimzo-Indian
+        assert_eq!(name("c2c4 g8f6 b1c3 e7e6 d2d4 f8b4"), Some("Nimzo-Indian Defence"));
+        // 2.Nf3 before 2.e4 is still the Italian
+        assert_eq!(name("g1f3 b8c6 e2e4 e7e5 f1c4 g8f6"), Some("Two Knights Defence"));
+        assert_eq!(name("d2d4 g8f6 e2e4 d7d6"), Some("Pirc Defence"));
+        // An illegal move ends the path
+        assert_eq!(name("e2e4 e7e5 e1e3 b8c6"), Some("Open Game"));
+
+        let italian = OPENINGS.iter().find(|o| o.name == "Italian Game").unwrap();
+        assert_eq!(deviation_ply(italian, START, &moves("g1f3 b8c6 e2e4 e7e5 f1c4 f8c5")), None);
+        assert_eq!(deviation_ply(italian, START, &moves("g1f3 b8c6 e2e4 e7e5 d2d4")), Some(4));
+        assert_eq!(deviation_ply(italian, START, &moves("d2d4 d7d5")), Some(0));
+        assert_eq!(deviation_ply(italian, START, &moves("e2e4 e7e5")), None);
+    }
+
+    #[test]
+    fn test_keys_are_unique() {
+        let keys: std::collections::HashSet<String> = OPENINGS.iter().map(|o| o.key()).collect();
+        assert_eq!(keys.len(), OPENINGS.len());
+        assert_eq!(opening_by_key("c60-ruy-lopez").map(|o| o.name), Some("Ruy Lopez"));
+        assert_eq!(opening_by_key("c42-petrovs-defence").map(|o| o.eco), Some("C42"));
+        assert!(opening_by_key("ruy-lopez").is_none());
+    }
+}
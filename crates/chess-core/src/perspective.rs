@@ -0,0 +1,123 @@
+use chess::{Board, Color, File, Piece, Rank, Square, ALL_SQUARES};
+use serde::{Deserialize, Serialize};
+
+/// Whose pieces, seen from the player's side of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Mine,
+    Theirs,
+}
+
+impl Side {
+    /// Side a piece of `color` is on for someone playing `player`
+    pub fn of(color: Color, player: Color) -> Side {
+        if color == player {
+            Side::Mine
+        } else {
+            Side::Theirs
+        }
+    }
+
+    pub fn color(self, player: Color) -> Color {
+        match self {
+            Side::Mine => player,
+            Side::Theirs => !player,
+        }
+    }
+}
+
+/// "white" or "black", as stored with games and sent by the UI; anything else is none
+pub fn color_from_name(name: &str) -> Option<Color> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "white" | "w" => Some(Color::White),
+        "black" | "b" => Some(Color::Black),
+        _ => None,
+    }
+}
+
+pub fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
+
+/// Column and row of `square` on screen, counted from the top left, with
+/// `orientation`'s pieces at the bottom
+pub fn screen_position(square: Square, orientation: Color) -> (usize, usize) {
+    let (file, rank) = (square.get_file().to_index(), square.get_rank().to_index());
+    match orientation {
+        Color::White => (file, 7 - rank),
+        Color::Black => (7 - file, rank),
+    }
+}
+
+/// The square shown at `column` and `row` from the top left; none off the board
+pub fn square_at(column: usize, row: usize, orientation: Color) -> Option<Square> {
+    if column > 7 || row > 7 {
+        return None;
+    }
+    let (file, rank) = match orientation {
+        Color::White => (column, 7 - row),
+        Color::Black => (7 - column, row),
+    };
+    Some(Square::make_square(Rank::from_index(rank), File::from_index(file)))
+}
+
+/// Every square in reading order on screen: the top row left to right, then the next
+pub fn screen_order(orientation: Color) -> Vec<Square> {
+    (0..64).filter_map(|i| square_at(i % 8, i / 8, orientation)).collect()
+}
+
+/// `side`'s pieces for someone playing `player`, king first and then by value, each
+/// kind from a1 to h8
+pub fn pieces_of(board: &Board, player: Color, side: Side) -> Vec<(Piece, Square)> {
+    let color = side.color(player);
+    let mut pieces: Vec<(Piece, Square)> = ALL_SQUARES
+        .into_iter()
+        .filter(|sq| board.color_on(*sq) == Some(color))
+        .filter_map(|sq| board.piece_on(sq).map(|piece| (piece, sq)))
+        .collect();
+    pieces.sort_by_key(|(piece, square)| (std::cmp::Reverse(value_order(*piece)), square.to_index()));
+    pieces
+}
+
+fn value_order(piece: Piece) -> u8 {
+    match piece {
+        Piece::King => 5,
+        Piece::Queen => 4,
+        Piece::Rook => 3,
+        Piece::Bishop => 2,
+        Piece::Knight => 1,
+        Piece::Pawn => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping_round_trips_for_both_orientations() {
+        for orientation in [Color::White, Color::Black] {
+            for square in ALL_SQUARES {
+                let (column, row) = screen_position(square, orientation);
+                assert_eq!(square_at(column, row, orientation), Some(square));
+            }
+        }
+        assert_eq!(screen_order(Color::White)[0], Square::A8);
+        assert_eq!(screen_order(Color::Black)[0], Square::H1);
+        assert_eq!(screen_position(Square::E1, Color::Black), (3, 0));
+        assert_eq!(square_at(8, 0, Color::White), None);
+
+        let board = Board::default();
+        let mine = pieces_of(&board, Color::Black, Side::Mine);
+        assert_eq!(mine.len(), 16);
+        assert_eq!(mine[0], (Piece::King, Square::E8));
+        assert_eq!(pieces_of(&board, Color::Black, Side::Theirs)[0], (Piece::King, Square::E1));
+        assert_eq!(Side::of(Color::White, Color::Black), Side::Theirs);
+        assert_eq!(color_from_name(" Black "), Some(Color::Black));
+        assert_eq!(color_from_name("red"), None);
+    }
+}
@@ -1,6 +1,59 @@
-use chess::{Board, Color, Piece, Square, ALL_SQUARES};
+use chess::{Board, Color, Piece, Rank, Square, ALL_SQUARES};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
+use crate::error::ChessError;
+
+/// Squares the knights and bishops start on
+const MINOR_PIECE_HOMES: [Square; 8] = [
+    Square::B1, Square::C1, Square::F1, Square::G1,
+    Square::B8, Square::C8, Square::F8, Square::G8,
+];
+/// Minor pieces still at home for a position to count as the opening
+const OPENING_UNDEVELOPED_MINORS: usize = 4;
+
+/// Stage of the game a position belongs to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamePhase {
+    Opening,
+    #[default]
+    Middlegame,
+    Endgame,
+}
+
+impl GamePhase {
+    pub const ALL: [GamePhase; 3] = [GamePhase::Opening, GamePhase::Middlegame, GamePhase::Endgame];
+
+    /// Phase of `board`; see [`Position::phase`]
+    pub fn of(board: &Board) -> Self {
+        Position::new(*board).phase()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GamePhase::Opening => "Opening",
+            GamePhase::Middlegame => "Middlegame",
+            GamePhase::Endgame => "Endgame",
+        }
+    }
+}
+
+impl std::fmt::Display for GamePhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GamePhase {
+    type Err = ChessError;
+
+    /// Case-insensitive, so "endgame" from the UI or a coach tool call parses
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GamePhase::ALL
+            .into_iter()
+            .find(|phase| phase.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ChessError::ParseError(format!("unknown game phase '{}'", s)))
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
@@ -20,7 +73,7 @@ where
     D: serde::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    Board::from_str(&s).map_err(serde::de::Error::custom)
+    crate::validate::parse_fen(&s).map_err(serde::de::Error::custom)
 }
 
 impl Position {
@@ -29,9 +82,9 @@ impl Position {
     }
 
     pub fn from_fen(fen: &str) -> Result<Self, String> {
-        Board::from_str(fen)
+        crate::validate::parse_fen(fen)
             .map(|board| Self { board })
-            .map_err(|e| format!("Invalid FEN: {}", e))
+            .map_err(|e| e.to_string())
     }
 
     pub fn material_count(&self, color: Color) -> i32 {
@@ -46,7 +99,7 @@ impl Position {
         count
     }
 
-    fn piece_value(piece: Piece) -> i32 {
+    pub fn piece_value(piece: Piece) -> i32 {
         match piece {
             Piece::Pawn => 1,
             Piece::Knight => 3,
@@ -82,10 +135,32 @@ impl Position {
         (white_queens == 0 && black_queens == 0) || total_material < 20
     }
 
+    /// Endgame by `is_endgame`, otherwise the opening while at least half the
+    /// knights and bishops are undeveloped, otherwise the middlegame
+    pub fn phase(&self) -> GamePhase {
+        if self.is_endgame() {
+            return GamePhase::Endgame;
+        }
+        let undeveloped = MINOR_PIECE_HOMES
+            .iter()
+            .filter(|square| {
+                let owner = if square.get_rank() == Rank::First { Color::White } else { Color::Black };
+                matches!(self.board.piece_on(**square), Some(Piece::Knight | Piece::Bishop))
+                    && self.board.color_on(**square) == Some(owner)
+            })
+            .count();
+        if undeveloped >= OPENING_UNDEVELOPED_MINORS {
+            GamePhase::Opening
+        } else {
+            GamePhase::Middlegame
+        }
+    }
+
     pub fn analyze(&self) -> PositionAnalysis {
         PositionAnalysis {
             material_balance: self.material_balance(),
             is_endgame: self.is_endgame(),
+            phase: self.phase(),
             white_material: self.material_count(Color::White),
             black_material: self.material_count(Color::Black),
             side_to_move: self.board.side_to_move(),
@@ -97,6 +172,8 @@ impl Position {
 pub struct PositionAnalysis {
     pub material_balance: i32,
     pub is_endgame: bool,
+    #[serde(default)]
+    pub phase: GamePhase,
     pub white_material: i32,
     pub black_material: i32,
     #[serde(serialize_with = "serialize_color", deserialize_with = "deserialize_color")]
@@ -160,4 +237,14 @@ mod tests {
         assert_eq!(position.material_count(Color::White), 39);
         assert_eq!(position.material_count(Color::Black), 39);
     }
+
+    #[test]
+    fn test_phase() {
+        let phase = |fen: &str| Position::from_fen(fen).unwrap().phase();
+        assert_eq!(phase("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"), GamePhase::Opening);
+        assert_eq!(phase("r1bq1rk1/ppp2ppp/2np1n2/2b1p3/2B1P3/2NP1N2/PPP2PPP/R1BQ1RK1 w - - 0 8"), GamePhase::Middlegame);
+        assert_eq!(phase("4k3/R7/8/3KP3/8/8/8/1r6 b - - 0 1"), GamePhase::Endgame);
+        assert_eq!("endgame".parse::<GamePhase>().unwrap(), GamePhase::Endgame);
+        assert!("late".parse::<GamePhase>().is_err());
+    }
 }
@@ -0,0 +1,64 @@
+/// Small seeded generator (SplitMix64) for reproducible sessions. The same
+/// seed always gives the same sequence on every platform, which is all QA and
+/// bug reports need; it is not suitable for anything security related.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededRng {
+    state: u64,
+}
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// An independent generator for one step of a seeded session, e.g. the
+    /// nth exercise or the position the engine is moving in
+    pub fn derive(seed: u64, stream: u64) -> Self {
+        Self::new(seed ^ Self::new(stream).next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index below `len`, which must be non-zero
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle in place
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.index(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let first: Vec<u64> = (0..5).scan(SeededRng::new(42), |rng, _| Some(rng.next_u64())).collect();
+        let second: Vec<u64> = (0..5).scan(SeededRng::new(42), |rng, _| Some(rng.next_u64())).collect();
+        assert_eq!(first, second);
+        assert_ne!(SeededRng::new(42).next_u64(), SeededRng::new(43).next_u64());
+        assert_ne!(SeededRng::derive(42, 0).next_u64(), SeededRng::derive(42, 1).next_u64());
+
+        let mut items: Vec<u32> = (0..20).collect();
+        SeededRng::new(7).shuffle(&mut items);
+        let mut again: Vec<u32> = (0..20).collect();
+        SeededRng::new(7).shuffle(&mut again);
+        assert_eq!(items, again);
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,231 @@
+use chess::{Board, ChessMove, File, MoveGen, Piece, Rank, Square};
+use std::str::FromStr;
+use crate::error::{ChessError, Result};
+use crate::notation::to_san;
+
+/// Longest input accepted from outside; a legal FEN is well under 100 bytes
+pub const MAX_INPUT_LEN: usize = 128;
+
+/// Parse a FEN from the UI or storage. The string is checked field by field
+/// before it reaches the chess crate, so sloppy input comes back as an error
+/// rather than a panic or a silently wrapped board.
+pub fn parse_fen(fen: &str) -> Result<Board> {
+    let fen = fen.trim();
+    check_length(fen, "FEN")?;
+
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    // Move counters are optional, as in many hand-written FENs
+    if fields.len() != 4 && fields.len() != 6 {
+        return Err(ChessError::InvalidFen(format!("expected 4 or 6 fields, found {}", fields.len())));
+    }
+
+    check_placement(fields[0])?;
+    if !matches!(fields[1], "w" | "b") {
+        return Err(ChessError::InvalidFen(format!("side to move must be 'w' or 'b', not '{}'", fields[1])));
+    }
+    if fields[2] != "-" && (fields[2].len() > 4 || !fields[2].chars().all(|c| "KQkq".contains(c))) {
+        return Err(ChessError::InvalidFen(format!("bad castling rights '{}'", fields[2])));
+    }
+    if fields[3] != "-" {
+        let square = parse_square(fields[3])
+            .ok_or_else(|| ChessError::InvalidFen(format!("bad en passant square '{}'", fields[3])))?;
+        if !matches!(square.get_rank(), Rank::Third | Rank::Sixth) {
+            return Err(ChessError::InvalidFen(format!("en passant square {} is not on the third or sixth rank", square)));
+        }
+    }
+    for counter in fields.iter().skip(4) {
+        counter
+            .parse::<u32>()
+            .map_err(|_| ChessError::InvalidFen(format!("bad move counter '{}'", counter)))?;
+    }
+
+    Board::from_str(fen).map_err(|e| ChessError::InvalidFen(e.to_string()))
+}
+
+/// Piece placement: eight ranks of exactly eight squares and one king per side
+fn check_placement(placement: &str) -> Result<()> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(ChessError::InvalidFen(format!("expected 8 ranks, found {}", ranks.len())));
+    }
+    for (i, rank) in ranks.iter().enumerate() {
+        let mut squares = 0;
+        for c in rank.chars() {
+            squares += match c {
+                '1'..='8' => c as u32 - '0' as u32,
+                'p' | 'n' | 'b' | 'r' | 'q' | 'k' | 'P' | 'N' | 'B' | 'R' | 'Q' | 'K' => 1,
+                _ => return Err(ChessError::InvalidFen(format!("unexpected '{}' in rank {}", c, 8 - i))),
+            };
+        }
+        if squares != 8 {
+            return Err(ChessError::InvalidFen(format!("rank {} has {} squares", 8 - i, squares)));
+        }
+    }
+    for king in ['K', 'k'] {
+        let count = placement.chars().filter(|c| *c == king).count();
+        if count != 1 {
+            return Err(ChessError::InvalidFen(format!("expected one '{}', found {}", king, count)));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a move in UCI form ("e2e4", "e7e8q"). Says nothing about legality.
+pub fn parse_uci(uci: &str) -> Result<ChessMove> {
+    let uci = uci.trim();
+    check_length(uci, "move")?;
+    let chars: Vec<char> = uci.chars().collect();
+    if chars.len() != 4 && chars.len() != 5 {
+        return Err(ChessError::InvalidMove(format!("'{}' is not a UCI move", uci)));
+    }
+
+    let from = square_at(&chars[0..2]).ok_or_else(|| ChessError::InvalidMove(format!("bad source square in '{}'", uci)))?;
+    let to = square_at(&chars[2..4]).ok_or_else(|| ChessError::InvalidMove(format!("bad destination square in '{}'", uci)))?;
+    let promotion = match chars.get(4).map(|c| c.to_ascii_lowercase()) {
+        None => None,
+        Some('q') => Some(Piece::Queen),
+        Some('r') => Some(Piece::Rook),
+        Some('b') => Some(Piece::Bishop),
+        Some('n') => Some(Piece::Knight),
+        Some(c) => return Err(ChessError::InvalidMove(format!("bad promotion piece '{}'", c))),
+    };
+    Ok(ChessMove::new(from, to, promotion))
+}
+
+/// Parse a UCI move and check it is legal on `board`
+pub fn parse_legal_uci(board: &Board, uci: &str) -> Result<ChessMove> {
+    let chess_move = parse_uci(uci)?;
+    if !board.legal(chess_move) {
+        let queening = ChessMove::new(chess_move.get_source(), chess_move.get_dest(), Some(Piece::Queen));
+        if chess_move.get_promotion().is_none() && board.legal(queening) {
+            return Err(ChessError::InvalidMove(format!("{} promotes; add the piece, e.g. {}", uci.trim(), queening)));
+        }
+        return Err(ChessError::InvalidMove(format!("{} is not legal here", uci.trim())));
+    }
+    Ok(chess_move)
+}
+
+/// Find the legal move written in SAN. Check and annotation marks are ignored,
+/// as is "0-0" for "O-O", and promotions may be written "e8=Q", "e8Q", "e8q" or "e8(Q)".
+pub fn parse_san(board: &Board, san: &str) -> Result<ChessMove> {
+    let san = san.trim();
+    check_length(san, "move")?;
+    let wanted = normalize_promotion(&strip_san(&san.replace('0', "O")));
+    if wanted.is_empty() {
+        return Err(ChessError::InvalidMove("empty move".to_string()));
+    }
+    MoveGen::new_legal(board)
+        .find(|m| strip_san(&to_san(board, *m)) == wanted)
+        .ok_or_else(|| ChessError::InvalidMove(format!("{} is not legal here", san)))
+}
+
+/// Accept a move in either UCI or SAN, as players and exercise data mix both
+pub fn parse_move(board: &Board, text: &str) -> Result<ChessMove> {
+    parse_legal_uci(board, text).or_else(|_| parse_san(board, text))
+}
+
+fn strip_san(san: &str) -> String {
+    san.trim_end_matches(['+', '#', '!', '?']).to_string()
+}
+
+/// Rewrite the other ways of writing a promotion as "e8=Q"
+fn normalize_promotion(san: &str) -> String {
+    let body = san.trim_end_matches(')');
+    let Some(piece) = body.chars().last().filter(|c| "QRBNqrbn".contains(*c)) else {
+        return san.to_string();
+    };
+    let square = body[..body.len() - 1].trim_end_matches(['=', '(', '/']);
+    if !square.ends_with(['1', '8']) {
+        return san.to_string();
+    }
+    format!("{}={}", square, piece.to_ascii_uppercase())
+}
+
+fn check_length(input: &str, what: &str) -> Result<()> {
+    if input.is_empty() {
+        return Err(ChessError::ParseError(format!("empty {}", what)));
+    }
+    if input.len() > MAX_INPUT_LEN {
+        return Err(ChessError::ParseError(format!("{} is longer than {} bytes", what, MAX_INPUT_LEN)));
+    }
+    Ok(())
+}
+
+fn parse_square(text: &str) -> Option<Square> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() != 2 {
+        return None;
+    }
+    square_at(&chars)
+}
+
+/// `chars` holds exactly a file letter and a rank digit
+fn square_at(chars: &[char]) -> Option<Square> {
+    let file = match chars.first()? {
+        c @ 'a'..='h' => File::from_index(*c as usize - 'a' as usize),
+        _ => return None,
+    };
+    let rank = match chars.get(1)? {
+        c @ '1'..='8' => Rank::from_index(*c as usize - '1' as usize),
+        _ => return None,
+    };
+    Some(Square::make_square(rank, file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_malformed_fens_are_rejected() {
+        let bad = [
+            "",
+            "   ",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR w KQkq - 0 1",
+            "rnbqkbnr/ppppéppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkqX - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - -1 1",
+        ];
+        for fen in bad {
+            assert!(parse_fen(fen).is_err(), "accepted {:?}", fen);
+        }
+        assert!(parse_fen(&"8/".repeat(100)).is_err());
+    }
+
+    #[test]
+    fn test_malformed_uci_is_rejected() {
+        for uci in ["", "e2", "e2e", "e2e4qq", "i2e4", "e9e4", "e2e4k", "é2e4", "e2é4", "ée4", "e2e4\u{0301}"] {
+            assert!(parse_uci(uci).is_err(), "accepted {:?}", uci);
+        }
+
+        let promotion = parse_uci(" e7e8Q ").unwrap();
+        assert_eq!(promotion.get_promotion(), Some(Piece::Queen));
+        assert_eq!(parse_uci("g1f3").unwrap().to_string(), "g1f3");
+    }
+
+    #[test]
+    fn test_promotions_in_every_notation() {
+        let board = parse_fen("3r4/4P3/8/8/8/k7/8/4K3 w - - 0 1").unwrap();
+        for (text, uci) in [
+            ("e8=N", "e7e8n"),
+            ("e8N+", "e7e8n"),
+            ("e8n", "e7e8n"),
+            ("e8(R)", "e7e8r"),
+            ("e8/B", "e7e8b"),
+            ("exd8=Q", "e7d8q"),
+            ("exd8q", "e7d8q"),
+            ("e7e8B", "e7e8b"),
+        ] {
+            assert_eq!(parse_move(&board, text).unwrap().to_string(), uci, "{}", text);
+        }
+        assert!(parse_move(&board, "e8").is_err());
+        let missing = parse_legal_uci(&board, "e7e8").unwrap_err().to_string();
+        assert!(missing.contains("e7e8q"), "{}", missing);
+    }
+}
@@ -0,0 +1,345 @@
+use chess::{Board, ChessMove, File, MoveGen, Piece, Rank, Square};
+
+/// Outcome of matching a spoken phrase against the legal moves of a position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpokenMove {
+    /// Exactly one legal move fits the phrase
+    Move(ChessMove),
+    /// Several legal moves fit; the caller should ask which one was meant
+    Ambiguous(Vec<ChessMove>),
+    /// Nothing legal fits the phrase
+    NotFound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CastleSide {
+    King,
+    Queen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Piece(Piece),
+    Square(Square),
+    File(File),
+    Rank(Rank),
+    Capture,
+    Castle,
+    Side(CastleSide),
+    Promote,
+}
+
+/// Everything a phrase said about the move it describes
+#[derive(Debug, Default)]
+struct MoveQuery {
+    piece: Option<Piece>,
+    captured: Option<Piece>,
+    capture: bool,
+    squares: Vec<Square>,
+    source_file: Option<File>,
+    source_rank: Option<Rank>,
+    dest_file: Option<File>,
+    promotion: Option<Piece>,
+    castle: bool,
+    castle_side: Option<CastleSide>,
+}
+
+fn piece_word(word: &str) -> Option<Piece> {
+    match word {
+        "pawn" | "pawns" | "pon" => Some(Piece::Pawn),
+        "knight" | "knights" | "night" | "nights" | "nite" | "horse" => Some(Piece::Knight),
+        "bishop" | "bishops" => Some(Piece::Bishop),
+        "rook" | "rooks" | "rock" | "ruck" => Some(Piece::Rook),
+        "queen" | "queens" => Some(Piece::Queen),
+        "king" | "kings" => Some(Piece::King),
+        _ => None,
+    }
+}
+
+fn file_word(word: &str) -> Option<File> {
+    let index = match word {
+        "a" | "alpha" | "ay" => 0,
+        "b" | "bee" | "be" | "bravo" => 1,
+        "c" | "see" | "sea" | "charlie" => 2,
+        "d" | "dee" | "delta" => 3,
+        "e" | "ee" | "echo" => 4,
+        "f" | "eff" | "foxtrot" => 5,
+        "g" | "gee" | "jee" | "golf" => 6,
+        "h" | "aitch" | "hotel" => 7,
+        _ => return None,
+    };
+    Some(File::from_index(index))
+}
+
+fn rank_word(word: &str) -> Option<Rank> {
+    let index = match word {
+        "1" | "one" | "won" => 0,
+        "2" | "two" | "too" => 1,
+        "3" | "three" | "tree" => 2,
+        "4" | "four" | "for" => 3,
+        "5" | "five" => 4,
+        "6" | "six" => 5,
+        "7" | "seven" => 6,
+        "8" | "eight" | "ate" => 7,
+        _ => return None,
+    };
+    Some(Rank::from_index(index))
+}
+
+fn tokenize(transcript: &str) -> Vec<Token> {
+    let text = transcript
+        .to_lowercase()
+        .replace("o-o-o", " castle queenside ")
+        .replace("o-o", " castle kingside ")
+        .replace("0-0-0", " castle queenside ")
+        .replace("0-0", " castle kingside ");
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || matches!(c, '-' | ',' | '.' | '!' | '?' | '='))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        let next = words.get(i + 1).copied();
+
+        // "e4", "e 4", "echo four"
+        // Compare chars rather than bytes; transcripts can hold any Unicode
+        let mut chars = word.chars();
+        if let (Some(first), Some(second), None) = (chars.next(), chars.next(), chars.next()) {
+            if let (Some(file), Some(rank)) = (file_word(&first.to_string()), rank_word(&second.to_string())) {
+                tokens.push(Token::Square(Square::make_square(rank, file)));
+                i += 1;
+                continue;
+            }
+        }
+        if let Some(file) = file_word(word) {
+            if let Some(rank) = next.and_then(rank_word) {
+                tokens.push(Token::Square(Square::make_square(rank, file)));
+                i += 2;
+                continue;
+            }
+            // A lone "a" is almost always the article
+            if word != "a" {
+                tokens.push(Token::File(file));
+            }
+            i += 1;
+            continue;
+        }
+
+        // "king side" / "queen side" before they are read as pieces
+        if matches!(word, "king" | "queen") && matches!(next, Some("side") | Some("sides")) {
+            tokens.push(Token::Side(if word == "king" { CastleSide::King } else { CastleSide::Queen }));
+            i += 2;
+            continue;
+        }
+
+        let token = match word {
+            "takes" | "take" | "captures" | "capture" | "capturing" | "x" => Some(Token::Capture),
+            "castle" | "castles" | "castling" => Some(Token::Castle),
+            "kingside" | "short" => Some(Token::Side(CastleSide::King)),
+            "queenside" | "long" => Some(Token::Side(CastleSide::Queen)),
+            "promote" | "promotes" | "promoting" | "promotion" | "equals" => Some(Token::Promote),
+            _ => piece_word(word)
+                .map(Token::Piece)
+                .or_else(|| rank_word(word).map(Token::Rank)),
+        };
+        if let Some(token) = token {
+            tokens.push(token);
+        }
+        i += 1;
+    }
+
+    tokens
+}
+
+fn build_query(tokens: &[Token]) -> MoveQuery {
+    let mut query = MoveQuery::default();
+    let mut promote_pending = false;
+
+    for token in tokens {
+        match *token {
+            Token::Piece(piece) => {
+                if promote_pending {
+                    query.promotion = Some(piece);
+                    promote_pending = false;
+                } else if query.piece.is_none() && !query.capture && query.squares.is_empty() {
+                    query.piece = Some(piece);
+                } else if query.capture && query.captured.is_none() && query.squares.is_empty() {
+                    query.captured = Some(piece);
+                } else if !query.squares.is_empty() {
+                    // "e8 queen"
+                    query.promotion = Some(piece);
+                } else if query.piece.is_none() {
+                    query.piece = Some(piece);
+                }
+            }
+            Token::Square(square) => query.squares.push(square),
+            Token::File(file) => {
+                if query.capture && query.squares.is_empty() {
+                    query.dest_file = Some(file);
+                } else if query.squares.is_empty() {
+                    query.source_file = Some(file);
+                }
+            }
+            Token::Rank(rank) => {
+                if query.squares.is_empty() && !query.capture {
+                    query.source_rank = Some(rank);
+                }
+            }
+            Token::Capture => query.capture = true,
+            Token::Castle => query.castle = true,
+            Token::Side(side) => query.castle_side = Some(side),
+            Token::Promote => promote_pending = true,
+        }
+    }
+
+    // A side on its own ("kingside") only makes sense as castling
+    if query.castle_side.is_some() && query.squares.is_empty() && query.piece.is_none() {
+        query.castle = true;
+    }
+
+    query
+}
+
+fn is_capture(board: &Board, chess_move: ChessMove) -> bool {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    board.piece_on(dest).is_some()
+        || (board.piece_on(source) == Some(Piece::Pawn) && source.get_file() != dest.get_file())
+}
+
+fn captured_piece(board: &Board, chess_move: ChessMove) -> Option<Piece> {
+    board.piece_on(chess_move.get_dest()).or_else(|| {
+        // En passant
+        is_capture(board, chess_move).then_some(Piece::Pawn)
+    })
+}
+
+fn matches_query(board: &Board, chess_move: ChessMove, query: &MoveQuery) -> bool {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let piece = board.piece_on(source);
+    let castles = piece == Some(Piece::King) && (dest.get_file().to_index() as i32 - source.get_file().to_index() as i32).abs() == 2;
+
+    if query.castle {
+        let side_ok = match query.castle_side {
+            Some(CastleSide::King) => dest.get_file() == File::G,
+            Some(CastleSide::Queen) => dest.get_file() == File::C,
+            None => true,
+        };
+        return castles && side_ok;
+    }
+
+    let (source_square, dest_square) = match query.squares.as_slice() {
+        [] => (None, None),
+        [dest] => (None, Some(*dest)),
+        [first, .., last] => (Some(*first), Some(*last)),
+    };
+
+    if query.piece.is_some() && piece != query.piece {
+        return false;
+    }
+    if dest_square.is_some_and(|d| d != dest) || source_square.is_some_and(|s| s != source) {
+        return false;
+    }
+    if query.dest_file.is_some_and(|f| f != dest.get_file())
+        || query.source_file.is_some_and(|f| f != source.get_file())
+        || query.source_rank.is_some_and(|r| r != source.get_rank())
+    {
+        return false;
+    }
+    if query.capture && !is_capture(board, chess_move) {
+        return false;
+    }
+    if query.captured.is_some() && captured_piece(board, chess_move) != query.captured {
+        return false;
+    }
+
+    match (query.promotion, chess_move.get_promotion()) {
+        (Some(wanted), promotion) => promotion == Some(wanted),
+        // Unspecified promotions default to a queen
+        (None, Some(promotion)) => promotion == Piece::Queen,
+        (None, None) => true,
+    }
+}
+
+/// Match a transcribed phrase like "knight to f3", "castle kingside" or
+/// "pawn takes on d5" against the legal moves of `board`.
+pub fn parse_spoken_move(board: &Board, transcript: &str) -> SpokenMove {
+    let query = build_query(&tokenize(transcript));
+
+    let says_something = query.castle
+        || !query.squares.is_empty()
+        || query.captured.is_some()
+        || query.dest_file.is_some();
+    if !says_something {
+        return SpokenMove::NotFound;
+    }
+
+    let candidates: Vec<ChessMove> = MoveGen::new_legal(board)
+        .filter(|m| matches_query(board, *m, &query))
+        .collect();
+
+    match candidates.len() {
+        0 => SpokenMove::NotFound,
+        1 => SpokenMove::Move(candidates[0]),
+        _ => SpokenMove::Ambiguous(candidates),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn parse(fen: &str, phrase: &str) -> SpokenMove {
+        parse_spoken_move(&Board::from_str(fen).unwrap(), phrase)
+    }
+
+    fn uci(m: &str) -> SpokenMove {
+        SpokenMove::Move(ChessMove::from_str(m).unwrap())
+    }
+
+    #[test]
+    fn test_simple_phrases() {
+        let board = Board::default();
+        assert_eq!(parse_spoken_move(&board, "knight to f3"), uci("g1f3"));
+        assert_eq!(parse_spoken_move(&board, "Night F three"), uci("g1f3"));
+        assert_eq!(parse_spoken_move(&board, "e4"), uci("e2e4"));
+        assert_eq!(parse_spoken_move(&board, "pawn to echo four"), uci("e2e4"));
+        assert_eq!(parse_spoken_move(&board, "hello there"), SpokenMove::NotFound);
+        assert_eq!(parse_spoken_move(&board, "bishop to c4"), SpokenMove::NotFound);
+    }
+
+    #[test]
+    fn test_captures_and_castling() {
+        let fen = "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQK2R w KQkq - 0 5";
+        assert_eq!(parse(fen, "castle kingside"), uci("e1g1"));
+        assert_eq!(parse(fen, "O-O"), uci("e1g1"));
+        assert_eq!(parse(fen, "knight takes e5"), uci("f3e5"));
+        assert_eq!(parse(fen, "knight takes pawn"), uci("f3e5"));
+
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+        assert_eq!(parse(fen, "pawn takes on d5"), uci("e4d5"));
+    }
+
+    #[test]
+    fn test_ambiguous_and_disambiguated() {
+        let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+        match parse(fen, "knight to d2") {
+            SpokenMove::Ambiguous(moves) => assert_eq!(moves.len(), 2),
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+        assert_eq!(parse(fen, "b knight to d2"), uci("b1d2"));
+        assert_eq!(parse(fen, "knight from f1 to d2"), uci("f1d2"));
+    }
+
+    #[test]
+    fn test_promotion() {
+        let fen = "8/4P3/8/8/8/8/k7/4K3 w - - 0 1";
+        assert_eq!(parse(fen, "e8"), uci("e7e8q"));
+        assert_eq!(parse(fen, "e8 promote to knight"), uci("e7e8n"));
+    }
+}
@@ -1,7 +1,20 @@
-use chess::{Board, ChessMove, Color};
+use chess::{Board, BoardStatus, ChessMove, Color};
 use chess_core::{ChessGame, MoveQuality, AnnotatedMove};
 use serde::{Deserialize, Serialize};
-use crate::evaluator::Evaluator;
+use crate::cancel::{Cancelled, CancellationToken};
+use crate::evaluator::{Evaluator, PositionEvaluation};
+use crate::quality::QualityCalibration;
+use crate::score::ScoreFromWhite;
+
+/// Plies after a sacrifice within which the mover's evaluation has to recover
+pub const SACRIFICE_WINDOW: usize = 6;
+
+/// Material, net of the opponent's reply, a move must give up to count as a sacrifice:
+/// the exchange or more, so a pawn dropped in passing is not one
+const SACRIFICE_MATERIAL: i32 = 150;
+
+/// How far below its evaluation before the sacrifice the mover may settle and still count as recovered
+const RECOVERY_MARGIN: i32 = 50;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TacticalPattern {
@@ -20,15 +33,29 @@ pub struct MoveAnalysis {
     pub move_number: usize,
     #[serde(serialize_with = "serialize_chess_move", deserialize_with = "deserialize_chess_move")]
     pub chess_move: ChessMove,
-    pub evaluation_before: i32,
-    pub evaluation_after: i32,
+    pub evaluation_before: ScoreFromWhite,
+    pub evaluation_after: ScoreFromWhite,
     #[serde(serialize_with = "serialize_chess_move", deserialize_with = "deserialize_chess_move")]
     pub best_move: ChessMove,
-    pub best_move_eval: i32,
+    /// Evaluation after `best_move`
+    pub best_move_eval: ScoreFromWhite,
     pub quality: MoveQuality,
+    /// How much worse the move left the mover than `best_move`, never negative
     pub centipawn_loss: i32,
     pub tactical_pattern: TacticalPattern,
     pub comment: String,
+    /// Set when the move gave up material, see `GameAnalyzer::mark_sacrifices`
+    #[serde(default)]
+    pub sacrifice: Option<Sacrifice>,
+}
+
+/// Material given up by a move and whether it paid off
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Sacrifice {
+    /// Centipawns of material lost once the opponent has replied
+    pub material: i32,
+    /// Whether the mover's evaluation recovered within `SACRIFICE_WINDOW` plies
+    pub sound: bool,
 }
 
 fn serialize_chess_move<S>(chess_move: &ChessMove, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -42,89 +69,156 @@ fn deserialize_chess_move<'de, D>(deserializer: D) -> std::result::Result<ChessM
 where
     D: serde::Deserializer<'de>,
 {
-    use std::str::FromStr;
     let s = String::deserialize(deserializer)?;
     // Parse the UCI move string (e.g., "e2e4")
-    if s.len() >= 4 {
-        let from = chess::Square::from_str(&s[0..2])
-            .map_err(|e| serde::de::Error::custom(format!("Invalid from square: {}", e)))?;
-        let to = chess::Square::from_str(&s[2..4])
-            .map_err(|e| serde::de::Error::custom(format!("Invalid to square: {}", e)))?;
-        let promotion = if s.len() == 5 {
-            let promo_char = s.chars().nth(4).unwrap();
-            Some(match promo_char.to_lowercase().next().unwrap() {
-                'q' => chess::Piece::Queen,
-                'r' => chess::Piece::Rook,
-                'b' => chess::Piece::Bishop,
-                'n' => chess::Piece::Knight,
-                _ => return Err(serde::de::Error::custom("Invalid promotion piece")),
-            })
-        } else {
-            None
-        };
-        Ok(ChessMove::new(from, to, promotion))
-    } else {
-        Err(serde::de::Error::custom("Move string too short"))
-    }
+    chess_core::validate::parse_uci(&s).map_err(serde::de::Error::custom)
 }
 
 pub struct GameAnalyzer;
 
 impl GameAnalyzer {
     pub fn analyze_game(game: &ChessGame) -> Vec<MoveAnalysis> {
+        Self::analyze_game_with(game, &QualityCalibration::default())
+    }
+
+    /// `analyze_game` judging moves by `calibration`
+    pub fn analyze_game_with(game: &ChessGame, calibration: &QualityCalibration) -> Vec<MoveAnalysis> {
+        Self::analyze_game_cancellable(game, calibration, &CancellationToken::new()).unwrap_or_default()
+    }
+
+    /// `analyze_game_with` that stops as soon as `token` is cancelled
+    pub fn analyze_game_cancellable(
+        game: &ChessGame,
+        calibration: &QualityCalibration,
+        token: &CancellationToken,
+    ) -> Result<Vec<MoveAnalysis>, Cancelled> {
+        let _span = tracing::debug_span!("analyze_game", moves = game.move_history.len()).entered();
         let mut analyses = Vec::new();
-        let mut board = Board::default();
+        let mut positions = vec![Board::default()];
 
         for (index, annotated_move) in game.move_history.iter().enumerate() {
-            let analysis = Self::analyze_move(&board, annotated_move.chess_move, index);
+            let board = positions[index];
+            let analysis = Self::analyze_move_cancellable(&board, annotated_move.chess_move, index, calibration, token)?;
             analyses.push(analysis);
-            board = board.make_move_new(annotated_move.chess_move);
+            positions.push(board.make_move_new(annotated_move.chess_move));
         }
 
-        analyses
+        Self::mark_sacrifices(&positions, &mut analyses, calibration);
+        Ok(analyses)
     }
 
     pub fn analyze_move(board: &Board, chess_move: ChessMove, move_number: usize) -> MoveAnalysis {
-        let eval_before = Evaluator::evaluate_position(board);
-        let new_board = board.make_move_new(chess_move);
-        let eval_after = Evaluator::evaluate_position(&new_board);
-
-        let best_move_eval = Evaluator::find_best_move(board);
-        let (best_move, best_move_score) = match best_move_eval {
-            Some(eval) => (eval.chess_move, eval.score),
-            None => (chess_move, eval_after.score),
+        // A fresh token is never cancelled, so this always yields an analysis
+        let calibration = QualityCalibration::default();
+        Self::analyze_move_cancellable(board, chess_move, move_number, &calibration, &CancellationToken::new())
+            .expect("uncancelled analysis")
+    }
+
+    pub fn analyze_move_cancellable(
+        board: &Board,
+        chess_move: ChessMove,
+        move_number: usize,
+        calibration: &QualityCalibration,
+        token: &CancellationToken,
+    ) -> Result<MoveAnalysis, Cancelled> {
+        let mover = board.side_to_move();
+        let eval_before = Evaluator::evaluate_position(board).score;
+        let eval_after = Evaluator::evaluate_position(&board.make_move_new(chess_move)).score;
+
+        let (best_move, best_move_eval) = match Evaluator::find_best_move_cancellable(board, token)? {
+            Some(eval) => (eval.chess_move, eval.score.to_white(mover)),
+            None => (chess_move, eval_after),
         };
 
-        // Calculate centipawn loss (from the player's perspective)
-        let centipawn_loss = (best_move_score - eval_after.score).abs();
+        // Both scores are White's, so compare them from the mover's side
+        let (best, played) = (best_move_eval.for_side(mover), eval_after.for_side(mover));
+        let centipawn_loss = (best - played).max(0);
 
-        let quality = Self::determine_move_quality(centipawn_loss);
+        let quality = calibration.judge(best, played);
         let tactical_pattern = Self::detect_tactical_pattern(board, chess_move);
         let comment = Self::generate_comment(&quality, centipawn_loss, &tactical_pattern, chess_move == best_move);
 
-        MoveAnalysis {
+        Ok(MoveAnalysis {
             move_number,
             chess_move,
-            evaluation_before: eval_before.score,
-            evaluation_after: eval_after.score,
+            evaluation_before: eval_before,
+            evaluation_after: eval_after,
             best_move,
-            best_move_eval: best_move_score,
+            best_move_eval,
             quality,
             centipawn_loss,
             tactical_pattern,
             comment,
+            sacrifice: None,
+        })
+    }
+
+    /// Flag the moves in `analyses` that gave up material. `positions` holds the board before
+    /// each analysed move followed by the final one.
+    ///
+    /// A sacrifice is sound when the mover's evaluation recovers within `SACRIFICE_WINDOW` plies.
+    /// The one-ply search cannot see that compensation, so a sound sacrifice is re-scored against
+    /// the recovered evaluation instead of being counted as a blunder. A check or capture that
+    /// loses material for good is an unsound sacrifice; a quiet move that does so stays a plain
+    /// blunder.
+    pub fn mark_sacrifices(positions: &[Board], analyses: &mut [MoveAnalysis], calibration: &QualityCalibration) {
+        let evaluations: Vec<PositionEvaluation> = positions.iter().map(Evaluator::evaluate_position).collect();
+
+        for (ply, analysis) in analyses.iter_mut().enumerate() {
+            let Some((sacrifice, before, recovered)) = Self::find_sacrifice(positions, &evaluations, ply) else {
+                continue;
+            };
+            let board = &positions[ply];
+            if sacrifice.sound {
+                analysis.centipawn_loss = (before - recovered).max(0);
+                analysis.quality = calibration.judge(before, recovered);
+            } else if !Self::is_forcing(board, analysis.chess_move) {
+                continue;
+            }
+
+            analysis.tactical_pattern = TacticalPattern::Sacrifice;
+            analysis.sacrifice = Some(sacrifice);
+            analysis.comment = Self::generate_comment(
+                &analysis.quality,
+                analysis.centipawn_loss,
+                &analysis.tactical_pattern,
+                analysis.chess_move == analysis.best_move,
+            );
         }
     }
 
-    fn determine_move_quality(centipawn_loss: i32) -> MoveQuality {
-        match centipawn_loss {
-            0..=25 => MoveQuality::Brilliant,
-            26..=50 => MoveQuality::Great,
-            51..=100 => MoveQuality::Good,
-            101..=200 => MoveQuality::Inaccuracy,
-            201..=400 => MoveQuality::Mistake,
-            _ => MoveQuality::Blunder,
+    /// The material the move at `ply` gives up, with the mover's evaluation before it and the
+    /// best one within the window
+    fn find_sacrifice(positions: &[Board], evaluations: &[PositionEvaluation], ply: usize) -> Option<(Sacrifice, i32, i32)> {
+        let last = positions.len().checked_sub(1)?;
+        if ply >= last {
+            return None;
         }
+        let mover = positions[ply].side_to_move();
+        let sign = if mover == Color::White { 1 } else { -1 };
+        // Wait for the opponent's reply so an even trade is not mistaken for a sacrifice
+        let settled = (ply + 2).min(last);
+        let material = sign * (evaluations[ply].material - evaluations[settled].material);
+        if material < SACRIFICE_MATERIAL {
+            return None;
+        }
+
+        let score = |index: usize| {
+            let position = &positions[index];
+            if position.status() == BoardStatus::Checkmate {
+                return if position.side_to_move() == mover { i32::MIN / 2 } else { i32::MAX / 2 };
+            }
+            evaluations[index].score.for_side(mover)
+        };
+        let before = score(ply);
+        let best = (settled..=(ply + SACRIFICE_WINDOW).min(last)).map(score).max()?;
+        let sound = best >= before - RECOVERY_MARGIN;
+
+        Some((Sacrifice { material, sound }, before, best))
+    }
+
+    fn is_forcing(board: &Board, chess_move: ChessMove) -> bool {
+        board.piece_on(chess_move.get_dest()).is_some() || board.make_move_new(chess_move).checkers().popcnt() > 0
     }
 
     fn detect_tactical_pattern(_board: &Board, _chess_move: ChessMove) -> TacticalPattern {
@@ -220,6 +314,7 @@ impl GameAnalyzer {
 mod tests {
     use super::*;
     use chess::Square;
+    use std::str::FromStr;
 
     #[test]
     fn test_analyze_move() {
@@ -231,10 +326,56 @@ mod tests {
         assert_eq!(analysis.chess_move, chess_move);
     }
 
+    #[test]
+    fn test_centipawn_loss_for_black() {
+        // Black can take a free queen; a quiet move instead is a big loss for Black
+        let board = Board::from_str("4k3/8/8/8/8/8/3q4/3Q3K b - - 0 1").unwrap();
+        let take = GameAnalyzer::analyze_move(&board, ChessMove::new(Square::D2, Square::D1, None), 0);
+        let quiet = GameAnalyzer::analyze_move(&board, ChessMove::new(Square::E8, Square::F8, None), 0);
+
+        assert_eq!(take.centipawn_loss, 0);
+        assert!(quiet.centipawn_loss > 500);
+        assert!(take.evaluation_after.centipawns() < 0, "Black is better after winning the queen");
+    }
+
     #[test]
     fn test_move_quality_determination() {
-        assert_eq!(GameAnalyzer::determine_move_quality(10), MoveQuality::Brilliant);
-        assert_eq!(GameAnalyzer::determine_move_quality(150), MoveQuality::Inaccuracy);
-        assert_eq!(GameAnalyzer::determine_move_quality(500), MoveQuality::Blunder);
+        let calibration = QualityCalibration::default();
+        assert_eq!(calibration.judge(0, -10), MoveQuality::Brilliant);
+        assert_eq!(calibration.judge(0, -150), MoveQuality::Inaccuracy);
+        assert_eq!(calibration.judge(0, -500), MoveQuality::Blunder);
+    }
+
+    fn analyze_line(moves: &[&str]) -> Vec<MoveAnalysis> {
+        let mut positions = vec![Board::default()];
+        let mut analyses = Vec::new();
+        for (index, uci) in moves.iter().enumerate() {
+            let chess_move = chess_core::validate::parse_uci(uci).unwrap();
+            analyses.push(GameAnalyzer::analyze_move(&positions[index], chess_move, index));
+            positions.push(positions[index].make_move_new(chess_move));
+        }
+        GameAnalyzer::mark_sacrifices(&positions, &mut analyses, &QualityCalibration::default());
+        analyses
+    }
+
+    #[test]
+    fn test_sacrifices_and_blunders() {
+        // Legal's mate: 5.Nxe5 gives up the queen and mates two moves later
+        let legal = analyze_line(&[
+            "e2e4", "e7e5", "g1f3", "d7d6", "f1c4", "c8g4", "b1c3", "g7g6", "f3e5", "g4d1", "c4f7", "e8e7", "c3d5",
+        ]);
+        assert_eq!(legal[8].sacrifice, Some(Sacrifice { material: 800, sound: true }));
+        assert_eq!(legal[8].tactical_pattern, TacticalPattern::Sacrifice);
+        assert_eq!(legal[8].centipawn_loss, 0);
+        assert_ne!(legal[8].quality, MoveQuality::Blunder);
+        assert_eq!(legal.iter().filter(|a| a.sacrifice.is_some()).count(), 1);
+
+        // 3.Qxe5+ is a forcing move that never wins the queen back
+        let unsound = analyze_line(&["e2e4", "e7e5", "d1h5", "b8c6", "h5e5", "c6e5", "a2a3", "a7a6", "b2b3", "b7b6"]);
+        assert_eq!(unsound[4].sacrifice, Some(Sacrifice { material: 800, sound: false }));
+
+        // 2.Qg4 just hangs the queen
+        let blunder = analyze_line(&["e2e4", "d7d5", "d1g4", "c8g4", "a2a3", "a7a6", "b2b3", "b7b6"]);
+        assert!(blunder.iter().all(|a| a.sacrifice.is_none()));
     }
 }
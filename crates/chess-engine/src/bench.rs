@@ -0,0 +1,169 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use chess::Board;
+use serde::{Deserialize, Serialize};
+use crate::cancel::{Cancelled, CancellationToken};
+use crate::engine::{Engine, MAX_DEPTH};
+use crate::evaluator::{ENGINE_NAME, ENGINE_VERSION};
+
+/// A benchmark position with the move a correct search must find
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchPosition {
+    pub name: &'static str,
+    pub fen: &'static str,
+    /// Expected best move in UCI
+    pub best_move: &'static str,
+}
+
+/// Fixed suite so runs stay comparable; extend it only together with a note in the changelog
+pub const BENCH_SUITE: &[BenchPosition] = &[
+    BenchPosition {
+        name: "Back-rank mate",
+        fen: "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+        best_move: "a1a8",
+    },
+    BenchPosition {
+        name: "Rook and king mate",
+        fen: "6k1/8/6K1/8/8/8/8/R7 w - - 0 1",
+        best_move: "a1a8",
+    },
+    BenchPosition {
+        name: "Back-rank mate for Black",
+        fen: "q5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1",
+        best_move: "a8a1",
+    },
+    BenchPosition {
+        name: "Hanging queen",
+        fen: "4k3/8/8/3q4/8/8/8/3QK3 w - - 0 1",
+        best_move: "d1d5",
+    },
+    BenchPosition {
+        name: "Pawn takes rook",
+        fen: "4k3/8/8/3r4/4P3/8/8/4K3 w - - 0 1",
+        best_move: "e4d5",
+    },
+    BenchPosition {
+        name: "Promotion",
+        fen: "8/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+        best_move: "e7e8q",
+    },
+];
+
+/// How hard each position is searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchConfig {
+    /// Deepest iteration tried per position
+    pub max_depth: u32,
+    /// No further iteration starts once a position has used this much time
+    pub time_per_position_ms: u64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            time_per_position_ms: 2000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub name: String,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+    /// Deepest completed iteration
+    pub depth: u32,
+    pub best_move: String,
+    pub expected: String,
+    pub agrees: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub engine_name: String,
+    pub engine_version: String,
+    pub config: BenchConfig,
+    pub positions: Vec<BenchResult>,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+    /// Nodes per second over the whole suite
+    pub nps: u64,
+    /// Share of positions where the expected move was found, 0.0 to 1.0
+    pub agreement: f32,
+}
+
+/// Search one position with iterative deepening until `max_depth` or the time budget
+fn bench_position(position: &BenchPosition, config: &BenchConfig, token: &CancellationToken) -> Result<BenchResult, Cancelled> {
+    let board = Board::from_str(position.fen).expect("bench positions are valid FENs");
+    let budget = Duration::from_millis(config.time_per_position_ms);
+    let started = Instant::now();
+    let (mut nodes, mut depth, mut best_move) = (0, 0, String::new());
+
+    for iteration in 1..=config.max_depth.clamp(1, MAX_DEPTH) {
+        let engine = Engine::builder().depth(iteration).build().expect("depth within MAX_DEPTH");
+        let (ranked, searched) = engine.rank_moves_counted(&board, token)?;
+        nodes += searched;
+        depth = iteration;
+        best_move = ranked.first().map(|e| e.chess_move.to_string()).unwrap_or_default();
+        if started.elapsed() >= budget {
+            break;
+        }
+    }
+
+    Ok(BenchResult {
+        name: position.name.to_string(),
+        nodes,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        depth,
+        agrees: best_move == position.best_move,
+        best_move,
+        expected: position.best_move.to_string(),
+    })
+}
+
+/// Run `BENCH_SUITE` and summarise speed and best-move agreement
+pub fn run_bench(config: &BenchConfig, token: &CancellationToken) -> Result<BenchReport, Cancelled> {
+    let _span = tracing::debug_span!("bench", max_depth = config.max_depth).entered();
+    let started = Instant::now();
+    let positions = BENCH_SUITE
+        .iter()
+        .map(|position| bench_position(position, config, token))
+        .collect::<Result<Vec<_>, _>>()?;
+    let elapsed = started.elapsed();
+
+    let nodes = positions.iter().map(|p| p.nodes).sum();
+    let agreed = positions.iter().filter(|p| p.agrees).count();
+    Ok(BenchReport {
+        engine_name: ENGINE_NAME.to_string(),
+        engine_version: ENGINE_VERSION.to_string(),
+        config: *config,
+        nps: (nodes as f64 / elapsed.as_secs_f64().max(1e-6)) as u64,
+        agreement: agreed as f32 / positions.len().max(1) as f32,
+        nodes,
+        elapsed_ms: elapsed.as_millis() as u64,
+        positions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_suite() {
+        let config = BenchConfig {
+            max_depth: 1,
+            time_per_position_ms: 0,
+        };
+        let report = run_bench(&config, &CancellationToken::new()).unwrap();
+        assert_eq!(report.positions.len(), BENCH_SUITE.len());
+        assert!(report.positions.iter().all(|p| p.depth == 1 && p.nodes > 0));
+        assert_eq!(report.agreement, 1.0, "{:?}", report.positions);
+        assert_eq!(report.nodes, report.positions.iter().map(|p| p.nodes).sum::<u64>());
+
+        let token = CancellationToken::new();
+        token.cancel();
+        assert_eq!(run_bench(&config, &token), Err(Cancelled));
+    }
+}
@@ -0,0 +1,132 @@
+use chess::{Board, BoardStatus, ChessMove, MoveGen};
+use chess_core::to_san;
+use serde::{Deserialize, Serialize};
+use crate::cancel::{Cancelled, CancellationToken};
+use crate::evaluator::Evaluator;
+
+/// Below this evaluation (centipawns, side to move) a position counts as lost
+pub const LOST_THRESHOLD: i32 = -300;
+
+/// Opponent replies within this many centipawns of their best keep the advantage
+const GOOD_REPLY_MARGIN: i32 = 50;
+/// Natural replies at least this much worse than the best throw the advantage away
+const TRAP_MARGIN: i32 = 150;
+
+/// Resilience weights: each extra good reply makes the win easier, each trap harder
+const GOOD_REPLY_PENALTY: i32 = 20;
+const TRAP_BONUS: i32 = 40;
+const CHECK_BONUS: i32 = 25;
+const MATE_SCORE: i32 = 100_000;
+
+/// A defensive try and how hard it makes the opponent's job
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DefensiveResource {
+    pub uci: String,
+    pub san: String,
+    /// Our evaluation after the opponent's best reply, in centipawns
+    pub eval_after_best_reply: i32,
+    /// Opponent replies that keep their advantage; fewer means harder to convert
+    pub good_replies: usize,
+    /// Captures and checks that look natural but let us back in
+    pub traps: usize,
+    pub gives_check: bool,
+    /// Ranking score: raw evaluation adjusted for how narrow the opponent's path is
+    pub resilience: i32,
+}
+
+/// Searches for the most stubborn defence rather than the objectively best move.
+/// In a lost position every move loses, so it prefers moves that leave the
+/// opponent few good answers and plenty of tempting wrong ones.
+pub struct DefenseFinder;
+
+impl DefenseFinder {
+    /// The most resilient defensive move, if there is a legal move
+    pub fn find_resilient_defense(board: &Board) -> Option<DefensiveResource> {
+        Self::rank_defenses(board).into_iter().next()
+    }
+
+    /// Every legal move ranked by resilience, most stubborn first
+    pub fn rank_defenses(board: &Board) -> Vec<DefensiveResource> {
+        // A fresh token is never cancelled
+        Self::rank_defenses_cancellable(board, &CancellationToken::new()).unwrap_or_default()
+    }
+
+    /// `rank_defenses` that polls `token` between candidate moves
+    pub fn rank_defenses_cancellable(board: &Board, token: &CancellationToken) -> Result<Vec<DefensiveResource>, Cancelled> {
+        let mut resources = Vec::new();
+        for chess_move in MoveGen::new_legal(board) {
+            token.check()?;
+            resources.push(Self::assess(board, chess_move));
+        }
+        resources.sort_by_key(|r| std::cmp::Reverse(r.resilience));
+        Ok(resources)
+    }
+
+    fn assess(board: &Board, chess_move: ChessMove) -> DefensiveResource {
+        let after = board.make_move_new(chess_move);
+        let gives_check = after.checkers().popcnt() > 0;
+
+        // Our evaluation after each opponent reply; the side to move is us again
+        let replies: Vec<(ChessMove, i32)> = MoveGen::new_legal(&after)
+            .map(|reply| (reply, Evaluator::evaluate_for_side_to_move(&after.make_move_new(reply)).centipawns()))
+            .collect();
+
+        let (eval_after_best_reply, good_replies, traps) = match after.status() {
+            BoardStatus::Checkmate => (MATE_SCORE, 0, 0),
+            // Stalemate is a draw, which is the best a lost position can hope for
+            BoardStatus::Stalemate => (0, 0, 0),
+            BoardStatus::Ongoing => {
+                let best = replies.iter().map(|(_, eval)| *eval).min().unwrap_or(0);
+                let good = replies.iter().filter(|(_, eval)| *eval <= best + GOOD_REPLY_MARGIN).count();
+                let traps = replies
+                    .iter()
+                    .filter(|(reply, eval)| {
+                        let natural = after.piece_on(reply.get_dest()).is_some()
+                            || after.make_move_new(*reply).checkers().popcnt() > 0;
+                        natural && *eval >= best + TRAP_MARGIN
+                    })
+                    .count();
+                (best, good, traps)
+            }
+        };
+
+        let resilience = eval_after_best_reply - GOOD_REPLY_PENALTY * good_replies as i32
+            + TRAP_BONUS * traps as i32
+            + if gives_check { CHECK_BONUS } else { 0 };
+
+        DefensiveResource {
+            uci: chess_move.to_string(),
+            san: to_san(board, chess_move),
+            eval_after_best_reply,
+            good_replies,
+            traps,
+            gives_check,
+            resilience,
+        }
+    }
+
+    /// Whether the side to move is objectively lost by the static evaluation
+    pub fn is_lost(board: &Board) -> bool {
+        Evaluator::evaluate_for_side_to_move(board).centipawns() <= LOST_THRESHOLD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_lost_position_finds_back_rank_mate() {
+        // Black is a queen down but White's king is boxed in
+        let board = Board::from_str("3r3k/QR4pp/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+        assert!(DefenseFinder::is_lost(&board));
+
+        let best = DefenseFinder::find_resilient_defense(&board).unwrap();
+        assert_eq!(best.uci, "d8d1");
+        assert_eq!(best.eval_after_best_reply, MATE_SCORE);
+
+        let ranked = DefenseFinder::rank_defenses(&board);
+        assert!(ranked.windows(2).all(|w| w[0].resilience >= w[1].resilience));
+    }
+}
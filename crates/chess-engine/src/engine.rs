@@ -0,0 +1,237 @@
+use chess::{Board, BoardStatus, ChessMove, MoveGen};
+use chess_core::SeededRng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::cancel::{Cancelled, CancellationToken};
+use crate::evaluator::{Evaluator, MoveEvaluation, SEARCH_DEPTH};
+use crate::score::ScoreFromSideToMove;
+
+/// Deepest search the builder accepts; the evaluator is too slow for more
+pub const MAX_DEPTH: u32 = 4;
+/// Playing strength range for `EngineBuilder::elo`
+pub const MIN_ELO: u32 = 400;
+pub const MAX_ELO: u32 = 2800;
+
+/// Centipawns a full-strength engine is willing to give up per 100 Elo below `MAX_ELO`
+const MARGIN_PER_100_ELO: i32 = 10;
+const MATE_SCORE: i32 = 100_000;
+
+/// Rejected engine settings
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigError {
+    #[error("search depth must be between 1 and {MAX_DEPTH}, got {0}")]
+    Depth(u32),
+    #[error("engine Elo must be between {MIN_ELO} and {MAX_ELO}, got {0}")]
+    Elo(u32),
+    #[error("move quality thresholds must rise from 0, got {0:?}")]
+    QualityThresholds([i32; 5]),
+}
+
+/// Which of several near-equal moves the engine prefers when playing below full strength
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Personality {
+    /// Uses the whole margin: the weakest move the rating allows, or a
+    /// random one within it when the engine is seeded
+    #[default]
+    Balanced,
+    /// Checks and captures first
+    Aggressive,
+    /// Quiet moves that keep material on the board
+    Solid,
+}
+
+/// Validated engine settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineConfig {
+    pub depth: u32,
+    /// Target strength; `None` plays at full strength
+    pub elo: Option<u32>,
+    pub personality: Personality,
+    /// Makes below-strength play vary reproducibly; the same seed and
+    /// position always give the same move
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            depth: SEARCH_DEPTH,
+            elo: None,
+            personality: Personality::Balanced,
+            seed: None,
+        }
+    }
+}
+
+/// Builds an [`Engine`], checking every setting once up front
+///
+/// ```ignore
+/// let engine = Engine::builder().depth(2).elo(1200).personality(Personality::Aggressive).build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EngineBuilder {
+    config: EngineConfig,
+}
+
+impl EngineBuilder {
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.config.depth = depth;
+        self
+    }
+
+    pub fn elo(mut self, elo: u32) -> Self {
+        self.config.elo = Some(elo);
+        self
+    }
+
+    pub fn personality(mut self, personality: Personality) -> Self {
+        self.config.personality = personality;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Result<Engine, ConfigError> {
+        let config = self.config;
+        if !(1..=MAX_DEPTH).contains(&config.depth) {
+            return Err(ConfigError::Depth(config.depth));
+        }
+        if let Some(elo) = config.elo {
+            if !(MIN_ELO..=MAX_ELO).contains(&elo) {
+                return Err(ConfigError::Elo(elo));
+            }
+        }
+        Ok(Engine { config })
+    }
+}
+
+/// A configured searcher on top of [`Evaluator`]. Scores are centipawns from
+/// the side to move, as with the evaluator.
+#[derive(Debug, Clone, Default)]
+pub struct Engine {
+    config: EngineConfig,
+}
+
+impl Engine {
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::default()
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Search score of `board` for the side to move
+    pub fn evaluate(&self, board: &Board) -> ScoreFromSideToMove {
+        ScoreFromSideToMove::new(search(board, self.config.depth, -MATE_SCORE, MATE_SCORE, &mut 0))
+    }
+
+    /// Every legal move scored by a full-depth search, best first
+    pub fn rank_moves(&self, board: &Board) -> Vec<MoveEvaluation> {
+        // A fresh token is never cancelled
+        self.rank_moves_cancellable(board, &CancellationToken::new()).unwrap_or_default()
+    }
+
+    /// `rank_moves` that polls `token` between root moves
+    pub fn rank_moves_cancellable(&self, board: &Board, token: &CancellationToken) -> Result<Vec<MoveEvaluation>, Cancelled> {
+        Ok(self.rank_moves_counted(board, token)?.0)
+    }
+
+    /// `rank_moves_cancellable` that also returns the number of positions searched
+    pub fn rank_moves_counted(&self, board: &Board, token: &CancellationToken) -> Result<(Vec<MoveEvaluation>, u64), Cancelled> {
+        let mut ranked = Vec::new();
+        let mut nodes = 0;
+        for chess_move in MoveGen::new_legal(board) {
+            token.check()?;
+            let mut evaluation = Evaluator::evaluate_move(board, chess_move);
+            let reply = search(&board.make_move_new(chess_move), self.config.depth - 1, -MATE_SCORE, MATE_SCORE, &mut nodes);
+            evaluation.score = -ScoreFromSideToMove::new(reply);
+            ranked.push(evaluation);
+        }
+        ranked.sort_by_key(|e| std::cmp::Reverse(e.score));
+        Ok((ranked, nodes))
+    }
+
+    /// The move this engine plays: the best one at full strength, otherwise the
+    /// personality's pick among moves within the Elo's margin of the best
+    pub fn best_move(&self, board: &Board) -> Option<MoveEvaluation> {
+        let ranked = self.rank_moves(board);
+        let best = ranked.first()?.score;
+        let margin = self
+            .config
+            .elo
+            .map_or(0, |elo| (MAX_ELO - elo) as i32 / 100 * MARGIN_PER_100_ELO);
+
+        let playable = ranked.iter().filter(|e| best - e.score <= margin);
+        let chosen = match (self.config.personality, self.config.seed) {
+            (Personality::Balanced, Some(seed)) => {
+                let playable: Vec<&MoveEvaluation> = playable.collect();
+                let mut rng = SeededRng::derive(seed, board.get_hash());
+                Some(playable[rng.index(playable.len())])
+            }
+            (Personality::Balanced, None) => playable.min_by_key(|e| e.score),
+            (Personality::Aggressive, _) => playable.max_by_key(|e| (e.is_check || e.is_capture, e.score)),
+            (Personality::Solid, _) => playable.max_by_key(|e| (!e.is_capture && !e.is_check, e.score)),
+        };
+        chosen.cloned()
+    }
+}
+
+/// Negamax with alpha-beta, scored from the side to move in `board`. Counts every
+/// position visited in `nodes`.
+fn search(board: &Board, depth: u32, mut alpha: i32, beta: i32, nodes: &mut u64) -> i32 {
+    *nodes += 1;
+    match board.status() {
+        BoardStatus::Checkmate => return -MATE_SCORE,
+        BoardStatus::Stalemate => return 0,
+        BoardStatus::Ongoing => {}
+    }
+    if depth == 0 {
+        return Evaluator::evaluate_for_side_to_move(board).centipawns();
+    }
+
+    let moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+    for chess_move in moves {
+        let score = -search(&board.make_move_new(chess_move), depth - 1, -beta, -alpha, nodes);
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_validates_config() {
+        assert_eq!(Engine::builder().depth(0).build().unwrap_err(), ConfigError::Depth(0));
+        assert_eq!(Engine::builder().depth(MAX_DEPTH + 1).build().unwrap_err(), ConfigError::Depth(MAX_DEPTH + 1));
+        assert_eq!(Engine::builder().elo(100).build().unwrap_err(), ConfigError::Elo(100));
+
+        let engine = Engine::builder().depth(2).elo(1200).personality(Personality::Solid).build().unwrap();
+        assert_eq!(engine.config().depth, 2);
+        assert_eq!(engine.config().elo, Some(1200));
+        assert_eq!(engine.config().personality, Personality::Solid);
+        assert_eq!(Engine::default().config(), &EngineConfig::default());
+    }
+
+    #[test]
+    fn test_seeded_engine_is_reproducible() {
+        let board = Board::default();
+        let engine = Engine::builder().depth(1).elo(MIN_ELO).seed(7).build().unwrap();
+        let first = engine.best_move(&board).unwrap();
+        assert_eq!(engine.best_move(&board).unwrap().chess_move, first.chess_move);
+
+        let best = engine.rank_moves(&board)[0].score;
+        let margin = (MAX_ELO - MIN_ELO) as i32 / 100 * MARGIN_PER_100_ELO;
+        assert!(best - first.score <= margin);
+    }
+}
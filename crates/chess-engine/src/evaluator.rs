@@ -1,5 +1,17 @@
 use chess::{Board, ChessMove, Color, Piece, Square, ALL_SQUARES, MoveGen};
 use serde::{Deserialize, Serialize};
+use crate::cancel::{Cancelled, CancellationToken};
+use crate::score::{ScoreFromSideToMove, ScoreFromWhite};
+
+/// Identifies this engine in stored analyses so results from older engines stay distinguishable
+pub const ENGINE_NAME: &str = "tacticus-eval";
+/// The crate version plus an evaluation revision, bumped whenever scores or centipawn
+/// loss change meaning so stored analyses from before are re-run. Revision 2 measures
+/// scores by perspective and fixes centipawn loss for Black; revision 3 counts mobility
+/// for both sides instead of only the side to move.
+pub const ENGINE_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "+eval.3");
+/// Plies searched by `find_best_move`
+pub const SEARCH_DEPTH: u32 = 1;
 
 const PAWN_VALUE: i32 = 100;
 const KNIGHT_VALUE: i32 = 320;
@@ -8,6 +20,14 @@ const ROOK_VALUE: i32 = 500;
 const QUEEN_VALUE: i32 = 900;
 const KING_VALUE: i32 = 20000;
 
+const DOUBLED_PAWN_PENALTY: i32 = 15;
+const ISOLATED_PAWN_PENALTY: i32 = 10;
+const PAWN_SHIELD_BONUS: i32 = 12;
+const MOBILITY_BONUS: i32 = 10;
+
+/// Below this many centipawns a term is described as balanced
+const BALANCED_THRESHOLD: i32 = 20;
+
 // Piece-square tables for positional evaluation
 const PAWN_TABLE: [i32; 64] = [
     0,  0,  0,  0,  0,  0,  0,  0,
@@ -33,17 +53,53 @@ const KNIGHT_TABLE: [i32; 64] = [
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionEvaluation {
-    pub score: i32, // In centipawns (from white's perspective)
+    pub score: ScoreFromWhite,
     pub material: i32,
     pub positional: i32,
     pub mobility: i32,
+    /// Per-term breakdown, each from white's perspective
+    #[serde(default)]
+    pub breakdown: Vec<EvalTerm>,
+}
+
+/// One component of a position evaluation with a human-readable description
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalTerm {
+    pub key: String,
+    pub label: String,
+    pub score: i32,
+    pub description: String,
+}
+
+impl EvalTerm {
+    fn new(key: &str, label: &str, score: i32) -> Self {
+        let description = if score.abs() < BALANCED_THRESHOLD {
+            format!("{} is roughly balanced", label)
+        } else {
+            let side = if score > 0 { "White" } else { "Black" };
+            format!(
+                "{} is better on {} (+{:.1} pawns)",
+                side,
+                label.to_lowercase(),
+                score.abs() as f32 / 100.0
+            )
+        };
+
+        Self {
+            key: key.to_string(),
+            label: label.to_string(),
+            score,
+            description,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoveEvaluation {
     #[serde(serialize_with = "serialize_chess_move", deserialize_with = "deserialize_chess_move")]
     pub chess_move: ChessMove,
-    pub score: i32,
+    /// From the point of view of the side playing the move
+    pub score: ScoreFromSideToMove,
     pub is_capture: bool,
     pub is_check: bool,
     pub is_promotion: bool,
@@ -60,30 +116,9 @@ fn deserialize_chess_move<'de, D>(deserializer: D) -> std::result::Result<ChessM
 where
     D: serde::Deserializer<'de>,
 {
-    use std::str::FromStr;
     let s = String::deserialize(deserializer)?;
     // Parse the UCI move string (e.g., "e2e4")
-    if s.len() >= 4 {
-        let from = chess::Square::from_str(&s[0..2])
-            .map_err(|e| serde::de::Error::custom(format!("Invalid from square: {}", e)))?;
-        let to = chess::Square::from_str(&s[2..4])
-            .map_err(|e| serde::de::Error::custom(format!("Invalid to square: {}", e)))?;
-        let promotion = if s.len() == 5 {
-            let promo_char = s.chars().nth(4).unwrap();
-            Some(match promo_char.to_lowercase().next().unwrap() {
-                'q' => chess::Piece::Queen,
-                'r' => chess::Piece::Rook,
-                'b' => chess::Piece::Bishop,
-                'n' => chess::Piece::Knight,
-                _ => return Err(serde::de::Error::custom("Invalid promotion piece")),
-            })
-        } else {
-            None
-        };
-        Ok(ChessMove::new(from, to, promotion))
-    } else {
-        Err(serde::de::Error::custom("Move string too short"))
-    }
+    chess_core::validate::parse_uci(&s).map_err(serde::de::Error::custom)
 }
 
 pub struct Evaluator;
@@ -91,24 +126,33 @@ pub struct Evaluator;
 impl Evaluator {
     pub fn evaluate_position(board: &Board) -> PositionEvaluation {
         let material = Self::evaluate_material(board);
-        let positional = Self::evaluate_positional(board);
+        let pawn_structure = Self::evaluate_pawn_structure(board);
+        let king_safety = Self::evaluate_king_safety(board);
+        let piece_activity = Self::evaluate_piece_activity(board);
         let mobility = Self::evaluate_mobility(board);
 
-        let mut score = material + positional + mobility;
-
-        // Flip score if black to move (make it from side-to-move perspective)
-        if board.side_to_move() == Color::Black {
-            score = -score;
-        }
+        let positional = pawn_structure + king_safety + piece_activity;
 
         PositionEvaluation {
-            score,
+            score: ScoreFromWhite::new(material + positional + mobility),
             material,
             positional,
             mobility,
+            breakdown: vec![
+                EvalTerm::new("material", "Material", material),
+                EvalTerm::new("pawn_structure", "Pawn structure", pawn_structure),
+                EvalTerm::new("king_safety", "King safety", king_safety),
+                EvalTerm::new("mobility", "Mobility", mobility),
+                EvalTerm::new("piece_activity", "Piece activity", piece_activity),
+            ],
         }
     }
 
+    /// Static score of `board` for the side to move, as a search wants it
+    pub fn evaluate_for_side_to_move(board: &Board) -> ScoreFromSideToMove {
+        Self::evaluate_position(board).score.to_side_to_move(board.side_to_move())
+    }
+
     fn evaluate_material(board: &Board) -> i32 {
         let mut score = 0;
 
@@ -127,7 +171,7 @@ impl Evaluator {
         score
     }
 
-    fn piece_value(piece: Piece) -> i32 {
+    pub(crate) fn piece_value(piece: Piece) -> i32 {
         match piece {
             Piece::Pawn => PAWN_VALUE,
             Piece::Knight => KNIGHT_VALUE,
@@ -138,14 +182,78 @@ impl Evaluator {
         }
     }
 
-    fn evaluate_positional(board: &Board) -> i32 {
+    /// Pawn placement plus penalties for doubled and isolated pawns
+    fn evaluate_pawn_structure(board: &Board) -> i32 {
+        let mut score = 0;
+        let mut pawns_per_file = [[0i32; 8]; 2];
+
+        for square in ALL_SQUARES.iter() {
+            if board.piece_on(*square) != Some(Piece::Pawn) {
+                continue;
+            }
+            if let Some(color) = board.color_on(*square) {
+                let table_score = Self::get_piece_square_value(*square, &PAWN_TABLE, color);
+                score += if color == Color::White { table_score } else { -table_score };
+                pawns_per_file[color.to_index()][square.get_file().to_index()] += 1;
+            }
+        }
+
+        for (color, files) in [Color::White, Color::Black].iter().zip(pawns_per_file.iter()) {
+            let mut penalty = 0;
+            for file in 0..8 {
+                let count = files[file];
+                if count == 0 {
+                    continue;
+                }
+                penalty += (count - 1) * DOUBLED_PAWN_PENALTY;
+
+                let left = if file > 0 { files[file - 1] } else { 0 };
+                let right = if file < 7 { files[file + 1] } else { 0 };
+                if left == 0 && right == 0 {
+                    penalty += count * ISOLATED_PAWN_PENALTY;
+                }
+            }
+            score += if *color == Color::White { -penalty } else { penalty };
+        }
+
+        score
+    }
+
+    /// Bonus for pawns sheltering the king on its own and adjacent files
+    fn evaluate_king_safety(board: &Board) -> i32 {
+        let mut score = 0;
+
+        for color in [Color::White, Color::Black] {
+            let king = board.king_square(color);
+            let king_file = king.get_file().to_index() as i32;
+            let king_rank = king.get_rank().to_index() as i32;
+            let forward = if color == Color::White { 1 } else { -1 };
+
+            let shield = ALL_SQUARES
+                .iter()
+                .filter(|sq| board.piece_on(**sq) == Some(Piece::Pawn) && board.color_on(**sq) == Some(color))
+                .filter(|sq| {
+                    let file_diff = (sq.get_file().to_index() as i32 - king_file).abs();
+                    let rank_diff = (sq.get_rank().to_index() as i32 - king_rank) * forward;
+                    file_diff <= 1 && (1..=2).contains(&rank_diff)
+                })
+                .count() as i32;
+
+            let bonus = shield * PAWN_SHIELD_BONUS;
+            score += if color == Color::White { bonus } else { -bonus };
+        }
+
+        score
+    }
+
+    /// Piece placement for minor pieces
+    fn evaluate_piece_activity(board: &Board) -> i32 {
         let mut score = 0;
 
         for square in ALL_SQUARES.iter() {
             if let Some(piece) = board.piece_on(*square) {
                 if let Some(color) = board.color_on(*square) {
                     let table_score = match piece {
-                        Piece::Pawn => Self::get_piece_square_value(*square, &PAWN_TABLE, color),
                         Piece::Knight => Self::get_piece_square_value(*square, &KNIGHT_TABLE, color),
                         _ => 0,
                     };
@@ -170,23 +278,25 @@ impl Evaluator {
         table[index]
     }
 
+    /// White's legal moves against Black's. The side not to move is counted on a
+    /// null-move board; while the side to move is in check that board doesn't exist,
+    /// so mobility is left out.
     fn evaluate_mobility(board: &Board) -> i32 {
-        let white_moves = MoveGen::new_legal(board).len();
-
-        // Temporarily make a move for black (or use a simple heuristic)
-        // For simplicity, we'll use a basic heuristic
-        let mobility_score = white_moves as i32 * 10;
-
+        let Some(passed) = board.null_move() else {
+            return 0;
+        };
+        let to_move = MoveGen::new_legal(board).len() as i32;
+        let waiting = MoveGen::new_legal(&passed).len() as i32;
+        let score = (to_move - waiting) * MOBILITY_BONUS;
         if board.side_to_move() == Color::White {
-            mobility_score
+            score
         } else {
-            -mobility_score
+            -score
         }
     }
 
     pub fn evaluate_move(board: &Board, chess_move: ChessMove) -> MoveEvaluation {
         let new_board = board.make_move_new(chess_move);
-        let position_eval = Self::evaluate_position(&new_board);
 
         let is_capture = board.piece_on(chess_move.get_dest()).is_some();
         let is_check = new_board.checkers().popcnt() > 0;
@@ -194,7 +304,8 @@ impl Evaluator {
 
         MoveEvaluation {
             chess_move,
-            score: -position_eval.score, // Negate because it's from opponent's perspective
+            // The new position is scored for the opponent, who is to move in it
+            score: -Self::evaluate_for_side_to_move(&new_board),
             is_capture,
             is_check,
             is_promotion,
@@ -202,16 +313,27 @@ impl Evaluator {
     }
 
     pub fn find_best_move(board: &Board) -> Option<MoveEvaluation> {
-        let legal_moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+        // A fresh token is never cancelled
+        Self::find_best_move_cancellable(board, &CancellationToken::new()).unwrap_or(None)
+    }
+
+    /// `find_best_move` that polls `token` between candidate moves
+    pub fn find_best_move_cancellable(board: &Board, token: &CancellationToken) -> Result<Option<MoveEvaluation>, Cancelled> {
+        let mut best: Option<MoveEvaluation> = None;
 
-        if legal_moves.is_empty() {
-            return None;
+        for chess_move in MoveGen::new_legal(board) {
+            token.check()?;
+            let eval = Self::evaluate_move(board, chess_move);
+            // `>=` keeps the last of equally scored moves, as `max_by_key` did
+            if best.as_ref().is_none_or(|b| eval.score >= b.score) {
+                best = Some(eval);
+            }
         }
 
-        legal_moves
-            .into_iter()
-            .map(|m| Self::evaluate_move(board, m))
-            .max_by_key(|eval| eval.score)
+        if let Some(best) = &best {
+            tracing::trace!(fen = %board, best_move = %best.chess_move, score = best.score.centipawns(), "best move found");
+        }
+        Ok(best)
     }
 
     pub fn evaluate_all_moves(board: &Board) -> Vec<MoveEvaluation> {
@@ -230,13 +352,42 @@ impl Evaluator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::str::FromStr;
 
     #[test]
     fn test_evaluate_starting_position() {
         let board = Board::default();
         let eval = Evaluator::evaluate_position(&board);
         // Starting position should be roughly equal (allow wider margin for positional factors)
-        assert!(eval.score.abs() < 500, "Score was {}, expected near 0", eval.score);
+        assert!(eval.score.centipawns().abs() < 500, "Score was {:?}, expected near 0", eval.score);
+    }
+
+    #[test]
+    fn test_breakdown_terms() {
+        let board = Board::default();
+        let eval = Evaluator::evaluate_position(&board);
+        let keys: Vec<&str> = eval.breakdown.iter().map(|t| t.key.as_str()).collect();
+        assert_eq!(keys, vec!["material", "pawn_structure", "king_safety", "mobility", "piece_activity"]);
+
+        // The starting position is symmetric
+        let material = &eval.breakdown[0];
+        assert_eq!(material.score, 0);
+        assert_eq!(material.description, "Material is roughly balanced");
+    }
+
+    #[test]
+    fn test_mobility_counts_both_sides() {
+        let white_to_move = Board::default();
+        let black_to_move = white_to_move.null_move().unwrap();
+        assert_eq!(Evaluator::evaluate_position(&white_to_move).mobility, 0);
+        assert_eq!(Evaluator::evaluate_position(&black_to_move).mobility, 0);
+
+        // A rook up, White has more moves whoever is to move
+        let white = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let black = Board::from_str("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+        let mobility = Evaluator::evaluate_position(&white).mobility;
+        assert!(mobility > 0);
+        assert_eq!(Evaluator::evaluate_position(&black).mobility, mobility);
     }
 
     #[test]
@@ -245,4 +396,18 @@ mod tests {
         let best_move = Evaluator::find_best_move(&board);
         assert!(best_move.is_some());
     }
+
+    #[test]
+    fn test_score_perspective() {
+        // White is a queen up; the score favours White whoever is to move
+        let white_to_move = Board::from_str("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let black_to_move = Board::from_str("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert!(Evaluator::evaluate_position(&white_to_move).score.centipawns() > 0);
+        assert!(Evaluator::evaluate_position(&black_to_move).score.centipawns() > 0);
+        assert!(Evaluator::evaluate_for_side_to_move(&black_to_move).centipawns() < 0);
+
+        // Black's best move is scored for Black
+        let best = Evaluator::find_best_move(&black_to_move).unwrap();
+        assert!(best.score.centipawns() < 0);
+    }
 }
@@ -1,5 +1,26 @@
 pub mod evaluator;
 pub mod analyzer;
+pub mod bench;
+pub mod cancel;
+pub mod defense;
+pub mod engine;
+pub mod mate_patterns;
+pub mod quality;
+pub mod score;
+pub mod strength;
+pub mod threats;
 
-pub use evaluator::{Evaluator, MoveEvaluation, PositionEvaluation};
-pub use analyzer::{GameAnalyzer, MoveAnalysis, TacticalPattern};
+pub use evaluator::{EvalTerm, Evaluator, MoveEvaluation, PositionEvaluation, ENGINE_NAME, ENGINE_VERSION, SEARCH_DEPTH};
+pub use analyzer::{GameAnalyzer, MoveAnalysis, Sacrifice, TacticalPattern, SACRIFICE_WINDOW};
+pub use bench::{run_bench, BenchConfig, BenchPosition, BenchReport, BenchResult, BENCH_SUITE};
+pub use cancel::{Cancelled, CancellationToken};
+pub use defense::{DefenseFinder, DefensiveResource, LOST_THRESHOLD};
+pub use engine::{ConfigError, Engine, EngineBuilder, EngineConfig, Personality, MAX_DEPTH, MAX_ELO, MIN_ELO};
+pub use mate_patterns::{MatePattern, MatePatternFinder, PatternSighting};
+pub use quality::{QualityCalibration, REFERENCE_RATING};
+pub use score::{ScoreFromSideToMove, ScoreFromWhite};
+pub use strength::{
+    assess_strength, performance_rating, PuzzleOutcome, RatedPuzzle, RatingBand, StrengthAssessment, StrengthEstimate,
+    BAND_WIDTH, RATED_PUZZLES,
+};
+pub use threats::{Threat, ThreatDetector, ThreatKind, ThreatReport};
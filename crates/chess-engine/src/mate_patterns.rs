@@ -0,0 +1,276 @@
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, BitBoard, Board,
+    BoardStatus, ChessMove, Color, File, MoveGen, Piece, Square, EMPTY,
+};
+use serde::{Deserialize, Serialize};
+
+/// Named mating patterns and attacking set-ups worth drilling until they are recognised on sight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatePattern {
+    /// A knight mates a king boxed in by its own pieces
+    SmotheredMate,
+    /// A knight takes the escape squares of a king on the edge while a rook or queen mates along it
+    AnastasiaMate,
+    /// A rook mates a cornered king from beside it, guarded by a knight that also covers the escape
+    ArabianMate,
+    /// The bishop sacrifice on h7 (h2) against a castled king, followed by a knight check on g5 (g4)
+    GreekGift,
+}
+
+impl MatePattern {
+    pub const ALL: [MatePattern; 4] = [
+        MatePattern::SmotheredMate,
+        MatePattern::AnastasiaMate,
+        MatePattern::ArabianMate,
+        MatePattern::GreekGift,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MatePattern::SmotheredMate => "Smothered Mate",
+            MatePattern::AnastasiaMate => "Anastasia's Mate",
+            MatePattern::ArabianMate => "Arabian Mate",
+            MatePattern::GreekGift => "Greek Gift",
+        }
+    }
+
+    /// Id of the concept library entry that teaches the pattern
+    pub fn concept_id(self) -> &'static str {
+        match self {
+            MatePattern::SmotheredMate => "smothered_mate",
+            MatePattern::AnastasiaMate => "anastasias_mate",
+            MatePattern::ArabianMate => "arabian_mate",
+            MatePattern::GreekGift => "greek_gift",
+        }
+    }
+
+    pub fn from_concept_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.concept_id() == id)
+    }
+
+    /// A position, White to move, where the pattern is on the board
+    pub fn example_fen(self) -> &'static str {
+        match self {
+            MatePattern::SmotheredMate => "6rk/6pp/8/6N1/8/8/8/K7 w - - 0 1",
+            MatePattern::AnastasiaMate => "8/4N1pk/8/3R4/8/8/8/K7 w - - 0 1",
+            MatePattern::ArabianMate => "7k/R7/5N2/8/8/8/8/2K5 w - - 0 1",
+            MatePattern::GreekGift => "r1bq1rk1/pp1nnppp/4p3/2ppP3/3P4/2PB1N2/PP3PPP/RNBQK2R w KQ - 0 1",
+        }
+    }
+
+    /// What to look for, without giving the move away
+    pub fn hint(self) -> &'static str {
+        match self {
+            MatePattern::SmotheredMate => "The king's own pieces leave it no squares. Which piece can check without being blocked?",
+            MatePattern::AnastasiaMate => "The king is on the edge. Take away its squares next to the edge, then check along it.",
+            MatePattern::ArabianMate => "The king is in the corner. A rook next to it is mate if something guards the rook.",
+            MatePattern::GreekGift => "The h-pawn is only guarded by the king. What happens after a bishop takes it with check?",
+        }
+    }
+
+    /// Whether `board`, checkmated, shows this pattern. Always false for the Greek gift, which is an attack rather than a mate.
+    pub fn matches_mate(self, board: &Board) -> bool {
+        if board.status() != BoardStatus::Checkmate {
+            return false;
+        }
+        let defender = board.side_to_move();
+        let king = board.king_square(defender);
+        let checkers = *board.checkers();
+        if checkers.popcnt() != 1 {
+            return false;
+        }
+        let checker = checkers.to_square();
+        let checker_piece = board.piece_on(checker);
+        let attacker_knights = *board.pieces(Piece::Knight) & *board.color_combined(!defender);
+
+        match self {
+            MatePattern::SmotheredMate => {
+                checker_piece == Some(Piece::Knight)
+                    && (get_king_moves(king) & !*board.color_combined(defender)) == EMPTY
+            }
+            MatePattern::AnastasiaMate => {
+                let Some(inner) = inner_file(king) else {
+                    return false;
+                };
+                let beside = get_king_moves(king) & inner;
+                let covered_by_knight = attacker_knights.into_iter().any(|n| get_knight_moves(n) & beside != EMPTY);
+                matches!(checker_piece, Some(Piece::Rook | Piece::Queen))
+                    && checker.get_file() == king.get_file()
+                    && covered_by_knight
+                    && beside & *board.color_combined(defender) != EMPTY
+            }
+            MatePattern::ArabianMate => {
+                checker_piece == Some(Piece::Rook)
+                    && [Square::A1, Square::A8, Square::H1, Square::H8].contains(&king)
+                    && get_king_moves(king) & get_rook_moves(king, EMPTY) & BitBoard::from_square(checker) != EMPTY
+                    && attacker_knights.into_iter().any(|n| get_knight_moves(n) & BitBoard::from_square(checker) != EMPTY)
+            }
+            MatePattern::GreekGift => false,
+        }
+    }
+}
+
+/// A pattern that is there to be played in a position
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternSighting {
+    pub pattern: MatePattern,
+    /// UCI moves from the position: the mating line, or just the sacrifice for the Greek gift
+    pub line: Vec<String>,
+    /// Moves to mate for the side to move; none for the Greek gift
+    pub mate_in: Option<u8>,
+}
+
+impl PatternSighting {
+    /// The move that starts the pattern
+    pub fn first_move(&self) -> &str {
+        &self.line[0]
+    }
+}
+
+pub struct MatePatternFinder;
+
+impl MatePatternFinder {
+    /// Patterns the side to move can play: mates in one, then mates in two that
+    /// start with a check and end in the same pattern whatever the reply, then
+    /// the Greek gift. One sighting per pattern and first move.
+    pub fn find(board: &Board) -> Vec<PatternSighting> {
+        let mut sightings: Vec<PatternSighting> = Vec::new();
+        let mut add = |sighting: PatternSighting| {
+            if !sightings.iter().any(|s| s.pattern == sighting.pattern && s.first_move() == sighting.first_move()) {
+                sightings.push(sighting);
+            }
+        };
+
+        for (chess_move, pattern) in Self::mates_in_one(board) {
+            add(PatternSighting { pattern, line: vec![chess_move.to_string()], mate_in: Some(1) });
+        }
+
+        for first in MoveGen::new_legal(board) {
+            let after = board.make_move_new(first);
+            if *after.checkers() == EMPTY || after.status() != BoardStatus::Ongoing {
+                continue;
+            }
+            for pattern in MatePattern::ALL.into_iter().filter(|p| *p != MatePattern::GreekGift) {
+                if let Some(line) = Self::forced_in_two(&after, pattern) {
+                    let mut full = vec![first.to_string()];
+                    full.extend(line);
+                    add(PatternSighting { pattern, line: full, mate_in: Some(2) });
+                }
+            }
+        }
+
+        if let Some(sacrifice) = Self::greek_gift(board) {
+            add(PatternSighting { pattern: MatePattern::GreekGift, line: vec![sacrifice.to_string()], mate_in: None });
+        }
+        sightings
+    }
+
+    fn mates_in_one(board: &Board) -> Vec<(ChessMove, MatePattern)> {
+        let mut found = Vec::new();
+        for chess_move in MoveGen::new_legal(board) {
+            let after = board.make_move_new(chess_move);
+            if after.status() != BoardStatus::Checkmate {
+                continue;
+            }
+            for pattern in MatePattern::ALL {
+                if pattern.matches_mate(&after) {
+                    found.push((chess_move, pattern));
+                }
+            }
+        }
+        found
+    }
+
+    /// `board` has the defender to move; the reply and mate if every reply allows a `pattern` mate
+    fn forced_in_two(board: &Board, pattern: MatePattern) -> Option<Vec<String>> {
+        let mut line = None;
+        for reply in MoveGen::new_legal(board) {
+            let after = board.make_move_new(reply);
+            let mate = MoveGen::new_legal(&after).find(|m| pattern.matches_mate(&after.make_move_new(*m)))?;
+            line.get_or_insert_with(|| vec![reply.to_string(), mate.to_string()]);
+        }
+        line
+    }
+
+    /// Bxh7+ (Bxh2+ for Black) against a king on g8 (g1), with a knight ready
+    /// for g5 (g4) and the queen able to reach h5 (h4) once the knight has gone
+    pub fn greek_gift(board: &Board) -> Option<ChessMove> {
+        let attacker = board.side_to_move();
+        let (pawn, king, knight_to, queen_to) = match attacker {
+            Color::White => (Square::H7, Square::G8, Square::G5, Square::H5),
+            Color::Black => (Square::H2, Square::G1, Square::G4, Square::H4),
+        };
+        let own = *board.color_combined(attacker);
+        let theirs = *board.color_combined(!attacker);
+        if board.king_square(!attacker) != king
+            || board.piece_on(pawn) != Some(Piece::Pawn)
+            || theirs & BitBoard::from_square(pawn) == EMPTY
+        {
+            return None;
+        }
+
+        let bishops = *board.pieces(Piece::Bishop) & own;
+        let sacrifice = bishops
+            .into_iter()
+            .map(|b| ChessMove::new(b, pawn, None))
+            .find(|m| board.legal(*m) && *board.make_move_new(*m).checkers() != EMPTY)?;
+
+        let their_pawns = *board.pieces(Piece::Pawn) & theirs;
+        if own & BitBoard::from_square(knight_to) != EMPTY || get_pawn_attacks(knight_to, attacker, their_pawns) != EMPTY {
+            return None;
+        }
+        let knight = (*board.pieces(Piece::Knight) & own)
+            .into_iter()
+            .find(|n| get_knight_moves(*n) & BitBoard::from_square(knight_to) != EMPTY)?;
+
+        let occupied = *board.combined() ^ BitBoard::from_square(knight) ^ BitBoard::from_square(sacrifice.get_source());
+        let queen_reaches = (*board.pieces(Piece::Queen) & own).into_iter().any(|q| {
+            (get_bishop_moves(q, occupied) | get_rook_moves(q, occupied)) & BitBoard::from_square(queen_to) != EMPTY
+        });
+        (queen_reaches && own & BitBoard::from_square(queen_to) == EMPTY).then_some(sacrifice)
+    }
+}
+
+/// The file next to an edge file the king stands on, as a mask; none away from the edge
+fn inner_file(king: Square) -> Option<BitBoard> {
+    let file = match king.get_file() {
+        File::A => File::B,
+        File::H => File::G,
+        _ => return None,
+    };
+    Some(
+        chess::ALL_SQUARES
+            .into_iter()
+            .filter(|sq| sq.get_file() == file)
+            .fold(EMPTY, |mask, sq| mask | BitBoard::from_square(sq)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_finds_each_example() {
+        for pattern in MatePattern::ALL {
+            let board = Board::from_str(pattern.example_fen()).unwrap();
+            let sightings = MatePatternFinder::find(&board);
+            assert!(sightings.iter().any(|s| s.pattern == pattern), "{:?} not found in its example", pattern);
+        }
+
+        let smothered = Board::from_str(MatePattern::SmotheredMate.example_fen()).unwrap();
+        let sightings = MatePatternFinder::find(&smothered);
+        assert_eq!(sightings[0].line, vec!["g5f7"]);
+        assert_eq!(sightings[0].mate_in, Some(1));
+
+        let greek = Board::from_str(MatePattern::GreekGift.example_fen()).unwrap();
+        assert_eq!(MatePatternFinder::greek_gift(&greek).map(|m| m.to_string()).as_deref(), Some("d3h7"));
+        // Without the knight on f3 there is no follow-up
+        let no_knight = Board::from_str("r1bq1rk1/pp1nnppp/4p3/2ppP3/3P4/2PB4/PP3PPP/RNBQK2R w KQ - 0 1").unwrap();
+        assert_eq!(MatePatternFinder::greek_gift(&no_knight), None);
+
+        assert!(MatePatternFinder::find(&Board::default()).is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+use chess_core::MoveQuality;
+use serde::{Deserialize, Serialize};
+use crate::engine::ConfigError;
+
+/// Rating the default thresholds are set for
+pub const REFERENCE_RATING: u32 = 2000;
+
+/// Rating gap below `REFERENCE_RATING` over which the buckets double in width
+const RATING_SPAN: f32 = 2000.0;
+const MIN_RATING_SCALE: f32 = 0.75;
+const MAX_RATING_SCALE: f32 = 1.6;
+
+/// Judgements in bucket order; a loss beyond the last threshold is a blunder
+const QUALITIES: [MoveQuality; 5] = [
+    MoveQuality::Brilliant,
+    MoveQuality::Great,
+    MoveQuality::Good,
+    MoveQuality::Inaccuracy,
+    MoveQuality::Mistake,
+];
+
+/// How centipawn loss turns into a `MoveQuality`. The buckets widen for weaker players,
+/// and a loss in an already decided position counts for what it does to the likely result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityCalibration {
+    /// Largest loss still judged Brilliant, Great, Good, Inaccuracy and Mistake, at `REFERENCE_RATING`
+    pub thresholds: [i32; 5],
+    /// Rating of the player whose moves are judged; `None` uses the thresholds as they are
+    pub rating: Option<u32>,
+    /// Measure a loss by how much it changes the expected score, so that dropping 100cp
+    /// while eight pawns up is not a mistake
+    pub account_for_sharpness: bool,
+}
+
+impl Default for QualityCalibration {
+    fn default() -> Self {
+        Self {
+            thresholds: [25, 50, 100, 200, 400],
+            rating: None,
+            account_for_sharpness: true,
+        }
+    }
+}
+
+/// Expected score for a side this many centipawns ahead, as in the Elo formula
+fn expected_score(centipawns: i32) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-centipawns as f64 / 400.0))
+}
+
+impl QualityCalibration {
+    pub fn for_rating(rating: u32) -> Self {
+        Self {
+            rating: Some(rating),
+            ..Self::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let rising = self.thresholds.windows(2).all(|pair| pair[0] < pair[1]);
+        if self.thresholds[0] < 0 || !rising {
+            return Err(ConfigError::QualityThresholds(self.thresholds));
+        }
+        Ok(())
+    }
+
+    /// Factor the thresholds are widened by for `rating`
+    fn rating_scale(&self) -> f32 {
+        self.rating.map_or(1.0, |rating| {
+            (1.0 + (REFERENCE_RATING as f32 - rating as f32) / RATING_SPAN).clamp(MIN_RATING_SCALE, MAX_RATING_SCALE)
+        })
+    }
+
+    /// The loss between `best` and `played`, both from the mover's side. With sharpness on,
+    /// this is the loss from a level position that would lower the expected score as much.
+    pub fn effective_loss(&self, best: i32, played: i32) -> i32 {
+        let loss = (best - played).max(0);
+        if !self.account_for_sharpness {
+            return loss;
+        }
+        // Half the expected score is all a level position has to lose
+        let drop = (expected_score(best) - expected_score(played)).clamp(0.0, 0.499);
+        let level = 400.0 * ((0.5 + drop) / (0.5 - drop)).log10();
+        (level.round() as i32).min(loss)
+    }
+
+    /// Judge a move that scored `played` where `best` was available, both from the mover's side
+    pub fn judge(&self, best: i32, played: i32) -> MoveQuality {
+        let loss = self.effective_loss(best, played) as f32;
+        let scale = self.rating_scale();
+        self.thresholds
+            .iter()
+            .position(|threshold| loss <= *threshold as f32 * scale)
+            .map_or(MoveQuality::Blunder, |bucket| QUALITIES[bucket])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration() {
+        let fixed = QualityCalibration {
+            account_for_sharpness: false,
+            ..QualityCalibration::default()
+        };
+        assert_eq!(fixed.judge(0, -150), MoveQuality::Inaccuracy);
+        assert_eq!(fixed.judge(800, 700), MoveQuality::Good);
+
+        // A level position keeps roughly the raw loss; a won one barely notices 100cp
+        let sharp = QualityCalibration::default();
+        assert_eq!(sharp.effective_loss(50, -50), 100);
+        assert!(sharp.effective_loss(800, 700) < 10);
+        assert_eq!(sharp.judge(800, 700), MoveQuality::Brilliant);
+        assert_eq!(sharp.judge(0, -500), MoveQuality::Blunder);
+
+        // The same loss is a mistake for a master and an inaccuracy for a beginner
+        assert_eq!(QualityCalibration::for_rating(2400).judge(0, -250), MoveQuality::Mistake);
+        assert_eq!(QualityCalibration::for_rating(800).judge(0, -250), MoveQuality::Inaccuracy);
+
+        assert!(sharp.validate().is_ok());
+        let unordered = QualityCalibration {
+            thresholds: [25, 50, 40, 200, 400],
+            ..QualityCalibration::default()
+        };
+        assert_eq!(unordered.validate(), Err(ConfigError::QualityThresholds([25, 50, 40, 200, 400])));
+    }
+}
@@ -0,0 +1,108 @@
+//! Centipawn scores tagged with the side they are measured for. A score from White's
+//! point of view and one from the side to move only meet through an explicit conversion.
+
+use chess::Color;
+use serde::{Deserialize, Serialize};
+use std::ops::{Neg, Sub};
+
+/// Centipawns from White's point of view: positive means White is better
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScoreFromWhite(i32);
+
+/// Centipawns from the point of view of the side to move in the position the score belongs to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScoreFromSideToMove(i32);
+
+impl ScoreFromWhite {
+    pub const fn new(centipawns: i32) -> Self {
+        Self(centipawns)
+    }
+
+    pub const fn centipawns(self) -> i32 {
+        self.0
+    }
+
+    pub fn pawns(self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+
+    /// Centipawns from `side`'s point of view
+    pub fn for_side(self, side: Color) -> i32 {
+        match side {
+            Color::White => self.0,
+            Color::Black => -self.0,
+        }
+    }
+
+    /// The score for a position in which `side_to_move` is to move
+    pub fn to_side_to_move(self, side_to_move: Color) -> ScoreFromSideToMove {
+        ScoreFromSideToMove(self.for_side(side_to_move))
+    }
+}
+
+impl ScoreFromSideToMove {
+    pub const fn new(centipawns: i32) -> Self {
+        Self(centipawns)
+    }
+
+    pub const fn centipawns(self) -> i32 {
+        self.0
+    }
+
+    pub fn pawns(self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+
+    /// The score from White's point of view, given who is to move in its position
+    pub fn to_white(self, side_to_move: Color) -> ScoreFromWhite {
+        ScoreFromWhite(match side_to_move {
+            Color::White => self.0,
+            Color::Black => -self.0,
+        })
+    }
+}
+
+/// The same score for the other side, e.g. a reply's score seen from the move before it
+impl Neg for ScoreFromSideToMove {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+/// Centipawn difference between two scores for the same side
+impl Sub for ScoreFromWhite {
+    type Output = i32;
+
+    fn sub(self, other: Self) -> i32 {
+        self.0 - other.0
+    }
+}
+
+/// Centipawn difference between two scores for the same side
+impl Sub for ScoreFromSideToMove {
+    type Output = i32;
+
+    fn sub(self, other: Self) -> i32 {
+        self.0 - other.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        let white = ScoreFromWhite::new(150);
+        assert_eq!(white.for_side(Color::Black), -150);
+        assert_eq!(white.to_side_to_move(Color::Black), ScoreFromSideToMove::new(-150));
+        assert_eq!(white.to_side_to_move(Color::Black).to_white(Color::Black), white);
+        assert_eq!(white.to_side_to_move(Color::White).to_white(Color::White), white);
+        assert_eq!(-ScoreFromSideToMove::new(40), ScoreFromSideToMove::new(-40));
+        assert_eq!(ScoreFromSideToMove::new(90) - ScoreFromSideToMove::new(-10), 100);
+    }
+}
@@ -0,0 +1,305 @@
+use std::str::FromStr;
+use chess::Board;
+use serde::{Deserialize, Serialize};
+use crate::cancel::{Cancelled, CancellationToken};
+use crate::engine::Engine;
+
+/// A puzzle with a rating on the usual puzzle scale and the moves that solve it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatedPuzzle {
+    pub name: &'static str,
+    pub fen: &'static str,
+    /// Every first move that solves it, in UCI
+    pub solutions: &'static [&'static str],
+    pub rating: u32,
+}
+
+/// Fixed set, easiest first, so assessments stay comparable. Ratings are estimates of
+/// what a rated solver would need, not taken from a puzzle server.
+pub const RATED_PUZZLES: &[RatedPuzzle] = &[
+    RatedPuzzle {
+        name: "Hanging queen",
+        fen: "4k3/8/8/3q4/8/8/8/3QK3 w - - 0 1",
+        solutions: &["d1d5"],
+        rating: 600,
+    },
+    RatedPuzzle {
+        name: "Pawn takes rook",
+        fen: "4k3/8/8/3r4/4P3/8/8/4K3 w - - 0 1",
+        solutions: &["e4d5"],
+        rating: 650,
+    },
+    RatedPuzzle {
+        name: "Free pawn",
+        fen: "4k3/8/8/3p4/8/8/8/3RK3 w - - 0 1",
+        solutions: &["d1d5"],
+        rating: 700,
+    },
+    RatedPuzzle {
+        name: "Back-rank mate",
+        fen: "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+        solutions: &["a1a8"],
+        rating: 700,
+    },
+    RatedPuzzle {
+        name: "Back-rank mate for Black",
+        fen: "q5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1",
+        solutions: &["a8a1"],
+        rating: 750,
+    },
+    RatedPuzzle {
+        name: "Promotion",
+        fen: "8/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+        solutions: &["e7e8q"],
+        rating: 800,
+    },
+    RatedPuzzle {
+        name: "Rook and king mate",
+        fen: "6k1/8/6K1/8/8/8/8/R7 w - - 0 1",
+        solutions: &["a1a8"],
+        rating: 850,
+    },
+    RatedPuzzle {
+        name: "Smothered mate",
+        fen: "6rk/6pp/8/4N3/8/8/8/6K1 w - - 0 1",
+        solutions: &["e5f7"],
+        rating: 900,
+    },
+    RatedPuzzle {
+        name: "Knight fork",
+        fen: "q3k3/8/8/1N6/8/8/8/6K1 w - - 0 1",
+        solutions: &["b5c7"],
+        rating: 1000,
+    },
+    RatedPuzzle {
+        name: "Queen fork",
+        fen: "r5k1/8/8/8/8/8/8/3Q2K1 w - - 0 1",
+        solutions: &["d1d5"],
+        rating: 1100,
+    },
+    RatedPuzzle {
+        name: "Two-rook ladder",
+        fen: "6k1/8/8/8/8/8/8/RR4K1 w - - 0 1",
+        solutions: &["a1a7", "b1b7"],
+        rating: 1150,
+    },
+    RatedPuzzle {
+        name: "Skewer",
+        fen: "7R/8/8/8/q3k3/8/8/7K w - - 0 1",
+        solutions: &["h8h4"],
+        rating: 1200,
+    },
+    RatedPuzzle {
+        name: "Doubled rooks on the back rank",
+        fen: "3r2k1/5ppp/8/8/8/8/4R3/4R1K1 w - - 0 1",
+        solutions: &["e2e8"],
+        rating: 1300,
+    },
+    RatedPuzzle {
+        name: "Attack the pinned knight",
+        fen: "4k3/1p6/2n5/1B6/3P4/8/8/6K1 w - - 0 1",
+        solutions: &["d4d5"],
+        rating: 1350,
+    },
+    RatedPuzzle {
+        name: "Queen sacrifice on the back rank",
+        fen: "2r3k1/5ppp/8/3Q4/8/8/8/3R2K1 w - - 0 1",
+        solutions: &["d5d8"],
+        rating: 1500,
+    },
+    RatedPuzzle {
+        name: "Knight underpromotion",
+        fen: "8/2q1P1k1/8/8/8/8/8/7K w - - 0 1",
+        solutions: &["e7e8n"],
+        rating: 1600,
+    },
+    RatedPuzzle {
+        name: "Battery mate in three",
+        fen: "1rr3k1/5ppp/8/3Q4/8/8/3R4/3R2K1 w - - 0 1",
+        solutions: &["d5d8"],
+        rating: 1800,
+    },
+];
+
+/// Width of the rating bands results are grouped into
+pub const BAND_WIDTH: u32 = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleOutcome {
+    pub name: String,
+    pub rating: u32,
+    /// Move the engine played, in UCI; empty when it had none
+    pub played: String,
+    pub solved: bool,
+}
+
+/// Puzzles rated from `from` up to, not including, `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatingBand {
+    pub from: u32,
+    pub to: u32,
+    pub attempted: usize,
+    pub solved: usize,
+}
+
+/// Puzzle rating the engine would score 50% against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StrengthEstimate {
+    /// Solved nothing, so only known to be below the easiest puzzle
+    Below { rating: u32 },
+    Rated { rating: u32 },
+    /// Solved everything, so only known to be above the hardest puzzle
+    Above { rating: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrengthAssessment {
+    /// Elo setting assessed; `None` is full strength
+    pub elo: Option<u32>,
+    pub depth: u32,
+    pub puzzles: Vec<PuzzleOutcome>,
+    /// Results per `BAND_WIDTH` of puzzle rating, lowest first, bands without puzzles left out
+    pub bands: Vec<RatingBand>,
+    pub solved: usize,
+    pub estimate: StrengthEstimate,
+    /// Band holding a `Rated` estimate: where the engine scores about half
+    pub fifty_percent_band: Option<RatingBand>,
+}
+
+/// Expected share of a puzzle rated `puzzle` that a solver rated `solver` gets right
+fn expected_score(solver: f64, puzzle: u32) -> f64 {
+    1.0 / (1.0 + 10f64.powf((puzzle as f64 - solver) / 400.0))
+}
+
+/// Rating at which the expected score over `ratings` equals `solved`
+pub fn performance_rating(ratings: &[u32], solved: usize) -> StrengthEstimate {
+    let lowest = ratings.iter().copied().min().unwrap_or(0);
+    let highest = ratings.iter().copied().max().unwrap_or(0);
+    if solved == 0 {
+        return StrengthEstimate::Below { rating: lowest };
+    }
+    if solved >= ratings.len() {
+        return StrengthEstimate::Above { rating: highest };
+    }
+
+    // The expected score rises with the rating, so bisect for the one that matches
+    let expected = |solver: f64| ratings.iter().map(|&puzzle| expected_score(solver, puzzle)).sum::<f64>();
+    let (mut low, mut high) = (lowest as f64 - 1200.0, highest as f64 + 1200.0);
+    for _ in 0..60 {
+        let mid = (low + high) / 2.0;
+        if expected(mid) < solved as f64 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    StrengthEstimate::Rated { rating: ((low + high) / 2.0).round().max(0.0) as u32 }
+}
+
+fn band_start(rating: u32) -> u32 {
+    rating / BAND_WIDTH * BAND_WIDTH
+}
+
+/// Play `engine` on every puzzle in `RATED_PUZZLES` and estimate its puzzle rating
+pub fn assess_strength(engine: &Engine, token: &CancellationToken) -> Result<StrengthAssessment, Cancelled> {
+    let config = engine.config();
+    let _span = tracing::debug_span!("assess_strength", elo = ?config.elo, depth = config.depth).entered();
+
+    let mut puzzles = Vec::with_capacity(RATED_PUZZLES.len());
+    for puzzle in RATED_PUZZLES {
+        token.check()?;
+        let board = Board::from_str(puzzle.fen).expect("rated puzzles are valid FENs");
+        let played = engine.best_move(&board).map(|e| e.chess_move.to_string()).unwrap_or_default();
+        puzzles.push(PuzzleOutcome {
+            name: puzzle.name.to_string(),
+            rating: puzzle.rating,
+            solved: puzzle.solutions.contains(&played.as_str()),
+            played,
+        });
+    }
+
+    let mut bands: Vec<RatingBand> = Vec::new();
+    for outcome in &puzzles {
+        let from = band_start(outcome.rating);
+        let band = match bands.iter_mut().find(|b| b.from == from) {
+            Some(band) => band,
+            None => {
+                bands.push(RatingBand { from, to: from + BAND_WIDTH, attempted: 0, solved: 0 });
+                bands.last_mut().expect("just pushed")
+            }
+        };
+        band.attempted += 1;
+        band.solved += usize::from(outcome.solved);
+    }
+    bands.sort_by_key(|b| b.from);
+
+    let ratings: Vec<u32> = puzzles.iter().map(|p| p.rating).collect();
+    let solved = puzzles.iter().filter(|p| p.solved).count();
+    let estimate = performance_rating(&ratings, solved);
+    let fifty_percent_band = match estimate {
+        StrengthEstimate::Rated { rating } => {
+            let from = band_start(rating);
+            let empty = RatingBand { from, to: from + BAND_WIDTH, attempted: 0, solved: 0 };
+            Some(bands.iter().copied().find(|b| b.from == from).unwrap_or(empty))
+        }
+        _ => None,
+    };
+
+    Ok(StrengthAssessment {
+        elo: config.elo,
+        depth: config.depth,
+        puzzles,
+        bands,
+        solved,
+        estimate,
+        fifty_percent_band,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::MoveGen;
+
+    #[test]
+    fn test_puzzles_are_legal_and_full_strength_finds_the_shallow_ones() {
+        for puzzle in RATED_PUZZLES {
+            let board = Board::from_str(puzzle.fen).unwrap();
+            let legal: Vec<String> = MoveGen::new_legal(&board).map(|m| m.to_string()).collect();
+            for solution in puzzle.solutions {
+                assert!(legal.contains(&solution.to_string()), "{}: {} is not legal", puzzle.name, solution);
+            }
+        }
+        assert!(RATED_PUZZLES.windows(2).all(|w| w[0].rating <= w[1].rating));
+
+        let engine = Engine::builder().depth(3).build().unwrap();
+        let assessment = assess_strength(&engine, &CancellationToken::new()).unwrap();
+        assert_eq!(assessment.puzzles.len(), RATED_PUZZLES.len());
+        // Everything up to mate in two is within three plies
+        for outcome in assessment.puzzles.iter().filter(|p| p.rating <= 1600) {
+            assert!(outcome.solved, "{} not solved: played {}", outcome.name, outcome.played);
+        }
+        assert_eq!(assessment.bands.iter().map(|b| b.attempted).sum::<usize>(), RATED_PUZZLES.len());
+
+        let token = CancellationToken::new();
+        token.cancel();
+        assert_eq!(assess_strength(&engine, &token), Err(Cancelled));
+    }
+
+    #[test]
+    fn test_performance_rating_meets_the_solve_count() {
+        let ratings = [800, 1000, 1200, 1400, 1600];
+        assert_eq!(performance_rating(&ratings, 0), StrengthEstimate::Below { rating: 800 });
+        assert_eq!(performance_rating(&ratings, 5), StrengthEstimate::Above { rating: 1600 });
+        // Half of a set symmetric around 1200 is a 1200 performance
+        let StrengthEstimate::Rated { rating } = performance_rating(&[1000, 1400], 1) else { panic!() };
+        assert_eq!(rating, 1200);
+        let (StrengthEstimate::Rated { rating: weaker }, StrengthEstimate::Rated { rating: stronger }) =
+            (performance_rating(&ratings, 2), performance_rating(&ratings, 4))
+        else {
+            panic!()
+        };
+        assert!(weaker < stronger);
+    }
+}
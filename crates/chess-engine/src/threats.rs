@@ -0,0 +1,250 @@
+use chess::{Board, BoardStatus, ChessMove, Color, MoveGen, Piece, Square, EMPTY};
+use chess_core::bitboards::{attackers_of, attacks_from};
+use chess_core::notation::piece_name;
+use chess_core::to_san;
+use serde::{Deserialize, Serialize};
+use crate::evaluator::Evaluator;
+
+/// Opponent replies listed in a threat report
+pub const BEST_REPLIES: usize = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreatKind {
+    /// The side to move is already in check
+    Check,
+    /// The opponent could mate next move
+    MateThreat,
+    /// A piece that can be taken for free or by something cheaper
+    HangingPiece,
+    /// One opponent move would attack two valuable targets at once
+    Fork,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Threat {
+    pub kind: ThreatKind,
+    /// Squares involved, e.g. the hanging piece or the forked targets
+    pub squares: Vec<String>,
+    /// Opponent moves that carry the threat out, in SAN
+    pub moves: Vec<String>,
+    pub description: String,
+}
+
+/// What the opponent would do if it were their move. Only describes the
+/// opponent's ideas, never the side to move's best reply.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThreatReport {
+    /// "White" or "Black"
+    pub opponent: String,
+    pub in_check: bool,
+    /// The opponent's strongest moves in SAN, best first. Empty when in check.
+    pub best_replies: Vec<String>,
+    pub threats: Vec<Threat>,
+}
+
+impl ThreatReport {
+    /// Plain-text summary, one threat per line
+    pub fn summary(&self) -> String {
+        if self.threats.is_empty() {
+            let mut summary = format!("{} has no immediate threats.", self.opponent);
+            if !self.best_replies.is_empty() {
+                summary.push_str(&format!(" Their most active idea is {}.", self.best_replies[0]));
+            }
+            return summary;
+        }
+        self.threats.iter().map(|t| t.description.clone()).collect::<Vec<_>>().join("\n")
+    }
+}
+
+pub struct ThreatDetector;
+
+impl ThreatDetector {
+    /// Threats against the side to move in `board`
+    pub fn get_threats(board: &Board) -> ThreatReport {
+        let player = board.side_to_move();
+        let opponent = !player;
+        let in_check = board.checkers().popcnt() > 0;
+        let mut threats = Vec::new();
+
+        if in_check {
+            let squares: Vec<Square> = (*board.checkers()).collect();
+            threats.push(Threat {
+                kind: ThreatKind::Check,
+                squares: squares.iter().map(|s| s.to_string()).collect(),
+                moves: Vec::new(),
+                description: format!("You are in check from {}.", describe_pieces(board, &squares)),
+            });
+        }
+
+        threats.extend(Self::hanging_pieces(board, player));
+
+        // Let the opponent move twice to see what they are threatening
+        let mut best_replies = Vec::new();
+        if let Some(passed) = board.null_move() {
+            threats.extend(Self::mate_threats(&passed));
+            threats.extend(Self::forks(&passed, player));
+            best_replies = Evaluator::evaluate_all_moves(&passed)
+                .into_iter()
+                .take(BEST_REPLIES)
+                .map(|e| to_san(&passed, e.chess_move))
+                .collect();
+        }
+
+        ThreatReport {
+            opponent: color_name(opponent).to_string(),
+            in_check,
+            best_replies,
+            threats,
+        }
+    }
+
+    fn hanging_pieces(board: &Board, player: Color) -> Vec<Threat> {
+        let mut threats = Vec::new();
+        for square in *board.color_combined(player) {
+            let Some(piece) = board.piece_on(square) else { continue };
+            if piece == Piece::King {
+                continue;
+            }
+            let attackers = attackers_of(board, square, !player);
+            let Some(cheapest) = attackers.filter_map(|s| board.piece_on(s)).min_by_key(|p| Evaluator::piece_value(*p)) else {
+                continue;
+            };
+            let defended = attackers_of(board, square, player) != EMPTY;
+            if defended && Evaluator::piece_value(cheapest) >= Evaluator::piece_value(piece) {
+                continue;
+            }
+
+            let reason = if defended {
+                format!("can be won by a {}", piece_name(cheapest).to_lowercase())
+            } else {
+                "is undefended".to_string()
+            };
+            threats.push(Threat {
+                kind: ThreatKind::HangingPiece,
+                squares: vec![square.to_string()],
+                moves: attackers
+                    .iter()
+                    .map(|from| to_san_for_opponent(board, ChessMove::new(*from, square, None)))
+                    .collect(),
+                description: format!(
+                    "Your {} on {} is attacked by {} and {}.",
+                    piece_name(piece).to_lowercase(),
+                    square,
+                    describe_pieces(board, &attackers),
+                    reason
+                ),
+            });
+        }
+        threats
+    }
+
+    /// `passed` has the opponent to move
+    fn mate_threats(passed: &Board) -> Vec<Threat> {
+        let mates: Vec<ChessMove> = MoveGen::new_legal(passed)
+            .filter(|m| passed.make_move_new(*m).status() == BoardStatus::Checkmate)
+            .collect();
+        if mates.is_empty() {
+            return Vec::new();
+        }
+        vec![Threat {
+            kind: ThreatKind::MateThreat,
+            squares: mates.iter().map(|m| m.get_dest().to_string()).collect(),
+            moves: mates.iter().map(|m| to_san(passed, *m)).collect(),
+            description: format!("{} is threatening checkmate.", color_name(passed.side_to_move())),
+        }]
+    }
+
+    /// `passed` has the opponent to move; targets belong to `player`
+    fn forks(passed: &Board, player: Color) -> Vec<Threat> {
+        let mut threats = Vec::new();
+        for chess_move in MoveGen::new_legal(passed) {
+            let Some(mover) = passed.piece_on(chess_move.get_source()) else { continue };
+            let after = passed.make_move_new(chess_move);
+            let dest = chess_move.get_dest();
+            // A fork that just loses the forking piece isn't a threat
+            if attackers_of(&after, dest, player) != EMPTY {
+                continue;
+            }
+
+            let targets: Vec<Square> = attacks_from(&after, dest)
+                .filter(|s| after.color_on(*s) == Some(player))
+                .filter(|s| {
+                    after.piece_on(*s).is_some_and(|p| {
+                        p == Piece::King || Evaluator::piece_value(p) > Evaluator::piece_value(mover)
+                    })
+                })
+                .collect();
+            if targets.len() < 2 {
+                continue;
+            }
+
+            let san = to_san(passed, chess_move);
+            threats.push(Threat {
+                kind: ThreatKind::Fork,
+                squares: targets.iter().map(|s| s.to_string()).collect(),
+                description: format!(
+                    "{} would fork {} with its {}.",
+                    san,
+                    describe_pieces(&after, &targets),
+                    piece_name(mover).to_lowercase()
+                ),
+                moves: vec![san],
+            });
+        }
+        threats
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "White",
+        Color::Black => "Black",
+    }
+}
+
+/// "the bishop on b7 and the pawn on e5"
+fn describe_pieces(board: &Board, squares: &[Square]) -> String {
+    squares
+        .iter()
+        .filter_map(|s| board.piece_on(*s).map(|p| format!("the {} on {}", piece_name(p).to_lowercase(), s)))
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// SAN for a capture the opponent could make if it were their move
+fn to_san_for_opponent(board: &Board, chess_move: ChessMove) -> String {
+    match board.null_move() {
+        Some(passed) if passed.legal(chess_move) => to_san(&passed, chess_move),
+        _ => chess_move.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_mate_threat() {
+        // Queen and bishop both aim at f7
+        let board = Board::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR b KQkq - 3 3").unwrap();
+        let report = ThreatDetector::get_threats(&board);
+
+        assert_eq!(report.opponent, "White");
+        assert!(!report.in_check);
+        assert!(report.threats.iter().any(|t| t.kind == ThreatKind::MateThreat && t.moves == vec!["Qxf7#"]));
+        assert!(report.best_replies.len() <= BEST_REPLIES);
+    }
+
+    #[test]
+    fn test_undefended_piece_is_hanging() {
+        let board = Board::from_str("4k3/8/8/3n4/8/8/3R4/4K3 b - - 0 1").unwrap();
+        let report = ThreatDetector::get_threats(&board);
+
+        let hanging: Vec<&Threat> = report.threats.iter().filter(|t| t.kind == ThreatKind::HangingPiece).collect();
+        assert_eq!(hanging.len(), 1);
+        assert_eq!(hanging[0].squares, vec!["d5"]);
+        assert_eq!(hanging[0].description, "Your knight on d5 is attacked by the rook on d2 and is undefended.");
+    }
+}
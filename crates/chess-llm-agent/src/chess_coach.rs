@@ -3,9 +3,10 @@ use chess_engine::{GameAnalyzer, MoveAnalysis};
 use chess_ai::{PlayerProfile, PlayStyle};
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
-use crate::openrouter::{OpenRouterClient, ChatMessage};
+use crate::openrouter::OpenRouterClient;
 use crate::prompts::ChessCoachPrompts;
 use crate::conversation::ConversationManager;
+use crate::routing::{ModelRouter, TaskType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoachFeedback {
@@ -54,28 +55,114 @@ pub struct PlayerStats {
     pub recent_progress: String,
 }
 
+/// Voice the coach speaks in, chosen once when the coach is built
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoachPersonality {
+    #[default]
+    Standard,
+    /// Simpler vocabulary and shorter answers for a child profile
+    Kid,
+    /// Extra guidance appended to the standard prompt
+    Custom(String),
+}
+
+impl CoachPersonality {
+    pub fn system_prompt(&self) -> String {
+        match self {
+            CoachPersonality::Standard => ChessCoachPrompts::system_prompt(),
+            CoachPersonality::Kid => ChessCoachPrompts::kid_system_prompt(),
+            CoachPersonality::Custom(guidance) => format!("{}\n\n{}", ChessCoachPrompts::system_prompt(), guidance),
+        }
+    }
+}
+
 /// Chess coach powered by LLM with tool-calling capabilities
 pub struct ChessCoach {
     client: OpenRouterClient,
-    model: String,
+    router: ModelRouter,
+    personality: CoachPersonality,
+}
+
+/// Builds a [`ChessCoach`]. Without a client, one is configured from the environment.
+///
+/// ```ignore
+/// let coach = ChessCoach::builder().client(client).model("openai/gpt-4o-mini").build()?;
+/// ```
+#[derive(Default)]
+pub struct ChessCoachBuilder {
+    client: Option<OpenRouterClient>,
+    router: Option<ModelRouter>,
+    personality: CoachPersonality,
+}
+
+impl ChessCoachBuilder {
+    pub fn client(mut self, client: OpenRouterClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Use `model` for every task, with no fallback
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.router = Some(ModelRouter::single(model));
+        self
+    }
+
+    /// Pick models per task; defaults to a fast model for quick replies and a strong one for analysis
+    pub fn router(mut self, router: ModelRouter) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    pub fn personality(mut self, personality: CoachPersonality) -> Self {
+        self.personality = personality;
+        self
+    }
+
+    pub fn build(self) -> Result<ChessCoach> {
+        let router = self.router.unwrap_or_default();
+        for task in TaskType::ALL {
+            let models = router.candidates(task);
+            if models.is_empty() {
+                anyhow::bail!("No models configured for {}", task.as_str());
+            }
+            if models.iter().any(|m| m.trim().is_empty()) {
+                anyhow::bail!("Empty model name configured for {}", task.as_str());
+            }
+        }
+        if matches!(&self.personality, CoachPersonality::Custom(g) if g.trim().is_empty()) {
+            anyhow::bail!("Custom personality needs some guidance");
+        }
+
+        let client = match self.client {
+            Some(client) => client,
+            None => OpenRouterClient::new()?,
+        };
+        Ok(ChessCoach {
+            client,
+            router,
+            personality: self.personality,
+        })
+    }
 }
 
 impl ChessCoach {
+    pub fn builder() -> ChessCoachBuilder {
+        ChessCoachBuilder::default()
+    }
+
+    /// Coach with default models and personality
     pub fn new(client: OpenRouterClient) -> Self {
         Self {
             client,
-            model: "anthropic/claude-3.5-sonnet".to_string(), // High-quality model for coaching
+            router: ModelRouter::default(),
+            personality: CoachPersonality::default(),
         }
     }
 
-    pub fn with_model(mut self, model: impl Into<String>) -> Self {
-        self.model = model.into();
-        self
-    }
-
     /// Start a new coaching session
-    pub fn start_session(player_id: u64, profile: &PlayerProfile) -> CoachingSession {
-        let system_prompt = ChessCoachPrompts::system_prompt();
+    pub fn start_session(&self, player_id: u64, profile: &PlayerProfile) -> CoachingSession {
+        let system_prompt = self.personality.system_prompt();
         let conversation = ConversationManager::new(system_prompt);
 
         CoachingSession {
@@ -88,7 +175,7 @@ impl ChessCoach {
                     rating: profile.estimated_rating,
                     games_played: profile.games_played,
                     win_rate: 0.0, // Calculate from games
-                    play_style: format!("{:?}", profile.play_style),
+                    play_style: profile.play_style.to_string(),
                     top_weaknesses: profile.weaknesses.clone(),
                     recent_progress: "Just starting out".to_string(),
                 },
@@ -126,8 +213,7 @@ impl ChessCoach {
         session.conversation.add_user_message(prompt);
 
         // Get LLM response
-        let messages = session.conversation.get_chat_messages();
-        let response = self.client.simple_chat(&self.model, messages).await?;
+        let response = self.router.chat_conversation(&self.client, TaskType::GameAnalysis, &session.conversation).await?;
 
         // Add response to conversation
         session.conversation.add_assistant_message(response.clone());
@@ -147,12 +233,12 @@ impl ChessCoach {
             profile.style_characteristics.aggression_score,
             profile.style_characteristics.tactical_score,
             profile.style_characteristics.positional_score,
+            &profile.style_characteristics.sacrifices,
             games_count,
         );
 
         session.conversation.add_user_message(prompt);
-        let messages = session.conversation.get_chat_messages();
-        let response = self.client.simple_chat(&self.model, messages).await?;
+        let response = self.router.chat_conversation(&self.client, TaskType::PlaystyleAnalysis, &session.conversation).await?;
         session.conversation.add_assistant_message(response.clone());
 
         Ok(response)
@@ -169,8 +255,7 @@ impl ChessCoach {
         let prompt = ChessCoachPrompts::exercise_hint_prompt(position_fen, exercise_goal, hint_level);
 
         session.conversation.add_user_message(prompt);
-        let messages = session.conversation.get_chat_messages();
-        let response = self.client.simple_chat(&self.model, messages).await?;
+        let response = self.router.chat_conversation(&self.client, TaskType::Hint, &session.conversation).await?;
         session.conversation.add_assistant_message(response.clone());
 
         Ok(response)
@@ -192,8 +277,7 @@ impl ChessCoach {
         );
 
         session.conversation.add_user_message(prompt);
-        let messages = session.conversation.get_chat_messages();
-        let response = self.client.simple_chat(&self.model, messages).await?;
+        let response = self.router.chat_conversation(&self.client, TaskType::TrainingPlan, &session.conversation).await?;
         session.conversation.add_assistant_message(response.clone());
 
         Ok(response)
@@ -206,28 +290,12 @@ impl ChessCoach {
         user_message: &str,
     ) -> Result<String> {
         session.conversation.add_user_message(user_message);
-        let messages = session.conversation.get_chat_messages();
-        let response = self.client.simple_chat(&self.model, messages).await?;
+        let response = self.router.chat_conversation(&self.client, TaskType::Chat, &session.conversation).await?;
         session.conversation.add_assistant_message(response.clone());
 
         Ok(response)
     }
 
-    /// Provide encouragement based on context
-    pub async fn encourage(
-        &self,
-        context: &str,
-    ) -> Result<String> {
-        let prompt = ChessCoachPrompts::encouragement_prompt(context);
-        let messages = vec![
-            ChatMessage::system(ChessCoachPrompts::system_prompt()),
-            ChatMessage::user(prompt),
-        ];
-
-        let response = self.client.simple_chat(&self.model, messages).await?;
-        Ok(response)
-    }
-
     // Helper methods
 
     fn build_move_quality_summary(&self, analyses: &[MoveAnalysis]) -> String {
@@ -276,7 +344,7 @@ impl ChessCoach {
                 color,
                 analysis.chess_move,
                 quality_symbol,
-                analysis.evaluation_after,
+                analysis.evaluation_after.centipawns(),
                 analysis.centipawn_loss
             ));
         }
@@ -285,6 +353,33 @@ impl ChessCoach {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> OpenRouterClient {
+        OpenRouterClient::builder().api_key("test-key").build().unwrap()
+    }
+
+    #[test]
+    fn test_builder_validates_config() {
+        assert!(ChessCoach::builder().client(client()).model("").build().is_err());
+        assert!(ChessCoach::builder().client(client()).router(ModelRouter::new(vec![], vec!["strong".into()])).build().is_err());
+        assert!(ChessCoach::builder()
+            .client(client())
+            .personality(CoachPersonality::Custom(" ".into()))
+            .build()
+            .is_err());
+
+        let coach = ChessCoach::builder()
+            .client(client())
+            .personality(CoachPersonality::Kid)
+            .build()
+            .unwrap();
+        assert_eq!(coach.personality.system_prompt(), ChessCoachPrompts::kid_system_prompt());
+    }
+}
+
 // UUID helper (simple implementation)
 mod uuid {
     use std::time::{SystemTime, UNIX_EPOCH};
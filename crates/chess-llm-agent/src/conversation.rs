@@ -1,5 +1,72 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use crate::openrouter::ChatMessage;
+use crate::routing::context_length;
+
+/// Tokens a chat API adds per message for the role and separators
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Longest the running summary of dropped turns may grow
+pub const SUMMARY_MAX_TOKENS: usize = 400;
+/// Opens the system message carrying the summary
+const SUMMARY_HEADER: &str = "Earlier in this conversation:\n";
+/// Characters of each dropped turn kept in the summary
+const SUMMARY_LINE_CHARS: usize = 160;
+
+/// Rough token count for `text`: about four characters per token for English
+/// prose, which errs high for the move lists and FENs coaching prompts carry
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn message_tokens(content: &str) -> usize {
+    estimate_tokens(content) + MESSAGE_OVERHEAD_TOKENS
+}
+
+/// `text` cut to fit `tokens` (overhead included), keeping its start
+fn truncate_to_tokens(text: &str, tokens: usize) -> String {
+    let chars = tokens.saturating_sub(MESSAGE_OVERHEAD_TOKENS) * 4;
+    if text.chars().count() <= chars {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(chars.saturating_sub(3)).collect();
+    cut.push_str("...");
+    cut
+}
+
+/// `content` cut to what is left of the budget, which it then uses up; `None` once nothing fits
+fn take_within(content: &str, remaining: &mut usize) -> Option<String> {
+    if *remaining <= MESSAGE_OVERHEAD_TOKENS {
+        return None;
+    }
+    let content = truncate_to_tokens(content, *remaining);
+    *remaining -= message_tokens(&content).min(*remaining);
+    Some(content)
+}
+
+/// One summary line for a dropped turn
+fn summary_line(message: &Message) -> String {
+    let speaker = match message.role.as_str() {
+        "user" => "Player",
+        "assistant" => "Coach",
+        _ => "Note",
+    };
+    let text = message.content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut line: String = text.chars().take(SUMMARY_LINE_CHARS).collect();
+    if text.chars().count() > SUMMARY_LINE_CHARS {
+        line.push_str("...");
+    }
+    format!("- {}: {}", speaker, line)
+}
+
+/// `summary` with `lines` appended, dropping its oldest lines to stay under `max_tokens`
+fn extend_summary(summary: Option<&str>, lines: impl IntoIterator<Item = String>, max_tokens: usize) -> Option<String> {
+    let mut all: Vec<String> = summary.into_iter().flat_map(str::lines).map(str::to_string).collect();
+    all.extend(lines);
+    while !all.is_empty() && message_tokens(&all.join("\n")) > max_tokens {
+        all.remove(0);
+    }
+    (!all.is_empty()).then(|| all.join("\n"))
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -38,6 +105,9 @@ impl Message {
 pub struct ConversationManager {
     messages: Vec<Message>,
     max_history: usize,
+    /// Condensed record of turns trimmed from `messages`
+    #[serde(default)]
+    summary: Option<String>,
 }
 
 impl ConversationManager {
@@ -45,6 +115,7 @@ impl ConversationManager {
         let mut manager = Self {
             messages: Vec::new(),
             max_history: 20, // Keep last 20 messages for context
+            summary: None,
         };
         manager.add_system_message(system_prompt);
         manager
@@ -78,23 +149,90 @@ impl ConversationManager {
             .collect()
     }
 
+    /// Summary of turns no longer kept in full, if any were trimmed
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    /// Replace the running summary, e.g. with one the model wrote
+    pub fn set_summary(&mut self, summary: impl Into<String>) {
+        self.summary = Some(summary.into());
+    }
+
+    /// Estimated tokens for sending the whole conversation as is
+    pub fn estimated_tokens(&self) -> usize {
+        self.messages.iter().map(|m| message_tokens(&m.content)).sum::<usize>()
+            + self.summary.as_deref().map_or(0, message_tokens)
+    }
+
+    /// Messages to send within `budget` tokens. The system prompt and the latest
+    /// exchange come first, then the summary of older turns, then as many older
+    /// turns as still fit, newest first; turns left out are folded into the summary.
+    pub fn context_messages(&self, budget: usize) -> Vec<ChatMessage> {
+        let (system, turns) = match self.messages.split_first() {
+            Some((first, rest)) if first.role == "system" => (Some(first), rest),
+            _ => (None, &self.messages[..]),
+        };
+        let mut remaining = budget;
+        let system = system.and_then(|m| take_within(&m.content, &mut remaining));
+
+        // The latest exchange: the last player message and anything after it
+        let latest_start = turns.iter().rposition(|m| m.role == "user").unwrap_or(turns.len().saturating_sub(1));
+        let mut latest = Vec::new();
+        for message in turns[latest_start..].iter().rev() {
+            match take_within(&message.content, &mut remaining) {
+                Some(content) => latest.push(ChatMessage { role: message.role.clone(), content }),
+                None => break,
+            }
+        }
+        latest.reverse();
+
+        // Room for the summary is held back before older turns claim the rest
+        let summary_reserve = remaining.min(SUMMARY_MAX_TOKENS);
+        remaining -= summary_reserve;
+        let older = &turns[..latest_start];
+        let mut kept = Vec::new();
+        for message in older.iter().rev() {
+            let tokens = message_tokens(&message.content);
+            if tokens > remaining {
+                break;
+            }
+            remaining -= tokens;
+            kept.push(ChatMessage { role: message.role.clone(), content: message.content.clone() });
+        }
+        kept.reverse();
+        let dropped = &older[..older.len() - kept.len()];
+        let summary = extend_summary(
+            self.summary.as_deref(),
+            dropped.iter().map(summary_line),
+            (summary_reserve + remaining).saturating_sub(estimate_tokens(SUMMARY_HEADER)),
+        );
+
+        let mut messages: Vec<ChatMessage> = system.map(ChatMessage::system).into_iter().collect();
+        if let Some(summary) = summary {
+            messages.push(ChatMessage::system(format!("{}{}", SUMMARY_HEADER, summary)));
+        }
+        messages.extend(kept);
+        messages.extend(latest);
+        messages
+    }
+
+    /// `context_messages` sized for `model`'s context window, leaving
+    /// `reply_tokens` free for the response
+    pub fn context_for_model(&self, model: &str, reply_tokens: usize) -> Vec<ChatMessage> {
+        self.context_messages(context_length(model).saturating_sub(reply_tokens))
+    }
+
     fn trim_history(&mut self) {
-        // Keep the system message (first) and last N messages
+        // Keep the system message (first) and last N messages, summarizing the rest
         if self.messages.len() > self.max_history + 1 {
-            let system_msg = self.messages[0].clone();
-            let recent_messages: Vec<_> = self.messages
-                .iter()
-                .skip(self.messages.len() - self.max_history)
-                .cloned()
-                .collect();
-
-            self.messages.clear();
-            self.messages.push(system_msg);
-            self.messages.extend(recent_messages);
+            let dropped: Vec<Message> = self.messages.drain(1..self.messages.len() - self.max_history).collect();
+            self.summary = extend_summary(self.summary.as_deref(), dropped.iter().map(summary_line), SUMMARY_MAX_TOKENS);
         }
     }
 
     pub fn clear(&mut self) {
+        self.summary = None;
         let system_msg = self.messages.first().cloned();
         self.messages.clear();
         if let Some(msg) = system_msg {
@@ -108,3 +246,58 @@ impl Default for ConversationManager {
         Self::new("You are a helpful chess coach.")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total_tokens(messages: &[ChatMessage]) -> usize {
+        messages.iter().map(|m| message_tokens(&m.content)).sum()
+    }
+
+    fn long_session() -> ConversationManager {
+        let mut conversation = ConversationManager::new("You are a chess coach.");
+        for i in 0..15 {
+            conversation.add_user_message(format!("Question {} about the Sicilian: {}", i, "why ".repeat(50)));
+            conversation.add_assistant_message(format!("Answer {}: {}", i, "because ".repeat(50)));
+        }
+        conversation
+    }
+
+    #[test]
+    fn test_context_stays_within_budget() {
+        let conversation = long_session();
+        assert!(conversation.summary().is_some(), "turns beyond max_history are summarized");
+
+        for budget in [60, 300, 1_000, 5_000] {
+            let messages = conversation.context_messages(budget);
+            assert!(total_tokens(&messages) <= budget, "budget {}", budget);
+            assert_eq!(messages[0].content, "You are a chess coach.");
+            assert!(messages.last().unwrap().content.starts_with("Answer 14"));
+        }
+
+        let roomy = conversation.context_messages(100_000);
+        assert_eq!(roomy.len(), conversation.get_messages().len() + 1);
+        assert!(roomy[1].content.starts_with("Earlier in this conversation:"));
+    }
+
+    #[test]
+    fn test_tight_budget_keeps_latest_exchange_and_summarizes_the_rest() {
+        let conversation = long_session();
+        let messages = conversation.context_messages(500);
+
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(&roles[roles.len() - 2..], ["user", "assistant"]);
+        assert!(messages[messages.len() - 2].content.starts_with("Question 14"));
+        assert!(messages.iter().any(|m| m.content.contains("- Player: Question")));
+        assert!(messages.len() < conversation.get_messages().len());
+    }
+
+    #[test]
+    fn test_context_for_model_uses_its_window() {
+        let conversation = long_session();
+        assert!(conversation.estimated_tokens() > 1_000);
+        let messages = conversation.context_for_model("anthropic/claude-3-haiku", 2_000);
+        assert_eq!(messages.len(), conversation.get_messages().len() + 1);
+    }
+}
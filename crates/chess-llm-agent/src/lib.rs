@@ -3,8 +3,10 @@ pub mod chess_coach;
 pub mod prompts;
 pub mod conversation;
 pub mod tools;
+pub mod routing;
 
-pub use openrouter::{OpenRouterClient, ChatMessage, ChatRequest, ChatResponse};
-pub use chess_coach::{ChessCoach, CoachingSession, CoachFeedback, GameSummary, PlayerStats, SessionContext};
-pub use conversation::{ConversationManager, Message};
+pub use openrouter::{OpenRouterClient, OpenRouterClientBuilder, ChatMessage, ChatRequest, ChatResponse};
+pub use chess_coach::{ChessCoach, ChessCoachBuilder, CoachPersonality, CoachingSession, CoachFeedback, GameSummary, PlayerStats, SessionContext};
+pub use conversation::{estimate_tokens, ConversationManager, Message};
 pub use tools::{ChessTools, Tool, ToolResult};
+pub use routing::{context_length, ModelRouter, ModelTier, TaskType};
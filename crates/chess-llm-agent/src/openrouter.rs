@@ -1,7 +1,9 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
-use std::env;
+use std::time::Duration;
+
+pub use chess_config::DEFAULT_BASE_URL;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -64,35 +66,137 @@ pub struct Usage {
     pub total_tokens: u32,
 }
 
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+pub const DEFAULT_TEMPERATURE: f32 = 0.7;
+pub const DEFAULT_MAX_TOKENS: u32 = 2000;
+
 pub struct OpenRouterClient {
     client: Client,
     api_key: String,
     base_url: String,
+    temperature: f32,
+    max_tokens: u32,
 }
 
-impl OpenRouterClient {
-    pub fn new() -> Result<Self> {
-        dotenv::dotenv().ok();
+/// Builds an [`OpenRouterClient`]; anything left unset takes the defaults above
+///
+/// ```ignore
+/// let client = OpenRouterClient::builder().api_key(key).timeout(Duration::from_secs(20)).build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OpenRouterClientBuilder {
+    api_key: Option<String>,
+    base_url: Option<String>,
+    timeout: Option<Duration>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+}
+
+impl OpenRouterClientBuilder {
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Whole-request timeout, including reading the response
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-        let api_key = env::var("OPENROUTER_API_KEY")
-            .context("OPENROUTER_API_KEY not found in environment")?;
+    /// Sampling temperature for `simple_chat`, between 0 and 2
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Reply length cap for `simple_chat`
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
 
-        let base_url = env::var("OPENROUTER_BASE_URL")
-            .unwrap_or_else(|_| "https://openrouter.ai/api/v1".to_string());
+    /// Fill the key and base URL from the app config (config file, then
+    /// `OPENROUTER_API_KEY` and `OPENROUTER_BASE_URL` or a `.env` file) where
+    /// they weren't set explicitly
+    pub fn from_env(mut self) -> Self {
+        let (config, _) = chess_config::Config::load();
+        if self.api_key.is_none() {
+            self.api_key = config.llm.api_key;
+        }
+        if self.base_url.is_none() {
+            self.base_url = Some(config.llm.base_url);
+        }
+        self
+    }
 
-        Ok(Self {
-            client: Client::new(),
+    pub fn build(self) -> Result<OpenRouterClient> {
+        let api_key = self.api_key.context("OpenRouter API key not set")?;
+        if api_key.trim().is_empty() {
+            anyhow::bail!("OpenRouter API key is empty");
+        }
+
+        let base_url = self.base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        if !base_url.starts_with("https://") && !base_url.starts_with("http://") {
+            anyhow::bail!("OpenRouter base URL must be http(s): {}", base_url);
+        }
+
+        let timeout = self.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        if timeout.is_zero() {
+            anyhow::bail!("OpenRouter timeout must be greater than zero");
+        }
+
+        let temperature = self.temperature.unwrap_or(DEFAULT_TEMPERATURE);
+        if !(0.0..=2.0).contains(&temperature) {
+            anyhow::bail!("Temperature must be between 0 and 2, got {}", temperature);
+        }
+
+        let max_tokens = self.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+        if max_tokens == 0 {
+            anyhow::bail!("max_tokens must be greater than zero");
+        }
+
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(OpenRouterClient {
+            client,
             api_key,
-            base_url,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            temperature,
+            max_tokens,
         })
     }
+}
 
-    pub fn from_api_key(api_key: String) -> Self {
-        Self {
-            client: Client::new(),
-            api_key,
-            base_url: "https://openrouter.ai/api/v1".to_string(),
-        }
+impl OpenRouterClient {
+    pub fn builder() -> OpenRouterClientBuilder {
+        OpenRouterClientBuilder::default()
+    }
+
+    /// Client configured from the environment, with default settings
+    pub fn new() -> Result<Self> {
+        Self::builder()
+            .from_env()
+            .build()
+            .context("OPENROUTER_API_KEY not found in environment")
+    }
+
+    /// Client for `api_key` with default settings
+    pub fn from_api_key(api_key: String) -> Result<Self> {
+        Self::builder().api_key(api_key).build()
+    }
+
+    /// Reply length requested from the model, in tokens
+    pub fn max_tokens(&self) -> u32 {
+        self.max_tokens
     }
 
     pub async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
@@ -113,6 +217,7 @@ impl OpenRouterClient {
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
+            tracing::warn!(%status, model = %request.model, "OpenRouter request failed");
             anyhow::bail!("OpenRouter API error ({}): {}", status, error_text);
         }
 
@@ -128,8 +233,8 @@ impl OpenRouterClient {
         let request = ChatRequest {
             model: model.to_string(),
             messages,
-            temperature: Some(0.7),
-            max_tokens: Some(2000),
+            temperature: Some(self.temperature),
+            max_tokens: Some(self.max_tokens),
             top_p: None,
         };
 
@@ -159,4 +264,24 @@ mod tests {
         let result = OpenRouterClient::new();
         assert!(result.is_ok() || result.is_err()); // Either works for the test
     }
+
+    #[test]
+    fn test_builder_validates_config() {
+        assert!(OpenRouterClient::builder().build().is_err());
+        assert!(OpenRouterClient::builder().api_key("  ").build().is_err());
+        assert!(OpenRouterClient::builder().api_key("key").base_url("ftp://example.com").build().is_err());
+        assert!(OpenRouterClient::builder().api_key("key").timeout(Duration::ZERO).build().is_err());
+        assert!(OpenRouterClient::builder().api_key("key").temperature(3.0).build().is_err());
+        assert!(OpenRouterClient::builder().api_key("key").max_tokens(0).build().is_err());
+
+        let client = OpenRouterClient::builder()
+            .api_key("key")
+            .base_url("http://localhost:8080/v1/")
+            .temperature(0.2)
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, "http://localhost:8080/v1");
+        assert_eq!(client.temperature, 0.2);
+        assert_eq!(client.max_tokens, DEFAULT_MAX_TOKENS);
+    }
 }
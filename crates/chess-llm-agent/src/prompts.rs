@@ -1,4 +1,4 @@
-use chess_ai::PlayStyle;
+use chess_ai::{PlayStyle, SacrificeStats};
 
 pub struct ChessCoachPrompts;
 
@@ -24,6 +24,15 @@ When analyzing games:
 Remember: Your goal is to make chess learning enjoyable and to build the player's confidence while genuinely improving their skills."#.to_string()
     }
 
+    /// System prompt for a child profile: simpler vocabulary, shorter answers
+    pub fn kid_system_prompt() -> String {
+        format!("{}\n\n{}", Self::system_prompt(), Self::kid_mode_guidance())
+    }
+
+    pub fn kid_mode_guidance() -> String {
+        chess_trainer::kid_mode::PROMPT_GUIDANCE.to_string()
+    }
+
     pub fn game_analysis_prompt(
         pgn: &str,
         player_color: &str,
@@ -64,8 +73,18 @@ Keep the tone conversational, supportive, and educational. Use chess notation wh
         aggression: f32,
         tactical: f32,
         positional: f32,
+        sacrifices: &SacrificeStats,
         games_analyzed: usize,
     ) -> String {
+        let sacrifice_line = match sacrifices.success_rate() {
+            Some(rate) => format!(
+                "{} in {} moves, {:.0}% of them sound",
+                sacrifices.sacrifices,
+                sacrifices.moves_analyzed,
+                rate * 100.0
+            ),
+            None => "none yet".to_string(),
+        };
         format!(
             r#"Based on analyzing {games_analyzed} games, here are the player's style characteristics:
 
@@ -73,6 +92,7 @@ Primary Playing Style: {style:?}
 Aggression Score: {aggression:.1}%
 Tactical Awareness: {tactical:.1}%
 Positional Understanding: {positional:.1}%
+Sacrifices: {sacrifice_line}
 
 Please provide:
 
@@ -87,6 +107,7 @@ Make it personal and motivating - help them understand and embrace their unique
             aggression = aggression * 100.0,
             tactical = tactical * 100.0,
             positional = positional * 100.0,
+            sacrifice_line = sacrifice_line,
             games_analyzed = games_analyzed
         )
     }
@@ -149,7 +170,7 @@ Keep the hint encouraging and educational. If it's not hint level 4, don't give
             r#"Create a personalized 2-week chess training plan for a player with:
 
 Current Rating: {current_rating}
-Playing Style: {play_style:?}
+Playing Style: {play_style}
 Main Weaknesses: {weaknesses}
 Recent Progress: {recent_progress}
 
@@ -172,18 +193,4 @@ Make it actionable, achievable, and tailored to their unique situation!"#,
             recent_progress = recent_progress
         )
     }
-
-    pub fn encouragement_prompt(context: &str) -> String {
-        format!(
-            r#"The player just: {context}
-
-Provide a brief, genuine encouragement message (1-2 sentences) that:
-- Acknowledges their effort or achievement
-- Motivates them to keep going
-- Is specific to their situation (not generic)
-
-Be warm, authentic, and brief!"#,
-            context = context
-        )
-    }
 }
@@ -0,0 +1,245 @@
+use anyhow::Result;
+use chess_config::{FAST_MODELS, STRONG_MODELS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::str::FromStr;
+
+use crate::conversation::ConversationManager;
+use crate::openrouter::{ChatMessage, OpenRouterClient};
+
+/// Context window assumed for models not listed in [`context_length`]
+pub const DEFAULT_CONTEXT_LENGTH: usize = 8_192;
+
+/// Context window of `model` in tokens, by OpenRouter model id prefix
+pub fn context_length(model: &str) -> usize {
+    const KNOWN: [(&str, usize); 8] = [
+        ("anthropic/claude", 200_000),
+        ("openai/gpt-4o", 128_000),
+        ("openai/gpt-4-turbo", 128_000),
+        ("openai/gpt-4", 8_192),
+        ("openai/gpt-3.5-turbo", 16_385),
+        ("google/gemini", 1_000_000),
+        ("mistralai/", 32_768),
+        ("meta-llama/llama-3", 8_192),
+    ];
+    KNOWN
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(DEFAULT_CONTEXT_LENGTH, |(_, length)| *length)
+}
+
+/// What the coach is being asked to do, used to pick a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskType {
+    Hint,
+    Encouragement,
+    Chat,
+    GameAnalysis,
+    PlaystyleAnalysis,
+    TrainingPlan,
+}
+
+/// Cost/quality class of model a task needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelTier {
+    /// Cheap, low-latency model for short replies
+    Fast,
+    /// Strongest available model for in-depth analysis
+    Strong,
+}
+
+impl TaskType {
+    pub const ALL: [TaskType; 6] = [
+        TaskType::Hint,
+        TaskType::Encouragement,
+        TaskType::Chat,
+        TaskType::GameAnalysis,
+        TaskType::PlaystyleAnalysis,
+        TaskType::TrainingPlan,
+    ];
+
+    pub fn tier(self) -> ModelTier {
+        match self {
+            TaskType::Hint | TaskType::Encouragement | TaskType::Chat => ModelTier::Fast,
+            TaskType::GameAnalysis | TaskType::PlaystyleAnalysis | TaskType::TrainingPlan => ModelTier::Strong,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskType::Hint => "hint",
+            TaskType::Encouragement => "encouragement",
+            TaskType::Chat => "chat",
+            TaskType::GameAnalysis => "game_analysis",
+            TaskType::PlaystyleAnalysis => "playstyle_analysis",
+            TaskType::TrainingPlan => "training_plan",
+        }
+    }
+}
+
+impl FromStr for TaskType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        TaskType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown task type: {}", s))
+    }
+}
+
+/// Picks models per task, falling back down a list when a model errors
+#[derive(Debug, Clone)]
+pub struct ModelRouter {
+    fast_models: Vec<String>,
+    strong_models: Vec<String>,
+    overrides: HashMap<TaskType, String>,
+}
+
+impl Default for ModelRouter {
+    fn default() -> Self {
+        Self {
+            fast_models: FAST_MODELS.iter().map(|m| m.to_string()).collect(),
+            strong_models: STRONG_MODELS.iter().map(|m| m.to_string()).collect(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl ModelRouter {
+    pub fn new(fast_models: Vec<String>, strong_models: Vec<String>) -> Self {
+        Self {
+            fast_models,
+            strong_models,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// A router that sends every task to `model`, with no fallback
+    pub fn single(model: impl Into<String>) -> Self {
+        let model = model.into();
+        Self::new(vec![model.clone()], vec![model])
+    }
+
+    /// Prefer `model` for `task`; the tier's models remain as fallbacks
+    pub fn with_override(mut self, task: TaskType, model: impl Into<String>) -> Self {
+        self.overrides.insert(task, model.into());
+        self
+    }
+
+    /// Models to try for `task`, in order
+    pub fn candidates(&self, task: TaskType) -> Vec<String> {
+        let tier_models = match task.tier() {
+            ModelTier::Fast => &self.fast_models,
+            ModelTier::Strong => &self.strong_models,
+        };
+
+        let mut models: Vec<String> = self.overrides.get(&task).cloned().into_iter().collect();
+        for model in tier_models {
+            if !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+        models
+    }
+
+    /// Run `call` with each candidate model until one succeeds.
+    /// Returns the model that answered alongside its result, or the last error.
+    pub async fn route<T, F, Fut>(&self, task: TaskType, mut call: F) -> Result<(String, T)>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_error = None;
+        for model in self.candidates(task) {
+            match call(model.clone()).await {
+                Ok(result) => return Ok((model, result)),
+                Err(e) => {
+                    tracing::warn!(task = task.as_str(), model = %model, "model failed, trying fallback: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No models configured for {}", task.as_str())))
+    }
+
+    /// `OpenRouterClient::simple_chat` with the routed model and fallback
+    pub async fn chat(&self, client: &OpenRouterClient, task: TaskType, messages: Vec<ChatMessage>) -> Result<String> {
+        let (_, response) = self
+            .route(task, |model| {
+                let messages = messages.clone();
+                async move { client.simple_chat(&model, messages).await }
+            })
+            .await?;
+        Ok(response)
+    }
+
+    /// `chat` with `conversation` trimmed to fit each candidate model's context
+    /// window, leaving room for the client's reply tokens
+    pub async fn chat_conversation(
+        &self,
+        client: &OpenRouterClient,
+        task: TaskType,
+        conversation: &ConversationManager,
+    ) -> Result<String> {
+        let reply_tokens = client.max_tokens() as usize;
+        let (_, response) = self
+            .route(task, |model| {
+                let messages = conversation.context_for_model(&model, reply_tokens);
+                async move { client.simple_chat(&model, messages).await }
+            })
+            .await?;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_by_tier_and_override() {
+        let router = ModelRouter::new(vec!["fast".into()], vec!["strong".into(), "backup".into()])
+            .with_override(TaskType::Hint, "custom");
+
+        assert_eq!(router.candidates(TaskType::Hint), vec!["custom", "fast"]);
+        assert_eq!(router.candidates(TaskType::Encouragement), vec!["fast"]);
+        assert_eq!(router.candidates(TaskType::GameAnalysis), vec!["strong", "backup"]);
+    }
+
+    #[tokio::test]
+    async fn test_route_falls_back_on_error() {
+        let router = ModelRouter::new(vec![], vec!["strong".into(), "backup".into()]);
+
+        let (model, answer) = router
+            .route(TaskType::TrainingPlan, |model| async move {
+                if model == "strong" {
+                    anyhow::bail!("rate limited")
+                }
+                Ok(format!("plan from {}", model))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(model, "backup");
+        assert_eq!(answer, "plan from backup");
+    }
+
+    #[test]
+    fn test_context_length_by_prefix() {
+        assert_eq!(context_length("openai/gpt-4o-mini"), 128_000);
+        assert_eq!(context_length("openai/gpt-4"), 8_192);
+        assert_eq!(context_length("anthropic/claude-3.5-sonnet"), 200_000);
+        assert_eq!(context_length("someone/unknown-model"), DEFAULT_CONTEXT_LENGTH);
+    }
+
+    #[test]
+    fn test_task_type_round_trip() {
+        for task in TaskType::ALL {
+            assert_eq!(task.as_str().parse::<TaskType>().unwrap(), task);
+        }
+    }
+}
@@ -32,6 +32,7 @@ impl ChessTools {
             Self::get_games_with_mistakes_tool(),
             Self::get_training_progress_tool(),
             Self::get_improvement_trend_tool(),
+            Self::lookup_concept_tool(),
         ]
     }
 
@@ -161,6 +162,23 @@ impl ChessTools {
             },
         }
     }
+
+    fn lookup_concept_tool() -> Tool {
+        Tool {
+            name: "lookup_concept".to_string(),
+            description: "Look up a concept in the learning library: its explanation, an example position (FEN) and related concepts".to_string(),
+            parameters: ToolParameters {
+                param_type: "object".to_string(),
+                properties: serde_json::json!({
+                    "name": {
+                        "type": "string",
+                        "description": "Concept name or id, e.g. 'knight fork' or 'opposition'"
+                    }
+                }),
+                required: vec!["name".to_string()],
+            },
+        }
+    }
 }
 
 /// Tool execution results
@@ -1,5 +1,6 @@
+use chess_core::uid_at;
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
-use sqlx::Result;
+use sqlx::{Result, Row};
 
 pub struct Database {
     pool: SqlitePool,
@@ -68,6 +69,7 @@ impl Database {
                 player_color TEXT NOT NULL,
                 created_at TEXT NOT NULL,
                 finished_at TEXT,
+                uid TEXT,
                 FOREIGN KEY (user_id) REFERENCES profiles(user_id)
             )
             "#,
@@ -106,6 +108,7 @@ impl Database {
                 time_taken_seconds INTEGER NOT NULL,
                 hints_used INTEGER NOT NULL,
                 completed_at TEXT NOT NULL,
+                uid TEXT,
                 FOREIGN KEY (exercise_id) REFERENCES exercises(id),
                 FOREIGN KEY (user_id) REFERENCES profiles(user_id)
             )
@@ -134,6 +137,43 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        // Global ids for rows merged across devices; older databases get them backfilled
+        self.add_uid_column("games", "created_at").await?;
+        self.add_uid_column("exercise_results", "completed_at").await?;
+
+        Ok(())
+    }
+
+    /// Add a unique `uid` column to `table` if missing and give existing rows ids
+    /// stamped with their `created_column` time, keeping them in creation order
+    async fn add_uid_column(&self, table: &str, created_column: &str) -> Result<()> {
+        let columns = sqlx::query(&format!("PRAGMA table_info({})", table))
+            .fetch_all(&self.pool)
+            .await?;
+        if !columns.iter().any(|c| c.get::<String, _>("name") == "uid") {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN uid TEXT", table))
+                .execute(&self.pool)
+                .await?;
+        }
+
+        let rows = sqlx::query(&format!("SELECT id, {} FROM {} WHERE uid IS NULL", created_column, table))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut tx = self.pool.begin().await?;
+        for row in rows {
+            let created: String = row.get(1);
+            let ms = chrono::DateTime::parse_from_rfc3339(&created).map_or(0, |dt| dt.timestamp_millis().max(0) as u64);
+            sqlx::query(&format!("UPDATE {} SET uid = ? WHERE id = ?", table))
+                .bind(uid_at(ms))
+                .bind(row.get::<i64, _>(0))
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        sqlx::query(&format!("CREATE UNIQUE INDEX IF NOT EXISTS idx_{0}_uid ON {0}(uid)", table))
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
@@ -4,5 +4,5 @@ pub mod models;
 
 pub use database::Database;
 pub use repositories::{
-    ProfileRepository, GameRepository, ExerciseRepository, SessionRepository,
+    ProfileRepository, ProfileUpdateError, GameRepository, ExerciseRepository, SessionRepository,
 };
@@ -1,9 +1,20 @@
 use sqlx::sqlite::SqlitePool;
 use sqlx::{Result, Row};
 use chess_ai::PlayerProfile;
-use chess_core::ChessGame;
+use chess_core::{new_uid, ChessGame};
 use chess_trainer::{Exercise, ExerciseResult, TrainingSession};
 use serde_json;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// Why `ProfileRepository::update` didn't save
+#[derive(Debug, Error)]
+pub enum ProfileUpdateError {
+    #[error("profile {0} was changed by another update since it was read")]
+    Conflict(u64),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
 
 pub struct ProfileRepository<'a> {
     pool: &'a SqlitePool,
@@ -28,7 +39,7 @@ impl<'a> ProfileRepository<'a> {
         .bind(profile.user_id as i64)
         .bind(format!("{:?}", profile.skill_level))
         .bind(profile.estimated_rating as i64)
-        .bind(format!("{:?}", profile.play_style))
+        .bind(profile.play_style.to_string())
         .bind(serde_json::to_string(&profile.style_characteristics).unwrap())
         .bind(profile.games_played as i64)
         .bind(profile.exercises_completed as i64)
@@ -61,7 +72,7 @@ impl<'a> ProfileRepository<'a> {
                 user_id: row.get::<i64, _>("user_id") as u64,
                 skill_level: serde_json::from_str(&row.get::<String, _>("skill_level")).unwrap(),
                 estimated_rating: row.get::<i64, _>("estimated_rating") as u32,
-                play_style: serde_json::from_str(&row.get::<String, _>("play_style")).unwrap(),
+                play_style: row.get::<String, _>("play_style").parse().unwrap(),
                 style_characteristics: serde_json::from_str(&row.get::<String, _>("style_characteristics")).unwrap(),
                 games_played: row.get::<i64, _>("games_played") as u32,
                 exercises_completed: row.get::<i64, _>("exercises_completed") as u32,
@@ -80,19 +91,22 @@ impl<'a> ProfileRepository<'a> {
         }
     }
 
-    pub async fn update(&self, profile: &PlayerProfile) -> Result<()> {
-        sqlx::query(
+    /// Save `profile` unless another writer got there first. `read_at` is the
+    /// `updated_at` the profile had when it was loaded; `PlayerProfile`'s own
+    /// setters move `updated_at` forward, so it can't be used for the check.
+    pub async fn update(&self, profile: &PlayerProfile, read_at: DateTime<Utc>) -> std::result::Result<(), ProfileUpdateError> {
+        let updated = sqlx::query(
             r#"
             UPDATE profiles
             SET skill_level = ?, estimated_rating = ?, play_style = ?,
                 style_characteristics = ?, games_played = ?, exercises_completed = ?,
                 weaknesses = ?, strengths = ?, updated_at = ?
-            WHERE user_id = ?
+            WHERE user_id = ? AND updated_at = ?
             "#,
         )
         .bind(format!("{:?}", profile.skill_level))
         .bind(profile.estimated_rating as i64)
-        .bind(format!("{:?}", profile.play_style))
+        .bind(profile.play_style.to_string())
         .bind(serde_json::to_string(&profile.style_characteristics).unwrap())
         .bind(profile.games_played as i64)
         .bind(profile.exercises_completed as i64)
@@ -100,6 +114,33 @@ impl<'a> ProfileRepository<'a> {
         .bind(serde_json::to_string(&profile.strengths).unwrap())
         .bind(profile.updated_at.to_rfc3339())
         .bind(profile.user_id as i64)
+        .bind(read_at.to_rfc3339())
+        .execute(self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(ProfileUpdateError::Conflict(profile.user_id));
+        }
+        Ok(())
+    }
+
+    /// Count a finished game without reading the profile first, so concurrent
+    /// callers can't lose each other's increments
+    pub async fn increment_games_played(&self, user_id: u64) -> Result<()> {
+        self.increment(user_id, "games_played").await
+    }
+
+    pub async fn increment_exercises_completed(&self, user_id: u64) -> Result<()> {
+        self.increment(user_id, "exercises_completed").await
+    }
+
+    /// `column` is one of the counter columns above, never user input
+    async fn increment(&self, user_id: u64, column: &str) -> Result<()> {
+        sqlx::query(&format!(
+            "UPDATE profiles SET {column} = {column} + 1, updated_at = ? WHERE user_id = ?"
+        ))
+        .bind(Utc::now().to_rfc3339())
+        .bind(user_id as i64)
         .execute(self.pool)
         .await?;
 
@@ -121,9 +162,9 @@ impl<'a> GameRepository<'a> {
             r#"
             INSERT INTO games (
                 user_id, board_fen, move_history, game_state,
-                player_color, created_at, finished_at
+                player_color, created_at, finished_at, uid
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(0i64) // Default user_id
@@ -133,6 +174,7 @@ impl<'a> GameRepository<'a> {
         .bind(format!("{:?}", game.player_color))
         .bind(game.created_at.to_rfc3339())
         .bind(game.finished_at.map(|dt| dt.to_rfc3339()))
+        .bind(new_uid())
         .execute(self.pool)
         .await?;
 
@@ -207,8 +249,8 @@ impl<'a> ExerciseRepository<'a> {
             VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(format!("{:?}", exercise.exercise_type))
-        .bind(format!("{:?}", exercise.difficulty))
+        .bind(exercise.exercise_type.to_string())
+        .bind(exercise.difficulty.to_string())
         .bind(&exercise.position)
         .bind(&exercise.title)
         .bind(&exercise.description)
@@ -226,9 +268,9 @@ impl<'a> ExerciseRepository<'a> {
             r#"
             INSERT INTO exercise_results (
                 exercise_id, user_id, solved, attempts,
-                time_taken_seconds, hints_used, completed_at
+                time_taken_seconds, hints_used, completed_at, uid
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(result.exercise_id as i64)
@@ -238,6 +280,7 @@ impl<'a> ExerciseRepository<'a> {
         .bind(result.time_taken_seconds as i64)
         .bind(result.hints_used as i64)
         .bind(result.completed_at.to_rfc3339())
+        .bind(new_uid())
         .execute(self.pool)
         .await?;
 
@@ -269,7 +312,7 @@ impl<'a> SessionRepository<'a> {
         .bind(session.current_exercise_index as i64)
         .bind(serde_json::to_string(&session.results).unwrap())
         .bind(serde_json::to_string(&session.strategies).unwrap())
-        .bind(format!("{:?}", session.difficulty))
+        .bind(session.difficulty.to_string())
         .bind(session.started_at.to_rfc3339())
         .bind(session.finished_at.map(|dt| dt.to_rfc3339()))
         .execute(self.pool)
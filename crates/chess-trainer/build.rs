@@ -0,0 +1,42 @@
+//! Turns `assets/strategies.json` into Rust, so the built-in strategies load without
+//! `serde_json` at run time; only custom strategies need the `json` feature.
+
+use serde::Deserialize;
+use std::fmt::Write;
+use std::path::Path;
+use std::{env, fs};
+
+const ASSET: &str = "assets/strategies.json";
+
+#[derive(Deserialize)]
+struct StrategyAsset {
+    pattern: String,
+    name: String,
+    description: String,
+    #[serde(default)]
+    weakness_codes: Vec<String>,
+    key_concepts: Vec<String>,
+    recommended_exercises: Vec<String>,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", ASSET);
+
+    let json = fs::read_to_string(ASSET).expect("built-in strategies asset is readable");
+    let strategies: Vec<StrategyAsset> = serde_json::from_str(&json).expect("built-in strategies are valid JSON");
+
+    // `{:?}` writes strings and string slices as valid Rust literals
+    let mut code = String::from("vec![\n");
+    for s in &strategies {
+        writeln!(
+            code,
+            "    builtin_strategy({:?}, {:?}, {:?}, &{:?}, &{:?}, &{:?}),",
+            s.pattern, s.name, s.description, s.weakness_codes, s.key_concepts, s.recommended_exercises
+        )
+        .unwrap();
+    }
+    code.push(']');
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("builtin_strategies.rs");
+    fs::write(out, code).expect("generated strategies are writable");
+}
@@ -1,8 +1,8 @@
 use chess::{Board, ChessMove, Color};
+use chess_core::GamePhase;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ExerciseType {
     Tactics,           // Find the best tactical move
     Endgame,          // Practice endgame positions
@@ -10,9 +10,20 @@ pub enum ExerciseType {
     Positional,       // Improve positional understanding
     Calculation,      // Calculate variations
     Strategy,         // Strategic planning
+    Defense,          // Hold a worse position as stubbornly as possible
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
+crate::named_enum!(ExerciseType, "exercise type", {
+    ExerciseType::Tactics => "Tactics",
+    ExerciseType::Endgame => "Endgame",
+    ExerciseType::Opening => "Opening",
+    ExerciseType::Positional => "Positional",
+    ExerciseType::Calculation => "Calculation",
+    ExerciseType::Strategy => "Strategy",
+    ExerciseType::Defense => "Defense",
+});
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum ExerciseDifficulty {
     Beginner = 1,
     Intermediate = 2,
@@ -20,6 +31,33 @@ pub enum ExerciseDifficulty {
     Expert = 4,
 }
 
+crate::named_enum!(ExerciseDifficulty, "exercise difficulty", {
+    ExerciseDifficulty::Beginner => "Beginner",
+    ExerciseDifficulty::Intermediate => "Intermediate",
+    ExerciseDifficulty::Advanced => "Advanced",
+    ExerciseDifficulty::Expert => "Expert",
+});
+
+impl ExerciseDifficulty {
+    /// One level up, staying at Expert
+    pub fn harder(&self) -> Self {
+        match self {
+            ExerciseDifficulty::Beginner => ExerciseDifficulty::Intermediate,
+            ExerciseDifficulty::Intermediate => ExerciseDifficulty::Advanced,
+            ExerciseDifficulty::Advanced | ExerciseDifficulty::Expert => ExerciseDifficulty::Expert,
+        }
+    }
+
+    /// One level down, staying at Beginner
+    pub fn easier(&self) -> Self {
+        match self {
+            ExerciseDifficulty::Beginner | ExerciseDifficulty::Intermediate => ExerciseDifficulty::Beginner,
+            ExerciseDifficulty::Advanced => ExerciseDifficulty::Intermediate,
+            ExerciseDifficulty::Expert => ExerciseDifficulty::Advanced,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Exercise {
     pub id: Option<u64>,
@@ -31,6 +69,13 @@ pub struct Exercise {
     pub solution_moves: Vec<String>, // Best move(s) in algebraic notation
     pub hints: Vec<String>,
     pub explanation: String,
+    /// Game phase of `position`, for phase-targeted sessions
+    #[serde(default)]
+    pub phase: GamePhase,
+    /// Whole line for multi-move puzzles: the solver's moves with the opponent's
+    /// replies between them. Empty when one move solves it, as `solution_moves` covers.
+    #[serde(default)]
+    pub solution_line: Vec<String>,
 }
 
 impl Exercise {
@@ -43,6 +88,7 @@ impl Exercise {
         solution_moves: Vec<String>,
         explanation: String,
     ) -> Self {
+        let phase = chess_core::parse_fen(&position).map(|board| GamePhase::of(&board)).unwrap_or_default();
         Self {
             id: None,
             exercise_type,
@@ -53,6 +99,8 @@ impl Exercise {
             solution_moves,
             hints: Vec::new(),
             explanation,
+            phase,
+            solution_line: Vec::new(),
         }
     }
 
@@ -61,9 +109,36 @@ impl Exercise {
         self
     }
 
+    /// Override the phase read from the position
+    pub fn with_phase(mut self, phase: GamePhase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Make this a multi-move puzzle played out along `line`; its first move also
+    /// becomes the stored solution
+    pub fn with_line(mut self, line: Vec<String>) -> Self {
+        if let Some(first) = line.first() {
+            if !self.solution_moves.contains(first) {
+                self.solution_moves.insert(0, first.clone());
+            }
+        }
+        self.solution_line = line;
+        self
+    }
+
+    pub fn is_multi_move(&self) -> bool {
+        self.solution_line.len() > 1
+    }
+
+    /// Moves the solver has to find: every other ply of the line, or one
+    pub fn moves_to_find(&self) -> usize {
+        self.solution_line.len().div_ceil(2).max(1)
+    }
+
     pub fn get_board(&self) -> Result<Board, String> {
-        Board::from_str(&self.position)
-            .map_err(|e| format!("Invalid FEN in exercise: {}", e))
+        chess_core::parse_fen(&self.position)
+            .map_err(|e| format!("Bad exercise position: {}", e))
     }
 
     pub fn check_solution(&self, user_move: &str) -> bool {
@@ -84,6 +159,10 @@ pub struct ExerciseResult {
     pub attempts: u32,
     pub time_taken_seconds: u32,
     pub hints_used: u32,
+    /// Guided-help questions the coach asked before the solve; any at all means it was assisted
+    #[serde(default)]
+    pub scaffolding_steps: u32,
+    #[cfg(feature = "timestamps")]
     pub completed_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -96,6 +175,8 @@ impl ExerciseResult {
             attempts: 0,
             time_taken_seconds: 0,
             hints_used: 0,
+            scaffolding_steps: 0,
+            #[cfg(feature = "timestamps")]
             completed_at: chrono::Utc::now(),
         }
     }
@@ -174,12 +255,116 @@ impl ExerciseLibrary {
         ]
     }
 
+    /// "Defend this worse position" drills; any sufficiently stubborn move counts
+    pub fn get_defense_exercises() -> Vec<Exercise> {
+        vec![
+            Exercise::new(
+                ExerciseType::Defense,
+                ExerciseDifficulty::Intermediate,
+                "4k3/R7/8/3KP3/8/8/8/1r6 b - - 0 1".to_string(),
+                "Philidor Defense".to_string(),
+                "Black is a pawn down in a rook endgame. Find the setup that holds.".to_string(),
+                vec!["Rb6".to_string()],
+                "Rb6 keeps White's king off the sixth rank. If the pawn advances to e6, the rook drops back and checks from behind.".to_string(),
+            )
+            .with_hints(vec!["Stop the enemy king from advancing in front of its pawn.".to_string()]),
+            Exercise::new(
+                ExerciseType::Defense,
+                ExerciseDifficulty::Advanced,
+                "4k3/R7/1r2P3/3K4/8/8/8/8 b - - 0 1".to_string(),
+                "Checks From Behind".to_string(),
+                "White's pawn has reached the sixth rank. How does Black keep the draw?".to_string(),
+                vec!["Rb1".to_string()],
+                "Once the pawn is on e6 the king has no shelter from checks, so the rook goes to the first rank and checks from behind.".to_string(),
+            )
+            .with_hints(vec!["The king can no longer hide in front of its pawn.".to_string()]),
+        ]
+    }
+
+    /// Promotions, including the underpromotions where a queen would throw the win away
+    pub fn get_promotion_exercises() -> Vec<Exercise> {
+        vec![
+            Exercise::new(
+                ExerciseType::Endgame,
+                ExerciseDifficulty::Beginner,
+                "8/4P3/8/8/8/k7/8/4K3 w - - 0 1".to_string(),
+                "Promote the Pawn".to_string(),
+                "White's pawn is one step from the last rank. Finish the job.".to_string(),
+                vec!["e8=Q".to_string()],
+                "e8=Q turns the pawn into a queen, the strongest piece, and the rest is a simple mate.".to_string(),
+            )
+            .with_hints(vec!["A pawn that reaches the last rank can become any piece.".to_string()]),
+            Exercise::new(
+                ExerciseType::Endgame,
+                ExerciseDifficulty::Advanced,
+                "5N2/4P3/5K1k/8/8/8/8/8 w - - 0 1".to_string(),
+                "Avoid the Stalemate".to_string(),
+                "White to move and win. Which piece should the pawn become?".to_string(),
+                vec!["e8=R".to_string()],
+                "e8=Q and e8=B both cover h5 and leave Black without a move: stalemate. e8=R leaves the king h5, and the rook and knight mate soon after.".to_string(),
+            )
+            .with_hints(vec!["Count the king's moves after each promotion. A queen isn't always best.".to_string()]),
+            Exercise::new(
+                ExerciseType::Tactics,
+                ExerciseDifficulty::Advanced,
+                "8/3P1k2/2q5/8/8/8/7P/K7 w - - 0 1".to_string(),
+                "Knight Promotion Fork".to_string(),
+                "White to move. A new queen only gets you a level queen ending. Find better.".to_string(),
+                vec!["d8=N+".to_string()],
+                "d8=N+ forks the king and the queen on c6. After the king moves, Nxc6 leaves White a knight and pawn up.".to_string(),
+            )
+            .with_hints(vec!["Which new piece gives check as it lands?".to_string()]),
+            Exercise::new(
+                ExerciseType::Tactics,
+                ExerciseDifficulty::Advanced,
+                "k7/7p/8/8/8/2Q5/3p1K2/8 b - - 0 1".to_string(),
+                "Knight Promotion Fork (Black)".to_string(),
+                "Black to move. Promote to win White's queen.".to_string(),
+                vec!["d1=N+".to_string()],
+                "d1=N+ checks the king on f2 and attacks the queen on c3, which falls next move.".to_string(),
+            )
+            .with_hints(vec!["Which new piece gives check as it lands?".to_string()]),
+        ]
+    }
+
+    /// Multi-move combinations, played out against the opponent's replies
+    pub fn get_combination_exercises() -> Vec<Exercise> {
+        vec![
+            Exercise::new(
+                ExerciseType::Tactics,
+                ExerciseDifficulty::Intermediate,
+                "2r3k1/5ppp/8/8/8/8/4QPPP/4R1K1 w - - 0 1".to_string(),
+                "Back Rank Breakthrough".to_string(),
+                "White to move and mate in two. The rook on c8 is Black's only guard of the back rank.".to_string(),
+                vec![],
+                "Qe8+ drags the rook away from c8 with Rxe8, and Rxe8# mates: the king is boxed in by its own pawns.".to_string(),
+            )
+            .with_line(vec!["Qe8+".to_string(), "Rxe8".to_string(), "Rxe8#".to_string()])
+            .with_hints(vec!["Which of Black's pieces is overloaded?".to_string()]),
+            Exercise::new(
+                ExerciseType::Tactics,
+                ExerciseDifficulty::Beginner,
+                "r3k3/pp3ppp/5n2/1N6/8/8/5PPP/6K1 w - - 0 1".to_string(),
+                "Royal Fork".to_string(),
+                "White to move and win material.".to_string(),
+                vec![],
+                "Nc7+ checks the king and attacks the rook on a8 at once. Once the king moves, Nxa8 wins the rook.".to_string(),
+            )
+            .with_line(vec!["Nc7+".to_string(), "Ke7".to_string(), "Nxa8".to_string()])
+            .with_hints(vec!["Knights can attack two pieces at once. Look for a check.".to_string()]),
+        ]
+    }
+
     pub fn get_all_exercises() -> Vec<Exercise> {
         let mut exercises = Vec::new();
         exercises.extend(Self::get_tactical_exercises());
         exercises.extend(Self::get_opening_exercises());
         exercises.extend(Self::get_endgame_exercises());
         exercises.extend(Self::get_positional_exercises());
+        exercises.extend(Self::get_defense_exercises());
+        // Last, so existing exercises keep their indices
+        exercises.extend(Self::get_promotion_exercises());
+        exercises.extend(Self::get_combination_exercises());
         exercises
     }
 }
@@ -219,4 +404,17 @@ mod tests {
         assert!(exercise.check_solution("e4"));
         assert!(!exercise.check_solution("d4"));
     }
+
+    #[test]
+    fn test_library_is_tagged_with_phase() {
+        let phase_of = |title: &str| {
+            ExerciseLibrary::get_all_exercises()
+                .into_iter()
+                .find(|e| e.title == title)
+                .map(|e| e.phase)
+        };
+        assert_eq!(phase_of("Opening Principles"), Some(GamePhase::Opening));
+        assert_eq!(phase_of("Develop Your Pieces"), Some(GamePhase::Middlegame));
+        assert_eq!(phase_of("Philidor Defense"), Some(GamePhase::Endgame));
+    }
 }
@@ -0,0 +1,217 @@
+use chess::{Board, BoardStatus, ChessMove, Color, MoveGen, Piece, Square, EMPTY};
+use chess_core::bitboards::{attackers_of, squares};
+use chess_core::notation::piece_name;
+use chess_core::{parse_fen, parse_move, to_san, Position};
+use serde::{Deserialize, Serialize};
+
+use crate::exercise::Exercise;
+use crate::scaffolding::{scaffold, ScaffoldStep};
+
+/// Seconds on one exercise after which the student counts as stuck
+pub const STUCK_SECONDS: u32 = 90;
+
+/// A wrong move and the position it was played from, which part way through a
+/// multi-move puzzle is not the exercise's start
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrongMove {
+    /// In UCI or SAN
+    #[serde(rename = "move")]
+    pub tried: String,
+    /// The exercise's start when absent
+    #[serde(default)]
+    pub fen: Option<String>,
+}
+
+impl WrongMove {
+    /// A move tried at the exercise's start
+    pub fn at_start(tried: impl Into<String>) -> Self {
+        Self { tried: tried.into(), fen: None }
+    }
+
+    /// The position the move was played from, given the exercise's `start`
+    pub fn board(&self, start: Option<&Board>) -> Option<Board> {
+        match &self.fen {
+            Some(fen) => parse_fen(fen).ok(),
+            None => start.copied(),
+        }
+    }
+}
+
+/// How the student's attempt at an exercise has gone so far
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttemptContext {
+    /// Wrong moves tried, oldest first
+    #[serde(default)]
+    pub wrong_moves: Vec<WrongMove>,
+    #[serde(default)]
+    pub elapsed_seconds: u32,
+}
+
+impl AttemptContext {
+    pub fn is_stuck(&self) -> bool {
+        self.elapsed_seconds >= STUCK_SECONDS
+    }
+}
+
+/// Why a tried move fails, as far as the board alone shows it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misconception {
+    /// The move in SAN
+    pub tried: String,
+    /// e.g. "f7 is defended by the rook on f8"
+    pub reason: String,
+}
+
+impl Misconception {
+    pub fn to_hint(&self) -> String {
+        format!("You tried {}, but {}.", self.tried, self.reason)
+    }
+}
+
+fn named(board: &Board, square: Square) -> String {
+    let piece = board.piece_on(square).map(piece_name).unwrap_or("piece");
+    format!("{} on {}", piece.to_lowercase(), square)
+}
+
+/// `color` pieces, kings aside, attacked by the other side and defended by none of their own
+fn hanging(board: &Board, color: Color) -> Vec<Square> {
+    squares(*board.color_combined(color) & !*board.pieces(Piece::King))
+        .into_iter()
+        .filter(|sq| attackers_of(board, *sq, !color) != EMPTY && attackers_of(board, *sq, color) == EMPTY)
+        .collect()
+}
+
+/// The cheapest legal capture on `square` in `board`, kings last
+fn cheapest_capture(board: &Board, square: Square) -> Option<ChessMove> {
+    MoveGen::new_legal(board).filter(|m| m.get_dest() == square).min_by_key(|m| {
+        let piece = board.piece_on(m.get_source()).unwrap_or(Piece::Pawn);
+        (piece == Piece::King, Position::piece_value(piece))
+    })
+}
+
+/// What goes wrong after `tried` in `board`: a mate it allows, the moved piece
+/// lost for less than it took, or another piece it leaves hanging. `None` when
+/// the move is illegal here or the board shows nothing wrong with it.
+pub fn misconception(board: &Board, tried: &str) -> Option<Misconception> {
+    let chess_move = parse_move(board, tried).ok()?;
+    let tried = to_san(board, chess_move);
+    let after = board.make_move_new(chess_move);
+    let us = board.side_to_move();
+
+    if let Some(mate) = MoveGen::new_legal(&after).find(|m| after.make_move_new(*m).status() == BoardStatus::Checkmate) {
+        return Some(Misconception { tried, reason: format!("it allows {}", to_san(&after, mate)) });
+    }
+
+    let dest = chess_move.get_dest();
+    let moved = after.piece_on(dest)?;
+    if let Some(capture) = cheapest_capture(&after, dest) {
+        let taker = after.piece_on(capture.get_source()).unwrap_or(Piece::Pawn);
+        let taken = board.piece_on(dest).map(Position::piece_value).unwrap_or(0);
+        let lost = if attackers_of(&after, dest, us) == EMPTY {
+            Position::piece_value(moved)
+        } else {
+            Position::piece_value(moved) - Position::piece_value(taker)
+        };
+        if moved != Piece::King && lost > taken {
+            let reason = if board.piece_on(dest).is_some() {
+                format!("{} is defended by the {}", dest, named(&after, capture.get_source()))
+            } else {
+                format!(
+                    "the {} can take your {} there",
+                    named(&after, capture.get_source()),
+                    piece_name(moved).to_lowercase()
+                )
+            };
+            return Some(Misconception { tried, reason });
+        }
+    }
+
+    let before = hanging(board, us);
+    let left = hanging(&after, us).into_iter().find(|sq| *sq != dest && !before.contains(sq))?;
+    Some(Misconception { tried, reason: format!("it leaves your {} undefended", named(&after, left)) })
+}
+
+/// The hint to show for request `hint_index` on `exercise`, given how the attempt
+/// has gone. The latest wrong try is explained first, so the hint meets the
+/// mistake actually made; then comes the written hint. Past the written hints
+/// the coach's leading questions take over, and only a stuck student is led
+/// as far as the solution itself.
+pub fn select_hint(exercise: &Exercise, hint_index: usize, context: &AttemptContext) -> Option<String> {
+    let board = exercise.get_board().ok()?;
+    let mut parts = vec![];
+    let latest = context.wrong_moves.last();
+    if let Some(found) = latest.and_then(|tried| misconception(&tried.board(Some(&board))?, &tried.tried)) {
+        parts.push(found.to_hint());
+    }
+
+    match exercise.hints.get(hint_index) {
+        Some(hint) => parts.push(hint.clone()),
+        None => {
+            let step = ScaffoldStep::LADDER
+                .iter()
+                .filter(|step| context.is_stuck() || !step.reveals_solution())
+                .nth(hint_index - exercise.hints.len());
+            if let Some(step) = step.and_then(|step| scaffold(exercise, *step).ok()) {
+                parts.push(step.question);
+            }
+        }
+    }
+
+    // A long think without trying anything usually means the forcing moves went uncounted
+    if context.is_stuck() && context.wrong_moves.is_empty() && hint_index == 0 && !parts.is_empty() {
+        if let Ok(step) = scaffold(exercise, ScaffoldStep::ForcingMoves) {
+            parts.push(step.question);
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise::{ExerciseDifficulty, ExerciseType};
+
+    #[test]
+    fn test_hints_answer_the_wrong_try() {
+        let exercise = Exercise::new(
+            ExerciseType::Tactics,
+            ExerciseDifficulty::Beginner,
+            "5rk1/5p1p/8/8/8/8/5Q2/6K1 w - - 0 1".to_string(),
+            "Loose pawn".to_string(),
+            "Find the safe capture.".to_string(),
+            vec!["Qa7".to_string()],
+            "The queen attacks f7 twice over from a7.".to_string(),
+        )
+        .with_hints(vec!["Count the defenders before you take.".to_string()]);
+        let board = exercise.get_board().unwrap();
+
+        let tried = misconception(&board, "f2f7").unwrap();
+        assert_eq!(tried.to_hint(), "You tried Qxf7+, but f7 is defended by the rook on f8.");
+        assert_eq!(misconception(&board, "Qa7"), None);
+        assert_eq!(misconception(&board, "a1a2"), None);
+
+        let tried_it = AttemptContext { wrong_moves: vec![WrongMove::at_start("f2f7")], elapsed_seconds: 20 };
+        assert_eq!(
+            select_hint(&exercise, 0, &tried_it).unwrap(),
+            "You tried Qxf7+, but f7 is defended by the rook on f8. Count the defenders before you take."
+        );
+        assert_eq!(
+            select_hint(&exercise, 0, &AttemptContext::default()).unwrap(),
+            "Count the defenders before you take."
+        );
+        assert_eq!(
+            select_hint(&exercise, 1, &AttemptContext::default()).unwrap(),
+            "Before looking for your own move: what is your opponent threatening?"
+        );
+
+        let stuck = AttemptContext { wrong_moves: vec![], elapsed_seconds: STUCK_SECONDS };
+        assert!(select_hint(&exercise, 0, &stuck).unwrap().ends_with("What checks and captures do you have here?"));
+        assert!(select_hint(&exercise, 4, &stuck).unwrap().contains("queen on f2"));
+        assert_eq!(select_hint(&exercise, 4, &AttemptContext::default()), None);
+
+        // A try from further along is judged where it was played, where f7 is no longer defended
+        let later = WrongMove { tried: "f2f7".to_string(), fen: Some("7k/5p1p/8/8/8/8/5Q2/6K1 w - - 0 1".to_string()) };
+        let mid_line = AttemptContext { wrong_moves: vec![later], elapsed_seconds: 20 };
+        assert_eq!(select_hint(&exercise, 0, &mid_line).unwrap(), "Count the defenders before you take.");
+    }
+}
@@ -1,7 +1,24 @@
 pub mod exercise;
+pub mod hints;
+pub mod kid_mode;
+pub mod names;
+pub mod opening_walkthrough;
+pub mod promotion;
+pub mod puzzle_line;
+pub mod scaffolding;
+pub mod selection;
+pub mod solution_check;
 pub mod strategy;
 pub mod training_session;
 
 pub use exercise::{Exercise, ExerciseType, ExerciseDifficulty, ExerciseResult, ExerciseLibrary};
-pub use strategy::{Strategy, StrategyPattern};
+pub use hints::{misconception, select_hint, AttemptContext, Misconception, WrongMove};
+pub use names::ParseNameError;
+pub use opening_walkthrough::{explain_move, OpeningWalkthrough, QuizAnswer, QuizVerdict, WalkthroughStep};
+pub use promotion::{is_underpromotion, underpromotion_exercise};
+pub use puzzle_line::{LineProgress, LineStep};
+pub use scaffolding::{scaffold, Scaffold, ScaffoldStep};
+pub use selection::{AdaptiveSelector, ConceptRefresher, ExerciseSelector};
+pub use solution_check::{SolutionChecker, SolutionMatch};
+pub use strategy::{Strategy, StrategyError, StrategyLibrary, StrategyPattern, StrategyRegistry};
 pub use training_session::{TrainingSession, SessionResult};
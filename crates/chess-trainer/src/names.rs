@@ -0,0 +1,114 @@
+use thiserror::Error;
+
+/// A stored or user-supplied name that doesn't match any variant
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unknown {kind} '{value}'")]
+pub struct ParseNameError {
+    pub kind: &'static str,
+    pub value: String,
+}
+
+/// Lowercase with spaces, underscores and dashes dropped, so "Opening Principles",
+/// "opening_principles" and "OpeningPrinciples" all compare equal
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Gives a fieldless enum one canonical string form shared by `Display`, `FromStr`
+/// and serde. Parsing goes through [`normalize_name`], so older lowercase or
+/// snake_case spellings still load.
+///
+/// ```ignore
+/// named_enum!(PlayStyle, "play style", {
+///     PlayStyle::Aggressive => "Aggressive",
+///     PlayStyle::Solid => "Solid",
+/// });
+/// ```
+#[macro_export]
+macro_rules! named_enum {
+    ($ty:ident, $kind:literal, { $($variant:path => $name:literal),+ $(,)? }) => {
+        impl $ty {
+            /// Every variant, in declaration order
+            pub const ALL: &'static [$ty] = &[$($variant),+];
+
+            /// Canonical name, as written to storage and shown in the UI
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($variant => $name),+
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::std::str::FromStr for $ty {
+            type Err = $crate::ParseNameError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                let wanted = $crate::names::normalize_name(s);
+                $ty::ALL
+                    .iter()
+                    .find(|v| $crate::names::normalize_name(v.as_str()) == wanted)
+                    .cloned()
+                    .ok_or_else(|| $crate::ParseNameError { kind: $kind, value: s.to_string() })
+            }
+        }
+
+        impl ::serde::Serialize for $ty {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $ty {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+                let name = <::std::string::String as ::serde::Deserialize>::deserialize(deserializer)?;
+                name.parse().map_err(::serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exercise::{ExerciseDifficulty, ExerciseType};
+    use crate::strategy::StrategyPattern;
+
+    #[test]
+    fn test_names_round_trip() {
+        for t in ExerciseType::ALL {
+            assert_eq!(&t.to_string().parse::<ExerciseType>().unwrap(), t);
+        }
+        for d in ExerciseDifficulty::ALL {
+            assert_eq!(&d.to_string().parse::<ExerciseDifficulty>().unwrap(), d);
+        }
+        for p in StrategyPattern::ALL {
+            assert_eq!(&p.to_string().parse::<StrategyPattern>().unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn test_parsing_accepts_legacy_spellings() {
+        assert_eq!("tactics".parse::<ExerciseType>().unwrap(), ExerciseType::Tactics);
+        assert_eq!(" ADVANCED".trim().parse::<ExerciseDifficulty>().unwrap(), ExerciseDifficulty::Advanced);
+        assert_eq!("opening_principles".parse::<StrategyPattern>().unwrap(), StrategyPattern::OpeningPrinciples);
+
+        let err = "Tactix".parse::<ExerciseType>().unwrap_err();
+        assert_eq!(err.to_string(), "unknown exercise type 'Tactix'");
+    }
+
+    #[test]
+    fn test_serde_matches_display() {
+        assert_eq!(serde_json::to_string(&ExerciseDifficulty::Expert).unwrap(), "\"Expert\"");
+        let parsed: ExerciseType = serde_json::from_str("\"endgame\"").unwrap();
+        assert_eq!(parsed, ExerciseType::Endgame);
+        assert!(serde_json::from_str::<ExerciseType>("\"Endgames\"").is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use chess::{Board, ChessMove, Color, MoveGen, Piece, Square, EMPTY};
+use chess_core::bitboards::{attacks_from, piece_attacks};
+use chess_core::notation::piece_name;
+use chess_core::{parse_legal_uci, parse_move, position_key, to_san, Opening, OPENINGS};
+use serde::{Deserialize, Serialize};
+
+const CENTRE: [Square; 4] = [Square::D4, Square::E4, Square::D5, Square::E5];
+
+/// One move of an opening's mainline, as the walkthrough presents it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalkthroughStep {
+    pub ply: usize,
+    pub uci: String,
+    pub san: String,
+    /// Position before the move
+    pub fen: String,
+    /// What the move does, from the position and the opening data
+    pub explanation: String,
+}
+
+/// How a quiz answer relates to the mainline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuizVerdict {
+    /// The next move of the mainline
+    Mainline,
+    /// A later move of the line played early; the line's own moves still reach its final position
+    Transposition,
+    /// Legal, but it leaves the line
+    Wrong,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuizAnswer {
+    pub verdict: QuizVerdict,
+    /// Moves played so far in UCI: the answer and the opponent's reply when it kept to the line
+    pub played: Vec<String>,
+    /// Position after `played`
+    pub fen: String,
+    /// The opponent's reply, in SAN
+    pub reply: Option<String>,
+    /// The move the line expected, in SAN, when the answer left it
+    pub expected: Option<String>,
+    /// Whether the line's final position has been reached
+    pub completed: bool,
+}
+
+/// An opening line played out move by move from the standard start. The
+/// student is quizzed on one side's moves; any order of the line's own moves
+/// that reaches its final position counts.
+#[derive(Debug, Clone)]
+pub struct OpeningWalkthrough {
+    pub opening: &'static Opening,
+    pub steps: Vec<WalkthroughStep>,
+    /// `position_key` of the line's final position
+    final_key: String,
+}
+
+impl OpeningWalkthrough {
+    pub fn new(opening: &'static Opening) -> Result<Self, String> {
+        let mut board = Board::default();
+        let mut steps = Vec::with_capacity(opening.moves.len());
+        let mut previous: Option<ChessMove> = None;
+        for (ply, uci) in opening.moves.iter().enumerate() {
+            let chess_move =
+                parse_legal_uci(&board, uci).map_err(|e| format!("{} has a bad move {}: {}", opening.name, uci, e))?;
+            let mut explanation = explain_move(&board, chess_move, previous);
+            if let Some(named) = OPENINGS.iter().find(|o| o.moves.len() == ply + 1 && o.moves == &opening.moves[..=ply]) {
+                explanation.push_str(&format!(" This is the {} ({}).", named.name, named.eco));
+            }
+            steps.push(WalkthroughStep {
+                ply,
+                uci: uci.to_string(),
+                san: to_san(&board, chess_move),
+                fen: board.to_string(),
+                explanation,
+            });
+            previous = Some(chess_move);
+            board = board.make_move_new(chess_move);
+        }
+        Ok(Self { opening, steps, final_key: position_key(&board) })
+    }
+
+    /// The position after `played`, in UCI from the standard start
+    pub fn replay(&self, played: &[String]) -> Result<Board, String> {
+        let mut board = Board::default();
+        for uci in played {
+            let chess_move = parse_legal_uci(&board, uci).map_err(|e| format!("Bad move {}: {}", uci, e))?;
+            board = board.make_move_new(chess_move);
+        }
+        Ok(board)
+    }
+
+    pub fn is_complete(&self, played: &[String]) -> bool {
+        played.len() == self.steps.len()
+            && self.replay(played).is_ok_and(|board| position_key(&board) == self.final_key)
+    }
+
+    /// Moves of the line the side to move can play after `played` and still
+    /// reach its final position, in mainline order
+    pub fn next_moves(&self, played: &[String]) -> Result<Vec<ChessMove>, String> {
+        let board = self.replay(played)?;
+        Ok(self
+            .remaining(played)
+            .into_iter()
+            .filter_map(|uci| parse_legal_uci(&board, &uci).ok())
+            .filter(|m| {
+                let mut line = played.to_vec();
+                line.push(m.to_string());
+                self.completes(&board.make_move_new(*m), &mut line)
+            })
+            .collect())
+    }
+
+    /// Check the student's `answer` (UCI or SAN) after `played`. A move that keeps
+    /// to the line is answered with the opponent's next move of the line.
+    pub fn answer(&self, played: &[String], answer: &str) -> Result<QuizAnswer, String> {
+        let board = self.replay(played)?;
+        let chess_move = parse_move(&board, answer).map_err(|e| format!("Invalid move: {}", e))?;
+        let candidates = self.next_moves(played)?;
+        let mut line = played.to_vec();
+
+        if !candidates.contains(&chess_move) {
+            return Ok(QuizAnswer {
+                verdict: QuizVerdict::Wrong,
+                played: line,
+                fen: board.to_string(),
+                reply: None,
+                expected: candidates.first().map(|m| to_san(&board, *m)),
+                completed: false,
+            });
+        }
+        line.push(chess_move.to_string());
+        let on_mainline = self.steps.len() >= line.len() && self.steps.iter().zip(&line).all(|(s, uci)| s.uci == *uci);
+        let verdict = if on_mainline { QuizVerdict::Mainline } else { QuizVerdict::Transposition };
+
+        let mut after = board.make_move_new(chess_move);
+        let mut reply = None;
+        if !self.is_complete(&line) {
+            if let Some(response) = self.next_moves(&line)?.first() {
+                reply = Some(to_san(&after, *response));
+                line.push(response.to_string());
+                after = after.make_move_new(*response);
+            }
+        }
+        let completed = self.is_complete(&line);
+        Ok(QuizAnswer { verdict, played: line, fen: after.to_string(), reply, expected: None, completed })
+    }
+
+    /// Moves of the line not yet played by the side to move after `played`
+    fn remaining(&self, played: &[String]) -> Vec<String> {
+        let parity = played.len() % 2;
+        let mut remaining: Vec<String> =
+            self.steps.iter().filter(|s| s.ply % 2 == parity).map(|s| s.uci.clone()).collect();
+        for uci in played.iter().skip(parity).step_by(2) {
+            if let Some(index) = remaining.iter().position(|m| m == uci) {
+                remaining.remove(index);
+            }
+        }
+        remaining
+    }
+
+    /// Whether the rest of the line's moves, in some order, reach its final position from `board`
+    fn completes(&self, board: &Board, line: &mut Vec<String>) -> bool {
+        if line.len() >= self.steps.len() {
+            return line.len() == self.steps.len() && position_key(board) == self.final_key;
+        }
+        for uci in self.remaining(line) {
+            let Ok(chess_move) = parse_legal_uci(board, &uci) else {
+                continue;
+            };
+            line.push(uci);
+            let reaches = self.completes(&board.make_move_new(chess_move), line);
+            line.pop();
+            if reaches {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// One sentence on what `chess_move` does in `board`, from the move itself and the position it leaves
+pub fn explain_move(board: &Board, chess_move: ChessMove, previous: Option<ChessMove>) -> String {
+    let side = if board.side_to_move() == Color::White { "White" } else { "Black" };
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let piece = board.piece_on(source).unwrap_or(Piece::Pawn);
+    let name = piece_name(piece).to_lowercase();
+    let after = board.make_move_new(chess_move);
+    let mut clauses = vec![];
+
+    let captures = board.piece_on(dest).is_some();
+    if piece == Piece::King && source.get_file().to_index().abs_diff(dest.get_file().to_index()) == 2 {
+        clauses.push("castles, tucking the king away and bringing the rook toward the centre".to_string());
+    } else if let Some(taken) = board.piece_on(dest) {
+        let verb = if previous.is_some_and(|m| m.get_dest() == dest) { "recaptures" } else { "takes" };
+        clauses.push(format!("{} the {} on {}", verb, piece_name(taken).to_lowercase(), dest));
+    } else if piece == Piece::Pawn {
+        clauses.push(pawn_idea(&after, dest));
+    } else if matches!(piece, Piece::Knight | Piece::Bishop) && source.get_rank() == board.side_to_move().to_my_backrank() {
+        clauses.push(format!("develops the {}", name));
+    } else {
+        clauses.push(format!("brings the {} to {}", name, dest));
+    }
+
+    // What the king covers isn't worth a clause
+    let attacked = if piece == Piece::King { EMPTY } else { attacks_from(&after, dest) };
+    let target = |square: Square| after.piece_on(square).map(piece_name).unwrap_or("piece").to_lowercase();
+    if *after.checkers() != EMPTY {
+        clauses.push("gives check".to_string());
+    } else if let Some(square) = (*after.pinned() & attacked).into_iter().next() {
+        clauses.push(format!("pins the {} on {} to the king", target(square), square));
+    } else if piece != Piece::Pawn && !captures {
+        let enemies = attacked & *after.color_combined(after.side_to_move());
+        if let Some(square) = enemies.into_iter().find(|sq| after.piece_on(*sq) != Some(Piece::King)) {
+            clauses.push(format!("eyes the {} on {}", target(square), square));
+        }
+    }
+
+    let sentence = match clauses.as_slice() {
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+        [] => "makes a useful move".to_string(),
+    };
+    format!("{}: {} {}.", to_san(board, chess_move), side, sentence)
+}
+
+fn pawn_idea(after: &Board, dest: Square) -> String {
+    let file = dest.get_file().to_index();
+    if is_offered(after, dest) {
+        format!("offers the {}-pawn", (b'a' + file as u8) as char)
+    } else if CENTRE.contains(&dest) {
+        "stakes a claim in the centre".to_string()
+    } else if matches!(file, 2 | 5) {
+        "fights for the centre from the side".to_string()
+    } else if matches!(dest, Square::B3 | Square::G3 | Square::B6 | Square::G6) {
+        "makes room to fianchetto the bishop".to_string()
+    } else {
+        let mover = !after.side_to_move();
+        let covered: Vec<String> = piece_attacks(Piece::Pawn, mover, dest, EMPTY).map(|sq| sq.to_string()).collect();
+        format!("takes {} away from the opponent's pieces", covered.join(" and "))
+    }
+}
+
+/// Whether the pawn just moved to `dest` can be taken without being won back there
+fn is_offered(after: &Board, dest: Square) -> bool {
+    MoveGen::new_legal(after)
+        .filter(|m| m.get_dest() == dest)
+        .any(|capture| !MoveGen::new_legal(&after.make_move_new(capture)).any(|m| m.get_dest() == dest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_core::opening_by_key;
+
+    fn walkthrough(key: &str) -> OpeningWalkthrough {
+        OpeningWalkthrough::new(opening_by_key(key).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_walkthrough_accepts_transpositions() {
+        let italian = walkthrough("c50-italian-game");
+        assert_eq!(italian.steps.len(), 5);
+        assert_eq!(italian.steps[0].explanation, "e4: White stakes a claim in the centre. This is the King's Pawn Game (B00).");
+        assert_eq!(italian.steps[1].san, "e5");
+        assert!(italian.steps[2].explanation.contains("develops the knight and eyes the pawn on e5"));
+        assert!(italian.steps[4].explanation.ends_with("This is the Italian Game (C50)."));
+
+        // 1.Nf3 first still reaches the Italian
+        let answer = italian.answer(&[], "Nf3").unwrap();
+        assert_eq!(answer.verdict, QuizVerdict::Transposition);
+        assert_eq!(answer.reply.as_deref(), Some("e5"));
+        let answer = italian.answer(&answer.played, "e2e4").unwrap();
+        assert_eq!(answer.verdict, QuizVerdict::Transposition);
+        assert_eq!(answer.reply.as_deref(), Some("Nc6"));
+        let answer = italian.answer(&answer.played, "Bc4").unwrap();
+        assert!(answer.completed);
+        assert!(italian.is_complete(&answer.played));
+
+        // Leaving the line is wrong and points back at it
+        let wrong = italian.answer(&[], "Nc3").unwrap();
+        assert_eq!(wrong.verdict, QuizVerdict::Wrong);
+        assert_eq!(wrong.expected.as_deref(), Some("e4"));
+        assert!(wrong.played.is_empty());
+        assert!(italian.answer(&[], "Bc4").is_err());
+
+        let mainline = italian.answer(&[], "e4").unwrap();
+        assert_eq!(mainline.verdict, QuizVerdict::Mainline);
+        assert_eq!(mainline.played, vec!["e2e4", "e7e5"]);
+
+        let gambit = walkthrough("d06-queens-gambit");
+        assert!(gambit.steps[2].explanation.contains("offers the c-pawn"));
+        let nimzo = walkthrough("e20-nimzo-indian-defence");
+        assert!(nimzo.steps[5].explanation.contains("pins the knight on c3 to the king"));
+    }
+}
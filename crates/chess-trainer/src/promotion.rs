@@ -0,0 +1,113 @@
+use chess::{Board, BoardStatus, ChessMove, Color, MoveGen, Piece};
+use chess_core::{to_san, GamePhase};
+use chess_engine::Engine;
+
+use crate::exercise::{Exercise, ExerciseDifficulty, ExerciseType};
+
+/// Deep enough to see a promotion, the reply and the fork or mate that follows
+const SEARCH_DEPTH: u32 = 3;
+/// Centipawns an underpromotion must reach to count as a win
+const WINNING_SCORE: i32 = 300;
+/// Lead an underpromotion needs over every move that isn't one, queening included
+const ONLY_WIN_MARGIN: i32 = 200;
+
+/// A promotion to anything but a queen
+pub fn is_underpromotion(chess_move: ChessMove) -> bool {
+    chess_move.get_promotion().is_some_and(|piece| piece != Piece::Queen)
+}
+
+/// An exercise on `board` if the side to move wins only by underpromoting:
+/// queening stalemates or lets the defence hold, while a rook, bishop or
+/// knight wins. Every underpromotion that wins by `ONLY_WIN_MARGIN` solves it.
+pub fn underpromotion_exercise(board: &Board) -> Option<Exercise> {
+    // Searching is only worth it when a pawn can promote
+    if !MoveGen::new_legal(board).any(|m| m.get_promotion().is_some()) {
+        return None;
+    }
+    let engine = Engine::builder().depth(SEARCH_DEPTH).build().expect("depth within MAX_DEPTH");
+    let ranked = engine.rank_moves(board);
+    let best_other = ranked
+        .iter()
+        .filter(|e| !is_underpromotion(e.chess_move))
+        .map(|e| e.score.centipawns())
+        .max()?;
+    let winners: Vec<ChessMove> = ranked
+        .iter()
+        .filter(|e| is_underpromotion(e.chess_move))
+        .filter(|e| e.score.centipawns() >= WINNING_SCORE && e.score.centipawns() >= best_other + ONLY_WIN_MARGIN)
+        .map(|e| e.chess_move)
+        .collect();
+    let best = *winners.first()?;
+    let queening = ChessMove::new(best.get_source(), best.get_dest(), Some(Piece::Queen));
+
+    let stalemates = board.make_move_new(queening).status() == BoardStatus::Stalemate;
+    let checks = *board.make_move_new(best).checkers() != chess::EMPTY;
+    let (hint, why) = if stalemates {
+        (
+            "Count the king's moves after each promotion. A queen isn't always best.",
+            format!("{} would be stalemate.", to_san(board, queening)),
+        )
+    } else if checks {
+        (
+            "Which new piece gives check as it lands?",
+            "The check comes first, and whatever it attacks falls next.".to_string(),
+        )
+    } else {
+        (
+            "A queen isn't always best. Try each promotion in your head.",
+            format!("After {} the defence holds.", to_san(board, queening)),
+        )
+    };
+
+    let side = if board.side_to_move() == Color::White { "White" } else { "Black" };
+    let exercise_type = match GamePhase::of(board) {
+        GamePhase::Endgame => ExerciseType::Endgame,
+        _ => ExerciseType::Tactics,
+    };
+    Some(
+        Exercise::new(
+            exercise_type,
+            ExerciseDifficulty::Advanced,
+            board.to_string(),
+            "Underpromotion".to_string(),
+            format!("{} to move and win.", side),
+            winners.iter().map(|m| m.to_string()).collect(),
+            format!("{} wins. {}", to_san(board, best), why),
+        )
+        .with_hints(vec![hint.to_string()]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise::ExerciseLibrary;
+    use chess_core::parse_move;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_finds_only_the_underpromotions_that_win() {
+        // Every underpromotion puzzle in the library is one the generator would find
+        for exercise in ExerciseLibrary::get_promotion_exercises() {
+            let board = exercise.get_board().unwrap();
+            let solution = parse_move(&board, &exercise.solution_moves[0]).unwrap();
+            if !is_underpromotion(solution) {
+                continue;
+            }
+            let generated = underpromotion_exercise(&board).unwrap_or_else(|| panic!("{} not generated", exercise.title));
+            assert!(generated.solution_moves.contains(&solution.to_string()), "{}", exercise.title);
+        }
+
+        // Rook rather than queen, since the queen stalemates
+        let stalemate = Board::from_str("5N2/4P3/5K1k/8/8/8/8/8 w - - 0 1").unwrap();
+        let exercise = underpromotion_exercise(&stalemate).unwrap();
+        assert!(exercise.solution_moves.contains(&"e7e8r".to_string()));
+        assert!(!exercise.solution_moves.contains(&"e7e8q".to_string()));
+        assert!(exercise.explanation.contains("e8=Q would be stalemate"));
+
+        // Queening wins, so there is nothing to find
+        let queening = Board::from_str("8/4P3/8/8/8/k7/8/4K3 w - - 0 1").unwrap();
+        assert!(underpromotion_exercise(&queening).is_none());
+        assert!(underpromotion_exercise(&Board::default()).is_none());
+    }
+}
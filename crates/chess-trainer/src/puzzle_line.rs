@@ -0,0 +1,162 @@
+use chess::{Board, BoardStatus};
+use chess_core::{parse_fen, parse_move, to_san};
+use serde::{Deserialize, Serialize};
+
+use crate::exercise::Exercise;
+
+/// What one move in a multi-move puzzle led to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LineStep {
+    /// On the line; the opponent answered and it is the solver's move again
+    Continue {
+        /// The opponent's answer in UCI and SAN
+        reply: String,
+        reply_san: String,
+        /// Position after the answer
+        fen: String,
+    },
+    /// The line's last move was played, or a mate that ends the game just as well
+    Solved,
+    /// Off the line; `expected` is the move it wanted, in SAN
+    Deviated { expected: String },
+}
+
+/// How far a solver has got through an exercise's `solution_line`. A wrong move
+/// ends the attempt; retrying starts a fresh progress.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineProgress {
+    /// Plies of the line played so far, the opponent's replies included
+    pub played: usize,
+}
+
+impl LineProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_finished(&self, exercise: &Exercise) -> bool {
+        self.played >= exercise.solution_line.len()
+    }
+
+    /// The position in front of the solver
+    pub fn board(&self, exercise: &Exercise) -> Result<Board, String> {
+        let mut board = exercise.get_board()?;
+        for text in exercise.solution_line.iter().take(self.played) {
+            let chess_move = parse_move(&board, text).map_err(|e| format!("Bad solution line: {}", e))?;
+            board = board.make_move_new(chess_move);
+        }
+        Ok(board)
+    }
+
+    /// Check `user_move` (UCI or SAN) against the line and, if it is on it, play the
+    /// opponent's reply. Any mate solves the puzzle, as on Lichess: a move that ends
+    /// the game can't be worse than the stored one.
+    pub fn play(&mut self, exercise: &Exercise, user_move: &str) -> Result<LineStep, String> {
+        let line = &exercise.solution_line;
+        let board = self.board(exercise)?;
+        let wanted = line.get(self.played).ok_or_else(|| "The puzzle is already solved".to_string())?;
+        let expected = parse_move(&board, wanted).map_err(|e| format!("Bad solution line: {}", e))?;
+
+        let played = parse_move(&board, user_move).ok();
+        let mates = played.is_some_and(|m| board.make_move_new(m).status() == BoardStatus::Checkmate);
+        if played != Some(expected) && !mates {
+            return Ok(LineStep::Deviated { expected: to_san(&board, expected) });
+        }
+        if mates || self.played + 1 >= line.len() {
+            self.played = line.len();
+            return Ok(LineStep::Solved);
+        }
+
+        let after = board.make_move_new(expected);
+        let reply = parse_move(&after, &line[self.played + 1]).map_err(|e| format!("Bad solution line: {}", e))?;
+        self.played += 2;
+        // A line that ends on the opponent's move is done once it is played
+        if self.is_finished(exercise) {
+            return Ok(LineStep::Solved);
+        }
+        Ok(LineStep::Continue {
+            reply: reply.to_string(),
+            reply_san: to_san(&after, reply),
+            fen: after.make_move_new(reply).to_string(),
+        })
+    }
+}
+
+/// Check each move of `line` is legal in turn from `fen`, returning them in UCI
+pub fn validate_line(fen: &str, line: &[String]) -> Result<Vec<String>, String> {
+    let mut board = parse_fen(fen).map_err(|e| format!("Bad puzzle position: {}", e))?;
+    let mut uci = Vec::with_capacity(line.len());
+    for (ply, text) in line.iter().enumerate() {
+        let chess_move = parse_move(&board, text).map_err(|e| format!("Move {} of the line: {}", ply + 1, e))?;
+        uci.push(chess_move.to_string());
+        board = board.make_move_new(chess_move);
+    }
+    Ok(uci)
+}
+
+/// Read a puzzle the way the Lichess puzzle database writes it: `fen` is the
+/// position before the opponent's last move, and `moves` start with that move.
+/// Returns the position the solver sees and the line from there, in UCI.
+pub fn from_lichess(fen: &str, moves: &[String]) -> Result<(String, Vec<String>), String> {
+    let board = parse_fen(fen).map_err(|e| format!("Bad puzzle position: {}", e))?;
+    let (setup, line) = moves
+        .split_first()
+        .filter(|(_, line)| !line.is_empty())
+        .ok_or_else(|| "A puzzle needs the opponent's move and at least one answer".to_string())?;
+    let setup = parse_move(&board, setup).map_err(|e| format!("Opponent's move: {}", e))?;
+    let start = board.make_move_new(setup).to_string();
+    let line = validate_line(&start, line)?;
+    Ok((start, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise::ExerciseLibrary;
+
+    fn line(moves: &[&str]) -> Vec<String> {
+        moves.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn test_plays_through_the_line_and_fails_on_deviation() {
+        let exercise = ExerciseLibrary::get_combination_exercises()
+            .into_iter()
+            .find(|e| e.title == "Back Rank Breakthrough")
+            .unwrap();
+        assert!(exercise.is_multi_move());
+        assert_eq!(exercise.moves_to_find(), 2);
+
+        let mut progress = LineProgress::new();
+        match progress.play(&exercise, "e2e8").unwrap() {
+            LineStep::Continue { reply, reply_san, fen } => {
+                assert_eq!((reply.as_str(), reply_san.as_str()), ("c8e8", "Rxe8"));
+                assert_eq!(fen, progress.board(&exercise).unwrap().to_string());
+            }
+            other => panic!("expected the rook to take, got {:?}", other),
+        }
+        assert_eq!(progress.play(&exercise, "Rxe8#").unwrap(), LineStep::Solved);
+        assert!(progress.is_finished(&exercise));
+        assert!(progress.play(&exercise, "Rxe8").is_err());
+
+        let mut wrong = LineProgress::new();
+        assert_eq!(wrong.play(&exercise, "h2h3").unwrap(), LineStep::Deviated { expected: "Qe8+".to_string() });
+        assert_eq!(wrong.played, 0);
+    }
+
+    #[test]
+    fn test_reads_lichess_puzzles() {
+        // Black develops the knight and walks into Nc7+, forking king and rook
+        let (fen, moves) = from_lichess(
+            "r3k3/pp1n1ppp/8/1N6/8/8/5PPP/6K1 b - - 0 1",
+            &line(&["d7f6", "b5c7", "e8e7", "c7a8"]),
+        )
+        .unwrap();
+        assert_eq!(fen, "r3k3/pp3ppp/5n2/1N6/8/8/5PPP/6K1 w - - 0 1");
+        assert_eq!(moves, line(&["b5c7", "e8e7", "c7a8"]));
+
+        assert!(from_lichess("r3k3/pp1n1ppp/8/1N6/8/8/5PPP/6K1 b - - 0 1", &line(&["d7f6"])).is_err());
+        assert!(validate_line(&fen, &line(&["Nc7+", "Ke6"])).unwrap_err().starts_with("Move 2"));
+    }
+}
@@ -0,0 +1,166 @@
+use chess::{Board, ChessMove, Color, MoveGen, Piece, Square, EMPTY};
+use chess_core::bitboards::{attackers_of, squares};
+use chess_core::notation::piece_name;
+use chess_core::{parse_move, to_san};
+
+use crate::exercise::Exercise;
+
+/// A leading question the coach asks on the way to a solution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaffoldStep {
+    Threats,
+    LoosePieces,
+    ForcingMoves,
+    PieceToMove,
+    TargetSquare,
+}
+
+crate::named_enum!(ScaffoldStep, "scaffold step", {
+    ScaffoldStep::Threats => "Threats",
+    ScaffoldStep::LoosePieces => "Loose Pieces",
+    ScaffoldStep::ForcingMoves => "Forcing Moves",
+    ScaffoldStep::PieceToMove => "Piece To Move",
+    ScaffoldStep::TargetSquare => "Target Square",
+});
+
+impl ScaffoldStep {
+    /// Steps in the order they are asked, each giving away more than the one before
+    pub const LADDER: [ScaffoldStep; 5] = [
+        ScaffoldStep::Threats,
+        ScaffoldStep::LoosePieces,
+        ScaffoldStep::ForcingMoves,
+        ScaffoldStep::PieceToMove,
+        ScaffoldStep::TargetSquare,
+    ];
+
+    /// The step to ask after `asked`, or none once the ladder is used up
+    pub fn after(asked: &[ScaffoldStep]) -> Option<ScaffoldStep> {
+        Self::LADDER.get(asked.len()).copied()
+    }
+
+    /// Whether the step points at the solution itself rather than at the position
+    pub fn reveals_solution(self) -> bool {
+        matches!(self, ScaffoldStep::PieceToMove | ScaffoldStep::TargetSquare)
+    }
+}
+
+/// The question for one step, with what the coach can check the answer against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scaffold {
+    pub step: ScaffoldStep,
+    pub question: String,
+    /// For the coach only; the student is meant to find these
+    pub facts: String,
+}
+
+fn describe(board: &Board, found: &[Square]) -> String {
+    let named: Vec<String> = found
+        .iter()
+        .filter_map(|sq| board.piece_on(*sq).map(|p| format!("{} on {}", piece_name(p).to_lowercase(), sq)))
+        .collect();
+    named.join(", ")
+}
+
+/// `color` pieces, kings aside, that no piece of their own side defends
+fn undefended(board: &Board, color: Color) -> Vec<Square> {
+    squares(*board.color_combined(color) & !*board.pieces(Piece::King))
+        .into_iter()
+        .filter(|sq| attackers_of(board, *sq, color) == EMPTY)
+        .collect()
+}
+
+fn solution_move(exercise: &Exercise, board: &Board) -> Option<ChessMove> {
+    parse_move(board, exercise.solution_moves.first()?).ok()
+}
+
+/// Build the question for `step` about `exercise`'s position, from the side to move's view
+pub fn scaffold(exercise: &Exercise, step: ScaffoldStep) -> Result<Scaffold, String> {
+    let board = exercise.get_board()?;
+    let us = board.side_to_move();
+    let them = !us;
+
+    let (question, facts) = match step {
+        ScaffoldStep::Threats => {
+            let hanging: Vec<Square> =
+                undefended(&board, us).into_iter().filter(|sq| attackers_of(&board, *sq, them) != EMPTY).collect();
+            let mut facts = vec![];
+            if *board.checkers() != EMPTY {
+                facts.push("The student is in check.".to_string());
+            }
+            facts.push(match hanging.as_slice() {
+                [] => "None of the student's pieces is attacked and undefended.".to_string(),
+                found => format!("Attacked and undefended: {}.", describe(&board, found)),
+            });
+            ("Before looking for your own move: what is your opponent threatening?".to_string(), facts.join(" "))
+        }
+        ScaffoldStep::LoosePieces => {
+            let loose = undefended(&board, them);
+            let facts = match loose.as_slice() {
+                [] => "Every opposing piece is defended; the target may be the king.".to_string(),
+                found => format!("Undefended opposing pieces: {}.", describe(&board, found)),
+            };
+            ("Which of your opponent's pieces are undefended?".to_string(), facts)
+        }
+        ScaffoldStep::ForcingMoves => {
+            let forcing: Vec<String> = MoveGen::new_legal(&board)
+                .filter(|m| board.piece_on(m.get_dest()).is_some() || *board.make_move_new(*m).checkers() != EMPTY)
+                .map(|m| to_san(&board, m))
+                .collect();
+            let facts = if forcing.is_empty() {
+                "There are no checks or captures; the solution is a quiet move.".to_string()
+            } else {
+                format!("Checks and captures available: {}.", forcing.join(", "))
+            };
+            ("What checks and captures do you have here?".to_string(), facts)
+        }
+        ScaffoldStep::PieceToMove | ScaffoldStep::TargetSquare => {
+            let solution = solution_move(exercise, &board).ok_or("The exercise has no playable solution")?;
+            let source = solution.get_source();
+            let piece = board.piece_on(source).map(piece_name).unwrap_or("piece").to_lowercase();
+            if step == ScaffoldStep::PieceToMove {
+                (
+                    format!("Look at your {} on {}. What could it do from here?", piece, source),
+                    format!("The solution moves the {} on {}.", piece, source),
+                )
+            } else {
+                let dest = solution.get_dest();
+                (
+                    format!("Where could your {} on {} go? Have a look at {}.", piece, source, dest),
+                    format!("The solution is {}.", to_san(&board, solution)),
+                )
+            }
+        }
+    };
+    Ok(Scaffold { step, question, facts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise::{ExerciseDifficulty, ExerciseType};
+
+    #[test]
+    fn test_ladder_narrows_onto_the_fork() {
+        let fork = Exercise::new(
+            ExerciseType::Tactics,
+            ExerciseDifficulty::Beginner,
+            "q3k3/8/8/1N6/8/8/8/6K1 w - - 0 1".to_string(),
+            "Knight fork".to_string(),
+            "Win the queen.".to_string(),
+            vec!["Nc7+".to_string()],
+            "The knight checks and attacks the queen.".to_string(),
+        );
+        let steps: Vec<Scaffold> = ScaffoldStep::LADDER.iter().map(|s| scaffold(&fork, *s).unwrap()).collect();
+
+        assert_eq!(steps[0].facts, "None of the student's pieces is attacked and undefended.");
+        assert_eq!(steps[1].facts, "Undefended opposing pieces: queen on a8.");
+        assert!(steps[2].facts.contains("Nc7+") && steps[2].facts.contains("Nd6+"));
+        assert_eq!(steps[3].question, "Look at your knight on b5. What could it do from here?");
+        assert_eq!(steps[4].facts, "The solution is Nc7+.");
+        assert!(!steps[1].question.contains("a8"));
+
+        assert_eq!(ScaffoldStep::after(&[]), Some(ScaffoldStep::Threats));
+        assert_eq!(ScaffoldStep::after(&ScaffoldStep::LADDER), None);
+        assert_eq!("loose_pieces".parse::<ScaffoldStep>().unwrap(), ScaffoldStep::LoosePieces);
+    }
+}
@@ -0,0 +1,272 @@
+use chess_core::{GamePhase, SeededRng};
+use serde::{Deserialize, Serialize};
+use crate::exercise::{Exercise, ExerciseDifficulty, ExerciseLibrary, ExerciseResult, ExerciseType};
+use crate::strategy::{Strategy, StrategyLibrary};
+
+/// Solves at or under this time count towards stepping up
+pub const FAST_SOLVE_SECONDS: u32 = 30;
+/// Quick solves in a row before puzzles get harder
+pub const STEP_UP_STREAK: u32 = 3;
+/// Failures in a row before puzzles get easier
+pub const STEP_DOWN_FAILURES: u32 = 2;
+
+const DEFAULT_MAX_EXERCISES: usize = 10;
+
+/// A concept to review before continuing, offered after repeated failures
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConceptRefresher {
+    pub strategy: String,
+    pub concept: String,
+    pub description: String,
+}
+
+/// Chooses exercises one at a time as a session progresses
+pub trait ExerciseSelector {
+    /// The next exercise given everything served so far, or `None` to end the session
+    fn next_exercise(&mut self, served: &[Exercise]) -> Option<Exercise>;
+
+    /// Called after each recorded result so the selector can adapt
+    fn observe(&mut self, _exercise: &Exercise, _result: &ExerciseResult) {}
+
+    /// A concept to review before the next exercise; returned once
+    fn take_refresher(&mut self) -> Option<ConceptRefresher> {
+        None
+    }
+}
+
+/// Steps difficulty up after a run of quick solves and down after repeated
+/// failures, queueing a refresher on the concept that was missed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveSelector {
+    strategies: Vec<Strategy>,
+    difficulty: ExerciseDifficulty,
+    max_exercises: usize,
+    quick_streak: u32,
+    failures: u32,
+    refreshers_given: usize,
+    pending_refresher: Option<ConceptRefresher>,
+    /// Breaks ties between equally suitable exercises reproducibly instead of
+    /// always taking the first in library order
+    #[serde(default)]
+    seed: Option<u64>,
+    /// Only serve exercises from these phases; empty allows every phase
+    #[serde(default)]
+    phases: Vec<GamePhase>,
+}
+
+impl AdaptiveSelector {
+    pub fn new(strategies: Vec<Strategy>, difficulty: ExerciseDifficulty) -> Self {
+        Self {
+            strategies,
+            difficulty,
+            max_exercises: DEFAULT_MAX_EXERCISES,
+            quick_streak: 0,
+            failures: 0,
+            refreshers_given: 0,
+            pending_refresher: None,
+            seed: None,
+            phases: Vec::new(),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Restrict the session to exercises from `phases`, e.g. endgames only
+    pub fn with_phases(mut self, phases: Vec<GamePhase>) -> Self {
+        self.phases = phases;
+        self
+    }
+
+    pub fn with_max_exercises(mut self, max_exercises: usize) -> Self {
+        self.max_exercises = max_exercises;
+        self
+    }
+
+    /// Difficulty the next exercise will be pitched at
+    pub fn difficulty(&self) -> &ExerciseDifficulty {
+        &self.difficulty
+    }
+
+    fn allows(&self, exercise_type: &ExerciseType) -> bool {
+        self.strategies.is_empty() || self.strategies.iter().any(|s| s.recommended_exercises.contains(exercise_type))
+    }
+
+    fn in_phase(&self, exercise: &Exercise) -> bool {
+        self.phases.is_empty() || self.phases.contains(&exercise.phase)
+    }
+
+    fn distance(&self, exercise: &Exercise) -> i32 {
+        (exercise.difficulty.clone() as i32 - self.difficulty.clone() as i32).abs()
+    }
+
+    fn refresher_for(&mut self, exercise_type: &ExerciseType) -> Option<ConceptRefresher> {
+        let strategy = self
+            .strategies
+            .iter()
+            .find(|s| s.recommended_exercises.contains(exercise_type))
+            .cloned()
+            .or_else(|| {
+                StrategyLibrary::get_all_strategies()
+                    .into_iter()
+                    .find(|s| s.recommended_exercises.contains(exercise_type))
+            })?;
+        if strategy.key_concepts.is_empty() {
+            return None;
+        }
+
+        // Rotate through the strategy's concepts on repeat refreshers
+        let concept = strategy.key_concepts[self.refreshers_given % strategy.key_concepts.len()].clone();
+        self.refreshers_given += 1;
+        Some(ConceptRefresher {
+            strategy: strategy.name,
+            concept,
+            description: strategy.description,
+        })
+    }
+}
+
+fn same_exercise(a: &Exercise, b: &Exercise) -> bool {
+    a.position == b.position && a.title == b.title
+}
+
+impl ExerciseSelector for AdaptiveSelector {
+    fn next_exercise(&mut self, served: &[Exercise]) -> Option<Exercise> {
+        if served.len() >= self.max_exercises {
+            return None;
+        }
+
+        let mut pool: Vec<Exercise> = ExerciseLibrary::get_all_exercises()
+            .into_iter()
+            .filter(|e| self.allows(&e.exercise_type) && self.in_phase(e))
+            .collect();
+        if let Some(seed) = self.seed {
+            SeededRng::derive(seed, served.len() as u64).shuffle(&mut pool);
+        }
+
+        // Unseen exercises closest to the current level first, then whichever was served longest ago
+        let last_served = |exercise: &Exercise| served.iter().rposition(|s| same_exercise(s, exercise));
+        pool.into_iter()
+            .min_by_key(|e| (last_served(e).map_or(-1, |i| i as i64), self.distance(e)))
+    }
+
+    fn observe(&mut self, exercise: &Exercise, result: &ExerciseResult) {
+        if !result.solved {
+            self.quick_streak = 0;
+            self.failures += 1;
+            if self.failures >= STEP_DOWN_FAILURES {
+                self.failures = 0;
+                self.difficulty = self.difficulty.easier();
+                self.pending_refresher = self.refresher_for(&exercise.exercise_type);
+                tracing::debug!(difficulty = ?self.difficulty, "stepping training difficulty down");
+            }
+            return;
+        }
+
+        self.failures = 0;
+        // A solve the coach walked the student through shows the level is about right, not too easy
+        let unaided = result.scaffolding_steps == 0;
+        if unaided && result.time_taken_seconds <= FAST_SOLVE_SECONDS && result.attempts <= 1 {
+            self.quick_streak += 1;
+            if self.quick_streak >= STEP_UP_STREAK {
+                self.quick_streak = 0;
+                self.difficulty = self.difficulty.harder();
+                tracing::debug!(difficulty = ?self.difficulty, "stepping training difficulty up");
+            }
+        } else {
+            self.quick_streak = 0;
+        }
+    }
+
+    fn take_refresher(&mut self) -> Option<ConceptRefresher> {
+        self.pending_refresher.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(solved: bool, seconds: u32) -> ExerciseResult {
+        ExerciseResult {
+            solved,
+            attempts: 1,
+            time_taken_seconds: seconds,
+            ..ExerciseResult::new(0, 1)
+        }
+    }
+
+    #[test]
+    fn test_steps_up_after_quick_streak() {
+        let mut selector = AdaptiveSelector::new(Vec::new(), ExerciseDifficulty::Beginner);
+        let exercise = selector.next_exercise(&[]).unwrap();
+        assert_eq!(exercise.difficulty, ExerciseDifficulty::Beginner);
+
+        selector.observe(&exercise, &result(true, 10));
+        selector.observe(&exercise, &result(true, 90));
+        selector.observe(&exercise, &ExerciseResult { scaffolding_steps: 2, ..result(true, 10) });
+        selector.observe(&exercise, &result(true, 10));
+        selector.observe(&exercise, &result(true, 10));
+        assert_eq!(selector.difficulty(), &ExerciseDifficulty::Beginner);
+
+        selector.observe(&exercise, &result(true, 10));
+        assert_eq!(selector.difficulty(), &ExerciseDifficulty::Intermediate);
+        assert_eq!(selector.next_exercise(&[]).unwrap().difficulty, ExerciseDifficulty::Intermediate);
+    }
+
+    #[test]
+    fn test_steps_down_with_refresher_after_failures() {
+        let strategies = vec![StrategyLibrary::get_tactical_awareness()];
+        let mut selector = AdaptiveSelector::new(strategies, ExerciseDifficulty::Intermediate);
+        let exercise = selector.next_exercise(&[]).unwrap();
+        assert_eq!(exercise.exercise_type, ExerciseType::Tactics);
+
+        selector.observe(&exercise, &result(false, 60));
+        assert!(selector.take_refresher().is_none());
+        selector.observe(&exercise, &result(false, 60));
+
+        assert_eq!(selector.difficulty(), &ExerciseDifficulty::Beginner);
+        let refresher = selector.take_refresher().unwrap();
+        assert_eq!(refresher.strategy, "Tactical Awareness");
+        assert!(selector.take_refresher().is_none());
+    }
+
+    #[test]
+    fn test_prefers_unseen_exercises_and_stops_at_limit() {
+        let mut selector = AdaptiveSelector::new(Vec::new(), ExerciseDifficulty::Beginner).with_max_exercises(2);
+        let first = selector.next_exercise(&[]).unwrap();
+        let second = selector.next_exercise(std::slice::from_ref(&first)).unwrap();
+
+        assert!(!same_exercise(&first, &second));
+        assert!(selector.next_exercise(&[first, second]).is_none());
+    }
+
+    #[test]
+    fn test_phase_filter() {
+        let mut selector = AdaptiveSelector::new(Vec::new(), ExerciseDifficulty::Beginner)
+            .with_phases(vec![GamePhase::Endgame])
+            .with_max_exercises(6);
+        let mut served: Vec<Exercise> = Vec::new();
+        while let Some(exercise) = selector.next_exercise(&served) {
+            served.push(exercise);
+        }
+        assert_eq!(served.len(), 6);
+        assert!(served.iter().all(|e| e.phase == GamePhase::Endgame));
+    }
+
+    #[test]
+    fn test_seeded_selection_is_reproducible() {
+        let run = |seed: u64| {
+            let mut selector = AdaptiveSelector::new(Vec::new(), ExerciseDifficulty::Beginner).with_seed(seed);
+            let mut served: Vec<Exercise> = Vec::new();
+            for _ in 0..4 {
+                let exercise = selector.next_exercise(&served).unwrap();
+                served.push(exercise);
+            }
+            served.into_iter().map(|e| e.title).collect::<Vec<_>>()
+        };
+        assert_eq!(run(11), run(11));
+    }
+}
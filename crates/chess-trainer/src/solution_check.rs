@@ -0,0 +1,137 @@
+use chess::{Board, ChessMove, MoveGen};
+use chess_core::parse_move;
+use chess_engine::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::exercise::Exercise;
+
+/// Plies searched after each candidate move at least: the reply and our next move,
+/// so a mate in two is seen from either side
+const REPLY_DEPTH: u32 = 2;
+
+/// How a move answered an exercise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolutionMatch {
+    /// One of the exercise's stored solutions
+    Canonical,
+    /// Not stored, but the engine rates it at least as good as the stored solution
+    Alternative,
+    Wrong,
+}
+
+impl SolutionMatch {
+    pub fn is_correct(self) -> bool {
+        self != SolutionMatch::Wrong
+    }
+}
+
+/// Checks answers against an exercise's stored solutions, then lets the engine
+/// accept alternatives that score at least as well as the best of them, searched
+/// deep enough to see the whole solution line. Where the search can't cover the
+/// line, or rates the stored solution more than `margin` centipawns below its own
+/// best move, it doesn't understand the puzzle and only the stored moves count.
+#[derive(Debug, Clone)]
+pub struct SolutionChecker {
+    margin: i32,
+}
+
+impl SolutionChecker {
+    pub fn new(margin: i32) -> Self {
+        Self { margin: margin.max(0) }
+    }
+
+    /// `user_move` in UCI or SAN
+    pub fn check(&self, exercise: &Exercise, user_move: &str) -> SolutionMatch {
+        if exercise.check_solution(user_move) {
+            return SolutionMatch::Canonical;
+        }
+        let Ok(board) = exercise.get_board() else {
+            return SolutionMatch::Wrong;
+        };
+        let Ok(played) = parse_move(&board, user_move) else {
+            return SolutionMatch::Wrong;
+        };
+        let solutions: Vec<ChessMove> =
+            exercise.solution_moves.iter().filter_map(|s| parse_move(&board, s).ok()).collect();
+        if solutions.contains(&played) {
+            return SolutionMatch::Canonical;
+        }
+
+        let Some(engine) = line_engine(exercise) else {
+            return SolutionMatch::Wrong;
+        };
+        let Some(best) = solutions.iter().map(|m| score(&engine, &board, *m)).max() else {
+            return SolutionMatch::Wrong;
+        };
+        let top = MoveGen::new_legal(&board).map(|m| score(&engine, &board, m)).max().unwrap_or(best);
+        if best < top - self.margin {
+            return SolutionMatch::Wrong;
+        }
+        if score(&engine, &board, played) >= best {
+            SolutionMatch::Alternative
+        } else {
+            SolutionMatch::Wrong
+        }
+    }
+}
+
+/// An engine searching every ply of the solution line after its first move, or
+/// None when the line is longer than the engine can search
+fn line_engine(exercise: &Exercise) -> Option<Engine> {
+    let depth = (exercise.solution_line.len() as u32).max(REPLY_DEPTH);
+    Engine::builder().depth(depth).build().ok()
+}
+
+/// Centipawns for the side to move in `board` after `chess_move`. Mates score
+/// the same however long they take, so any mate the search sees matches another.
+fn score(engine: &Engine, board: &Board, chess_move: ChessMove) -> i32 {
+    -engine.evaluate(&board.make_move_new(chess_move)).centipawns()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise::{ExerciseDifficulty, ExerciseType};
+
+    fn exercise(fen: &str, solutions: &[&str]) -> Exercise {
+        Exercise::new(
+            ExerciseType::Tactics,
+            ExerciseDifficulty::Beginner,
+            fen.to_string(),
+            "Test".to_string(),
+            String::new(),
+            solutions.iter().map(|s| s.to_string()).collect(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_accepts_equally_good_alternatives() {
+        let checker = SolutionChecker::new(30);
+
+        // Both rooks mate on the back rank; only one is stored
+        let back_rank = exercise("6k1/5ppp/8/8/8/8/5PPP/R3R1K1 w - - 0 1", &["Ra8"]);
+        assert_eq!(checker.check(&back_rank, "Ra8"), SolutionMatch::Canonical);
+        assert_eq!(checker.check(&back_rank, "a1a8"), SolutionMatch::Canonical);
+        assert_eq!(checker.check(&back_rank, "e1e8"), SolutionMatch::Alternative);
+        assert_eq!(checker.check(&back_rank, "g1f1"), SolutionMatch::Wrong);
+
+        let hanging = exercise("4k3/8/8/3q4/4P3/2N5/8/4K3 w - - 0 1", &["Nxd5"]);
+        let lenient = SolutionChecker::new(100);
+        assert_eq!(lenient.check(&hanging, "Kd2"), SolutionMatch::Wrong);
+        assert_eq!(lenient.check(&hanging, "e9e5"), SolutionMatch::Wrong);
+        assert!(!SolutionMatch::Wrong.is_correct());
+
+        // A stored solution the search rates far below its own best move is beyond
+        // it, so nothing but the stored move counts, not even the mate
+        let quiet = exercise("6k1/5ppp/8/8/8/8/5PPP/R3R1K1 w - - 0 1", &["Kf1"]);
+        assert_eq!(checker.check(&quiet, "Kf1"), SolutionMatch::Canonical);
+        assert_eq!(checker.check(&quiet, "Ra8"), SolutionMatch::Wrong);
+
+        // Lines longer than the search can cover are matched exactly
+        let long = exercise("4k3/8/8/3q4/4P3/2N5/8/4K3 w - - 0 1", &["Nxd5"])
+            .with_line(["c3d5", "e8f7", "e1f2", "f7e6", "f2f3", "e6e5"].map(String::from).to_vec());
+        assert_eq!(lenient.check(&long, "exd5"), SolutionMatch::Wrong);
+    }
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use crate::exercise::{Exercise, ExerciseType, ExerciseDifficulty, ExerciseLibrary};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum StrategyPattern {
     OpeningPrinciples,      // Control center, develop pieces, castle
     TacticalAwareness,      // Forks, pins, skewers
@@ -13,11 +15,25 @@ pub enum StrategyPattern {
     TimeManagement,         // Managing time in games
 }
 
+crate::named_enum!(StrategyPattern, "strategy pattern", {
+    StrategyPattern::OpeningPrinciples => "OpeningPrinciples",
+    StrategyPattern::TacticalAwareness => "TacticalAwareness",
+    StrategyPattern::EndgameTechnique => "EndgameTechnique",
+    StrategyPattern::PositionalPlay => "PositionalPlay",
+    StrategyPattern::AttackingPlay => "AttackingPlay",
+    StrategyPattern::DefensivePlay => "DefensivePlay",
+    StrategyPattern::CalculationSkills => "CalculationSkills",
+    StrategyPattern::TimeManagement => "TimeManagement",
+});
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Strategy {
     pub pattern: StrategyPattern,
     pub name: String,
     pub description: String,
+    /// Lowercase fragments of weakness descriptions this strategy addresses, e.g. "blunder"
+    #[serde(default)]
+    pub weakness_codes: Vec<String>,
     pub key_concepts: Vec<String>,
     pub recommended_exercises: Vec<ExerciseType>,
 }
@@ -34,11 +50,23 @@ impl Strategy {
             pattern,
             name,
             description,
+            weakness_codes: Vec::new(),
             key_concepts,
             recommended_exercises,
         }
     }
 
+    pub fn with_weakness_codes(mut self, codes: Vec<String>) -> Self {
+        self.weakness_codes = codes.into_iter().map(|c| c.to_lowercase()).collect();
+        self
+    }
+
+    /// Whether `weakness` mentions any of this strategy's weakness codes
+    pub fn addresses(&self, weakness: &str) -> bool {
+        let weakness = weakness.to_lowercase();
+        self.weakness_codes.iter().any(|code| weakness.contains(code.as_str()))
+    }
+
     pub fn get_exercises(&self, difficulty: ExerciseDifficulty) -> Vec<Exercise> {
         let all_exercises = ExerciseLibrary::get_all_exercises();
 
@@ -50,99 +78,183 @@ impl Strategy {
             })
             .collect()
     }
+
+    fn validate(&self) -> Result<(), StrategyError> {
+        let invalid = |reason: &str| StrategyError::Invalid { name: self.name.clone(), reason: reason.to_string() };
+        if self.name.trim().is_empty() {
+            return Err(invalid("name is empty"));
+        }
+        if self.weakness_codes.iter().any(|c| c.trim().is_empty()) {
+            return Err(invalid("weakness codes must not be empty"));
+        }
+        if self.key_concepts.is_empty() {
+            return Err(invalid("no key concepts"));
+        }
+        if self.recommended_exercises.is_empty() {
+            return Err(invalid("no recommended exercises"));
+        }
+        Ok(())
+    }
+}
+
+/// A strategy definition that couldn't be loaded or registered
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StrategyError {
+    #[error("could not parse strategies: {0}")]
+    Parse(String),
+    #[error("strategy '{name}' is invalid: {reason}")]
+    Invalid { name: String, reason: String },
+    #[error("a strategy named '{0}' already exists")]
+    Duplicate(String),
+}
+
+/// Built-in strategies, one per pattern, in the order weaknesses are matched. The build
+/// script turns `assets/strategies.json` into `builtin_strategy` calls.
+fn builtin_strategies() -> Vec<Strategy> {
+    include!(concat!(env!("OUT_DIR"), "/builtin_strategies.rs"))
+}
+
+fn builtin_strategy(
+    pattern: &str,
+    name: &str,
+    description: &str,
+    weakness_codes: &[&str],
+    key_concepts: &[&str],
+    recommended_exercises: &[&str],
+) -> Strategy {
+    let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+    Strategy {
+        pattern: pattern.parse().expect("built-in strategy pattern"),
+        name: name.to_string(),
+        description: description.to_string(),
+        weakness_codes: strings(weakness_codes),
+        key_concepts: strings(key_concepts),
+        recommended_exercises: recommended_exercises.iter().map(|e| e.parse().expect("built-in exercise type")).collect(),
+    }
+}
+
+/// Strategies loaded from data rather than code. Starts from the built-in set
+/// and accepts custom strategies, which are matched after the built-ins.
+#[derive(Debug, Clone)]
+pub struct StrategyRegistry {
+    strategies: Vec<Strategy>,
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
 }
 
+impl StrategyRegistry {
+    /// The strategies bundled with the trainer
+    pub fn builtin() -> Self {
+        // The asset is checked by the build script and covered by tests, so it always registers
+        let mut registry = Self { strategies: Vec::new() };
+        for strategy in builtin_strategies() {
+            registry.register(strategy).expect("built-in strategies are valid");
+        }
+        registry
+    }
+
+    /// A registry holding only the strategies in `json`, an array of strategy objects
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, StrategyError> {
+        let mut registry = Self { strategies: Vec::new() };
+        registry.extend_from_json(json)?;
+        Ok(registry)
+    }
+
+    /// Register every strategy in `json`; nothing is added if any of them is rejected
+    #[cfg(feature = "json")]
+    pub fn extend_from_json(&mut self, json: &str) -> Result<usize, StrategyError> {
+        let strategies: Vec<Strategy> = serde_json::from_str(json).map_err(|e| StrategyError::Parse(e.to_string()))?;
+        let mut extended = self.clone();
+        for strategy in strategies.iter().cloned() {
+            extended.register(strategy)?;
+        }
+        *self = extended;
+        Ok(strategies.len())
+    }
+
+    /// Add a custom strategy. Names must be unique; weakness codes are stored lowercase.
+    pub fn register(&mut self, strategy: Strategy) -> Result<(), StrategyError> {
+        let strategy = Strategy {
+            weakness_codes: strategy.weakness_codes.iter().map(|c| c.to_lowercase()).collect(),
+            ..strategy
+        };
+        strategy.validate()?;
+        if self.strategies.iter().any(|s| s.name.eq_ignore_ascii_case(&strategy.name)) {
+            return Err(StrategyError::Duplicate(strategy.name));
+        }
+        self.strategies.push(strategy);
+        Ok(())
+    }
+
+    pub fn strategies(&self) -> &[Strategy] {
+        &self.strategies
+    }
+
+    /// The first strategy for `pattern`
+    pub fn get(&self, pattern: &StrategyPattern) -> Option<&Strategy> {
+        self.strategies.iter().find(|s| &s.pattern == pattern)
+    }
+
+    /// The first strategy whose weakness codes appear in `weakness`
+    pub fn for_weakness(&self, weakness: &str) -> Option<&Strategy> {
+        self.strategies.iter().find(|s| s.addresses(weakness))
+    }
+}
+
+/// Shortcuts into the built-in [`StrategyRegistry`]
 pub struct StrategyLibrary;
 
 impl StrategyLibrary {
+    fn builtin(pattern: StrategyPattern) -> Strategy {
+        StrategyRegistry::builtin()
+            .get(&pattern)
+            .cloned()
+            .expect("every pattern has a built-in strategy")
+    }
+
     pub fn get_opening_principles() -> Strategy {
-        Strategy::new(
-            StrategyPattern::OpeningPrinciples,
-            "Opening Principles".to_string(),
-            "Learn the fundamental principles of chess openings: control the center, develop your pieces, and ensure king safety.".to_string(),
-            vec![
-                "Control the center with pawns (e4, d4) or pieces".to_string(),
-                "Develop knights before bishops".to_string(),
-                "Don't move the same piece twice in the opening".to_string(),
-                "Castle early to ensure king safety".to_string(),
-                "Don't bring the queen out too early".to_string(),
-                "Connect your rooks".to_string(),
-            ],
-            vec![ExerciseType::Opening, ExerciseType::Positional],
-        )
+        Self::builtin(StrategyPattern::OpeningPrinciples)
     }
 
     pub fn get_tactical_awareness() -> Strategy {
-        Strategy::new(
-            StrategyPattern::TacticalAwareness,
-            "Tactical Awareness".to_string(),
-            "Develop your ability to spot tactical opportunities like forks, pins, skewers, and discovered attacks.".to_string(),
-            vec![
-                "Fork: Attack two pieces at once".to_string(),
-                "Pin: Restrict piece movement by threatening a more valuable piece behind it".to_string(),
-                "Skewer: Force a valuable piece to move, exposing a less valuable piece".to_string(),
-                "Discovered attack: Move a piece to reveal an attack from another piece".to_string(),
-                "Double attack: Attack two targets simultaneously".to_string(),
-            ],
-            vec![ExerciseType::Tactics, ExerciseType::Calculation],
-        )
+        Self::builtin(StrategyPattern::TacticalAwareness)
     }
 
     pub fn get_endgame_technique() -> Strategy {
-        Strategy::new(
-            StrategyPattern::EndgameTechnique,
-            "Endgame Technique".to_string(),
-            "Master essential endgame positions and techniques to convert advantages into wins.".to_string(),
-            vec![
-                "Activate your king in the endgame".to_string(),
-                "Learn basic checkmates (K+Q vs K, K+R vs K)".to_string(),
-                "Understand pawn endgames and the opposition".to_string(),
-                "Know when to trade pieces".to_string(),
-                "Create passed pawns".to_string(),
-            ],
-            vec![ExerciseType::Endgame, ExerciseType::Calculation],
-        )
+        Self::builtin(StrategyPattern::EndgameTechnique)
     }
 
     pub fn get_positional_play() -> Strategy {
-        Strategy::new(
-            StrategyPattern::PositionalPlay,
-            "Positional Play".to_string(),
-            "Improve your understanding of positional chess: pawn structure, piece activity, and long-term planning.".to_string(),
-            vec![
-                "Understand pawn structures (chains, islands, doubled pawns)".to_string(),
-                "Improve piece activity and coordination".to_string(),
-                "Control key squares and files".to_string(),
-                "Identify weak pawns and squares".to_string(),
-                "Create and exploit weaknesses in opponent's position".to_string(),
-            ],
-            vec![ExerciseType::Positional, ExerciseType::Strategy],
-        )
+        Self::builtin(StrategyPattern::PositionalPlay)
+    }
+
+    pub fn get_attacking_play() -> Strategy {
+        Self::builtin(StrategyPattern::AttackingPlay)
+    }
+
+    pub fn get_defensive_play() -> Strategy {
+        Self::builtin(StrategyPattern::DefensivePlay)
+    }
+
+    pub fn get_calculation_skills() -> Strategy {
+        Self::builtin(StrategyPattern::CalculationSkills)
+    }
+
+    pub fn get_time_management() -> Strategy {
+        Self::builtin(StrategyPattern::TimeManagement)
     }
 
     pub fn get_all_strategies() -> Vec<Strategy> {
-        vec![
-            Self::get_opening_principles(),
-            Self::get_tactical_awareness(),
-            Self::get_endgame_technique(),
-            Self::get_positional_play(),
-        ]
+        StrategyRegistry::builtin().strategies().to_vec()
     }
 
     pub fn get_strategy_for_weakness(weakness: &str) -> Option<Strategy> {
-        let weakness_lower = weakness.to_lowercase();
-
-        if weakness_lower.contains("opening") {
-            Some(Self::get_opening_principles())
-        } else if weakness_lower.contains("tactic") || weakness_lower.contains("blunder") {
-            Some(Self::get_tactical_awareness())
-        } else if weakness_lower.contains("endgame") {
-            Some(Self::get_endgame_technique())
-        } else if weakness_lower.contains("positional") || weakness_lower.contains("inaccuracy") {
-            Some(Self::get_positional_play())
-        } else {
-            None
-        }
+        StrategyRegistry::builtin().for_weakness(weakness).cloned()
     }
 }
 
@@ -170,5 +282,43 @@ mod tests {
         let strategy = StrategyLibrary::get_strategy_for_weakness("weak opening play");
         assert!(strategy.is_some());
         assert_eq!(strategy.unwrap().pattern, StrategyPattern::OpeningPrinciples);
+
+        let strategy = StrategyLibrary::get_strategy_for_weakness("Frequent blunders - practice tactics and calculation");
+        assert_eq!(strategy.unwrap().pattern, StrategyPattern::TacticalAwareness);
+        let strategy = StrategyLibrary::get_strategy_for_weakness("Often in time trouble");
+        assert_eq!(strategy.unwrap().pattern, StrategyPattern::TimeManagement);
+    }
+
+    #[test]
+    fn test_builtin_registry_covers_every_pattern() {
+        let registry = StrategyRegistry::builtin();
+        for pattern in StrategyPattern::ALL {
+            let strategy = registry.get(pattern).unwrap();
+            assert!(!strategy.weakness_codes.is_empty(), "{} has no weakness codes", strategy.name);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_register_custom_strategy() {
+        let mut registry = StrategyRegistry::builtin();
+        let custom = r#"[{
+            "pattern": "EndgameTechnique",
+            "name": "Rook Endgames",
+            "description": "Lucena, Philidor and active rooks.",
+            "weakness_codes": ["Rook Ending"],
+            "key_concepts": ["Cut the king off along a file"],
+            "recommended_exercises": ["Endgame", "Defense"]
+        }]"#;
+        assert_eq!(registry.extend_from_json(custom).unwrap(), 1);
+
+        // Built-ins are matched first; the custom code is lowercased
+        assert_eq!(registry.for_weakness("weak endgame play").unwrap().name, "Endgame Technique");
+        assert_eq!(registry.for_weakness("misplays ROOK ENDINGS").unwrap().name, "Rook Endgames");
+
+        assert_eq!(registry.extend_from_json(custom), Err(StrategyError::Duplicate("Rook Endgames".to_string())));
+        assert!(matches!(registry.extend_from_json("{"), Err(StrategyError::Parse(_))));
+        let empty = Strategy::new(StrategyPattern::TimeManagement, "Blitz".into(), String::new(), vec![], vec![ExerciseType::Tactics]);
+        assert!(matches!(registry.register(empty), Err(StrategyError::Invalid { .. })));
     }
 }
@@ -1,54 +1,79 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "timestamps")]
 use chrono::{DateTime, Utc};
 use crate::exercise::{Exercise, ExerciseDifficulty, ExerciseResult};
+use crate::selection::{AdaptiveSelector, ConceptRefresher, ExerciseSelector};
 use crate::strategy::{Strategy, StrategyLibrary};
 
+/// A run of exercises chosen one at a time by `selector`, which can react to
+/// each result. `exercises` holds what has been served so far.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TrainingSession {
+pub struct TrainingSession<S = AdaptiveSelector> {
     pub id: Option<u64>,
     pub user_id: u64,
     pub exercises: Vec<Exercise>,
     pub current_exercise_index: usize,
     pub results: Vec<ExerciseResult>,
     pub strategies: Vec<Strategy>,
+    /// Difficulty the session started at
     pub difficulty: ExerciseDifficulty,
+    pub selector: S,
+    #[cfg(feature = "timestamps")]
     pub started_at: DateTime<Utc>,
+    #[cfg(feature = "timestamps")]
     pub finished_at: Option<DateTime<Utc>>,
 }
 
 impl TrainingSession {
     pub fn new(user_id: u64, difficulty: ExerciseDifficulty) -> Self {
-        Self {
-            id: None,
-            user_id,
-            exercises: Vec::new(),
-            current_exercise_index: 0,
-            results: Vec::new(),
-            strategies: Vec::new(),
-            difficulty,
-            started_at: Utc::now(),
-            finished_at: None,
-        }
+        Self::with_strategies(user_id, Vec::new(), difficulty)
     }
 
     pub fn with_weaknesses(user_id: u64, weaknesses: Vec<String>, difficulty: ExerciseDifficulty) -> Self {
-        let mut session = Self::new(user_id, difficulty);
-
         // Get strategies based on weaknesses
+        let mut strategies: Vec<Strategy> = Vec::new();
         for weakness in &weaknesses {
             if let Some(strategy) = StrategyLibrary::get_strategy_for_weakness(weakness) {
-                session.add_strategy(strategy);
+                if !strategies.iter().any(|s| s.pattern == strategy.pattern) {
+                    strategies.push(strategy);
+                }
             }
         }
 
         // If no specific strategies were found, add general strategies
-        if session.strategies.is_empty() {
-            session.strategies = StrategyLibrary::get_all_strategies();
+        if strategies.is_empty() {
+            strategies = StrategyLibrary::get_all_strategies();
         }
 
-        // Generate exercises based on strategies
-        session.generate_exercises();
+        Self::with_strategies(user_id, strategies, difficulty)
+    }
+
+    fn with_strategies(user_id: u64, strategies: Vec<Strategy>, difficulty: ExerciseDifficulty) -> Self {
+        let selector = AdaptiveSelector::new(strategies.clone(), difficulty.clone());
+        let mut session = Self::with_selector(user_id, difficulty, selector);
+        session.strategies = strategies;
+        session
+    }
+}
 
+impl<S: ExerciseSelector> TrainingSession<S> {
+    /// A session driven by a custom selection strategy. The first exercise is chosen immediately.
+    pub fn with_selector(user_id: u64, difficulty: ExerciseDifficulty, selector: S) -> Self {
+        let mut session = Self {
+            id: None,
+            user_id,
+            exercises: Vec::new(),
+            current_exercise_index: 0,
+            results: Vec::new(),
+            strategies: Vec::new(),
+            difficulty,
+            selector,
+            #[cfg(feature = "timestamps")]
+            started_at: Utc::now(),
+            #[cfg(feature = "timestamps")]
+            finished_at: None,
+        };
+        session.serve_next();
         session
     }
 
@@ -58,32 +83,16 @@ impl TrainingSession {
         }
     }
 
-    pub fn generate_exercises(&mut self) {
-        self.exercises.clear();
-
-        for strategy in &self.strategies {
-            let mut exercises = strategy.get_exercises(self.difficulty.clone());
-            // Limit to 2-3 exercises per strategy to reach 5-10 total
-            exercises.truncate(3);
-            self.exercises.extend(exercises);
-        }
-
-        // Ensure we have between 5-10 exercises
-        if self.exercises.len() < 5 {
-            // Add more exercises from all strategies
-            let all_strategies = StrategyLibrary::get_all_strategies();
-            for strategy in all_strategies {
-                let exercises = strategy.get_exercises(self.difficulty.clone());
-                self.exercises.extend(exercises);
-                if self.exercises.len() >= 5 {
-                    break;
-                }
+    /// Ask the selector for another exercise; `false` when it has ended the session
+    fn serve_next(&mut self) -> bool {
+        match self.selector.next_exercise(&self.exercises) {
+            Some(exercise) => {
+                self.exercises.push(exercise);
+                tracing::debug!(served = self.exercises.len(), "selected training exercise");
+                true
             }
+            None => false,
         }
-
-        // Limit to 10 exercises maximum
-        self.exercises.truncate(10);
-        self.current_exercise_index = 0;
     }
 
     pub fn current_exercise(&self) -> Option<&Exercise> {
@@ -94,19 +103,34 @@ impl TrainingSession {
         if self.current_exercise_index < self.exercises.len() {
             self.current_exercise_index += 1;
         }
+        if self.current_exercise_index == self.exercises.len() {
+            self.serve_next();
+        }
         self.current_exercise()
     }
 
+    /// Record the result for the current exercise and let the selector adapt
     pub fn record_result(&mut self, result: ExerciseResult) {
+        if let Some(exercise) = self.exercises.get(self.current_exercise_index) {
+            self.selector.observe(exercise, &result);
+        }
         self.results.push(result);
     }
 
+    /// A concept to review before the next exercise, offered after repeated failures
+    pub fn take_refresher(&mut self) -> Option<ConceptRefresher> {
+        self.selector.take_refresher()
+    }
+
     pub fn is_finished(&self) -> bool {
         self.current_exercise_index >= self.exercises.len()
     }
 
     pub fn finish(&mut self) {
-        self.finished_at = Some(Utc::now());
+        #[cfg(feature = "timestamps")]
+        {
+            self.finished_at = Some(Utc::now());
+        }
     }
 
     pub fn get_session_result(&self) -> SessionResult {
@@ -122,11 +146,15 @@ impl TrainingSession {
             0.0
         };
 
+        #[cfg(feature = "timestamps")]
         let duration_seconds = if let Some(finished) = self.finished_at {
             (finished - self.started_at).num_seconds() as u32
         } else {
             0
         };
+        // Without a clock, fall back to the time reported for each exercise
+        #[cfg(not(feature = "timestamps"))]
+        let duration_seconds = self.results.iter().map(|r| r.time_taken_seconds).sum();
 
         SessionResult {
             total_exercises,
@@ -198,7 +226,6 @@ mod tests {
     #[test]
     fn test_session_result() {
         let mut session = TrainingSession::new(1, ExerciseDifficulty::Beginner);
-        session.generate_exercises();
 
         let result = ExerciseResult {
             exercise_id: 1,
@@ -207,6 +234,8 @@ mod tests {
             attempts: 1,
             time_taken_seconds: 30,
             hints_used: 0,
+            scaffolding_steps: 0,
+            #[cfg(feature = "timestamps")]
             completed_at: Utc::now(),
         };
 
@@ -215,4 +244,24 @@ mod tests {
 
         assert!(session_result.success_rate > 0.0);
     }
+
+    #[test]
+    fn test_session_adapts_to_results() {
+        let mut session = TrainingSession::new(1, ExerciseDifficulty::Beginner);
+        assert_eq!(session.current_exercise().unwrap().difficulty, ExerciseDifficulty::Beginner);
+
+        for _ in 0..3 {
+            session.record_result(ExerciseResult {
+                solved: true,
+                attempts: 1,
+                time_taken_seconds: 5,
+                ..ExerciseResult::new(0, 1)
+            });
+            session.next_exercise();
+        }
+
+        assert_eq!(session.selector.difficulty(), &ExerciseDifficulty::Intermediate);
+        assert_eq!(session.current_exercise().unwrap().difficulty, ExerciseDifficulty::Intermediate);
+        assert_eq!(session.exercises.len(), 4);
+    }
 }
@@ -0,0 +1,38 @@
+//! The Tacticus trainer as one dependency: the engine, exercises and training
+//! sessions, and the player model that picks what to train, at versions that
+//! are known to work together.
+//!
+//! Most programs only need the [`prelude`]. The underlying crates are
+//! re-exported whole for anything it leaves out.
+//!
+//! ```
+//! use tacticus::prelude::*;
+//!
+//! // Back-rank mate: the black king is boxed in by its own pawns
+//! let board = parse_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1")?;
+//! let engine = Engine::builder().depth(2).build()?;
+//! let best = engine.best_move(&board).expect("white has legal moves");
+//! assert_eq!(to_san(&board, best.chess_move), "Ra8#");
+//!
+//! // The same position as an exercise; any notation is accepted as an answer
+//! let exercise = Exercise::new(
+//!     ExerciseType::Tactics,
+//!     ExerciseDifficulty::Beginner,
+//!     board.to_string(),
+//!     "Back rank".to_string(),
+//!     "White to move and mate".to_string(),
+//!     vec!["a1a8".to_string()],
+//!     "Black's pawns take away every escape square.".to_string(),
+//! );
+//! let checker = SolutionChecker::new(50);
+//! assert_eq!(checker.check(&exercise, "Ra8#"), SolutionMatch::Canonical);
+//! assert_eq!(checker.check(&exercise, "h2h3"), SolutionMatch::Wrong);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod prelude;
+
+pub use chess_ai;
+pub use chess_core;
+pub use chess_engine;
+pub use chess_trainer;
@@ -0,0 +1,36 @@
+//! The types and functions most programs embedding the trainer use
+//!
+//! ```
+//! use tacticus::prelude::*;
+//!
+//! // A short game, judged against a beginner's rating
+//! let mut game = ChessGame::new(Color::White);
+//! for uci in ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7"] {
+//!     game.make_move(parse_uci(uci)?)?;
+//! }
+//! assert!(game.is_finished());
+//!
+//! let mut agent = LearningAgent::new(1);
+//! let advice = agent.analyze_game(game);
+//! assert_eq!(agent.get_profile().games_played, 1);
+//!
+//! // A session on what the game showed, pitched at the player's level
+//! let session = agent.create_training_session();
+//! let first = session.current_exercise().expect("sessions start with an exercise");
+//! assert_eq!(first.difficulty, advice.recommended_difficulty);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub use chess_core::{
+    detect_opening, parse_fen, parse_legal_uci, parse_move, parse_san, parse_uci, to_san, Board, ChessError, ChessGame,
+    ChessMove, Color, GamePhase, GameState, MoveQuality, Opening, Piece, Square,
+};
+pub use chess_engine::{
+    CancellationToken, DefenseFinder, Engine, EngineBuilder, GameAnalyzer, MatePattern, MoveAnalysis, MoveEvaluation,
+    Personality, QualityCalibration, ThreatDetector,
+};
+pub use chess_trainer::{
+    Exercise, ExerciseDifficulty, ExerciseLibrary, ExerciseResult, ExerciseType, SessionResult, SolutionChecker,
+    SolutionMatch, TrainingSession,
+};
+pub use chess_ai::{AgentRecommendation, LearningAgent, PlayStyle, PlayStyleAnalyzer, PlayerProfile, SkillLevel};
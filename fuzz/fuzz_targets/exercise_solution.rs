@@ -0,0 +1,17 @@
+#![no_main]
+
+use chess_core::parse_move;
+use chess_trainer::ExerciseLibrary;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    // Submitted answers are checked against every bundled exercise position
+    for exercise in ExerciseLibrary::get_all_exercises() {
+        let _ = exercise.check_solution(data);
+        if let Ok(board) = exercise.get_board() {
+            if let Ok(chess_move) = parse_move(&board, data) {
+                assert!(board.legal(chess_move));
+            }
+        }
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use chess_core::parse_fen;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    // Anything that parses must survive a round trip through the board's own FEN
+    if let Ok(board) = parse_fen(data) {
+        let fen = board.to_string();
+        assert_eq!(parse_fen(&fen).map(|b| b.to_string()).ok(), Some(fen));
+    }
+});
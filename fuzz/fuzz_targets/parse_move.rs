@@ -0,0 +1,18 @@
+#![no_main]
+
+use chess::Board;
+use chess_core::{parse_legal_uci, parse_move, parse_san, parse_uci};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = parse_uci(data);
+
+    let board = Board::default();
+    if let Ok(chess_move) = parse_legal_uci(&board, data) {
+        assert!(board.legal(chess_move));
+    }
+    if let Ok(chess_move) = parse_san(&board, data) {
+        assert!(board.legal(chess_move));
+    }
+    let _ = parse_move(&board, data);
+});
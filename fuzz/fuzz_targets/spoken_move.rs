@@ -0,0 +1,9 @@
+#![no_main]
+
+use chess::Board;
+use chess_core::parse_spoken_move;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = parse_spoken_move(&Board::default(), data);
+});
@@ -0,0 +1,204 @@
+use chess_engine::{CancellationToken, MoveAnalysis};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long a background job sleeps between checks while interactive work is running
+const YIELD_INTERVAL: Duration = Duration::from_millis(20);
+
+lazy_static! {
+    pub static ref SCHEDULER: AnalysisScheduler = AnalysisScheduler::new();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// A background full-game analysis and its progress so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisJob {
+    pub id: u64,
+    pub game_id: i64,
+    pub status: JobStatus,
+    pub completed: usize,
+    pub total: usize,
+    pub analyses: Vec<MoveAnalysis>,
+    pub error: Option<String>,
+}
+
+struct JobEntry {
+    job: AnalysisJob,
+    token: CancellationToken,
+}
+
+/// Marks interactive work as running. Background jobs yield until every guard is dropped.
+pub struct InteractiveGuard<'a> {
+    scheduler: &'a AnalysisScheduler,
+    pub token: CancellationToken,
+}
+
+impl Drop for InteractiveGuard<'_> {
+    fn drop(&mut self) {
+        self.scheduler.active_interactive.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Coordinates engine work so the position the user is looking at is analysed first.
+///
+/// Interactive requests are grouped by channel (e.g. the eval bar); starting a new request on a
+/// channel cancels the one it supersedes. Background jobs check in between moves and pause while
+/// any interactive request is running.
+pub struct AnalysisScheduler {
+    interactive: Mutex<HashMap<String, CancellationToken>>,
+    active_interactive: AtomicUsize,
+    jobs: Mutex<HashMap<u64, JobEntry>>,
+    next_job_id: AtomicU64,
+}
+
+impl Default for AnalysisScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisScheduler {
+    pub fn new() -> Self {
+        Self {
+            interactive: Mutex::new(HashMap::new()),
+            active_interactive: AtomicUsize::new(0),
+            jobs: Mutex::new(HashMap::new()),
+            next_job_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Start an interactive request on `channel`, cancelling whatever was running there
+    pub fn begin_interactive(&self, channel: &str) -> InteractiveGuard<'_> {
+        let token = CancellationToken::new();
+        let previous = self
+            .interactive
+            .lock()
+            .unwrap()
+            .insert(channel.to_string(), token.clone());
+        if let Some(previous) = previous {
+            previous.cancel();
+        }
+
+        self.active_interactive.fetch_add(1, Ordering::SeqCst);
+        InteractiveGuard { scheduler: self, token }
+    }
+
+    pub fn interactive_running(&self) -> bool {
+        self.active_interactive.load(Ordering::SeqCst) > 0
+    }
+
+    /// Block a background job until no interactive work is running or the job is cancelled
+    pub fn yield_to_interactive(&self, token: &CancellationToken) {
+        while self.interactive_running() && !token.is_cancelled() {
+            std::thread::sleep(YIELD_INTERVAL);
+        }
+    }
+
+    /// Register a background job and return its id with the token it should check
+    pub fn create_job(&self, game_id: i64, total: usize) -> (u64, CancellationToken) {
+        let id = self.next_job_id.fetch_add(1, Ordering::SeqCst);
+        let token = CancellationToken::new();
+        let job = AnalysisJob {
+            id,
+            game_id,
+            status: JobStatus::Running,
+            completed: 0,
+            total,
+            analyses: Vec::new(),
+            error: None,
+        };
+        self.jobs.lock().unwrap().insert(id, JobEntry { job, token: token.clone() });
+        (id, token)
+    }
+
+    pub fn record_progress(&self, id: u64, analysis: MoveAnalysis) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(&id) {
+            entry.job.completed += 1;
+            entry.job.analyses.push(analysis);
+        }
+    }
+
+    /// Move a job out of `Running`. A job cancelled by the user stays cancelled.
+    pub fn finish_job(&self, id: u64, status: JobStatus, error: Option<String>) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(&id) {
+            if entry.job.status == JobStatus::Running {
+                entry.job.status = status;
+                entry.job.error = error;
+            }
+        }
+    }
+
+    pub fn job(&self, id: u64) -> Option<AnalysisJob> {
+        self.jobs.lock().unwrap().get(&id).map(|entry| entry.job.clone())
+    }
+
+    /// Returns false if the job doesn't exist or already finished
+    pub fn cancel_job(&self, id: u64) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        match jobs.get_mut(&id) {
+            Some(entry) if entry.job.status == JobStatus::Running => {
+                entry.token.cancel();
+                entry.job.status = JobStatus::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget finished jobs once their results have been collected
+    pub fn remove_finished_jobs(&self) {
+        self.jobs
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.job.status == JobStatus::Running);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_request_cancels_superseded_one() {
+        let scheduler = AnalysisScheduler::new();
+        let first = scheduler.begin_interactive("eval_bar");
+        let other_channel = scheduler.begin_interactive("hint");
+        let second = scheduler.begin_interactive("eval_bar");
+
+        assert!(first.token.is_cancelled());
+        assert!(!other_channel.token.is_cancelled());
+        assert!(!second.token.is_cancelled());
+    }
+
+    #[test]
+    fn test_background_job_waits_for_interactive_work() {
+        let scheduler = AnalysisScheduler::new();
+        let (id, token) = scheduler.create_job(1, 10);
+
+        {
+            let _guard = scheduler.begin_interactive("eval_bar");
+            assert!(scheduler.interactive_running());
+        }
+        assert!(!scheduler.interactive_running());
+        // Returns immediately now that nothing interactive is running
+        scheduler.yield_to_interactive(&token);
+
+        assert!(scheduler.cancel_job(id));
+        assert!(token.is_cancelled());
+        assert!(!scheduler.cancel_job(id));
+
+        scheduler.finish_job(id, JobStatus::Completed, None);
+        assert_eq!(scheduler.job(id).unwrap().status, JobStatus::Cancelled);
+    }
+}
@@ -0,0 +1,100 @@
+use chess_config::{Config, ConfigError};
+use rusqlite::Connection;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::database::repositories;
+use crate::DB;
+
+lazy_static! {
+    /// Defaults, config file and environment, read once before the database opens
+    static ref BASE: (Config, Vec<ConfigError>) = Config::load();
+}
+
+/// LLM endpoint and key every other source gives way to, set once for `--mock-llm`
+static LLM_ENDPOINT: OnceLock<(String, String)> = OnceLock::new();
+
+/// Set when all data is wiped: the API key read from the config file or environment
+/// at startup is dropped for the rest of the run, leaving only one saved afterwards
+static API_KEY_FORGOTTEN: AtomicBool = AtomicBool::new(false);
+
+/// Stop using the API key loaded at startup; see `API_KEY_FORGOTTEN`
+pub fn forget_api_key() {
+    API_KEY_FORGOTTEN.store(true, Ordering::SeqCst);
+}
+
+/// Send every model call to `base_url` with `api_key`, whatever the settings say.
+/// Only the first call has any effect.
+pub fn override_llm_endpoint(base_url: &str, api_key: &str) {
+    let _ = LLM_ENDPOINT.set((base_url.trim_end_matches('/').to_string(), api_key.to_string()));
+}
+
+fn with_endpoint(mut config: Config) -> Config {
+    if let Some((base_url, api_key)) = LLM_ENDPOINT.get() {
+        config.llm.base_url = base_url.clone();
+        config.llm.api_key = Some(api_key.clone());
+    }
+    config
+}
+
+/// Config without the settings table, for what has to be known before the database opens
+pub fn base() -> &'static Config {
+    &BASE.0
+}
+
+/// Report config file and environment values that were ignored; call once logging is up
+pub fn log_problems() {
+    for problem in &BASE.1 {
+        tracing::warn!("ignoring config: {}", problem);
+    }
+}
+
+/// Full config with the stored settings layered on top
+pub fn with_settings(conn: &Connection) -> Config {
+    layered(base().clone(), conn)
+}
+
+fn layered(mut config: Config, conn: &Connection) -> Config {
+    if API_KEY_FORGOTTEN.load(Ordering::SeqCst) {
+        config.llm.api_key = None;
+    }
+    for problem in config.apply_settings(|setting| repositories::get_setting(conn, setting).ok().flatten()) {
+        tracing::debug!("ignoring setting: {}", problem);
+    }
+    with_endpoint(config)
+}
+
+/// `with_settings` on the app database. Not for use inside `DB.with_conn`.
+pub fn current() -> Config {
+    DB.with_conn(|conn| Ok(with_settings(conn))).unwrap_or_else(|_| {
+        let mut config = base().clone();
+        if API_KEY_FORGOTTEN.load(Ordering::SeqCst) {
+            config.llm.api_key = None;
+        }
+        with_endpoint(config)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_wiping_everything_forgets_the_api_key() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let mut startup = Config::default();
+        startup.llm.api_key = Some("sk-from-config-file".to_string());
+        repositories::set_setting(&conn, "api_key", "sk-saved").unwrap();
+        assert_eq!(layered(startup.clone(), &conn).llm.api_key.as_deref(), Some("sk-saved"));
+
+        repositories::securely(&conn, repositories::wipe_all_tables).unwrap();
+        forget_api_key();
+        assert_eq!(layered(startup.clone(), &conn).llm.api_key, None);
+
+        // A key saved after the wipe is used again
+        repositories::set_setting(&conn, "api_key", "sk-new").unwrap();
+        assert_eq!(layered(startup, &conn).llm.api_key.as_deref(), Some("sk-new"));
+    }
+}
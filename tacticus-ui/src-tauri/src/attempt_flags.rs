@@ -0,0 +1,140 @@
+use chess_trainer::ExerciseDifficulty;
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories::{self, ExerciseResult};
+
+const FLAG_SETTING_KEY: &str = "flag_suspicious_attempts";
+
+/// An advanced or expert puzzle solved first try, unaided, this fast is flagged on its own
+pub const INSTANT_SOLVE_SECONDS: i32 = 3;
+
+/// A perfect run this long in one session, averaging under `FAST_STREAK_SECONDS`
+/// a puzzle, is flagged when it breaks sharply from the player's record
+pub const STREAK_LENGTH: usize = 8;
+pub const FAST_STREAK_SECONDS: f64 = 10.0;
+
+/// The record has to hold this many unflagged attempts before a streak is judged against it
+pub const MIN_HISTORY: i32 = 20;
+
+/// Solve rate, in percent, below which a fast perfect streak is out of character
+pub const LOW_ACCURACY: f64 = 50.0;
+
+/// Why an attempt looks engine-assisted, and which earlier attempts in the same
+/// session should be flagged along with it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suspicion {
+    pub reason: String,
+    pub also_flag: Vec<i64>,
+}
+
+pub fn is_enabled(conn: &Connection, profile_id: i64) -> Result<bool> {
+    Ok(repositories::get_profile_setting(conn, profile_id, FLAG_SETTING_KEY)?
+        .map(|v| v == "true")
+        .unwrap_or(false))
+}
+
+pub fn set_enabled(conn: &Connection, profile_id: i64, enabled: bool) -> Result<()> {
+    repositories::set_profile_setting(conn, profile_id, FLAG_SETTING_KEY, if enabled { "true" } else { "false" })
+}
+
+fn clean_solve(result: &ExerciseResult) -> bool {
+    result.solved && result.attempts <= 1 && result.hints_used == 0 && !result.assisted()
+}
+
+/// Judge a new attempt against the earlier attempts of its session and the
+/// player's `(attempts, solved)` record outside it
+pub fn review(result: &ExerciseResult, session_before: &[ExerciseResult], history: (i32, i32)) -> Option<Suspicion> {
+    let difficulty = result.difficulty.parse::<ExerciseDifficulty>().ok();
+    if clean_solve(result)
+        && difficulty.is_some_and(|d| d >= ExerciseDifficulty::Advanced)
+        && result.time_seconds <= INSTANT_SOLVE_SECONDS
+    {
+        return Some(Suspicion {
+            reason: format!("{} puzzle solved in {}s", result.difficulty, result.time_seconds),
+            also_flag: vec![],
+        });
+    }
+
+    let (attempts, solved) = history;
+    if attempts < MIN_HISTORY || solved as f64 * 100.0 / attempts as f64 >= LOW_ACCURACY {
+        return None;
+    }
+    let earlier = session_before.len().checked_sub(STREAK_LENGTH - 1)?;
+    let streak = &session_before[earlier..];
+    if !clean_solve(result) || !streak.iter().all(clean_solve) {
+        return None;
+    }
+    let total_seconds: i32 = streak.iter().map(|r| r.time_seconds).sum::<i32>() + result.time_seconds;
+    let average = total_seconds as f64 / STREAK_LENGTH as f64;
+    if average > FAST_STREAK_SECONDS {
+        return None;
+    }
+    Some(Suspicion {
+        reason: format!(
+            "{} puzzles in a row solved in {:.0}s on average, against a {:.0}% record",
+            STREAK_LENGTH,
+            average,
+            solved as f64 * 100.0 / attempts as f64
+        ),
+        also_flag: streak.iter().filter(|r| !r.flagged).map(|r| r.id).collect(),
+    })
+}
+
+/// Review an attempt about to be recorded; `None` whenever flagging is off for the profile
+pub fn check(conn: &Connection, profile_id: i64, result: &ExerciseResult) -> Result<Option<Suspicion>> {
+    if !is_enabled(conn, profile_id)? {
+        return Ok(None);
+    }
+    let session_before = match result.session_id {
+        Some(session_id) => repositories::get_session_exercise_results(conn, session_id)?,
+        None => vec![],
+    };
+    let history = repositories::get_exercise_accuracy(conn, profile_id, result.session_id)?;
+    Ok(review(result, &session_before, history))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(id: i64, difficulty: &str, solved: bool, time_seconds: i32) -> ExerciseResult {
+        ExerciseResult {
+            id,
+            profile_id: 1,
+            session_id: Some(1),
+            exercise_type: "Tactics".to_string(),
+            difficulty: difficulty.to_string(),
+            position_fen: String::new(),
+            solved,
+            attempts: 1,
+            time_seconds,
+            hints_used: 0,
+            created_at: String::new(),
+            flagged: false,
+            flag_reason: None,
+            alternative_solution: false,
+            scaffolding: vec![],
+        }
+    }
+
+    #[test]
+    fn test_review() {
+        let strong_record = (40, 30);
+        let weak_record = (40, 12);
+
+        // Instant expert solves are flagged whatever the record; beginner ones are not
+        let instant = review(&attempt(9, "Expert", true, 2), &[], strong_record).unwrap();
+        assert!(instant.also_flag.is_empty());
+        assert!(review(&attempt(9, "Beginner", true, 2), &[], weak_record).is_none());
+        assert!(review(&attempt(9, "Expert", false, 2), &[], weak_record).is_none());
+
+        // A fast perfect run is only out of character for a weak record
+        let run: Vec<ExerciseResult> = (1..8).map(|id| attempt(id, "Intermediate", true, 6)).collect();
+        let streak = review(&attempt(8, "Intermediate", true, 6), &run, weak_record).unwrap();
+        assert_eq!(streak.also_flag, (1..8).collect::<Vec<_>>());
+        assert!(review(&attempt(8, "Intermediate", true, 6), &run, strong_record).is_none());
+        assert!(review(&attempt(8, "Intermediate", true, 6), &run, (10, 2)).is_none());
+        assert!(review(&attempt(8, "Intermediate", true, 6), &run[1..], weak_record).is_none());
+        assert!(review(&attempt(8, "Intermediate", true, 60), &run, weak_record).is_none());
+    }
+}
@@ -0,0 +1,335 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chess_core::{DiagramStyle, PieceArt, PIECE_KEYS};
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::app_config;
+use crate::database::repositories;
+use crate::notation_prefs;
+use crate::DB;
+
+/// Piece set and board theme used by the board, diagrams and reports
+const PIECE_SET_SETTING_KEY: &str = "piece_set";
+const BOARD_THEME_SETTING_KEY: &str = "board_theme";
+
+pub use chess_config::{DEFAULT_BOARD_THEME, DEFAULT_PIECE_SET};
+
+/// Plain lettered discs, generated rather than loaded, that stay legible when printed
+const LETTERS_PIECE_SET: &str = "letters";
+
+/// Built-in board themes as (name, light square, dark square)
+const BUILTIN_THEMES: &[(&str, &str, &str)] = &[
+    ("brown", "#f0d9b5", "#b58863"),
+    ("green", "#eeeed2", "#769656"),
+    ("blue", "#dee3e6", "#8ca2ad"),
+    ("grey", "#e0e0e0", "#9e9e9e"),
+];
+
+lazy_static! {
+    /// Loaded piece sets by name, so each folder is read once
+    static ref PIECE_SETS: Mutex<HashMap<String, Arc<HashMap<String, String>>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardTheme {
+    pub name: String,
+    pub light_square: String,
+    pub dark_square: String,
+    /// "builtin", "bundled" or "user"
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceSetInfo {
+    pub name: String,
+    /// "builtin", "bundled" or "user"
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardAssets {
+    pub piece_sets: Vec<PieceSetInfo>,
+    pub board_themes: Vec<BoardTheme>,
+    /// Folder the user can drop `pieces/<set>/` and `boards/<theme>.json` into
+    pub user_dir: Option<String>,
+}
+
+/// The selected assets, ready for the GUI board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardAppearance {
+    pub piece_set: String,
+    pub board_theme: BoardTheme,
+    /// Data URI per piece key ("wK", "bN", ...); empty for the standard set
+    pub pieces: HashMap<String, String>,
+}
+
+/// Board theme file contents
+#[derive(Deserialize)]
+struct ThemeFile {
+    light_square: String,
+    dark_square: String,
+}
+
+/// Assets shipped next to the executable
+fn bundled_dir() -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.join("assets"))
+}
+
+fn user_dir() -> Option<PathBuf> {
+    Some(dirs::data_local_dir()?.join("tacticus").join("assets"))
+}
+
+/// Asset folders with their source label; later ones override earlier ones
+fn asset_roots() -> Vec<(&'static str, PathBuf)> {
+    let mut roots = vec![];
+    if let Some(dir) = bundled_dir() {
+        roots.push(("bundled", dir));
+    }
+    if let Some(dir) = user_dir() {
+        roots.push(("user", dir));
+    }
+    roots
+}
+
+fn data_uri(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", mime, STANDARD.encode(bytes))
+}
+
+fn is_hex_colour(value: &str) -> bool {
+    value.len() == 7 && value.starts_with('#') && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Read a piece set folder holding `wK.svg` ... `bP.svg` (or `.png`). Every piece must be present.
+pub fn load_piece_set(dir: &Path) -> std::result::Result<HashMap<String, String>, String> {
+    PIECE_KEYS
+        .iter()
+        .map(|key| {
+            let (path, mime) = [("svg", "image/svg+xml"), ("png", "image/png")]
+                .iter()
+                .map(|(ext, mime)| (dir.join(format!("{}.{}", key, ext)), *mime))
+                .find(|(path, _)| path.is_file())
+                .ok_or_else(|| format!("{} has no image for {}", dir.display(), key))?;
+            let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            Ok((key.to_string(), data_uri(mime, &bytes)))
+        })
+        .collect()
+}
+
+/// Read a board theme file with `light_square` and `dark_square` as `#rrggbb`
+pub fn load_board_theme(path: &Path) -> std::result::Result<(String, String), String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let theme: ThemeFile = serde_json::from_str(&text).map_err(|e| format!("Invalid theme {}: {}", path.display(), e))?;
+    if !is_hex_colour(&theme.light_square) || !is_hex_colour(&theme.dark_square) {
+        return Err(format!("{} needs colours written as #rrggbb", path.display()));
+    }
+    Ok((theme.light_square, theme.dark_square))
+}
+
+fn letters_piece_set() -> HashMap<String, String> {
+    PIECE_KEYS
+        .iter()
+        .map(|key| {
+            let (fill, ink) = if key.starts_with('w') { ("#ffffff", "#000000") } else { ("#000000", "#ffffff") };
+            let svg = format!(
+                r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 45 45"><circle cx="22.5" cy="22.5" r="17" fill="{fill}" stroke="#000000" stroke-width="2"/><text x="22.5" y="23.5" font-family="sans-serif" font-size="20" font-weight="bold" text-anchor="middle" dominant-baseline="central" fill="{ink}">{}</text></svg>"##,
+                &key[1..]
+            );
+            (key.to_string(), data_uri("image/svg+xml", svg.as_bytes()))
+        })
+        .collect()
+}
+
+/// Piece set folders under every asset root, later roots winning on name clashes.
+/// Built-in names cannot be overridden.
+fn piece_set_dirs() -> Vec<(String, &'static str, PathBuf)> {
+    let mut sets: Vec<(String, &'static str, PathBuf)> = vec![];
+    for (source, root) in asset_roots() {
+        let Ok(entries) = std::fs::read_dir(root.join("pieces")) else {
+            continue;
+        };
+        for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name == DEFAULT_PIECE_SET || name == LETTERS_PIECE_SET {
+                continue;
+            }
+            sets.retain(|(existing, _, _)| *existing != name);
+            sets.push((name, source, entry.path()));
+        }
+    }
+    sets
+}
+
+fn board_themes() -> Vec<BoardTheme> {
+    let mut themes: Vec<BoardTheme> = BUILTIN_THEMES
+        .iter()
+        .map(|(name, light, dark)| BoardTheme {
+            name: name.to_string(),
+            light_square: light.to_string(),
+            dark_square: dark.to_string(),
+            source: "builtin".to_string(),
+        })
+        .collect();
+    for (source, root) in asset_roots() {
+        let Ok(entries) = std::fs::read_dir(root.join("boards")) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "json")) {
+            let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            match load_board_theme(&path) {
+                Ok((light_square, dark_square)) => {
+                    themes.retain(|t| t.name != name);
+                    themes.push(BoardTheme { name, light_square, dark_square, source: source.to_string() });
+                }
+                Err(e) => tracing::warn!("skipping board theme: {}", e),
+            }
+        }
+    }
+    themes
+}
+
+/// Every piece set and board theme that can be selected
+pub fn list() -> BoardAssets {
+    let mut piece_sets = vec![
+        PieceSetInfo { name: DEFAULT_PIECE_SET.to_string(), source: "builtin".to_string() },
+        PieceSetInfo { name: LETTERS_PIECE_SET.to_string(), source: "builtin".to_string() },
+    ];
+    for (name, source, _) in piece_set_dirs() {
+        piece_sets.retain(|s| s.name != name);
+        piece_sets.push(PieceSetInfo { name, source: source.to_string() });
+    }
+
+    BoardAssets {
+        piece_sets,
+        board_themes: board_themes(),
+        user_dir: user_dir().map(|d| d.display().to_string()),
+    }
+}
+
+/// Images of a piece set, read once and cached. The standard set has none.
+pub fn piece_images(name: &str) -> std::result::Result<Arc<HashMap<String, String>>, String> {
+    if let Some(images) = PIECE_SETS.lock().unwrap().get(name) {
+        return Ok(images.clone());
+    }
+
+    let images = match name {
+        DEFAULT_PIECE_SET => HashMap::new(),
+        LETTERS_PIECE_SET => letters_piece_set(),
+        _ => {
+            let (_, _, dir) = piece_set_dirs()
+                .into_iter()
+                .find(|(set, _, _)| set == name)
+                .ok_or_else(|| format!("Unknown piece set: {}", name))?;
+            load_piece_set(&dir)?
+        }
+    };
+    let images = Arc::new(images);
+    PIECE_SETS.lock().unwrap().insert(name.to_string(), images.clone());
+    Ok(images)
+}
+
+/// Forget loaded piece sets so edited folders are read again
+pub fn reload() {
+    PIECE_SETS.lock().unwrap().clear();
+}
+
+fn find_theme(name: &str) -> Option<BoardTheme> {
+    board_themes().into_iter().find(|t| t.name == name)
+}
+
+/// Stored piece set and board theme names, falling back to the config
+pub fn selection(conn: &Connection) -> Result<(String, String)> {
+    let theme = app_config::with_settings(conn).theme;
+    Ok((theme.piece_set, theme.board_theme))
+}
+
+/// Check a piece set and board theme exist and load, before selecting them
+pub fn check(piece_set: &str, board_theme: &str) -> std::result::Result<(), String> {
+    piece_images(piece_set)?;
+    find_theme(board_theme).ok_or_else(|| format!("Unknown board theme: {}", board_theme))?;
+    Ok(())
+}
+
+pub fn select(conn: &Connection, piece_set: &str, board_theme: &str) -> Result<()> {
+    repositories::set_setting(conn, PIECE_SET_SETTING_KEY, piece_set)?;
+    repositories::set_setting(conn, BOARD_THEME_SETTING_KEY, board_theme)
+}
+
+/// The selected assets, falling back to the defaults when a stored one has gone missing
+pub fn appearance(conn: &Connection) -> Result<BoardAppearance> {
+    let (piece_set, theme) = selection(conn)?;
+    let (piece_set, pieces) = match piece_images(&piece_set) {
+        Ok(images) => (piece_set, images),
+        Err(e) => {
+            tracing::warn!("using the standard pieces: {}", e);
+            (DEFAULT_PIECE_SET.to_string(), Arc::new(HashMap::new()))
+        }
+    };
+    let board_theme = find_theme(&theme)
+        .or_else(|| find_theme(DEFAULT_BOARD_THEME))
+        .expect("default board theme is built in");
+
+    Ok(BoardAppearance {
+        piece_set,
+        board_theme,
+        pieces: (*pieces).clone(),
+    })
+}
+
+/// Diagram style for exports and reports, matching the GUI board
+pub fn diagram_style() -> DiagramStyle {
+    let coordinates = notation_prefs::active().coordinates;
+    let Ok(appearance) = DB.with_conn(appearance) else {
+        return DiagramStyle { coordinates, ..DiagramStyle::default() };
+    };
+    DiagramStyle {
+        light_square: appearance.board_theme.light_square,
+        dark_square: appearance.board_theme.dark_square,
+        pieces: if appearance.pieces.is_empty() { PieceArt::Glyphs } else { PieceArt::Images(appearance.pieces) },
+        coordinates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_loads_assets_and_stores_selection() {
+        let dir = std::env::temp_dir().join(format!("tacticus-pieces-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for key in &PIECE_KEYS[..11] {
+            std::fs::write(dir.join(format!("{}.svg", key)), "<svg/>").unwrap();
+        }
+        assert!(load_piece_set(&dir).unwrap_err().contains("bP"));
+        std::fs::write(dir.join("bP.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let set = load_piece_set(&dir).unwrap();
+        assert_eq!(set["wK"], "data:image/svg+xml;base64,PHN2Zy8+");
+        assert!(set["bP"].starts_with("data:image/png;base64,"));
+
+        let theme = dir.join("night.json");
+        std::fs::write(&theme, r##"{"light_square": "#cccccc", "dark_square": "#333333"}"##).unwrap();
+        assert_eq!(load_board_theme(&theme).unwrap(), ("#cccccc".to_string(), "#333333".to_string()));
+        std::fs::write(&theme, r##"{"light_square": "white\" onload=\"x", "dark_square": "#333333"}"##).unwrap();
+        assert!(load_board_theme(&theme).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        assert_eq!(appearance(&conn).unwrap().board_theme.name, DEFAULT_BOARD_THEME);
+        assert!(check("no-such-set", "green").is_err());
+        assert!(check(LETTERS_PIECE_SET, "no-such-theme").is_err());
+        check(LETTERS_PIECE_SET, "green").unwrap();
+        select(&conn, LETTERS_PIECE_SET, "green").unwrap();
+        let selected = appearance(&conn).unwrap();
+        assert_eq!(selected.piece_set, LETTERS_PIECE_SET);
+        assert_eq!(selected.board_theme.dark_square, "#769656");
+        assert_eq!(selected.pieces.len(), 12);
+    }
+}
@@ -0,0 +1,277 @@
+use chess::{Board, Color};
+use chess_core::notation::piece_letter;
+use chess_core::{color_from_name, color_name, parse_fen, parse_legal_uci, pieces_of, to_san, Position, Side};
+use chess_engine::Evaluator;
+use chess_trainer::{misconception, WrongMove};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Moves of history included in the coach context
+pub const RECENT_MOVES: usize = 8;
+
+/// What the coach is told about the board the player is looking at
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BoardContext {
+    pub fen: String,
+    /// The last few moves in SAN, oldest first
+    pub recent_moves: Vec<String>,
+    /// Engine evaluation in pawns from White's point of view
+    pub evaluation: Option<f32>,
+    /// Material in pawns, White minus Black
+    pub material_balance: i32,
+    /// What the active exercise asks for, if the board belongs to one
+    pub exercise_goal: Option<String>,
+    /// The exercise on screen, numbered as the training commands number it
+    pub exercise_id: Option<usize>,
+    /// "white" or "black"; none when the player isn't playing a side, e.g. on the analysis board
+    pub player_color: Option<String>,
+    /// Side shown at the bottom of the screen, "white" or "black"
+    pub orientation: String,
+    /// The player's pieces, e.g. "Ke1", king first; empty without a player colour
+    pub my_pieces: Vec<String>,
+    pub their_pieces: Vec<String>,
+    /// Wrong moves tried at the exercise, in SAN with why each fails when the board shows it
+    pub wrong_tries: Vec<String>,
+    /// How long the player has been on the exercise
+    pub seconds_on_exercise: Option<u32>,
+}
+
+impl BoardContext {
+    /// Context block added to the coach prompt
+    pub fn to_prompt(&self) -> String {
+        let mut lines = vec![
+            "The player is looking at this board; use it when they ask about \"this move\" or \"this position\".".to_string(),
+            format!("FEN: {}", self.fen),
+        ];
+        if !self.recent_moves.is_empty() {
+            lines.push(format!("Last moves: {}", self.recent_moves.join(" ")));
+        }
+        if let Some(evaluation) = self.evaluation {
+            lines.push(format!("Engine evaluation: {:+.2} (positive favours White)", evaluation));
+        }
+        lines.push(format!("Material balance: {:+} (White minus Black)", self.material_balance));
+        match &self.player_color {
+            Some(color) => {
+                lines.push(format!("The player has {} and sees the board from {}'s side.", color, self.orientation));
+                lines.push(format!("Their pieces: {}", self.my_pieces.join(" ")));
+                lines.push(format!("Opponent's pieces: {}", self.their_pieces.join(" ")));
+            }
+            None => lines.push(format!("The player sees the board from {}'s side.", self.orientation)),
+        }
+        if let Some(goal) = &self.exercise_goal {
+            lines.push(format!("Exercise goal: {}", goal));
+        }
+        if !self.wrong_tries.is_empty() {
+            lines.push(format!(
+                "Wrong moves they have tried: {}. When giving hints, speak to why these fail rather than nudging in general.",
+                self.wrong_tries.join("; ")
+            ));
+        }
+        if let Some(seconds) = self.seconds_on_exercise {
+            lines.push(match seconds {
+                0..=59 => format!("They have been on this exercise for {} seconds.", seconds),
+                _ => format!("They have been on this exercise for {} minute(s).", seconds / 60),
+            });
+        }
+        lines.join("\n")
+    }
+}
+
+/// Anything that can describe the board currently on screen
+pub trait ContextProvider: Send {
+    /// `None` when there is no board to describe
+    fn board_context(&self) -> Option<BoardContext>;
+}
+
+/// Board state pushed by the UI as the player moves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardSnapshot {
+    /// Position the moves were played from; the standard start when absent
+    #[serde(default)]
+    pub start_fen: Option<String>,
+    /// Moves in UCI from `start_fen`
+    #[serde(default)]
+    pub moves: Vec<String>,
+    pub fen: String,
+    #[serde(default)]
+    pub exercise_goal: Option<String>,
+    /// Index of the exercise on screen; chats about the board keep its solution locked
+    #[serde(default)]
+    pub exercise_id: Option<usize>,
+    /// Side the player is playing, "white" or "black"
+    #[serde(default)]
+    pub player_color: Option<String>,
+    /// Side at the bottom of the board as shown, which the player may have flipped;
+    /// the player's side, or White, when absent
+    #[serde(default)]
+    pub orientation: Option<String>,
+    /// Wrong moves tried at the exercise on screen, each with the position it was played
+    /// from; `start_fen` for those without one
+    #[serde(default)]
+    pub wrong_moves: Vec<WrongMove>,
+    /// When the player started the exercise, in milliseconds since the epoch
+    #[serde(default)]
+    pub exercise_started_at: Option<i64>,
+}
+
+impl BoardSnapshot {
+    /// SAN for the last `RECENT_MOVES` moves, stopping at the first one that doesn't replay
+    fn recent_san(&self) -> Vec<String> {
+        let mut board = match &self.start_fen {
+            Some(fen) => match parse_fen(fen) {
+                Ok(board) => board,
+                Err(_) => return Vec::new(),
+            },
+            None => Board::default(),
+        };
+
+        let mut san = Vec::new();
+        for uci in &self.moves {
+            let Ok(chess_move) = parse_legal_uci(&board, uci) else { break };
+            san.push(to_san(&board, chess_move));
+            board = board.make_move_new(chess_move);
+        }
+        let skip = san.len().saturating_sub(RECENT_MOVES);
+        san.split_off(skip)
+    }
+
+    /// Each wrong move with why it fails where it was played; moves that don't replay there as given
+    fn wrong_tries(&self) -> Vec<String> {
+        let start = self.start_fen.as_deref().and_then(|fen| parse_fen(fen).ok());
+        self.wrong_moves
+            .iter()
+            .map(|wrong| match wrong.board(start.as_ref()).and_then(|board| misconception(&board, &wrong.tried)) {
+                Some(found) => format!("{} ({})", found.tried, found.reason),
+                None => wrong.tried.clone(),
+            })
+            .collect()
+    }
+}
+
+impl ContextProvider for BoardSnapshot {
+    fn board_context(&self) -> Option<BoardContext> {
+        let board = parse_fen(&self.fen).ok()?;
+        let evaluation = Evaluator::evaluate_position(&board).score.pawns();
+        let player = self.player_color.as_deref().and_then(color_from_name);
+        let orientation = self.orientation.as_deref().and_then(color_from_name).or(player).unwrap_or(Color::White);
+        let pieces = |side: Side| match player {
+            Some(player) => pieces_of(&board, player, side)
+                .into_iter()
+                .map(|(piece, square)| format!("{}{}", piece_letter(piece), square))
+                .collect(),
+            None => vec![],
+        };
+
+        Some(BoardContext {
+            fen: self.fen.clone(),
+            recent_moves: self.recent_san(),
+            evaluation: Some(evaluation),
+            material_balance: Position::new(board).material_balance(),
+            exercise_goal: self.exercise_goal.clone(),
+            exercise_id: self.exercise_id,
+            player_color: player.map(|c| color_name(c).to_string()),
+            orientation: color_name(orientation).to_string(),
+            my_pieces: pieces(Side::Mine),
+            their_pieces: pieces(Side::Theirs),
+            wrong_tries: self.wrong_tries(),
+            seconds_on_exercise: self.exercise_started_at.map(|started| {
+                let elapsed = chrono::Utc::now().timestamp_millis().saturating_sub(started) / 1000;
+                elapsed.clamp(0, u32::MAX as i64) as u32
+            }),
+        })
+    }
+}
+
+lazy_static! {
+    /// Where the coach reads the on-screen board from
+    static ref PROVIDER: Mutex<Option<Box<dyn ContextProvider>>> = Mutex::new(None);
+}
+
+/// Replace the active provider; `None` when no board is on screen
+pub fn set_provider(provider: Option<Box<dyn ContextProvider>>) {
+    if let Ok(mut active) = PROVIDER.lock() {
+        *active = provider;
+    }
+}
+
+/// Context from the active provider, if there is one
+pub fn current() -> Option<BoardContext> {
+    PROVIDER.lock().ok()?.as_ref()?.board_context()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(BoardContext);
+
+    impl ContextProvider for Fixed {
+        fn board_context(&self) -> Option<BoardContext> {
+            Some(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_prompt_and_provider() {
+        let context = BoardContext {
+            fen: "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3".to_string(),
+            recent_moves: vec!["e4".to_string(), "e5".to_string(), "Nf3".to_string(), "Nc6".to_string()],
+            evaluation: Some(0.3),
+            material_balance: 0,
+            exercise_goal: Some("Develop a piece towards the centre".to_string()),
+            exercise_id: None,
+            player_color: None,
+            orientation: "white".to_string(),
+            my_pieces: vec![],
+            their_pieces: vec![],
+            wrong_tries: vec![],
+            seconds_on_exercise: None,
+        };
+
+        let prompt = context.to_prompt();
+        assert!(prompt.contains("FEN: r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"));
+        assert!(prompt.contains("Last moves: e4 e5 Nf3 Nc6"));
+        assert!(prompt.contains("Engine evaluation: +0.30"));
+        assert!(prompt.contains("Material balance: +0"));
+        assert!(prompt.contains("Exercise goal: Develop a piece towards the centre"));
+        assert!(prompt.contains("The player sees the board from white's side."));
+
+        // As Black with the board flipped to White's side, the player's pieces are still Black's
+        let snapshot = BoardSnapshot {
+            start_fen: None,
+            moves: vec![],
+            fen: "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1".to_string(),
+            exercise_goal: None,
+            exercise_id: None,
+            player_color: Some("black".to_string()),
+            orientation: Some("white".to_string()),
+            wrong_moves: vec![],
+            exercise_started_at: None,
+        };
+        let flipped = snapshot.board_context().unwrap();
+        assert_eq!(flipped.my_pieces, vec!["Ke8"]);
+        assert_eq!(flipped.their_pieces, vec!["Ke1", "e2"]);
+        assert!(flipped.to_prompt().contains("The player has black and sees the board from white's side."));
+
+        // Wrong tries reach the coach with the reason they fail
+        let exercise = BoardSnapshot {
+            start_fen: Some("5rk1/5p1p/8/8/8/8/5Q2/6K1 w - - 0 1".to_string()),
+            moves: vec![],
+            fen: "5rk1/5p1p/8/8/8/8/5Q2/6K1 w - - 0 1".to_string(),
+            exercise_goal: None,
+            exercise_id: Some(3),
+            player_color: Some("white".to_string()),
+            orientation: None,
+            wrong_moves: vec![WrongMove::at_start("f2f7"), WrongMove::at_start("h9h9")],
+            exercise_started_at: Some(chrono::Utc::now().timestamp_millis() - 125_000),
+        };
+        let prompt = exercise.board_context().unwrap().to_prompt();
+        assert!(prompt.contains("Wrong moves they have tried: Qxf7+ (f7 is defended by the rook on f8); h9h9."));
+        assert!(prompt.contains("They have been on this exercise for 2 minute(s)."));
+
+        set_provider(Some(Box::new(Fixed(context.clone()))));
+        assert_eq!(current(), Some(context));
+        set_provider(None);
+        assert_eq!(current(), None);
+    }
+}
@@ -0,0 +1,246 @@
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage};
+use ort::session::Session;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Side length, in pixels, each square is resized to before classification
+pub const SQUARE_INPUT_SIZE: u32 = 32;
+
+/// Class order of the piece classifier's output. Index 0 is an empty square,
+/// the rest are FEN piece letters.
+pub const PIECE_CLASSES: [Option<char>; 13] = [
+    None,
+    Some('P'), Some('N'), Some('B'), Some('R'), Some('Q'), Some('K'),
+    Some('p'), Some('n'), Some('b'), Some('r'), Some('q'), Some('k'),
+];
+
+/// Environment variable that overrides the bundled classifier model path
+pub const MODEL_PATH_ENV: &str = "TACTICUS_PIECE_MODEL";
+
+/// Rows/columns whose brightness varies less than this are treated as border, not board
+const BORDER_STDDEV_THRESHOLD: f32 = 4.0;
+
+/// The recognised contents of the 64 squares, rank 8 first, a-file first.
+/// Each entry is a FEN piece letter or `None` for an empty square.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecognizedBoard {
+    pub squares: Vec<Option<char>>,
+    /// Classifier confidence per square (0.0 - 1.0)
+    pub confidence: Vec<f32>,
+}
+
+impl RecognizedBoard {
+    /// Lowest per-square confidence, a quick signal for whether the user should double-check
+    pub fn min_confidence(&self) -> f32 {
+        self.confidence.iter().cloned().fold(1.0, f32::min)
+    }
+
+    /// Piece placement plus the given side to move. Castling and en passant rights
+    /// can't be seen in a picture, so they are left empty.
+    pub fn to_fen(&self, white_to_move: bool) -> String {
+        let ranks: Vec<String> = self
+            .squares
+            .chunks(8)
+            .map(|rank| {
+                let mut row = String::new();
+                let mut empty = 0;
+                for square in rank {
+                    match square {
+                        Some(piece) => {
+                            if empty > 0 {
+                                row.push_str(&empty.to_string());
+                                empty = 0;
+                            }
+                            row.push(*piece);
+                        }
+                        None => empty += 1,
+                    }
+                }
+                if empty > 0 {
+                    row.push_str(&empty.to_string());
+                }
+                row
+            })
+            .collect();
+
+        format!("{} {} - - 0 1", ranks.join("/"), if white_to_move { "w" } else { "b" })
+    }
+}
+
+/// Turns a picture of a chessboard into square contents. Implementations can use
+/// any backend (local model, remote service, ...) - the import command only sees this trait.
+pub trait BoardRecognizer: Send + Sync {
+    /// Short identifier reported back to the UI
+    fn name(&self) -> &str;
+
+    /// Classify the 64 squares of an image already cropped to the board
+    fn recognize(&self, board: &GrayImage) -> Result<RecognizedBoard, String>;
+}
+
+/// Piece classifier backed by a small ONNX model. The model takes a batch of
+/// `[64, 1, 32, 32]` grayscale squares scaled to 0..1 and returns `[64, 13]`
+/// scores in `PIECE_CLASSES` order.
+pub struct OnnxRecognizer {
+    session: Mutex<Session>,
+}
+
+impl OnnxRecognizer {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let session = Session::builder()
+            .and_then(|b| b.commit_from_file(path))
+            .map_err(|e| format!("Failed to load piece classifier {}: {}", path.display(), e))?;
+        Ok(Self { session: Mutex::new(session) })
+    }
+
+    /// `$TACTICUS_PIECE_MODEL`, falling back to `<data dir>/tacticus/models/piece_classifier.onnx`
+    pub fn default_model_path() -> Option<PathBuf> {
+        std::env::var_os(MODEL_PATH_ENV).map(PathBuf::from).or_else(|| {
+            dirs::data_local_dir().map(|p| p.join("tacticus").join("models").join("piece_classifier.onnx"))
+        })
+    }
+}
+
+impl BoardRecognizer for OnnxRecognizer {
+    fn name(&self) -> &str {
+        "onnx"
+    }
+
+    fn recognize(&self, board: &GrayImage) -> Result<RecognizedBoard, String> {
+        let input = square_tensor(board);
+        let tensor = ort::value::Tensor::from_array(([64usize, 1, SQUARE_INPUT_SIZE as usize, SQUARE_INPUT_SIZE as usize], input))
+            .map_err(|e| format!("Failed to build input tensor: {}", e))?;
+        let mut session = self.session.lock().map_err(|_| "Piece classifier is unavailable".to_string())?;
+        let outputs = session
+            .run(ort::inputs![tensor])
+            .map_err(|e| format!("Piece classification failed: {}", e))?;
+        let (_, scores) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| format!("Unexpected classifier output: {}", e))?;
+
+        if scores.len() != 64 * PIECE_CLASSES.len() {
+            return Err(format!("Classifier returned {} scores, expected {}", scores.len(), 64 * PIECE_CLASSES.len()));
+        }
+
+        let mut squares = Vec::with_capacity(64);
+        let mut confidence = Vec::with_capacity(64);
+        for logits in scores.chunks(PIECE_CLASSES.len()) {
+            let (class, probability) = softmax_argmax(logits);
+            squares.push(PIECE_CLASSES[class]);
+            confidence.push(probability);
+        }
+
+        Ok(RecognizedBoard { squares, confidence })
+    }
+}
+
+fn softmax_argmax(logits: &[f32]) -> (usize, f32) {
+    let max = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = logits.iter().map(|l| (l - max).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+    let (index, best) = exps
+        .iter()
+        .enumerate()
+        .fold((0, 0.0), |acc, (i, &e)| if e > acc.1 { (i, e) } else { acc });
+    (index, best / sum)
+}
+
+/// Split a cropped board into 64 squares, resize each and flatten to the model's input layout
+fn square_tensor(board: &GrayImage) -> Vec<f32> {
+    let board = imageops::resize(board, SQUARE_INPUT_SIZE * 8, SQUARE_INPUT_SIZE * 8, FilterType::Triangle);
+    let mut data = Vec::with_capacity((64 * SQUARE_INPUT_SIZE * SQUARE_INPUT_SIZE) as usize);
+    for rank in 0..8 {
+        for file in 0..8 {
+            let square = imageops::crop_imm(&board, file * SQUARE_INPUT_SIZE, rank * SQUARE_INPUT_SIZE, SQUARE_INPUT_SIZE, SQUARE_INPUT_SIZE);
+            data.extend(square.to_image().pixels().map(|p| p.0[0] as f32 / 255.0));
+        }
+    }
+    data
+}
+
+fn line_is_uniform(values: impl Iterator<Item = u8>) -> bool {
+    let values: Vec<f32> = values.map(|v| v as f32).collect();
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    variance.sqrt() < BORDER_STDDEV_THRESHOLD
+}
+
+/// Locate the board in a screenshot by trimming flat-coloured margins, then taking the
+/// largest centred square of what remains. Returns `(x, y, size)`.
+pub fn detect_board_region(image: &GrayImage) -> Option<(u32, u32, u32)> {
+    let (width, height) = image.dimensions();
+    if width < 8 || height < 8 {
+        return None;
+    }
+
+    let row_uniform = |y: u32| line_is_uniform((0..width).map(|x| image.get_pixel(x, y).0[0]));
+    let col_uniform = |x: u32| line_is_uniform((0..height).map(|y| image.get_pixel(x, y).0[0]));
+
+    let top = (0..height).find(|&y| !row_uniform(y))?;
+    let bottom = (top..height).rev().find(|&y| !row_uniform(y))?;
+    let left = (0..width).find(|&x| !col_uniform(x))?;
+    let right = (left..width).rev().find(|&x| !col_uniform(x))?;
+
+    let region_width = right - left + 1;
+    let region_height = bottom - top + 1;
+    let size = region_width.min(region_height);
+    if size < 8 {
+        return None;
+    }
+
+    Some((left + (region_width - size) / 2, top + (region_height - size) / 2, size))
+}
+
+/// Load an image, find the board and classify it
+pub fn recognize_image(path: &Path, recognizer: &dyn BoardRecognizer) -> Result<RecognizedBoard, String> {
+    let image: DynamicImage = image::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+    let gray = image.to_luma8();
+    let (x, y, size) = detect_board_region(&gray).ok_or_else(|| "Could not find a chessboard in the image".to_string())?;
+    let board = imageops::crop_imm(&gray, x, y, size, size).to_image();
+    recognizer.recognize(&board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    fn checkerboard_with_margin(margin: u32, square: u32) -> GrayImage {
+        let size = margin * 2 + square * 8;
+        GrayImage::from_fn(size, size, |x, y| {
+            if x < margin || y < margin || x >= margin + square * 8 || y >= margin + square * 8 {
+                Luma([255])
+            } else if ((x - margin) / square + (y - margin) / square) % 2 == 0 {
+                Luma([220])
+            } else {
+                Luma([90])
+            }
+        })
+    }
+
+    #[test]
+    fn test_detect_board_region_trims_margin() {
+        let image = checkerboard_with_margin(20, 10);
+        assert_eq!(detect_board_region(&image), Some((20, 20, 80)));
+    }
+
+    #[test]
+    fn test_detect_board_region_blank_image() {
+        let image = GrayImage::from_pixel(64, 64, Luma([128]));
+        assert_eq!(detect_board_region(&image), None);
+    }
+
+    #[test]
+    fn test_recognized_board_to_fen() {
+        let mut squares = vec![None; 64];
+        squares[4] = Some('k');
+        squares[60] = Some('K');
+        squares[52] = Some('P');
+        let board = RecognizedBoard { squares, confidence: vec![0.9; 64] };
+
+        assert_eq!(board.to_fen(true), "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        assert_eq!(board.to_fen(false), "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1");
+        assert!((board.min_confidence() - 0.9).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,267 @@
+use chess_core::{parse_fen, parse_legal_uci};
+use chess_trainer::ExerciseType;
+use serde::{Deserialize, Serialize};
+
+use crate::coach_tools;
+use crate::commands::learning::{available_concepts, ChessConcept};
+use crate::database::repositories::{self, Game};
+use crate::DB;
+
+/// Buttons shown under one coach message
+pub const MAX_ACTIONS: usize = 4;
+
+/// Taught to the model so it can attach buttons to a reply. Markers are
+/// stripped from the text and only become buttons once they validate.
+pub const PROMPT_GUIDANCE: &str = "You can add buttons under your reply by writing markers on their own, \
+    which the app removes from the text:\n\
+    - [[open_concept:<concept name or id>]] opens a concept page\n\
+    - [[load_game:<game id>:<ply>]] opens one of the student's saved games after that many half-moves\n\
+    - [[start_training:<theme>]] starts training; themes are Tactics, Endgame, Opening, Positional, \
+    Calculation, Strategy and Defense\n\
+    - [[setup_position:<FEN>]] sets up a position on the analysis board\n\
+    Only offer a button when it helps, at most a few per reply.";
+
+/// Where a coach action button takes the user. Serialized with its fields
+/// alongside `action_type`, e.g. `{"action_type":"load_game","game_id":3,"ply":12,"fen":"..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action_type", rename_all = "snake_case")]
+pub enum ActionTarget {
+    OpenSettings,
+    PlayGame,
+    /// Start a quiz on positions from the player's own games
+    QuizMe,
+    StartTraining {
+        /// Canonical exercise type name; none for a general session
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        theme: Option<String>,
+    },
+    /// Pick up a stored training session where it was left, from its autosaved snapshot
+    ResumeTraining {
+        session_id: i64,
+        /// Theme of a fresh session to start instead when the snapshot is gone
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        theme: Option<String>,
+    },
+    OpenConcept {
+        concept_id: String,
+    },
+    LoadGame {
+        game_id: i64,
+        ply: usize,
+        /// Position after `ply` half-moves, worked out when the action was validated
+        fen: String,
+    },
+    SetupPosition {
+        fen: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoachAction {
+    pub label: String,
+    #[serde(flatten)]
+    pub target: ActionTarget,
+}
+
+impl CoachAction {
+    pub fn new(label: impl Into<String>, target: ActionTarget) -> Self {
+        Self {
+            label: label.into(),
+            target,
+        }
+    }
+}
+
+/// An action as the model wrote it, before validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionRequest {
+    OpenConcept(String),
+    LoadGame { game_id: i64, ply: usize },
+    StartTraining(String),
+    SetupPosition(String),
+}
+
+impl ActionRequest {
+    /// `kind:args` from inside a `[[...]]` marker
+    fn parse(marker: &str) -> Result<Self, String> {
+        let (kind, args) = marker.split_once(':').ok_or_else(|| format!("Malformed action '{}'", marker))?;
+        let args = args.trim();
+        if args.is_empty() {
+            return Err(format!("Action '{}' has no argument", kind.trim()));
+        }
+        match kind.trim() {
+            "open_concept" => Ok(ActionRequest::OpenConcept(args.to_string())),
+            "start_training" => Ok(ActionRequest::StartTraining(args.to_string())),
+            "setup_position" => Ok(ActionRequest::SetupPosition(args.to_string())),
+            "load_game" => {
+                let (game_id, ply) = args.split_once(':').unwrap_or((args, "0"));
+                Ok(ActionRequest::LoadGame {
+                    game_id: game_id.trim().parse().map_err(|_| format!("Invalid game id '{}'", game_id))?,
+                    ply: ply.trim().parse().map_err(|_| format!("Invalid ply '{}'", ply))?,
+                })
+            }
+            other => Err(format!("Unknown action '{}'", other)),
+        }
+    }
+}
+
+/// Pull `[[kind:args]]` markers out of a coach reply. Returns the reply with
+/// the markers removed and every marker in order, parsed or not.
+pub fn extract_actions(text: &str) -> (String, Vec<Result<ActionRequest, String>>) {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut requests = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        let Some(len) = rest[start + 2..].find("]]") else {
+            break;
+        };
+        cleaned.push_str(&rest[..start]);
+        requests.push(ActionRequest::parse(&rest[start + 2..start + 2 + len]));
+        rest = &rest[start + 2 + len + 2..];
+    }
+    cleaned.push_str(rest);
+
+    // Markers on their own line leave blank runs behind
+    let mut tidy = String::with_capacity(cleaned.len());
+    for line in cleaned.lines().map(str::trim_end) {
+        if line.is_empty() && (tidy.is_empty() || tidy.ends_with("\n\n")) {
+            continue;
+        }
+        tidy.push_str(line);
+        tidy.push('\n');
+    }
+    (tidy.trim_end().to_string(), requests)
+}
+
+/// Check `request` against what the app actually has, returning the button for it
+pub fn validate(request: &ActionRequest) -> Result<CoachAction, String> {
+    match request {
+        ActionRequest::OpenConcept(name) => validate_concept(&available_concepts(), name),
+        ActionRequest::StartTraining(theme) => validate_theme(theme),
+        ActionRequest::SetupPosition(fen) => {
+            parse_fen(fen.trim()).map_err(|e| e.to_string())?;
+            Ok(CoachAction::new("Set up position", ActionTarget::SetupPosition { fen: fen.trim().to_string() }))
+        }
+        ActionRequest::LoadGame { game_id, ply } => {
+            let profile = DB
+                .with_conn(repositories::get_first_profile)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "No profile".to_string())?;
+            let game = DB
+                .with_conn(|conn| repositories::get_game_by_id(conn, *game_id))
+                .map_err(|e| e.to_string())?
+                .filter(|g| g.profile_id == profile.id)
+                .ok_or_else(|| format!("No saved game {}", game_id))?;
+            validate_game(&game, *ply)
+        }
+    }
+}
+
+/// Validate every marker in a reply, dropping the ones that fail and any duplicates
+pub fn validate_all(requests: Vec<Result<ActionRequest, String>>) -> Vec<CoachAction> {
+    let mut actions: Vec<CoachAction> = vec![];
+    for request in requests {
+        match request.and_then(|r| validate(&r)) {
+            Ok(action) if !actions.contains(&action) => actions.push(action),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("dropping coach action: {}", e),
+        }
+    }
+    actions.truncate(MAX_ACTIONS);
+    actions
+}
+
+pub fn concept_action(concept: &ChessConcept) -> CoachAction {
+    CoachAction::new(
+        format!("Open {}", concept.name),
+        ActionTarget::OpenConcept { concept_id: concept.id.clone() },
+    )
+}
+
+fn validate_concept(concepts: &[ChessConcept], name: &str) -> Result<CoachAction, String> {
+    coach_tools::find_concept(concepts, name)
+        .map(concept_action)
+        .ok_or_else(|| format!("No concept called '{}'", name))
+}
+
+fn validate_theme(theme: &str) -> Result<CoachAction, String> {
+    let theme: ExerciseType = theme.parse().map_err(|e: chess_trainer::ParseNameError| e.to_string())?;
+    Ok(CoachAction::new(
+        format!("Train {}", theme),
+        ActionTarget::StartTraining { theme: Some(theme.as_str().to_string()) },
+    ))
+}
+
+/// Replay `game` to `ply` so the frontend gets a position it can show as is
+pub fn validate_game(game: &Game, ply: usize) -> Result<CoachAction, String> {
+    if ply > game.moves.len() {
+        return Err(format!("Game {} only has {} half-moves, not {}", game.id, game.moves.len(), ply));
+    }
+    let mut board = parse_fen(&game.initial_fen).map_err(|e| e.to_string())?;
+    for uci in &game.moves[..ply] {
+        let chess_move = parse_legal_uci(&board, uci).map_err(|e| format!("Game {}: {}", game.id, e))?;
+        board = board.make_move_new(chess_move);
+    }
+    let label = if ply == 0 {
+        format!("Review game #{}", game.id)
+    } else {
+        format!("Review game #{}, move {}", game.id, ply.div_ceil(2))
+    };
+    Ok(CoachAction::new(
+        label,
+        ActionTarget::LoadGame {
+            game_id: game.id,
+            ply,
+            fen: board.to_string(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concept(id: &str, name: &str) -> ChessConcept {
+        ChessConcept {
+            id: id.to_string(),
+            name: name.to_string(),
+            category: "Tactics".to_string(),
+            difficulty: "Beginner".to_string(),
+            short_description: String::new(),
+            full_explanation: String::new(),
+            example_fen: None,
+            example_arrows: vec![],
+            example_highlights: vec![],
+            related_concepts: vec![],
+            practice_exercises: vec![],
+            model_games: vec![],
+        }
+    }
+
+    #[test]
+    fn test_extracts_and_validates_actions() {
+        let reply = "Your knight can fork here.\n\n[[open_concept:Knight Fork]]\n[[load_game:12:31]]\n\n[[teleport:now]]\nTry it.";
+        let (text, requests) = extract_actions(reply);
+        assert_eq!(text, "Your knight can fork here.\n\nTry it.");
+        assert_eq!(requests[0], Ok(ActionRequest::OpenConcept("Knight Fork".to_string())));
+        assert_eq!(requests[1], Ok(ActionRequest::LoadGame { game_id: 12, ply: 31 }));
+        assert!(requests[2].is_err());
+        assert!(ActionRequest::parse("load_game:twelve").is_err());
+        assert_eq!(extract_actions("no markers [[unclosed").0, "no markers [[unclosed");
+
+        let concepts = vec![concept("knight_fork", "Knight Fork")];
+        let action = validate_concept(&concepts, "knight fork").unwrap();
+        assert_eq!(action.label, "Open Knight Fork");
+        assert_eq!(action.target, ActionTarget::OpenConcept { concept_id: "knight_fork".to_string() });
+        assert!(validate_concept(&concepts, "castling").is_err());
+
+        let training = validate_theme("endgame").unwrap();
+        assert_eq!(training.target, ActionTarget::StartTraining { theme: Some("Endgame".to_string()) });
+        assert!(validate_theme("juggling").is_err());
+
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(json["action_type"], "open_concept");
+        assert_eq!(json["concept_id"], "knight_fork");
+        assert_eq!(serde_json::from_value::<CoachAction>(json).unwrap(), action);
+    }
+}
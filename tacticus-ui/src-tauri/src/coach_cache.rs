@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
+
+use crate::database::repositories;
+use crate::DB;
+
+/// How long a cached coach reply stays valid
+pub const CACHE_TTL_HOURS: i64 = 24 * 7;
+
+lazy_static! {
+    /// One lock per request key, so identical requests in flight wait for the first reply
+    static ref IN_FLIGHT: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+/// Collapse whitespace and case so trivially different prompts share a cache entry
+pub fn normalize_prompt(prompt: &str) -> String {
+    prompt.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Drop the move counters, which don't change the position being discussed
+pub fn normalize_fen(fen: &str) -> String {
+    fen.split_whitespace().take(4).collect::<Vec<_>>().join(" ")
+}
+
+/// Hash of everything that determines a reply. The hasher isn't stable across Rust
+/// releases, which at worst turns an old entry into a miss.
+pub fn cache_key(model: &str, system_prompts: &[&str], user_prompt: &str, position: Option<&str>) -> String {
+    let mut hasher = DefaultHasher::new();
+    model.hash(&mut hasher);
+    for prompt in system_prompts {
+        normalize_prompt(prompt).hash(&mut hasher);
+    }
+    normalize_prompt(user_prompt).hash(&mut hasher);
+    position.map(normalize_fen).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+pub fn lookup(key: &str) -> Option<String> {
+    DB.with_conn(|conn| repositories::get_cached_response(conn, key, CACHE_TTL_HOURS))
+        .ok()
+        .flatten()
+}
+
+pub fn store(key: &str, model: &str, response: &str) {
+    if let Err(e) = DB.with_conn(|conn| repositories::put_cached_response(conn, key, model, response)) {
+        tracing::warn!("Failed to cache coach response: {}", e);
+    }
+}
+
+/// Hold while checking the cache and making the request for `key`
+pub async fn lock(key: &str) -> OwnedMutexGuard<()> {
+    let entry = IN_FLIGHT
+        .lock()
+        .unwrap()
+        .entry(key.to_string())
+        .or_default()
+        .clone();
+    let guard = entry.lock_owned().await;
+
+    // Forget locks nobody else is waiting on so the map doesn't grow without bound
+    IN_FLIGHT.lock().unwrap().retain(|_, lock| Arc::strong_count(lock) > 1);
+    guard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_normalization() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        let later = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 4 9";
+
+        let key = cache_key("model", &["System"], "Analyze  this\nposition", Some(fen));
+        assert_eq!(key, cache_key("model", &["system"], "analyze this position", Some(later)));
+        assert_ne!(key, cache_key("other-model", &["System"], "Analyze this position", Some(fen)));
+        assert_ne!(key, cache_key("model", &["System", "Kid mode"], "Analyze this position", Some(fen)));
+        assert_ne!(key, cache_key("model", &["System"], "Analyze this position", None));
+    }
+}
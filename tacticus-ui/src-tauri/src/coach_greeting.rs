@@ -0,0 +1,226 @@
+use chess_trainer::ExerciseType;
+use rusqlite::{Connection, Result};
+
+use crate::coach_actions::{ActionTarget, CoachAction, MAX_ACTIONS};
+use crate::database::repositories::{self, Profile, TrainingSessionRecord, WeaknessEntry};
+use crate::progress_diff::{self, MetricChange};
+
+/// How long after a miss a position comes back for review
+const REVIEW_DELAY_HOURS: i64 = 20;
+
+/// Look back for the weakness the greeting mentions
+const WEAKNESS_DAYS: i32 = 30;
+
+/// Attempts on a theme before it is worth calling a weakness
+const MIN_WEAKNESS_ATTEMPTS: i32 = 5;
+
+/// Wins in a row worth mentioning
+const MIN_STREAK: i32 = 2;
+
+/// What the coach knows about the player when they open the chat
+#[derive(Debug, Clone)]
+pub struct Briefing {
+    pub profile: Profile,
+    pub unfinished_session: Option<TrainingSessionRecord>,
+    /// Missed positions due for another try, by exercise type, most first
+    pub due_reviews: Vec<(String, i32)>,
+    pub weakness: Option<WeaknessEntry>,
+    /// Clearest improvement of the latest games over the ones before them
+    pub progress: Option<MetricChange>,
+}
+
+pub fn gather(conn: &Connection, profile: Profile) -> Result<Briefing> {
+    let due_before = (chrono::Utc::now() - chrono::Duration::hours(REVIEW_DELAY_HOURS)).to_rfc3339();
+    let weakness = repositories::get_weakness_history(conn, profile.id, WEAKNESS_DAYS)?
+        .into_iter()
+        .find(|w| w.total_attempts >= MIN_WEAKNESS_ATTEMPTS);
+    Ok(Briefing {
+        unfinished_session: repositories::get_unfinished_training_session(conn, profile.id)?,
+        due_reviews: repositories::get_due_reviews(conn, profile.id, &due_before)?,
+        weakness,
+        progress: progress_diff::progress_diff(conn, profile.id, progress_diff::DEFAULT_GAMES)?
+            .best_improvement()
+            .cloned(),
+        profile,
+    })
+}
+
+/// Canonical name of a stored exercise type, if it still is one
+fn theme(exercise_type: &str) -> Option<ExerciseType> {
+    exercise_type.parse().ok()
+}
+
+fn train(label: String, theme: Option<ExerciseType>) -> CoachAction {
+    CoachAction::new(
+        label,
+        ActionTarget::StartTraining {
+            theme: theme.map(|t| t.as_str().to_string()),
+        },
+    )
+}
+
+/// The greeting text and the buttons under it, most pressing first
+pub fn compose(briefing: &Briefing) -> (String, Vec<CoachAction>) {
+    let profile = &briefing.profile;
+    if profile.exercises_completed == 0 && profile.games_played == 0 {
+        let greeting = format!(
+            "Welcome to Tacticus, {}. I'm Gurgeh, your chess coach - named after the legendary \
+             game player from the Culture.\n\n\
+             I see you're starting at {} ELO. Let's begin with some fundamentals and discover \
+             where your strengths lie. Together, we'll master this ancient game.",
+            profile.name, profile.current_elo
+        );
+        let actions = vec![
+            train("Start Training".to_string(), None),
+            CoachAction::new("Play a Game", ActionTarget::PlayGame),
+        ];
+        return (greeting, actions);
+    }
+
+    let mut lines = vec![format!("Welcome back, {}.", profile.name)];
+    let mut actions: Vec<CoachAction> = vec![];
+
+    if profile.streak >= MIN_STREAK {
+        lines.push(format!("You've won your last {} games in a row - let's keep that going.", profile.streak));
+    }
+
+    if let Some(progress) = &briefing.progress {
+        lines.push(progress.summary.clone());
+    }
+
+    if let Some(session) = &briefing.unfinished_session {
+        lines.push(format!(
+            "You stopped a training session {} of {} exercises in.",
+            session.completed_exercises, session.total_exercises
+        ));
+        let theme = session.strategies_covered.first().and_then(|s| theme(s));
+        actions.push(CoachAction::new(
+            "Resume Training",
+            ActionTarget::ResumeTraining { session_id: session.id, theme: theme.map(|t| t.as_str().to_string()) },
+        ));
+    }
+
+    if let Some((exercise_type, _)) = briefing.due_reviews.first() {
+        let total: i32 = briefing.due_reviews.iter().map(|(_, count)| count).sum();
+        lines.push(if total == 1 {
+            format!("One {} position you missed is due for another try.", exercise_type.to_lowercase())
+        } else {
+            format!(
+                "{} positions you missed are due for another try, mostly {}.",
+                total,
+                exercise_type.to_lowercase()
+            )
+        });
+        actions.push(train(format!("Review {}", exercise_type), theme(exercise_type)));
+    }
+
+    if let Some(weakness) = &briefing.weakness {
+        let name = weakness.exercise_type.to_lowercase();
+        lines.push(match weakness.recent_trend.as_str() {
+            "declining" => format!(
+                "Your {} has slipped lately and sits at {:.0}% solved, so it's worth some attention.",
+                name, weakness.success_rate
+            ),
+            "improving" => format!(
+                "Your {} is coming along, now {:.0}% solved - a little more and it stops being your weakest theme.",
+                name, weakness.success_rate
+            ),
+            _ => format!("{} is your weakest theme at {:.0}% solved.", weakness.exercise_type, weakness.success_rate),
+        });
+        actions.push(train(format!("Train {}", weakness.exercise_type), theme(&weakness.exercise_type)));
+    }
+
+    if lines.len() == 1 {
+        lines.push(format!(
+            "You've completed {} exercises so far and your rating is {}. Ready to continue your training?",
+            profile.exercises_completed, profile.current_elo
+        ));
+        actions.push(train("Start Training".to_string(), None));
+    }
+    actions.push(CoachAction::new("Play a Game", ActionTarget::PlayGame));
+    // The quiz draws on the player's own games
+    if profile.games_played > 0 {
+        actions.push(CoachAction::new("Quiz Me", ActionTarget::QuizMe));
+    }
+
+    // Reviews and weaknesses often land on the same theme
+    let mut unique: Vec<CoachAction> = vec![];
+    for action in actions {
+        if !unique.iter().any(|a| a.target == action.target) {
+            unique.push(action);
+        }
+    }
+    unique.truncate(MAX_ACTIONS);
+    (lines.join(" "), unique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::ExerciseResult;
+    use crate::database::schema::create_tables;
+
+    fn result(profile_id: i64, fen: &str, solved: bool) -> ExerciseResult {
+        ExerciseResult {
+            id: 0,
+            profile_id,
+            session_id: None,
+            exercise_type: "Tactics".to_string(),
+            difficulty: "Beginner".to_string(),
+            position_fen: fen.to_string(),
+            solved,
+            attempts: 1,
+            time_seconds: 30,
+            hints_used: 0,
+            created_at: String::new(),
+            flagged: false,
+            flag_reason: None,
+            alternative_solution: false,
+            scaffolding: vec![],
+        }
+    }
+
+    #[test]
+    fn test_briefing_from_history() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+
+        let (greeting, actions) = compose(&gather(&conn, profile.clone()).unwrap());
+        assert!(greeting.starts_with("Welcome to Tacticus, Ana."));
+        assert_eq!(actions.len(), 2);
+
+        // Two misses from yesterday, one since solved, and one fresh miss not yet due
+        for (fen, solved) in [("a", false), ("b", false), ("b", true), ("c", false)] {
+            repositories::record_exercise_result(&conn, &result(profile.id, fen, solved)).unwrap();
+        }
+        let yesterday = (chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339();
+        conn.execute("UPDATE exercise_results SET created_at = ?1 WHERE position_fen != 'c'", [&yesterday])
+            .unwrap();
+        let session = repositories::create_training_session(&conn, profile.id, 10, &["Endgame".to_string()]).unwrap();
+        conn.execute("UPDATE training_sessions SET completed_exercises = 4 WHERE id = ?1", [session])
+            .unwrap();
+        repositories::record_game_outcome(&conn, profile.id, 920, true).unwrap();
+        repositories::record_game_outcome(&conn, profile.id, 940, true).unwrap();
+
+        let profile = repositories::get_profile_by_id(&conn, profile.id).unwrap().unwrap();
+        let briefing = gather(&conn, profile).unwrap();
+        assert_eq!(briefing.due_reviews, vec![("Tactics".to_string(), 1)]);
+        // Four attempts are too few to call a weakness
+        assert!(briefing.weakness.is_none());
+
+        let (greeting, actions) = compose(&briefing);
+        assert_eq!(
+            greeting,
+            "Welcome back, Ana. You've won your last 2 games in a row - let's keep that going. \
+             You stopped a training session 4 of 10 exercises in. \
+             One tactics position you missed is due for another try."
+        );
+        let labels: Vec<&str> = actions.iter().map(|a| a.label.as_str()).collect();
+        assert_eq!(labels, vec!["Resume Training", "Review Tactics", "Play a Game", "Quiz Me"]);
+        assert_eq!(
+            actions[0].target,
+            ActionTarget::ResumeTraining { session_id: session, theme: Some("Endgame".to_string()) }
+        );
+    }
+}
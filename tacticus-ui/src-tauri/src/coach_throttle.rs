@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Error code prefix for calls refused because the per-minute cap was hit
+pub const COOLING_DOWN: &str = "cooling_down";
+/// Error code prefix for position analyses dropped in favour of a newer one
+pub const SUPERSEDED: &str = "superseded";
+
+/// Model calls allowed per window, across every LLM-backed command
+pub const MAX_CALLS_PER_MINUTE: usize = 20;
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Quiet period a position analysis waits for before it is sent, so scrubbing
+/// through moves only analyses the position the user stops on
+pub const ANALYSIS_DEBOUNCE: Duration = Duration::from_millis(400);
+
+lazy_static! {
+    pub static ref COACH_CALLS: Throttle = Throttle::new(MAX_CALLS_PER_MINUTE, WINDOW);
+    pub static ref POSITION_ANALYSIS: Debouncer = Debouncer::default();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleError {
+    CoolingDown { retry_after_secs: u64 },
+    Superseded,
+}
+
+impl fmt::Display for ThrottleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThrottleError::CoolingDown { retry_after_secs } => write!(
+                f,
+                "{}: Gurgeh needs a moment. Too many coach requests in the last minute; try again in {}s",
+                COOLING_DOWN, retry_after_secs
+            ),
+            ThrottleError::Superseded => write!(f, "{}: a newer position was requested", SUPERSEDED),
+        }
+    }
+}
+
+impl From<ThrottleError> for String {
+    fn from(e: ThrottleError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Sliding-window cap on calls
+pub struct Throttle {
+    limit: usize,
+    window: Duration,
+    calls: Mutex<VecDeque<Instant>>,
+}
+
+impl Throttle {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            calls: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Take one call from the allowance, or say how long until one frees up
+    pub fn acquire(&self) -> Result<(), ThrottleError> {
+        self.acquire_at(Instant::now())
+    }
+
+    pub fn acquire_at(&self, now: Instant) -> Result<(), ThrottleError> {
+        let mut calls = self.calls.lock().unwrap();
+        while calls.front().is_some_and(|t| now.duration_since(*t) >= self.window) {
+            calls.pop_front();
+        }
+        if calls.len() >= self.limit {
+            let oldest = calls.front().copied().unwrap_or(now);
+            let wait = self.window.saturating_sub(now.duration_since(oldest));
+            return Err(ThrottleError::CoolingDown {
+                retry_after_secs: wait.as_secs_f64().ceil().max(1.0) as u64,
+            });
+        }
+        calls.push_back(now);
+        Ok(())
+    }
+}
+
+/// Coalesces bursts of requests: each takes a ticket, waits out the debounce
+/// period and only goes ahead if no newer request arrived meanwhile
+#[derive(Default)]
+pub struct Debouncer {
+    latest: AtomicU64,
+}
+
+impl Debouncer {
+    pub fn ticket(&self) -> u64 {
+        self.latest.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn is_latest(&self, ticket: u64) -> bool {
+        self.latest.load(Ordering::SeqCst) == ticket
+    }
+
+    /// Wait `quiet` and fail with `Superseded` if another request came in
+    pub async fn settle(&self, quiet: Duration) -> Result<(), ThrottleError> {
+        let ticket = self.ticket();
+        tokio::time::sleep(quiet).await;
+        if self.is_latest(ticket) {
+            Ok(())
+        } else {
+            Err(ThrottleError::Superseded)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_caps_calls_per_window() {
+        let throttle = Throttle::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(throttle.acquire_at(start).is_ok());
+        assert!(throttle.acquire_at(start + Duration::from_secs(10)).is_ok());
+
+        let refused = throttle.acquire_at(start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(refused, ThrottleError::CoolingDown { retry_after_secs: 40 });
+        assert!(refused.to_string().starts_with(COOLING_DOWN));
+
+        // The first call has left the window
+        assert!(throttle.acquire_at(start + Duration::from_secs(60)).is_ok());
+        assert!(throttle.acquire_at(start + Duration::from_secs(61)).is_err());
+
+        let debouncer = Debouncer::default();
+        let first = debouncer.ticket();
+        let second = debouncer.ticket();
+        assert!(!debouncer.is_latest(first));
+        assert!(debouncer.is_latest(second));
+    }
+}
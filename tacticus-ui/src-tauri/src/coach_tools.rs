@@ -0,0 +1,350 @@
+use chess_core::GamePhase;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::commands::learning::{available_concepts, ChessConcept};
+use crate::conversation_mode;
+use crate::game_quiz;
+use crate::past_advice;
+
+pub const LOOKUP_CONCEPT: &str = "lookup_concept";
+pub const GET_QUIZ_POSITION: &str = "get_quiz_position";
+pub const CHECK_QUIZ_ANSWER: &str = "check_quiz_answer";
+pub const SEARCH_PAST_ADVICE: &str = "search_past_advice";
+pub const NEXT_GUIDED_QUESTION: &str = "next_guided_question";
+
+/// Concept names offered back to the model when a lookup misses
+const MAX_SUGGESTIONS: usize = 8;
+
+/// A function the coach model may call, in the OpenAI tool format OpenRouter accepts
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSpec {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub function: FunctionSpec,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: Value,
+}
+
+/// A call the model asked for; `arguments` is a JSON object encoded as a string
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_kind")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_kind() -> String {
+    "function".to_string()
+}
+
+/// Tools available in coach chat
+pub fn coach_tools() -> Vec<ToolSpec> {
+    vec![
+        ToolSpec {
+            kind: "function",
+            function: FunctionSpec {
+                name: LOOKUP_CONCEPT,
+                description: "Look up a concept in Tacticus's learning library. Returns the explanation the app shows, \
+                    an example position as FEN and related concepts. Use it before teaching a concept so your \
+                    explanation matches the in-app concept pages.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Concept name or id, e.g. 'knight fork', 'Pin' or 'opposition'"
+                        }
+                    },
+                    "required": ["name"]
+                }),
+            },
+        },
+        ToolSpec {
+            kind: "function",
+            function: FunctionSpec {
+                name: GET_QUIZ_POSITION,
+                description: "Get a position where the student went wrong in one of their recent games: the FEN, \
+                    move number, when the game was played and against whom. The answer is not included; check \
+                    the student's reply with check_quiz_answer. Pass a phase when the student wants to work on \
+                    one part of the game, e.g. endgames from their own games.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "phase": {
+                            "type": "string",
+                            "enum": ["Opening", "Middlegame", "Endgame"],
+                            "description": "Only positions from this phase of the game (optional)"
+                        }
+                    }
+                }),
+            },
+        },
+        ToolSpec {
+            kind: "function",
+            function: FunctionSpec {
+                name: CHECK_QUIZ_ANSWER,
+                description: "Check the move the student suggests for a quiz position and record the attempt. \
+                    Returns whether it was the engine's move, what they played in the game, the better move and \
+                    the quiz score so far.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "game_id": { "type": "integer", "description": "game_id from get_quiz_position" },
+                        "ply": { "type": "integer", "description": "ply from get_quiz_position" },
+                        "answer": { "type": "string", "description": "The student's move in SAN or UCI, e.g. 'Nf3' or 'g1f3'" }
+                    },
+                    "required": ["game_id", "ply", "answer"]
+                }),
+            },
+        },
+        ToolSpec {
+            kind: "function",
+            function: FunctionSpec {
+                name: SEARCH_PAST_ADVICE,
+                description: "Search what you told the student in earlier conversations, best matches first, with \
+                    how many days ago you said it. Use it before recommending what to study or how to play, so \
+                    you build on your earlier advice instead of contradicting it, e.g. 'last month I suggested \
+                    rook endgames, let's see how that went'.",
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "Words to look for, e.g. 'rook endgames' or 'Sicilian'"
+                        }
+                    },
+                    "required": ["query"]
+                }),
+            },
+        },
+        ToolSpec {
+            kind: "function",
+            function: FunctionSpec {
+                name: NEXT_GUIDED_QUESTION,
+                description: "Move a guided exercise on to its next leading question. Returns the question, what \
+                    the position shows to check the answer against, and on the last question the solution. Call \
+                    it only once the student has answered the question they are on or is plainly stuck, never \
+                    for replies like 'ok' or 'thanks'.",
+                parameters: json!({ "type": "object", "properties": {} }),
+            },
+        },
+    ]
+}
+
+/// A related concept, by id and display name
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedConcept {
+    pub id: String,
+    pub name: String,
+}
+
+/// What `lookup_concept` hands back to the model
+#[derive(Debug, Clone, Serialize)]
+pub struct ConceptLookup {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub difficulty: String,
+    pub explanation: String,
+    pub example_fen: Option<String>,
+    pub related_concepts: Vec<RelatedConcept>,
+}
+
+/// Result of running one tool call
+#[derive(Debug, Clone)]
+pub struct ToolOutput {
+    /// Sent back to the model as the tool message
+    pub content: String,
+    /// Concept card the reply can link to, as (id, name)
+    pub concept: Option<(String, String)>,
+}
+
+/// Run a tool call for the active profile. Quiz answers are scored against
+/// `conversation_id` when it is a quiz conversation, and guided questions move it on.
+pub fn run_tool(call: &FunctionCall, conversation_id: Option<i64>) -> ToolOutput {
+    match call.name.as_str() {
+        GET_QUIZ_POSITION => {
+            let args: Value = serde_json::from_str(&call.arguments).unwrap_or_default();
+            let phase = match args.get("phase").and_then(Value::as_str).map(str::parse::<GamePhase>) {
+                Some(Ok(phase)) => Some(phase),
+                Some(Err(e)) => return error_output(e.to_string()),
+                None => None,
+            };
+            json_output(game_quiz::next_position(conversation_id, phase))
+        }
+        CHECK_QUIZ_ANSWER => {
+            let args: Value = serde_json::from_str(&call.arguments).unwrap_or_default();
+            let (Some(game_id), Some(ply), Some(answer)) = (
+                args.get("game_id").and_then(Value::as_i64),
+                args.get("ply").and_then(Value::as_u64),
+                args.get("answer").and_then(Value::as_str),
+            ) else {
+                return error_output("check_quiz_answer needs 'game_id', 'ply' and 'answer' arguments".to_string());
+            };
+            json_output(game_quiz::submit_answer(conversation_id, game_id, ply as usize, answer))
+        }
+        SEARCH_PAST_ADVICE => {
+            let args: Value = serde_json::from_str(&call.arguments).unwrap_or_default();
+            let Some(query) = args.get("query").and_then(Value::as_str) else {
+                return error_output("search_past_advice needs a 'query' argument".to_string());
+            };
+            json_output(past_advice::search_past_advice(conversation_id, query))
+        }
+        NEXT_GUIDED_QUESTION => json_output(conversation_mode::next_question(conversation_id)),
+        _ => run_tool_with(&available_concepts(), call),
+    }
+}
+
+pub fn run_tool_with(concepts: &[ChessConcept], call: &FunctionCall) -> ToolOutput {
+    if call.name != LOOKUP_CONCEPT {
+        return error_output(format!("Unknown tool '{}'", call.name));
+    }
+    let name = serde_json::from_str::<Value>(&call.arguments)
+        .ok()
+        .and_then(|args| args.get("name").and_then(Value::as_str).map(str::to_string));
+    let Some(name) = name else {
+        return error_output("lookup_concept needs a 'name' argument".to_string());
+    };
+
+    match lookup_concept(concepts, &name) {
+        Ok(lookup) => ToolOutput {
+            content: serde_json::to_string(&lookup).unwrap_or_default(),
+            concept: Some((lookup.id, lookup.name)),
+        },
+        Err(e) => error_output(e),
+    }
+}
+
+/// The concept `name` refers to, with its related concepts resolved to names
+pub fn lookup_concept(concepts: &[ChessConcept], name: &str) -> Result<ConceptLookup, String> {
+    let Some(concept) = find_concept(concepts, name) else {
+        let known: Vec<&str> = concepts.iter().take(MAX_SUGGESTIONS).map(|c| c.name.as_str()).collect();
+        return Err(format!("No concept called '{}'. Known concepts include: {}", name, known.join(", ")));
+    };
+    Ok(ConceptLookup {
+        id: concept.id.clone(),
+        name: concept.name.clone(),
+        category: concept.category.clone(),
+        difficulty: concept.difficulty.clone(),
+        explanation: concept.full_explanation.clone(),
+        example_fen: concept.example_fen.clone(),
+        related_concepts: concept
+            .related_concepts
+            .iter()
+            .filter_map(|id| concepts.iter().find(|c| &c.id == id))
+            .map(|c| RelatedConcept { id: c.id.clone(), name: c.name.clone() })
+            .collect(),
+    })
+}
+
+/// Output for a call to a tool the conversation's mode does not allow
+pub fn unavailable(name: &str) -> ToolOutput {
+    error_output(format!("Tool '{}' is not available in this conversation", name))
+}
+
+fn json_output<T: Serialize>(result: Result<T, String>) -> ToolOutput {
+    match result {
+        Ok(value) => ToolOutput {
+            content: serde_json::to_string(&value).unwrap_or_default(),
+            concept: None,
+        },
+        Err(e) => error_output(e),
+    }
+}
+
+fn error_output(message: String) -> ToolOutput {
+    ToolOutput {
+        content: json!({ "error": message }).to_string(),
+        concept: None,
+    }
+}
+
+/// "Knight-Fork", "knight_fork" and "knight forks" all become "knight fork"
+pub(crate) fn normalize(name: &str) -> String {
+    let words: Vec<String> = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.strip_suffix('s').filter(|w| w.len() > 2).unwrap_or(w).to_string())
+        .collect();
+    words.join(" ")
+}
+
+/// The concept `query` names: an exact id or name first, otherwise the most
+/// specific concept whose name appears in the query or the other way round
+pub fn find_concept<'a>(concepts: &'a [ChessConcept], query: &str) -> Option<&'a ChessConcept> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return None;
+    }
+    concepts
+        .iter()
+        .find(|c| normalize(&c.id) == query || normalize(&c.name) == query)
+        .or_else(|| {
+            concepts
+                .iter()
+                .filter(|c| {
+                    let name = normalize(&c.name);
+                    query.contains(&name) || name.contains(&query)
+                })
+                .max_by_key(|c| c.name.len())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concept(id: &str, name: &str, related: &[&str]) -> ChessConcept {
+        ChessConcept {
+            id: id.to_string(),
+            name: name.to_string(),
+            category: "Tactics".to_string(),
+            difficulty: "Beginner".to_string(),
+            short_description: String::new(),
+            full_explanation: format!("All about the {}", name.to_lowercase()),
+            example_fen: Some("4k3/8/8/8/8/8/4R3/4K3 w - - 0 1".to_string()),
+            example_arrows: vec![],
+            example_highlights: vec![],
+            related_concepts: related.iter().map(|r| r.to_string()).collect(),
+            practice_exercises: vec![],
+            model_games: vec![],
+        }
+    }
+
+    #[test]
+    fn test_lookup_concept() {
+        let concepts = vec![concept("fork", "Fork", &["knight_fork"]), concept("knight_fork", "Knight Fork", &["fork"])];
+
+        assert_eq!(find_concept(&concepts, "knight_fork").unwrap().id, "knight_fork");
+        assert_eq!(find_concept(&concepts, "Forks").unwrap().id, "fork");
+        assert_eq!(find_concept(&concepts, "a knight-fork on e7").unwrap().id, "knight_fork");
+        assert!(find_concept(&concepts, "zugzwang").is_none());
+        assert!(find_concept(&concepts, "  ").is_none());
+
+        let call = FunctionCall { name: LOOKUP_CONCEPT.to_string(), arguments: r#"{"name":"fork"}"#.to_string() };
+        let output = run_tool_with(&concepts, &call);
+        assert_eq!(output.concept, Some(("fork".to_string(), "Fork".to_string())));
+        let lookup: Value = serde_json::from_str(&output.content).unwrap();
+        assert_eq!(lookup["explanation"], "All about the fork");
+        assert_eq!(lookup["related_concepts"][0]["name"], "Knight Fork");
+
+        let missing = run_tool_with(&concepts, &FunctionCall { name: LOOKUP_CONCEPT.to_string(), arguments: "{}".to_string() });
+        assert!(missing.concept.is_none());
+        assert!(missing.content.contains("error"));
+    }
+}
@@ -0,0 +1,96 @@
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories::{self, CoachTrace};
+
+/// Coach transparency is off until the user turns it on. Traces stay on this machine.
+const COACH_TRANSPARENCY_SETTING_KEY: &str = "coach_transparency";
+
+pub fn is_enabled(conn: &Connection) -> Result<bool> {
+    Ok(repositories::get_setting(conn, COACH_TRANSPARENCY_SETTING_KEY)?
+        .map(|v| v == "true")
+        .unwrap_or(false))
+}
+
+/// Turning it off also deletes every trace recorded so far
+pub fn set_enabled(conn: &Connection, enabled: bool) -> Result<()> {
+    repositories::set_setting(conn, COACH_TRANSPARENCY_SETTING_KEY, if enabled { "true" } else { "false" })?;
+    if !enabled {
+        repositories::clear_coach_traces(conn)?;
+    }
+    Ok(())
+}
+
+/// Store `trace` if the user has turned transparency on. Returns its id, or `None` when off.
+pub fn record(conn: &Connection, trace: &CoachTrace) -> Result<Option<i64>> {
+    if !is_enabled(conn)? {
+        return Ok(None);
+    }
+    repositories::insert_coach_trace(conn, trace).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{TraceMessage, TraceToolCall};
+    use crate::database::schema::create_tables;
+
+    fn trace() -> CoachTrace {
+        CoachTrace {
+            id: 0,
+            conversation_id: None,
+            message_id: None,
+            model: "anthropic/claude-3-haiku".to_string(),
+            prompt: vec![TraceMessage { role: "user".to_string(), content: "Why is Nf3 good?".to_string() }],
+            tool_calls: vec![TraceToolCall {
+                name: "getPlayerStats".to_string(),
+                arguments: "{}".to_string(),
+                result: "{\"success\":true}".to_string(),
+            }],
+            responses: vec![String::new(), "It develops a piece.".to_string()],
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_traces_only_recorded_when_enabled() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        let conversation = repositories::create_conversation(&conn, profile.id, None, None).unwrap();
+        let message = repositories::add_message(&conn, conversation, "assistant", "It develops a piece.", None, None).unwrap();
+
+        assert_eq!(record(&conn, &trace()).unwrap(), None);
+
+        set_enabled(&conn, true).unwrap();
+        let trace_id = record(&conn, &trace()).unwrap().unwrap();
+        assert!(repositories::get_message_trace(&conn, message).unwrap().is_none());
+        assert!(repositories::attach_coach_trace(&conn, trace_id, message).unwrap());
+
+        let stored = repositories::get_message_trace(&conn, message).unwrap().unwrap();
+        assert_eq!(stored.prompt, trace().prompt);
+        assert_eq!(stored.tool_calls, trace().tool_calls);
+        assert_eq!(stored.responses.len(), 2);
+
+        set_enabled(&conn, false).unwrap();
+        assert!(repositories::get_message_trace(&conn, message).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_traces_go_with_their_conversation() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        set_enabled(&conn, true).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        let conversation = repositories::create_conversation(&conn, profile.id, None, None).unwrap();
+        let message = repositories::add_message(&conn, conversation, "assistant", "It develops a piece.", None, None).unwrap();
+        let attached = record(&conn, &trace()).unwrap().unwrap();
+        repositories::attach_coach_trace(&conn, attached, message).unwrap();
+        record(&conn, &CoachTrace { conversation_id: Some(conversation), ..trace() }).unwrap();
+
+        conn.execute("DELETE FROM messages WHERE conversation_id = ?1", [conversation]).unwrap();
+        conn.execute("DELETE FROM conversations WHERE id = ?1", [conversation]).unwrap();
+        let left: i64 = conn.query_row("SELECT COUNT(*) FROM coach_traces", [], |row| row.get(0)).unwrap();
+        assert_eq!(left, 0);
+    }
+}
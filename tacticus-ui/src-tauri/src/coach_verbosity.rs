@@ -0,0 +1,112 @@
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories;
+use crate::DB;
+
+const VERBOSITY_SETTING_KEY: &str = "coach_verbosity";
+
+/// Reply length cap for coach chat at normal verbosity
+pub const CHAT_MAX_TOKENS: u32 = 1000;
+
+const TERSE_GUIDANCE: &str = r#"The student wants short answers:
+- Lead with the answer: the move, the plan or the mistake
+- At most 2-3 sentences; skip background, caveats and recaps
+- Give one line of analysis, not alternatives
+- Only explain further when asked"#;
+
+const DEEP_DIVE_GUIDANCE: &str = r#"The student wants thorough answers:
+- Explain the reasoning behind each point, not just the conclusion
+- Walk through the main line and the most important alternatives move by move
+- Name the principles and patterns involved and how they apply here
+- Point out what to look for in similar positions"#;
+
+/// How much the coach says. Terse suits players who find analyses long-winded,
+/// deep-dive those who want every line explained; normal is the coach's own style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verbosity {
+    Terse,
+    #[default]
+    Normal,
+    DeepDive,
+}
+
+impl Verbosity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verbosity::Terse => "terse",
+            Verbosity::Normal => "normal",
+            Verbosity::DeepDive => "deep_dive",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "terse" => Some(Verbosity::Terse),
+            "normal" => Some(Verbosity::Normal),
+            "deep_dive" => Some(Verbosity::DeepDive),
+            _ => None,
+        }
+    }
+
+    /// Extra system prompt for every coach request; none at normal verbosity
+    pub fn prompt_guidance(self) -> Option<&'static str> {
+        match self {
+            Verbosity::Terse => Some(TERSE_GUIDANCE),
+            Verbosity::Normal => None,
+            Verbosity::DeepDive => Some(DEEP_DIVE_GUIDANCE),
+        }
+    }
+
+    /// Reply length cap for a coach feature whose cap at normal verbosity is `normal`
+    pub fn max_tokens(self, normal: u32) -> u32 {
+        match self {
+            Verbosity::Terse => (normal / 2).max(1),
+            Verbosity::Normal => normal,
+            Verbosity::DeepDive => normal * 2,
+        }
+    }
+}
+
+pub fn load(conn: &Connection, profile_id: i64) -> Result<Verbosity> {
+    Ok(repositories::get_profile_setting(conn, profile_id, VERBOSITY_SETTING_KEY)?
+        .and_then(|v| Verbosity::parse(&v))
+        .unwrap_or_default())
+}
+
+pub fn save(conn: &Connection, profile_id: i64, verbosity: Verbosity) -> Result<()> {
+    repositories::set_profile_setting(conn, profile_id, VERBOSITY_SETTING_KEY, verbosity.as_str())
+}
+
+/// Verbosity for the active profile; normal without one
+pub fn active() -> Verbosity {
+    DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => load(conn, profile.id),
+        None => Ok(Verbosity::Normal),
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_verbosity_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        assert_eq!(load(&conn, profile.id).unwrap(), Verbosity::Normal);
+
+        save(&conn, profile.id, Verbosity::DeepDive).unwrap();
+        assert_eq!(load(&conn, profile.id).unwrap(), Verbosity::DeepDive);
+        repositories::set_profile_setting(&conn, profile.id, VERBOSITY_SETTING_KEY, "chatty").unwrap();
+        assert_eq!(load(&conn, profile.id).unwrap(), Verbosity::Normal);
+
+        assert_eq!(Verbosity::Terse.max_tokens(CHAT_MAX_TOKENS), 500);
+        assert_eq!(Verbosity::DeepDive.max_tokens(80), 160);
+        assert!(Verbosity::Normal.prompt_guidance().is_none());
+    }
+}
@@ -0,0 +1,524 @@
+use chess::{Color, File, Piece};
+use chess_core::{parse_fen, parse_legal_uci, MoveQuality};
+use chess_engine::{CancellationToken, Evaluator, GameAnalyzer, MoveAnalysis, QualityCalibration, ENGINE_NAME, ENGINE_VERSION, SEARCH_DEPTH};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use crate::DB;
+use crate::analysis_scheduler::{AnalysisJob, JobStatus, SCHEDULER};
+use crate::usage_analytics;
+use crate::commands::data::period_to_days;
+use crate::move_quality::{self, MoveQualitySettings};
+use crate::database::repositories::{self, Game, GameAnalysisRecord};
+
+/// Minimum mistakes in a group before the coach calls it a pattern
+const MIN_PATTERN_MISTAKES: u32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PieceMistakes {
+    pub piece: String,
+    pub mistakes: u32,
+    pub total_eval_drop: i32,
+}
+
+/// Where the player's mistakes land on the board.
+/// Matrices are indexed `[rank][file]` from White's side, so `[0][0]` is a1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MistakeHeatmap {
+    pub period_days: Option<i32>,
+    pub games_analyzed: usize,
+    pub total_mistakes: u32,
+    /// Number of mistakes whose move landed on each square
+    pub counts: [[u32; 8]; 8],
+    /// Summed centipawn drop of those mistakes per square
+    pub eval_drop: [[i32; 8]; 8],
+    pub by_piece: Vec<PieceMistakes>,
+    /// Short observations the coach can surface, e.g. recurring kingside pawn mistakes
+    pub patterns: Vec<String>,
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => "pawn",
+        Piece::Knight => "knight",
+        Piece::Bishop => "bishop",
+        Piece::Rook => "rook",
+        Piece::Queen => "queen",
+        Piece::King => "king",
+    }
+}
+
+fn wing(file: File) -> &'static str {
+    match file.to_index() {
+        0..=2 => "queenside",
+        3..=4 => "central",
+        _ => "kingside",
+    }
+}
+
+/// Whether the stored judgement of a move puts it on the heatmap (inaccuracy or worse)
+fn is_mistake(quality: MoveQuality) -> bool {
+    matches!(quality, MoveQuality::Inaccuracy | MoveQuality::Mistake | MoveQuality::Blunder)
+}
+
+/// Bucket the player's mistakes by square, piece and wing. Each game comes with its
+/// latest stored analysis, whose move judgements already follow the rating calibration
+/// and the sacrifice check; games without one are left out.
+fn build_mistake_heatmap(games: &[(Game, Vec<MoveAnalysis>)], period_days: Option<i32>) -> MistakeHeatmap {
+    let mut counts = [[0u32; 8]; 8];
+    let mut eval_drop = [[0i32; 8]; 8];
+    let mut pieces: Vec<PieceMistakes> = Vec::new();
+    // Ordered so equally common patterns are always listed the same way
+    let mut groups: std::collections::BTreeMap<(&'static str, &'static str), u32> = std::collections::BTreeMap::new();
+    let mut total_mistakes = 0;
+    let mut games_analyzed = 0;
+
+    for (game, analyses) in games {
+        if analyses.is_empty() {
+            continue;
+        }
+        let Ok(mut board) = parse_fen(&game.initial_fen) else {
+            continue;
+        };
+        let player_color = if game.player_color == "black" { Color::Black } else { Color::White };
+        games_analyzed += 1;
+
+        for (uci, analysis) in game.moves.iter().zip(analyses) {
+            let Ok(chess_move) = parse_legal_uci(&board, uci) else {
+                break;
+            };
+
+            if board.side_to_move() == player_color && is_mistake(analysis.quality) {
+                let to = chess_move.get_dest();
+                let (rank, file) = (to.get_rank().to_index(), to.get_file().to_index());
+                counts[rank][file] += 1;
+                eval_drop[rank][file] += analysis.centipawn_loss;
+                total_mistakes += 1;
+
+                if let Some(piece) = board.piece_on(chess_move.get_source()) {
+                    let name = piece_name(piece);
+                    match pieces.iter_mut().find(|p| p.piece == name) {
+                        Some(entry) => {
+                            entry.mistakes += 1;
+                            entry.total_eval_drop += analysis.centipawn_loss;
+                        }
+                        None => pieces.push(PieceMistakes {
+                            piece: name.to_string(),
+                            mistakes: 1,
+                            total_eval_drop: analysis.centipawn_loss,
+                        }),
+                    }
+                    *groups.entry((wing(to.get_file()), name)).or_insert(0) += 1;
+                }
+            }
+
+            board = board.make_move_new(chess_move);
+        }
+    }
+
+    pieces.sort_by_key(|p| std::cmp::Reverse(p.mistakes));
+
+    let mut recurring: Vec<_> = groups
+        .into_iter()
+        .filter(|(_, n)| *n >= MIN_PATTERN_MISTAKES && *n * 4 >= total_mistakes)
+        .collect();
+    recurring.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+
+    let patterns = recurring
+        .into_iter()
+        .map(|((wing, piece), n)| {
+            format!("Your {} {} moves are a recurring problem ({} of {} mistakes)", wing, piece, n, total_mistakes)
+        })
+        .collect();
+
+    MistakeHeatmap {
+        period_days,
+        games_analyzed,
+        total_mistakes,
+        counts,
+        eval_drop,
+        by_piece: pieces,
+        patterns,
+    }
+}
+
+/// Mistake heatmap over `profile_id`'s games from the last `days` (all when `None`)
+pub(crate) fn mistake_heatmap(
+    conn: &Connection,
+    profile_id: i64,
+    days: Option<i32>,
+) -> rusqlite::Result<MistakeHeatmap> {
+    let mut games = vec![];
+    for game in repositories::get_games_since(conn, profile_id, days)? {
+        if let Some(record) = repositories::get_game_analyses(conn, game.id)?.into_iter().next() {
+            let analyses: Vec<MoveAnalysis> = serde_json::from_str(&record.analysis).unwrap_or_default();
+            games.push((game, analyses));
+        }
+    }
+    Ok(build_mistake_heatmap(&games, days))
+}
+
+/// The coach's note on where the student's mistakes keep landing; none without a pattern
+pub(crate) fn mistake_patterns_prompt(heatmap: &MistakeHeatmap) -> Option<String> {
+    if heatmap.patterns.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Patterns in the student's mistakes across {} recently analysed games: {}. Point them out when they \
+         come up.",
+        heatmap.games_analyzed,
+        heatmap.patterns.join("; ")
+    ))
+}
+
+/// Mistake heatmap over the player's analysed games. `period` is one of "day", "week", "month" or "all".
+#[tauri::command]
+pub async fn get_mistake_heatmap(period: String) -> Result<MistakeHeatmap, String> {
+    let days = period_to_days(&period)?;
+
+    tokio::task::spawn_blocking(move || {
+        let profile = DB
+            .with_conn(|conn| repositories::get_first_profile(conn))
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or_else(|| "No user profile found".to_string())?;
+
+        DB.with_conn(|conn| mistake_heatmap(conn, profile.id, days))
+            .map_err(|e| format!("Failed to build mistake heatmap: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Mistake heatmap failed: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionAnalysis {
+    pub fen: String,
+    /// In pawns from White's perspective, as is `best_move_eval`
+    pub evaluation: f32,
+    pub best_move: Option<String>,
+    pub best_move_eval: Option<f32>,
+}
+
+/// Analyse the position the user is looking at. A newer request on the same `channel`
+/// (default "eval_bar") cancels this one, in which case `None` is returned.
+#[tauri::command]
+pub async fn request_analysis(fen: String, channel: Option<String>) -> Result<Option<PositionAnalysis>, String> {
+    let channel = channel.unwrap_or_else(|| "eval_bar".to_string());
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    usage_analytics::record_usage(usage_analytics::ANALYSIS_RUN);
+
+    tokio::task::spawn_blocking(move || {
+        let guard = SCHEDULER.begin_interactive(&channel);
+        let evaluation = Evaluator::evaluate_position(&board).score.pawns();
+        let Ok(best) = Evaluator::find_best_move_cancellable(&board, &guard.token) else {
+            tracing::debug!(channel = %channel, "analysis superseded");
+            return None;
+        };
+
+        Some(PositionAnalysis {
+            fen,
+            evaluation,
+            best_move: best.as_ref().map(|b| b.chess_move.to_string()),
+            best_move_eval: best.map(|b| b.score.to_white(board.side_to_move()).pawns()),
+        })
+    })
+    .await
+    .map_err(|e| format!("Analysis failed: {}", e))
+}
+
+/// Start a full-game analysis in the background and return its job id.
+/// The job pauses whenever an interactive request is running.
+#[tauri::command]
+pub fn start_game_analysis(game_id: i64) -> Result<u64, String> {
+    let game = DB
+        .with_conn(|conn| repositories::get_game_by_id(conn, game_id))
+        .map_err(|e| format!("Failed to load game: {}", e))?
+        .ok_or_else(|| format!("Game {} not found", game_id))?;
+
+    let mut board = parse_fen(&game.initial_fen).map_err(|e| e.to_string())?;
+    let mut positions = Vec::with_capacity(game.moves.len());
+    for uci in &game.moves {
+        let chess_move = parse_legal_uci(&board, uci).map_err(|_| format!("Illegal move in game record: {}", uci))?;
+        positions.push((board, chess_move));
+        board = board.make_move_new(chess_move);
+    }
+
+    usage_analytics::record_usage(usage_analytics::ANALYSIS_RUN);
+    let calibration = move_quality::active();
+    SCHEDULER.remove_finished_jobs();
+    let (job_id, token) = SCHEDULER.create_job(game_id, positions.len());
+
+    std::thread::spawn(move || {
+        for (index, (board, chess_move)) in positions.into_iter().enumerate() {
+            SCHEDULER.yield_to_interactive(&token);
+            match GameAnalyzer::analyze_move_cancellable(&board, chess_move, index, &calibration, &token) {
+                Ok(analysis) => SCHEDULER.record_progress(job_id, analysis),
+                Err(_) => {
+                    SCHEDULER.finish_job(job_id, JobStatus::Cancelled, None);
+                    return;
+                }
+            }
+        }
+        SCHEDULER.finish_job(job_id, JobStatus::Completed, None);
+        tracing::debug!(job_id, game_id, "game analysis finished");
+    });
+
+    Ok(job_id)
+}
+
+#[tauri::command]
+pub fn get_analysis_job(job_id: u64) -> Result<AnalysisJob, String> {
+    SCHEDULER
+        .job(job_id)
+        .ok_or_else(|| format!("Analysis job {} not found", job_id))
+}
+
+#[tauri::command]
+pub fn cancel_analysis_job(job_id: u64) -> bool {
+    SCHEDULER.cancel_job(job_id)
+}
+
+/// Which stored games to re-analyse. Explicit `game_ids` take precedence over the other fields.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReanalysisFilter {
+    pub game_ids: Option<Vec<i64>>,
+    /// One of "day", "week", "month" or "all"
+    pub period: Option<String>,
+    pub opening_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReanalysisSettings {
+    /// Re-run games whose latest analysis already comes from the current engine version
+    #[serde(default)]
+    pub force: bool,
+    pub max_games: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReanalysisReport {
+    pub engine_name: String,
+    pub engine_version: String,
+    pub depth: u32,
+    pub reanalyzed: usize,
+    pub skipped_up_to_date: usize,
+    /// Games whose move record could not be replayed
+    pub failed: Vec<i64>,
+}
+
+fn select_games_for_reanalysis(filter: &ReanalysisFilter) -> Result<Vec<Game>, String> {
+    if let Some(ids) = &filter.game_ids {
+        let mut games = Vec::with_capacity(ids.len());
+        for id in ids {
+            let game = DB
+                .with_conn(|conn| repositories::get_game_by_id(conn, *id))
+                .map_err(|e| format!("Failed to load game: {}", e))?
+                .ok_or_else(|| format!("Game {} not found", id))?;
+            games.push(game);
+        }
+        return Ok(games);
+    }
+
+    let days = period_to_days(filter.period.as_deref().unwrap_or("all"))?;
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let games = DB
+        .with_conn(|conn| repositories::get_games_since(conn, profile.id, days))
+        .map_err(|e| format!("Failed to load games: {}", e))?;
+
+    Ok(games
+        .into_iter()
+        .filter(|g| match &filter.opening_name {
+            Some(opening) => g.opening_name.as_deref() == Some(opening.as_str()),
+            None => true,
+        })
+        .collect())
+}
+
+/// Analyse every move of a stored game with the current engine and summarise the player's moves
+pub(crate) fn analyze_stored_game(game: &Game, token: &CancellationToken) -> Option<GameAnalysisRecord> {
+    analyze_stored_game_with(game, &move_quality::active(), token)
+}
+
+/// `analyze_stored_game` with a given calibration, for callers already holding the database
+pub(crate) fn analyze_stored_game_with(
+    game: &Game,
+    calibration: &QualityCalibration,
+    token: &CancellationToken,
+) -> Option<GameAnalysisRecord> {
+    let mut board = parse_fen(&game.initial_fen).ok()?;
+    let player_color = if game.player_color == "black" { Color::Black } else { Color::White };
+    let mut analyses: Vec<MoveAnalysis> = Vec::with_capacity(game.moves.len());
+    let mut positions = vec![board];
+
+    for (index, uci) in game.moves.iter().enumerate() {
+        let chess_move = parse_legal_uci(&board, uci).ok()?;
+        SCHEDULER.yield_to_interactive(token);
+        analyses.push(GameAnalyzer::analyze_move_cancellable(&board, chess_move, index, calibration, token).ok()?);
+        board = board.make_move_new(chess_move);
+        positions.push(board);
+    }
+    // Sound sacrifices are re-scored here, so count mistakes only afterwards
+    GameAnalyzer::mark_sacrifices(&positions, &mut analyses, calibration);
+
+    let (mut mistakes, mut blunders, mut player_moves, mut total_loss) = (0, 0, 0, 0i64);
+    for (analysis, position) in analyses.iter().zip(&positions) {
+        if position.side_to_move() == player_color {
+            player_moves += 1;
+            total_loss += analysis.centipawn_loss.max(0) as i64;
+            match analysis.quality {
+                MoveQuality::Mistake => mistakes += 1,
+                MoveQuality::Blunder => blunders += 1,
+                _ => {}
+            }
+        }
+    }
+
+    Some(GameAnalysisRecord {
+        id: 0,
+        game_id: game.id,
+        engine_name: ENGINE_NAME.to_string(),
+        engine_version: ENGINE_VERSION.to_string(),
+        depth: SEARCH_DEPTH as i32,
+        analysis: serde_json::to_string(&analyses).ok()?,
+        mistakes,
+        blunders,
+        average_centipawn_loss: if player_moves > 0 { total_loss as f64 / player_moves as f64 } else { 0.0 },
+        created_at: String::new(),
+    })
+}
+
+/// Maintenance command: re-run analysis on stored games with the current engine.
+/// Each run is stored alongside earlier ones with the engine name, version and depth that produced it.
+#[tauri::command]
+pub async fn reanalyze_games(filter: ReanalysisFilter, settings: ReanalysisSettings) -> Result<ReanalysisReport, String> {
+    let mut games = select_games_for_reanalysis(&filter)?;
+    if let Some(max) = settings.max_games {
+        games.truncate(max);
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let token = CancellationToken::new();
+        let mut report = ReanalysisReport {
+            engine_name: ENGINE_NAME.to_string(),
+            engine_version: ENGINE_VERSION.to_string(),
+            depth: SEARCH_DEPTH,
+            reanalyzed: 0,
+            skipped_up_to_date: 0,
+            failed: Vec::new(),
+        };
+
+        for game in &games {
+            if !settings.force {
+                let latest = DB
+                    .with_conn(|conn| repositories::get_game_analyses(conn, game.id))
+                    .map_err(|e| format!("Failed to load analyses: {}", e))?;
+                let up_to_date = latest.first().is_some_and(|a| {
+                    a.engine_name == ENGINE_NAME && a.engine_version == ENGINE_VERSION && a.depth == SEARCH_DEPTH as i32
+                });
+                if up_to_date {
+                    report.skipped_up_to_date += 1;
+                    continue;
+                }
+            }
+
+            let Some(record) = analyze_stored_game(game, &token) else {
+                tracing::warn!(game_id = game.id, "could not replay game for re-analysis");
+                report.failed.push(game.id);
+                continue;
+            };
+
+            DB.with_conn(|conn| repositories::insert_game_analysis(conn, &record))
+                .map_err(|e| format!("Failed to save analysis: {}", e))?;
+            report.reanalyzed += 1;
+        }
+
+        tracing::info!(
+            reanalyzed = report.reanalyzed,
+            skipped = report.skipped_up_to_date,
+            failed = report.failed.len(),
+            engine_version = ENGINE_VERSION,
+            "re-analysis finished"
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Re-analysis failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn get_move_quality_settings() -> Result<MoveQualitySettings, String> {
+    DB.with_conn(move_quality::get)
+        .map_err(|e| format!("Failed to load move quality settings: {}", e))
+}
+
+/// Applies to games analysed from now on; re-analyse with `force` to rejudge stored games
+#[tauri::command]
+pub fn set_move_quality_settings(settings: MoveQualitySettings) -> Result<MoveQualitySettings, String> {
+    settings.calibration(None).validate().map_err(|e| e.to_string())?;
+    DB.with_conn(|conn| move_quality::set(conn, &settings))
+        .map_err(|e| format!("Failed to save move quality settings: {}", e))?;
+    Ok(settings)
+}
+
+/// Every stored analysis run for a game, newest first
+#[tauri::command]
+pub fn get_game_analysis_history(game_id: i64) -> Result<Vec<GameAnalysisRecord>, String> {
+    DB.with_conn(|conn| repositories::get_game_analyses(conn, game_id))
+        .map_err(|e| format!("Failed to load analyses: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `moves` analysed by the engine, with the judgement of each ply in `judged` replaced
+    fn analysed(game: &Game, judged: &[(usize, MoveQuality, i32)]) -> Vec<MoveAnalysis> {
+        let mut board = parse_fen(&game.initial_fen).unwrap();
+        let mut analyses = vec![];
+        for (index, uci) in game.moves.iter().enumerate() {
+            let chess_move = parse_legal_uci(&board, uci).unwrap();
+            let mut analysis = GameAnalyzer::analyze_move(&board, chess_move, index);
+            (analysis.quality, analysis.centipawn_loss) = judged
+                .iter()
+                .find(|(ply, ..)| *ply == index)
+                .map_or((MoveQuality::Good, 0), |(_, quality, loss)| (*quality, *loss));
+            analyses.push(analysis);
+            board = board.make_move_new(chess_move);
+        }
+        analyses
+    }
+
+    #[test]
+    fn test_heatmap_follows_stored_judgements() {
+        // 1.g4 e5 2.f3 d5 3.h4 Nc6, with every White move judged a mistake and one of Black's a blunder
+        let game = Game::for_test(1, &["g2g4", "e7e5", "f2f3", "d7d5", "h2h4", "b8c6"]);
+        let judged = [
+            (0, MoveQuality::Inaccuracy, 60),
+            (1, MoveQuality::Blunder, 400),
+            (2, MoveQuality::Mistake, 150),
+            (4, MoveQuality::Blunder, 300),
+        ];
+        let unanalysed = Game::for_test(1, &["e2e4"]);
+        let games = vec![(game.clone(), analysed(&game, &judged)), (unanalysed, vec![])];
+
+        let heatmap = build_mistake_heatmap(&games, Some(7));
+        assert_eq!(heatmap.games_analyzed, 1);
+        assert_eq!(heatmap.total_mistakes, 3);
+        assert_eq!((heatmap.counts[3][6], heatmap.counts[2][5], heatmap.counts[3][7]), (1, 1, 1));
+        assert_eq!(heatmap.eval_drop[3][7], 300);
+        // Black's blunder on e5 isn't the player's
+        assert_eq!(heatmap.counts[4][4], 0);
+        assert_eq!(heatmap.by_piece.len(), 1);
+        assert_eq!((heatmap.by_piece[0].mistakes, heatmap.by_piece[0].total_eval_drop), (3, 510));
+        assert_eq!(heatmap.patterns, vec!["Your kingside pawn moves are a recurring problem (3 of 3 mistakes)"]);
+        assert!(mistake_patterns_prompt(&heatmap).unwrap().contains("across 1 recently analysed games"));
+
+        // Good moves with a large loss, e.g. re-scored sacrifices, stay off the map
+        let clean = vec![(game.clone(), analysed(&game, &[(0, MoveQuality::Good, 250)]))];
+        let heatmap = build_mistake_heatmap(&clean, None);
+        assert_eq!((heatmap.games_analyzed, heatmap.total_mistakes), (1, 0));
+        assert_eq!(mistake_patterns_prompt(&heatmap), None);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::DB;
+use crate::board_assets::{self, BoardAppearance, BoardAssets};
+use crate::notation_prefs;
+use chess_core::NotationPrefs;
+
+/// Piece sets and board themes to choose from, built in and from the asset folders
+#[tauri::command]
+pub fn list_board_assets() -> BoardAssets {
+    board_assets::list()
+}
+
+/// The selected piece set and board theme, with piece images for the board
+#[tauri::command]
+pub fn get_board_appearance() -> Result<BoardAppearance, String> {
+    DB.with_conn(board_assets::appearance)
+        .map_err(|e| format!("Failed to read board appearance: {}", e))
+}
+
+/// Select the piece set and board theme used by the board, exports and reports
+#[tauri::command]
+pub fn set_board_appearance(piece_set: String, board_theme: String) -> Result<BoardAppearance, String> {
+    board_assets::check(&piece_set, &board_theme)?;
+    DB.with_conn(|conn| {
+        board_assets::select(conn, &piece_set, &board_theme)?;
+        board_assets::appearance(conn)
+    })
+    .map_err(|e| format!("Failed to save board appearance: {}", e))
+}
+
+/// Read the asset folders again after piece sets were added or edited
+#[tauri::command]
+pub fn reload_board_assets() -> BoardAssets {
+    board_assets::reload();
+    board_assets::list()
+}
+
+/// How moves and boards are written: move style, piece symbols and coordinates
+#[tauri::command]
+pub fn get_notation_prefs() -> Result<NotationPrefs, String> {
+    DB.with_conn(|conn| Ok(notation_prefs::load(conn)))
+        .map_err(|e| format!("Failed to read notation preferences: {}", e))
+}
+
+/// Change the notation used by move lists, the coach, exports and the CLI
+#[tauri::command]
+pub fn set_notation_prefs(prefs: NotationPrefs) -> Result<NotationPrefs, String> {
+    DB.with_conn(|conn| {
+        notation_prefs::save(conn, &prefs)?;
+        Ok(notation_prefs::load(conn))
+    })
+    .map_err(|e| format!("Failed to save notation preferences: {}", e))
+}
@@ -1,5 +1,35 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "llm")]
 use reqwest::Client;
+use chess_config::{FAST_MODELS, STRONG_MODELS};
+use chess_core::parse_fen;
+use chess_engine::{DefenseFinder, ThreatDetector};
+use crate::DB;
+use crate::app_config;
+use crate::board_context::{self, BoardSnapshot};
+use crate::coach_actions::{self, ActionTarget, CoachAction};
+use crate::coach_cache;
+use crate::coach_greeting;
+use crate::coach_trace;
+use crate::coach_verbosity::{self, Verbosity};
+use crate::coach_throttle::{ANALYSIS_DEBOUNCE, COACH_CALLS, POSITION_ANALYSIS};
+use crate::coach_tools::{self, ToolCall, ToolSpec};
+use crate::conversation_mode::{self, CoachSetup, ConversationMode, GuidedQuestion};
+use crate::database::repositories::{self, CoachTrace, TraceMessage, TraceToolCall};
+use crate::kid_mode;
+use crate::moderation::{self, ModerationPolicy};
+use crate::move_mentions::{self, MoveCheck};
+use crate::notation_prefs;
+use crate::past_advice::{self, PastAdvice};
+use crate::prompt_privacy::{self, PromptEntry, PromptMessage};
+use crate::quick_answers;
+use crate::usage_analytics;
+
+#[cfg(feature = "llm")]
+lazy_static! {
+    /// One connection pool for every request to the model provider
+    pub(crate) static ref HTTP_CLIENT: Client = Client::new();
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoachMessage {
@@ -9,19 +39,15 @@ pub struct CoachMessage {
     pub actions: Vec<CoachAction>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CoachAction {
-    pub action_type: String,
-    pub label: String,
-    pub data: String,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoachResponse {
     pub message: CoachMessage,
     pub board_fen: Option<String>,
     pub highlights: Vec<String>,
     pub arrows: Vec<(String, String)>,
+    /// Transparency trace behind the reply, to pass to `add_message` when it is saved
+    #[serde(default)]
+    pub trace_id: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -30,24 +56,134 @@ struct ChatRequest {
     messages: Vec<ChatMessage>,
     temperature: f32,
     max_tokens: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ToolSpec>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct ChatMessage {
     role: String,
+    /// Null when the model only asked for tool calls
+    #[serde(default, deserialize_with = "null_as_empty")]
     content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<ToolCall>>,
+    /// Set on "tool" messages: the call this is the result of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.into(),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+}
+
+impl PromptEntry for ChatMessage {
+    fn role(&self) -> &str {
+        &self.role
+    }
+
+    fn content_mut(&mut self) -> &mut String {
+        &mut self.content
+    }
+
+    fn calls_tools(&self) -> bool {
+        self.tool_calls.as_ref().is_some_and(|calls| !calls.is_empty())
+    }
 }
 
+fn null_as_empty<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Rounds of tool calls a single coach reply may make before it must answer
+const MAX_TOOL_ROUNDS: usize = 3;
+
+#[cfg(feature = "llm")]
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
 }
 
+#[cfg(feature = "llm")]
 #[derive(Debug, Deserialize)]
 struct Choice {
     message: ChatMessage,
 }
 
+/// Settings key prefix for per-task model overrides, e.g. `coach_model.chat`
+const MODEL_OVERRIDE_KEY_PREFIX: &str = "coach_model.";
+
+/// Kinds of coach request, each routed to a model tier shared with the coach agent's
+/// `ModelRouter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoachTask {
+    /// Free conversation with Gurgeh
+    Chat,
+    /// Short glossary definitions
+    Definition,
+    /// Full position analysis
+    PositionAnalysis,
+}
+
+impl CoachTask {
+    const ALL: [CoachTask; 3] = [CoachTask::Chat, CoachTask::Definition, CoachTask::PositionAnalysis];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            CoachTask::Chat => "chat",
+            CoachTask::Definition => "definition",
+            CoachTask::PositionAnalysis => "position_analysis",
+        }
+    }
+
+    fn default_models(self) -> &'static [&'static str] {
+        match self {
+            CoachTask::Chat | CoachTask::Definition => FAST_MODELS,
+            CoachTask::PositionAnalysis => STRONG_MODELS,
+        }
+    }
+
+    fn override_key(self) -> String {
+        format!("{}{}", MODEL_OVERRIDE_KEY_PREFIX, self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRoute {
+    pub task: CoachTask,
+    /// Model chosen in settings, tried before the defaults
+    pub override_model: Option<String>,
+    pub default_models: Vec<String>,
+}
+
+fn model_override(task: CoachTask) -> Option<String> {
+    DB.with_conn(|conn| repositories::get_setting(conn, &task.override_key()))
+        .ok()
+        .flatten()
+        .filter(|model| !model.is_empty())
+}
+
+/// Models to try for `task`, in order: the settings override, the configured
+/// model, then the tier defaults
+fn model_candidates(task: CoachTask) -> Vec<String> {
+    let mut models: Vec<String> = model_override(task).into_iter().collect();
+    let configured = app_config::current().llm.model;
+    for model in configured.iter().map(String::as_str).chain(task.default_models().iter().copied()) {
+        if !models.iter().any(|m| m == model) {
+            models.push(model.to_string());
+        }
+    }
+    models
+}
+
 const GURGEH_SYSTEM_PROMPT: &str = r#"You are Gurgeh, an AI chess coach named after the legendary game player from Iain M. Banks' Culture series. You are wise, patient, and deeply knowledgeable about chess.
 
 Your personality:
@@ -69,153 +205,465 @@ Guidelines:
 - Use algebraic notation for moves (e.g., e4, Nf3, O-O)
 - When explaining concepts, give concrete examples
 - Adapt your explanations to the user's level
-- Never use emojis in your responses
+- Never use emojis in your responses, unless you are told you are coaching a child
 
 You are helping a chess student improve their game from beginner to advanced."#;
 
+/// Opening message of the coach chat, built from the player's streak, their clearest
+/// recent improvement, an unfinished training session, missed positions due for review
+/// and their weakest theme
 #[tauri::command]
-pub fn get_coach_greeting(user_name: String, current_elo: i32, exercises_completed: i32) -> CoachResponse {
-    let greeting = if exercises_completed == 0 {
-        format!(
-            "Welcome to Tacticus, {}. I'm Gurgeh, your chess coach - named after the legendary \
-             game player from the Culture.\n\n\
-             I see you're starting at {} ELO. Let's begin with some fundamentals and discover \
-             where your strengths lie. Together, we'll master this ancient game.",
-            user_name, current_elo
-        )
-    } else {
-        format!(
-            "Welcome back, {}. You've completed {} exercises so far. \
-             Your current rating is {}. Ready to continue your training?",
-            user_name, exercises_completed, current_elo
-        )
-    };
-    
-    CoachResponse {
+pub fn get_coach_greeting() -> Result<CoachResponse, String> {
+    let (content, actions) = DB
+        .with_conn(|conn| {
+            let profile = repositories::get_first_profile(conn)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+            coach_greeting::gather(conn, profile)
+        })
+        .map(|briefing| coach_greeting::compose(&briefing))
+        .map_err(|e| format!("Failed to build greeting: {}", e))?;
+
+    Ok(CoachResponse {
         message: CoachMessage {
             role: "gurgeh".to_string(),
-            content: greeting,
+            content,
             timestamp: chrono::Utc::now().timestamp(),
-            actions: vec![
-                CoachAction {
-                    action_type: "start_training".to_string(),
-                    label: "Start Training".to_string(),
-                    data: "".to_string(),
-                },
-                CoachAction {
-                    action_type: "play_game".to_string(),
-                    label: "Play a Game".to_string(),
-                    data: "".to_string(),
-                },
-            ],
+            actions,
         },
         board_fen: None,
         highlights: vec![],
         arrows: vec![],
+        trace_id: None,
+    })
+}
+
+/// Resolve the OpenRouter API key from the argument or the config
+fn resolve_api_key(api_key: Option<String>) -> Option<String> {
+    api_key.or_else(|| app_config::current().llm.api_key)
+}
+
+/// Why a chat completion request failed
+#[derive(Debug)]
+enum RequestError {
+    /// No model would answer: the provider turned the API key down, or this build
+    /// can't send requests
+    Rejected(String),
+    /// This model failed; another may still answer
+    Failed(String),
+}
+
+/// Send a chat completion request and return the first choice's message, if any
+#[cfg(feature = "llm")]
+async fn send_chat_request(api_key: &str, request: &ChatRequest) -> Result<Option<ChatMessage>, RequestError> {
+    let base_url = app_config::current().llm.base_url;
+    let response = HTTP_CLIENT
+        .post(format!("{}/chat/completions", base_url))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .header("HTTP-Referer", "https://github.com/tacticus-chess")
+        .header("X-Title", "Tacticus Chess Trainer")
+        .json(request)
+        .send()
+        .await
+        .map_err(|e| RequestError::Failed(format!("Failed to send request: {}", e)))?;
+    
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        let error = format!("API error ({}): {}", status, error_text);
+        return Err(if matches!(status.as_u16(), 401 | 403) {
+            RequestError::Rejected(error)
+        } else {
+            RequestError::Failed(error)
+        });
     }
+    
+    let chat_response: ChatResponse = response
+        .json()
+        .await
+        .map_err(|e| RequestError::Failed(format!("Failed to parse response: {}", e)))?;
+    
+    Ok(chat_response.choices.into_iter().next().map(|c| c.message))
+}
+
+/// Offline builds have no HTTP client, so every coach request fails cleanly
+#[cfg(not(feature = "llm"))]
+async fn send_chat_request(_api_key: &str, _request: &ChatRequest) -> Result<Option<ChatMessage>, RequestError> {
+    Err(RequestError::Rejected("This build of Tacticus has no online coach".to_string()))
 }
 
+/// Send `request` to each model routed for `task` until one succeeds, stopping at once
+/// when the API key is rejected. Returns the model that answered with its reply.
+/// Counts against the per-minute call cap.
+async fn send_routed_request(
+    api_key: &str,
+    task: CoachTask,
+    request: &mut ChatRequest,
+) -> Result<(String, Option<ChatMessage>), String> {
+    COACH_CALLS.acquire()?;
+    // Every request the backend sends passes through here
+    request.messages = prompt_privacy::apply_active(std::mem::take(&mut request.messages));
+    let mut last_error = String::new();
+    for model in model_candidates(task) {
+        request.model = model;
+        match send_chat_request(api_key, request).await {
+            Ok(reply) => return Ok((request.model.clone(), reply)),
+            Err(RequestError::Rejected(e)) => {
+                tracing::warn!(task = task.as_str(), model = %request.model, "not trying other models: {}", e);
+                return Err(e);
+            }
+            Err(RequestError::Failed(e)) => {
+                tracing::warn!(task = task.as_str(), model = %request.model, "model failed, trying fallback: {}", e);
+                last_error = e;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+/// `send_routed_request` for deterministic requests: identical requests (same preferred model,
+/// system prompts, normalised user prompt and position) reuse a cached reply until it expires.
+/// Concurrent duplicates wait for the first to finish. `bypass_cache` forces a fresh reply.
+async fn send_cached_request(
+    api_key: &str,
+    task: CoachTask,
+    mut request: ChatRequest,
+    position: Option<&str>,
+    bypass_cache: bool,
+) -> Result<Option<String>, String> {
+    // Keyed on what is actually sent, so changing the settings can't serve a reply to the old prompt
+    request.messages = prompt_privacy::apply_active(std::mem::take(&mut request.messages));
+    let preferred_model = model_candidates(task).into_iter().next().unwrap_or_default();
+    let system_prompts: Vec<&str> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let user_prompt = request.messages.iter().rev().find(|m| m.role == "user").map_or("", |m| m.content.as_str());
+    let key = coach_cache::cache_key(&preferred_model, &system_prompts, user_prompt, position);
+
+    let _in_flight = coach_cache::lock(&key).await;
+    if !bypass_cache {
+        if let Some(cached) = coach_cache::lookup(&key) {
+            tracing::debug!(task = task.as_str(), "coach cache hit");
+            return Ok(Some(cached));
+        }
+    }
+
+    let (model, reply) = send_routed_request(api_key, task, &mut request).await?;
+    let content = reply.map(|m| m.content).filter(|c| !c.is_empty());
+    if let Some(content) = &content {
+        coach_cache::store(&key, &model, content);
+    }
+    Ok(content)
+}
+
+/// One-off completion outside the coach conversation (definitions, summaries).
+/// `max_tokens` is the cap at normal verbosity; the active profile's verbosity
+/// scales it. Output is moderated with the active profile's policy.
+pub(crate) async fn complete_once(task: CoachTask, system: &str, prompt: &str, max_tokens: u32) -> Result<String, String> {
+    let api_key = resolve_api_key(None).ok_or_else(|| "No API key configured".to_string())?;
+    
+    let mut messages = vec![ChatMessage::new("system", system)];
+    let verbosity = if kid_mode::active() { Verbosity::Normal } else { coach_verbosity::active() };
+    if let Some(guidance) = verbosity.prompt_guidance() {
+        messages.push(ChatMessage::new("system", guidance));
+    }
+    if let Some(guidance) = notation_prefs::prompt_guidance(&notation_prefs::active()) {
+        messages.push(ChatMessage::new("system", guidance));
+    }
+    messages.push(ChatMessage::new("user", prompt));
+
+    let request = ChatRequest {
+        model: String::new(),
+        messages,
+        temperature: 0.3,
+        max_tokens: verbosity.max_tokens(max_tokens),
+        tools: vec![],
+    };
+    
+    let content = send_cached_request(&api_key, task, request, None, false)
+        .await?
+        .ok_or_else(|| "Empty response".to_string())?;
+    
+    Ok(moderation::moderate(&content, &moderation::active_policy()).content)
+}
+
+/// Chat with Gurgeh. With a `conversation_id` the conversation's mode decides the
+/// extra prompts, which tools may run and what the reply may reveal. Simple factual
+/// questions in general chat are answered locally, without the model.
 #[tauri::command]
 pub async fn chat_with_coach(
     message: String,
     context: Option<String>,
     api_key: Option<String>,
+    conversation_id: Option<i64>,
 ) -> Result<CoachResponse, String> {
-    // Check for API key
-    let key = api_key
-        .or_else(|| std::env::var("OPENROUTER_API_KEY").ok())
-        .or_else(|| {
-            dotenv::dotenv().ok();
-            std::env::var("OPENROUTER_API_KEY").ok()
-        });
+    let setup = conversation_mode::for_chat(conversation_id)?;
+    if setup.mode == ConversationMode::General {
+        if let Some(response) = get_quick_answer(message.clone()) {
+            usage_analytics::record_usage(usage_analytics::COACH_MESSAGE);
+            return Ok(response);
+        }
+    }
+    coach_reply(CoachTask::Chat, message, context, api_key, None, &setup).await
+}
+
+/// The app's own answer to a simple factual question (a piece's value, an opening's
+/// moves, a rule, a concept's definition), or none when it needs the model. Clients
+/// that talk to the model themselves call this first in general chat.
+#[tauri::command]
+pub fn get_quick_answer(message: String) -> Option<CoachResponse> {
+    let answer = quick_answers::answer(&message)?;
+    Some(CoachResponse {
+        message: CoachMessage {
+            role: "gurgeh".to_string(),
+            content: answer.content,
+            timestamp: chrono::Utc::now().timestamp(),
+            actions: answer.actions,
+        },
+        board_fen: answer.fen,
+        highlights: vec![],
+        arrows: vec![],
+        trace_id: None,
+    })
+}
+
+/// Count a model call the frontend makes itself against the shared per-minute cap.
+/// Fails with a `cooling_down` error when the cap is reached.
+#[tauri::command]
+pub fn reserve_coach_call() -> Result<(), String> {
+    Ok(COACH_CALLS.acquire()?)
+}
+
+/// Move a guided-help conversation on to its next leading question, for clients
+/// that run the coach's `next_guided_question` tool themselves
+#[tauri::command]
+pub fn next_guided_question(conversation_id: Option<i64>) -> Result<GuidedQuestion, String> {
+    conversation_mode::next_question(conversation_id)
+}
+
+/// Prompts, tool whitelist, solution lock and reply length for a conversation,
+/// or for general chat without one, for clients that talk to the model themselves
+#[tauri::command]
+pub fn get_coach_setup(conversation_id: Option<i64>) -> Result<CoachSetup, String> {
+    conversation_mode::for_chat(conversation_id)
+}
+
+/// Apply the active profile's prompt privacy to a prompt a client is about to send
+/// itself. Call it before every request, tool rounds included.
+#[tauri::command]
+pub fn prepare_coach_prompt(messages: Vec<PromptMessage>) -> Vec<PromptMessage> {
+    prompt_privacy::apply_active(messages)
+}
+
+/// What the coach told the player about `query` in earlier conversations, so it
+/// can stay consistent with itself. `conversation_id` is the one asking.
+#[tauri::command]
+pub fn search_past_advice(query: String, conversation_id: Option<i64>) -> Result<Vec<PastAdvice>, String> {
+    past_advice::search_past_advice(conversation_id, &query)
+}
+
+/// The prompt for one coach request, before privacy settings are applied
+fn coach_messages(task: CoachTask, message: &str, context: Option<String>, setup: &CoachSetup) -> Vec<ChatMessage> {
+    let mut messages = vec![
+        ChatMessage::new("system", GURGEH_SYSTEM_PROMPT),
+    ];
+    
+    messages.push(ChatMessage::new("system", coach_actions::PROMPT_GUIDANCE));
+    for prompt in &setup.system_prompts {
+        messages.push(ChatMessage::new("system", prompt.as_str()));
+    }
     
-    let Some(api_key) = key else {
+    if let Some(ctx) = context {
+        messages.push(ChatMessage::new("system", format!("Current context: {}", ctx)));
+    }
+
+    // Position analysis brings its own FEN; chat picks up whatever board is on screen
+    if task == CoachTask::Chat {
+        if let Some(board) = board_context::current() {
+            messages.push(ChatMessage::new("system", board.to_prompt()));
+            // Moves the player names are resolved here so the model can't analyse impossible ones
+            if let Some(check) = move_mentions::check_fen(&board.fen, message, !setup.solution_locked) {
+                messages.push(ChatMessage::new("system", check.prompt));
+            }
+        }
+    }
+    
+    messages.push(ChatMessage::new("user", message));
+    messages
+}
+
+/// Ask Gurgeh. Replies are cached only when `cache` is given, as `(position, bypass_cache)`.
+async fn coach_reply(
+    task: CoachTask,
+    message: String,
+    context: Option<String>,
+    api_key: Option<String>,
+    cache: Option<(&str, bool)>,
+    setup: &CoachSetup,
+) -> Result<CoachResponse, String> {
+    // Check for API key
+    let Some(api_key) = resolve_api_key(api_key) else {
         return Ok(CoachResponse {
             message: CoachMessage {
                 role: "gurgeh".to_string(),
                 content: "I need an API key to respond. Please configure your OpenRouter API key in Settings to enable AI coaching.".to_string(),
                 timestamp: chrono::Utc::now().timestamp(),
                 actions: vec![
-                    CoachAction {
-                        action_type: "open_settings".to_string(),
-                        label: "Open Settings".to_string(),
-                        data: "".to_string(),
-                    },
+                    CoachAction::new("Open Settings", ActionTarget::OpenSettings),
                 ],
             },
             board_fen: None,
             highlights: vec![],
             arrows: vec![],
+            trace_id: None,
         });
     };
     
-    // Build messages
-    let mut messages = vec![
-        ChatMessage {
-            role: "system".to_string(),
-            content: GURGEH_SYSTEM_PROMPT.to_string(),
-        },
-    ];
-    
-    if let Some(ctx) = context {
-        messages.push(ChatMessage {
-            role: "system".to_string(),
-            content: format!("Current context: {}", ctx),
-        });
-    }
-    
-    messages.push(ChatMessage {
-        role: "user".to_string(),
-        content: message.clone(),
-    });
+    usage_analytics::record_usage(usage_analytics::COACH_MESSAGE);
+
+    let messages = coach_messages(task, &message, context, setup);
     
-    // Make API request
-    let client = Client::new();
-    let request = ChatRequest {
-        model: "anthropic/claude-3-haiku".to_string(),
+    // Make API request. Cached replies must not depend on tool results, so only
+    // uncached chat gets tools.
+    let mut request = ChatRequest {
+        model: String::new(),
         messages,
         temperature: 0.7,
-        max_tokens: 1000,
+        max_tokens: setup.max_tokens,
+        tools: if cache.is_none() {
+            coach_tools::coach_tools().into_iter().filter(|t| setup.allows_tool(t.function.name)).collect()
+        } else {
+            vec![]
+        },
     };
     
-    let response = client
-        .post("https://openrouter.ai/api/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .header("Content-Type", "application/json")
-        .header("HTTP-Referer", "https://github.com/tacticus-chess")
-        .header("X-Title", "Tacticus Chess Trainer")
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+    let mut actions = vec![];
+    let mut trace_id = None;
+    let response_content = match cache {
+        Some((position, bypass_cache)) => send_cached_request(&api_key, task, request, Some(position), bypass_cache).await?,
+        None => {
+            let mut trace = CoachTrace { conversation_id: setup.conversation_id, ..Default::default() };
+            let reply = send_with_tools(&api_key, task, &mut request, setup.conversation_id, &mut actions, &mut trace).await?;
+            trace_id = DB.with_conn(|conn| coach_trace::record(conn, &trace)).unwrap_or_else(|e| {
+                tracing::warn!("failed to record coach trace: {}", e);
+                None
+            });
+            reply
+        }
+    };
+    let response_content = response_content
+        .unwrap_or_else(|| "I apologize, but I couldn't generate a response. Please try again.".to_string());
     
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("API error ({}): {}", status, error_text));
+    // Action markers become buttons only if they check out against the app's data
+    let (response_content, requests) = coach_actions::extract_actions(&setup.redact(&response_content));
+    for action in coach_actions::validate_all(requests) {
+        if !actions.contains(&action) && actions.len() < coach_actions::MAX_ACTIONS {
+            actions.push(action);
+        }
     }
     
-    let chat_response: ChatResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-    
-    let response_content = chat_response
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .unwrap_or_else(|| "I apologize, but I couldn't generate a response. Please try again.".to_string());
+    // Run output through the profile's moderation policy before it reaches the UI
+    let response_content = moderation::moderate(&response_content, &moderation::active_policy()).content;
     
     Ok(CoachResponse {
         message: CoachMessage {
             role: "gurgeh".to_string(),
             content: response_content,
             timestamp: chrono::Utc::now().timestamp(),
-            actions: vec![],
+            actions,
         },
         board_fen: None,
         highlights: vec![],
         arrows: vec![],
+        trace_id,
+    })
+}
+
+/// `send_routed_request`, running any tools the model calls and sending their
+/// results back until it answers. Concepts it looked up become deep links in `actions`;
+/// what was sent and what came back goes in `trace`.
+async fn send_with_tools(
+    api_key: &str,
+    task: CoachTask,
+    request: &mut ChatRequest,
+    conversation_id: Option<i64>,
+    actions: &mut Vec<CoachAction>,
+    trace: &mut CoachTrace,
+) -> Result<Option<String>, String> {
+    for round in 0..=MAX_TOOL_ROUNDS {
+        // Last round: no more tools, the model has to answer with what it has
+        if round == MAX_TOOL_ROUNDS {
+            request.tools.clear();
+        }
+        let (model, reply) = send_routed_request(api_key, task, request).await?;
+        if trace.prompt.is_empty() {
+            // The first request as sent, once privacy settings have been applied
+            trace.model = model;
+            trace.prompt = request
+                .messages
+                .iter()
+                .map(|m| TraceMessage { role: m.role.clone(), content: m.content.clone() })
+                .collect();
+        }
+        let Some(reply) = reply else {
+            return Ok(None);
+        };
+        trace.responses.push(reply.content.clone());
+        let calls = match &reply.tool_calls {
+            Some(calls) if !calls.is_empty() && !request.tools.is_empty() => calls.clone(),
+            _ => return Ok(Some(reply.content).filter(|c| !c.is_empty())),
+        };
+
+        request.messages.push(reply);
+        for call in calls {
+            // Only tools offered in this conversation's mode may run
+            let output = if request.tools.iter().any(|t| t.function.name == call.function.name) {
+                coach_tools::run_tool(&call.function, conversation_id)
+            } else {
+                coach_tools::unavailable(&call.function.name)
+            };
+            if let Some((concept_id, name)) = output.concept {
+                let action = CoachAction::new(format!("Open {}", name), ActionTarget::OpenConcept { concept_id });
+                if !actions.contains(&action) {
+                    actions.push(action);
+                }
+            }
+            trace.tool_calls.push(TraceToolCall {
+                name: call.function.name.clone(),
+                arguments: call.function.arguments.clone(),
+                result: output.content.clone(),
+            });
+            let mut result = ChatMessage::new("tool", output.content);
+            result.tool_call_id = Some(call.id);
+            request.messages.push(result);
+        }
+    }
+    Ok(None)
+}
+
+/// A streamed coach reply with its action markers turned into buttons
+#[derive(Debug, Serialize)]
+pub struct ResolvedReply {
+    pub content: String,
+    pub actions: Vec<CoachAction>,
+}
+
+/// Strip `[[kind:args]]` markers from a reply the frontend streamed itself and
+/// return only the actions that validate, so every button it renders is safe to run.
+/// With a `conversation_id` the reply is also held to that conversation's solution lock.
+/// The text is moderated with the active profile's policy before it reaches the UI.
+#[tauri::command]
+pub fn resolve_coach_actions(content: String, conversation_id: Option<i64>) -> Result<ResolvedReply, String> {
+    let content = match conversation_id {
+        Some(id) => conversation_mode::for_conversation(id)?.redact(&content),
+        None => content,
+    };
+    let (content, requests) = coach_actions::extract_actions(&content);
+    Ok(ResolvedReply {
+        content: moderation::moderate(&content, &moderation::active_policy()).content,
+        actions: coach_actions::validate_all(requests),
     })
 }
 
@@ -223,7 +671,13 @@ pub async fn chat_with_coach(
 pub async fn analyze_position_with_coach(
     fen: String,
     api_key: Option<String>,
+    bypass_cache: Option<bool>,
 ) -> Result<CoachResponse, String> {
+    // Scrubbing through moves fires one request per position; only the last one is analysed
+    POSITION_ANALYSIS.settle(ANALYSIS_DEBOUNCE).await?;
+
+    // Move counters don't change the analysis, so leave them out of the prompt and cache key
+    let fen = coach_cache::normalize_fen(&fen);
     let prompt = format!(
         "Analyze this chess position (FEN: {}).\n\n\
          Provide:\n\
@@ -235,7 +689,65 @@ pub async fn analyze_position_with_coach(
         fen
     );
     
-    chat_with_coach(prompt, Some(format!("Position FEN: {}", fen)), api_key).await
+    let mut context = format!("Position FEN: {}", fen);
+    if let Ok(board) = parse_fen(&fen) {
+        if DefenseFinder::is_lost(&board) {
+            let tries: Vec<String> = DefenseFinder::rank_defenses(&board).into_iter().take(3).map(|d| d.san).collect();
+            context.push_str(&format!(
+                "\nThe side to move is losing. Rather than the objectively best move, advise the most \
+                 resilient defence that makes the opponent's task hardest. Engine's most stubborn tries: {}",
+                tries.join(", ")
+            ));
+        }
+    }
+    let cache = Some((fen.as_str(), bypass_cache.unwrap_or(false)));
+    let setup = conversation_mode::for_general()?;
+    coach_reply(CoachTask::PositionAnalysis, prompt, Some(context), api_key, cache, &setup).await
+}
+
+/// Keeps "what is my opponent planning?" answers from giving the player's move away
+const NO_SPOILER_GUIDANCE: &str = "No-spoiler mode. Explain only what the opponent is threatening or planning. \
+Do not suggest, name or hint at a move for the player; they want to find it themselves.";
+
+/// Answer "what is my opponent planning?" for `fen` without spoiling the player's best move.
+/// Without an API key the engine's threat summary is returned as is.
+#[tauri::command]
+pub async fn explain_opponent_plan(fen: String, api_key: Option<String>) -> Result<CoachResponse, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    let report = ThreatDetector::get_threats(&board);
+    let highlights: Vec<String> = report.threats.iter().flat_map(|t| t.squares.clone()).collect();
+
+    let content = if resolve_api_key(api_key.clone()).is_none() {
+        report.summary()
+    } else {
+        let prompt = format!(
+            "What is my opponent planning in this position (FEN: {})?\n\n\
+             Engine findings about {}'s ideas:\n{}\n\
+             Their strongest moves if it were their turn: {}",
+            fen,
+            report.opponent,
+            report.summary(),
+            if report.best_replies.is_empty() { "none".to_string() } else { report.best_replies.join(", ") },
+        );
+        let setup = conversation_mode::for_general()?;
+        coach_reply(CoachTask::Chat, prompt, Some(NO_SPOILER_GUIDANCE.to_string()), api_key, None, &setup)
+            .await?
+            .message
+            .content
+    };
+
+    Ok(CoachResponse {
+        message: CoachMessage {
+            role: "gurgeh".to_string(),
+            content,
+            timestamp: chrono::Utc::now().timestamp(),
+            actions: vec![],
+        },
+        board_fen: Some(fen),
+        highlights,
+        arrows: vec![],
+        trace_id: None,
+    })
 }
 
 #[tauri::command]
@@ -268,11 +780,134 @@ pub fn get_position_feedback(
         board_fen: Some(fen),
         highlights: vec![],
         arrows: vec![],
+        trace_id: None,
     }
 }
 
 #[tauri::command]
 pub fn check_api_key_configured() -> bool {
-    dotenv::dotenv().ok();
-    std::env::var("OPENROUTER_API_KEY").is_ok()
+    if !cfg!(feature = "llm") {
+        return false;
+    }
+    app_config::current().llm.api_key.is_some()
+}
+
+/// Whether this build has an online coach. Clients that call the model themselves
+/// check this first, as offline builds must send nothing.
+#[tauri::command]
+pub fn llm_enabled() -> bool {
+    cfg!(feature = "llm")
+}
+
+/// Where model calls go, for the chat the frontend streams itself
+#[tauri::command]
+pub fn get_llm_base_url() -> String {
+    app_config::current().llm.base_url
+}
+
+#[tauri::command]
+pub fn get_moderation_policy() -> Result<ModerationPolicy, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| ModerationPolicy::load(conn, profile.id))
+        .map_err(|e| format!("Failed to load moderation policy: {}", e))
+}
+
+#[tauri::command]
+pub fn update_moderation_policy(policy: ModerationPolicy) -> Result<(), String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    // Kid mode's stricter filtering follows the kid mode setting, not the form
+    DB.with_conn(|conn| {
+        let policy = ModerationPolicy { kid_mode: kid_mode::is_enabled(conn, profile.id)?, ..policy };
+        policy.save(conn, profile.id)
+    })
+    .map_err(|e| format!("Failed to save moderation policy: {}", e))
+}
+
+#[tauri::command]
+pub fn get_model_routing() -> Vec<ModelRoute> {
+    CoachTask::ALL
+        .into_iter()
+        .map(|task| ModelRoute {
+            task,
+            override_model: model_override(task),
+            default_models: task.default_models().iter().map(|m| m.to_string()).collect(),
+        })
+        .collect()
+}
+
+/// Prefer `model` (an OpenRouter model id) for `task`. `None` restores the defaults.
+#[tauri::command]
+pub fn set_model_override(task: CoachTask, model: Option<String>) -> Result<(), String> {
+    let model = model.map(|m| m.trim().to_string()).unwrap_or_default();
+    DB.with_conn(|conn| repositories::set_setting(conn, &task.override_key(), &model))
+        .map_err(|e| format!("Failed to save model override: {}", e))
+}
+
+/// Drop every cached coach reply, returning how many were removed
+#[tauri::command]
+pub fn clear_coach_cache() -> Result<usize, String> {
+    DB.with_conn(repositories::clear_cached_responses)
+        .map_err(|e| format!("Failed to clear coach cache: {}", e))
+}
+
+/// Whether coach replies are being traced for the transparency view
+#[tauri::command]
+pub fn get_coach_transparency() -> Result<bool, String> {
+    DB.with_conn(coach_trace::is_enabled)
+        .map_err(|e| format!("Failed to read coach transparency setting: {}", e))
+}
+
+/// Keep a trace of every prompt, tool call and raw response behind coach replies.
+/// Turning it off deletes the traces recorded so far.
+#[tauri::command]
+pub fn set_coach_transparency(enabled: bool) -> Result<(), String> {
+    DB.with_conn(|conn| coach_trace::set_enabled(conn, enabled))
+        .map_err(|e| format!("Failed to update coach transparency setting: {}", e))
+}
+
+/// Store the trace of a reply the frontend streamed itself. Returns its id to pass to
+/// `add_message` when the reply is saved, or `None` while transparency is off.
+#[tauri::command]
+pub fn record_coach_trace(trace: CoachTrace) -> Result<Option<i64>, String> {
+    DB.with_conn(|conn| coach_trace::record(conn, &trace))
+        .map_err(|e| format!("Failed to record coach trace: {}", e))
+}
+
+/// What was sent to the model and what came back for a saved coach message
+#[tauri::command]
+pub fn get_message_trace(message_id: i64) -> Result<Option<CoachTrace>, String> {
+    DB.with_conn(|conn| repositories::get_message_trace(conn, message_id))
+        .map_err(|e| format!("Failed to get message trace: {}", e))
+}
+
+/// Report the board on screen so coach chats can refer to it; `None` when the board is closed
+#[tauri::command]
+pub fn set_board_context(snapshot: Option<BoardSnapshot>) {
+    board_context::set_provider(snapshot.map(|s| Box::new(s) as Box<dyn board_context::ContextProvider>));
+}
+
+/// Board context block for chats assembled in the frontend
+#[tauri::command]
+pub fn get_board_context_prompt() -> Option<String> {
+    board_context::current().map(|context| context.to_prompt())
+}
+
+/// Moves named in `message` checked against the board on screen, for chats assembled
+/// in the frontend. Evaluations and the engine's own choice are left out while the
+/// conversation's solution is locked.
+#[tauri::command]
+pub fn check_move_mentions(message: String, conversation_id: Option<i64>) -> Result<Option<MoveCheck>, String> {
+    let Some(board) = board_context::current() else {
+        return Ok(None);
+    };
+    let setup = conversation_mode::for_chat(conversation_id)?;
+    Ok(move_mentions::check_fen(&board.fen, &message, !setup.solution_locked))
 }
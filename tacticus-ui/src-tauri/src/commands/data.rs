@@ -1,6 +1,34 @@
+use chess_core::{detect_opening, format_move, move_label, parse_fen, parse_legal_uci};
+use chess_engine::CancellationToken;
+use chess_trainer::{ExerciseDifficulty, ExerciseType};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::AppHandle;
 use crate::DB;
-use crate::database::repositories::{self, Game, ExerciseResult as DbExerciseResult, TrainingProgress, PlayerStats, ImprovementTrend, WeaknessEntry};
+use crate::attempt_flags;
+use crate::board_assets;
+use crate::commands::analysis::analyze_stored_game;
+use crate::conversation_mode::{self, ConversationMode};
+use crate::game_comparison::{self, GameComparison};
+use crate::file_open::{self, OpenedFile};
+use crate::game_debrief;
+use crate::game_import::{self, ImportedGame};
+use crate::game_report::{self, GameReportCard, ReportFormat};
+use crate::game_result::{self, GameSubmission};
+use crate::notation_prefs;
+use crate::onboarding::{self, PuzzleOutcome};
+use crate::opening_deviation;
+use crate::operation_journal;
+use crate::position_import::{self, ImportSource, ImportedPosition};
+use crate::position_library;
+use crate::progress_diff::{self, ProgressDiff};
+use crate::sync_merge::{self, MergeReport, SyncBatch};
+use crate::time_control::TimeClass;
+use crate::transcript::{self, ExportFormat};
+use crate::usage_analytics;
+use super::user::profile_write_error;
+use crate::database::repositories::{self, Game, ExerciseResult as DbExerciseResult, TrainingProgress, TrainingSessionRecord, PlayerStats, ImprovementTrend, WeaknessEntry, ThemePerformance, OpponentStrengthStats, TimeBreakdown, TimeClassStats, SavedPosition, OperationRecord, OpeningDeviation};
 
 // ============================================================================
 // Game Commands
@@ -19,45 +47,141 @@ pub struct SaveGameRequest {
     pub mistakes: i32,
     pub blunders: i32,
     pub opening_name: Option<String>,
+    /// "white" or "black" when the game ended by resignation
+    #[serde(default)]
+    pub resigned_by: Option<String>,
+    /// PGN-style time control, base seconds plus increment, e.g. "300+3"; none when untimed
+    #[serde(default)]
+    pub time_control: Option<String>,
 }
 
+/// Save a finished game. The moves are replayed first, and the stored result,
+/// final position and termination come from them rather than from the request.
+/// The game is then analysed, debriefed and checked against the repertoire in the background.
 #[tauri::command]
-pub fn save_game(game: SaveGameRequest) -> Result<i64, String> {
+pub fn save_game(app: AppHandle, game: SaveGameRequest) -> Result<i64, String> {
+    let verified = game_result::verify_game(&GameSubmission {
+        initial_fen: &game.initial_fen,
+        moves: &game.moves,
+        player_color: &game.player_color,
+        result: &game.result,
+        final_fen: &game.final_fen,
+        resigned_by: game.resigned_by.as_deref(),
+    })
+    .map_err(|e| format!("Invalid game: {}", e))?;
+
     let profile = DB
         .with_conn(|conn| repositories::get_first_profile(conn))
         .map_err(|e| format!("Database error: {}", e))?
         .ok_or_else(|| "No user profile found".to_string())?;
 
+    // Games from the standard start are named even when the frontend didn't
+    let opening_name = game
+        .opening_name
+        .or_else(|| detect_opening(&game.initial_fen, &game.moves).map(|o| o.name.to_string()));
+
     let db_game = Game {
         id: 0,
         profile_id: profile.id,
         initial_fen: game.initial_fen,
-        final_fen: game.final_fen,
+        final_fen: verified.final_fen,
         moves: game.moves,
-        result: game.result,
+        result: verified.result,
         player_color: game.player_color,
         opponent_type: game.opponent_type,
         opponent_elo: game.opponent_elo,
         analysis: game.analysis,
         mistakes: game.mistakes,
         blunders: game.blunders,
-        opening_name: game.opening_name,
+        opening_name,
         created_at: String::new(),
         finished_at: Some(chrono::Utc::now().to_rfc3339()),
+        termination: Some(verified.termination.as_str().to_string()),
+        time_class: game
+            .time_control
+            .as_deref()
+            .and_then(TimeClass::from_time_control)
+            .map(|class| class.as_str().to_string()),
     };
 
-    DB.with_conn(|conn| repositories::create_game(conn, &db_game))
-        .map_err(|e| format!("Failed to save game: {}", e))
+    let id = DB
+        .with_conn(|conn| repositories::create_game(conn, &db_game))
+        .map_err(|e| format!("Failed to save game: {}", e))?;
+    usage_analytics::record_usage(usage_analytics::GAME_PLAYED);
+    game_debrief::spawn(app, id);
+    opening_deviation::spawn(id);
+    Ok(id)
 }
 
+/// A pasted game as it was read, and its id once saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameImport {
+    pub game: ImportedGame,
+    /// Side the player is taken to have played; none when their name isn't in the game
+    pub player_color: Option<String>,
+    pub saved_id: Option<i64>,
+    /// The game was already among the player's games, so `saved_id` is the copy stored before
+    #[serde(default)]
+    pub duplicate: bool,
+}
+
+/// Read a game pasted from Lichess, Chess.com or a chat, and add it to the
+/// player's games when `save` is set. Without `player_color` the profile name is
+/// looked for among the players. Saved games are analysed and debriefed like played ones;
+/// a game already saved, with the same moves, players and date, isn't saved again.
 #[tauri::command]
-pub fn get_recent_games(count: i32) -> Result<Vec<Game>, String> {
+pub fn import_game(
+    app: AppHandle,
+    source: String,
+    player_color: Option<String>,
+    save: Option<bool>,
+) -> Result<GameImport, String> {
+    let game = game_import::parse(&source)?;
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+    let player_color = player_color.or_else(|| game.side_of(&profile.name).map(str::to_string));
+    if !save.unwrap_or(false) {
+        return Ok(GameImport { game, player_color, saved_id: None, duplicate: false });
+    }
+
+    let color = player_color
+        .as_deref()
+        .ok_or_else(|| "Choose whether you played White or Black".to_string())?;
+    let record = game.to_record(profile.id, color)?;
+    let existing = DB
+        .with_conn(|conn| repositories::find_duplicate_game(conn, &record))
+        .map_err(|e| format!("Failed to check for duplicate games: {}", e))?;
+    if let Some(id) = existing {
+        return Ok(GameImport { game, player_color, saved_id: Some(id), duplicate: true });
+    }
+    let id = DB
+        .with_conn(|conn| repositories::create_game(conn, &record))
+        .map_err(|e| format!("Failed to save game: {}", e))?;
+    game_debrief::spawn(app, id);
+    opening_deviation::spawn(id);
+
+    Ok(GameImport { game, player_color, saved_id: Some(id), duplicate: false })
+}
+
+/// Check a time class filter from the frontend or the coach and give its stored name
+fn time_class_filter(time_class: Option<String>) -> Result<Option<&'static str>, String> {
+    time_class
+        .map(|class| class.parse::<TimeClass>().map(TimeClass::as_str))
+        .transpose()
+}
+
+/// Newest games first; `time_class` limits them to "bullet", "blitz", "rapid" or "classical"
+#[tauri::command]
+pub fn get_recent_games(count: i32, time_class: Option<String>) -> Result<Vec<Game>, String> {
+    let time_class = time_class_filter(time_class)?;
     let profile = DB
         .with_conn(|conn| repositories::get_first_profile(conn))
         .map_err(|e| format!("Database error: {}", e))?
         .ok_or_else(|| "No user profile found".to_string())?;
 
-    DB.with_conn(|conn| repositories::get_recent_games(conn, profile.id, count))
+    DB.with_conn(|conn| repositories::get_recent_games(conn, profile.id, count, time_class))
         .map_err(|e| format!("Failed to get games: {}", e))
 }
 
@@ -73,16 +197,183 @@ pub fn search_games_by_opening(opening_name: String) -> Result<Vec<Game>, String
 }
 
 #[tauri::command]
-pub fn get_games_with_mistakes(min_mistakes: i32) -> Result<Vec<Game>, String> {
+pub fn get_games_with_mistakes(min_mistakes: i32, time_class: Option<String>) -> Result<Vec<Game>, String> {
+    let time_class = time_class_filter(time_class)?;
     let profile = DB
         .with_conn(|conn| repositories::get_first_profile(conn))
         .map_err(|e| format!("Database error: {}", e))?
         .ok_or_else(|| "No user profile found".to_string())?;
 
-    DB.with_conn(|conn| repositories::get_games_with_mistakes(conn, profile.id, min_mistakes))
+    DB.with_conn(|conn| repositories::get_games_with_mistakes(conn, profile.id, min_mistakes, time_class))
         .map_err(|e| format!("Failed to get games: {}", e))
 }
 
+/// Delete a saved game, undoable with `undo_last_operation` for a short while.
+/// `None` when there was no such game.
+#[tauri::command]
+pub fn delete_game(game_id: i64) -> Result<Option<OperationRecord>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| operation_journal::delete_game(conn, profile.id, game_id))
+        .map_err(|e| format!("Failed to delete game: {}", e))
+}
+
+/// Where recent games left the player's repertoire, newest first, for fixing opening leaks
+#[tauri::command]
+pub fn get_recent_deviations(limit: Option<i32>) -> Result<Vec<OpeningDeviation>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_recent_deviations(conn, profile.id, limit.unwrap_or(10).clamp(1, 50)))
+        .map_err(|e| format!("Failed to get opening deviations: {}", e))
+}
+
+// ============================================================================
+// Position Library Commands
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionImport {
+    #[serde(flatten)]
+    pub position: ImportedPosition,
+    /// Library id when the position was saved
+    pub saved_id: Option<i64>,
+}
+
+/// Open a pasted FEN, Lichess analysis link, puzzle or PGN on the analysis board, and
+/// keep it in the position library when `save` is set
+#[tauri::command]
+pub fn import_position(
+    source: String,
+    save: Option<bool>,
+    title: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<PositionImport, String> {
+    let position = position_import::import(&source)?;
+    if !save.unwrap_or(false) {
+        return Ok(PositionImport { position, saved_id: None });
+    }
+
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let record = SavedPosition {
+        id: 0,
+        profile_id: profile.id,
+        fen: position.fen.clone(),
+        initial_fen: position.initial_fen.clone(),
+        moves: position.moves.clone(),
+        source: position.source.as_str().to_string(),
+        origin: "import".to_string(),
+        title: non_empty(title).or_else(|| position.opening_name.clone()),
+        note: None,
+        tags: position_library::normalize_tags(tags.unwrap_or_default()),
+        created_at: String::new(),
+        solution_line: position.solution_line.clone(),
+    };
+    let saved_id = DB
+        .with_conn(|conn| repositories::insert_saved_position(conn, &record))
+        .map_err(|e| format!("Failed to save position: {}", e))?;
+
+    Ok(PositionImport { position, saved_id: Some(saved_id) })
+}
+
+/// .pgn and .fen files double-clicked in the OS since the last call, read and
+/// ready for `import_game` or `import_position`
+#[tauri::command]
+pub fn take_opened_files() -> Vec<OpenedFile> {
+    file_open::take_pending()
+}
+
+fn non_empty(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Bookmark the position on any board - a game, a puzzle, the analysis board or
+/// one the coach showed - into the position library. Returns the bookmark's id.
+#[tauri::command]
+pub fn bookmark_position(
+    fen: String,
+    origin: String,
+    note: Option<String>,
+    tags: Option<Vec<String>>,
+    title: Option<String>,
+) -> Result<i64, String> {
+    position_library::check_origin(&origin)?;
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    let fen = format!("{}", board);
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let record = SavedPosition {
+        id: 0,
+        profile_id: profile.id,
+        initial_fen: fen.clone(),
+        fen,
+        moves: vec![],
+        source: ImportSource::Fen.as_str().to_string(),
+        origin,
+        title: non_empty(title),
+        note: non_empty(note),
+        tags: position_library::normalize_tags(tags.unwrap_or_default()),
+        created_at: String::new(),
+        solution_line: vec![],
+    };
+    DB.with_conn(|conn| repositories::insert_saved_position(conn, &record))
+        .map_err(|e| format!("Failed to bookmark position: {}", e))
+}
+
+/// The position library, newest first; `tag` keeps only positions carrying it and
+/// `origin` only those saved from "import", "game", "puzzle", "coach" or "analysis"
+#[tauri::command]
+pub fn get_saved_positions(tag: Option<String>, origin: Option<String>) -> Result<Vec<SavedPosition>, String> {
+    if let Some(origin) = &origin {
+        position_library::check_origin(origin)?;
+    }
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let tag = non_empty(tag);
+    DB.with_conn(|conn| repositories::get_saved_positions(conn, profile.id, tag.as_deref(), origin.as_deref()))
+        .map_err(|e| format!("Failed to get saved positions: {}", e))
+}
+
+/// Replace a bookmark's note and tags
+#[tauri::command]
+pub fn update_saved_position(id: i64, note: Option<String>, tags: Vec<String>) -> Result<bool, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let note = non_empty(note);
+    let tags = position_library::normalize_tags(tags);
+    DB.with_conn(|conn| repositories::update_saved_position(conn, profile.id, id, note.as_deref(), &tags))
+        .map_err(|e| format!("Failed to update saved position: {}", e))
+}
+
+#[tauri::command]
+pub fn delete_saved_position(id: i64) -> Result<bool, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::delete_saved_position(conn, profile.id, id))
+        .map_err(|e| format!("Failed to delete saved position: {}", e))
+}
+
 // ============================================================================
 // Exercise Result Commands
 // ============================================================================
@@ -96,43 +387,82 @@ pub struct RecordExerciseRequest {
     pub attempts: i32,
     pub time_seconds: i32,
     pub hints_used: i32,
+    /// Training session from `start_training_session`, if the attempt was part of one
+    #[serde(default)]
+    pub session_id: Option<i64>,
+    /// Solved with an engine-approved alternative rather than the stored solution
+    #[serde(default)]
+    pub alternative_solution: bool,
+    /// Exercise the attempt was at, so guided help given during it is recorded with it
+    #[serde(default)]
+    pub exercise_id: Option<usize>,
 }
 
 #[tauri::command]
 pub fn record_exercise_result(result: RecordExerciseRequest) -> Result<i64, String> {
+    // Store canonical names so progress queries can filter on exact matches
+    let exercise_type: ExerciseType = result.exercise_type.parse().map_err(|e| format!("Invalid exercise result: {}", e))?;
+    let difficulty: ExerciseDifficulty = result.difficulty.parse().map_err(|e| format!("Invalid exercise result: {}", e))?;
+
     let profile = DB
         .with_conn(|conn| repositories::get_first_profile(conn))
         .map_err(|e| format!("Database error: {}", e))?
         .ok_or_else(|| "No user profile found".to_string())?;
 
-    let db_result = DbExerciseResult {
+    let mut db_result = DbExerciseResult {
         id: 0,
         profile_id: profile.id,
-        exercise_type: result.exercise_type,
-        difficulty: result.difficulty,
+        session_id: result.session_id,
+        exercise_type: exercise_type.to_string(),
+        difficulty: difficulty.to_string(),
         position_fen: result.position_fen,
         solved: result.solved,
         attempts: result.attempts,
         time_seconds: result.time_seconds,
         hints_used: result.hints_used,
         created_at: String::new(),
+        flagged: false,
+        flag_reason: None,
+        alternative_solution: result.solved && result.alternative_solution,
+        scaffolding: vec![],
     };
+    // Guided conversations started after the attempt began belong to it
+    let started = chrono::Utc::now() - chrono::Duration::seconds(result.time_seconds.max(0) as i64);
 
     let result_id = DB
-        .with_conn(|conn| repositories::record_exercise_result(conn, &db_result))
+        .with_conn(|conn| {
+            if let Some(exercise_id) = result.exercise_id {
+                let steps = conversation_mode::guided_steps(conn, profile.id, exercise_id, &started.to_rfc3339())?;
+                db_result.scaffolding = steps.iter().map(|s| s.to_string()).collect();
+            }
+            let suspicion = attempt_flags::check(conn, profile.id, &db_result)?;
+            if let Some(suspicion) = &suspicion {
+                db_result.flagged = true;
+                db_result.flag_reason = Some(suspicion.reason.clone());
+                repositories::flag_exercise_results(conn, &suspicion.also_flag, &suspicion.reason)?;
+            }
+            repositories::record_exercise_result(conn, &db_result)
+        })
         .map_err(|e| format!("Failed to record exercise: {}", e))?;
 
-    // Update profile exercise count
-    let mut updated_profile = profile;
-    updated_profile.exercises_completed += 1;
-    DB.with_conn(|conn| repositories::update_profile(conn, &updated_profile))
-        .map_err(|e| format!("Failed to update profile: {}", e))?;
+    if let Some(session_id) = result.session_id {
+        DB.with_conn(|conn| repositories::refresh_training_session(conn, session_id))
+            .map_err(|e| format!("Failed to update training session: {}", e))?;
+    }
+
+    DB.with_conn(|conn| repositories::increment_exercises_completed(conn, profile.id))
+        .map_err(profile_write_error)?;
 
     Ok(result_id)
 }
 
 #[tauri::command]
 pub fn get_training_progress(exercise_type: Option<String>) -> Result<TrainingProgress, String> {
+    let exercise_type = exercise_type
+        .map(|t| t.parse::<ExerciseType>().map(|t| t.to_string()))
+        .transpose()
+        .map_err(|e| format!("Invalid filter: {}", e))?;
+
     let profile = DB
         .with_conn(|conn| repositories::get_first_profile(conn))
         .map_err(|e| format!("Database error: {}", e))?
@@ -144,6 +474,90 @@ pub fn get_training_progress(exercise_type: Option<String>) -> Result<TrainingPr
     .map_err(|e| format!("Failed to get training progress: {}", e))
 }
 
+// ============================================================================
+// Training Session Commands
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionDetails {
+    pub session: TrainingSessionRecord,
+    pub results: Vec<DbExerciseResult>,
+    /// Post-mortem cards for the results that have one, to show why wrong answers failed
+    pub postmortems: Vec<repositories::ExercisePostMortem>,
+    pub summary: String,
+}
+
+/// Open a training session; pass the returned id with each `record_exercise_result`
+#[tauri::command]
+pub fn start_training_session(total_exercises: i32, focus_areas: Vec<String>) -> Result<i64, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::create_training_session(conn, profile.id, total_exercises, &focus_areas))
+        .map_err(|e| format!("Failed to start training session: {}", e))
+}
+
+/// Close a session and rate it from the difficulty of the puzzles solved and failed
+#[tauri::command]
+pub fn finish_training_session(session_id: i64) -> Result<TrainingSessionRecord, String> {
+    DB.with_conn(|conn| {
+        let Some(session) = repositories::finish_training_session(conn, session_id)? else {
+            return Ok(None);
+        };
+        let puzzles: Vec<PuzzleOutcome> = repositories::get_session_exercise_results(conn, session_id)?
+            .iter()
+            .filter(|result| !result.flagged)
+            .filter_map(|result| {
+                Some(PuzzleOutcome {
+                    theme: result.exercise_type.parse().ok()?,
+                    difficulty: result.difficulty.parse().ok()?,
+                    solved: result.solved,
+                })
+            })
+            .collect();
+        let performance_rating = onboarding::performance_rating(&puzzles);
+        repositories::set_session_performance_rating(conn, session_id, performance_rating)?;
+        Ok(Some(TrainingSessionRecord { performance_rating, ..session }))
+    })
+    .map_err(|e| format!("Failed to finish training session: {}", e))?
+    .ok_or_else(|| format!("Training session not found: {}", session_id))
+}
+
+/// Past training sessions, newest first
+#[tauri::command]
+pub fn get_session_history(limit: Option<i64>) -> Result<Vec<TrainingSessionRecord>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_training_sessions(conn, profile.id, limit.unwrap_or(20)))
+        .map_err(|e| format!("Failed to get session history: {}", e))
+}
+
+#[tauri::command]
+pub fn get_session_details(session_id: i64) -> Result<SessionDetails, String> {
+    let session = DB
+        .with_conn(|conn| repositories::get_training_session(conn, session_id))
+        .map_err(|e| format!("Failed to get training session: {}", e))?
+        .ok_or_else(|| format!("Training session not found: {}", session_id))?;
+    let results = DB
+        .with_conn(|conn| repositories::get_session_exercise_results(conn, session_id))
+        .map_err(|e| format!("Failed to get session results: {}", e))?;
+    let postmortems = DB
+        .with_conn(|conn| repositories::get_session_postmortems(conn, session_id))
+        .map_err(|e| format!("Failed to get session post-mortems: {}", e))?;
+
+    Ok(SessionDetails {
+        summary: session.to_session_result().summary(),
+        session,
+        results,
+        postmortems,
+    })
+}
+
 // ============================================================================
 // Player Stats Commands (for AI agent)
 // ============================================================================
@@ -182,19 +596,77 @@ pub fn get_weakness_history(days: i32) -> Result<Vec<WeaknessEntry>, String> {
         .map_err(|e| format!("Failed to get weakness history: {}", e))
 }
 
+/// Exercise themes ranked weakest first, over the last `days` or all time
+#[tauri::command]
+pub fn get_theme_performance(days: Option<i32>) -> Result<Vec<ThemePerformance>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_theme_performance(conn, profile.id, days))
+        .map_err(|e| format!("Failed to get theme performance: {}", e))
+}
+
+/// Results sliced by opponent rating band, for the profile view and the coach
+#[tauri::command]
+pub fn get_opponent_strength_stats() -> Result<OpponentStrengthStats, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_opponent_strength_stats(conn, profile.id))
+        .map_err(|e| format!("Failed to get opponent strength stats: {}", e))
+}
+
+/// Results and accuracy split by bullet, blitz, rapid and classical
+#[tauri::command]
+pub fn get_time_class_stats() -> Result<Vec<TimeClassStats>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_time_class_stats(conn, profile.id))
+        .map_err(|e| format!("Failed to get time class stats: {}", e))
+}
+
+/// The last `games` analysed games, ten by default, against the ones before them:
+/// ACPL by phase, blunders per 40 moves and theme miss rates, with how far to trust each change
+#[tauri::command]
+pub fn get_progress_diff(games: Option<usize>) -> Result<ProgressDiff, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let games = games.unwrap_or(progress_diff::DEFAULT_GAMES);
+    DB.with_conn(|conn| progress_diff::progress_diff(conn, profile.id, games))
+        .map_err(|e| format!("Failed to compare progress: {}", e))
+}
+
 // ============================================================================
 // Conversation Commands
 // ============================================================================
 
+/// Start a conversation in `mode`, general when none is given
 #[tauri::command]
-pub fn create_conversation(title: Option<String>, context: Option<String>) -> Result<i64, String> {
+pub fn create_conversation(title: Option<String>, mode: Option<ConversationMode>) -> Result<i64, String> {
     let profile = DB
         .with_conn(|conn| repositories::get_first_profile(conn))
         .map_err(|e| format!("Database error: {}", e))?
         .ok_or_else(|| "No user profile found".to_string())?;
 
+    // Help with an exercise always starts locked and a quiz at zero, whatever the caller claims
+    let mode = match mode.unwrap_or(ConversationMode::General) {
+        ConversationMode::ExerciseHelp { exercise_id, .. } => ConversationMode::ExerciseHelp { exercise_id, attempts: 0 },
+        ConversationMode::GuidedHelp { exercise_id, .. } => ConversationMode::GuidedHelp { exercise_id, steps: vec![] },
+        ConversationMode::QuizMe { .. } => ConversationMode::QuizMe { answered: 0, correct: 0 },
+        mode => mode,
+    };
     DB.with_conn(|conn| {
-        repositories::create_conversation(conn, profile.id, title.as_deref(), context.as_deref())
+        repositories::create_conversation(conn, profile.id, title.as_deref(), Some(&mode.to_context()))
     })
     .map_err(|e| format!("Failed to create conversation: {}", e))
 }
@@ -206,16 +678,29 @@ pub fn add_message(
     content: String,
     tool_calls: Option<String>,
     tool_results: Option<String>,
+    trace_id: Option<i64>,
 ) -> Result<i64, String> {
+    // Coach output is moderated before it is persisted
+    let content = if role == "assistant" {
+        crate::moderation::moderate(&content, &crate::moderation::active_policy()).content
+    } else {
+        content
+    };
+
     DB.with_conn(|conn| {
-        repositories::add_message(
+        let message_id = repositories::add_message(
             conn,
             conversation_id,
             &role,
             &content,
             tool_calls.as_deref(),
             tool_results.as_deref(),
-        )
+        )?;
+        // The coach transparency trace behind this reply, if one was recorded
+        if let Some(trace_id) = trace_id {
+            repositories::attach_coach_trace(conn, trace_id, message_id)?;
+        }
+        Ok(message_id)
     })
     .map_err(|e| format!("Failed to add message: {}", e))
 }
@@ -236,3 +721,352 @@ pub fn get_recent_conversations(limit: i32) -> Result<Vec<repositories::Conversa
     DB.with_conn(|conn| repositories::get_recent_conversations(conn, profile.id, limit))
         .map_err(|e| format!("Failed to get conversations: {}", e))
 }
+
+/// Delete a conversation, undoable with `undo_last_operation` for a short while
+#[tauri::command]
+pub fn delete_conversation(conversation_id: i64) -> Result<Option<OperationRecord>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| operation_journal::delete_conversation(conn, profile.id, conversation_id))
+        .map_err(|e| format!("Failed to delete conversation: {}", e))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationExport {
+    /// Where the transcript was written
+    pub path: String,
+    pub content: String,
+}
+
+/// Write a conversation as a Markdown or HTML transcript with board diagrams for any
+/// positions discussed. `format` is "markdown" or "html".
+#[tauri::command]
+pub fn export_conversation(conversation_id: i64, format: String) -> Result<ConversationExport, String> {
+    let format: ExportFormat = format.parse()?;
+
+    let conversation = DB
+        .with_conn(|conn| repositories::get_conversation(conn, conversation_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Conversation not found".to_string())?;
+    let messages = DB
+        .with_conn(|conn| repositories::get_conversation_messages(conn, conversation_id))
+        .map_err(|e| format!("Failed to get messages: {}", e))?;
+
+    let content = transcript::render_transcript(&conversation, &messages, format, &board_assets::diagram_style());
+
+    let path = exports_dir()?.join(format!("conversation-{}.{}", conversation_id, format.extension()));
+    std::fs::write(&path, &content).map_err(|e| format!("Failed to write export: {}", e))?;
+
+    Ok(ConversationExport {
+        path: path.display().to_string(),
+        content,
+    })
+}
+
+fn exports_dir() -> Result<PathBuf, String> {
+    let dir = dirs::data_local_dir()
+        .ok_or_else(|| "Could not determine the data directory".to_string())?
+        .join("tacticus")
+        .join("exports");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create export directory: {}", e))?;
+    Ok(dir)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameReportExport {
+    /// Where the report was written
+    pub path: String,
+    /// The report as HTML, also when a PDF was written
+    pub html: String,
+}
+
+/// The coach's messages from every review conversation about a game, oldest first
+fn game_coach_notes(conn: &rusqlite::Connection, game: &Game) -> rusqlite::Result<Vec<String>> {
+    let mode = ConversationMode::GameReview { game_id: game.id };
+    let mut conversations: Vec<_> = repositories::get_recent_conversations(conn, game.profile_id, 200)?
+        .into_iter()
+        .filter(|c| ConversationMode::parse(c.context.as_deref()) == mode)
+        .collect();
+    conversations.sort_by_key(|c| c.id);
+
+    let mut notes = vec![];
+    for conversation in conversations {
+        for message in repositories::get_conversation_messages(conn, conversation.id)? {
+            if message.role == "assistant" && !message.content.trim().is_empty() {
+                notes.push(message.content);
+            }
+        }
+    }
+    Ok(notes)
+}
+
+/// Print an HTML file to PDF with whatever headless renderer is installed
+fn html_to_pdf(html_path: &Path, pdf_path: &Path) -> Result<(), String> {
+    let status = Command::new("wkhtmltopdf").arg("--quiet").arg(html_path).arg(pdf_path).status().or_else(|_| {
+        let print_to = format!("--print-to-pdf={}", pdf_path.display());
+        ["chromium", "chromium-browser", "google-chrome", "chrome"]
+            .iter()
+            .map(|browser| Command::new(browser).args(["--headless", "--disable-gpu", "--no-pdf-header-footer", &print_to]).arg(html_path).status())
+            .find(|status| status.is_ok())
+            .unwrap_or_else(|| Err(std::io::Error::from(std::io::ErrorKind::NotFound)))
+    });
+
+    match status {
+        Ok(s) if s.success() && pdf_path.exists() => Ok(()),
+        Ok(s) => Err(format!("PDF renderer exited with {}", s)),
+        Err(_) => Err("No PDF renderer found (wkhtmltopdf or Chromium); export as HTML and print it instead".to_string()),
+    }
+}
+
+/// Write a printable report of a game: eval graph, annotated move table, diagrams of
+/// the key moments, the coach's commentary and a training recommendation.
+/// `format` is "html" or "pdf". Games without a stored analysis are analysed first.
+#[tauri::command]
+pub async fn export_game_report(game_id: i64, format: String) -> Result<GameReportExport, String> {
+    let format: ReportFormat = format.parse()?;
+
+    let game = DB
+        .with_conn(|conn| repositories::get_game_by_id(conn, game_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let stored = DB
+        .with_conn(|conn| repositories::get_game_analyses(conn, game_id))
+        .map_err(|e| format!("Failed to get analysis: {}", e))?
+        .into_iter()
+        .next();
+    let record = match stored {
+        Some(record) => record,
+        None => {
+            let analysed = game.clone();
+            let record = tokio::task::spawn_blocking(move || analyze_stored_game(&analysed, &CancellationToken::new()))
+                .await
+                .map_err(|e| format!("Analysis failed: {}", e))?
+                .ok_or_else(|| format!("Could not replay game {}", game_id))?;
+            DB.with_conn(|conn| repositories::insert_game_analysis(conn, &record))
+                .map_err(|e| format!("Failed to save analysis: {}", e))?;
+            record
+        }
+    };
+
+    let notes = DB
+        .with_conn(|conn| game_coach_notes(conn, &game))
+        .map_err(|e| format!("Failed to get coach notes: {}", e))?;
+    let html = game_report::render_report(&game, &record, &notes, &board_assets::diagram_style(), &notation_prefs::active());
+
+    let dir = exports_dir()?;
+    let html_path = dir.join(format!("game-{}-report.html", game_id));
+    std::fs::write(&html_path, &html).map_err(|e| format!("Failed to write report: {}", e))?;
+    let path = match format {
+        ReportFormat::Html => html_path,
+        ReportFormat::Pdf => {
+            let pdf_path = dir.join(format!("game-{}-report.{}", game_id, format.extension()));
+            html_to_pdf(&html_path, &pdf_path)?;
+            pdf_path
+        }
+    };
+
+    Ok(GameReportExport {
+        path: path.display().to_string(),
+        html,
+    })
+}
+
+/// Summary card of an analysed game: accuracy and move quality counts for both
+/// sides, the opening, key moment positions and a performance estimate.
+/// Reads the stored analysis only; `None` when the game has not been analysed yet.
+#[tauri::command]
+pub fn get_game_report_card(game_id: i64) -> Result<Option<GameReportCard>, String> {
+    let game = DB
+        .with_conn(|conn| repositories::get_game_by_id(conn, game_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let record = DB
+        .with_conn(|conn| repositories::get_game_analyses(conn, game_id))
+        .map_err(|e| format!("Failed to get analysis: {}", e))?
+        .into_iter()
+        .next();
+    Ok(record.map(|record| game_report::report_card(&game, &record)))
+}
+
+/// A stored game for stepping through on the analysis board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameReplay {
+    pub game_id: i64,
+    /// The start, then the position after each move, so `positions[ply]` follows `ply` half-moves
+    pub positions: Vec<String>,
+    /// Each move with its number in the player's notation, e.g. "12...Nf6"
+    pub moves: Vec<String>,
+}
+
+/// Every position of a stored game, for opening it at any ply. The replay stops at
+/// the first move that doesn't play.
+#[tauri::command]
+pub fn get_game_replay(game_id: i64) -> Result<GameReplay, String> {
+    let game = DB
+        .with_conn(|conn| repositories::get_game_by_id(conn, game_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let notation = notation_prefs::active();
+    let mut board = parse_fen(&game.initial_fen).map_err(|e| e.to_string())?;
+    let mut positions = vec![board.to_string()];
+    let mut moves = vec![];
+    for (ply, uci) in game.moves.iter().enumerate() {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else { break };
+        moves.push(format!("{}{}", move_label(&game.initial_fen, ply), format_move(&board, chess_move, &notation)));
+        board = board.make_move_new(chess_move);
+        positions.push(board.to_string());
+    }
+    Ok(GameReplay { game_id, positions, moves })
+}
+
+/// Two to four games lined up ply by ply: the moves they share, where they part
+/// ways, and each game's evaluation curve and accuracy from its stored analysis.
+/// Games keep the order given; unanalysed games come without curves or accuracy.
+#[tauri::command]
+pub fn compare_games(game_ids: Vec<i64>) -> Result<GameComparison, String> {
+    game_comparison::check_ids(&game_ids)?;
+    let games = DB
+        .with_conn(|conn| {
+            game_ids
+                .iter()
+                .map(|id| {
+                    let game = repositories::get_game_by_id(conn, *id)?;
+                    let record = repositories::get_game_analyses(conn, *id)?.into_iter().next();
+                    Ok(game.map(|game| (game, record)))
+                })
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let games = games
+        .into_iter()
+        .zip(&game_ids)
+        .map(|(game, id)| game.ok_or_else(|| format!("Game {} not found", id)))
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(game_comparison::compare(&games, &notation_prefs::active()))
+}
+
+// ============================================================================
+// Activity Tracking Commands
+// ============================================================================
+
+/// Activities that can be timed
+const TRACKED_ACTIVITIES: &[&str] = &["playing", "puzzles", "analysis", "coach_chat"];
+
+#[tauri::command]
+pub fn start_activity(activity: String) -> Result<i64, String> {
+    if !TRACKED_ACTIVITIES.contains(&activity.as_str()) {
+        return Err(format!("Unknown activity: {}", activity));
+    }
+
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::start_activity(conn, profile.id, &activity))
+        .map_err(|e| format!("Failed to start activity: {}", e))
+}
+
+#[tauri::command]
+pub fn stop_activity(session_id: i64) -> Result<Option<i64>, String> {
+    DB.with_conn(|conn| repositories::stop_activity(conn, session_id))
+        .map_err(|e| format!("Failed to stop activity: {}", e))
+}
+
+/// Map a reporting period ("day", "week", "month" or "all") to a day count
+pub(crate) fn period_to_days(period: &str) -> Result<Option<i32>, String> {
+    match period {
+        "day" => Ok(Some(1)),
+        "week" => Ok(Some(7)),
+        "month" => Ok(Some(30)),
+        "all" => Ok(None),
+        other => Err(format!("Unknown period: {}", other)),
+    }
+}
+
+/// The coach's context block on how the student spent their study time, so plans it
+/// proposes fit it; none before any time is logged
+pub(crate) fn study_time_prompt(breakdown: &TimeBreakdown) -> Option<String> {
+    if breakdown.total_seconds == 0 {
+        return None;
+    }
+    let minutes = |seconds: i64| (seconds + 30) / 60;
+    let parts: Vec<String> = breakdown
+        .by_activity
+        .iter()
+        .map(|a| format!("{} min {}", minutes(a.total_seconds), a.activity.replace('_', " ")))
+        .collect();
+    Some(format!(
+        "The student's study time over the last {} days: {} min in all ({}). Fit any plan you suggest to it.",
+        breakdown.period_days.unwrap_or_default(),
+        minutes(breakdown.total_seconds),
+        parts.join(", ")
+    ))
+}
+
+/// Time spent per activity. `period` is one of "day", "week", "month" or "all".
+#[tauri::command]
+pub fn get_time_breakdown(period: String) -> Result<TimeBreakdown, String> {
+    let days = period_to_days(&period)?;
+
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_time_breakdown(conn, profile.id, days))
+        .map_err(|e| format!("Failed to get time breakdown: {}", e))
+}
+
+// ============================================================================
+// Undo Commands
+// ============================================================================
+
+/// Bring back what the latest delete or reset removed, if it is still within the
+/// undo window. Returns the operation undone, or `None` when there is nothing to undo.
+#[tauri::command]
+pub fn undo_last_operation() -> Result<Option<OperationRecord>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| operation_journal::undo_last(conn, profile.id))
+        .map_err(|e| format!("Failed to undo: {}", e))
+}
+
+// ============================================================================
+// Sync Commands
+// ============================================================================
+
+/// This device's history for another device to merge. `after` is the `watermark` of
+/// the last batch the other side received; without it everything is sent.
+#[tauri::command]
+pub fn export_sync_batch(after: Option<String>) -> Result<SyncBatch, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| sync_merge::export(conn, profile.id, after.as_deref()))
+        .map_err(|e| format!("Failed to export sync batch: {}", e))
+}
+
+/// Merge another device's history; rows already present are kept unless the batch's copy is newer
+#[tauri::command]
+pub fn merge_sync_batch(batch: SyncBatch) -> Result<MergeReport, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| sync_merge::merge(conn, profile.id, &batch))
+        .map_err(|e| format!("Failed to merge sync batch: {}", e))
+}
@@ -0,0 +1,92 @@
+use chess::Color;
+use chess_core::ChessGame;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use crate::dgt_board::{self, DgtConnection, DgtEvent, DgtSyncStatus};
+
+lazy_static! {
+    /// The connected electronic board, if any
+    static ref DGT_CONNECTION: Mutex<Option<DgtConnection>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DgtBoardStatus {
+    pub connected: bool,
+    pub port: Option<String>,
+    pub sync: Option<DgtSyncStatus>,
+    /// Position of the game the board is driving
+    pub fen: Option<String>,
+}
+
+fn parse_color(player_color: &str) -> Color {
+    if player_color.eq_ignore_ascii_case("black") { Color::Black } else { Color::White }
+}
+
+#[tauri::command]
+pub fn list_dgt_ports() -> Result<Vec<String>, String> {
+    dgt_board::available_ports()
+}
+
+/// Connect to a board and start a session from `fen`. Moves made on the board are played in
+/// that session and reported through `poll_dgt_events`.
+#[tauri::command]
+pub fn connect_dgt_board(port: String, fen: String, player_color: String) -> Result<DgtBoardStatus, String> {
+    let game = ChessGame::from_fen(&fen, parse_color(&player_color)).map_err(|e| e.to_string())?;
+
+    let mut connection = DGT_CONNECTION.lock().map_err(|_| "Board connection lock poisoned".to_string())?;
+    // Dropping the old connection stops its reader thread before the port is reopened
+    *connection = None;
+    *connection = Some(DgtConnection::open(&port, game)?);
+    drop(connection);
+
+    get_dgt_status()
+}
+
+#[tauri::command]
+pub fn disconnect_dgt_board() -> Result<(), String> {
+    let mut connection = DGT_CONNECTION.lock().map_err(|_| "Board connection lock poisoned".to_string())?;
+    *connection = None;
+    Ok(())
+}
+
+/// Tell the board session about a position change made in the app (engine reply, takeback,
+/// new game). The board reports out of sync until the pieces are set up to match.
+#[tauri::command]
+pub fn sync_dgt_board(fen: String, player_color: String) -> Result<DgtBoardStatus, String> {
+    let game = ChessGame::from_fen(&fen, parse_color(&player_color)).map_err(|e| e.to_string())?;
+
+    {
+        let connection = DGT_CONNECTION.lock().map_err(|_| "Board connection lock poisoned".to_string())?;
+        let connection = connection.as_ref().ok_or_else(|| "No board connected".to_string())?;
+        let mut session = connection.session.lock().map_err(|_| "Board session lock poisoned".to_string())?;
+        session.set_game(game);
+    }
+
+    get_dgt_status()
+}
+
+#[tauri::command]
+pub fn get_dgt_status() -> Result<DgtBoardStatus, String> {
+    let connection = DGT_CONNECTION.lock().map_err(|_| "Board connection lock poisoned".to_string())?;
+
+    let Some(connection) = connection.as_ref() else {
+        return Ok(DgtBoardStatus { connected: false, port: None, sync: None, fen: None });
+    };
+    let session = connection.session.lock().map_err(|_| "Board session lock poisoned".to_string())?;
+
+    Ok(DgtBoardStatus {
+        connected: connection.is_connected(),
+        port: Some(connection.port_name.clone()),
+        sync: Some(session.status()),
+        fen: Some(session.game().get_fen()),
+    })
+}
+
+/// Moves, illegal-position warnings and resyncs since the last poll
+#[tauri::command]
+pub fn poll_dgt_events() -> Result<Vec<DgtEvent>, String> {
+    let connection = DGT_CONNECTION.lock().map_err(|_| "Board connection lock poisoned".to_string())?;
+    let connection = connection.as_ref().ok_or_else(|| "No board connected".to_string())?;
+    let mut session = connection.session.lock().map_err(|_| "Board session lock poisoned".to_string())?;
+    Ok(session.drain_events())
+}
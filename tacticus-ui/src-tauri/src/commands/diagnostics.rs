@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use chess_engine::{BenchConfig, CancellationToken};
+use crate::DB;
+use crate::app_config;
+use crate::database::repositories;
+use crate::engine_bench::{self, BenchOutcome, BenchRun};
+use crate::engine_strength::{self, StrengthReport};
+use crate::health::{self, HealthStatus};
+use crate::logging;
+use crate::session_seed;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsBundle {
+    /// Where the bundle was written, for attaching to a bug report
+    pub path: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub sqlite_version: String,
+    pub log_level: String,
+    /// Deterministic mode seed, so the session can be replayed
+    pub session_seed: Option<u64>,
+    pub integrity_check: Vec<String>,
+    pub log_excerpt: String,
+}
+
+impl DiagnosticsBundle {
+    fn to_report(&self) -> String {
+        format!(
+            "Tacticus diagnostics\n\
+             ====================\n\
+             App version: {}\n\
+             OS: {} ({})\n\
+             SQLite: {}\n\
+             Log level: {}\n\
+             Session seed: {}\n\
+             Database integrity: {}\n\
+             \n\
+             Recent log\n\
+             ----------\n\
+             {}\n",
+            self.app_version,
+            self.os,
+            self.arch,
+            self.sqlite_version,
+            self.log_level,
+            self.session_seed.map_or("off".to_string(), |s| s.to_string()),
+            self.integrity_check.join("; "),
+            self.log_excerpt,
+        )
+    }
+}
+
+#[tauri::command]
+pub fn get_log_level() -> String {
+    logging::stored_level().to_string()
+}
+
+#[tauri::command]
+pub fn set_log_level(level: String) -> Result<String, String> {
+    let level = logging::set_level(&level)?;
+
+    DB.with_conn(|conn| repositories::set_setting(conn, logging::LOG_LEVEL_SETTING_KEY, level))
+        .map_err(|e| format!("Failed to save log level: {}", e))?;
+
+    Ok(level.to_string())
+}
+
+#[tauri::command]
+pub fn get_session_seed() -> Result<Option<u64>, String> {
+    DB.with_conn(session_seed::get)
+        .map_err(|e| format!("Failed to read session seed: {}", e))
+}
+
+/// Turn deterministic mode on with `seed`, or off with `None`
+#[tauri::command]
+pub fn set_session_seed(seed: Option<u64>) -> Result<(), String> {
+    DB.with_conn(|conn| session_seed::set(conn, seed))
+        .map_err(|e| format!("Failed to save session seed: {}", e))?;
+    tracing::info!(?seed, "session seed changed");
+    Ok(())
+}
+
+/// Gather logs, versions and a database integrity check into a text file for bug reports.
+/// API keys are scrubbed from everything in the bundle.
+#[tauri::command]
+pub fn collect_diagnostics() -> Result<DiagnosticsBundle, String> {
+    let integrity_check = DB
+        .with_conn(repositories::integrity_check)
+        .unwrap_or_else(|e| vec![format!("integrity check failed: {}", e)]);
+
+    let mut secrets: Vec<String> = Vec::new();
+    if let Ok(Some(key)) = DB.with_conn(|conn| repositories::get_setting(conn, "api_key")) {
+        secrets.push(key);
+    }
+    secrets.extend(app_config::base().llm.api_key.clone());
+
+    let log_excerpt = logging::scrub_secrets(&logging::recent_log_lines().join("\n"), &secrets);
+
+    let dir = dirs::data_local_dir()
+        .ok_or_else(|| "Could not determine the data directory".to_string())?
+        .join("tacticus")
+        .join("diagnostics");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create diagnostics directory: {}", e))?;
+    let path = dir.join(format!("diagnostics-{}.txt", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+
+    let bundle = DiagnosticsBundle {
+        path: path.display().to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        sqlite_version: rusqlite::version().to_string(),
+        log_level: logging::stored_level().to_string(),
+        session_seed: session_seed::active(),
+        integrity_check,
+        log_excerpt,
+    };
+
+    std::fs::write(&path, logging::scrub_secrets(&bundle.to_report(), &secrets))
+        .map_err(|e| format!("Failed to write diagnostics: {}", e))?;
+    tracing::info!(path = %bundle.path, "diagnostics bundle written");
+
+    Ok(bundle)
+}
+
+/// Result of the startup self-test, running it again first when it hasn't finished
+/// yet or `refresh` is set
+#[tauri::command]
+pub async fn get_health_status(refresh: Option<bool>) -> HealthStatus {
+    match health::last_status() {
+        Some(status) if !refresh.unwrap_or(false) => status,
+        _ => health::run_checks().await,
+    }
+}
+
+/// Whether the database needs repairing, from its cached quick check, without
+/// waiting for the rest of the self-test
+#[tauri::command]
+pub fn get_quick_health_status() -> HealthStatus {
+    health::quick_status()
+}
+
+/// Open the database again after it failed at startup. With `start_fresh` the broken
+/// file is renamed aside, kept for recovery, and an empty one is created in its place.
+#[tauri::command]
+pub async fn repair_database(start_fresh: bool) -> Result<HealthStatus, String> {
+    if start_fresh {
+        if !health::quick_status().needs_repair {
+            return Err("The database is working; there is nothing to repair".to_string());
+        }
+        let path = app_config::base().db_path.clone();
+        if path.exists() {
+            let aside = path.with_extension(format!("broken-{}.db", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+            std::fs::rename(&path, &aside).map_err(|e| format!("Failed to move the broken database aside: {}", e))?;
+            tracing::warn!(path = %aside.display(), "moved the broken database aside");
+        }
+    }
+
+    DB.reopen().map_err(|e| format!("The database still won't open: {}", e))?;
+    health::clear_database_error();
+    super::user::init_profile();
+    Ok(health::run_checks().await)
+}
+
+/// Run the engine bench suite, store the result and compare it with the previous run
+#[tauri::command]
+pub async fn run_engine_bench(config: Option<BenchConfig>) -> Result<BenchOutcome, String> {
+    let config = config.unwrap_or_default();
+    tokio::task::spawn_blocking(move || engine_bench::run_and_record(&config, &CancellationToken::new()))
+        .await
+        .map_err(|e| format!("Bench task failed: {}", e))?
+}
+
+/// Stored bench runs, newest first
+#[tauri::command]
+pub fn get_bench_history(limit: Option<i64>) -> Result<Vec<BenchRun>, String> {
+    DB.with_conn(|conn| engine_bench::history(conn, limit.unwrap_or(engine_bench::HISTORY_LIMIT)))
+        .map_err(|e| format!("Failed to load bench history: {}", e))
+}
+
+/// Play each engine Elo setting on rated puzzles and store what rating it performs at
+#[tauri::command]
+pub async fn assess_engine_strength() -> Result<StrengthReport, String> {
+    tokio::task::spawn_blocking(|| engine_strength::measure_and_record(&CancellationToken::new()))
+        .await
+        .map_err(|e| format!("Strength assessment failed: {}", e))?
+}
+
+/// The stored strength report, if it still matches the engine and its settings
+#[tauri::command]
+pub fn get_engine_strength() -> Result<Option<StrengthReport>, String> {
+    let settings = app_config::current().engine;
+    DB.with_conn(|conn| engine_strength::latest(conn, settings.depth, settings.personality))
+        .map_err(|e| format!("Failed to load engine strength: {}", e))
+}
@@ -1,7 +1,15 @@
-use chess::{Board, ChessMove, Color, MoveGen, Piece, Square};
-use chess_engine::Evaluator;
+use chess::{Board, ChessMove, Color, MoveGen};
+use chess_core::{format_move, format_moves, parse_fen, parse_spoken_move, parse_uci, to_spoken, SpokenMove};
+use chess_engine::{DefenseFinder, DefensiveResource, Engine, EvalTerm, Evaluator, ThreatDetector, ThreatReport, MAX_ELO, MIN_ELO};
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::path::Path;
+use crate::app_config;
+use crate::board_recognition::{self, BoardRecognizer, OnnxRecognizer};
+use crate::game_assistance::{self, GameAssistance, RetryDrill, RetryVerdict};
+use crate::move_quality;
+use crate::notation_prefs;
+use crate::session_seed;
+use crate::usage_analytics;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GameState {
@@ -12,6 +20,7 @@ pub struct GameState {
     pub is_check: bool,
     pub legal_moves: Vec<String>,
     pub last_move: Option<String>,
+    /// In pawns from White's perspective
     pub evaluation: f32,
 }
 
@@ -22,10 +31,49 @@ pub struct MoveResult {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvaluationExplanation {
+    /// Overall score in pawns from White's perspective
+    pub evaluation: f32,
+    pub summary: String,
+    pub terms: Vec<EvalTerm>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageImport {
+    pub fen: String,
+    /// False when the recognised placement isn't a legal position (e.g. a missing king)
+    pub is_valid: bool,
+    /// Lowest per-square classifier confidence, 0.0 - 1.0
+    pub confidence: f32,
+    pub recognizer: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoiceMoveCandidate {
+    pub uci: String,
+    /// In the user's notation
+    pub san: String,
+    pub spoken: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoiceMoveResult {
+    /// "matched", "ambiguous" or "not_found"
+    pub status: String,
+    pub candidate: Option<VoiceMoveCandidate>,
+    /// Every fitting move when the phrase was ambiguous
+    pub options: Vec<VoiceMoveCandidate>,
+    /// Question to read back to the user when the move wasn't understood
+    pub prompt: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EngineMove {
     pub uci: String,
+    /// In the user's notation
     pub san: String,
+    /// In pawns from White's perspective
     pub evaluation: f32,
 }
 
@@ -47,7 +95,7 @@ fn board_to_game_state(board: &Board, last_move: Option<String>) -> GameState {
         is_check,
         legal_moves,
         last_move,
-        evaluation: eval.score as f32 / 100.0,
+        evaluation: eval.score.pawns(),
     }
 }
 
@@ -59,7 +107,7 @@ pub fn get_initial_position() -> GameState {
 
 #[tauri::command]
 pub fn get_legal_moves(fen: String) -> Result<Vec<String>, String> {
-    let board = Board::from_str(&fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
     let moves: Vec<String> = MoveGen::new_legal(&board)
         .map(|m| format!("{}", m))
         .collect();
@@ -68,56 +116,24 @@ pub fn get_legal_moves(fen: String) -> Result<Vec<String>, String> {
 
 #[tauri::command]
 pub fn make_move(fen: String, uci_move: String) -> MoveResult {
-    let board = match Board::from_str(&fen) {
+    let board = match parse_fen(&fen) {
         Ok(b) => b,
         Err(e) => return MoveResult {
             success: false,
             new_state: None,
-            error: Some(format!("Invalid FEN: {}", e)),
+            error: Some(e.to_string()),
         },
     };
     
-    // Parse the UCI move
-    if uci_move.len() < 4 {
-        return MoveResult {
-            success: false,
-            new_state: None,
-            error: Some("Invalid move format".to_string()),
-        };
-    }
-    
-    let from = match Square::from_str(&uci_move[0..2]) {
-        Ok(sq) => sq,
-        Err(_) => return MoveResult {
-            success: false,
-            new_state: None,
-            error: Some("Invalid source square".to_string()),
-        },
-    };
-    
-    let to = match Square::from_str(&uci_move[2..4]) {
-        Ok(sq) => sq,
-        Err(_) => return MoveResult {
+    let chess_move = match parse_uci(&uci_move) {
+        Ok(m) => m,
+        Err(e) => return MoveResult {
             success: false,
             new_state: None,
-            error: Some("Invalid destination square".to_string()),
+            error: Some(e.to_string()),
         },
     };
     
-    let promotion = if uci_move.len() == 5 {
-        match uci_move.chars().nth(4).unwrap() {
-            'q' => Some(Piece::Queen),
-            'r' => Some(Piece::Rook),
-            'b' => Some(Piece::Bishop),
-            'n' => Some(Piece::Knight),
-            _ => None,
-        }
-    } else {
-        None
-    };
-    
-    let chess_move = ChessMove::new(from, to, promotion);
-    
     // Verify the move is legal
     let legal_moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
     if !legal_moves.contains(&chess_move) {
@@ -132,39 +148,203 @@ pub fn make_move(fen: String, uci_move: String) -> MoveResult {
     
     MoveResult {
         success: true,
-        new_state: Some(board_to_game_state(&new_board, Some(uci_move))),
+        new_state: Some(board_to_game_state(&new_board, Some(chess_move.to_string()))),
         error: None,
     }
 }
 
 #[tauri::command]
 pub fn get_engine_move(fen: String, engine_elo: i32) -> Result<EngineMove, String> {
-    let board = Board::from_str(&fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
     
-    // Get the best move (we'll add ELO-based move selection later)
-    let best = Evaluator::find_best_move(&board)
+    // Ratings from the UI can fall outside what the engine models
+    let elo = (engine_elo.max(0) as u32).clamp(MIN_ELO, MAX_ELO);
+    let config = app_config::current();
+    let mut builder = Engine::builder().depth(config.engine.depth).personality(config.engine.personality).elo(elo);
+    if let Some(seed) = session_seed::active() {
+        builder = builder.seed(seed);
+    }
+    let engine = builder.build().map_err(|e| e.to_string())?;
+    let best = engine.best_move(&board)
         .ok_or_else(|| "No legal moves available".to_string())?;
     
-    // For now, we return the best move. Later we'll add randomization based on ELO
-    // Lower ELO = more likely to pick suboptimal moves
-    let _strength_factor = (engine_elo as f32 / 2000.0).min(1.0);
-    
     Ok(EngineMove {
         uci: format!("{}", best.chess_move),
-        san: format!("{}", best.chess_move), // TODO: Convert to SAN
-        evaluation: best.score as f32 / 100.0,
+        san: format_move(&board, best.chess_move, &config.notation),
+        evaluation: best.score.to_white(board.side_to_move()).pawns(),
     })
 }
 
+/// UCI moves played in turn from `fen`, written in the user's notation for move lists.
+/// Stops at the first move that isn't legal.
+#[tauri::command]
+pub fn format_move_list(fen: String, moves: Vec<String>) -> Result<Vec<String>, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    let moves: Vec<ChessMove> = moves.iter().map_while(|m| parse_uci(m).ok()).collect();
+    Ok(format_moves(&board, &moves, &notation_prefs::active()))
+}
+
 #[tauri::command]
 pub fn evaluate_position(fen: String) -> Result<f32, String> {
-    let board = Board::from_str(&fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    let eval = Evaluator::evaluate_position(&board);
+    usage_analytics::record_usage(usage_analytics::ANALYSIS_RUN);
+    Ok(eval.score.pawns())
+}
+
+#[tauri::command]
+pub fn explain_evaluation(fen: String) -> Result<EvaluationExplanation, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
     let eval = Evaluator::evaluate_position(&board);
-    Ok(eval.score as f32 / 100.0)
+
+    // Lead with the term that contributes the most
+    let summary = eval
+        .breakdown
+        .iter()
+        .max_by_key(|t| t.score.abs())
+        .map(|t| t.description.clone())
+        .unwrap_or_default();
+
+    Ok(EvaluationExplanation {
+        evaluation: eval.score.pawns(),
+        summary,
+        terms: eval.breakdown,
+    })
+}
+
+/// What the opponent is threatening in `fen`, without revealing the side to move's best reply
+#[tauri::command]
+pub fn get_threats(fen: String) -> Result<ThreatReport, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    Ok(ThreatDetector::get_threats(&board))
+}
+
+/// The side to move's most stubborn defensive tries, best first. Unlike `get_engine_move`
+/// this favours moves that make the opponent's win hardest over the raw evaluation.
+#[tauri::command]
+pub fn get_resilient_defenses(fen: String, limit: Option<usize>) -> Result<Vec<DefensiveResource>, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    let mut defenses = DefenseFinder::rank_defenses(&board);
+    defenses.truncate(limit.unwrap_or(3));
+    Ok(defenses)
 }
 
 #[tauri::command]
 pub fn get_position_from_fen(fen: String) -> Result<GameState, String> {
-    let board = Board::from_str(&fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
     Ok(board_to_game_state(&board, None))
 }
+
+/// Read a position from a screenshot or photo of a board. Side to move defaults to White;
+/// castling rights can't be seen in a picture and are left for the user to set.
+#[tauri::command]
+pub fn fen_from_image(path: String, white_to_move: Option<bool>) -> Result<ImageImport, String> {
+    let model_path = OnnxRecognizer::default_model_path()
+        .ok_or_else(|| "Could not determine the piece classifier location".to_string())?;
+    let recognizer = OnnxRecognizer::from_file(&model_path)?;
+
+    let recognized = board_recognition::recognize_image(Path::new(&path), &recognizer)?;
+    let fen = recognized.to_fen(white_to_move.unwrap_or(true));
+
+    Ok(ImageImport {
+        is_valid: parse_fen(&fen).is_ok(),
+        confidence: recognized.min_confidence(),
+        recognizer: recognizer.name().to_string(),
+        fen,
+    })
+}
+
+/// The retry drill for the player's `played` at `fen`, when it was a blunder and the
+/// profile's game assistance offers retries. Call it before asking for the engine's reply;
+/// calibration games shouldn't call it at all.
+#[tauri::command]
+pub fn check_for_blunder(fen: String, played: String) -> Result<Option<RetryDrill>, String> {
+    if game_assistance::active() != GameAssistance::Retries {
+        return Ok(None);
+    }
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    game_assistance::check_move(&board, &played, &move_quality::active())
+}
+
+/// Judge a retry of the blunder at a drill's `fen`. The attempt is recorded by the
+/// frontend with `record_exercise_result` once the drill ends.
+#[tauri::command]
+pub fn check_retry_move(fen: String, attempt: String) -> Result<RetryVerdict, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    game_assistance::check_retry(&board, &attempt, &move_quality::active())
+}
+
+/// Turn transcribed speech ("knight to f3", "castle kingside", "pawn takes on d5") into a legal
+/// move. The move is not played - the UI confirms it and calls `make_move`.
+#[tauri::command]
+pub fn parse_voice_move(fen: String, transcript: String) -> Result<VoiceMoveResult, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    let notation = notation_prefs::active();
+    let describe = |m: ChessMove| VoiceMoveCandidate {
+        uci: format!("{}", m),
+        san: format_move(&board, m, &notation),
+        spoken: to_spoken(&board, m),
+    };
+
+    let result = match parse_spoken_move(&board, &transcript) {
+        SpokenMove::Move(m) => VoiceMoveResult {
+            status: "matched".to_string(),
+            candidate: Some(describe(m)),
+            options: Vec::new(),
+            prompt: None,
+        },
+        SpokenMove::Ambiguous(moves) => {
+            let options: Vec<VoiceMoveCandidate> = moves.into_iter().map(describe).collect();
+            let spoken: Vec<&str> = options.iter().map(|o| o.spoken.as_str()).collect();
+            VoiceMoveResult {
+                status: "ambiguous".to_string(),
+                candidate: None,
+                prompt: Some(format!("Which move did you mean: {}?", spoken.join(", or "))),
+                options,
+            }
+        }
+        SpokenMove::NotFound => VoiceMoveResult {
+            status: "not_found".to_string(),
+            candidate: None,
+            options: Vec::new(),
+            prompt: Some(format!("I couldn't find a legal move for \"{}\". Try something like \"knight to f3\".", transcript.trim())),
+        },
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `fen` with the colours swapped and the board turned round
+    fn mirrored(fen: &str) -> String {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let swap_case = |rank: &str| -> String {
+            rank.chars()
+                .map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() })
+                .collect()
+        };
+        let placement: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+        let side = if fields[1] == "w" { "b" } else { "w" };
+        format!("{} {} {} - 0 1", placement.join("/"), side, fields[2])
+    }
+
+    #[test]
+    fn test_breakdown_is_from_whites_side() {
+        let start = explain_evaluation("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()).unwrap();
+        assert!(start.terms.iter().all(|t| t.score == 0), "{:?}", start.terms);
+
+        // Castled Italian with White to move, against the same position for Black
+        let fen = "r1bq1rk1/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQ1RK1 w - - 0 1";
+        let white = explain_evaluation(fen.to_string()).unwrap();
+        let black = explain_evaluation(mirrored(fen)).unwrap();
+        assert_eq!(white.terms.len(), black.terms.len());
+        for (ours, theirs) in white.terms.iter().zip(&black.terms) {
+            assert_eq!(ours.key, theirs.key);
+            assert_eq!(ours.score, -theirs.score, "{} doesn't negate", ours.key);
+        }
+        assert_eq!(white.evaluation, -black.evaluation);
+    }
+}
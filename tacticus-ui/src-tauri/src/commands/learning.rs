@@ -1,4 +1,12 @@
+use chess_engine::MatePattern;
 use serde::{Deserialize, Serialize};
+use crate::DB;
+use crate::kid_mode;
+use crate::coach_tools::{self, ConceptLookup};
+use crate::commands::coach::{complete_once, CoachTask};
+use crate::database::repositories;
+use crate::commands::model_games::load_model_games;
+use crate::opening_walkthrough::{self, OpeningLine, OpeningQuizResult, StepElaboration, WalkthroughSession};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChessConcept {
@@ -13,6 +21,8 @@ pub struct ChessConcept {
     pub example_highlights: Vec<String>,
     pub related_concepts: Vec<String>,
     pub practice_exercises: Vec<String>,
+    /// Annotated model games that illustrate this concept
+    pub model_games: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,8 +32,8 @@ pub struct ConceptCategory {
 }
 
 // Built-in concept library
-fn get_concept_library() -> Vec<ChessConcept> {
-    vec![
+pub(crate) fn get_concept_library() -> Vec<ChessConcept> {
+    let mut concepts = vec![
         // TACTICS
         ChessConcept {
             id: "fork".to_string(),
@@ -41,6 +51,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["h5".to_string(), "f7".to_string()],
             related_concepts: vec!["knight_fork".to_string(), "double_attack".to_string()],
             practice_exercises: vec!["fork_1".to_string(), "fork_2".to_string()],
+            model_games: vec![],
         },
         ChessConcept {
             id: "knight_fork".to_string(),
@@ -58,6 +69,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["e4".to_string()],
             related_concepts: vec!["fork".to_string(), "family_fork".to_string()],
             practice_exercises: vec!["knight_fork_1".to_string()],
+            model_games: vec![],
         },
         ChessConcept {
             id: "pin".to_string(),
@@ -76,6 +88,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["c4".to_string(), "f7".to_string(), "e8".to_string()],
             related_concepts: vec!["skewer".to_string(), "absolute_pin".to_string(), "relative_pin".to_string()],
             practice_exercises: vec!["pin_1".to_string(), "pin_2".to_string()],
+            model_games: vec![],
         },
         ChessConcept {
             id: "skewer".to_string(),
@@ -92,6 +105,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["e2".to_string(), "e8".to_string()],
             related_concepts: vec!["pin".to_string(), "x_ray".to_string()],
             practice_exercises: vec!["skewer_1".to_string()],
+            model_games: vec![],
         },
         ChessConcept {
             id: "back_rank_mate".to_string(),
@@ -109,8 +123,9 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_fen: Some("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1".to_string()),
             example_arrows: vec![("a1".to_string(), "a8".to_string())],
             example_highlights: vec!["a1".to_string(), "g8".to_string()],
-            related_concepts: vec!["checkmate_patterns".to_string(), "luft".to_string()],
+            related_concepts: vec!["smothered_mate".to_string(), "anastasias_mate".to_string(), "luft".to_string()],
             practice_exercises: vec!["back_rank_1".to_string()],
+            model_games: vec![],
         },
         ChessConcept {
             id: "discovered_attack".to_string(),
@@ -128,7 +143,81 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec![],
             related_concepts: vec!["double_check".to_string(), "windmill".to_string()],
             practice_exercises: vec!["discovered_1".to_string()],
+            model_games: vec![],
         },
+        // CHECKMATE PATTERNS
+        ChessConcept {
+            id: "smothered_mate".to_string(),
+            name: "Smothered Mate".to_string(),
+            category: "Checkmate Patterns".to_string(),
+            difficulty: "Beginner".to_string(),
+            short_description: "A knight checkmates a king that is hemmed in by its own pieces.".to_string(),
+            full_explanation: "In a smothered mate the king has no squares to go to because its own pieces \
+                are standing on all of them, and the knight's check can't be blocked.\n\n\
+                It usually strikes a king in the corner behind its pawns with a rook beside it. Look for it \
+                when the knight can reach f7 (f2 for Black) or the square next to the corner. The famous \
+                version starts with a queen sacrifice on g8 that forces the rook to smother its own king.".to_string(),
+            example_fen: Some(MatePattern::SmotheredMate.example_fen().to_string()),
+            example_arrows: vec![("g5".to_string(), "f7".to_string())],
+            example_highlights: vec!["h8".to_string(), "g8".to_string(), "g7".to_string(), "h7".to_string()],
+            related_concepts: vec!["back_rank_mate".to_string(), "arabian_mate".to_string()],
+            practice_exercises: vec!["smothered_mate".to_string()],
+            model_games: vec![],
+        },
+        ChessConcept {
+            id: "anastasias_mate".to_string(),
+            name: "Anastasia's Mate".to_string(),
+            category: "Checkmate Patterns".to_string(),
+            difficulty: "Intermediate".to_string(),
+            short_description: "A knight and a rook or queen trap the king on the edge of the board.".to_string(),
+            full_explanation: "The king sits on the h-file (or a-file) with its own pawn beside it. A knight \
+                on e7 takes away g8 and g6, so a rook or queen checking down the h-file is mate.\n\n\
+                The rook often gets to the h-file with a sacrifice: a queen takes on h7 and the rook \
+                recaptures with mate. Watch for it whenever your knight lands on e7 against a castled king.".to_string(),
+            example_fen: Some(MatePattern::AnastasiaMate.example_fen().to_string()),
+            example_arrows: vec![("d5".to_string(), "h5".to_string())],
+            example_highlights: vec!["h7".to_string(), "e7".to_string(), "g8".to_string(), "g6".to_string()],
+            related_concepts: vec!["arabian_mate".to_string(), "back_rank_mate".to_string()],
+            practice_exercises: vec!["anastasias_mate".to_string()],
+            model_games: vec![],
+        },
+        ChessConcept {
+            id: "arabian_mate".to_string(),
+            name: "Arabian Mate".to_string(),
+            category: "Checkmate Patterns".to_string(),
+            difficulty: "Beginner".to_string(),
+            short_description: "A rook mates the cornered king from next to it, protected by a knight.".to_string(),
+            full_explanation: "One of the oldest known mates. The king is in the corner; the rook checks from \
+                the square beside it and the knight both guards the rook and covers the last escape square.\n\n\
+                With a knight on f6 against a king on h8, any rook that reaches the 7th rank or the h-file \
+                is a mating threat.".to_string(),
+            example_fen: Some(MatePattern::ArabianMate.example_fen().to_string()),
+            example_arrows: vec![("a7".to_string(), "h7".to_string())],
+            example_highlights: vec!["h8".to_string(), "f6".to_string()],
+            related_concepts: vec!["anastasias_mate".to_string(), "smothered_mate".to_string()],
+            practice_exercises: vec!["arabian_mate".to_string()],
+            model_games: vec![],
+        },
+        ChessConcept {
+            id: "greek_gift".to_string(),
+            name: "Greek Gift".to_string(),
+            category: "Checkmate Patterns".to_string(),
+            difficulty: "Advanced".to_string(),
+            short_description: "The bishop sacrifice on h7 that tears open a castled king.".to_string(),
+            full_explanation: "Bxh7+ Kxh7, Ng5+ and the queen comes to h5 with mate threats on h7. It works \
+                when the set-up is there: a bishop aimed at h7, a knight that can safely reach g5, the queen \
+                able to get to the h-file, and no defending knight on f6 to cover h7 and h5.\n\n\
+                The sacrifice doesn't always mate. Before playing it, check what happens after Kg8 and \
+                Kg6, and whether the defender can bring a piece back in time. A pawn on e5 that drives the \
+                knight from f6 is often what makes it work.".to_string(),
+            example_fen: Some(MatePattern::GreekGift.example_fen().to_string()),
+            example_arrows: vec![("d3".to_string(), "h7".to_string()), ("f3".to_string(), "g5".to_string())],
+            example_highlights: vec!["h7".to_string(), "g8".to_string()],
+            related_concepts: vec!["king_safety".to_string(), "smothered_mate".to_string()],
+            practice_exercises: vec!["greek_gift".to_string()],
+            model_games: vec![],
+        },
+
         // STRATEGY
         ChessConcept {
             id: "piece_activity".to_string(),
@@ -148,6 +237,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec![],
             related_concepts: vec!["development".to_string(), "centralization".to_string()],
             practice_exercises: vec![],
+            model_games: vec![],
         },
         ChessConcept {
             id: "pawn_structure".to_string(),
@@ -168,6 +258,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec![],
             related_concepts: vec!["isolated_pawn".to_string(), "passed_pawn".to_string(), "doubled_pawns".to_string()],
             practice_exercises: vec![],
+            model_games: vec![],
         },
         // OPENINGS
         ChessConcept {
@@ -189,6 +280,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["e4".to_string(), "d4".to_string(), "e5".to_string(), "d5".to_string()],
             related_concepts: vec!["development".to_string(), "center_control".to_string(), "castling".to_string()],
             practice_exercises: vec![],
+            model_games: vec![],
         },
         ChessConcept {
             id: "castling".to_string(),
@@ -210,6 +302,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["e1".to_string(), "g1".to_string(), "h1".to_string(), "f1".to_string()],
             related_concepts: vec!["king_safety".to_string(), "opening_principles".to_string()],
             practice_exercises: vec![],
+            model_games: vec![],
         },
         // ENDGAMES
         ChessConcept {
@@ -231,6 +324,7 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["d3".to_string(), "d5".to_string()],
             related_concepts: vec!["king_and_pawn".to_string(), "zugzwang".to_string()],
             practice_exercises: vec!["opposition_1".to_string()],
+            model_games: vec![],
         },
         ChessConcept {
             id: "zugzwang".to_string(),
@@ -249,25 +343,56 @@ fn get_concept_library() -> Vec<ChessConcept> {
             example_highlights: vec!["b1".to_string()],
             related_concepts: vec!["opposition".to_string(), "triangulation".to_string()],
             practice_exercises: vec![],
+            model_games: vec![],
         },
-    ]
+    ];
+
+    // Link concepts to the model games that illustrate them
+    let model_games = load_model_games();
+    for concept in &mut concepts {
+        concept.model_games = model_games
+            .iter()
+            .filter(|g| g.concepts.contains(&concept.id))
+            .map(|g| g.id.clone())
+            .collect();
+    }
+
+    concepts
+}
+
+/// Concept library filtered for the active profile (kid mode only sees beginner concepts)
+pub(crate) fn available_concepts() -> Vec<ChessConcept> {
+    let concepts = get_concept_library();
+    if !kid_mode::active() {
+        return concepts;
+    }
+    concepts
+        .into_iter()
+        .filter(|c| kid_mode::allows_concept(&c.difficulty))
+        .collect()
 }
 
 #[tauri::command]
 pub fn get_all_concepts() -> Vec<ChessConcept> {
-    get_concept_library()
+    available_concepts()
 }
 
 #[tauri::command]
 pub fn get_concept(concept_id: String) -> Option<ChessConcept> {
-    get_concept_library()
+    available_concepts()
         .into_iter()
         .find(|c| c.id == concept_id)
 }
 
+/// Concept lookup for the coach's `lookup_concept` tool, matching names loosely
+#[tauri::command]
+pub fn lookup_concept(name: String) -> Result<ConceptLookup, String> {
+    coach_tools::lookup_concept(&available_concepts(), &name)
+}
+
 #[tauri::command]
 pub fn get_concepts_by_category(category: String) -> Vec<ChessConcept> {
-    get_concept_library()
+    available_concepts()
         .into_iter()
         .filter(|c| c.category.to_lowercase() == category.to_lowercase())
         .collect()
@@ -276,7 +401,7 @@ pub fn get_concepts_by_category(category: String) -> Vec<ChessConcept> {
 #[tauri::command]
 pub fn search_concepts(query: String) -> Vec<ChessConcept> {
     let query_lower = query.to_lowercase();
-    get_concept_library()
+    available_concepts()
         .into_iter()
         .filter(|c| {
             c.name.to_lowercase().contains(&query_lower) ||
@@ -288,50 +413,197 @@ pub fn search_concepts(query: String) -> Vec<ChessConcept> {
 
 #[tauri::command]
 pub fn get_concept_categories() -> Vec<ConceptCategory> {
-    let concepts = get_concept_library();
-    let mut categories: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
-    
-    for concept in concepts {
-        categories
-            .entry(concept.category.clone())
-            .or_insert_with(Vec::new)
-            .push(concept.id);
+    // Categories in library order, so the list is the same on every call
+    let mut categories: Vec<ConceptCategory> = Vec::new();
+    for concept in available_concepts() {
+        match categories.iter_mut().find(|c| c.name == concept.category) {
+            Some(category) => category.concepts.push(concept.id),
+            None => categories.push(ConceptCategory {
+                name: concept.category,
+                concepts: vec![concept.id],
+            }),
+        }
     }
-    
     categories
+}
+
+/// Built-in glossary, written to the database on first lookup
+const BUILTIN_GLOSSARY: &[(&str, &str)] = &[
+    ("check", "An attack on the king. The king must get out of check on the next move."),
+    ("checkmate", "A check that cannot be escaped. The game ends."),
+    ("stalemate", "When a player has no legal moves but is not in check. The game is a draw."),
+    ("en passant", "A special pawn capture that can occur when a pawn advances two squares and lands beside an enemy pawn."),
+    ("promotion", "When a pawn reaches the opposite end of the board, it must become a queen, rook, bishop, or knight."),
+    ("underpromotion", "Promoting a pawn to a rook, bishop or knight instead of a queen, usually to avoid stalemate or give a knight check."),
+    ("tempo", "A unit of time in chess, essentially one move. Gaining tempo means making your opponent waste a move."),
+    ("fianchetto", "Developing a bishop to g2/b2 (or g7/b7) after moving the knight pawn one square."),
+    ("gambit", "An opening where material (usually a pawn) is sacrificed for positional compensation."),
+    ("blunder", "A serious mistake that dramatically worsens your position."),
+    ("inaccuracy", "A move that is not the best and slightly worsens your position."),
+    ("brilliancy", "An exceptionally creative or beautiful move, often involving a sacrifice."),
+    ("elo", "A rating system used to calculate the relative skill levels of players."),
+    ("material", "The pieces and pawns. Having more material generally means having an advantage."),
+    ("initiative", "Having control of the game, making threats and forcing your opponent to respond."),
+    ("compensation", "Non-material advantages (like activity or attack) that balance material losses."),
+    ("development", "Moving pieces off their starting squares to active posts, mainly in the opening."),
+    ("outpost", "A square, usually in enemy territory, that cannot be attacked by enemy pawns - ideal for a knight."),
+    ("open file", "A file with no pawns on it, where rooks and queens are most effective."),
+    ("half-open file", "A file with pawns of only one color on it."),
+    ("passed pawn", "A pawn with no enemy pawns in front of it on its own or adjacent files."),
+    ("isolated pawn", "A pawn with no friendly pawns on the adjacent files."),
+    ("doubled pawns", "Two pawns of the same color on the same file."),
+    ("backward pawn", "A pawn that has fallen behind its neighbors and cannot be safely advanced or defended by another pawn."),
+    ("pawn chain", "A diagonal line of pawns each defending the one in front."),
+    ("luft", "An escape square made for the king, usually by advancing a pawn in front of it."),
+    ("exchange", "Trading pieces. 'Winning the exchange' means winning a rook for a bishop or knight."),
+    ("sacrifice", "Deliberately giving up material to gain an attack, position, or other advantage."),
+    ("desperado", "A piece that is lost anyway and so sells itself as dearly as possible."),
+    ("zwischenzug", "An in-between move played before the expected recapture or response."),
+    ("deflection", "Forcing an enemy piece away from a square or duty it was defending."),
+    ("decoy", "Luring an enemy piece onto a square where it can be attacked or trapped."),
+    ("overloading", "Giving a defending piece more duties than it can handle at once."),
+    ("perpetual check", "An endless series of checks the opponent cannot escape, leading to a draw."),
+    ("threefold repetition", "A draw that can be claimed when the same position occurs three times."),
+    ("fifty-move rule", "A draw can be claimed after fifty moves by each side without a capture or pawn move."),
+    ("bishop pair", "Having both bishops while the opponent does not, often an advantage in open positions."),
+];
+
+fn ensure_glossary(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    if repositories::count_glossary_entries(conn, "builtin")? > 0 {
+        return Ok(());
+    }
+    for (term, definition) in BUILTIN_GLOSSARY {
+        repositories::upsert_glossary_entry(conn, term, definition, "builtin")?;
+    }
+    Ok(())
+}
+
+/// Define a chess term: concept library first, then the glossary, then
+/// (if `allow_llm` is set) a one-sentence LLM definition cached for reuse.
+#[tauri::command]
+pub async fn define_term(term: String, allow_llm: Option<bool>) -> Option<String> {
+    let term_lower = term.trim().to_lowercase();
+    if term_lower.is_empty() {
+        return None;
+    }
+
+    // Concept library, matched by name or id
+    let concept_id = term_lower.replace([' ', '-'], "_");
+    if let Some(concept) = get_concept_library()
         .into_iter()
-        .map(|(name, concepts)| ConceptCategory { name, concepts })
-        .collect()
+        .find(|c| c.id == concept_id || c.name.to_lowercase() == term_lower)
+    {
+        return Some(concept.short_description);
+    }
+
+    // Glossary (built-in and previously cached definitions)
+    let cached = DB
+        .with_conn(|conn| {
+            ensure_glossary(conn)?;
+            repositories::get_glossary_entry(conn, &term_lower)
+        })
+        .ok()
+        .flatten();
+    if let Some(entry) = cached {
+        return Some(entry.definition);
+    }
+
+    if !allow_llm.unwrap_or(false) {
+        return None;
+    }
+
+    let prompt = format!(
+        "Define the chess term \"{}\" in one plain sentence. If it is not a chess term, reply with exactly: UNKNOWN",
+        term_lower
+    );
+    let definition = complete_once(CoachTask::Definition, "You are a concise chess glossary.", &prompt, 80).await.ok()?;
+    let definition = definition.trim().to_string();
+    if definition.is_empty() || definition.contains("UNKNOWN") {
+        return None;
+    }
+
+    if let Err(e) = DB.with_conn(|conn| repositories::upsert_glossary_entry(conn, &term_lower, &definition, "llm")) {
+        tracing::warn!(term = %term_lower, "Failed to cache definition: {}", e);
+    }
+    Some(definition)
 }
 
+/// Opening lines to walk through, with the active profile's progress on each
 #[tauri::command]
-pub fn define_term(term: String) -> Option<String> {
-    let term_lower = term.to_lowercase();
-    
-    // Quick definitions for common terms
-    let definitions: std::collections::HashMap<&str, &str> = [
-        ("check", "An attack on the king. The king must get out of check on the next move."),
-        ("checkmate", "A check that cannot be escaped. The game ends."),
-        ("stalemate", "When a player has no legal moves but is not in check. The game is a draw."),
-        ("en passant", "A special pawn capture that can occur when a pawn advances two squares and lands beside an enemy pawn."),
-        ("promotion", "When a pawn reaches the opposite end of the board, it must become a queen, rook, bishop, or knight."),
-        ("tempo", "A unit of time in chess, essentially one move. Gaining tempo means making your opponent waste a move."),
-        ("fianchetto", "Developing a bishop to g2/b2 (or g7/b7) after moving the knight pawn one square."),
-        ("gambit", "An opening where material (usually a pawn) is sacrificed for positional compensation."),
-        ("blunder", "A serious mistake that dramatically worsens your position."),
-        ("brilliancy", "An exceptionally creative or beautiful move, often involving a sacrifice."),
-        ("elo", "A rating system used to calculate the relative skill levels of players."),
-        ("material", "The pieces and pawns. Having more material generally means having an advantage."),
-        ("initiative", "Having control of the game, making threats and forcing your opponent to respond."),
-        ("compensation", "Non-material advantages (like activity or attack) that balance material losses."),
-    ].into_iter().collect();
-    
-    definitions.get(term_lower.as_str()).map(|s| s.to_string())
+pub fn get_opening_lines() -> Result<Vec<OpeningLine>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let progress = DB
+        .with_conn(|conn| repositories::get_opening_progress(conn, profile.id))
+        .map_err(|e| format!("Failed to get opening progress: {}", e))?;
+    Ok(opening_walkthrough::lines(&progress))
+}
+
+/// Start walking through the line `key` as `color` ("white" unless "black"). The
+/// whole mainline comes back with each move's explanation; it counts as an attempt.
+#[tauri::command]
+pub fn start_opening_walkthrough(key: String, color: Option<String>) -> Result<WalkthroughSession, String> {
+    let walkthrough = opening_walkthrough::find(&key)?;
+    let color = match color.as_deref() {
+        Some("black") => chess::Color::Black,
+        _ => chess::Color::White,
+    };
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let progress = DB
+        .with_conn(|conn| {
+            repositories::record_opening_attempt(conn, profile.id, &key)?;
+            repositories::get_opening_progress(conn, profile.id)
+        })
+        .map_err(|e| format!("Failed to record opening attempt: {}", e))?;
+    let progress = progress.into_iter().find(|p| p.opening_key == key);
+    Ok(opening_walkthrough::session(&walkthrough, color, progress))
+}
+
+/// Quiz the student on their next move of the line after `played` (UCI). Moves of
+/// the line played in another order count when they still reach its final
+/// position. Finishing the line records a completion with `mistakes` wrong answers.
+#[tauri::command]
+pub fn answer_opening_quiz(
+    key: String,
+    played: Vec<String>,
+    answer: String,
+    mistakes: Option<u32>,
+) -> Result<OpeningQuizResult, String> {
+    let walkthrough = opening_walkthrough::find(&key)?;
+    let mut result = opening_walkthrough::quiz(&walkthrough, &played, &answer)?;
+    if !result.answer.completed {
+        return Ok(result);
+    }
+
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+    let mistakes = i32::try_from(mistakes.unwrap_or(0)).unwrap_or(i32::MAX);
+    let progress = DB
+        .with_conn(|conn| repositories::record_opening_completion(conn, profile.id, &key, mistakes))
+        .map_err(|e| format!("Failed to record opening completion: {}", e))?;
+    result.progress = Some(progress);
+    Ok(result)
+}
+
+/// The coach's explanation of move `ply` of the line, or the line's own when the coach is unavailable
+#[tauri::command]
+pub async fn explain_opening_step(key: String, ply: usize) -> Result<StepElaboration, String> {
+    let walkthrough = opening_walkthrough::find(&key)?;
+    opening_walkthrough::elaborate(&walkthrough, ply).await
 }
 
 #[tauri::command]
 pub fn get_related_concepts(concept_id: String) -> Vec<ChessConcept> {
-    let concepts = get_concept_library();
+    let concepts = available_concepts();
     
     // First find the related concept IDs
     let related_ids: Vec<String> = concepts
@@ -2,12 +2,36 @@ pub mod game;
 pub mod training;
 pub mod coach;
 pub mod user;
+pub mod onboarding;
 pub mod learning;
 pub mod data;
+pub mod analysis;
+pub mod model_games;
+pub mod quiz;
+pub mod narration;
+pub mod dgt;
+pub mod diagnostics;
+pub mod session;
+pub mod storage;
+pub mod usage;
+pub mod appearance;
+pub mod plan;
 
 pub use game::*;
 pub use training::*;
 pub use coach::*;
 pub use user::*;
+pub use onboarding::*;
 pub use learning::*;
 pub use data::*;
+pub use analysis::*;
+pub use model_games::*;
+pub use quiz::*;
+pub use narration::*;
+pub use dgt::*;
+pub use diagnostics::*;
+pub use session::*;
+pub use storage::*;
+pub use usage::*;
+pub use appearance::*;
+pub use plan::*;
@@ -0,0 +1,121 @@
+use chess_core::{parse_fen, parse_legal_uci};
+use serde::{Deserialize, Serialize};
+
+/// Bundled library of annotated model games, see `assets/model_games.json`
+const MODEL_GAMES_JSON: &str = include_str!("../../assets/model_games.json");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelGameMove {
+    pub uci: String,
+    pub san: String,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelGame {
+    pub id: String,
+    pub title: String,
+    pub white: String,
+    pub black: String,
+    pub event: String,
+    pub year: Option<i32>,
+    /// Concept ids from the learning library this game illustrates
+    pub concepts: Vec<String>,
+    pub summary: String,
+    pub initial_fen: String,
+    pub moves: Vec<ModelGameMove>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelGameSummary {
+    pub id: String,
+    pub title: String,
+    pub white: String,
+    pub black: String,
+    pub event: String,
+    pub year: Option<i32>,
+    pub concepts: Vec<String>,
+    pub summary: String,
+    pub total_plies: usize,
+}
+
+impl From<&ModelGame> for ModelGameSummary {
+    fn from(g: &ModelGame) -> Self {
+        ModelGameSummary {
+            id: g.id.clone(),
+            title: g.title.clone(),
+            white: g.white.clone(),
+            black: g.black.clone(),
+            event: g.event.clone(),
+            year: g.year,
+            concepts: g.concepts.clone(),
+            summary: g.summary.clone(),
+            total_plies: g.moves.len(),
+        }
+    }
+}
+
+/// One step of a guided replay: the position after `ply` moves and the annotation for that move
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuidedReplayStep {
+    pub game_id: String,
+    pub ply: usize,
+    pub total_plies: usize,
+    pub fen: String,
+    pub last_move: Option<String>,
+    pub last_move_san: Option<String>,
+    pub comment: Option<String>,
+    pub is_final: bool,
+}
+
+pub(crate) fn load_model_games() -> Vec<ModelGame> {
+    serde_json::from_str(MODEL_GAMES_JSON).unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_model_games() -> Vec<ModelGameSummary> {
+    load_model_games().iter().map(ModelGameSummary::from).collect()
+}
+
+#[tauri::command]
+pub fn get_model_games_by_concept(concept_id: String) -> Vec<ModelGameSummary> {
+    load_model_games()
+        .iter()
+        .filter(|g| g.concepts.contains(&concept_id))
+        .map(ModelGameSummary::from)
+        .collect()
+}
+
+/// Serve a model game ply by ply. Ply 0 is the starting position.
+#[tauri::command]
+pub fn get_guided_replay_step(game_id: String, ply: usize) -> Result<GuidedReplayStep, String> {
+    let game = load_model_games()
+        .into_iter()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| format!("Model game not found: {}", game_id))?;
+
+    if ply > game.moves.len() {
+        return Err(format!("Ply {} is past the end of the game ({} plies)", ply, game.moves.len()));
+    }
+
+    let mut board = parse_fen(&game.initial_fen).map_err(|e| e.to_string())?;
+    for model_move in &game.moves[..ply] {
+        let chess_move = parse_legal_uci(&board, &model_move.uci)
+            .map_err(|_| format!("Illegal move in model game: {}", model_move.uci))?;
+        board = board.make_move_new(chess_move);
+    }
+
+    let last = ply.checked_sub(1).map(|i| &game.moves[i]);
+
+    Ok(GuidedReplayStep {
+        game_id: game.id.clone(),
+        ply,
+        total_plies: game.moves.len(),
+        fen: format!("{}", board),
+        last_move: last.map(|m| m.uci.clone()),
+        last_move_san: last.map(|m| m.san.clone()),
+        comment: last.and_then(|m| m.comment.clone()),
+        is_final: ply == game.moves.len(),
+    })
+}
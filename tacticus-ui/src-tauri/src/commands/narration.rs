@@ -0,0 +1,212 @@
+use chess::{Board, ChessMove, Color};
+use chess_core::{move_number, parse_fen, parse_legal_uci, to_san, to_spoken, MoveQuality};
+use chess_engine::{CancellationToken, GameAnalyzer, QualityCalibration};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+use crate::DB;
+use crate::database::repositories::{self, Game};
+use crate::move_quality;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NarrationLine {
+    pub ply: usize,
+    pub move_number: usize,
+    pub side: String,
+    pub san: String,
+    pub spoken: String,
+    pub commentary: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NarrationScript {
+    pub game_id: i64,
+    pub introduction: String,
+    pub lines: Vec<NarrationLine>,
+    pub conclusion: String,
+    /// The whole narration as plain text, ready for a TTS engine
+    pub script: String,
+}
+
+fn key_moment_commentary(board: &Board, chess_move: ChessMove, ply: usize, calibration: &QualityCalibration) -> Option<String> {
+    let analysis =
+        GameAnalyzer::analyze_move_cancellable(board, chess_move, ply, calibration, &CancellationToken::new()).ok()?;
+    let label = match analysis.quality {
+        _ if analysis.best_move == chess_move => return None,
+        MoveQuality::Blunder => "A blunder",
+        MoveQuality::Mistake => "A mistake",
+        _ => return None,
+    };
+    Some(format!("{}. Better was {}.", label, to_spoken(board, analysis.best_move)))
+}
+
+fn build_narration(game: &Game, calibration: &QualityCalibration) -> Result<NarrationScript, String> {
+    let mut board = parse_fen(&game.initial_fen).map_err(|e| e.to_string())?;
+
+    let opening = game
+        .opening_name
+        .as_deref()
+        .map(|o| format!(" The opening was the {}.", o))
+        .unwrap_or_default();
+    let introduction = format!(
+        "Game review. You played {} against {}.{}",
+        game.player_color, game.opponent_type, opening
+    );
+
+    let mut lines = Vec::new();
+    for (ply, uci) in game.moves.iter().enumerate() {
+        let chess_move = parse_legal_uci(&board, uci).map_err(|_| format!("Illegal move in game record: {}", uci))?;
+
+        let side = if board.side_to_move() == Color::White { "White" } else { "Black" };
+        lines.push(NarrationLine {
+            ply,
+            move_number: move_number(&game.initial_fen, ply).0,
+            side: side.to_string(),
+            san: to_san(&board, chess_move),
+            spoken: to_spoken(&board, chess_move),
+            commentary: key_moment_commentary(&board, chess_move, ply, calibration),
+        });
+
+        board = board.make_move_new(chess_move);
+    }
+
+    let conclusion = match game.result.as_str() {
+        "win" => "You won the game.".to_string(),
+        "loss" => "You lost the game.".to_string(),
+        "draw" => "The game was drawn.".to_string(),
+        other => format!("Result: {}.", other),
+    };
+
+    let mut script = vec![introduction.clone()];
+    for line in &lines {
+        let mut sentence = if line.side == "White" || line.ply == 0 {
+            format!("Move {}, {}: {}.", line.move_number, line.side, line.spoken)
+        } else {
+            format!("{}: {}.", line.side, line.spoken)
+        };
+        if let Some(commentary) = &line.commentary {
+            sentence.push(' ');
+            sentence.push_str(commentary);
+        }
+        script.push(sentence);
+    }
+    script.push(conclusion.clone());
+
+    Ok(NarrationScript {
+        game_id: game.id,
+        introduction,
+        lines,
+        conclusion,
+        script: script.join("\n"),
+    })
+}
+
+#[tauri::command]
+pub fn narrate_game(game_id: i64) -> Result<NarrationScript, String> {
+    let game = DB
+        .with_conn(|conn| repositories::get_game_by_id(conn, game_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Game not found: {}", game_id))?;
+
+    build_narration(&game, &move_quality::active())
+}
+
+/// Fixed System.Speech script for Windows; reads its paths from the environment
+const WINDOWS_TTS_SCRIPT: &str = "Add-Type -AssemblyName System.Speech; \
+    $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+    $s.SetOutputToWaveFile($env:TACTICUS_NARRATION_AUDIO); \
+    $s.Speak([IO.File]::ReadAllText($env:TACTICUS_NARRATION_SCRIPT)); $s.Dispose()";
+
+/// Render a game's narration to an audio file with the platform's local TTS
+/// engine (`say` on macOS, `espeak-ng`/`espeak` on Linux, System.Speech on Windows).
+/// Returns the path of the written file.
+#[tauri::command]
+pub fn render_narration_audio(game_id: i64, output_dir: Option<String>) -> Result<String, String> {
+    let narration = narrate_game(game_id)?;
+
+    let dir = output_dir
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_local_dir().map(|p| p.join("tacticus").join("narration")))
+        .ok_or_else(|| "Could not determine an output directory".to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    let script_path = dir.join(format!("game_{}.txt", game_id));
+    std::fs::write(&script_path, &narration.script)
+        .map_err(|e| format!("Failed to write narration script: {}", e))?;
+
+    let (audio_path, status) = if cfg!(target_os = "macos") {
+        let audio_path = dir.join(format!("game_{}.aiff", game_id));
+        let status = Command::new("say")
+            .arg("-o")
+            .arg(&audio_path)
+            .arg("-f")
+            .arg(&script_path)
+            .status();
+        (audio_path, status)
+    } else if cfg!(target_os = "windows") {
+        let audio_path = dir.join(format!("game_{}.wav", game_id));
+        // The paths go through the environment, never into the script text, so
+        // quotes in a user name can't end the string and run commands
+        let status = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_TTS_SCRIPT])
+            .env("TACTICUS_NARRATION_AUDIO", &audio_path)
+            .env("TACTICUS_NARRATION_SCRIPT", &script_path)
+            .status();
+        (audio_path, status)
+    } else {
+        let audio_path = dir.join(format!("game_{}.wav", game_id));
+        let status = Command::new("espeak-ng")
+            .arg("-w")
+            .arg(&audio_path)
+            .arg("-f")
+            .arg(&script_path)
+            .status()
+            .or_else(|_| {
+                Command::new("espeak")
+                    .arg("-w")
+                    .arg(&audio_path)
+                    .arg("-f")
+                    .arg(&script_path)
+                    .status()
+            });
+        (audio_path, status)
+    };
+
+    match status {
+        Ok(s) if s.success() => Ok(audio_path.display().to_string()),
+        Ok(s) => Err(format!("TTS engine exited with {}", s)),
+        Err(e) => Err(format!("No local TTS engine available: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_narration_follows_the_game() {
+        let game = Game {
+            initial_fen: "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 3 12".to_string(),
+            result: "loss".to_string(),
+            player_color: "black".to_string(),
+            opening_name: Some("Two Knights Defense".to_string()),
+            ..Game::for_test(1, &["g8f6", "f1c4", "f8c5"])
+        };
+        let narration = build_narration(&game, &QualityCalibration::default()).unwrap();
+
+        assert_eq!(
+            narration.introduction,
+            "Game review. You played black against engine. The opening was the Two Knights Defense."
+        );
+        assert_eq!(narration.conclusion, "You lost the game.");
+
+        // A game starting with Black to move numbers its first move too
+        let script: Vec<&str> = narration.script.lines().collect();
+        assert_eq!(script.len(), 5);
+        assert_eq!(script[0], narration.introduction);
+        assert!(script[1].starts_with("Move 12, Black: "), "{}", script[1]);
+        assert!(script[2].starts_with("Move 13, White: "), "{}", script[2]);
+        assert!(script[3].starts_with("Black: "), "{}", script[3]);
+        assert_eq!(script[4], narration.conclusion);
+    }
+}
@@ -0,0 +1,193 @@
+use chess_core::{parse_move, to_san};
+use chess_trainer::{Exercise, SolutionMatch};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use crate::DB;
+use crate::database::repositories;
+use crate::onboarding::{self, CalibrationEstimate, GameOutcome, PuzzleOutcome};
+use super::training::{exercise_to_data, ExerciseData, ExerciseResult};
+use super::user::UserProfile;
+
+lazy_static! {
+    /// Calibration in progress, from `start_onboarding` until `finish_onboarding`
+    static ref ONBOARDING: Mutex<Option<OnboardingSession>> = Mutex::new(None);
+}
+
+struct OnboardingSession {
+    name: String,
+    puzzles: Vec<(usize, Exercise)>,
+    next_puzzle: usize,
+    outcomes: Vec<PuzzleOutcome>,
+    game: Option<GameOutcome>,
+    game_finished: bool,
+}
+
+impl OnboardingSession {
+    fn current_step(&self) -> OnboardingStep {
+        if let Some((index, exercise)) = self.puzzles.get(self.next_puzzle) {
+            return OnboardingStep::Puzzle {
+                number: self.next_puzzle + 1,
+                total: self.puzzles.len(),
+                exercise: exercise_to_data(exercise, *index, false),
+            };
+        }
+        if !self.game_finished {
+            return OnboardingStep::Game {
+                opponent_elo: onboarding::provisional_rating(&self.outcomes),
+            };
+        }
+        OnboardingStep::Complete {
+            estimate: onboarding::estimate(&self.outcomes, self.game.as_ref()),
+        }
+    }
+}
+
+/// What the player should do next
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OnboardingStep {
+    Puzzle { number: usize, total: usize, exercise: ExerciseData },
+    /// Optional quick game against the engine set to the puzzle estimate
+    Game { opponent_elo: i32 },
+    Complete { estimate: CalibrationEstimate },
+}
+
+/// Answer to the current step. A missing move or result skips the step.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OnboardingAnswer {
+    Puzzle { user_move: Option<String> },
+    /// "win", "draw" or "loss"
+    Game { result: Option<String> },
+}
+
+#[derive(Debug, Serialize)]
+pub struct OnboardingProgress {
+    /// How the submitted puzzle went, if one was answered
+    pub feedback: Option<ExerciseResult>,
+    pub next: OnboardingStep,
+}
+
+/// Exercise solutions are written in SAN while the board reports UCI, so accept either
+pub(crate) fn is_solution(exercise: &Exercise, user_move: &str) -> bool {
+    if exercise.check_solution(user_move) {
+        return true;
+    }
+    let Ok(board) = exercise.get_board() else {
+        return false;
+    };
+    let Ok(chess_move) = parse_move(&board, user_move) else {
+        return false;
+    };
+    let san = to_san(&board, chess_move);
+    exercise.check_solution(san.trim_end_matches(['+', '#']))
+}
+
+fn game_score(result: &str) -> Result<f32, String> {
+    match result {
+        "win" => Ok(1.0),
+        "draw" => Ok(0.5),
+        "loss" => Ok(0.0),
+        other => Err(format!("Unknown game result: {}", other)),
+    }
+}
+
+/// Begin calibration for a new player. Restarting discards any earlier attempt.
+#[tauri::command]
+pub fn start_onboarding(name: String) -> Result<OnboardingStep, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Name is required".to_string());
+    }
+    let existing = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?;
+    if existing.is_some() {
+        return Err("Onboarding has already been completed".to_string());
+    }
+
+    let session = OnboardingSession {
+        name,
+        puzzles: onboarding::calibration_set(),
+        next_puzzle: 0,
+        outcomes: Vec::new(),
+        game: None,
+        game_finished: false,
+    };
+    let step = session.current_step();
+
+    *ONBOARDING.lock().map_err(|_| "Onboarding lock poisoned".to_string())? = Some(session);
+    Ok(step)
+}
+
+#[tauri::command]
+pub fn submit_onboarding_step(answer: OnboardingAnswer) -> Result<OnboardingProgress, String> {
+    let mut guard = ONBOARDING.lock().map_err(|_| "Onboarding lock poisoned".to_string())?;
+    let session = guard.as_mut().ok_or_else(|| "Onboarding has not been started".to_string())?;
+
+    let feedback = match (answer, session.current_step()) {
+        (OnboardingAnswer::Puzzle { user_move }, OnboardingStep::Puzzle { .. }) => {
+            let (_, exercise) = &session.puzzles[session.next_puzzle];
+            let feedback = user_move.map(|user_move| {
+                let correct = is_solution(exercise, &user_move);
+                session.outcomes.push(PuzzleOutcome {
+                    theme: exercise.exercise_type.clone(),
+                    difficulty: exercise.difficulty.clone(),
+                    solved: correct,
+                });
+                ExerciseResult {
+                    correct,
+                    explanation: exercise.explanation.clone(),
+                    correct_move: if correct { None } else { exercise.solution_moves.first().cloned() },
+                    solution_match: if correct { SolutionMatch::Canonical } else { SolutionMatch::Wrong },
+                    reply: None,
+                    fen: None,
+                }
+            });
+            session.next_puzzle += 1;
+            feedback
+        }
+        (OnboardingAnswer::Game { result }, OnboardingStep::Game { opponent_elo }) => {
+            session.game = match result {
+                Some(result) => Some(GameOutcome { opponent_elo, score: game_score(&result)? }),
+                None => None,
+            };
+            session.game_finished = true;
+            None
+        }
+        (_, step) => return Err(format!("Answer does not match the current step: {:?}", step)),
+    };
+
+    Ok(OnboardingProgress { feedback, next: session.current_step() })
+}
+
+/// Create the profile from the calibration so far; unanswered steps are treated as skipped
+#[tauri::command]
+pub fn finish_onboarding() -> Result<UserProfile, String> {
+    let session = ONBOARDING
+        .lock()
+        .map_err(|_| "Onboarding lock poisoned".to_string())?
+        .take()
+        .ok_or_else(|| "Onboarding has not been started".to_string())?;
+
+    let estimate = onboarding::estimate(&session.outcomes, session.game.as_ref());
+    let level = onboarding::level_for_rating(estimate.rating);
+
+    let profile = DB
+        .with_conn(|conn| {
+            let mut profile = repositories::create_profile(conn, &session.name, level, estimate.rating)?;
+            profile.weaknesses = estimate.weaknesses.clone();
+            profile.strengths = estimate.strengths.clone();
+            repositories::update_profile(conn, &mut profile)?;
+            Ok(profile)
+        })
+        .map_err(|e| format!("Failed to create profile: {}", e))?;
+
+    tracing::info!(
+        rating = estimate.rating,
+        puzzles = session.outcomes.len(),
+        played_game = session.game.is_some(),
+        "onboarding complete"
+    );
+    Ok(profile.into())
+}
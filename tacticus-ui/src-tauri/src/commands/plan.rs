@@ -0,0 +1,143 @@
+use crate::DB;
+use crate::database::repositories::{self, Flashcard, OperationRecord, PendingCoachAction};
+use crate::operation_journal;
+use crate::training_plan::{self, PlanChange, PlanEntry};
+
+// ============================================================================
+// Coach Proposals
+// ============================================================================
+// The coach's write tools only propose; each change waits in the chat until
+// the player approves or declines it.
+
+/// Propose training on a theme, optionally with a due date (`YYYY-MM-DD`)
+#[tauri::command]
+pub fn propose_training_plan_item(
+    theme: String,
+    title: Option<String>,
+    due_date: Option<String>,
+    note: Option<String>,
+    conversation_id: Option<i64>,
+) -> Result<PendingCoachAction, String> {
+    training_plan::propose(conversation_id, PlanChange::AddTraining { theme, title, due_date, note })
+}
+
+/// Propose reviewing one of the player's saved games
+#[tauri::command]
+pub fn propose_game_review(
+    game_id: i64,
+    due_date: Option<String>,
+    note: Option<String>,
+    conversation_id: Option<i64>,
+) -> Result<PendingCoachAction, String> {
+    training_plan::propose(conversation_id, PlanChange::ScheduleGameReview { game_id, title: None, due_date, note })
+}
+
+/// Propose a question/answer flashcard, optionally on a position
+#[tauri::command]
+pub fn propose_flashcard(
+    front: String,
+    back: String,
+    fen: Option<String>,
+    conversation_id: Option<i64>,
+) -> Result<PendingCoachAction, String> {
+    training_plan::propose(conversation_id, PlanChange::CreateFlashcard { front, back, fen })
+}
+
+/// Proposals still waiting on the player, from one conversation or all of them
+#[tauri::command]
+pub fn get_pending_coach_actions(conversation_id: Option<i64>) -> Result<Vec<PendingCoachAction>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_pending_coach_actions(conn, profile.id, conversation_id))
+        .map_err(|e| format!("Failed to get pending coach actions: {}", e))
+}
+
+/// Approve (and apply) or decline a proposal
+#[tauri::command]
+pub fn resolve_pending_coach_action(id: i64, approve: bool) -> Result<PendingCoachAction, String> {
+    training_plan::resolve(id, approve)
+}
+
+// ============================================================================
+// Training Plan Commands
+// ============================================================================
+
+/// Open plan items by due date, with completed ones after them when asked for
+#[tauri::command]
+pub fn get_training_plan(include_completed: Option<bool>) -> Result<Vec<PlanEntry>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| {
+        repositories::get_training_plan(conn, profile.id, include_completed.unwrap_or(false))?
+            .into_iter()
+            .map(|item| {
+                let action = training_plan::item_action(conn, &item)?;
+                Ok(PlanEntry { item, action })
+            })
+            .collect()
+    })
+    .map_err(|e| format!("Failed to get training plan: {}", e))
+}
+
+#[tauri::command]
+pub fn complete_training_plan_item(id: i64) -> Result<bool, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::complete_training_plan_item(conn, profile.id, id))
+        .map_err(|e| format!("Failed to update training plan: {}", e))
+}
+
+#[tauri::command]
+pub fn delete_training_plan_item(id: i64) -> Result<bool, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::delete_training_plan_item(conn, profile.id, id))
+        .map_err(|e| format!("Failed to update training plan: {}", e))
+}
+
+/// Clear every open item from the plan, undoable with `undo_last_operation` for a
+/// short while. `None` when the plan was already empty.
+#[tauri::command]
+pub fn reset_training_plan() -> Result<Option<OperationRecord>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| operation_journal::reset_plan(conn, profile.id))
+        .map_err(|e| format!("Failed to reset training plan: {}", e))
+}
+
+#[tauri::command]
+pub fn get_flashcards() -> Result<Vec<Flashcard>, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_flashcards(conn, profile.id))
+        .map_err(|e| format!("Failed to get flashcards: {}", e))
+}
+
+#[tauri::command]
+pub fn delete_flashcard(id: i64) -> Result<bool, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::delete_flashcard(conn, profile.id, id))
+        .map_err(|e| format!("Failed to delete flashcard: {}", e))
+}
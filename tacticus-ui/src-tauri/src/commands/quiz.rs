@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use crate::DB;
+use crate::database::repositories::{self, ConceptMastery, QuizQuestion};
+use crate::game_quiz::{self, QuizAnswer, QuizPosition};
+use crate::usage_analytics;
+use super::training::parse_phase;
+
+/// A quiz question as sent to the UI, without the answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuizQuestionView {
+    pub id: i64,
+    pub question_type: String,
+    pub prompt: String,
+    pub fen: Option<String>,
+    pub choices: Vec<String>,
+}
+
+impl From<QuizQuestion> for QuizQuestionView {
+    fn from(q: QuizQuestion) -> Self {
+        QuizQuestionView {
+            id: q.id,
+            question_type: q.question_type,
+            prompt: q.prompt,
+            fen: q.fen,
+            choices: q.choices,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConceptQuiz {
+    pub session_id: i64,
+    pub concept_id: String,
+    pub questions: Vec<QuizQuestionView>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuizAnswerResult {
+    pub correct: bool,
+    pub correct_answer: String,
+    pub explanation: String,
+    pub score: i32,
+    pub answered: i32,
+    pub total_questions: i32,
+    pub quiz_complete: bool,
+    pub mastery: ConceptMastery,
+}
+
+/// (concept, type, prompt, fen, choices, answer, explanation)
+type QuestionSeed = (&'static str, &'static str, &'static str, Option<&'static str>, &'static [&'static str], &'static str, &'static str);
+
+/// Built-in question bank, written to the database on first use
+const DEFAULT_QUESTIONS: &[QuestionSeed] = &[
+    ("fork", "multiple_choice", "What is a fork?", None,
+        &["Two pieces defending each other", "One piece attacking two or more enemy pieces", "A pawn reaching the last rank", "A piece that cannot move"],
+        "One piece attacking two or more enemy pieces",
+        "A fork attacks several targets at once, so the opponent can usually only save one."),
+    ("fork", "move", "Play the knight move that forks king and rook.", Some("r3k3/8/8/3N4/8/8/8/4K3 w - - 0 1"), &[],
+        "d5c7",
+        "Nc7+ checks the king on e8 and attacks the rook on a8 at the same time."),
+    ("knight_fork", "multiple_choice", "Why are knight forks hard to see coming?", None,
+        &["Knights move in an L-shape and jump over pieces", "Knights can move backwards", "Knights are worth more than bishops", "Knights can only capture forwards"],
+        "Knights move in an L-shape and jump over pieces",
+        "The knight's L-shaped jump doesn't line up with the straight lines players usually scan."),
+    ("pin", "square", "Click the piece that is pinned.", Some("4k3/8/2n5/1B6/8/8/8/4K3 w - - 0 1"), &[],
+        "c6",
+        "The bishop on b5 pins the knight on c6 to the king on e8, so the knight cannot legally move."),
+    ("pin", "multiple_choice", "In an absolute pin, what stands behind the pinned piece?", None,
+        &["The queen", "A rook", "The king", "Any undefended piece"],
+        "The king",
+        "When the king is behind, moving the pinned piece would be illegal - that is an absolute pin."),
+    ("skewer", "multiple_choice", "How does a skewer differ from a pin?", None,
+        &["The more valuable piece is in front", "It only works with knights", "The attacked pieces are on different lines", "It always gives check"],
+        "The more valuable piece is in front",
+        "In a skewer the valuable piece is attacked first and must move, exposing the piece behind it."),
+    ("back_rank_mate", "move", "Deliver checkmate.", Some("6k1/5ppp/8/8/8/8/5PPP/4R1K1 w - - 0 1"), &[],
+        "e1e8",
+        "Re8# - the king's own pawns take away every escape square."),
+    ("back_rank_mate", "multiple_choice", "What is the usual cure for a back-rank weakness?", None,
+        &["Trade queens", "Move a pawn in front of the king to make an escape square", "Castle queenside", "Keep both rooks on the seventh rank"],
+        "Move a pawn in front of the king to make an escape square",
+        "Making 'luft' with a move like h3 or h6 gives the king a flight square."),
+    ("discovered_attack", "multiple_choice", "What makes a discovered attack possible?", None,
+        &["A piece moves out of the way of another piece's line", "Two pawns side by side", "The king in the center", "A passed pawn"],
+        "A piece moves out of the way of another piece's line",
+        "Moving the front piece uncovers an attack by the piece behind, so two threats appear at once."),
+    ("opening_principles", "multiple_choice", "Which is a core opening principle?", None,
+        &["Move the same piece several times", "Bring the queen out early", "Control the center and develop pieces", "Push the rook pawns first"],
+        "Control the center and develop pieces",
+        "Central control and quick development give your pieces the most activity."),
+    ("castling", "multiple_choice", "Which of these is NOT required to castle?", None,
+        &["The king is not in check", "The king has not moved", "The queen has been developed", "The squares between king and rook are empty"],
+        "The queen has been developed",
+        "Castling needs an unmoved king and rook, empty squares between them and no check on the path - the queen is irrelevant."),
+    ("opposition", "multiple_choice", "Kings are in direct opposition when they are...", None,
+        &["On the same diagonal", "On the same file or rank with one square between them", "On opposite sides of the board", "Both on the back rank"],
+        "On the same file or rank with one square between them",
+        "With one square between them, the side NOT to move has the opposition."),
+    ("zugzwang", "multiple_choice", "What does zugzwang mean?", None,
+        &["A draw by repetition", "A forced checkmate", "Any move you make worsens your position", "Trading all the pieces"],
+        "Any move you make worsens your position",
+        "In zugzwang you would rather pass, but chess forces you to move."),
+];
+
+fn ensure_question_bank(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    if repositories::count_quiz_questions(conn)? > 0 {
+        return Ok(());
+    }
+
+    for (concept_id, question_type, prompt, fen, choices, answer, explanation) in DEFAULT_QUESTIONS {
+        repositories::insert_quiz_question(conn, &QuizQuestion {
+            id: 0,
+            concept_id: concept_id.to_string(),
+            question_type: question_type.to_string(),
+            prompt: prompt.to_string(),
+            fen: fen.map(|f| f.to_string()),
+            choices: choices.iter().map(|c| c.to_string()).collect(),
+            answer: answer.to_string(),
+            explanation: explanation.to_string(),
+        })?;
+    }
+
+    Ok(())
+}
+
+fn is_correct_answer(question: &QuizQuestion, answer: &str) -> bool {
+    answer.trim().eq_ignore_ascii_case(question.answer.trim())
+}
+
+#[tauri::command]
+pub fn start_concept_quiz(concept_id: String, count: i32) -> Result<ConceptQuiz, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    let questions = DB
+        .with_conn(|conn| {
+            ensure_question_bank(conn)?;
+            repositories::get_quiz_questions(conn, &concept_id, count)
+        })
+        .map_err(|e| format!("Failed to load quiz questions: {}", e))?;
+
+    if questions.is_empty() {
+        return Err(format!("No quiz questions for concept: {}", concept_id));
+    }
+
+    let session_id = DB
+        .with_conn(|conn| repositories::create_quiz_session(conn, profile.id, &concept_id, questions.len() as i32))
+        .map_err(|e| format!("Failed to start quiz: {}", e))?;
+
+    Ok(ConceptQuiz {
+        session_id,
+        concept_id,
+        questions: questions.into_iter().map(QuizQuestionView::from).collect(),
+    })
+}
+
+/// Answers are the choice text for multiple choice, a square name ("e4") or a UCI move ("e2e4")
+#[tauri::command]
+pub fn submit_quiz_answer(session_id: i64, question_id: i64, answer: String) -> Result<QuizAnswerResult, String> {
+    let session = DB
+        .with_conn(|conn| repositories::get_quiz_session(conn, session_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Quiz session not found".to_string())?;
+
+    let question = DB
+        .with_conn(|conn| repositories::get_quiz_question(conn, question_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Quiz question not found".to_string())?;
+
+    if question.concept_id != session.concept_id {
+        return Err("Question does not belong to this quiz".to_string());
+    }
+
+    let correct = is_correct_answer(&question, &answer);
+
+    let recorded = DB
+        .with_conn(|conn| repositories::record_quiz_answer(conn, session_id, question_id, &answer, correct))
+        .map_err(|e| format!("Failed to record answer: {}", e))?;
+
+    if !recorded {
+        return Err("Question already answered".to_string());
+    }
+    usage_analytics::record_usage(usage_analytics::QUIZ_ANSWERED);
+
+    let mastery = DB
+        .with_conn(|conn| repositories::update_concept_mastery(conn, session.profile_id, &session.concept_id, correct))
+        .map_err(|e| format!("Failed to update mastery: {}", e))?;
+
+    let session = DB
+        .with_conn(|conn| repositories::get_quiz_session(conn, session_id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Quiz session not found".to_string())?;
+
+    Ok(QuizAnswerResult {
+        correct,
+        correct_answer: question.answer,
+        explanation: question.explanation,
+        score: session.score,
+        answered: session.answered,
+        total_questions: session.total_questions,
+        quiz_complete: session.answered >= session.total_questions,
+        mastery,
+    })
+}
+
+#[tauri::command]
+pub fn get_concept_mastery() -> Result<Vec<ConceptMastery>, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| repositories::get_concept_mastery(conn, profile.id))
+        .map_err(|e| format!("Failed to get concept mastery: {}", e))
+}
+
+/// A critical position from the player's recent games for the coach's quiz, without the answer.
+/// `phase` limits it to e.g. endgames from the player's own games.
+#[tauri::command]
+pub fn get_quiz_position(conversation_id: Option<i64>, phase: Option<String>) -> Result<QuizPosition, String> {
+    game_quiz::next_position(conversation_id, parse_phase(phase.as_deref())?)
+}
+
+/// Check a move suggested for a quiz position, recording it as an exercise attempt
+/// and scoring it in the quiz conversation
+#[tauri::command]
+pub fn check_quiz_answer(conversation_id: Option<i64>, game_id: i64, ply: usize, answer: String) -> Result<QuizAnswer, String> {
+    game_quiz::submit_answer(conversation_id, game_id, ply, &answer)
+}
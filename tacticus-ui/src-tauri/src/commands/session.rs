@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use crate::DB;
+use crate::database::repositories;
+
+/// Kinds of in-flight state the UI autosaves
+pub const SNAPSHOT_KINDS: &[&str] = &["game", "training", "coach_draft"];
+
+/// Snapshots older than this are assumed abandoned and removed on startup
+const MAX_SNAPSHOT_AGE_DAYS: i32 = 7;
+
+/// Upper bound on a single snapshot so a runaway payload can't bloat the database
+const MAX_PAYLOAD_BYTES: usize = 512 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverableSession {
+    pub kind: String,
+    pub session_key: String,
+    pub payload: serde_json::Value,
+    pub saved_at: String,
+}
+
+/// Called once at startup. Snapshots are discarded when a session ends normally, so any that
+/// survive to the next launch belong to a session that was interrupted. Timed activities
+/// the interrupted session left running are closed too.
+pub fn init_session_recovery() {
+    match DB.with_conn(repositories::close_open_activities) {
+        Ok(0) => {}
+        Ok(closed) => tracing::info!(closed, "closed activity sessions left open"),
+        Err(e) => tracing::warn!("Failed to close open activity sessions: {}", e),
+    }
+
+    match DB.with_conn(|conn| repositories::delete_stale_session_snapshots(conn, MAX_SNAPSHOT_AGE_DAYS)) {
+        Ok(0) => {}
+        Ok(removed) => tracing::info!(removed, "removed stale session snapshots"),
+        Err(e) => tracing::warn!("Failed to clean up session snapshots: {}", e),
+    }
+
+    if let Ok(snapshots) = DB.with_conn(repositories::get_session_snapshots) {
+        if !snapshots.is_empty() {
+            tracing::info!(count = snapshots.len(), "found interrupted sessions to recover");
+        }
+    }
+}
+
+fn validate_kind(kind: &str) -> Result<(), String> {
+    if SNAPSHOT_KINDS.contains(&kind) {
+        Ok(())
+    } else {
+        Err(format!("Unknown session kind: {}", kind))
+    }
+}
+
+#[tauri::command]
+pub fn save_session_snapshot(kind: String, session_key: String, payload: serde_json::Value) -> Result<(), String> {
+    validate_kind(&kind)?;
+
+    let payload = payload.to_string();
+    if payload.len() > MAX_PAYLOAD_BYTES {
+        return Err(format!("Snapshot too large ({} bytes)", payload.len()));
+    }
+
+    let profile_id = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .ok()
+        .flatten()
+        .map(|p| p.id);
+
+    DB.with_conn(|conn| repositories::upsert_session_snapshot(conn, profile_id, &kind, &session_key, &payload))
+        .map_err(|e| format!("Failed to save session snapshot: {}", e))
+}
+
+/// Drop a snapshot once its session finishes normally or the user declines to restore it
+#[tauri::command]
+pub fn discard_session_snapshot(kind: String, session_key: String) -> Result<bool, String> {
+    DB.with_conn(|conn| repositories::delete_session_snapshot(conn, &kind, &session_key))
+        .map_err(|e| format!("Failed to discard session snapshot: {}", e))
+}
+
+#[tauri::command]
+pub fn get_recoverable_sessions() -> Result<Vec<RecoverableSession>, String> {
+    let snapshots = DB
+        .with_conn(repositories::get_session_snapshots)
+        .map_err(|e| format!("Failed to load session snapshots: {}", e))?;
+
+    Ok(snapshots
+        .into_iter()
+        .filter_map(|s| {
+            // A snapshot that no longer parses can't be restored; skip it rather than fail the lot
+            let payload = serde_json::from_str(&s.payload).ok()?;
+            Some(RecoverableSession {
+                kind: s.kind,
+                session_key: s.session_key,
+                payload,
+                saved_at: s.updated_at,
+            })
+        })
+        .collect())
+}
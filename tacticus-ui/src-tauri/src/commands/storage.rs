@@ -0,0 +1,256 @@
+use chess_core::detect_opening;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use crate::DB;
+use crate::coach_cache;
+use crate::data_wipe::{self, WipeReport, WipeScope};
+use crate::database::repositories::{self, TableSize};
+
+const MAX_CONVERSATIONS_KEY: &str = "storage.max_conversations";
+const ARCHIVE_ANALYSES_DAYS_KEY: &str = "storage.archive_analyses_after_days";
+
+/// Games named per database round trip; other commands get the database between batches
+const DEFAULT_BACKFILL_BATCH: usize = 100;
+const MAX_BACKFILL_BATCH: usize = 1000;
+
+lazy_static! {
+    /// The current or most recent opening backfill
+    static ref OPENING_BACKFILL: Mutex<OpeningBackfillProgress> = Mutex::new(OpeningBackfillProgress::default());
+}
+
+/// Limits applied automatically at startup and by `prune_storage`.
+/// `None` disables the corresponding policy; both are off until the player sets them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruningPolicy {
+    pub max_conversations: Option<i64>,
+    pub archive_analyses_after_days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageReport {
+    pub database_bytes: i64,
+    pub tables: Vec<TableSize>,
+    pub policy: PruningPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruneReport {
+    pub conversations_deleted: usize,
+    pub analyses_archived: usize,
+    pub cached_responses_expired: usize,
+}
+
+/// How far the opening backfill has got. Games that match no known opening, or
+/// started from a custom position, count as unmatched and stay unnamed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpeningBackfillProgress {
+    pub running: bool,
+    /// Unnamed games when the backfill started
+    pub total: usize,
+    pub processed: usize,
+    pub named: usize,
+    pub unmatched: usize,
+    pub error: Option<String>,
+}
+
+/// A stored limit: missing or "off" means disabled, so nothing is pruned unless the player asked for it
+fn read_limit<T: std::str::FromStr>(key: &str) -> Option<T> {
+    DB.with_conn(|conn| repositories::get_setting(conn, key))
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+}
+
+fn write_limit<T: ToString>(key: &str, value: Option<T>) -> Result<(), String> {
+    let value = value.map(|v| v.to_string()).unwrap_or_else(|| "off".to_string());
+    DB.with_conn(|conn| repositories::set_setting(conn, key, &value))
+        .map_err(|e| format!("Failed to save storage policy: {}", e))
+}
+
+fn load_policy() -> PruningPolicy {
+    PruningPolicy {
+        max_conversations: read_limit(MAX_CONVERSATIONS_KEY),
+        archive_analyses_after_days: read_limit(ARCHIVE_ANALYSES_DAYS_KEY),
+    }
+}
+
+fn apply_policy(policy: &PruningPolicy) -> Result<PruneReport, String> {
+    let conversations_deleted = match policy.max_conversations {
+        Some(keep) => DB
+            .with_conn(|conn| repositories::prune_conversations(conn, keep))
+            .map_err(|e| format!("Failed to prune conversations: {}", e))?,
+        None => 0,
+    };
+    let analyses_archived = match policy.archive_analyses_after_days {
+        Some(days) => DB
+            .with_conn(|conn| repositories::archive_old_analyses(conn, days))
+            .map_err(|e| format!("Failed to archive analyses: {}", e))?,
+        None => 0,
+    };
+
+    let cached_responses_expired = DB
+        .with_conn(|conn| repositories::delete_expired_cached_responses(conn, coach_cache::CACHE_TTL_HOURS))
+        .map_err(|e| format!("Failed to expire cached coach responses: {}", e))?;
+
+    Ok(PruneReport { conversations_deleted, analyses_archived, cached_responses_expired })
+}
+
+/// Called once at startup to enforce the pruning policy the player opted into
+pub fn init_storage_maintenance() {
+    match apply_policy(&load_policy()) {
+        Ok(PruneReport { conversations_deleted: 0, analyses_archived: 0, cached_responses_expired: 0 }) => {}
+        Ok(report) => tracing::info!(
+            conversations_deleted = report.conversations_deleted,
+            analyses_archived = report.analyses_archived,
+            cached_responses_expired = report.cached_responses_expired,
+            "pruned stored data"
+        ),
+        Err(e) => tracing::warn!("Storage maintenance failed: {}", e),
+    }
+}
+
+#[tauri::command]
+pub fn get_storage_report() -> Result<StorageReport, String> {
+    let database_bytes = DB
+        .with_conn(repositories::database_size)
+        .map_err(|e| format!("Failed to read database size: {}", e))?;
+    let tables = DB
+        .with_conn(repositories::table_sizes)
+        .map_err(|e| format!("Failed to read table sizes: {}", e))?;
+
+    Ok(StorageReport {
+        database_bytes,
+        tables,
+        policy: load_policy(),
+    })
+}
+
+/// Compact the database file. Returns the number of bytes reclaimed.
+#[tauri::command]
+pub fn vacuum_database() -> Result<i64, String> {
+    let reclaimed = DB
+        .with_conn(repositories::vacuum)
+        .map_err(|e| format!("Failed to vacuum database: {}", e))?;
+    tracing::info!(reclaimed, "database vacuumed");
+    Ok(reclaimed)
+}
+
+/// Result rows of SQLite's integrity check; `["ok"]` means the database is healthy
+#[tauri::command]
+pub fn check_database_integrity() -> Result<Vec<String>, String> {
+    DB.with_conn(repositories::integrity_check)
+        .map_err(|e| format!("Failed to check database integrity: {}", e))
+}
+
+#[tauri::command]
+pub fn set_pruning_policy(policy: PruningPolicy) -> Result<PruningPolicy, String> {
+    if policy.max_conversations.is_some_and(|n| n < 1) {
+        return Err("Keep at least one conversation".to_string());
+    }
+    if policy.archive_analyses_after_days.is_some_and(|d| d < 1) {
+        return Err("Archive age must be at least one day".to_string());
+    }
+
+    write_limit(MAX_CONVERSATIONS_KEY, policy.max_conversations)?;
+    write_limit(ARCHIVE_ANALYSES_DAYS_KEY, policy.archive_analyses_after_days)?;
+    Ok(load_policy())
+}
+
+/// Apply the pruning policy now instead of waiting for the next launch
+#[tauri::command]
+pub fn prune_storage() -> Result<PruneReport, String> {
+    apply_policy(&load_policy())
+}
+
+/// First step of a wipe: a token to pass to the matching wipe command within a minute,
+/// so a single stray call can never delete anything
+#[tauri::command]
+pub fn request_wipe_token(scope: WipeScope) -> String {
+    data_wipe::issue_token(scope)
+}
+
+/// Delete everything Tacticus stores on this machine: the database, the saved API key,
+/// config files, exports, logs and caches. The app starts over as a fresh install.
+#[tauri::command]
+pub fn wipe_all_data(confirm_token: String) -> Result<WipeReport, String> {
+    data_wipe::wipe(WipeScope::All, &confirm_token)
+}
+
+/// Delete every coach conversation and exported transcript
+#[tauri::command]
+pub fn wipe_conversations(confirm_token: String) -> Result<WipeReport, String> {
+    data_wipe::wipe(WipeScope::Conversations, &confirm_token)
+}
+
+/// Delete every game, its analyses, exported reports and narrations
+#[tauri::command]
+pub fn wipe_games(confirm_token: String) -> Result<WipeReport, String> {
+    data_wipe::wipe(WipeScope::Games, &confirm_token)
+}
+
+/// Name the openings of stored games that have none, in the background.
+/// Poll `get_opening_backfill_progress` for progress.
+#[tauri::command]
+pub fn start_opening_backfill(batch_size: Option<usize>) -> Result<OpeningBackfillProgress, String> {
+    let batch_size = batch_size.unwrap_or(DEFAULT_BACKFILL_BATCH).clamp(1, MAX_BACKFILL_BATCH);
+    let total = DB
+        .with_conn(repositories::count_games_missing_opening)
+        .map_err(|e| format!("Failed to count games: {}", e))?;
+
+    let mut progress = OPENING_BACKFILL.lock().map_err(|_| "Backfill lock poisoned".to_string())?;
+    if progress.running {
+        return Err("An opening backfill is already running".to_string());
+    }
+    *progress = OpeningBackfillProgress {
+        running: true,
+        total: total as usize,
+        ..Default::default()
+    };
+    let started = progress.clone();
+    drop(progress);
+
+    std::thread::spawn(move || {
+        let error = run_opening_backfill(batch_size).err();
+        if let Ok(mut progress) = OPENING_BACKFILL.lock() {
+            progress.running = false;
+            progress.error = error;
+            tracing::info!(named = progress.named, unmatched = progress.unmatched, "opening backfill finished");
+        }
+    });
+
+    Ok(started)
+}
+
+#[tauri::command]
+pub fn get_opening_backfill_progress() -> Result<OpeningBackfillProgress, String> {
+    OPENING_BACKFILL
+        .lock()
+        .map(|p| p.clone())
+        .map_err(|_| "Backfill lock poisoned".to_string())
+}
+
+fn run_opening_backfill(batch_size: usize) -> Result<(), String> {
+    let mut after_id = 0;
+    loop {
+        let games = DB
+            .with_conn(|conn| repositories::get_games_missing_opening(conn, after_id, batch_size as i64))
+            .map_err(|e| format!("Failed to load games: {}", e))?;
+        let Some(last) = games.last() else {
+            return Ok(());
+        };
+        after_id = last.id;
+
+        let openings: Vec<(i64, String)> = games
+            .iter()
+            .filter_map(|g| detect_opening(&g.initial_fen, &g.moves).map(|o| (g.id, o.name.to_string())))
+            .collect();
+        let named = DB
+            .with_conn(|conn| repositories::set_game_openings(conn, &openings))
+            .map_err(|e| format!("Failed to save openings: {}", e))?;
+
+        let mut progress = OPENING_BACKFILL.lock().map_err(|_| "Backfill lock poisoned".to_string())?;
+        progress.processed += games.len();
+        progress.named += named;
+        progress.unmatched += games.len() - openings.len();
+    }
+}
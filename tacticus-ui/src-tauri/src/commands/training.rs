@@ -1,5 +1,35 @@
-use chess_trainer::{Exercise, ExerciseLibrary, ExerciseDifficulty};
+use chess_core::{parse_fen, parse_move, to_san, GamePhase};
+use chess_engine::{DefenseFinder, MatePattern, MatePatternFinder, PatternSighting};
+use chess_trainer::{select_hint, AttemptContext, Exercise, ExerciseLibrary, ExerciseDifficulty, ExerciseType, LineProgress, LineStep, SolutionChecker, SolutionMatch, WrongMove};
+use chrono::Datelike;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::DB;
+use crate::app_config;
+use crate::conversation_mode;
+use crate::daily_workout::{self, DailyWorkout, WorkoutInputs};
+use crate::database::repositories::{self, ExercisePostMortem};
+use crate::encouragement;
+use crate::exercise_postmortem;
+use crate::exercise_queue;
+use crate::kid_mode;
+use crate::mate_drills;
+use crate::position_library;
+use crate::session_seed;
+use crate::usage_analytics;
+use super::learning::available_concepts;
+use super::model_games::get_model_games;
+use super::onboarding::is_solution;
+
+/// Defense drills accept any move within this much resilience of the most stubborn one
+const DEFENSE_TOLERANCE: i32 = 50;
+
+lazy_static! {
+    /// How far the player is through each multi-move puzzle they are in the middle of,
+    /// by exercise id. Kept here so the frontend can't skip ahead in a line.
+    static ref LINE_PROGRESS: Mutex<HashMap<usize, LineProgress>> = Mutex::new(HashMap::new());
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExerciseData {
@@ -11,6 +41,10 @@ pub struct ExerciseData {
     pub fen: String,
     pub hints: Vec<String>,
     pub solution_moves: Vec<String>,
+    pub time_limit_seconds: u32,
+    pub phase: String,
+    /// Moves the player must find; more than one for puzzles played out against replies
+    pub moves_to_find: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,89 +59,443 @@ pub struct ExerciseResult {
     pub correct: bool,
     pub explanation: String,
     pub correct_move: Option<String>,
+    /// Whether the stored solution or an engine-approved alternative was played
+    pub solution_match: SolutionMatch,
+    /// The opponent's answer in UCI when a multi-move puzzle goes on after a right move
+    pub reply: Option<String>,
+    /// Position after `reply`, where the player finds the next move
+    pub fen: Option<String>,
 }
 
-fn exercise_to_data(exercise: &Exercise, id: usize) -> ExerciseData {
+/// Base allowance for timed modes, scaled up in kid mode
+fn base_time_limit(difficulty: &ExerciseDifficulty) -> u32 {
+    match difficulty {
+        ExerciseDifficulty::Beginner => 60,
+        ExerciseDifficulty::Intermediate => 90,
+        ExerciseDifficulty::Advanced => 120,
+        ExerciseDifficulty::Expert => 180,
+    }
+}
+
+pub(crate) fn exercise_to_data(exercise: &Exercise, id: usize, kid_mode: bool) -> ExerciseData {
     ExerciseData {
         id,
         title: exercise.title.clone(),
         description: exercise.description.clone(),
-        difficulty: match exercise.difficulty {
-            ExerciseDifficulty::Beginner => "Beginner".to_string(),
-            ExerciseDifficulty::Intermediate => "Intermediate".to_string(),
-            ExerciseDifficulty::Advanced => "Advanced".to_string(),
-            ExerciseDifficulty::Expert => "Expert".to_string(),
-        },
-        exercise_type: format!("{:?}", exercise.exercise_type),
+        difficulty: exercise.difficulty.to_string(),
+        exercise_type: exercise.exercise_type.to_string(),
         fen: exercise.position.clone(),
         hints: exercise.hints.clone(),
         solution_moves: exercise.solution_moves.clone(),
+        time_limit_seconds: kid_mode::time_allowance(base_time_limit(&exercise.difficulty), kid_mode),
+        phase: exercise.phase.to_string(),
+        moves_to_find: exercise.moves_to_find(),
     }
 }
 
+/// A phase filter from the frontend; `None` or an empty string means every phase
+pub(crate) fn parse_phase(phase: Option<&str>) -> Result<Option<GamePhase>, String> {
+    match phase.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(name) => name.parse().map(Some).map_err(|e: chess_core::ChessError| e.to_string()),
+    }
+}
+
+fn in_phase(exercise: &Exercise, phase: Option<GamePhase>) -> bool {
+    phase.is_none_or(|p| exercise.phase == p)
+}
+
+/// Up to `count` of the active profile's pre-generated exercises that pass `keep`,
+/// taken out of the ready queue
+fn take_queued(count: usize, keep: impl Fn(&Exercise) -> bool) -> Vec<(usize, Exercise)> {
+    let taken = DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => exercise_queue::take(conn, profile.id, count, keep),
+        None => Ok(vec![]),
+    });
+    taken.unwrap_or_else(|e| {
+        tracing::warn!("failed to take pre-generated exercises: {}", e);
+        vec![]
+    })
+}
+
+/// `phase` narrows the session to one game phase, e.g. "Endgame"
 #[tauri::command]
-pub fn get_training_exercises(count: usize, _user_elo: i32, weaknesses: Vec<String>) -> TrainingSessionData {
+pub fn get_training_exercises(count: usize, _user_elo: i32, weaknesses: Vec<String>, phase: Option<String>) -> Result<TrainingSessionData, String> {
+    let phase = parse_phase(phase.as_deref())?;
     // Get all exercises
     let all_exercises = ExerciseLibrary::get_all_exercises();
     
     // For now, just return the first N exercises
     // Later this will be adaptive based on user weaknesses and ELO
-    let exercises: Vec<ExerciseData> = all_exercises
+    // Ids stay as library indices so kid mode filtering doesn't break lookups
+    let kid_mode = kid_mode::active();
+    // Weaknesses naming an exercise type (e.g. a coach "train endgames" button) narrow the session to it
+    let themes: Vec<ExerciseType> = weaknesses.iter().filter_map(|w| w.parse().ok()).collect();
+    let on_theme = |e: &Exercise| themes.is_empty() || themes.contains(&e.exercise_type);
+    let allowed = |e: &Exercise| in_phase(e, phase) && (!kid_mode || kid_mode::allows_exercise(&e.difficulty));
+    let themed = all_exercises.iter().any(|e| on_theme(e) && allowed(e));
+    let mut candidates: Vec<(usize, &Exercise)> = all_exercises
         .iter()
-        .take(count)
         .enumerate()
-        .map(|(i, e)| exercise_to_data(e, i))
+        .filter(|(_, e)| allowed(e))
+        .filter(|(_, e)| !themed || on_theme(e))
+        .collect();
+    // Exercises pre-generated from the player's games come first; the library tops the session up
+    let mut queued = take_queued(count, |e| allowed(e) && (!themed || on_theme(e)));
+    // Easier first while the player is struggling, harder first on a streak; stable otherwise
+    let shift = encouragement::active().difficulty_shift;
+    queued.sort_by_key(|(_, e)| encouragement::difficulty_order(&e.difficulty, shift));
+    candidates.sort_by_key(|(_, e)| encouragement::difficulty_order(&e.difficulty, shift));
+    let exercises: Vec<ExerciseData> = queued
+        .iter()
+        .map(|(i, e)| exercise_to_data(e, *i, kid_mode))
+        .chain(candidates.into_iter().take(count - queued.len()).map(|(i, e)| exercise_to_data(e, i, kid_mode)))
         .collect();
     
-    let focus_areas = if weaknesses.is_empty() {
+    let mut focus_areas = if weaknesses.is_empty() {
         vec!["General tactics".to_string(), "Pattern recognition".to_string()]
     } else {
         weaknesses
     };
+    if let Some(phase) = phase {
+        focus_areas.push(phase.to_string());
+    }
     
-    TrainingSessionData {
+    Ok(TrainingSessionData {
         total_exercises: exercises.len(),
         exercises,
         focus_areas,
+    })
+}
+
+/// A session drilling one exercise theme: positions the player got wrong last
+/// time first, then ones they have not tried, then ones they solved. `phase`
+/// narrows it to one game phase.
+#[tauri::command]
+pub fn generate_theme_drill(exercise_type: String, count: usize, phase: Option<String>) -> Result<TrainingSessionData, String> {
+    let theme: ExerciseType = exercise_type.parse().map_err(|e: chess_trainer::ParseNameError| e.to_string())?;
+    let phase = parse_phase(phase.as_deref())?;
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+    let latest = DB
+        .with_conn(|conn| repositories::get_latest_position_results(conn, profile.id, theme.as_str()))
+        .map_err(|e| format!("Failed to get exercise history: {}", e))?;
+
+    let kid_mode = kid_mode::active();
+    let all_exercises = ExerciseLibrary::get_all_exercises();
+    let mut candidates: Vec<(usize, &Exercise)> = all_exercises
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.exercise_type == theme && in_phase(e, phase) && (!kid_mode || kid_mode::allows_exercise(&e.difficulty)))
+        .collect();
+    if candidates.is_empty() {
+        return Err(match phase {
+            Some(phase) => format!("No {} exercises available in the {}", theme, phase.as_str().to_lowercase()),
+            None => format!("No {} exercises available", theme),
+        });
     }
+    // Stable, so library order breaks ties
+    candidates.sort_by_key(|(_, e)| match latest.get(&e.position) {
+        Some(false) => 0,
+        None => 1,
+        Some(true) => 2,
+    });
+    let exercises: Vec<ExerciseData> = candidates
+        .into_iter()
+        .take(count)
+        .map(|(i, e)| exercise_to_data(e, i, kid_mode))
+        .collect();
+
+    let mut focus_areas = vec![theme.to_string()];
+    focus_areas.extend(phase.map(|p| p.to_string()));
+    Ok(TrainingSessionData {
+        total_exercises: exercises.len(),
+        exercises,
+        focus_areas,
+    })
+}
+
+/// Whether `user_move` defends about as stubbornly as the engine's best defensive try
+fn holds_defense(exercise: &Exercise, user_move: &str) -> SolutionMatch {
+    if is_solution(exercise, user_move) {
+        return SolutionMatch::Canonical;
+    }
+    let Ok(board) = exercise.get_board() else {
+        return SolutionMatch::Wrong;
+    };
+    let Ok(chess_move) = parse_move(&board, user_move) else {
+        return SolutionMatch::Wrong;
+    };
+    let ranked = DefenseFinder::rank_defenses(&board);
+    let uci = chess_move.to_string();
+    match (ranked.first(), ranked.iter().find(|r| r.uci == uci)) {
+        (Some(best), Some(played)) if played.resilience >= best.resilience - DEFENSE_TOLERANCE => SolutionMatch::Alternative,
+        _ => SolutionMatch::Wrong,
+    }
+}
+
+/// The first stored solution in SAN, however the exercise wrote it
+fn solution_san(exercise: &Exercise) -> Option<String> {
+    let solution = exercise.solution_moves.first()?;
+    let board = exercise.get_board().ok()?;
+    Some(parse_move(&board, solution).map(|m| to_san(&board, m)).unwrap_or_else(|_| solution.clone()))
 }
 
+/// Count a wrong answer towards unlocking the solution in coach help for this exercise
+fn record_help_attempt(exercise_id: usize) {
+    let recorded = DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => conversation_mode::record_failed_attempt(conn, profile.id, exercise_id),
+        None => Ok(()),
+    });
+    if let Err(e) = recorded {
+        tracing::warn!("failed to record exercise help attempt: {}", e);
+    }
+}
+
+/// A library exercise by index, or one built from a bookmark or game of the active profile
+fn find_exercise(exercise_id: usize) -> Option<Exercise> {
+    let from_profile = position_library::position_id(exercise_id).is_some()
+        || exercise_queue::is_queue_id(exercise_id)
+        || mate_drills::is_drill_id(exercise_id);
+    if !from_profile {
+        return ExerciseLibrary::get_all_exercises().get(exercise_id).cloned();
+    }
+    let found = DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => position_library::find_exercise(conn, profile.id, exercise_id),
+        None => Ok(None),
+    });
+    found.unwrap_or_else(|e| {
+        tracing::warn!("failed to load profile exercise: {}", e);
+        None
+    })
+}
+
+/// Play `user_move` on the line of multi-move puzzle `exercise_id` from where the
+/// player left it. A wrong move or the last one ends the attempt, so the next
+/// check starts the line over.
+fn play_line(exercise_id: usize, exercise: &Exercise, user_move: &str) -> Result<LineStep, String> {
+    let mut lines = LINE_PROGRESS.lock().map_err(|_| "Puzzle progress is unavailable".to_string())?;
+    let step = lines.entry(exercise_id).or_default().play(exercise, user_move);
+    if !matches!(step, Ok(LineStep::Continue { .. })) {
+        lines.remove(&exercise_id);
+    }
+    step
+}
+
+fn check_line_move(exercise_id: usize, exercise: &Exercise, user_move: &str) -> ExerciseResult {
+    let step = play_line(exercise_id, exercise, user_move).unwrap_or_else(|e| {
+        tracing::warn!(exercise_id, "could not play the puzzle line: {}", e);
+        LineStep::Deviated { expected: exercise.solution_line.first().cloned().unwrap_or_default() }
+    });
+    if !matches!(step, LineStep::Continue { .. }) {
+        usage_analytics::record_usage(usage_analytics::PUZZLE_ATTEMPTED);
+    }
+    match step {
+        LineStep::Continue { reply, reply_san, fen } => ExerciseResult {
+            correct: true,
+            explanation: format!("Good move. Your opponent answers {}; keep going.", reply_san),
+            correct_move: None,
+            solution_match: SolutionMatch::Canonical,
+            reply: Some(reply),
+            fen: Some(fen),
+        },
+        LineStep::Solved => ExerciseResult {
+            correct: true,
+            explanation: exercise.explanation.clone(),
+            correct_move: None,
+            solution_match: SolutionMatch::Canonical,
+            reply: None,
+            fen: None,
+        },
+        LineStep::Deviated { expected } => {
+            record_help_attempt(exercise_id);
+            ExerciseResult {
+                correct: false,
+                explanation: format!("Not quite! {}", exercise.hints.first().unwrap_or(&"Try again.".to_string())),
+                correct_move: Some(expected),
+                solution_match: SolutionMatch::Wrong,
+                reply: None,
+                fen: None,
+            }
+        }
+    }
+}
+
+/// Start multi-move puzzle `exercise_id` over from its first move, as on a retry
+#[tauri::command]
+pub fn restart_exercise_line(exercise_id: usize) -> Result<(), String> {
+    LINE_PROGRESS
+        .lock()
+        .map_err(|_| "Puzzle progress is unavailable".to_string())?
+        .remove(&exercise_id);
+    Ok(())
+}
+
+/// Check a move at exercise `exercise_id`. Multi-move puzzles answer a right move
+/// with the opponent's reply and the position to continue from, until the line ends.
 #[tauri::command]
 pub fn check_exercise_solution(exercise_id: usize, user_move: String) -> ExerciseResult {
-    let all_exercises = ExerciseLibrary::get_all_exercises();
-    
-    if let Some(exercise) = all_exercises.get(exercise_id) {
-        let is_correct = exercise.check_solution(&user_move);
+    if let Some(exercise) = find_exercise(exercise_id) {
+        if exercise.is_multi_move() {
+            return check_line_move(exercise_id, &exercise, &user_move);
+        }
+        let solution_match = if exercise.exercise_type == ExerciseType::Defense {
+            holds_defense(&exercise, &user_move)
+        } else {
+            SolutionChecker::new(app_config::current().engine.solution_margin).check(&exercise, &user_move)
+        };
+        let is_correct = solution_match.is_correct();
+        usage_analytics::record_usage(usage_analytics::PUZZLE_ATTEMPTED);
+        if !is_correct {
+            record_help_attempt(exercise_id);
+        }
         
         ExerciseResult {
             correct: is_correct,
-            explanation: if is_correct {
-                exercise.explanation.clone()
-            } else {
-                format!("Not quite! {}", exercise.hints.first().unwrap_or(&"Try again.".to_string()))
+            explanation: match (solution_match, solution_san(&exercise)) {
+                (SolutionMatch::Alternative, Some(solution)) => {
+                    format!("That works too! The move we had in mind was {}. {}", solution, exercise.explanation)
+                }
+                (SolutionMatch::Wrong, _) => {
+                    format!("Not quite! {}", exercise.hints.first().unwrap_or(&"Try again.".to_string()))
+                }
+                _ => exercise.explanation.clone(),
             },
             correct_move: if !is_correct {
                 exercise.solution_moves.first().cloned()
             } else {
                 None
             },
+            solution_match,
+            reply: None,
+            fen: None,
         }
     } else {
         ExerciseResult {
             correct: false,
             explanation: "Exercise not found".to_string(),
             correct_move: None,
+            solution_match: SolutionMatch::Wrong,
+            reply: None,
+            fen: None,
         }
     }
 }
 
+/// Post-mortem card for a finished exercise, saved with its recorded result: the
+/// exercise's explanation, the engine's refutation of each wrong try and, unless it was
+/// a clean solve, the coach's elaboration. `wrong_moves` are the tries, oldest first,
+/// each with the position it was played from; `assisted` is whether hints were used.
 #[tauri::command]
-pub fn get_exercise_hint(exercise_id: usize, hint_index: usize) -> Option<String> {
-    let all_exercises = ExerciseLibrary::get_all_exercises();
-    
-    all_exercises
-        .get(exercise_id)
-        .and_then(|e| e.hints.get(hint_index).cloned())
+pub async fn build_exercise_postmortem(
+    result_id: i64,
+    exercise_id: usize,
+    solved: bool,
+    assisted: Option<bool>,
+    wrong_moves: Vec<WrongMove>,
+) -> Result<ExercisePostMortem, String> {
+    let exercise = find_exercise(exercise_id).ok_or_else(|| "Exercise not found".to_string())?;
+    let postmortem =
+        exercise_postmortem::build(&exercise, result_id, solved, assisted.unwrap_or(false), &wrong_moves).await;
+    DB.with_conn(|conn| repositories::save_exercise_postmortem(conn, &postmortem))
+        .map_err(|e| format!("Failed to save post-mortem: {}", e))?;
+    Ok(postmortem)
+}
+
+/// The saved card for a recorded result, when one was built
+#[tauri::command]
+pub fn get_exercise_postmortem(result_id: i64) -> Result<Option<ExercisePostMortem>, String> {
+    DB.with_conn(|conn| repositories::get_exercise_postmortem(conn, result_id))
+        .map_err(|e| format!("Failed to get post-mortem: {}", e))
+}
+
+/// The next hint, aimed at the wrong moves tried so far; the longer the player has
+/// been stuck, the further past the written hints it may go
+#[tauri::command]
+pub fn get_exercise_hint(
+    exercise_id: usize,
+    hint_index: usize,
+    wrong_moves: Option<Vec<WrongMove>>,
+    elapsed_seconds: Option<u32>,
+) -> Option<String> {
+    let context = AttemptContext { wrong_moves: wrong_moves.unwrap_or_default(), elapsed_seconds: elapsed_seconds.unwrap_or(0) };
+    find_exercise(exercise_id).and_then(|e| select_hint(&e, hint_index, &context))
+}
+
+/// User input from the frontend; pauses idle-time exercise generation
+#[tauri::command]
+pub fn report_user_activity() {
+    exercise_queue::mark_active();
+}
+
+/// A one-exercise session on a bookmarked position: find the engine's best move
+#[tauri::command]
+pub fn start_bookmark_exercise(position_id: i64) -> Result<TrainingSessionData, String> {
+    let exercise_id = position_library::exercise_id(position_id);
+    let exercise = find_exercise(exercise_id).ok_or_else(|| "Bookmarked position not found".to_string())?;
+    Ok(TrainingSessionData {
+        exercises: vec![exercise_to_data(&exercise, exercise_id, kid_mode::active())],
+        focus_areas: vec!["Bookmarked position".to_string()],
+        total_exercises: 1,
+    })
+}
+
+/// A session on exactly `exercise_ids`, e.g. a daily workout item, under `focus`.
+/// Ids no longer found are skipped.
+#[tauri::command]
+pub fn start_exercise_set(exercise_ids: Vec<usize>, focus: String) -> Result<TrainingSessionData, String> {
+    let kid_mode = kid_mode::active();
+    let exercises: Vec<ExerciseData> = exercise_ids
+        .iter()
+        .filter_map(|&id| find_exercise(id).map(|exercise| exercise_to_data(&exercise, id, kid_mode)))
+        .collect();
+    if exercises.is_empty() {
+        return Err("None of these exercises are available any more".to_string());
+    }
+    Ok(TrainingSessionData {
+        total_exercises: exercises.len(),
+        exercises,
+        focus_areas: vec![focus],
+    })
+}
+
+/// A session drilling checkmate patterns: positions from the player's recent games
+/// where one was on the board, topped up with textbook examples. `pattern` is a
+/// concept id such as "smothered_mate"; without one every pattern is drilled.
+#[tauri::command]
+pub async fn start_mate_pattern_drill(pattern: Option<String>, count: Option<usize>) -> Result<TrainingSessionData, String> {
+    let pattern = match pattern.as_deref() {
+        Some(id) => Some(MatePattern::from_concept_id(id).ok_or_else(|| format!("Unknown checkmate pattern: {}", id))?),
+        None => None,
+    };
+    let count = count.unwrap_or(5).clamp(1, 20);
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    // Scanning games for patterns runs a small search per position
+    let drill = tokio::task::spawn_blocking(move || DB.with_conn(|conn| mate_drills::drill(conn, profile.id, pattern, count)))
+        .await
+        .map_err(|e| format!("Drill generation failed: {}", e))?
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let kid_mode = kid_mode::active();
+    let exercises: Vec<ExerciseData> = drill.iter().map(|(id, exercise)| exercise_to_data(exercise, *id, kid_mode)).collect();
+    Ok(TrainingSessionData {
+        focus_areas: vec![pattern.map_or("Checkmate Patterns", |p| p.name()).to_string()],
+        total_exercises: exercises.len(),
+        exercises,
+    })
+}
+
+/// Checkmate patterns the side to move can play in a position
+#[tauri::command]
+pub fn detect_mate_patterns(fen: String) -> Result<Vec<PatternSighting>, String> {
+    let board = parse_fen(&fen).map_err(|e| e.to_string())?;
+    Ok(MatePatternFinder::find(&board))
 }
 
 #[tauri::command]
@@ -121,5 +509,38 @@ pub fn get_all_exercise_types() -> Vec<String> {
         "Calculation Ladder".to_string(),
         "Endgame Drills".to_string(),
         "Opening Traps".to_string(),
+        "Defend Worse Positions".to_string(),
     ]
 }
+
+/// Today's mixed-mode session built from the profile's weaknesses, concept mastery and
+/// the time they spent studying over the last week
+#[tauri::command]
+pub fn generate_daily_workout() -> Result<DailyWorkout, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+    let mastery = DB
+        .with_conn(|conn| repositories::get_concept_mastery(conn, profile.id))
+        .map_err(|e| format!("Failed to get concept mastery: {}", e))?;
+
+    let study_time = DB
+        .with_conn(|conn| repositories::get_time_breakdown(conn, profile.id, Some(7)))
+        .map_err(|e| format!("Failed to get time breakdown: {}", e))?;
+
+    let concepts: Vec<(String, String)> = available_concepts().into_iter().map(|c| (c.id, c.category)).collect();
+    let games = get_model_games();
+    let today = chrono::Local::now().date_naive();
+
+    Ok(daily_workout::generate_workout(&WorkoutInputs {
+        date: today.format("%Y-%m-%d").to_string(),
+        day: session_seed::workout_day(session_seed::active(), today.ordinal() as usize),
+        weaknesses: &profile.weaknesses,
+        mastery: &mastery,
+        concepts: &concepts,
+        games: &games,
+        kid_mode: kid_mode::active(),
+        daily_minutes: (study_time.total_seconds > 0).then(|| (study_time.total_seconds / 60 / 7) as u32),
+    }))
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use crate::DB;
+use crate::commands::data::period_to_days;
+use crate::database::repositories::{self, DailyUsage, FeatureUsage};
+use crate::usage_analytics;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub enabled: bool,
+    pub period_days: Option<i32>,
+    pub features: Vec<FeatureUsage>,
+    pub daily: Vec<DailyUsage>,
+    pub insights: Vec<String>,
+}
+
+#[tauri::command]
+pub fn get_usage_analytics_enabled() -> Result<bool, String> {
+    DB.with_conn(usage_analytics::is_enabled)
+        .map_err(|e| format!("Failed to read usage analytics setting: {}", e))
+}
+
+/// Opt in to or out of local usage analytics. Opting out deletes the recorded counts.
+#[tauri::command]
+pub fn set_usage_analytics_enabled(enabled: bool) -> Result<(), String> {
+    DB.with_conn(|conn| usage_analytics::set_enabled(conn, enabled))
+        .map_err(|e| format!("Failed to update usage analytics setting: {}", e))
+}
+
+/// Feature usage and habit insights. `period` is one of "day", "week", "month" or "all".
+#[tauri::command]
+pub fn get_usage_stats(period: String) -> Result<UsageStats, String> {
+    let days = period_to_days(&period)?;
+
+    let enabled = DB
+        .with_conn(usage_analytics::is_enabled)
+        .map_err(|e| format!("Failed to read usage analytics setting: {}", e))?;
+    let features = DB
+        .with_conn(|conn| repositories::get_feature_usage(conn, days))
+        .map_err(|e| format!("Failed to get usage stats: {}", e))?;
+    let daily = DB
+        .with_conn(|conn| repositories::get_daily_usage(conn, days))
+        .map_err(|e| format!("Failed to get usage stats: {}", e))?;
+
+    Ok(UsageStats {
+        enabled,
+        period_days: days,
+        insights: usage_analytics::habit_insights(&features, &daily, days),
+        features,
+        daily,
+    })
+}
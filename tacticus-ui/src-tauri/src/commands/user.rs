@@ -1,5 +1,12 @@
 use serde::{Deserialize, Serialize};
 use crate::DB;
+use crate::app_config;
+use crate::attempt_flags;
+use crate::coach_verbosity::{self, Verbosity};
+use crate::encouragement::{self, Encouragement, EncouragementSettings};
+use crate::game_assistance::{self, GameAssistance};
+use crate::kid_mode;
+use crate::prompt_privacy::{self, PromptPrivacy};
 use crate::database::repositories::{self, Profile};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,12 +55,12 @@ pub struct UserStats {
     pub exercises_until_calibration: i32,
 }
 
-// Initialize API key from database or file on startup
+// Move an API key from the old file-based storage into settings on startup.
+// Keys from the environment or .env file are picked up by the config.
 pub fn init_api_key() {
     // First check database
     if let Ok(Some(key)) = DB.with_conn(|conn| repositories::get_setting(conn, "api_key")) {
         if !key.is_empty() {
-            std::env::set_var("OPENROUTER_API_KEY", &key);
             return;
         }
     }
@@ -64,16 +71,14 @@ pub fn init_api_key() {
             if let Ok(key) = std::fs::read_to_string(&path) {
                 let key = key.trim().to_string();
                 if !key.is_empty() {
-                    std::env::set_var("OPENROUTER_API_KEY", &key);
                     // Migrate to database
-                    let _ = DB.with_conn(|conn| repositories::set_setting(conn, "api_key", &key));
+                    if let Err(e) = DB.with_conn(|conn| repositories::set_setting(conn, "api_key", &key)) {
+                        tracing::warn!("Failed to migrate API key to database: {}", e);
+                    }
                 }
             }
         }
     }
-
-    // Check .env file for backwards compatibility
-    dotenv::dotenv().ok();
 }
 
 // Initialize profile from database on startup
@@ -104,7 +109,9 @@ pub fn init_profile() {
                         updated.streak = old_profile["streak"].as_i64().unwrap_or(0) as i32;
                         updated.style = old_profile["style"].as_str().unwrap_or("Unknown").to_string();
 
-                        let _ = DB.with_conn(|conn| repositories::update_profile(conn, &updated));
+                        if let Err(e) = DB.with_conn(|conn| repositories::update_profile(conn, &mut updated)) {
+                            tracing::warn!("Failed to migrate profile details: {}", e);
+                        }
                     }
                 }
             }
@@ -112,20 +119,14 @@ pub fn init_profile() {
     }
 }
 
-#[tauri::command]
-pub fn create_user_profile(name: String, initial_level: String) -> Result<UserProfile, String> {
-    let elo = match initial_level.as_str() {
-        "beginner" => 600,
-        "intermediate" => 1000,
-        "advanced" => 1400,
-        other => other.parse().unwrap_or(800),
-    };
-
-    let profile = DB
-        .with_conn(|conn| repositories::create_profile(conn, &name, &initial_level, elo))
-        .map_err(|e| format!("Failed to create profile: {}", e))?;
-
-    Ok(profile.into())
+/// Message for a failed profile write. A conflict means another command saved
+/// the profile first, so the caller should reload it rather than report a fault.
+pub(crate) fn profile_write_error(error: rusqlite::Error) -> String {
+    if repositories::is_profile_conflict(&error) {
+        "Your profile was changed by another action. Reload it and try again.".to_string()
+    } else {
+        format!("Failed to update profile: {}", error)
+    }
 }
 
 #[tauri::command]
@@ -156,52 +157,180 @@ pub fn get_user_stats() -> Option<UserStats> {
 
 #[tauri::command]
 pub fn update_user_elo(new_elo: i32, game_result: String) -> Result<UserProfile, String> {
-    let mut profile = DB
+    let profile = DB
         .with_conn(|conn| repositories::get_first_profile(conn))
         .map_err(|e| format!("Database error: {}", e))?
         .ok_or_else(|| "No user profile found".to_string())?;
 
-    profile.current_elo = new_elo;
-    profile.peak_elo = profile.peak_elo.max(new_elo);
-    profile.games_played += 1;
+    DB.with_conn(|conn| {
+        repositories::record_game_outcome(conn, profile.id, new_elo, game_result == "win")?;
+        repositories::get_profile_by_id(conn, profile.id)
+    })
+    .map_err(profile_write_error)?
+    .map(|p| p.into())
+    .ok_or_else(|| "No user profile found".to_string())
+}
 
-    if game_result == "win" {
-        profile.streak += 1;
-    } else {
-        profile.streak = 0;
-    }
+#[tauri::command]
+pub fn save_api_key(api_key: String) -> Result<(), String> {
+    // Saved keys take precedence over the environment and config file
+    DB.with_conn(|conn| repositories::set_setting(conn, "api_key", &api_key))
+        .map_err(|e| format!("Failed to save API key: {}", e))
+}
 
-    DB.with_conn(|conn| repositories::update_profile(conn, &profile))
-        .map_err(|e| format!("Failed to update profile: {}", e))?;
+#[tauri::command]
+pub fn get_api_key() -> Option<String> {
+    app_config::current().llm.api_key
+}
 
-    Ok(profile.into())
+#[tauri::command]
+pub fn get_kid_mode() -> Result<bool, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| kid_mode::is_enabled(conn, profile.id))
+        .map_err(|e| format!("Failed to load kid mode: {}", e))
 }
 
 #[tauri::command]
-pub fn save_api_key(api_key: String) -> Result<(), String> {
-    // Save to database
-    DB.with_conn(|conn| repositories::set_setting(conn, "api_key", &api_key))
-        .map_err(|e| format!("Failed to save API key: {}", e))?;
+pub fn set_kid_mode(enabled: bool) -> Result<(), String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| kid_mode::set_enabled(conn, profile.id, enabled))
+        .map_err(|e| format!("Failed to save kid mode: {}", e))
+}
 
-    // Also set as environment variable for immediate use
-    std::env::set_var("OPENROUTER_API_KEY", &api_key);
+/// How much the coach says, for every coach feature
+#[tauri::command]
+pub fn get_coach_verbosity() -> Result<Verbosity, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
 
-    Ok(())
+    DB.with_conn(|conn| coach_verbosity::load(conn, profile.id))
+        .map_err(|e| format!("Failed to load coaching verbosity: {}", e))
 }
 
 #[tauri::command]
-pub fn get_api_key() -> Option<String> {
-    // First check environment
-    if let Ok(key) = std::env::var("OPENROUTER_API_KEY") {
-        if !key.is_empty() {
-            return Some(key);
-        }
-    }
+pub fn set_coach_verbosity(verbosity: Verbosity) -> Result<(), String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
 
-    // Then check database
-    DB.with_conn(|conn| repositories::get_setting(conn, "api_key"))
-        .ok()
-        .flatten()
+    DB.with_conn(|conn| coach_verbosity::save(conn, profile.id, verbosity))
+        .map_err(|e| format!("Failed to save coaching verbosity: {}", e))
+}
+
+/// What coach prompts may carry about the student
+#[tauri::command]
+pub fn get_prompt_privacy() -> Result<PromptPrivacy, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| prompt_privacy::load(conn, profile.id))
+        .map_err(|e| format!("Failed to load prompt privacy: {}", e))
+}
+
+#[tauri::command]
+pub fn set_prompt_privacy(privacy: PromptPrivacy) -> Result<(), String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| prompt_privacy::save(conn, profile.id, &privacy))
+        .map_err(|e| format!("Failed to save prompt privacy: {}", e))
+}
+
+/// How much help casual games give
+#[tauri::command]
+pub fn get_game_assistance() -> Result<GameAssistance, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| game_assistance::load(conn, profile.id))
+        .map_err(|e| format!("Failed to load game assistance: {}", e))
+}
+
+#[tauri::command]
+pub fn set_game_assistance(assistance: GameAssistance) -> Result<(), String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| game_assistance::save(conn, profile.id, assistance))
+        .map_err(|e| format!("Failed to save game assistance: {}", e))
+}
+
+/// How the encouragement policy reads frustration and what it may change
+#[tauri::command]
+pub fn get_encouragement_settings() -> Result<EncouragementSettings, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| EncouragementSettings::load(conn, profile.id))
+        .map_err(|e| format!("Failed to load encouragement settings: {}", e))
+}
+
+#[tauri::command]
+pub fn set_encouragement_settings(settings: EncouragementSettings) -> Result<(), String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| settings.save(conn, profile.id))
+        .map_err(|e| format!("Failed to save encouragement settings: {}", e))
+}
+
+/// The policy's current read of the player: mood, the signals behind it and
+/// what it changes in the coach's tone and session difficulty
+#[tauri::command]
+pub fn get_encouragement() -> Result<Encouragement, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| encouragement::for_profile(conn, profile.id))
+        .map_err(|e| format!("Failed to read encouragement signals: {}", e))
+}
+
+/// Whether suspicious exercise attempts are flagged and kept out of progress stats
+#[tauri::command]
+pub fn get_flag_suspicious_attempts() -> Result<bool, String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| attempt_flags::is_enabled(conn, profile.id))
+        .map_err(|e| format!("Failed to load attempt flagging: {}", e))
+}
+
+#[tauri::command]
+pub fn set_flag_suspicious_attempts(enabled: bool) -> Result<(), String> {
+    let profile = DB
+        .with_conn(|conn| repositories::get_first_profile(conn))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+
+    DB.with_conn(|conn| attempt_flags::set_enabled(conn, profile.id, enabled))
+        .map_err(|e| format!("Failed to save attempt flagging: {}", e))
 }
 
 #[tauri::command]
@@ -0,0 +1,734 @@
+use chess_core::{format_move, parse_fen, parse_move, to_san, MoveStyle, NotationPrefs};
+use chess_trainer::{scaffold, Exercise, ScaffoldStep};
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::board_context::{self, BoardContext};
+use crate::commands::{analysis, data};
+use crate::coach_verbosity::{self, CHAT_MAX_TOKENS};
+use crate::encouragement;
+use crate::database::repositories::{self, Game};
+use crate::game_summary;
+use crate::kid_mode;
+use crate::moderation::ModerationPolicy;
+use crate::notation_prefs;
+use crate::position_library;
+use crate::prompt_privacy::{self, PromptPrivacy};
+use crate::DB;
+
+/// Wrong tries before exercise help may talk about the solution
+pub const SOLUTION_UNLOCK_ATTEMPTS: u32 = 3;
+
+/// Conversations searched when a failed attempt is recorded
+const RECENT_CONVERSATIONS: i32 = 20;
+
+/// Days of analysed games general chat looks through for recurring mistakes
+const MISTAKE_PATTERN_DAYS: i32 = 30;
+
+/// Days of logged study time general chat is told about
+const STUDY_TIME_DAYS: i32 = 7;
+
+const HIDDEN: &str = "[hidden]";
+
+/// Stands in for a game review or quiz when the student doesn't share their games
+const GAMES_WITHHELD: &str = "The student has chosen not to share their saved games with the coach. If they \
+    ask about a game, tell them so and offer to go through it if they paste the moves.";
+
+/// Backend names of the tools general chat can call, whichever client runs them.
+/// The quiz tools record exercise attempts, so only quiz conversations get them.
+pub const ALL_TOOLS: &[&str] = &[
+    "get_recent_games",
+    "get_player_stats",
+    "get_weakness_history",
+    "search_games_by_opening",
+    "get_games_with_mistakes",
+    "get_training_progress",
+    "get_improvement_trend",
+    "get_opponent_strength_stats",
+    "get_time_class_stats",
+    "get_progress_diff",
+    "get_saved_positions",
+    "bookmark_position",
+    "propose_training_plan_item",
+    "propose_game_review",
+    "propose_flashcard",
+    "lookup_concept",
+    "search_past_advice",
+];
+
+const GAME_REVIEW_TOOLS: &[&str] = &[
+    "get_player_stats",
+    "search_games_by_opening",
+    "get_games_with_mistakes",
+    "get_opponent_strength_stats",
+    "get_time_class_stats",
+    "bookmark_position",
+    "propose_training_plan_item",
+    "propose_game_review",
+    "propose_flashcard",
+    "lookup_concept",
+    "search_past_advice",
+];
+
+/// Nothing that lists games or stats, which could wander off the exercise
+const EXERCISE_HELP_TOOLS: &[&str] = &["get_training_progress", "lookup_concept"];
+
+const GUIDED_HELP_TOOLS: &[&str] = &["get_training_progress", "lookup_concept", "next_guided_question"];
+
+const QUIZ_ME_TOOLS: &[&str] = &["get_quiz_position", "check_quiz_answer", "lookup_concept"];
+
+/// What a conversation is about, stored as JSON in `conversations.context`.
+/// Rows without a context, or with the free text older versions wrote, are general chats.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ConversationMode {
+    General,
+    GameReview {
+        game_id: i64,
+    },
+    ExerciseHelp {
+        /// Index into the exercise library, as used by the training commands
+        exercise_id: usize,
+        /// Wrong answers given since the conversation started
+        #[serde(default)]
+        attempts: u32,
+    },
+    /// The coach leads the student to the solution one question at a time
+    GuidedHelp {
+        exercise_id: usize,
+        /// Questions asked so far, in ladder order; the last is the one being worked on
+        #[serde(default)]
+        steps: Vec<ScaffoldStep>,
+    },
+    /// The coach quizzes the student on critical positions from their own games
+    QuizMe {
+        /// Answers checked so far, and how many were right
+        #[serde(default)]
+        answered: u32,
+        #[serde(default)]
+        correct: u32,
+    },
+}
+
+impl ConversationMode {
+    pub fn parse(context: Option<&str>) -> Self {
+        context
+            .and_then(|c| serde_json::from_str(c).ok())
+            .unwrap_or(ConversationMode::General)
+    }
+
+    pub fn to_context(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Tools the coach may call in this mode
+    pub fn allowed_tools(&self) -> &'static [&'static str] {
+        match self {
+            ConversationMode::General => ALL_TOOLS,
+            ConversationMode::GameReview { .. } => GAME_REVIEW_TOOLS,
+            ConversationMode::ExerciseHelp { .. } => EXERCISE_HELP_TOOLS,
+            ConversationMode::GuidedHelp { .. } => GUIDED_HELP_TOOLS,
+            ConversationMode::QuizMe { .. } => QUIZ_ME_TOOLS,
+        }
+    }
+
+    pub fn allows_tool(&self, name: &str) -> bool {
+        self.allowed_tools().contains(&name)
+    }
+
+    /// Mode for a chat outside any stored conversation: help with the exercise on
+    /// `board`, counting the wrong tries made at it, or general chat
+    pub fn for_board(board: Option<&BoardContext>) -> Self {
+        match board.and_then(|b| b.exercise_id.map(|id| (id, b.wrong_tries.len()))) {
+            Some((exercise_id, tries)) => ConversationMode::ExerciseHelp { exercise_id, attempts: tries as u32 },
+            None => ConversationMode::General,
+        }
+    }
+
+    /// Whether replies may discuss the exercise's solution yet
+    pub fn solution_unlocked(&self) -> bool {
+        match self {
+            ConversationMode::ExerciseHelp { attempts, .. } => *attempts >= SOLUTION_UNLOCK_ATTEMPTS,
+            ConversationMode::GuidedHelp { steps, .. } => ScaffoldStep::after(steps).is_none(),
+            _ => true,
+        }
+    }
+}
+
+/// Everything the coach needs to hold a conversation in its mode
+#[derive(Debug, Clone, Serialize)]
+pub struct CoachSetup {
+    /// Stored conversation the setup is for; quiz answers are scored against it
+    pub conversation_id: Option<i64>,
+    pub mode: ConversationMode,
+    /// Added after the base coach prompt
+    pub system_prompts: Vec<String>,
+    pub allowed_tools: Vec<String>,
+    /// Replies must be checked before they are shown, so clients should not stream them
+    pub solution_locked: bool,
+    /// The profile's moderation policy can change replies, so clients should check
+    /// them whole before showing them instead of streaming
+    pub moderated: bool,
+    /// Reply length cap, from kid mode or the profile's coaching verbosity
+    pub max_tokens: u32,
+    /// What tool results may carry; clients building them leave ratings out when asked
+    pub privacy: PromptPrivacy,
+    /// Moves to scrub from replies while the solution is still locked
+    #[serde(skip)]
+    pub hidden_moves: Vec<String>,
+}
+
+impl CoachSetup {
+    /// Setup for chats outside any stored conversation
+    pub fn general() -> Self {
+        Self {
+            conversation_id: None,
+            mode: ConversationMode::General,
+            system_prompts: vec![],
+            allowed_tools: ALL_TOOLS.iter().map(|t| t.to_string()).collect(),
+            solution_locked: false,
+            moderated: ModerationPolicy::default().filters_anything(),
+            max_tokens: CHAT_MAX_TOKENS,
+            privacy: PromptPrivacy::default(),
+            hidden_moves: vec![],
+        }
+    }
+
+    /// Apply `profile_id`'s reply style: its moderation, the notation moves are written
+    /// in, the tone the encouragement policy reads from recent training, then kid mode's
+    /// simple words and short answers or its coaching verbosity
+    pub fn styled_for(mut self, conn: &Connection, profile_id: i64) -> Result<Self> {
+        self.moderated = ModerationPolicy::load(conn, profile_id)?.filters_anything();
+        self.system_prompts.extend(notation_prefs::prompt_guidance(&notation_prefs::load(conn)));
+        self.system_prompts.extend(encouragement::for_profile(conn, profile_id)?.guidance);
+        if kid_mode::is_enabled(conn, profile_id)? {
+            self.system_prompts.push(kid_mode::PROMPT_GUIDANCE.to_string());
+            self.max_tokens = kid_mode::MAX_TOKENS;
+            return Ok(self);
+        }
+        let verbosity = coach_verbosity::load(conn, profile_id)?;
+        self.system_prompts.extend(verbosity.prompt_guidance().map(str::to_string));
+        self.max_tokens = verbosity.max_tokens(CHAT_MAX_TOKENS);
+        Ok(self)
+    }
+
+    pub fn allows_tool(&self, name: &str) -> bool {
+        self.allowed_tools.iter().any(|t| t == name)
+    }
+
+    /// Replace any move the coach may not reveal yet
+    pub fn redact(&self, reply: &str) -> String {
+        redact_moves(reply, &self.hidden_moves)
+    }
+}
+
+/// Build the setup for `mode`, pulling in the game or exercise it is about, or the
+/// student's recent form for general chat. Games and ratings stay out when the
+/// profile's prompt privacy says so.
+pub fn setup(conn: &Connection, profile_id: i64, mode: &ConversationMode) -> Result<CoachSetup> {
+    let privacy = prompt_privacy::load(conn, profile_id)?;
+    let mut system_prompts = vec![];
+    let mut hidden_moves = vec![];
+    match mode {
+        ConversationMode::General if privacy.share_games => {
+            system_prompts.extend(game_summary::recent_form_prompt(&game_summary::recent(conn, profile_id)?));
+            let heatmap = analysis::mistake_heatmap(conn, profile_id, Some(MISTAKE_PATTERN_DAYS))?;
+            system_prompts.extend(analysis::mistake_patterns_prompt(&heatmap));
+        }
+        ConversationMode::General => {}
+        ConversationMode::GameReview { .. } | ConversationMode::QuizMe { .. } if !privacy.share_games => {
+            system_prompts.push(GAMES_WITHHELD.to_string())
+        }
+        ConversationMode::GameReview { game_id } => {
+            let game = repositories::get_game_by_id(conn, *game_id)?.filter(|g| g.profile_id == profile_id);
+            system_prompts.push(match game {
+                Some(game) => game_review_prompt(&game, privacy.share_ratings),
+                None => "The student wanted to review a game that is no longer saved. Tell them so and offer \
+                         to look at their recent games instead."
+                    .to_string(),
+            });
+        }
+        ConversationMode::ExerciseHelp { exercise_id, attempts } => {
+            if let Some(exercise) = position_library::find_exercise(conn, profile_id, *exercise_id)? {
+                system_prompts.push(exercise_help_prompt(&exercise, *attempts));
+                if !mode.solution_unlocked() {
+                    hidden_moves = solution_moves(&exercise, &notation_prefs::load(conn));
+                }
+            }
+        }
+        ConversationMode::GuidedHelp { exercise_id, steps } => {
+            if let Some(exercise) = position_library::find_exercise(conn, profile_id, *exercise_id)? {
+                system_prompts.push(guided_help_prompt(&exercise, steps));
+                if !mode.solution_unlocked() {
+                    hidden_moves = solution_moves(&exercise, &notation_prefs::load(conn));
+                }
+            }
+        }
+        ConversationMode::QuizMe { answered, correct } => system_prompts.push(quiz_me_prompt(*answered, *correct)),
+    }
+    if *mode == ConversationMode::General {
+        let study_time = repositories::get_time_breakdown(conn, profile_id, Some(STUDY_TIME_DAYS))?;
+        system_prompts.extend(data::study_time_prompt(&study_time));
+    }
+    CoachSetup {
+        conversation_id: None,
+        mode: mode.clone(),
+        system_prompts,
+        allowed_tools: mode
+            .allowed_tools()
+            .iter()
+            .filter(|t| privacy.allows_tool(t))
+            .map(|t| t.to_string())
+            .collect(),
+        solution_locked: !hidden_moves.is_empty(),
+        moderated: false,
+        max_tokens: CHAT_MAX_TOKENS,
+        privacy,
+        hidden_moves,
+    }
+    .styled_for(conn, profile_id)
+}
+
+/// Setup for chats outside any stored conversation, styled for the active profile.
+/// With an exercise on screen the chat is help with it, so its solution stays locked.
+pub fn for_general() -> std::result::Result<CoachSetup, String> {
+    let mode = ConversationMode::for_board(board_context::current().as_ref());
+    DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => setup(conn, profile.id, &mode),
+        None => Ok(CoachSetup::general()),
+    })
+    .map_err(|e| format!("Failed to load coach settings: {}", e))
+}
+
+/// Setup for a stored conversation, or for general chat without one
+pub fn for_chat(conversation_id: Option<i64>) -> std::result::Result<CoachSetup, String> {
+    match conversation_id {
+        Some(id) => for_conversation(id),
+        None => for_general(),
+    }
+}
+
+/// Setup for `conversation_id` under the active profile
+pub fn for_conversation(conversation_id: i64) -> std::result::Result<CoachSetup, String> {
+    DB.with_conn(|conn| {
+        let Some(profile) = repositories::get_first_profile(conn)? else {
+            return Ok(CoachSetup::general());
+        };
+        let mode = load(conn, profile.id, conversation_id)?;
+        let mut setup = setup(conn, profile.id, &mode)?;
+        setup.conversation_id = Some(conversation_id);
+        Ok(setup)
+    })
+    .map_err(|e| format!("Failed to load conversation mode: {}", e))
+}
+
+/// The mode stored for `conversation_id`; general when the conversation is
+/// missing or belongs to another profile
+pub fn load(conn: &Connection, profile_id: i64, conversation_id: i64) -> Result<ConversationMode> {
+    Ok(repositories::get_conversation(conn, conversation_id)?
+        .filter(|c| c.profile_id == profile_id)
+        .map(|c| ConversationMode::parse(c.context.as_deref()))
+        .unwrap_or(ConversationMode::General))
+}
+
+/// Count a wrong answer against the newest help conversation for `exercise_id`.
+/// Attempts are tracked here, where the answer is checked, so the frontend
+/// cannot unlock the solution early.
+pub fn record_failed_attempt(conn: &Connection, profile_id: i64, exercise_id: usize) -> Result<()> {
+    for conversation in repositories::get_recent_conversations(conn, profile_id, RECENT_CONVERSATIONS)? {
+        if let ConversationMode::ExerciseHelp { exercise_id: id, attempts } = ConversationMode::parse(conversation.context.as_deref()) {
+            if id == exercise_id {
+                let mode = ConversationMode::ExerciseHelp { exercise_id, attempts: attempts + 1 };
+                return repositories::set_conversation_context(conn, conversation.id, &mode.to_context());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The question a guided conversation has moved on to, for the coach to ask
+#[derive(Debug, Clone, Serialize)]
+pub struct GuidedQuestion {
+    pub step: ScaffoldStep,
+    pub question: String,
+    /// What the position shows, to check the answer against; not for the student
+    pub facts: String,
+    /// On the last question, the solution to explain if they still can't find it
+    pub solution: Option<String>,
+}
+
+/// Move guided conversation `conversation_id` on to its next question. The coach
+/// asks for this once it has judged an answer or decided to move on, so replies
+/// like "ok" don't walk the ladder. Returns the question now being asked; other
+/// conversations are left alone and give `None`, as does a ladder already used up.
+pub fn next_guided_question(conn: &Connection, profile_id: i64, conversation_id: i64) -> Result<Option<GuidedQuestion>> {
+    let ConversationMode::GuidedHelp { exercise_id, mut steps } = load(conn, profile_id, conversation_id)? else {
+        return Ok(None);
+    };
+    let Some(next) = ScaffoldStep::after(&steps) else {
+        return Ok(None);
+    };
+    let Some(exercise) = position_library::find_exercise(conn, profile_id, exercise_id)? else {
+        return Ok(None);
+    };
+    steps.push(next);
+    repositories::set_conversation_context(conn, conversation_id, &ConversationMode::GuidedHelp { exercise_id, steps: steps.clone() }.to_context())?;
+
+    let (question, facts) = match scaffold(&exercise, next) {
+        Ok(step) => (step.question, step.facts),
+        Err(e) => {
+            tracing::warn!("no scaffold for {:?}: {}", next, e);
+            (format!("Ask about {}.", next.as_str().to_lowercase()), String::new())
+        }
+    };
+    let solution = ScaffoldStep::after(&steps)
+        .is_none()
+        .then(|| format!("{}. {}", exercise.solution_moves.join(" "), exercise.explanation));
+    Ok(Some(GuidedQuestion { step: next, question, facts, solution }))
+}
+
+/// `next_guided_question` for the active profile, as the coach's tool runs it
+pub fn next_question(conversation_id: Option<i64>) -> std::result::Result<GuidedQuestion, String> {
+    let question = match conversation_id {
+        Some(id) => DB
+            .with_conn(|conn| match repositories::get_first_profile(conn)? {
+                Some(profile) => next_guided_question(conn, profile.id, id),
+                None => Ok(None),
+            })
+            .map_err(|e| format!("Failed to move guided help on: {}", e))?,
+        None => None,
+    };
+    question.ok_or_else(|| "There are no more questions to ask in this conversation.".to_string())
+}
+
+/// Questions the coach asked in the newest guided conversation about `exercise_id`
+/// started at or after `since` (RFC 3339), i.e. the scaffolding behind the attempt
+/// being recorded. Empty when the student solved it alone.
+pub fn guided_steps(conn: &Connection, profile_id: i64, exercise_id: usize, since: &str) -> Result<Vec<ScaffoldStep>> {
+    for conversation in repositories::get_recent_conversations(conn, profile_id, RECENT_CONVERSATIONS)? {
+        if conversation.created_at.as_str() < since {
+            continue;
+        }
+        if let ConversationMode::GuidedHelp { exercise_id: id, steps } = ConversationMode::parse(conversation.context.as_deref()) {
+            if id == exercise_id {
+                return Ok(steps);
+            }
+        }
+    }
+    Ok(vec![])
+}
+
+/// Score a checked answer in quiz conversation `conversation_id`, returning the new
+/// `(answered, correct)`. Other conversations are left alone and give `None`.
+pub fn record_quiz_answer(conn: &Connection, profile_id: i64, conversation_id: i64, right: bool) -> Result<Option<(u32, u32)>> {
+    let ConversationMode::QuizMe { answered, correct } = load(conn, profile_id, conversation_id)? else {
+        return Ok(None);
+    };
+    let (answered, correct) = (answered + 1, correct + right as u32);
+    repositories::set_conversation_context(conn, conversation_id, &ConversationMode::QuizMe { answered, correct }.to_context())?;
+    Ok(Some((answered, correct)))
+}
+
+fn game_review_prompt(game: &Game, share_ratings: bool) -> String {
+    format!(
+        "You are reviewing one of the student's saved games with them (game #{id}). Keep the discussion on \
+         this game: its turning points, the mistakes and what to learn from them.\n\
+         The student played {color} against {opponent}{elo}. Result: {result}{termination}. Opening: {opening}.\n\
+         Mistakes: {mistakes}, blunders: {blunders}.\n\
+         Moves (UCI): {moves}",
+        id = game.id,
+        color = game.player_color,
+        opponent = game.opponent_type,
+        elo = game.opponent_elo.filter(|_| share_ratings).map(|e| format!(" ({})", e)).unwrap_or_default(),
+        result = game.result,
+        termination = game.termination.as_deref().map(|t| format!(" by {}", t.replace('_', " "))).unwrap_or_default(),
+        opening = game.opening_name.as_deref().unwrap_or("unknown"),
+        mistakes = game.mistakes,
+        blunders = game.blunders,
+        moves = game.moves.join(" "),
+    )
+}
+
+fn exercise_help_prompt(exercise: &Exercise, attempts: u32) -> String {
+    let mut prompt = format!(
+        "The student is working on an exercise and wants help with it.\n\
+         Exercise: {} ({}, {}). {}\nPosition (FEN): {}",
+        exercise.title, exercise.exercise_type, exercise.difficulty, exercise.description, exercise.position
+    );
+    if !exercise.hints.is_empty() {
+        prompt.push_str(&format!("\nHints you may give, in order: {}", exercise.hints.join(" | ")));
+    }
+    if attempts < SOLUTION_UNLOCK_ATTEMPTS {
+        prompt.push_str(&format!(
+            "\nThey have made {} wrong attempt(s). Do not reveal or name the solution, even if asked. Guide \
+             them with questions about the position and the hints instead. You can explain the solution once \
+             they have tried {} times.",
+            attempts, SOLUTION_UNLOCK_ATTEMPTS
+        ));
+    } else {
+        prompt.push_str(&format!(
+            "\nThey have tried {} times, so you may now explain the solution: {}. {}",
+            attempts,
+            exercise.solution_moves.join(" "),
+            exercise.explanation
+        ));
+    }
+    prompt
+}
+
+fn guided_help_prompt(exercise: &Exercise, steps: &[ScaffoldStep]) -> String {
+    let mut prompt = format!(
+        "The student asked to be guided through an exercise. Lead them to the answer with questions rather \
+         than explanations: ask exactly one leading question per reply, react to their last answer in a \
+         sentence, and let them do the finding.\n\
+         Exercise: {} ({}, {}). {}\nPosition (FEN): {}",
+        exercise.title, exercise.exercise_type, exercise.difficulty, exercise.description, exercise.position
+    );
+    prompt.push_str(
+        "\nWhen they have answered your question, right or wrong, or are plainly stuck on it, call \
+         next_guided_question for the next one. Don't call it for replies that don't answer, like 'ok' or \
+         'thanks'.",
+    );
+    let Some((current, asked)) = steps.split_last() else {
+        prompt.push_str("\nStart by asking what they have noticed about the position so far.");
+        return prompt;
+    };
+    if !asked.is_empty() {
+        let asked: Vec<&str> = asked.iter().map(|s| s.as_str()).collect();
+        prompt.push_str(&format!("\nQuestions already worked through: {}.", asked.join(", ")));
+    }
+    match scaffold(exercise, *current) {
+        Ok(step) => prompt.push_str(&format!(
+            "\nThe question they are on: {}\nWhat the position shows, to check their answer against \
+             (do not list it for them): {}",
+            step.question, step.facts
+        )),
+        Err(e) => tracing::warn!("no scaffold for {:?}: {}", current, e),
+    }
+    if ScaffoldStep::after(steps).is_none() {
+        prompt.push_str(&format!(
+            "\nThis is the last question. If they still can't find it, explain the solution: {}. {}",
+            exercise.solution_moves.join(" "),
+            exercise.explanation
+        ));
+    } else {
+        prompt.push_str("\nDo not reveal or name the solution, even if asked.");
+    }
+    prompt
+}
+
+fn quiz_me_prompt(answered: u32, correct: u32) -> String {
+    let mut prompt = "You are quizzing the student on critical positions from their own games. Call \
+        get_quiz_position to get one, then show it: when the game was played, the opponent, the move number \
+        and the position as FEN. Ask what they played there and what would have been better. You are not told \
+        the answer, so do not guess it. When they reply with a move, call check_quiz_answer with it, then tell \
+        them what they actually played, what the engine preferred and why, and offer the next position."
+        .to_string();
+    if answered > 0 {
+        prompt.push_str(&format!("\nScore so far: {} of {} right.", correct, answered));
+    }
+    prompt
+}
+
+/// Every spelling of the exercise's solutions the coach might write: as stored, in
+/// English SAN and UCI, and in the notation `prefs` has the coach use, short and long
+fn solution_moves(exercise: &Exercise, prefs: &NotationPrefs) -> Vec<String> {
+    let mut moves = exercise.solution_moves.clone();
+    let Ok(board) = parse_fen(&exercise.position) else {
+        return moves;
+    };
+    let styles = [MoveStyle::San, MoveStyle::LongAlgebraic].map(|moves| NotationPrefs { moves, ..*prefs });
+    for chess_move in exercise.solution_moves.iter().filter_map(|m| parse_move(&board, m).ok()) {
+        moves.push(to_san(&board, chess_move));
+        moves.push(chess_move.to_string());
+        moves.extend(styles.iter().map(|style| format_move(&board, chess_move, style)));
+    }
+    moves
+}
+
+/// Replace whole-word occurrences of `moves` in `text`. Check and
+/// annotation marks are ignored, so "Qh5" also hides "Qh5+" and "Qh5!".
+pub fn redact_moves(text: &str, moves: &[String]) -> String {
+    let mut tokens: Vec<&str> = moves
+        .iter()
+        .map(|m| m.trim().trim_end_matches(['+', '#', '!', '?']))
+        .filter(|m| m.len() >= 2)
+        .collect();
+    tokens.sort_by_key(|t| std::cmp::Reverse(t.len()));
+    tokens.dedup();
+
+    let is_word = |c: char| c.is_ascii_alphanumeric();
+    let mut redacted = text.to_string();
+    for token in tokens {
+        let mut out = String::with_capacity(redacted.len());
+        let mut rest = redacted.as_str();
+        while let Some(at) = rest.find(token) {
+            let before = rest[..at].chars().next_back();
+            let after = rest[at + token.len()..].chars().next();
+            out.push_str(&rest[..at]);
+            if before.is_some_and(is_word) || after.is_some_and(is_word) {
+                out.push_str(token);
+            } else {
+                out.push_str(HIDDEN);
+            }
+            rest = &rest[at + token.len()..];
+        }
+        out.push_str(rest);
+        redacted = out;
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_context::{BoardSnapshot, ContextProvider};
+    use crate::database::schema::create_tables;
+    use chess_trainer::WrongMove;
+
+    #[test]
+    fn test_modes_round_trip_and_unlock() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap();
+
+        let help = ConversationMode::ExerciseHelp { exercise_id: 2, attempts: 0 };
+        let id = repositories::create_conversation(&conn, profile.id, None, Some(&help.to_context())).unwrap();
+        let legacy = repositories::create_conversation(&conn, profile.id, None, Some("free text")).unwrap();
+        assert_eq!(load(&conn, profile.id, id).unwrap(), help);
+        assert_eq!(load(&conn, profile.id, legacy).unwrap(), ConversationMode::General);
+        assert_eq!(load(&conn, profile.id + 1, id).unwrap(), ConversationMode::General);
+
+        assert!(!help.solution_unlocked());
+        assert!(help.allows_tool("lookup_concept"));
+        assert!(!help.allows_tool("get_recent_games"));
+        for _ in 0..SOLUTION_UNLOCK_ATTEMPTS {
+            record_failed_attempt(&conn, profile.id, 2).unwrap();
+        }
+        record_failed_attempt(&conn, profile.id, 5).unwrap();
+        let unlocked = load(&conn, profile.id, id).unwrap();
+        assert_eq!(unlocked, ConversationMode::ExerciseHelp { exercise_id: 2, attempts: SOLUTION_UNLOCK_ATTEMPTS });
+        assert!(unlocked.solution_unlocked());
+    }
+
+    #[test]
+    fn test_exercise_on_screen_locks_general_chat() {
+        let snapshot = BoardSnapshot {
+            start_fen: None,
+            moves: vec![],
+            fen: "6k1/5ppp/8/8/8/8/5PPP/4R1K1 w - - 0 1".to_string(),
+            exercise_goal: Some("Back rank: Mate in one.".to_string()),
+            exercise_id: Some(4),
+            player_color: Some("white".to_string()),
+            orientation: None,
+            wrong_moves: vec![WrongMove::at_start("e1e2")],
+            exercise_started_at: None,
+        };
+        let mode = ConversationMode::for_board(snapshot.board_context().as_ref());
+        assert_eq!(mode, ConversationMode::ExerciseHelp { exercise_id: 4, attempts: 1 });
+        assert!(!mode.solution_unlocked());
+
+        let analysis = BoardSnapshot { exercise_id: None, ..snapshot };
+        assert_eq!(ConversationMode::for_board(analysis.board_context().as_ref()), ConversationMode::General);
+        assert_eq!(ConversationMode::for_board(None), ConversationMode::General);
+    }
+
+    #[test]
+    fn test_guided_help_climbs_the_ladder() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        let since = chrono::Utc::now().to_rfc3339();
+
+        let guided = ConversationMode::GuidedHelp { exercise_id: 0, steps: vec![] };
+        let id = repositories::create_conversation(&conn, profile.id, None, Some(&guided.to_context())).unwrap();
+        assert!(!guided.solution_unlocked());
+        assert_eq!(guided_steps(&conn, profile.id, 0, &since).unwrap(), vec![]);
+
+        assert!(guided.allows_tool("next_guided_question") && !ConversationMode::General.allows_tool("next_guided_question"));
+        let first = next_guided_question(&conn, profile.id, id).unwrap().unwrap();
+        assert_eq!((first.step, first.solution), (ScaffoldStep::Threats, None));
+        assert!(!first.question.is_empty());
+        assert_eq!(next_guided_question(&conn, profile.id, id).unwrap().unwrap().step, ScaffoldStep::LoosePieces);
+        let locked = setup(&conn, profile.id, &load(&conn, profile.id, id).unwrap()).unwrap();
+        assert!(locked.solution_locked);
+        assert!(locked.system_prompts[0].contains("Questions already worked through: Threats."));
+        assert_eq!(guided_steps(&conn, profile.id, 0, &since).unwrap(), vec![ScaffoldStep::Threats, ScaffoldStep::LoosePieces]);
+        assert_eq!(guided_steps(&conn, profile.id, 1, &since).unwrap(), vec![]);
+        assert_eq!(guided_steps(&conn, profile.id, 0, "9999").unwrap(), vec![]);
+
+        let mut last = None;
+        while let Some(question) = next_guided_question(&conn, profile.id, id).unwrap() {
+            last = Some(question);
+        }
+        assert_eq!(last.map(|q| (q.step, q.solution.is_some())), Some((ScaffoldStep::TargetSquare, true)));
+        let unlocked = load(&conn, profile.id, id).unwrap();
+        assert!(unlocked.solution_unlocked());
+        assert!(!setup(&conn, profile.id, &unlocked).unwrap().solution_locked);
+    }
+
+    #[test]
+    fn test_scores_quiz_answers() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap();
+
+        let quiz = ConversationMode::QuizMe { answered: 0, correct: 0 };
+        let id = repositories::create_conversation(&conn, profile.id, None, Some(&quiz.to_context())).unwrap();
+        let general = repositories::create_conversation(&conn, profile.id, None, None).unwrap();
+        assert!(quiz.allows_tool("check_quiz_answer"));
+        assert!(!ConversationMode::General.allows_tool("check_quiz_answer"));
+
+        assert_eq!(record_quiz_answer(&conn, profile.id, id, true).unwrap(), Some((1, 1)));
+        assert_eq!(record_quiz_answer(&conn, profile.id, id, false).unwrap(), Some((2, 1)));
+        assert_eq!(record_quiz_answer(&conn, profile.id, general, true).unwrap(), None);
+        let scored = setup(&conn, profile.id, &load(&conn, profile.id, id).unwrap()).unwrap();
+        assert!(scored.system_prompts[0].ends_with("Score so far: 1 of 2 right."));
+        // Profanity filtering is on by default, so the reply is checked before it is shown
+        assert!(scored.moderated);
+
+        kid_mode::set_enabled(&conn, profile.id, true).unwrap();
+        let kid = setup(&conn, profile.id, &ConversationMode::General).unwrap();
+        assert!(kid.system_prompts.iter().any(|p| p == kid_mode::PROMPT_GUIDANCE));
+        assert_eq!(kid.max_tokens, kid_mode::MAX_TOKENS);
+    }
+
+    #[test]
+    fn test_setup_tells_the_coach_the_notation() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        let standard = setup(&conn, profile.id, &ConversationMode::General).unwrap();
+
+        let german = NotationPrefs { language: chess_core::PieceLanguage::De, ..Default::default() };
+        notation_prefs::save(&conn, &german).unwrap();
+        let guidance = notation_prefs::prompt_guidance(&german).unwrap();
+        assert!(!standard.system_prompts.contains(&guidance));
+        assert!(setup(&conn, profile.id, &ConversationMode::General).unwrap().system_prompts.contains(&guidance));
+    }
+
+    #[test]
+    fn test_redacts_solution_moves() {
+        let moves = vec!["Qh5+".to_string(), "d1h5".to_string()];
+        assert_eq!(
+            redact_moves("Try Qh5+! Then d1h5 again, not Qh56 or aQh5.", &moves),
+            "Try [hidden]+! Then [hidden] again, not Qh56 or aQh5."
+        );
+        assert_eq!(redact_moves("Nothing to hide", &[]), "Nothing to hide");
+
+        // The coach writes moves the way the student reads them
+        let exercise = Exercise::new(
+            chess_trainer::ExerciseType::Tactics,
+            chess_trainer::ExerciseDifficulty::Beginner,
+            "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1".to_string(),
+            "Back rank".to_string(),
+            String::new(),
+            vec!["Ra8".to_string()],
+            String::new(),
+        );
+        let german = NotationPrefs { language: chess_core::PieceLanguage::De, ..Default::default() };
+        let figurine = NotationPrefs { pieces: chess_core::PieceStyle::Figurine, ..Default::default() };
+        assert_eq!(redact_moves("Spiele Ta8# oder Ta1-a8#", &solution_moves(&exercise, &german)), "Spiele [hidden]# oder [hidden]#");
+        assert_eq!(redact_moves("Play ♖a8#", &solution_moves(&exercise, &figurine)), "Play [hidden]#");
+    }
+}
@@ -0,0 +1,288 @@
+use chess_trainer::{Exercise, ExerciseLibrary, ExerciseType};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::model_games::ModelGameSummary;
+use crate::database::repositories::ConceptMastery;
+use crate::kid_mode;
+
+pub const MIN_WORKOUT_MINUTES: u32 = 20;
+pub const MAX_WORKOUT_MINUTES: u32 = 30;
+
+const MINUTES_PER_PUZZLE: u32 = 2;
+const ENDGAME_DRILL_MINUTES: u32 = 5;
+const GUESS_THE_MOVE_MINUTES: u32 = 8;
+const REPERTOIRE_REVIEW_MINUTES: u32 = 5;
+
+/// Concepts below this quiz mastery are treated as weak
+const WEAK_MASTERY: f64 = 0.6;
+
+/// What the player does in one part of the workout. Ids refer to the exercise
+/// library, the model game library and the concept library.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WorkoutSegment {
+    Tactics { exercise_ids: Vec<usize> },
+    EndgameDrill { exercise_id: usize },
+    /// Step through a model game with `get_guided_replay_step`, guessing each move first
+    GuessTheMove { game_id: String },
+    RepertoireReview { concept_ids: Vec<String>, exercise_ids: Vec<usize> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkoutItem {
+    pub title: String,
+    /// Why this segment was picked today
+    pub reason: String,
+    pub minutes: u32,
+    pub segment: WorkoutSegment,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyWorkout {
+    pub date: String,
+    pub total_minutes: u32,
+    pub focus_areas: Vec<String>,
+    pub items: Vec<WorkoutItem>,
+}
+
+pub struct WorkoutInputs<'a> {
+    pub date: String,
+    /// Rotates picks so consecutive days differ
+    pub day: usize,
+    pub weaknesses: &'a [String],
+    pub mastery: &'a [ConceptMastery],
+    /// (concept id, category) for every concept in the learning library
+    pub concepts: &'a [(String, String)],
+    pub games: &'a [ModelGameSummary],
+    pub kid_mode: bool,
+    /// Minutes a day the player has studied lately; none before any time is logged
+    pub daily_minutes: Option<u32>,
+}
+
+/// Concept library category a weakness refers to
+fn weakness_category(weakness: &str) -> Option<&'static str> {
+    let weakness = weakness.to_lowercase();
+    if weakness.contains("tactic") || weakness.contains("blunder") {
+        Some("Tactics")
+    } else if weakness.contains("opening") {
+        Some("Openings")
+    } else if weakness.contains("endgame") {
+        Some("Endgames")
+    } else if weakness.contains("positional") || weakness.contains("strateg") {
+        Some("Strategy")
+    } else {
+        None
+    }
+}
+
+fn mastery_of(mastery: &[ConceptMastery], concept_id: &str) -> Option<f64> {
+    mastery.iter().find(|m| m.concept_id == concept_id).map(|m| m.mastery)
+}
+
+/// Library exercises of the given types as (library index, exercise), rotated by `day`
+fn library_exercises(types: &[ExerciseType], day: usize, kid_mode: bool) -> Vec<(usize, Exercise)> {
+    let mut exercises: Vec<(usize, Exercise)> = ExerciseLibrary::get_all_exercises()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, e)| types.contains(&e.exercise_type))
+        .filter(|(_, e)| !kid_mode || kid_mode::allows_exercise(&e.difficulty))
+        .collect();
+    if !exercises.is_empty() {
+        let shift = day % exercises.len();
+        exercises.rotate_left(shift);
+    }
+    exercises
+}
+
+/// A balanced 20-30 minute session across modes, weighted towards the player's weaknesses
+/// and lengthened towards the time they usually spend a day
+pub fn generate_workout(inputs: &WorkoutInputs) -> DailyWorkout {
+    let mut focus_areas: Vec<String> = Vec::new();
+    for category in inputs.weaknesses.iter().filter_map(|w| weakness_category(w)) {
+        if !focus_areas.iter().any(|f| f == category) {
+            focus_areas.push(category.to_string());
+        }
+    }
+    let focused = |category: &str| focus_areas.iter().any(|f| f == category);
+    let reason = |category: &str, label: &str| {
+        if focused(category) {
+            format!("{} is one of your focus areas", label)
+        } else {
+            "Part of your daily routine".to_string()
+        }
+    };
+
+    let mut items: Vec<WorkoutItem> = Vec::new();
+
+    let puzzle_count = if focused("Tactics") { 5 } else { 3 };
+    let tactics: Vec<usize> = library_exercises(&[ExerciseType::Tactics, ExerciseType::Calculation], inputs.day, inputs.kid_mode)
+        .into_iter()
+        .take(puzzle_count)
+        .map(|(id, _)| id)
+        .collect();
+    if !tactics.is_empty() {
+        items.push(WorkoutItem {
+            title: format!("{} tactics puzzles", tactics.len()),
+            reason: reason("Tactics", "Tactics"),
+            minutes: MINUTES_PER_PUZZLE * tactics.len() as u32,
+            segment: WorkoutSegment::Tactics { exercise_ids: tactics },
+        });
+    }
+
+    if let Some((id, exercise)) = library_exercises(&[ExerciseType::Endgame], inputs.day, inputs.kid_mode).into_iter().next() {
+        items.push(WorkoutItem {
+            title: format!("Endgame drill: {}", exercise.title),
+            reason: reason("Endgames", "Endgame technique"),
+            minutes: ENDGAME_DRILL_MINUTES,
+            segment: WorkoutSegment::EndgameDrill { exercise_id: id },
+        });
+    }
+
+    // Prefer games illustrating focus-area or weakly mastered concepts
+    let relevance = |game: &ModelGameSummary| {
+        game.concepts
+            .iter()
+            .filter(|concept| {
+                let in_focus = inputs
+                    .concepts
+                    .iter()
+                    .any(|(id, category)| id == *concept && focused(category));
+                let weak = mastery_of(inputs.mastery, concept).is_some_and(|m| m < WEAK_MASTERY);
+                in_focus || weak
+            })
+            .count()
+    };
+    let todays_game = inputs.day % inputs.games.len().max(1);
+    let game = inputs
+        .games
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, game)| (relevance(game), *index == todays_game))
+        .map(|(_, game)| game);
+    if let Some(game) = game {
+        items.push(WorkoutItem {
+            title: format!("Guess the move: {}", game.title),
+            reason: if relevance(game) > 0 {
+                "Illustrates concepts you are working on".to_string()
+            } else {
+                "Learn from a master game".to_string()
+            },
+            minutes: GUESS_THE_MOVE_MINUTES,
+            segment: WorkoutSegment::GuessTheMove { game_id: game.id.clone() },
+        });
+    }
+
+    // Weakest opening concepts first; unquizzed ones count as unknown
+    let mut openings: Vec<&String> = inputs
+        .concepts
+        .iter()
+        .filter(|(_, category)| category == "Openings")
+        .map(|(id, _)| id)
+        .collect();
+    openings.sort_by(|a, b| {
+        let a = mastery_of(inputs.mastery, a).unwrap_or(0.0);
+        let b = mastery_of(inputs.mastery, b).unwrap_or(0.0);
+        a.total_cmp(&b)
+    });
+    let opening_exercises: Vec<usize> = library_exercises(&[ExerciseType::Opening], inputs.day, inputs.kid_mode)
+        .into_iter()
+        .map(|(id, _)| id)
+        .take(2)
+        .collect();
+    if !openings.is_empty() || !opening_exercises.is_empty() {
+        items.push(WorkoutItem {
+            title: "Repertoire review".to_string(),
+            reason: reason("Openings", "Opening play"),
+            minutes: REPERTOIRE_REVIEW_MINUTES,
+            segment: WorkoutSegment::RepertoireReview {
+                concept_ids: openings.into_iter().take(2).cloned().collect(),
+                exercise_ids: opening_exercises,
+            },
+        });
+    }
+
+    // Pad short workouts with extra time on the master game (or whatever segment there is)
+    let target =
+        inputs.daily_minutes.map_or(MIN_WORKOUT_MINUTES, |m| m.clamp(MIN_WORKOUT_MINUTES, MAX_WORKOUT_MINUTES));
+    let total: u32 = items.iter().map(|i| i.minutes).sum();
+    if total < target {
+        let stretch = items
+            .iter()
+            .position(|i| matches!(i.segment, WorkoutSegment::GuessTheMove { .. }))
+            .or(if items.is_empty() { None } else { Some(0) });
+        if let Some(index) = stretch {
+            items[index].minutes += target - total;
+        }
+    }
+
+    DailyWorkout {
+        date: inputs.date.clone(),
+        total_minutes: items.iter().map(|i| i.minutes).sum(),
+        focus_areas,
+        items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(id: &str, concepts: &[&str]) -> ModelGameSummary {
+        ModelGameSummary {
+            id: id.to_string(),
+            title: id.to_string(),
+            white: String::new(),
+            black: String::new(),
+            event: String::new(),
+            year: None,
+            concepts: concepts.iter().map(|c| c.to_string()).collect(),
+            summary: String::new(),
+            total_plies: 20,
+        }
+    }
+
+    #[test]
+    fn test_workout_is_balanced_and_follows_weaknesses() {
+        let concepts = vec![
+            ("fork".to_string(), "Tactics".to_string()),
+            ("opening_principles".to_string(), "Openings".to_string()),
+            ("castling".to_string(), "Openings".to_string()),
+            ("opposition".to_string(), "Endgames".to_string()),
+        ];
+        let mastery = vec![ConceptMastery { concept_id: "opening_principles".to_string(), attempts: 4, correct: 4, mastery: 1.0 }];
+        let games = vec![game("miniature", &["fork"]), game("king_and_pawn", &["opposition"])];
+        let weaknesses = vec!["Endgame technique".to_string()];
+
+        let mut inputs = WorkoutInputs {
+            date: "2024-06-01".to_string(),
+            day: 3,
+            weaknesses: &weaknesses,
+            mastery: &mastery,
+            concepts: &concepts,
+            games: &games,
+            kid_mode: false,
+            daily_minutes: None,
+        };
+        let workout = generate_workout(&inputs);
+
+        assert_eq!(workout.focus_areas, vec!["Endgames"]);
+        assert!((MIN_WORKOUT_MINUTES..=MAX_WORKOUT_MINUTES).contains(&workout.total_minutes));
+
+        let kinds: Vec<&WorkoutSegment> = workout.items.iter().map(|i| &i.segment).collect();
+        assert!(matches!(kinds[0], WorkoutSegment::Tactics { .. }));
+        assert!(matches!(kinds[1], WorkoutSegment::EndgameDrill { .. }));
+        assert_eq!(kinds[2], &WorkoutSegment::GuessTheMove { game_id: "king_and_pawn".to_string() });
+        match kinds[3] {
+            WorkoutSegment::RepertoireReview { concept_ids, .. } => {
+                assert_eq!(concept_ids, &vec!["castling".to_string(), "opening_principles".to_string()])
+            }
+            other => panic!("expected repertoire review, got {:?}", other),
+        }
+
+        // A player who studies longer gets a longer workout, within the usual range
+        inputs.daily_minutes = Some(45);
+        assert_eq!(generate_workout(&inputs).total_minutes, MAX_WORKOUT_MINUTES);
+        inputs.daily_minutes = Some(5);
+        assert_eq!(generate_workout(&inputs).total_minutes, workout.total_minutes);
+    }
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::app_config;
+use crate::database::repositories;
+use crate::DB;
+
+/// How long a confirmation token stays valid
+pub const TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// What a wipe removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WipeScope {
+    /// Everything: the database, the stored API key, config files, exports, logs and caches
+    All,
+    Conversations,
+    Games,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WipeReport {
+    pub rows_deleted: usize,
+    pub files_deleted: usize,
+    /// Files that could not be removed, e.g. because another program has them open
+    pub failures: Vec<String>,
+}
+
+/// The one outstanding confirmation token, for one scope
+struct PendingWipe {
+    scope: WipeScope,
+    token: String,
+    issued: Instant,
+}
+
+static PENDING: Mutex<Option<PendingWipe>> = Mutex::new(None);
+
+/// A token the caller must hand back within `TOKEN_TTL` to wipe `scope`.
+/// Issuing a new one cancels the previous.
+pub fn issue_token(scope: WipeScope) -> String {
+    let token = chess_core::new_uid();
+    *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some(PendingWipe {
+        scope,
+        token: token.clone(),
+        issued: Instant::now(),
+    });
+    token
+}
+
+/// Check and consume the token for `scope`; any attempt uses it up
+fn redeem_token(scope: WipeScope, token: &str) -> Result<(), String> {
+    let pending = PENDING.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match pending {
+        Some(p) if p.scope == scope && p.token == token && p.issued.elapsed() <= TOKEN_TTL => Ok(()),
+        Some(p) if p.issued.elapsed() > TOKEN_TTL => Err("Confirmation expired, please try again".to_string()),
+        _ => Err("Invalid confirmation token".to_string()),
+    }
+}
+
+fn data_dir() -> Option<PathBuf> {
+    Some(dirs::data_local_dir()?.join("tacticus"))
+}
+
+/// Files and directories outside the database that hold data for `scope`
+fn paths_for(scope: WipeScope) -> Vec<PathBuf> {
+    let Some(data) = data_dir() else {
+        return vec![];
+    };
+    let files_in = |dir: PathBuf, prefix: &str| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(prefix)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    match scope {
+        WipeScope::All => {
+            let mut paths: Vec<PathBuf> = ["exports", "diagnostics", "narration", "assets", "logs"]
+                .iter()
+                .map(|dir| data.join(dir))
+                .collect();
+            // The config file can hold an API key too
+            paths.push(data.join(chess_config::CONFIG_FILE_NAME));
+            // Where older versions kept the API key and profile
+            paths.extend(dirs::config_dir().map(|p| p.join("Tacticus")));
+            paths
+        }
+        WipeScope::Conversations => files_in(data.join("exports"), "conversation-"),
+        WipeScope::Games => {
+            let mut paths = files_in(data.join("exports"), "game-");
+            paths.extend(files_in(data.join("narration"), "game_"));
+            paths
+        }
+    }
+}
+
+/// Overwrite a file with zeros before removing it. On SSDs and copy-on-write file
+/// systems the old blocks may survive, but nothing is left behind at the file's path.
+fn shred_file(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; 8192];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..n])?;
+        remaining -= n as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)
+}
+
+/// Shred `path`, or everything under it if it is a directory
+fn shred(path: &Path, report: &mut WipeReport) {
+    if path.is_dir() {
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                shred(&entry.path(), report);
+            }
+        }
+        if let Err(e) = std::fs::remove_dir(path) {
+            report.failures.push(format!("{}: {}", path.display(), e));
+        }
+    } else if path.exists() {
+        match shred_file(path) {
+            Ok(()) => report.files_deleted += 1,
+            Err(e) => report.failures.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// Wipe `scope` if `token` is the one `issue_token` gave out for it
+pub fn wipe(scope: WipeScope, token: &str) -> Result<WipeReport, String> {
+    redeem_token(scope, token)?;
+
+    let rows_deleted = DB
+        .with_conn(|conn| match scope {
+            WipeScope::All => repositories::securely(conn, repositories::wipe_all_tables),
+            WipeScope::Conversations => repositories::securely(conn, repositories::wipe_conversations),
+            WipeScope::Games => repositories::securely(conn, repositories::wipe_games),
+        })
+        .map_err(|e| format!("Failed to wipe data: {}", e))?;
+
+    let mut report = WipeReport { rows_deleted, ..Default::default() };
+    for path in paths_for(scope) {
+        shred(&path, &mut report);
+    }
+    if scope == WipeScope::All {
+        // The settings row is gone, but a key read from the config file or environment is still loaded
+        app_config::forget_api_key();
+    }
+
+    tracing::info!(?scope, rows = report.rows_deleted, files = report.files_deleted, "wiped user data");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_single_use_and_scoped() {
+        let token = issue_token(WipeScope::Games);
+        assert!(redeem_token(WipeScope::Conversations, &token).is_err());
+        // The failed attempt used it up
+        assert!(redeem_token(WipeScope::Games, &token).is_err());
+
+        let token = issue_token(WipeScope::Games);
+        assert!(redeem_token(WipeScope::Games, "guess").is_err());
+        let token_again = issue_token(WipeScope::Games);
+        assert_ne!(token, token_again);
+        assert!(redeem_token(WipeScope::Games, &token_again).is_ok());
+        assert!(redeem_token(WipeScope::Games, &token_again).is_err());
+    }
+
+    #[test]
+    fn test_shred_removes_nested_files() {
+        let dir = std::env::temp_dir().join(format!("tacticus-wipe-{}", chess_core::new_uid()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.txt"), "secret").unwrap();
+        std::fs::write(dir.join("nested").join("b.txt"), vec![7u8; 20_000]).unwrap();
+
+        let mut report = WipeReport::default();
+        shred(&dir, &mut report);
+        assert_eq!(report.files_deleted, 2);
+        assert!(report.failures.is_empty());
+        assert!(!dir.exists());
+    }
+}
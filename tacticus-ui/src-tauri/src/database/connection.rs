@@ -12,30 +12,54 @@ pub struct Database {
 impl Database {
     /// Create a new database connection. Creates the database file and directory if needed.
     pub fn new() -> Result<Self> {
+        let db = Self {
+            conn: Mutex::new(Self::open_file()?),
+        };
+
+        // Initialize schema
+        db.init_schema()?;
+
+        Ok(db)
+    }
+
+    /// The app database, or an empty in-memory one when the file can't be opened or
+    /// migrated. The failure is left for the startup health check, so the app can
+    /// show its repair screen instead of dying.
+    pub fn open_or_fallback() -> Self {
+        Self::new().unwrap_or_else(|e| {
+            tracing::error!("Failed to initialize database: {}", e);
+            crate::health::record_database_error(e.to_string());
+            Self::new_in_memory().expect("in-memory database always opens")
+        })
+    }
+
+    /// Swap in a fresh connection to the database file, e.g. after the repair
+    /// screen moved a broken file aside
+    pub fn reopen(&self) -> Result<()> {
+        let conn = Self::open_file()?;
+        schema::create_tables(&conn)?;
+        *self.conn.lock().unwrap() = conn;
+        Ok(())
+    }
+
+    fn open_file() -> Result<Connection> {
         let db_path = Self::get_database_path();
 
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
-            std::fs::create_dir_all(parent).ok();
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                tracing::warn!(path = %parent.display(), "Failed to create database directory: {}", e);
+            }
         }
 
         let conn = Connection::open(&db_path)?;
 
         // Enable foreign keys
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
-
-        let db = Self {
-            conn: Mutex::new(conn),
-        };
-
-        // Initialize schema
-        db.init_schema()?;
-
-        Ok(db)
+        Ok(conn)
     }
 
-    /// Create an in-memory database (for testing)
-    #[allow(dead_code)]
+    /// Create an in-memory database, for tests and as the stand-in when the file won't open
     pub fn new_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
@@ -51,10 +75,7 @@ impl Database {
 
     /// Get the path to the database file
     fn get_database_path() -> PathBuf {
-        dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("tacticus")
-            .join("tacticus.db")
+        crate::app_config::base().db_path.clone()
     }
 
     /// Initialize the database schema
@@ -1,3 +1,4 @@
+use chess_core::new_uid;
 use rusqlite::{params, Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
 
@@ -91,18 +92,21 @@ pub fn get_first_profile(conn: &Connection) -> Result<Option<Profile>> {
     .optional()
 }
 
-pub fn update_profile(conn: &Connection, profile: &Profile) -> Result<()> {
+/// Save `profile` if nobody has written it since it was read, checked against
+/// `updated_at`. On success `profile.updated_at` moves forward so it can be saved
+/// again; a stale copy fails with an error `is_profile_conflict` recognises.
+pub fn update_profile(conn: &Connection, profile: &mut Profile) -> Result<()> {
     let now = chrono::Utc::now().to_rfc3339();
     let weaknesses_json = serde_json::to_string(&profile.weaknesses).unwrap_or_else(|_| "[]".to_string());
     let strengths_json = serde_json::to_string(&profile.strengths).unwrap_or_else(|_| "[]".to_string());
 
-    conn.execute(
+    let updated = conn.execute(
         r#"
         UPDATE profiles SET
             name = ?1, current_elo = ?2, peak_elo = ?3, games_played = ?4,
             exercises_completed = ?5, streak = ?6, style = ?7,
             weaknesses = ?8, strengths = ?9, updated_at = ?10
-        WHERE id = ?11
+        WHERE id = ?11 AND updated_at = ?12
         "#,
         params![
             profile.name,
@@ -116,9 +120,45 @@ pub fn update_profile(conn: &Connection, profile: &Profile) -> Result<()> {
             strengths_json,
             now,
             profile.id,
+            profile.updated_at,
         ],
     )?;
 
+    if updated == 0 {
+        return Err(rusqlite::Error::StatementChangedRows(0));
+    }
+    profile.updated_at = now;
+    Ok(())
+}
+
+/// Whether `update_profile` failed because the profile changed after it was read
+pub fn is_profile_conflict(error: &rusqlite::Error) -> bool {
+    matches!(error, rusqlite::Error::StatementChangedRows(0))
+}
+
+/// Count a completed exercise in one statement, so concurrent commands can't
+/// overwrite each other's increments
+pub fn increment_exercises_completed(conn: &Connection, profile_id: i64) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE profiles SET exercises_completed = exercises_completed + 1, updated_at = ?1 WHERE id = ?2",
+        params![now, profile_id],
+    )?;
+    Ok(())
+}
+
+/// Apply a finished game's rating and streak in one statement
+pub fn record_game_outcome(conn: &Connection, profile_id: i64, new_elo: i32, won: bool) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        r#"
+        UPDATE profiles SET
+            current_elo = ?1, peak_elo = MAX(peak_elo, ?1), games_played = games_played + 1,
+            streak = CASE WHEN ?2 THEN streak + 1 ELSE 0 END, updated_at = ?3
+        WHERE id = ?4
+        "#,
+        params![new_elo, won, now, profile_id],
+    )?;
     Ok(())
 }
 
@@ -143,16 +183,48 @@ pub struct Game {
     pub opening_name: Option<String>,
     pub created_at: String,
     pub finished_at: Option<String>,
+    /// How the game ended, e.g. "checkmate" or "resignation"; older rows have none
+    pub termination: Option<String>,
+    /// "bullet", "blitz", "rapid" or "classical"; none for untimed and older games
+    pub time_class: Option<String>,
+}
+
+#[cfg(test)]
+impl Game {
+    /// A won game as White against the engine from the standard start, with `moves` in
+    /// UCI. Tests set whatever else matters to them with struct update syntax.
+    pub fn for_test(profile_id: i64, moves: &[&str]) -> Game {
+        Game {
+            id: 0,
+            profile_id,
+            initial_fen: crate::position_import::START_FEN.to_string(),
+            final_fen: String::new(),
+            moves: moves.iter().map(|m| m.to_string()).collect(),
+            result: "win".to_string(),
+            player_color: "white".to_string(),
+            opponent_type: "engine".to_string(),
+            opponent_elo: None,
+            analysis: None,
+            mistakes: 0,
+            blunders: 0,
+            opening_name: None,
+            created_at: String::new(),
+            finished_at: None,
+            termination: None,
+            time_class: None,
+        }
+    }
 }
 
+/// Store a game; its `created_at` is when it was played, now when left empty
 pub fn create_game(conn: &Connection, game: &Game) -> Result<i64> {
     let moves_json = serde_json::to_string(&game.moves).unwrap_or_else(|_| "[]".to_string());
-    let now = chrono::Utc::now().to_rfc3339();
+    let created_at = if game.created_at.is_empty() { chrono::Utc::now().to_rfc3339() } else { game.created_at.clone() };
 
     conn.execute(
         r#"
-        INSERT INTO games (profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+        INSERT INTO games (profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at, termination, time_class, uid)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
         "#,
         params![
             game.profile_id,
@@ -167,26 +239,50 @@ pub fn create_game(conn: &Connection, game: &Game) -> Result<i64> {
             game.mistakes,
             game.blunders,
             game.opening_name,
-            now,
+            created_at,
             game.finished_at,
+            game.termination,
+            game.time_class,
+            new_uid(),
         ],
     )?;
 
     Ok(conn.last_insert_rowid())
 }
 
-pub fn get_recent_games(conn: &Connection, profile_id: i64, limit: i32) -> Result<Vec<Game>> {
+/// A stored game with the same moves between the same players on the same day as
+/// `game`, so an import isn't saved twice. Undated games match on moves and players alone.
+pub fn find_duplicate_game(conn: &Connection, game: &Game) -> Result<Option<i64>> {
+    let moves_json = serde_json::to_string(&game.moves).unwrap_or_else(|_| "[]".to_string());
+    let played_on = game.created_at.get(..10);
+    conn.query_row(
+        r#"
+        SELECT id FROM games
+        WHERE profile_id = ?1 AND deleted_at IS NULL
+          AND initial_fen = ?2 AND moves = ?3 AND player_color = ?4 AND opponent_type = ?5
+          AND (?6 IS NULL OR substr(created_at, 1, 10) = ?6)
+        ORDER BY id
+        LIMIT 1
+        "#,
+        params![game.profile_id, game.initial_fen, moves_json, game.player_color, game.opponent_type, played_on],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Newest first, only games of `time_class` when given
+pub fn get_recent_games(conn: &Connection, profile_id: i64, limit: i32, time_class: Option<&str>) -> Result<Vec<Game>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at
+        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at, termination, time_class
         FROM games
-        WHERE profile_id = ?1
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND (?3 IS NULL OR time_class = ?3)
         ORDER BY created_at DESC
         LIMIT ?2
         "#,
     )?;
 
-    let games = stmt.query_map(params![profile_id, limit], |row| {
+    let games = stmt.query_map(params![profile_id, limit, time_class], |row| {
         let moves_json: String = row.get(4)?;
         Ok(Game {
             id: row.get(0)?,
@@ -204,6 +300,8 @@ pub fn get_recent_games(conn: &Connection, profile_id: i64, limit: i32) -> Resul
             opening_name: row.get(12)?,
             created_at: row.get(13)?,
             finished_at: row.get(14)?,
+            termination: row.get(15)?,
+            time_class: row.get(16)?,
         })
     })?;
 
@@ -213,9 +311,9 @@ pub fn get_recent_games(conn: &Connection, profile_id: i64, limit: i32) -> Resul
 pub fn get_games_by_opening(conn: &Connection, profile_id: i64, opening: &str) -> Result<Vec<Game>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at
+        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at, termination, time_class
         FROM games
-        WHERE profile_id = ?1 AND opening_name LIKE ?2
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND opening_name LIKE ?2
         ORDER BY created_at DESC
         "#,
     )?;
@@ -239,23 +337,114 @@ pub fn get_games_by_opening(conn: &Connection, profile_id: i64, opening: &str) -
             opening_name: row.get(12)?,
             created_at: row.get(13)?,
             finished_at: row.get(14)?,
+            termination: row.get(15)?,
+            time_class: row.get(16)?,
         })
     })?;
 
     games.collect()
 }
 
-pub fn get_games_with_mistakes(conn: &Connection, profile_id: i64, min_mistakes: i32) -> Result<Vec<Game>> {
+/// Newest first, only games of `time_class` when given
+pub fn get_games_with_mistakes(conn: &Connection, profile_id: i64, min_mistakes: i32, time_class: Option<&str>) -> Result<Vec<Game>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at
+        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at, termination, time_class
         FROM games
-        WHERE profile_id = ?1 AND (mistakes >= ?2 OR blunders > 0)
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND (mistakes >= ?2 OR blunders > 0) AND (?3 IS NULL OR time_class = ?3)
         ORDER BY created_at DESC
         "#,
     )?;
 
-    let games = stmt.query_map(params![profile_id, min_mistakes], |row| {
+    let games = stmt.query_map(params![profile_id, min_mistakes, time_class], |row| {
+        let moves_json: String = row.get(4)?;
+        Ok(Game {
+            id: row.get(0)?,
+            profile_id: row.get(1)?,
+            initial_fen: row.get(2)?,
+            final_fen: row.get(3)?,
+            moves: serde_json::from_str(&moves_json).unwrap_or_default(),
+            result: row.get(5)?,
+            player_color: row.get(6)?,
+            opponent_type: row.get(7)?,
+            opponent_elo: row.get(8)?,
+            analysis: row.get(9)?,
+            mistakes: row.get(10)?,
+            blunders: row.get(11)?,
+            opening_name: row.get(12)?,
+            created_at: row.get(13)?,
+            finished_at: row.get(14)?,
+            termination: row.get(15)?,
+            time_class: row.get(16)?,
+        })
+    })?;
+
+    games.collect()
+}
+
+/// Games since `since` (RFC 3339) the player lost by resigning within `max_plies` moves
+pub fn count_early_resignations(conn: &Connection, profile_id: i64, since: &str, max_plies: i32) -> Result<u32> {
+    conn.query_row(
+        r#"
+        SELECT COUNT(*) FROM games
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND created_at >= ?2
+          AND termination = 'resignation' AND result = 'loss' AND json_array_length(moves) <= ?3
+        "#,
+        params![profile_id, since, max_plies],
+        |row| row.get(0),
+    )
+}
+
+pub fn get_game_by_id(conn: &Connection, id: i64) -> Result<Option<Game>> {
+    conn.query_row(
+        r#"
+        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at, termination, time_class
+        FROM games
+        WHERE id = ?1 AND deleted_at IS NULL
+        "#,
+        params![id],
+        |row| {
+            let moves_json: String = row.get(4)?;
+            Ok(Game {
+                id: row.get(0)?,
+                profile_id: row.get(1)?,
+                initial_fen: row.get(2)?,
+                final_fen: row.get(3)?,
+                moves: serde_json::from_str(&moves_json).unwrap_or_default(),
+                result: row.get(5)?,
+                player_color: row.get(6)?,
+                opponent_type: row.get(7)?,
+                opponent_elo: row.get(8)?,
+                analysis: row.get(9)?,
+                mistakes: row.get(10)?,
+                blunders: row.get(11)?,
+                opening_name: row.get(12)?,
+                created_at: row.get(13)?,
+                finished_at: row.get(14)?,
+                termination: row.get(15)?,
+                time_class: row.get(16)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Games played in the last `days` days (all games if None), oldest first
+pub fn get_games_since(conn: &Connection, profile_id: i64, days: Option<i32>) -> Result<Vec<Game>> {
+    let cutoff_str = days
+        .map(|d| (chrono::Utc::now() - chrono::Duration::days(d as i64)).to_rfc3339())
+        .unwrap_or_default();
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo, analysis, mistakes, blunders, opening_name, created_at, finished_at, termination, time_class
+        FROM games
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND created_at >= ?2
+        ORDER BY created_at ASC
+        "#,
+    )?;
+
+    let games = stmt.query_map(params![profile_id, cutoff_str], |row| {
         let moves_json: String = row.get(4)?;
         Ok(Game {
             id: row.get(0)?,
@@ -273,12 +462,148 @@ pub fn get_games_with_mistakes(conn: &Connection, profile_id: i64, min_mistakes:
             opening_name: row.get(12)?,
             created_at: row.get(13)?,
             finished_at: row.get(14)?,
+            termination: row.get(15)?,
+            time_class: row.get(16)?,
+        })
+    })?;
+
+    games.collect()
+}
+
+/// A stored game with no opening name, as swept by the opening backfill
+#[derive(Debug, Clone)]
+pub struct UnnamedGame {
+    pub id: i64,
+    pub initial_fen: String,
+    pub moves: Vec<String>,
+}
+
+/// Up to `limit` games without an opening name and with an id above `after_id`, lowest id first
+pub fn get_games_missing_opening(conn: &Connection, after_id: i64, limit: i64) -> Result<Vec<UnnamedGame>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, initial_fen, moves FROM games WHERE opening_name IS NULL AND id > ?1 ORDER BY id LIMIT ?2",
+    )?;
+
+    let games = stmt.query_map(params![after_id, limit], |row| {
+        let moves_json: String = row.get(2)?;
+        Ok(UnnamedGame {
+            id: row.get(0)?,
+            initial_fen: row.get(1)?,
+            moves: serde_json::from_str(&moves_json).unwrap_or_default(),
         })
     })?;
 
     games.collect()
 }
 
+pub fn count_games_missing_opening(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT COUNT(*) FROM games WHERE opening_name IS NULL", [], |row| row.get(0))
+}
+
+/// Record detected openings as one transaction. Games named in the meantime
+/// are left alone. Returns the number of games updated.
+pub fn set_game_openings(conn: &Connection, openings: &[(i64, String)]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut updated = 0;
+    for (id, name) in openings {
+        updated += tx.execute(
+            "UPDATE games SET opening_name = ?1, summary = NULL WHERE id = ?2 AND opening_name IS NULL",
+            params![name, id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(updated)
+}
+
+/// The game's cached coach summary, as JSON; none until one is built, and again
+/// once its analysis or opening changes
+pub fn get_game_summary(conn: &Connection, game_id: i64) -> Result<Option<String>> {
+    conn.query_row("SELECT summary FROM games WHERE id = ?1", params![game_id], |row| row.get(0))
+        .optional()
+        .map(Option::flatten)
+}
+
+pub fn set_game_summary(conn: &Connection, game_id: i64, summary: &str) -> Result<()> {
+    conn.execute("UPDATE games SET summary = ?1 WHERE id = ?2", params![summary, game_id])?;
+    Ok(())
+}
+
+/// One engine run over a game. Older runs are kept so accuracy figures can be
+/// compared like for like after the engine changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameAnalysisRecord {
+    pub id: i64,
+    pub game_id: i64,
+    pub engine_name: String,
+    pub engine_version: String,
+    pub depth: i32,
+    pub analysis: String,
+    pub mistakes: i32,
+    pub blunders: i32,
+    pub average_centipawn_loss: f64,
+    pub created_at: String,
+}
+
+/// Store an analysis run and make it the game's current analysis
+pub fn insert_game_analysis(conn: &Connection, record: &GameAnalysisRecord) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO game_analyses (game_id, engine_name, engine_version, depth, analysis, mistakes, blunders, average_centipawn_loss, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        "#,
+        params![
+            record.game_id,
+            record.engine_name,
+            record.engine_version,
+            record.depth,
+            record.analysis,
+            record.mistakes,
+            record.blunders,
+            record.average_centipawn_loss,
+            now,
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+
+    conn.execute(
+        "UPDATE games SET analysis = ?1, mistakes = ?2, blunders = ?3, summary = NULL WHERE id = ?4",
+        params![record.analysis, record.mistakes, record.blunders, record.game_id],
+    )?;
+
+    Ok(id)
+}
+
+/// All analysis runs for a game, newest first
+pub fn get_game_analyses(conn: &Connection, game_id: i64) -> Result<Vec<GameAnalysisRecord>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, game_id, engine_name, engine_version, depth, analysis, mistakes, blunders, average_centipawn_loss, created_at
+        FROM game_analyses
+        WHERE game_id = ?1
+        ORDER BY id DESC
+        "#,
+    )?;
+
+    let records = stmt.query_map(params![game_id], |row| {
+        Ok(GameAnalysisRecord {
+            id: row.get(0)?,
+            game_id: row.get(1)?,
+            engine_name: row.get(2)?,
+            engine_version: row.get(3)?,
+            depth: row.get(4)?,
+            analysis: row.get(5)?,
+            mistakes: row.get(6)?,
+            blunders: row.get(7)?,
+            average_centipawn_loss: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    })?;
+
+    records.collect()
+}
+
 // ============================================================================
 // Conversation Repository
 // ============================================================================
@@ -308,8 +633,8 @@ pub fn create_conversation(conn: &Connection, profile_id: i64, title: Option<&st
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT INTO conversations (profile_id, title, context, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
-        params![profile_id, title, context, now],
+        "INSERT INTO conversations (profile_id, title, context, created_at, updated_at, uid) VALUES (?1, ?2, ?3, ?4, ?4, ?5)",
+        params![profile_id, title, context, now, new_uid()],
     )?;
 
     Ok(conn.last_insert_rowid())
@@ -317,7 +642,7 @@ pub fn create_conversation(conn: &Connection, profile_id: i64, title: Option<&st
 
 pub fn get_conversation(conn: &Connection, id: i64) -> Result<Option<Conversation>> {
     conn.query_row(
-        "SELECT id, profile_id, title, context, created_at, updated_at FROM conversations WHERE id = ?1",
+        "SELECT id, profile_id, title, context, created_at, updated_at FROM conversations WHERE id = ?1 AND deleted_at IS NULL",
         params![id],
         |row| Ok(Conversation {
             id: row.get(0)?,
@@ -331,9 +656,15 @@ pub fn get_conversation(conn: &Connection, id: i64) -> Result<Option<Conversatio
     .optional()
 }
 
+/// Replace a conversation's structured context, e.g. its mode
+pub fn set_conversation_context(conn: &Connection, id: i64, context: &str) -> Result<()> {
+    conn.execute("UPDATE conversations SET context = ?1 WHERE id = ?2", params![context, id])?;
+    Ok(())
+}
+
 pub fn get_recent_conversations(conn: &Connection, profile_id: i64, limit: i32) -> Result<Vec<Conversation>> {
     let mut stmt = conn.prepare(
-        "SELECT id, profile_id, title, context, created_at, updated_at FROM conversations WHERE profile_id = ?1 ORDER BY updated_at DESC LIMIT ?2",
+        "SELECT id, profile_id, title, context, created_at, updated_at FROM conversations WHERE profile_id = ?1 AND deleted_at IS NULL ORDER BY updated_at DESC LIMIT ?2",
     )?;
 
     let convs = stmt.query_map(params![profile_id, limit], |row| {
@@ -354,8 +685,8 @@ pub fn add_message(conn: &Connection, conversation_id: i64, role: &str, content:
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT INTO messages (conversation_id, role, content, tool_calls, tool_results, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![conversation_id, role, content, tool_calls, tool_results, now],
+        "INSERT INTO messages (conversation_id, role, content, tool_calls, tool_results, created_at, uid) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![conversation_id, role, content, tool_calls, tool_results, now, new_uid()],
     )?;
 
     // Update conversation updated_at
@@ -367,9 +698,15 @@ pub fn add_message(conn: &Connection, conversation_id: i64, role: &str, content:
     Ok(conn.last_insert_rowid())
 }
 
+/// Messages of `conversation_id`, oldest first; none while the conversation is deleted
 pub fn get_conversation_messages(conn: &Connection, conversation_id: i64) -> Result<Vec<Message>> {
     let mut stmt = conn.prepare(
-        "SELECT id, conversation_id, role, content, tool_calls, tool_results, created_at FROM messages WHERE conversation_id = ?1 ORDER BY created_at ASC",
+        r#"
+        SELECT m.id, m.conversation_id, m.role, m.content, m.tool_calls, m.tool_results, m.created_at
+        FROM messages m JOIN conversations c ON c.id = m.conversation_id
+        WHERE m.conversation_id = ?1 AND c.deleted_at IS NULL
+        ORDER BY m.created_at ASC
+        "#,
     )?;
 
     let messages = stmt.query_map(params![conversation_id], |row| {
@@ -387,6 +724,51 @@ pub fn get_conversation_messages(conn: &Connection, conversation_id: i64) -> Res
     messages.collect()
 }
 
+/// A message found by `search_messages`, with a snippet around the matched words
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageMatch {
+    pub message_id: i64,
+    pub conversation_id: i64,
+    pub conversation_title: Option<String>,
+    /// Matched words are wrapped in [brackets]
+    pub snippet: String,
+    pub created_at: String,
+}
+
+/// Full-text search over the profile's messages from `role`, best matches first.
+/// `query` is an FTS5 expression; deleted conversations are left out.
+pub fn search_messages(
+    conn: &Connection,
+    profile_id: i64,
+    role: &str,
+    query: &str,
+    exclude_conversation: Option<i64>,
+    limit: i32,
+) -> Result<Vec<MessageMatch>> {
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.conversation_id, c.title, snippet(messages_fts, 0, '[', ']', '...', 24), m.created_at
+         FROM messages_fts
+         JOIN messages m ON m.id = messages_fts.rowid
+         JOIN conversations c ON c.id = m.conversation_id
+         WHERE messages_fts MATCH ?1 AND c.profile_id = ?2 AND m.role = ?3 AND c.deleted_at IS NULL
+           AND (?4 IS NULL OR m.conversation_id != ?4)
+         ORDER BY bm25(messages_fts)
+         LIMIT ?5",
+    )?;
+
+    let matches = stmt.query_map(params![query, profile_id, role, exclude_conversation, limit], |row| {
+        Ok(MessageMatch {
+            message_id: row.get(0)?,
+            conversation_id: row.get(1)?,
+            conversation_title: row.get(2)?,
+            snippet: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    })?;
+
+    matches.collect()
+}
+
 // ============================================================================
 // Exercise Results Repository
 // ============================================================================
@@ -395,6 +777,8 @@ pub fn get_conversation_messages(conn: &Connection, conversation_id: i64) -> Res
 pub struct ExerciseResult {
     pub id: i64,
     pub profile_id: i64,
+    /// Training session the attempt belongs to, if it was part of one
+    pub session_id: Option<i64>,
     pub exercise_type: String,
     pub difficulty: String,
     pub position_fen: String,
@@ -403,6 +787,24 @@ pub struct ExerciseResult {
     pub time_seconds: i32,
     pub hints_used: i32,
     pub created_at: String,
+    /// Looked like engine help; kept for the record but left out of progress and ratings
+    #[serde(default)]
+    pub flagged: bool,
+    #[serde(default)]
+    pub flag_reason: Option<String>,
+    /// Solved with a move the engine rated as good as the stored solution, rather than the solution itself
+    #[serde(default)]
+    pub alternative_solution: bool,
+    /// Guided-help questions asked before the attempt, by step name; empty when unassisted
+    #[serde(default)]
+    pub scaffolding: Vec<String>,
+}
+
+impl ExerciseResult {
+    /// Whether the coach led the student through the exercise
+    pub fn assisted(&self) -> bool {
+        !self.scaffolding.is_empty()
+    }
 }
 
 pub fn record_exercise_result(conn: &Connection, result: &ExerciseResult) -> Result<i64> {
@@ -410,11 +812,12 @@ pub fn record_exercise_result(conn: &Connection, result: &ExerciseResult) -> Res
 
     conn.execute(
         r#"
-        INSERT INTO exercise_results (profile_id, exercise_type, difficulty, position_fen, solved, attempts, time_seconds, hints_used, created_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        INSERT INTO exercise_results (profile_id, session_id, exercise_type, difficulty, position_fen, solved, attempts, time_seconds, hints_used, created_at, uid, flagged, flag_reason, alternative_solution, scaffolding)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
         "#,
         params![
             result.profile_id,
+            result.session_id,
             result.exercise_type,
             result.difficulty,
             result.position_fen,
@@ -423,12 +826,54 @@ pub fn record_exercise_result(conn: &Connection, result: &ExerciseResult) -> Res
             result.time_seconds,
             result.hints_used,
             now,
+            new_uid(),
+            result.flagged as i32,
+            result.flag_reason,
+            result.alternative_solution as i32,
+            serde_json::to_string(&result.scaffolding).unwrap_or_else(|_| "[]".to_string()),
         ],
     )?;
 
     Ok(conn.last_insert_rowid())
 }
 
+/// Mark already recorded results as flagged, keeping the first reason given
+pub fn flag_exercise_results(conn: &Connection, ids: &[i64], reason: &str) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "UPDATE exercise_results SET flagged = 1, flag_reason = COALESCE(flag_reason, ?2) WHERE id = ?1",
+    )?;
+    for id in ids {
+        stmt.execute(params![id, reason])?;
+    }
+    Ok(())
+}
+
+/// Unflagged attempts and solves outside `exclude_session`, the player's track
+/// record before the session being judged
+pub fn get_exercise_accuracy(conn: &Connection, profile_id: i64, exclude_session: Option<i64>) -> Result<(i32, i32)> {
+    conn.query_row(
+        r#"
+        SELECT COUNT(*), COALESCE(SUM(solved), 0)
+        FROM exercise_results
+        WHERE profile_id = ?1 AND flagged = 0
+          AND (?2 IS NULL OR session_id IS NULL OR session_id != ?2)
+        "#,
+        params![profile_id, exclude_session],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// Whether each of the latest `limit` unflagged attempts was solved, and whether the
+/// coach guided the student through it, newest first
+pub fn get_recent_solves(conn: &Connection, profile_id: i64, limit: i32) -> Result<Vec<(bool, bool)>> {
+    let mut stmt = conn.prepare(
+        "SELECT solved, scaffolding != '[]' FROM exercise_results WHERE profile_id = ?1 AND flagged = 0 \
+         ORDER BY created_at DESC, id DESC LIMIT ?2",
+    )?;
+    let solves = stmt.query_map(params![profile_id, limit], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    solves.collect()
+}
+
 pub fn get_training_progress(conn: &Connection, profile_id: i64, exercise_type: Option<&str>) -> Result<TrainingProgress> {
     let (total, solved, avg_time, avg_hints): (i32, i32, f64, f64) = if let Some(ex_type) = exercise_type {
         conn.query_row(
@@ -439,7 +884,7 @@ pub fn get_training_progress(conn: &Connection, profile_id: i64, exercise_type:
                 AVG(time_seconds) as avg_time,
                 AVG(hints_used) as avg_hints
             FROM exercise_results
-            WHERE profile_id = ?1 AND exercise_type = ?2
+            WHERE profile_id = ?1 AND exercise_type = ?2 AND flagged = 0
             "#,
             params![profile_id, ex_type],
             |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, Option<f64>>(2)?.unwrap_or(0.0), row.get::<_, Option<f64>>(3)?.unwrap_or(0.0))),
@@ -453,7 +898,7 @@ pub fn get_training_progress(conn: &Connection, profile_id: i64, exercise_type:
                 AVG(time_seconds) as avg_time,
                 AVG(hints_used) as avg_hints
             FROM exercise_results
-            WHERE profile_id = ?1
+            WHERE profile_id = ?1 AND flagged = 0
             "#,
             params![profile_id],
             |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, Option<f64>>(2)?.unwrap_or(0.0), row.get::<_, Option<f64>>(3)?.unwrap_or(0.0))),
@@ -479,65 +924,2160 @@ pub struct TrainingProgress {
 }
 
 // ============================================================================
-// Settings Repository
+// Training Sessions Repository
 // ============================================================================
 
-pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
-    conn.query_row(
-        "SELECT value FROM settings WHERE key = ?1",
-        params![key],
-        |row| row.get(0),
-    )
-    .optional()
+/// A stored workout. The totals mirror chess-trainer's `SessionResult` and are
+/// recomputed from the linked exercise results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingSessionRecord {
+    pub id: i64,
+    pub profile_id: i64,
+    pub total_exercises: i32,
+    pub completed_exercises: i32,
+    pub solved_exercises: i32,
+    pub success_rate: f64,
+    pub total_attempts: i32,
+    pub total_hints_used: i32,
+    pub duration_seconds: i32,
+    pub strategies_covered: Vec<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Rating the session's puzzles were solved at, set when it is finished
+    pub performance_rating: Option<i32>,
 }
 
-pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+impl TrainingSessionRecord {
+    pub fn to_session_result(&self) -> chess_trainer::SessionResult {
+        chess_trainer::SessionResult {
+            total_exercises: self.total_exercises as usize,
+            completed_exercises: self.completed_exercises as usize,
+            solved_exercises: self.solved_exercises as usize,
+            success_rate: self.success_rate as f32,
+            total_attempts: self.total_attempts as u32,
+            total_hints_used: self.total_hints_used as u32,
+            duration_seconds: self.duration_seconds as u32,
+            strategies_covered: self.strategies_covered.clone(),
+        }
+    }
+}
+
+const TRAINING_SESSION_COLUMNS: &str = "id, profile_id, total_exercises, completed_exercises, solved_exercises, success_rate, \
+     total_attempts, total_hints_used, duration_seconds, strategies_covered, started_at, finished_at, performance_rating";
+
+fn row_to_training_session(row: &rusqlite::Row) -> Result<TrainingSessionRecord> {
+    let strategies_json: String = row.get(9)?;
+    Ok(TrainingSessionRecord {
+        id: row.get(0)?,
+        profile_id: row.get(1)?,
+        total_exercises: row.get(2)?,
+        completed_exercises: row.get(3)?,
+        solved_exercises: row.get(4)?,
+        success_rate: row.get(5)?,
+        total_attempts: row.get(6)?,
+        total_hints_used: row.get(7)?,
+        duration_seconds: row.get(8)?,
+        strategies_covered: serde_json::from_str(&strategies_json).unwrap_or_default(),
+        started_at: row.get(10)?,
+        finished_at: row.get(11)?,
+        performance_rating: row.get(12)?,
+    })
+}
+
+pub fn create_training_session(conn: &Connection, profile_id: i64, total_exercises: i32, strategies: &[String]) -> Result<i64> {
     let now = chrono::Utc::now().to_rfc3339();
+    let strategies_json = serde_json::to_string(strategies).unwrap_or_else(|_| "[]".to_string());
 
     conn.execute(
-        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3) ON CONFLICT(key) DO UPDATE SET value = ?2, updated_at = ?3",
-        params![key, value, now],
+        r#"
+        INSERT INTO training_sessions (profile_id, total_exercises, strategies_covered, started_at, uid)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        params![profile_id, total_exercises, strategies_json, now, new_uid()],
     )?;
 
+    Ok(conn.last_insert_rowid())
+}
+
+/// Recompute a session's totals from its exercise results
+pub fn refresh_training_session(conn: &Connection, session_id: i64) -> Result<()> {
+    conn.execute(
+        r#"
+        UPDATE training_sessions SET
+            completed_exercises = totals.completed,
+            solved_exercises = totals.solved,
+            success_rate = CASE WHEN totals.completed > 0 THEN totals.solved * 100.0 / totals.completed ELSE 0 END,
+            total_attempts = totals.attempts,
+            total_hints_used = totals.hints
+        FROM (
+            SELECT
+                COUNT(*) AS completed,
+                COALESCE(SUM(solved), 0) AS solved,
+                COALESCE(SUM(attempts), 0) AS attempts,
+                COALESCE(SUM(hints_used), 0) AS hints
+            FROM exercise_results
+            WHERE session_id = ?1 AND flagged = 0
+        ) AS totals
+        WHERE id = ?1
+        "#,
+        params![session_id],
+    )?;
     Ok(())
 }
 
-// ============================================================================
-// Player Stats (computed from data)
-// ============================================================================
+/// Close a session, fixing its duration and totals
+pub fn finish_training_session(conn: &Connection, session_id: i64) -> Result<Option<TrainingSessionRecord>> {
+    refresh_training_session(conn, session_id)?;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlayerStats {
-    pub current_elo: i32,
-    pub peak_elo: i32,
-    pub games_played: i32,
-    pub wins: i32,
-    pub losses: i32,
-    pub draws: i32,
-    pub win_rate: f64,
-    pub exercises_completed: i32,
-    pub exercises_solved: i32,
-    pub exercise_success_rate: f64,
-    pub streak: i32,
-    pub style: String,
-    pub weaknesses: Vec<String>,
-    pub strengths: Vec<String>,
+    let Some(session) = get_training_session(conn, session_id)? else {
+        return Ok(None);
+    };
+    let now = chrono::Utc::now();
+    let duration = chrono::DateTime::parse_from_rfc3339(&session.started_at)
+        .map(|started| (now - started.with_timezone(&chrono::Utc)).num_seconds().max(0))
+        .unwrap_or(0);
+
+    conn.execute(
+        "UPDATE training_sessions SET finished_at = ?1, duration_seconds = ?2 WHERE id = ?3 AND finished_at IS NULL",
+        params![now.to_rfc3339(), duration, session_id],
+    )?;
+
+    get_training_session(conn, session_id)
 }
 
-pub fn get_player_stats(conn: &Connection, profile_id: i64) -> Result<Option<PlayerStats>> {
-    let profile = match get_profile_by_id(conn, profile_id)? {
-        Some(p) => p,
-        None => return Ok(None),
-    };
+pub fn set_session_performance_rating(conn: &Connection, session_id: i64, rating: Option<i32>) -> Result<()> {
+    conn.execute(
+        "UPDATE training_sessions SET performance_rating = ?1 WHERE id = ?2",
+        params![rating, session_id],
+    )?;
+    Ok(())
+}
 
-    // Get game stats
+pub fn get_training_session(conn: &Connection, session_id: i64) -> Result<Option<TrainingSessionRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM training_sessions WHERE id = ?1", TRAINING_SESSION_COLUMNS),
+        params![session_id],
+        row_to_training_session,
+    )
+    .optional()
+}
+
+/// Most recent sessions first
+pub fn get_training_sessions(conn: &Connection, profile_id: i64, limit: i64) -> Result<Vec<TrainingSessionRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM training_sessions WHERE profile_id = ?1 ORDER BY started_at DESC, id DESC LIMIT ?2",
+        TRAINING_SESSION_COLUMNS
+    ))?;
+
+    let sessions = stmt.query_map(params![profile_id, limit], row_to_training_session)?;
+    sessions.collect()
+}
+
+/// The latest session that was left with exercises still to do
+pub fn get_unfinished_training_session(conn: &Connection, profile_id: i64) -> Result<Option<TrainingSessionRecord>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM training_sessions WHERE profile_id = ?1 AND finished_at IS NULL AND completed_exercises < total_exercises \
+             ORDER BY started_at DESC, id DESC LIMIT 1",
+            TRAINING_SESSION_COLUMNS
+        ),
+        params![profile_id],
+        row_to_training_session,
+    )
+    .optional()
+}
+
+/// Exercise results recorded in a session, in the order they were attempted
+pub fn get_session_exercise_results(conn: &Connection, session_id: i64) -> Result<Vec<ExerciseResult>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, profile_id, session_id, exercise_type, difficulty, position_fen, solved, attempts, time_seconds, hints_used, created_at,
+               flagged, flag_reason, alternative_solution, scaffolding
+        FROM exercise_results
+        WHERE session_id = ?1
+        ORDER BY id ASC
+        "#,
+    )?;
+
+    let results = stmt.query_map(params![session_id], |row| {
+        Ok(ExerciseResult {
+            id: row.get(0)?,
+            profile_id: row.get(1)?,
+            session_id: row.get(2)?,
+            exercise_type: row.get(3)?,
+            difficulty: row.get(4)?,
+            position_fen: row.get(5)?,
+            solved: row.get::<_, i32>(6)? != 0,
+            attempts: row.get(7)?,
+            time_seconds: row.get(8)?,
+            hints_used: row.get(9)?,
+            created_at: row.get(10)?,
+            flagged: row.get::<_, i32>(11)? != 0,
+            flag_reason: row.get(12)?,
+            alternative_solution: row.get::<_, i32>(13)? != 0,
+            scaffolding: serde_json::from_str(&row.get::<_, String>(14)?).unwrap_or_default(),
+        })
+    })?;
+
+    results.collect()
+}
+
+// ============================================================================
+// Quiz Repository
+// ============================================================================
+
+/// Weight given to the latest answer when updating concept mastery
+const MASTERY_LEARNING_RATE: f64 = 0.2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuizQuestion {
+    pub id: i64,
+    pub concept_id: String,
+    pub question_type: String, // "multiple_choice", "square" or "move"
+    pub prompt: String,
+    pub fen: Option<String>,
+    pub choices: Vec<String>,
+    pub answer: String,
+    pub explanation: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuizSession {
+    pub id: i64,
+    pub profile_id: i64,
+    pub concept_id: String,
+    pub total_questions: i32,
+    pub answered: i32,
+    pub score: i32,
+    pub started_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConceptMastery {
+    pub concept_id: String,
+    pub attempts: i32,
+    pub correct: i32,
+    pub mastery: f64,
+}
+
+pub fn insert_quiz_question(conn: &Connection, question: &QuizQuestion) -> Result<i64> {
+    let choices_json = serde_json::to_string(&question.choices).unwrap_or_else(|_| "[]".to_string());
+
+    conn.execute(
+        r#"
+        INSERT INTO quiz_questions (concept_id, question_type, prompt, fen, choices, answer, explanation)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+        params![
+            question.concept_id,
+            question.question_type,
+            question.prompt,
+            question.fen,
+            choices_json,
+            question.answer,
+            question.explanation,
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn count_quiz_questions(conn: &Connection) -> Result<i32> {
+    conn.query_row("SELECT COUNT(*) FROM quiz_questions", [], |row| row.get(0))
+}
+
+fn map_quiz_question(row: &rusqlite::Row) -> Result<QuizQuestion> {
+    let choices_json: String = row.get(5)?;
+    Ok(QuizQuestion {
+        id: row.get(0)?,
+        concept_id: row.get(1)?,
+        question_type: row.get(2)?,
+        prompt: row.get(3)?,
+        fen: row.get(4)?,
+        choices: serde_json::from_str(&choices_json).unwrap_or_default(),
+        answer: row.get(6)?,
+        explanation: row.get(7)?,
+    })
+}
+
+/// A random selection of questions for a concept
+pub fn get_quiz_questions(conn: &Connection, concept_id: &str, limit: i32) -> Result<Vec<QuizQuestion>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, concept_id, question_type, prompt, fen, choices, answer, explanation
+        FROM quiz_questions
+        WHERE concept_id = ?1
+        ORDER BY RANDOM()
+        LIMIT ?2
+        "#,
+    )?;
+
+    let questions = stmt.query_map(params![concept_id, limit], map_quiz_question)?;
+    questions.collect()
+}
+
+pub fn get_quiz_question(conn: &Connection, id: i64) -> Result<Option<QuizQuestion>> {
+    conn.query_row(
+        r#"
+        SELECT id, concept_id, question_type, prompt, fen, choices, answer, explanation
+        FROM quiz_questions
+        WHERE id = ?1
+        "#,
+        params![id],
+        map_quiz_question,
+    )
+    .optional()
+}
+
+pub fn create_quiz_session(conn: &Connection, profile_id: i64, concept_id: &str, total_questions: i32) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO quiz_sessions (profile_id, concept_id, total_questions, started_at) VALUES (?1, ?2, ?3, ?4)",
+        params![profile_id, concept_id, total_questions, now],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_quiz_session(conn: &Connection, id: i64) -> Result<Option<QuizSession>> {
+    conn.query_row(
+        r#"
+        SELECT id, profile_id, concept_id, total_questions, answered, score, started_at
+        FROM quiz_sessions
+        WHERE id = ?1
+        "#,
+        params![id],
+        |row| {
+            Ok(QuizSession {
+                id: row.get(0)?,
+                profile_id: row.get(1)?,
+                concept_id: row.get(2)?,
+                total_questions: row.get(3)?,
+                answered: row.get(4)?,
+                score: row.get(5)?,
+                started_at: row.get(6)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Record an answer and update the session score. Returns false if the
+/// question was already answered in this session.
+pub fn record_quiz_answer(conn: &Connection, session_id: i64, question_id: i64, answer: &str, correct: bool) -> Result<bool> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let inserted = conn.execute(
+        r#"
+        INSERT OR IGNORE INTO quiz_answers (session_id, question_id, answer, correct, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        params![session_id, question_id, answer, correct as i32, now],
+    )?;
+
+    if inserted == 0 {
+        return Ok(false);
+    }
+
+    conn.execute(
+        "UPDATE quiz_sessions SET answered = answered + 1, score = score + ?1 WHERE id = ?2",
+        params![correct as i32, session_id],
+    )?;
+
+    Ok(true)
+}
+
+/// Fold one answer into a concept's mastery score (0.0 - 1.0)
+pub fn update_concept_mastery(conn: &Connection, profile_id: i64, concept_id: &str, correct: bool) -> Result<ConceptMastery> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let outcome = if correct { 1.0 } else { 0.0 };
+
+    conn.execute(
+        r#"
+        INSERT INTO concept_mastery (profile_id, concept_id, attempts, correct, mastery, updated_at)
+        VALUES (?1, ?2, 1, ?3, ?4, ?5)
+        ON CONFLICT(profile_id, concept_id) DO UPDATE SET
+            attempts = attempts + 1,
+            correct = correct + excluded.correct,
+            mastery = mastery * (1.0 - ?6) + ?7 * ?6,
+            updated_at = excluded.updated_at
+        "#,
+        params![
+            profile_id,
+            concept_id,
+            correct as i32,
+            outcome * MASTERY_LEARNING_RATE,
+            now,
+            MASTERY_LEARNING_RATE,
+            outcome,
+        ],
+    )?;
+
+    conn.query_row(
+        "SELECT concept_id, attempts, correct, mastery FROM concept_mastery WHERE profile_id = ?1 AND concept_id = ?2",
+        params![profile_id, concept_id],
+        |row| {
+            Ok(ConceptMastery {
+                concept_id: row.get(0)?,
+                attempts: row.get(1)?,
+                correct: row.get(2)?,
+                mastery: row.get(3)?,
+            })
+        },
+    )
+}
+
+pub fn get_concept_mastery(conn: &Connection, profile_id: i64) -> Result<Vec<ConceptMastery>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT concept_id, attempts, correct, mastery
+        FROM concept_mastery
+        WHERE profile_id = ?1
+        ORDER BY mastery ASC
+        "#,
+    )?;
+
+    let entries = stmt.query_map(params![profile_id], |row| {
+        Ok(ConceptMastery {
+            concept_id: row.get(0)?,
+            attempts: row.get(1)?,
+            correct: row.get(2)?,
+            mastery: row.get(3)?,
+        })
+    })?;
+
+    entries.collect()
+}
+
+// ============================================================================
+// Glossary Repository
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub term: String,
+    pub definition: String,
+    pub source: String, // "builtin" or "llm"
+}
+
+pub fn get_glossary_entry(conn: &Connection, term: &str) -> Result<Option<GlossaryEntry>> {
+    conn.query_row(
+        "SELECT term, definition, source FROM glossary WHERE term = ?1",
+        params![term.to_lowercase()],
+        |row| {
+            Ok(GlossaryEntry {
+                term: row.get(0)?,
+                definition: row.get(1)?,
+                source: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+}
+
+pub fn upsert_glossary_entry(conn: &Connection, term: &str, definition: &str, source: &str) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO glossary (term, definition, source, created_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(term) DO UPDATE SET definition = excluded.definition, source = excluded.source
+        "#,
+        params![term.to_lowercase(), definition, source, now],
+    )?;
+
+    Ok(())
+}
+
+pub fn count_glossary_entries(conn: &Connection, source: &str) -> Result<i32> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM glossary WHERE source = ?1",
+        params![source],
+        |row| row.get(0),
+    )
+}
+
+// ============================================================================
+// Settings Repository
+// ============================================================================
+
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3) ON CONFLICT(key) DO UPDATE SET value = ?2, updated_at = ?3",
+        params![key, value, now],
+    )?;
+
+    Ok(())
+}
+
+/// Settings scoped to a single profile are stored as `profile:{id}:{key}` rows
+fn profile_setting_key(profile_id: i64, key: &str) -> String {
+    format!("profile:{}:{}", profile_id, key)
+}
+
+pub fn get_profile_setting(conn: &Connection, profile_id: i64, key: &str) -> Result<Option<String>> {
+    get_setting(conn, &profile_setting_key(profile_id, key))
+}
+
+pub fn set_profile_setting(conn: &Connection, profile_id: i64, key: &str, value: &str) -> Result<()> {
+    set_setting(conn, &profile_setting_key(profile_id, key), value)
+}
+
+// ============================================================================
+// Activity Log Repository
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityTime {
+    pub activity: String,
+    pub sessions: i32,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeBreakdown {
+    pub period_days: Option<i32>,
+    pub total_seconds: i64,
+    pub by_activity: Vec<ActivityTime>,
+}
+
+pub fn start_activity(conn: &Connection, profile_id: i64, activity: &str) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO activity_log (profile_id, activity, started_at) VALUES (?1, ?2, ?3)",
+        params![profile_id, activity, now],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Close an open activity session, returning its duration. Returns None if the
+/// session doesn't exist or was already stopped.
+pub fn stop_activity(conn: &Connection, session_id: i64) -> Result<Option<i64>> {
+    let started_at: Option<String> = conn
+        .query_row(
+            "SELECT started_at FROM activity_log WHERE id = ?1 AND ended_at IS NULL",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let Some(started_at) = started_at else {
+        return Ok(None);
+    };
+
+    let now = chrono::Utc::now();
+    let duration = chrono::DateTime::parse_from_rfc3339(&started_at)
+        .map(|start| (now - start.with_timezone(&chrono::Utc)).num_seconds().max(0))
+        .unwrap_or(0);
+
+    conn.execute(
+        "UPDATE activity_log SET ended_at = ?1, duration_seconds = ?2 WHERE id = ?3",
+        params![now.to_rfc3339(), duration, session_id],
+    )?;
+
+    Ok(Some(duration))
+}
+
+/// Close sessions an earlier run left open, e.g. when the app was killed. How long
+/// they lasted is unknown, so they stay out of the breakdown. Returns how many were closed.
+pub fn close_open_activities(conn: &Connection) -> Result<usize> {
+    conn.execute("UPDATE activity_log SET ended_at = started_at WHERE ended_at IS NULL", [])
+}
+
+/// Time spent per activity over the last `days` days (all time if None).
+/// Sessions that were never stopped are not counted.
+pub fn get_time_breakdown(conn: &Connection, profile_id: i64, days: Option<i32>) -> Result<TimeBreakdown> {
+    let cutoff_str = days
+        .map(|d| (chrono::Utc::now() - chrono::Duration::days(d as i64)).to_rfc3339())
+        .unwrap_or_default();
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT activity, COUNT(*), SUM(duration_seconds)
+        FROM activity_log
+        WHERE profile_id = ?1 AND started_at >= ?2 AND duration_seconds IS NOT NULL
+        GROUP BY activity
+        ORDER BY SUM(duration_seconds) DESC
+        "#,
+    )?;
+
+    let by_activity: Vec<ActivityTime> = stmt
+        .query_map(params![profile_id, cutoff_str], |row| {
+            Ok(ActivityTime {
+                activity: row.get(0)?,
+                sessions: row.get(1)?,
+                total_seconds: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+            })
+        })?
+        .collect::<Result<_>>()?;
+
+    Ok(TimeBreakdown {
+        period_days: days,
+        total_seconds: by_activity.iter().map(|a| a.total_seconds).sum(),
+        by_activity,
+    })
+}
+
+// ============================================================================
+// Usage Counts Repository
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureUsage {
+    pub feature: String,
+    pub total: i64,
+    pub active_days: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyUsage {
+    /// `YYYY-MM-DD` in UTC
+    pub day: String,
+    pub total: i64,
+}
+
+pub fn increment_usage(conn: &Connection, feature: &str, day: &str) -> Result<()> {
+    conn.execute(
+        r#"
+        INSERT INTO usage_counts (feature, day, count) VALUES (?1, ?2, 1)
+        ON CONFLICT(feature, day) DO UPDATE SET count = count + 1
+        "#,
+        params![feature, day],
+    )?;
+    Ok(())
+}
+
+fn usage_cutoff(days: Option<i32>) -> String {
+    days.map(|d| (chrono::Utc::now() - chrono::Duration::days(d as i64)).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Totals per feature over the last `days` days (all time if None), most used first
+pub fn get_feature_usage(conn: &Connection, days: Option<i32>) -> Result<Vec<FeatureUsage>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT feature, SUM(count), COUNT(*)
+        FROM usage_counts
+        WHERE day >= ?1
+        GROUP BY feature
+        ORDER BY SUM(count) DESC
+        "#,
+    )?;
+
+    let usage = stmt.query_map(params![usage_cutoff(days)], |row| {
+        Ok(FeatureUsage {
+            feature: row.get(0)?,
+            total: row.get(1)?,
+            active_days: row.get(2)?,
+        })
+    })?;
+
+    usage.collect()
+}
+
+/// Total events per day over the last `days` days (all time if None), oldest first
+pub fn get_daily_usage(conn: &Connection, days: Option<i32>) -> Result<Vec<DailyUsage>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT day, SUM(count)
+        FROM usage_counts
+        WHERE day >= ?1
+        GROUP BY day
+        ORDER BY day ASC
+        "#,
+    )?;
+
+    let usage = stmt.query_map(params![usage_cutoff(days)], |row| {
+        Ok(DailyUsage {
+            day: row.get(0)?,
+            total: row.get(1)?,
+        })
+    })?;
+
+    usage.collect()
+}
+
+pub fn clear_usage(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM usage_counts", [])
+}
+
+// ============================================================================
+// Session Snapshot Repository
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub id: i64,
+    pub profile_id: Option<i64>,
+    pub kind: String,
+    pub session_key: String,
+    /// JSON state as saved by the UI
+    pub payload: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Insert or overwrite the snapshot for `(kind, session_key)`
+pub fn upsert_session_snapshot(
+    conn: &Connection,
+    profile_id: Option<i64>,
+    kind: &str,
+    session_key: &str,
+    payload: &str,
+) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO session_snapshots (profile_id, kind, session_key, payload, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+        ON CONFLICT(kind, session_key) DO UPDATE SET payload = ?4, updated_at = ?5
+        "#,
+        params![profile_id, kind, session_key, payload, now],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_session_snapshots(conn: &Connection) -> Result<Vec<SessionSnapshot>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, profile_id, kind, session_key, payload, created_at, updated_at FROM session_snapshots ORDER BY updated_at DESC",
+    )?;
+
+    let snapshots = stmt
+        .query_map([], |row| {
+            Ok(SessionSnapshot {
+                id: row.get(0)?,
+                profile_id: row.get(1)?,
+                kind: row.get(2)?,
+                session_key: row.get(3)?,
+                payload: row.get(4)?,
+                created_at: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        })?
+        .collect::<Result<_>>()?;
+
+    Ok(snapshots)
+}
+
+/// Returns false if there was nothing to delete
+pub fn delete_session_snapshot(conn: &Connection, kind: &str, session_key: &str) -> Result<bool> {
+    let deleted = conn.execute(
+        "DELETE FROM session_snapshots WHERE kind = ?1 AND session_key = ?2",
+        params![kind, session_key],
+    )?;
+    Ok(deleted > 0)
+}
+
+/// Drop snapshots not updated in the last `max_age_days` days, returning how many were removed
+pub fn delete_stale_session_snapshots(conn: &Connection, max_age_days: i32) -> Result<usize> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(max_age_days as i64)).to_rfc3339();
+    conn.execute("DELETE FROM session_snapshots WHERE updated_at < ?1", params![cutoff])
+}
+
+// ============================================================================
+// Coach Response Cache Repository
+// ============================================================================
+
+/// A cached reply younger than `max_age_hours`
+pub fn get_cached_response(conn: &Connection, key: &str, max_age_hours: i64) -> Result<Option<String>> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::hours(max_age_hours)).to_rfc3339();
+
+    conn.query_row(
+        "SELECT response FROM coach_response_cache WHERE cache_key = ?1 AND created_at >= ?2",
+        params![key, cutoff],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn put_cached_response(conn: &Connection, key: &str, model: &str, response: &str) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO coach_response_cache (cache_key, model, response, created_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(cache_key) DO UPDATE SET model = excluded.model, response = excluded.response, created_at = excluded.created_at
+        "#,
+        params![key, model, response, now],
+    )?;
+    Ok(())
+}
+
+/// Remove replies older than `max_age_hours`, returning how many were removed
+pub fn delete_expired_cached_responses(conn: &Connection, max_age_hours: i64) -> Result<usize> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::hours(max_age_hours)).to_rfc3339();
+    conn.execute("DELETE FROM coach_response_cache WHERE created_at < ?1", params![cutoff])
+}
+
+pub fn clear_cached_responses(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM coach_response_cache", [])
+}
+
+// ============================================================================
+// Coach Trace Repository
+// ============================================================================
+
+/// One message of the prompt as it was sent to the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceMessage {
+    pub role: String,
+    pub content: String,
+}
+
+/// A tool the model called while answering, with what it was given back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceToolCall {
+    pub name: String,
+    pub arguments: String,
+    pub result: String,
+}
+
+/// Everything exchanged with the model for one coach reply
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CoachTrace {
+    #[serde(default)]
+    pub id: i64,
+    pub conversation_id: Option<i64>,
+    /// Saved message the reply became; unset until the conversation is saved
+    pub message_id: Option<i64>,
+    pub model: String,
+    pub prompt: Vec<TraceMessage>,
+    pub tool_calls: Vec<TraceToolCall>,
+    /// Raw text of each model response, tool-calling rounds included
+    pub responses: Vec<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+pub fn insert_coach_trace(conn: &Connection, trace: &CoachTrace) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO coach_traces (conversation_id, message_id, model, prompt, tool_calls, responses, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+        params![
+            trace.conversation_id,
+            trace.message_id,
+            trace.model,
+            serde_json::to_string(&trace.prompt).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&trace.tool_calls).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&trace.responses).unwrap_or_else(|_| "[]".to_string()),
+            now,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Link a trace to the message its reply was saved as
+pub fn attach_coach_trace(conn: &Connection, trace_id: i64, message_id: i64) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE coach_traces SET message_id = ?1 WHERE id = ?2",
+        params![message_id, trace_id],
+    )?;
+    Ok(updated > 0)
+}
+
+pub fn get_message_trace(conn: &Connection, message_id: i64) -> Result<Option<CoachTrace>> {
+    conn.query_row(
+        r#"
+        SELECT id, conversation_id, message_id, model, prompt, tool_calls, responses, created_at
+        FROM coach_traces
+        WHERE message_id = ?1
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+        params![message_id],
+        |row| {
+            Ok(CoachTrace {
+                id: row.get(0)?,
+                conversation_id: row.get(1)?,
+                message_id: row.get(2)?,
+                model: row.get(3)?,
+                prompt: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                tool_calls: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+                responses: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+                created_at: row.get(7)?,
+            })
+        },
+    )
+    .optional()
+}
+
+pub fn clear_coach_traces(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM coach_traces", [])
+}
+
+// ============================================================================
+// Engine Bench Repository
+// ============================================================================
+
+/// One run of the engine bench suite; `report` is the full `BenchReport` as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchRunRecord {
+    pub id: i64,
+    pub engine_name: String,
+    pub engine_version: String,
+    pub nps: i64,
+    pub agreement: f64,
+    pub report: String,
+    pub created_at: String,
+}
+
+pub fn insert_bench_run(conn: &Connection, record: &BenchRunRecord) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO bench_runs (engine_name, engine_version, nps, agreement, report, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+        params![record.engine_name, record.engine_version, record.nps, record.agreement, record.report, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// The latest `limit` bench runs, newest first
+pub fn get_bench_runs(conn: &Connection, limit: i64) -> Result<Vec<BenchRunRecord>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, engine_name, engine_version, nps, agreement, report, created_at
+        FROM bench_runs
+        ORDER BY id DESC
+        LIMIT ?1
+        "#,
+    )?;
+
+    let records = stmt.query_map(params![limit], |row| {
+        Ok(BenchRunRecord {
+            id: row.get(0)?,
+            engine_name: row.get(1)?,
+            engine_version: row.get(2)?,
+            nps: row.get(3)?,
+            agreement: row.get(4)?,
+            report: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    })?;
+
+    records.collect()
+}
+
+// ============================================================================
+// Saved Positions Repository
+// ============================================================================
+
+/// A position the player imported or bookmarked and kept for later analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPosition {
+    pub id: i64,
+    pub profile_id: i64,
+    pub fen: String,
+    /// Where `moves` start; the same as `fen` when the position came without moves
+    pub initial_fen: String,
+    /// UCI moves leading from `initial_fen` to `fen`
+    pub moves: Vec<String>,
+    /// "fen", "lichess_url", "pgn" or "puzzle"
+    pub source: String,
+    /// Where it was saved from: "import", "game", "puzzle", "coach" or "analysis"
+    pub origin: String,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    /// Puzzle answer from `fen` in UCI, the opponent's replies included; empty
+    /// for positions without a known line
+    #[serde(default)]
+    pub solution_line: Vec<String>,
+}
+
+const SAVED_POSITION_COLUMNS: &str =
+    "id, profile_id, fen, initial_fen, moves, source, origin, title, note, tags, created_at, solution_line";
+
+fn row_to_saved_position(row: &rusqlite::Row) -> Result<SavedPosition> {
+    Ok(SavedPosition {
+        id: row.get(0)?,
+        profile_id: row.get(1)?,
+        fen: row.get(2)?,
+        initial_fen: row.get(3)?,
+        moves: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+        source: row.get(5)?,
+        origin: row.get(6)?,
+        title: row.get(7)?,
+        note: row.get(8)?,
+        tags: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
+        created_at: row.get(10)?,
+        solution_line: serde_json::from_str(&row.get::<_, String>(11)?).unwrap_or_default(),
+    })
+}
+
+pub fn insert_saved_position(conn: &Connection, position: &SavedPosition) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO saved_positions (profile_id, fen, initial_fen, moves, source, origin, title, note, tags, created_at, solution_line)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+        "#,
+        params![
+            position.profile_id,
+            position.fen,
+            position.initial_fen,
+            serde_json::to_string(&position.moves).unwrap_or_else(|_| "[]".to_string()),
+            position.source,
+            position.origin,
+            position.title,
+            position.note,
+            serde_json::to_string(&position.tags).unwrap_or_else(|_| "[]".to_string()),
+            now,
+            serde_json::to_string(&position.solution_line).unwrap_or_else(|_| "[]".to_string()),
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_saved_position(conn: &Connection, id: i64) -> Result<Option<SavedPosition>> {
+    conn.query_row(
+        &format!("SELECT {} FROM saved_positions WHERE id = ?1", SAVED_POSITION_COLUMNS),
+        params![id],
+        row_to_saved_position,
+    )
+    .optional()
+}
+
+/// Saved positions, newest first, optionally only those carrying `tag` (case-insensitive)
+/// or saved from `origin`
+pub fn get_saved_positions(
+    conn: &Connection,
+    profile_id: i64,
+    tag: Option<&str>,
+    origin: Option<&str>,
+) -> Result<Vec<SavedPosition>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM saved_positions
+        WHERE profile_id = ?1 AND (?2 IS NULL OR origin = ?2)
+        ORDER BY created_at DESC, id DESC
+        "#,
+        SAVED_POSITION_COLUMNS
+    ))?;
+
+    let positions = stmt.query_map(params![profile_id, origin], row_to_saved_position)?;
+
+    positions
+        .filter(|position| match (position, tag) {
+            (Ok(p), Some(tag)) => p.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Replace a saved position's note and tags
+pub fn update_saved_position(
+    conn: &Connection,
+    profile_id: i64,
+    id: i64,
+    note: Option<&str>,
+    tags: &[String],
+) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE saved_positions SET note = ?1, tags = ?2 WHERE id = ?3 AND profile_id = ?4",
+        params![
+            note,
+            serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string()),
+            id,
+            profile_id
+        ],
+    )?;
+    Ok(updated > 0)
+}
+
+pub fn delete_saved_position(conn: &Connection, profile_id: i64, id: i64) -> Result<bool> {
+    let deleted = conn.execute(
+        "DELETE FROM saved_positions WHERE id = ?1 AND profile_id = ?2",
+        params![id, profile_id],
+    )?;
+    Ok(deleted > 0)
+}
+
+// ============================================================================
+// Training Plan Repository
+// ============================================================================
+
+/// Something scheduled for the player: training on a theme, or reviewing one of their games
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingPlanItem {
+    pub id: i64,
+    pub profile_id: i64,
+    /// "training" or "game_review"
+    pub kind: String,
+    pub title: String,
+    /// Canonical exercise type, for training items
+    pub theme: Option<String>,
+    /// Game to review, for review items
+    pub game_id: Option<i64>,
+    pub note: Option<String>,
+    /// `YYYY-MM-DD`; none for "whenever"
+    pub due_date: Option<String>,
+    /// "player" or "coach"
+    pub source: String,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+}
+
+const TRAINING_PLAN_COLUMNS: &str =
+    "id, profile_id, kind, title, theme, game_id, note, due_date, source, completed_at, created_at";
+
+fn row_to_training_plan_item(row: &rusqlite::Row) -> Result<TrainingPlanItem> {
+    Ok(TrainingPlanItem {
+        id: row.get(0)?,
+        profile_id: row.get(1)?,
+        kind: row.get(2)?,
+        title: row.get(3)?,
+        theme: row.get(4)?,
+        game_id: row.get(5)?,
+        note: row.get(6)?,
+        due_date: row.get(7)?,
+        source: row.get(8)?,
+        completed_at: row.get(9)?,
+        created_at: row.get(10)?,
+    })
+}
+
+pub fn insert_training_plan_item(conn: &Connection, item: &TrainingPlanItem) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO training_plan_items (profile_id, kind, title, theme, game_id, note, due_date, source, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        "#,
+        params![
+            item.profile_id,
+            item.kind,
+            item.title,
+            item.theme,
+            item.game_id,
+            item.note,
+            item.due_date,
+            item.source,
+            now,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// The plan, open items first by due date (undated last), then completed ones newest first
+pub fn get_training_plan(conn: &Connection, profile_id: i64, include_completed: bool) -> Result<Vec<TrainingPlanItem>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM training_plan_items
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND (?2 OR completed_at IS NULL)
+        ORDER BY completed_at IS NOT NULL, due_date IS NULL, due_date, completed_at DESC, id
+        "#,
+        TRAINING_PLAN_COLUMNS
+    ))?;
+
+    let items = stmt.query_map(params![profile_id, include_completed], row_to_training_plan_item)?;
+    items.collect()
+}
+
+pub fn complete_training_plan_item(conn: &Connection, profile_id: i64, id: i64) -> Result<bool> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = conn.execute(
+        "UPDATE training_plan_items SET completed_at = ?1 WHERE id = ?2 AND profile_id = ?3 AND completed_at IS NULL AND deleted_at IS NULL",
+        params![now, id, profile_id],
+    )?;
+    Ok(updated > 0)
+}
+
+pub fn delete_training_plan_item(conn: &Connection, profile_id: i64, id: i64) -> Result<bool> {
+    let deleted = conn.execute(
+        "DELETE FROM training_plan_items WHERE id = ?1 AND profile_id = ?2",
+        params![id, profile_id],
+    )?;
+    Ok(deleted > 0)
+}
+
+/// A question on one side, the answer on the other, optionally about a position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Flashcard {
+    pub id: i64,
+    pub profile_id: i64,
+    pub front: String,
+    pub back: String,
+    pub fen: Option<String>,
+    /// "player" or "coach"
+    pub source: String,
+    pub created_at: String,
+}
+
+pub fn insert_flashcard(conn: &Connection, card: &Flashcard) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO flashcards (profile_id, front, back, fen, source, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![card.profile_id, card.front, card.back, card.fen, card.source, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Flashcards, newest first
+pub fn get_flashcards(conn: &Connection, profile_id: i64) -> Result<Vec<Flashcard>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, profile_id, front, back, fen, source, created_at
+        FROM flashcards
+        WHERE profile_id = ?1
+        ORDER BY created_at DESC, id DESC
+        "#,
+    )?;
+
+    let cards = stmt.query_map(params![profile_id], |row| {
+        Ok(Flashcard {
+            id: row.get(0)?,
+            profile_id: row.get(1)?,
+            front: row.get(2)?,
+            back: row.get(3)?,
+            fen: row.get(4)?,
+            source: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    })?;
+    cards.collect()
+}
+
+pub fn delete_flashcard(conn: &Connection, profile_id: i64, id: i64) -> Result<bool> {
+    let deleted = conn.execute(
+        "DELETE FROM flashcards WHERE id = ?1 AND profile_id = ?2",
+        params![id, profile_id],
+    )?;
+    Ok(deleted > 0)
+}
+
+/// A change the coach proposed, waiting on the player. `change` is the JSON the
+/// training plan module reads back when the player approves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingCoachAction {
+    pub id: i64,
+    pub profile_id: i64,
+    pub conversation_id: Option<i64>,
+    pub change: String,
+    /// One line for the approval prompt
+    pub summary: String,
+    /// "pending", "approved" or "declined"
+    pub status: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+const PENDING_COACH_ACTION_COLUMNS: &str =
+    "id, profile_id, conversation_id, change, summary, status, created_at, resolved_at";
+
+fn row_to_pending_coach_action(row: &rusqlite::Row) -> Result<PendingCoachAction> {
+    Ok(PendingCoachAction {
+        id: row.get(0)?,
+        profile_id: row.get(1)?,
+        conversation_id: row.get(2)?,
+        change: row.get(3)?,
+        summary: row.get(4)?,
+        status: row.get(5)?,
+        created_at: row.get(6)?,
+        resolved_at: row.get(7)?,
+    })
+}
+
+pub fn insert_pending_coach_action(
+    conn: &Connection,
+    profile_id: i64,
+    conversation_id: Option<i64>,
+    change: &str,
+    summary: &str,
+) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO pending_coach_actions (profile_id, conversation_id, change, summary, status, created_at)
+        VALUES (?1, ?2, ?3, ?4, 'pending', ?5)
+        "#,
+        params![profile_id, conversation_id, change, summary, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_pending_coach_action(conn: &Connection, id: i64) -> Result<Option<PendingCoachAction>> {
+    conn.query_row(
+        &format!("SELECT {} FROM pending_coach_actions WHERE id = ?1", PENDING_COACH_ACTION_COLUMNS),
+        params![id],
+        row_to_pending_coach_action,
+    )
+    .optional()
+}
+
+/// Actions still waiting on the player, oldest first, optionally from one conversation only
+pub fn get_pending_coach_actions(
+    conn: &Connection,
+    profile_id: i64,
+    conversation_id: Option<i64>,
+) -> Result<Vec<PendingCoachAction>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM pending_coach_actions
+        WHERE profile_id = ?1 AND status = 'pending' AND (?2 IS NULL OR conversation_id = ?2)
+        ORDER BY id
+        "#,
+        PENDING_COACH_ACTION_COLUMNS
+    ))?;
+
+    let actions = stmt.query_map(params![profile_id, conversation_id], row_to_pending_coach_action)?;
+    actions.collect()
+}
+
+/// Settle a pending action; false if it was already approved or declined
+pub fn resolve_pending_coach_action(conn: &Connection, id: i64, status: &str) -> Result<bool> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = conn.execute(
+        "UPDATE pending_coach_actions SET status = ?1, resolved_at = ?2 WHERE id = ?3 AND status = 'pending'",
+        params![status, now, id],
+    )?;
+    Ok(updated > 0)
+}
+
+// ============================================================================
+// Operation Journal
+// ============================================================================
+
+/// A destructive action, undoable for a short while. The rows it removed are
+/// soft-deleted, with `deleted_at` set, until the window closes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationRecord {
+    pub id: i64,
+    pub profile_id: i64,
+    /// "delete_game", "delete_conversation" or "reset_plan"
+    pub kind: String,
+    /// Rows removed from the table `kind` works on
+    pub target_ids: Vec<i64>,
+    /// One line for the undo prompt
+    pub label: String,
+    pub created_at: String,
+    pub undone_at: Option<String>,
+}
+
+const OPERATION_COLUMNS: &str = "id, profile_id, kind, target_ids, label, created_at, undone_at";
+
+fn row_to_operation(row: &rusqlite::Row) -> Result<OperationRecord> {
+    let target_ids: String = row.get(3)?;
+    Ok(OperationRecord {
+        id: row.get(0)?,
+        profile_id: row.get(1)?,
+        kind: row.get(2)?,
+        target_ids: serde_json::from_str(&target_ids).unwrap_or_default(),
+        label: row.get(4)?,
+        created_at: row.get(5)?,
+        undone_at: row.get(6)?,
+    })
+}
+
+pub fn insert_operation(conn: &Connection, profile_id: i64, kind: &str, target_ids: &[i64], label: &str) -> Result<i64> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let target_ids = serde_json::to_string(target_ids).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT INTO operation_journal (profile_id, kind, target_ids, label, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![profile_id, kind, target_ids, label, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_operation(conn: &Connection, id: i64) -> Result<Option<OperationRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM operation_journal WHERE id = ?1", OPERATION_COLUMNS),
+        params![id],
+        row_to_operation,
+    )
+    .optional()
+}
+
+/// The newest operation not yet undone, made at or after `since` (RFC 3339)
+pub fn get_last_undoable_operation(conn: &Connection, profile_id: i64, since: &str) -> Result<Option<OperationRecord>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM operation_journal WHERE profile_id = ?1 AND undone_at IS NULL AND created_at >= ?2 ORDER BY id DESC LIMIT 1",
+            OPERATION_COLUMNS
+        ),
+        params![profile_id, since],
+        row_to_operation,
+    )
+    .optional()
+}
+
+pub fn mark_operation_undone(conn: &Connection, id: i64) -> Result<bool> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = conn.execute(
+        "UPDATE operation_journal SET undone_at = ?1 WHERE id = ?2 AND undone_at IS NULL",
+        params![now, id],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Soft-delete `ids` in `table`, or restore them when `deleted` is false.
+/// Returns the ids that changed; rows of other profiles are left alone.
+pub fn set_soft_deleted(conn: &Connection, table: &str, profile_id: i64, ids: &[i64], deleted: bool) -> Result<Vec<i64>> {
+    let (value, current) = if deleted {
+        (Some(chrono::Utc::now().to_rfc3339()), "IS NULL")
+    } else {
+        (None, "IS NOT NULL")
+    };
+    let sql = format!(
+        "UPDATE {} SET deleted_at = ?1 WHERE id = ?2 AND profile_id = ?3 AND deleted_at {}",
+        table, current
+    );
+    let mut changed = Vec::new();
+    for id in ids {
+        if conn.execute(&sql, params![value, id, profile_id])? > 0 {
+            changed.push(*id);
+        }
+    }
+    Ok(changed)
+}
+
+/// Delete for good the rows soft-deleted before `before` (RFC 3339), with what
+/// hangs off them, and forget journal entries older than that
+pub fn purge_soft_deleted(conn: &Connection, before: &str) -> Result<usize> {
+    let games = "SELECT id FROM games WHERE deleted_at < ?1";
+    let conversations = "SELECT id FROM conversations WHERE deleted_at < ?1";
+
+    let tx = conn.unchecked_transaction()?;
+    let mut deleted = tx.execute(&format!("DELETE FROM game_analyses WHERE game_id IN ({})", games), params![before])?;
+    deleted += tx.execute(&format!("DELETE FROM opening_deviations WHERE game_id IN ({})", games), params![before])?;
+    deleted += tx.execute(&format!("DELETE FROM exercise_queue WHERE game_id IN ({})", games), params![before])?;
+    deleted += tx.execute(&format!("DELETE FROM training_plan_items WHERE game_id IN ({})", games), params![before])?;
+    deleted += tx.execute("DELETE FROM games WHERE deleted_at < ?1", params![before])?;
+    deleted += tx.execute(&format!("DELETE FROM messages WHERE conversation_id IN ({})", conversations), params![before])?;
+    deleted += tx.execute(
+        &format!("DELETE FROM pending_coach_actions WHERE conversation_id IN ({})", conversations),
+        params![before],
+    )?;
+    deleted += tx.execute("DELETE FROM conversations WHERE deleted_at < ?1", params![before])?;
+    deleted += tx.execute("DELETE FROM training_plan_items WHERE deleted_at < ?1", params![before])?;
+    tx.execute("DELETE FROM operation_journal WHERE created_at < ?1", params![before])?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+// ============================================================================
+// Opening Walkthroughs
+// ============================================================================
+
+/// How far a profile has got with one opening line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpeningProgress {
+    pub opening_key: String,
+    pub attempts: i32,
+    pub completions: i32,
+    /// Fewest wrong answers in a finished walkthrough
+    pub best_mistakes: Option<i32>,
+    pub last_completed_at: Option<String>,
+}
+
+const OPENING_PROGRESS_COLUMNS: &str = "opening_key, attempts, completions, best_mistakes, last_completed_at";
+
+fn row_to_opening_progress(row: &rusqlite::Row) -> Result<OpeningProgress> {
+    Ok(OpeningProgress {
+        opening_key: row.get(0)?,
+        attempts: row.get(1)?,
+        completions: row.get(2)?,
+        best_mistakes: row.get(3)?,
+        last_completed_at: row.get(4)?,
+    })
+}
+
+pub fn record_opening_attempt(conn: &Connection, profile_id: i64, opening_key: &str) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO opening_progress (profile_id, opening_key, attempts, updated_at)
+        VALUES (?1, ?2, 1, ?3)
+        ON CONFLICT(profile_id, opening_key) DO UPDATE SET
+            attempts = attempts + 1,
+            updated_at = excluded.updated_at
+        "#,
+        params![profile_id, opening_key, now],
+    )?;
+    Ok(())
+}
+
+/// Count a finished walkthrough and keep the best run. A completion without a
+/// recorded attempt counts as one.
+pub fn record_opening_completion(conn: &Connection, profile_id: i64, opening_key: &str, mistakes: i32) -> Result<OpeningProgress> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        r#"
+        INSERT INTO opening_progress (profile_id, opening_key, attempts, completions, best_mistakes, last_completed_at, updated_at)
+        VALUES (?1, ?2, 1, 1, ?3, ?4, ?4)
+        ON CONFLICT(profile_id, opening_key) DO UPDATE SET
+            completions = completions + 1,
+            best_mistakes = MIN(COALESCE(best_mistakes, excluded.best_mistakes), excluded.best_mistakes),
+            last_completed_at = excluded.last_completed_at,
+            updated_at = excluded.updated_at
+        "#,
+        params![profile_id, opening_key, mistakes, now],
+    )?;
+
+    conn.query_row(
+        &format!("SELECT {} FROM opening_progress WHERE profile_id = ?1 AND opening_key = ?2", OPENING_PROGRESS_COLUMNS),
+        params![profile_id, opening_key],
+        row_to_opening_progress,
+    )
+}
+
+/// Every line the profile has started, most recently touched first
+pub fn get_opening_progress(conn: &Connection, profile_id: i64) -> Result<Vec<OpeningProgress>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM opening_progress WHERE profile_id = ?1 ORDER BY updated_at DESC",
+        OPENING_PROGRESS_COLUMNS
+    ))?;
+    let progress = stmt.query_map(params![profile_id], row_to_opening_progress)?;
+    progress.collect()
+}
+
+/// Where a saved game first left a line of the player's repertoire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpeningDeviation {
+    pub game_id: i64,
+    pub profile_id: i64,
+    pub opening_key: String,
+    pub opening_name: String,
+    /// Index of the deviating move in the game
+    pub ply: usize,
+    /// The deviating move's number as written before it, "4." or "4...", counted
+    /// from the game's starting position
+    #[serde(default)]
+    pub move_number: String,
+    /// The player left the line, rather than their opponent
+    pub by_player: bool,
+    /// The deviating move and the line's move, in SAN
+    pub played: String,
+    pub expected: String,
+    /// How much worse the played move is than the line's, from the mover's side
+    pub centipawn_loss: i32,
+    pub played_worse: bool,
+    /// How to carry on, in SAN: the rest of the line after the player's own
+    /// deviation, the best reply after the opponent's
+    pub recommended: Vec<String>,
+    /// Position before the deviating move
+    pub fen: String,
+    pub created_at: String,
+}
+
+const DEVIATION_COLUMNS: &str =
+    "game_id, profile_id, opening_key, opening_name, ply, by_player, played, expected, centipawn_loss, played_worse, recommended, fen, created_at";
+
+/// Reads `DEVIATION_COLUMNS` followed by the game's initial FEN
+fn row_to_deviation(row: &rusqlite::Row) -> Result<OpeningDeviation> {
+    let recommended_json: String = row.get(10)?;
+    let ply = row.get::<_, i64>(4)? as usize;
+    let initial_fen: Option<String> = row.get(13)?;
+    Ok(OpeningDeviation {
+        game_id: row.get(0)?,
+        profile_id: row.get(1)?,
+        opening_key: row.get(2)?,
+        opening_name: row.get(3)?,
+        ply,
+        move_number: chess_core::move_label(initial_fen.as_deref().unwrap_or_default(), ply),
+        by_player: row.get(5)?,
+        played: row.get(6)?,
+        expected: row.get(7)?,
+        centipawn_loss: row.get(8)?,
+        played_worse: row.get(9)?,
+        recommended: serde_json::from_str(&recommended_json).unwrap_or_default(),
+        fen: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+/// Store the deviation found in `deviation.game_id`, replacing an earlier one
+pub fn save_opening_deviation(conn: &Connection, deviation: &OpeningDeviation) -> Result<()> {
+    let recommended_json = serde_json::to_string(&deviation.recommended).unwrap_or_else(|_| "[]".to_string());
+
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO opening_deviations ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            DEVIATION_COLUMNS
+        ),
+        params![
+            deviation.game_id,
+            deviation.profile_id,
+            deviation.opening_key,
+            deviation.opening_name,
+            deviation.ply as i64,
+            deviation.by_player,
+            deviation.played,
+            deviation.expected,
+            deviation.centipawn_loss,
+            deviation.played_worse,
+            recommended_json,
+            deviation.fen,
+            deviation.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// The profile's deviations from games still saved, newest first
+pub fn get_recent_deviations(conn: &Connection, profile_id: i64, limit: i32) -> Result<Vec<OpeningDeviation>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}, (SELECT initial_fen FROM games WHERE games.id = opening_deviations.game_id)
+        FROM opening_deviations
+        WHERE profile_id = ?1 AND game_id IN (SELECT id FROM games WHERE deleted_at IS NULL)
+        ORDER BY created_at DESC, game_id DESC
+        LIMIT ?2
+        "#,
+        DEVIATION_COLUMNS
+    ))?;
+    let deviations = stmt.query_map(params![profile_id, limit], row_to_deviation)?;
+    deviations.collect()
+}
+
+// ============================================================================
+// Exercise Queue
+// ============================================================================
+
+/// An exercise generated ahead of time from a position in one of the player's games
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedExercise {
+    pub id: i64,
+    pub profile_id: i64,
+    pub game_id: i64,
+    /// Half-moves played before the position
+    pub ply: usize,
+    /// Serialized exercise
+    pub exercise: String,
+    pub created_at: String,
+    pub served_at: Option<String>,
+}
+
+const QUEUED_EXERCISE_COLUMNS: &str = "id, profile_id, game_id, ply, exercise, created_at, served_at";
+
+fn row_to_queued_exercise(row: &rusqlite::Row) -> Result<QueuedExercise> {
+    Ok(QueuedExercise {
+        id: row.get(0)?,
+        profile_id: row.get(1)?,
+        game_id: row.get(2)?,
+        ply: row.get::<_, i64>(3)? as usize,
+        exercise: row.get(4)?,
+        created_at: row.get(5)?,
+        served_at: row.get(6)?,
+    })
+}
+
+/// Queue an exercise on the position at `ply` of `game_id`. Returns false when
+/// that position was queued before, served or not.
+pub fn enqueue_exercise(conn: &Connection, profile_id: i64, game_id: i64, ply: usize, exercise: &str) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO exercise_queue (profile_id, game_id, ply, exercise, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![profile_id, game_id, ply as i64, exercise, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// The profile's unserved exercises from games still saved, oldest first
+pub fn get_ready_exercises(conn: &Connection, profile_id: i64) -> Result<Vec<QueuedExercise>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {} FROM exercise_queue
+        WHERE profile_id = ?1 AND served_at IS NULL AND game_id IN (SELECT id FROM games WHERE deleted_at IS NULL)
+        ORDER BY id
+        "#,
+        QUEUED_EXERCISE_COLUMNS
+    ))?;
+    let exercises = stmt.query_map(params![profile_id], row_to_queued_exercise)?;
+    exercises.collect()
+}
+
+pub fn count_ready_exercises(conn: &Connection, profile_id: i64) -> Result<u32> {
+    conn.query_row(
+        r#"
+        SELECT COUNT(*) FROM exercise_queue
+        WHERE profile_id = ?1 AND served_at IS NULL AND game_id IN (SELECT id FROM games WHERE deleted_at IS NULL)
+        "#,
+        params![profile_id],
+        |row| row.get(0),
+    )
+}
+
+/// Every (game, ply) the profile has had queued, so generation can skip them
+pub fn get_queued_positions(conn: &Connection, profile_id: i64) -> Result<std::collections::HashSet<(i64, usize)>> {
+    let mut stmt = conn.prepare("SELECT game_id, ply FROM exercise_queue WHERE profile_id = ?1")?;
+    let positions = stmt.query_map(params![profile_id], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+    positions.collect()
+}
+
+pub fn get_queued_exercise(conn: &Connection, id: i64) -> Result<Option<QueuedExercise>> {
+    conn.query_row(
+        &format!("SELECT {} FROM exercise_queue WHERE id = ?1", QUEUED_EXERCISE_COLUMNS),
+        params![id],
+        row_to_queued_exercise,
+    )
+    .optional()
+}
+
+/// Take exercises out of the ready queue; they keep resolving by id for the session
+pub fn mark_exercises_served(conn: &Connection, ids: &[i64]) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.unchecked_transaction()?;
+    for id in ids {
+        tx.execute("UPDATE exercise_queue SET served_at = ?1 WHERE id = ?2 AND served_at IS NULL", params![now, id])?;
+    }
+    tx.commit()
+}
+
+// ============================================================================
+// Exercise Post-mortems
+// ============================================================================
+
+/// Why a wrong try fails, by the engine's reply to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Refutation {
+    /// The move tried, in SAN
+    pub tried: String,
+    /// The engine's best reply in SAN; none when the try ends the game
+    pub reply: Option<String>,
+    /// How much worse the try is than the solution, from the solver's side
+    pub centipawn_loss: i32,
+    pub summary: String,
+}
+
+/// The card shown once an exercise is solved or failed, kept with its result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExercisePostMortem {
+    pub result_id: i64,
+    /// The stored solution in SAN
+    pub solution: Option<String>,
+    /// The exercise's own explanation
+    pub explanation: String,
+    pub refutations: Vec<Refutation>,
+    /// The coach's take on the whole exercise, when it could be reached
+    pub elaboration: Option<String>,
+    /// "coach" when the elaboration was written by the model, "engine" otherwise
+    pub source: String,
+    pub created_at: String,
+}
+
+const POSTMORTEM_COLUMNS: &str = "result_id, solution, explanation, refutations, elaboration, source, created_at";
+
+fn row_to_postmortem(row: &rusqlite::Row) -> Result<ExercisePostMortem> {
+    let refutations_json: String = row.get(3)?;
+    Ok(ExercisePostMortem {
+        result_id: row.get(0)?,
+        solution: row.get(1)?,
+        explanation: row.get(2)?,
+        refutations: serde_json::from_str(&refutations_json).unwrap_or_default(),
+        elaboration: row.get(4)?,
+        source: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Store the card for `postmortem.result_id`, replacing an earlier one
+pub fn save_exercise_postmortem(conn: &Connection, postmortem: &ExercisePostMortem) -> Result<()> {
+    let refutations_json = serde_json::to_string(&postmortem.refutations).unwrap_or_else(|_| "[]".to_string());
+
+    conn.execute(
+        r#"
+        INSERT INTO exercise_postmortems (result_id, solution, explanation, refutations, elaboration, source, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        ON CONFLICT(result_id) DO UPDATE SET
+            solution = excluded.solution,
+            explanation = excluded.explanation,
+            refutations = excluded.refutations,
+            elaboration = excluded.elaboration,
+            source = excluded.source,
+            created_at = excluded.created_at
+        "#,
+        params![
+            postmortem.result_id,
+            postmortem.solution,
+            postmortem.explanation,
+            refutations_json,
+            postmortem.elaboration,
+            postmortem.source,
+            postmortem.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get_exercise_postmortem(conn: &Connection, result_id: i64) -> Result<Option<ExercisePostMortem>> {
+    conn.query_row(
+        &format!("SELECT {} FROM exercise_postmortems WHERE result_id = ?1", POSTMORTEM_COLUMNS),
+        params![result_id],
+        row_to_postmortem,
+    )
+    .optional()
+}
+
+/// Cards for a session's results, in the order the exercises were attempted
+pub fn get_session_postmortems(conn: &Connection, session_id: i64) -> Result<Vec<ExercisePostMortem>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {} FROM exercise_postmortems
+        WHERE result_id IN (SELECT id FROM exercise_results WHERE session_id = ?1)
+        ORDER BY result_id ASC
+        "#,
+        POSTMORTEM_COLUMNS
+    ))?;
+    let postmortems = stmt.query_map(params![session_id], row_to_postmortem)?;
+    postmortems.collect()
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// Result rows of `PRAGMA integrity_check` - a single "ok" for a healthy database
+pub fn integrity_check(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get(0))?.collect::<Result<_>>()?;
+    Ok(rows)
+}
+
+/// Result rows of SQLite's quick check, which skips the index cross-checks of the full
+/// integrity check and runs in a fraction of the time; `["ok"]` means it found nothing
+pub fn quick_check(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA quick_check")?;
+    let rows = stmt.query_map([], |row| row.get(0))?.collect::<Result<_>>()?;
+    Ok(rows)
+}
+
+// ============================================================================
+// Storage Maintenance
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSize {
+    pub name: String,
+    pub rows: i64,
+    /// Bytes used by the table and its indexes; None if SQLite was built without `dbstat`
+    pub bytes: Option<i64>,
+}
+
+/// Total size of the database file in bytes
+pub fn database_size(conn: &Connection) -> Result<i64> {
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    Ok(page_count * page_size)
+}
+
+/// Row count and on-disk size of every table, largest first
+pub fn table_sizes(conn: &Connection) -> Result<Vec<TableSize>> {
+    let names: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+
+    let mut sizes = Vec::with_capacity(names.len());
+    for name in names {
+        // Names come from sqlite_master, so quoting them is enough
+        let rows: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name), [], |row| row.get(0))?;
+        let bytes = conn
+            .query_row(
+                r#"
+                SELECT SUM(pgsize) FROM dbstat
+                WHERE name = ?1 OR name IN (SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = ?1)
+                "#,
+                params![name],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .ok()
+            .flatten();
+        sizes.push(TableSize { name, rows, bytes });
+    }
+
+    sizes.sort_by_key(|t| std::cmp::Reverse(t.bytes.unwrap_or(0)));
+    Ok(sizes)
+}
+
+/// Rebuild the database file, returning the bytes reclaimed
+pub fn vacuum(conn: &Connection) -> Result<i64> {
+    let before = database_size(conn)?;
+    conn.execute_batch("VACUUM")?;
+    Ok(before - database_size(conn)?)
+}
+
+/// Delete the oldest conversations (and their messages and traces) beyond the newest `keep`
+pub fn prune_conversations(conn: &Connection, keep: i64) -> Result<usize> {
+    let stale = "SELECT id FROM conversations ORDER BY updated_at DESC, id DESC LIMIT -1 OFFSET ?1";
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        &format!("DELETE FROM messages WHERE conversation_id IN ({})", stale),
+        params![keep],
+    )?;
+    let deleted = tx.execute(&format!("DELETE FROM conversations WHERE id IN ({})", stale), params![keep])?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Delete every conversation with its messages, coach traces and unsent drafts.
+/// Cached coach replies go too, since they can quote what was discussed.
+pub fn wipe_conversations(conn: &Connection) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut deleted = tx.execute("DELETE FROM coach_traces", [])?;
+    deleted += tx.execute("DELETE FROM messages", [])?;
+    deleted += tx.execute("DELETE FROM conversations", [])?;
+    deleted += tx.execute("DELETE FROM pending_coach_actions", [])?;
+    deleted += tx.execute("DELETE FROM session_snapshots WHERE kind = 'coach_draft'", [])?;
+    deleted += tx.execute("DELETE FROM coach_response_cache", [])?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Delete every game with its analyses and any autosaved game in progress.
+/// Cached coach replies go too, since they can describe the games.
+pub fn wipe_games(conn: &Connection) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut deleted = tx.execute("DELETE FROM game_analyses", [])?;
+    deleted += tx.execute("DELETE FROM opening_deviations", [])?;
+    deleted += tx.execute("DELETE FROM exercise_queue", [])?;
+    deleted += tx.execute("DELETE FROM games", [])?;
+    deleted += tx.execute("DELETE FROM training_plan_items WHERE game_id IS NOT NULL", [])?;
+    deleted += tx.execute("DELETE FROM session_snapshots WHERE kind = 'game'", [])?;
+    deleted += tx.execute("DELETE FROM coach_response_cache", [])?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Empty every table, leaving the schema as a fresh install has it. The message
+/// index is emptied by its triggers as the messages go; deleting from its own
+/// tables directly would corrupt it.
+pub fn wipe_all_tables(conn: &Connection) -> Result<usize> {
+    let tables: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name NOT LIKE 'messages_fts%'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+
+    // Foreign keys can only be toggled outside a transaction; with every table emptied, order doesn't matter
+    conn.execute_batch("PRAGMA foreign_keys = OFF")?;
+    let wiped = (|| -> Result<usize> {
+        let tx = conn.unchecked_transaction()?;
+        let mut deleted = 0;
+        for table in &tables {
+            deleted += tx.execute(&format!("DELETE FROM \"{}\"", table), [])?;
+        }
+        // Reset AUTOINCREMENT counters, which would otherwise hint at how much was stored
+        let has_sequence: bool =
+            tx.query_row("SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'sqlite_sequence'", [], |row| row.get(0))?;
+        if has_sequence {
+            tx.execute("DELETE FROM sqlite_sequence", [])?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    })();
+    conn.execute_batch("PRAGMA foreign_keys = ON")?;
+    wiped
+}
+
+/// Run `wipe` with SQLite zeroing the freed pages, then compact the file so no
+/// deleted content is left behind in free pages
+pub fn securely<T>(conn: &Connection, wipe: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    conn.execute_batch("PRAGMA secure_delete = ON")?;
+    let result = wipe(conn).and_then(|value| conn.execute_batch("VACUUM").map(|_| value));
+    conn.execute_batch("PRAGMA secure_delete = OFF")?;
+    result
+}
+
+/// Drop the per-move detail of superseded analysis runs older than `days`. The run's engine,
+/// mistake counts and average centipawn loss are kept, so historical accuracy stays comparable.
+/// Archived runs have an empty `analysis`. A game's latest run is never archived.
+pub fn archive_old_analyses(conn: &Connection, days: i32) -> Result<usize> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+
+    conn.execute(
+        r#"
+        UPDATE game_analyses SET analysis = ''
+        WHERE analysis != ''
+          AND created_at < ?1
+          AND id NOT IN (SELECT MAX(id) FROM game_analyses GROUP BY game_id)
+        "#,
+        params![cutoff],
+    )
+}
+
+// ============================================================================
+// Player Stats (computed from data)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub current_elo: i32,
+    pub peak_elo: i32,
+    pub games_played: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+    pub win_rate: f64,
+    pub exercises_completed: i32,
+    pub exercises_solved: i32,
+    pub exercise_success_rate: f64,
+    pub streak: i32,
+    pub style: String,
+    pub weaknesses: Vec<String>,
+    pub strengths: Vec<String>,
+    pub as_white: ColorStats,
+    pub as_black: ColorStats,
+}
+
+/// Openings listed per color in the player stats
+const FAVORITE_OPENINGS: usize = 3;
+
+/// Results of the games played with one color
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorStats {
+    pub color: String,
+    pub games: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+    pub win_rate: f64,
+    /// Points scored as a percentage, counting draws as half
+    pub score: f64,
+    /// Average centipawn loss over the analysed games, from each game's latest analysis
+    pub average_centipawn_loss: Option<f64>,
+    pub analysed_games: i32,
+    /// Most played openings with this color, most games first
+    pub favorite_openings: Vec<OpeningScore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningScore {
+    pub opening: String,
+    pub games: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+    pub score: f64,
+}
+
+fn score_percent(games: i32, wins: i32, draws: i32) -> f64 {
+    if games > 0 {
+        (wins as f64 + draws as f64 / 2.0) / games as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Finished games the player had `color` ("white" or "black") in
+pub fn get_color_stats(conn: &Connection, profile_id: i64, color: &str) -> Result<ColorStats> {
+    let (games, wins, losses, draws, average_centipawn_loss, analysed_games) = conn.query_row(
+        r#"
+        SELECT
+            COUNT(*),
+            SUM(CASE WHEN g.result = 'win' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN g.result = 'loss' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN g.result = 'draw' THEN 1 ELSE 0 END),
+            AVG(latest.average_centipawn_loss),
+            COUNT(latest.game_id)
+        FROM games g
+        LEFT JOIN game_analyses latest ON latest.id = (
+            SELECT MAX(id) FROM game_analyses WHERE game_id = g.id
+        )
+        WHERE g.profile_id = ?1 AND g.deleted_at IS NULL AND g.player_color = ?2 AND g.result IN ('win', 'loss', 'draw')
+        "#,
+        params![profile_id, color],
+        |row| Ok((
+            row.get::<_, i32>(0)?,
+            row.get::<_, Option<i32>>(1)?.unwrap_or(0),
+            row.get::<_, Option<i32>>(2)?.unwrap_or(0),
+            row.get::<_, Option<i32>>(3)?.unwrap_or(0),
+            row.get::<_, Option<f64>>(4)?,
+            row.get::<_, i32>(5)?,
+        )),
+    )?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            opening_name,
+            COUNT(*) AS games,
+            SUM(CASE WHEN result = 'win' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN result = 'loss' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN result = 'draw' THEN 1 ELSE 0 END)
+        FROM games
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND player_color = ?2 AND result IN ('win', 'loss', 'draw') AND opening_name IS NOT NULL
+        GROUP BY opening_name
+        ORDER BY games DESC, opening_name
+        LIMIT ?3
+        "#,
+    )?;
+    let favorite_openings = stmt
+        .query_map(params![profile_id, color, FAVORITE_OPENINGS as i64], |row| {
+            let games: i32 = row.get(1)?;
+            let wins: i32 = row.get(2)?;
+            let draws: i32 = row.get(4)?;
+            Ok(OpeningScore {
+                opening: row.get(0)?,
+                games,
+                wins,
+                losses: row.get(3)?,
+                draws,
+                score: score_percent(games, wins, draws),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ColorStats {
+        color: color.to_string(),
+        games,
+        wins,
+        losses,
+        draws,
+        win_rate: if games > 0 { (wins as f64 / games as f64) * 100.0 } else { 0.0 },
+        score: score_percent(games, wins, draws),
+        average_centipawn_loss,
+        analysed_games,
+        favorite_openings,
+    })
+}
+
+pub fn get_player_stats(conn: &Connection, profile_id: i64) -> Result<Option<PlayerStats>> {
+    let profile = match get_profile_by_id(conn, profile_id)? {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+
+    // Get game stats
     let (wins, losses, draws): (i32, i32, i32) = conn.query_row(
         r#"
         SELECT
             SUM(CASE WHEN result = 'win' THEN 1 ELSE 0 END),
             SUM(CASE WHEN result = 'loss' THEN 1 ELSE 0 END),
             SUM(CASE WHEN result = 'draw' THEN 1 ELSE 0 END)
-        FROM games WHERE profile_id = ?1
+        FROM games WHERE profile_id = ?1 AND deleted_at IS NULL
         "#,
         params![profile_id],
         |row| Ok((
@@ -555,157 +3095,617 @@ pub fn get_player_stats(conn: &Connection, profile_id: i64) -> Result<Option<Pla
         r#"
         SELECT
             COUNT(*),
-            SUM(CASE WHEN solved = 1 THEN 1 ELSE 0 END)
-        FROM exercise_results WHERE profile_id = ?1
-        "#,
+            SUM(CASE WHEN solved = 1 THEN 1 ELSE 0 END)
+        FROM exercise_results WHERE profile_id = ?1 AND flagged = 0
+        "#,
+        params![profile_id],
+        |row| Ok((
+            row.get::<_, Option<i32>>(0)?.unwrap_or(0),
+            row.get::<_, Option<i32>>(1)?.unwrap_or(0),
+        )),
+    )?;
+
+    let exercise_success_rate = if exercises_completed > 0 {
+        (exercises_solved as f64 / exercises_completed as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(Some(PlayerStats {
+        current_elo: profile.current_elo,
+        peak_elo: profile.peak_elo,
+        games_played: total_games,
+        wins,
+        losses,
+        draws,
+        win_rate,
+        exercises_completed,
+        exercises_solved,
+        exercise_success_rate,
+        streak: profile.streak,
+        style: profile.style,
+        weaknesses: profile.weaknesses,
+        strengths: profile.strengths,
+        as_white: get_color_stats(conn, profile_id, "white")?,
+        as_black: get_color_stats(conn, profile_id, "black")?,
+    }))
+}
+
+// ============================================================================
+// Improvement Trend
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImprovementTrend {
+    pub elo_change: i32,
+    pub games_in_period: i32,
+    pub win_rate_in_period: f64,
+    pub exercises_in_period: i32,
+    pub exercise_success_rate_in_period: f64,
+}
+
+pub fn get_improvement_trend(conn: &Connection, profile_id: i64, days: i32) -> Result<ImprovementTrend> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    let cutoff_str = cutoff.to_rfc3339();
+
+    // Get games in period
+    let (games_count, wins): (i32, i32) = conn.query_row(
+        r#"
+        SELECT
+            COUNT(*),
+            SUM(CASE WHEN result = 'win' THEN 1 ELSE 0 END)
+        FROM games
+        WHERE profile_id = ?1 AND deleted_at IS NULL AND created_at >= ?2
+        "#,
+        params![profile_id, cutoff_str],
+        |row| Ok((
+            row.get::<_, Option<i32>>(0)?.unwrap_or(0),
+            row.get::<_, Option<i32>>(1)?.unwrap_or(0),
+        )),
+    )?;
+
+    let win_rate = if games_count > 0 { (wins as f64 / games_count as f64) * 100.0 } else { 0.0 };
+
+    // Get exercises in period
+    let (exercises_count, exercises_solved): (i32, i32) = conn.query_row(
+        r#"
+        SELECT
+            COUNT(*),
+            SUM(CASE WHEN solved = 1 THEN 1 ELSE 0 END)
+        FROM exercise_results
+        WHERE profile_id = ?1 AND created_at >= ?2 AND flagged = 0
+        "#,
+        params![profile_id, cutoff_str],
+        |row| Ok((
+            row.get::<_, Option<i32>>(0)?.unwrap_or(0),
+            row.get::<_, Option<i32>>(1)?.unwrap_or(0),
+        )),
+    )?;
+
+    let exercise_success_rate = if exercises_count > 0 {
+        (exercises_solved as f64 / exercises_count as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    // Calculate ELO change (simplified - based on win/loss ratio)
+    let elo_change = (wins - (games_count - wins)) * 15; // Rough estimate
+
+    Ok(ImprovementTrend {
+        elo_change,
+        games_in_period: games_count,
+        win_rate_in_period: win_rate,
+        exercises_in_period: exercises_count,
+        exercise_success_rate_in_period: exercise_success_rate,
+    })
+}
+
+// ============================================================================
+// Theme Performance
+// ============================================================================
+
+/// Attempts at most in each half of the trend comparison
+const TREND_ATTEMPTS: usize = 10;
+/// Fewer recent attempts than this are too few to call a trend
+const MIN_TREND_ATTEMPTS: usize = 3;
+/// Change in solve rate, in percentage points, that counts as a trend
+const TREND_THRESHOLD: f64 = 10.0;
+
+/// How the player does on one exercise theme
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemePerformance {
+    pub exercise_type: String,
+    pub attempts: i32,
+    pub solved: i32,
+    pub solve_rate: f64,
+    pub avg_time_seconds: f64,
+    pub avg_hints_used: f64,
+    /// Solve rate over the latest attempts, against the ones just before
+    pub recent_solve_rate: Option<f64>,
+    pub previous_solve_rate: Option<f64>,
+    /// "improving", "stable", "declining" or "new" when there are too few attempts to tell
+    pub trend: String,
+}
+
+fn solve_rate(outcomes: &[bool]) -> f64 {
+    if outcomes.is_empty() {
+        return 0.0;
+    }
+    outcomes.iter().filter(|s| **s).count() as f64 / outcomes.len() as f64 * 100.0
+}
+
+/// Compare the latest attempts with the ones before them. `outcomes` is oldest first.
+/// Returns the trend and the recent and previous solve rates it was based on.
+pub fn solve_trend(outcomes: &[bool]) -> (&'static str, Option<f64>, Option<f64>) {
+    let window = (outcomes.len() / 2).min(TREND_ATTEMPTS);
+    if window < MIN_TREND_ATTEMPTS {
+        return ("new", None, None);
+    }
+    let recent = solve_rate(&outcomes[outcomes.len() - window..]);
+    let previous = solve_rate(&outcomes[outcomes.len() - 2 * window..outcomes.len() - window]);
+    let trend = if recent - previous >= TREND_THRESHOLD {
+        "improving"
+    } else if previous - recent >= TREND_THRESHOLD {
+        "declining"
+    } else {
+        "stable"
+    };
+    (trend, Some(recent), Some(previous))
+}
+
+/// Exercise results grouped by theme, weakest first: lowest solve rate, then
+/// declining before steady, then slowest. `days` limits the look back.
+pub fn get_theme_performance(conn: &Connection, profile_id: i64, days: Option<i32>) -> Result<Vec<ThemePerformance>> {
+    let cutoff = days
+        .map(|d| (chrono::Utc::now() - chrono::Duration::days(d as i64)).to_rfc3339())
+        .unwrap_or_default();
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT exercise_type, solved, time_seconds, hints_used
+        FROM exercise_results
+        WHERE profile_id = ?1 AND created_at >= ?2 AND flagged = 0
+        ORDER BY created_at, id
+        "#,
+    )?;
+    let rows = stmt.query_map(params![profile_id, cutoff], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?, row.get::<_, i32>(2)?, row.get::<_, i32>(3)?))
+    })?;
+
+    // Outcomes, total time and total hints per theme, in first-seen order
+    let mut themes: Vec<(String, Vec<bool>, i64, i64)> = vec![];
+    for row in rows {
+        let (exercise_type, solved, time_seconds, hints_used) = row?;
+        let index = match themes.iter().position(|t| t.0 == exercise_type) {
+            Some(i) => i,
+            None => {
+                themes.push((exercise_type, vec![], 0, 0));
+                themes.len() - 1
+            }
+        };
+        let theme = &mut themes[index];
+        theme.1.push(solved);
+        theme.2 += time_seconds as i64;
+        theme.3 += hints_used as i64;
+    }
+
+    let mut performance: Vec<ThemePerformance> = themes
+        .into_iter()
+        .map(|(exercise_type, outcomes, time, hints)| {
+            let attempts = outcomes.len();
+            let (trend, recent_solve_rate, previous_solve_rate) = solve_trend(&outcomes);
+            ThemePerformance {
+                exercise_type,
+                attempts: attempts as i32,
+                solved: outcomes.iter().filter(|s| **s).count() as i32,
+                solve_rate: solve_rate(&outcomes),
+                avg_time_seconds: time as f64 / attempts as f64,
+                avg_hints_used: hints as f64 / attempts as f64,
+                recent_solve_rate,
+                previous_solve_rate,
+                trend: trend.to_string(),
+            }
+        })
+        .collect();
+
+    performance.sort_by(|a, b| {
+        a.solve_rate
+            .total_cmp(&b.solve_rate)
+            .then_with(|| (b.trend == "declining").cmp(&(a.trend == "declining")))
+            .then_with(|| b.avg_time_seconds.total_cmp(&a.avg_time_seconds))
+    });
+    Ok(performance)
+}
+
+/// Whether the player solved each position of `exercise_type` unaided the last
+/// time they tried it, keyed by FEN. A solve the coach guided them through counts
+/// as a miss, so drills bring the position back.
+pub fn get_latest_position_results(
+    conn: &Connection,
+    profile_id: i64,
+    exercise_type: &str,
+) -> Result<std::collections::HashMap<String, bool>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT position_fen, solved AND scaffolding = '[]'
+        FROM exercise_results
+        WHERE profile_id = ?1 AND exercise_type = ?2 AND flagged = 0
+        ORDER BY created_at, id
+        "#,
+    )?;
+    let rows = stmt.query_map(params![profile_id, exercise_type], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+    })?;
+    // Later attempts overwrite earlier ones
+    rows.collect()
+}
+
+/// Positions the player has solved at least once, by FEN
+pub fn get_solved_positions(conn: &Connection, profile_id: i64) -> Result<std::collections::HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT position_fen FROM exercise_results WHERE profile_id = ?1 AND solved = 1 AND flagged = 0")?;
+    let rows = stmt.query_map(params![profile_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// Positions whose latest attempt failed no later than `due_before`, counted by
+/// exercise type, most first. A miss comes back for review once it has had time to settle.
+pub fn get_due_reviews(conn: &Connection, profile_id: i64, due_before: &str) -> Result<Vec<(String, i32)>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT r.exercise_type, COUNT(*)
+        FROM exercise_results r
+        WHERE r.profile_id = ?1 AND r.solved = 0 AND r.created_at <= ?2
+          AND r.id = (
+              SELECT id FROM exercise_results
+              WHERE profile_id = ?1 AND position_fen = r.position_fen AND flagged = 0
+              ORDER BY created_at DESC, id DESC LIMIT 1
+          )
+        GROUP BY r.exercise_type
+        ORDER BY COUNT(*) DESC, r.exercise_type
+        "#,
+    )?;
+    let rows = stmt.query_map(params![profile_id, due_before], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+// ============================================================================
+// Weakness History
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeaknessEntry {
+    pub exercise_type: String,
+    pub total_attempts: i32,
+    pub success_rate: f64,
+    pub recent_trend: String, // "improving", "stable", "declining", "new"
+}
+
+/// Theme performance over the last `days`, in the shape the coach tools use
+pub fn get_weakness_history(conn: &Connection, profile_id: i64, days: i32) -> Result<Vec<WeaknessEntry>> {
+    Ok(get_theme_performance(conn, profile_id, Some(days))?
+        .into_iter()
+        .map(|theme| WeaknessEntry {
+            exercise_type: theme.exercise_type,
+            total_attempts: theme.attempts,
+            success_rate: theme.solve_rate,
+            recent_trend: theme.trend,
+        })
+        .collect())
+}
+
+// ============================================================================
+// Opponent Strength
+// ============================================================================
+
+/// Lower bounds of the opponent rating bands after the first, so the bands are
+/// <1000, 1000-1400, 1400-1800, 1800-2200 and 2200+
+pub const OPPONENT_BAND_EDGES: [i32; 4] = [1000, 1400, 1800, 2200];
+
+/// Losing patterns reported per band
+const MAX_LOSING_PATTERNS: usize = 3;
+
+/// Results against opponents rated within one band
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpponentBandStats {
+    /// e.g. "<1000", "1400-1800" or "2200+"
+    pub band: String,
+    pub min_elo: Option<i32>,
+    /// Exclusive
+    pub max_elo: Option<i32>,
+    pub games: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+    pub win_rate: f64,
+    /// Average centipawn loss over the band's analysed games, from each game's latest analysis
+    pub average_centipawn_loss: Option<f64>,
+    pub analysed_games: i32,
+    pub avg_mistakes: f64,
+    pub avg_blunders: f64,
+    /// Most common ways the player lost in this band, most frequent first
+    pub losing_patterns: Vec<LosingPattern>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LosingPattern {
+    pub description: String,
+    pub games: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpponentStrengthStats {
+    pub player_elo: i32,
+    /// Only bands the player has games in, weakest opponents first
+    pub bands: Vec<OpponentBandStats>,
+    /// Games against opponents without a rating, which fit no band
+    pub unrated_games: i32,
+}
+
+fn band_bounds(band: usize) -> (Option<i32>, Option<i32>) {
+    let min = band.checked_sub(1).map(|i| OPPONENT_BAND_EDGES[i]);
+    (min, OPPONENT_BAND_EDGES.get(band).copied())
+}
+
+fn band_label(band: usize) -> String {
+    match band_bounds(band) {
+        (None, Some(max)) => format!("<{}", max),
+        (Some(min), Some(max)) => format!("{}-{}", min, max),
+        (Some(min), None) => format!("{}+", min),
+        (None, None) => "all".to_string(),
+    }
+}
+
+/// SQL expression giving the band index of `g.opponent_elo`
+fn band_case() -> String {
+    let whens: Vec<String> = OPPONENT_BAND_EDGES
+        .iter()
+        .enumerate()
+        .map(|(i, edge)| format!("WHEN g.opponent_elo < {} THEN {}", edge, i))
+        .collect();
+    format!("CASE {} ELSE {} END", whens.join(" "), OPPONENT_BAND_EDGES.len())
+}
+
+/// Win rate, accuracy and losing patterns of the player's finished games,
+/// sliced by the rating of the opponent
+pub fn get_opponent_strength_stats(conn: &Connection, profile_id: i64) -> Result<OpponentStrengthStats> {
+    let player_elo = get_profile_by_id(conn, profile_id)?.map(|p| p.current_elo).unwrap_or_default();
+    let band = band_case();
+
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT
+            {band} AS band,
+            COUNT(*),
+            SUM(CASE WHEN g.result = 'win' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN g.result = 'loss' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN g.result = 'draw' THEN 1 ELSE 0 END),
+            AVG(latest.average_centipawn_loss),
+            COUNT(latest.game_id),
+            AVG(g.mistakes),
+            AVG(g.blunders),
+            SUM(CASE WHEN g.result = 'loss' AND g.blunders > 0 THEN 1 ELSE 0 END)
+        FROM games g
+        LEFT JOIN game_analyses latest ON latest.id = (
+            SELECT MAX(id) FROM game_analyses WHERE game_id = g.id
+        )
+        WHERE g.profile_id = ?1 AND g.deleted_at IS NULL AND g.opponent_elo IS NOT NULL AND g.result IN ('win', 'loss', 'draw')
+        GROUP BY band
+        ORDER BY band
+        "#
+    ))?;
+    let rows = stmt.query_map(params![profile_id], |row| {
+        Ok((
+            row.get::<_, i64>(0)? as usize,
+            row.get::<_, i32>(1)?,
+            row.get::<_, i32>(2)?,
+            row.get::<_, i32>(3)?,
+            row.get::<_, i32>(4)?,
+            row.get::<_, Option<f64>>(5)?,
+            row.get::<_, i32>(6)?,
+            row.get::<_, f64>(7)?,
+            row.get::<_, f64>(8)?,
+            row.get::<_, i32>(9)?,
+        ))
+    })?;
+
+    let mut bands = vec![];
+    let mut blunder_losses = vec![];
+    for row in rows {
+        let (index, games, wins, losses, draws, acpl, analysed_games, avg_mistakes, avg_blunders, with_blunder) = row?;
+        let (min_elo, max_elo) = band_bounds(index);
+        bands.push(OpponentBandStats {
+            band: band_label(index),
+            min_elo,
+            max_elo,
+            games,
+            wins,
+            losses,
+            draws,
+            win_rate: if games > 0 { (wins as f64 / games as f64) * 100.0 } else { 0.0 },
+            average_centipawn_loss: acpl,
+            analysed_games,
+            avg_mistakes,
+            avg_blunders,
+            losing_patterns: vec![],
+        });
+        blunder_losses.push((index, with_blunder));
+    }
+
+    // How the losses ended and which openings they came from
+    let mut patterns: Vec<(usize, LosingPattern)> = blunder_losses
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(index, count)| (index, LosingPattern { description: "Losses that included a blunder".to_string(), games: count }))
+        .collect();
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {band} AS band, g.termination, COUNT(*)
+        FROM games g
+        WHERE g.profile_id = ?1 AND g.deleted_at IS NULL AND g.opponent_elo IS NOT NULL AND g.result = 'loss' AND g.termination IS NOT NULL
+        GROUP BY band, g.termination
+        "#
+    ))?;
+    for row in stmt.query_map(params![profile_id], |row| {
+        Ok((row.get::<_, i64>(0)? as usize, row.get::<_, String>(1)?, row.get::<_, i32>(2)?))
+    })? {
+        let (index, termination, count) = row?;
+        let description = format!("Lost by {}", termination.replace('_', " "));
+        patterns.push((index, LosingPattern { description, games: count }));
+    }
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {band} AS band, g.opening_name, COUNT(*)
+        FROM games g
+        WHERE g.profile_id = ?1 AND g.deleted_at IS NULL AND g.opponent_elo IS NOT NULL AND g.result = 'loss' AND g.opening_name IS NOT NULL
+        GROUP BY band, g.opening_name
+        HAVING COUNT(*) > 1
+        "#
+    ))?;
+    for row in stmt.query_map(params![profile_id], |row| {
+        Ok((row.get::<_, i64>(0)? as usize, row.get::<_, String>(1)?, row.get::<_, i32>(2)?))
+    })? {
+        let (index, opening, count) = row?;
+        patterns.push((index, LosingPattern { description: format!("Losses in the {}", opening), games: count }));
+    }
+
+    patterns.sort_by(|a, b| b.1.games.cmp(&a.1.games).then_with(|| a.1.description.cmp(&b.1.description)));
+    for stats in &mut bands {
+        stats.losing_patterns = patterns
+            .iter()
+            .filter(|(index, _)| band_bounds(*index).0 == stats.min_elo)
+            .map(|(_, pattern)| pattern.clone())
+            .take(MAX_LOSING_PATTERNS)
+            .collect();
+    }
+
+    let unrated_games: i32 = conn.query_row(
+        "SELECT COUNT(*) FROM games WHERE profile_id = ?1 AND deleted_at IS NULL AND opponent_elo IS NULL AND result IN ('win', 'loss', 'draw')",
         params![profile_id],
-        |row| Ok((
-            row.get::<_, Option<i32>>(0)?.unwrap_or(0),
-            row.get::<_, Option<i32>>(1)?.unwrap_or(0),
-        )),
+        |row| row.get(0),
     )?;
 
-    let exercise_success_rate = if exercises_completed > 0 {
-        (exercises_solved as f64 / exercises_completed as f64) * 100.0
-    } else {
-        0.0
-    };
-
-    Ok(Some(PlayerStats {
-        current_elo: profile.current_elo,
-        peak_elo: profile.peak_elo,
-        games_played: total_games,
-        wins,
-        losses,
-        draws,
-        win_rate,
-        exercises_completed,
-        exercises_solved,
-        exercise_success_rate,
-        streak: profile.streak,
-        style: profile.style,
-        weaknesses: profile.weaknesses,
-        strengths: profile.strengths,
-    }))
+    Ok(OpponentStrengthStats { player_elo, bands, unrated_games })
 }
 
 // ============================================================================
-// Improvement Trend
+// Time Class Stats
 // ============================================================================
 
+/// Results and accuracy over the games of one time class
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ImprovementTrend {
-    pub elo_change: i32,
-    pub games_in_period: i32,
-    pub win_rate_in_period: f64,
-    pub exercises_in_period: i32,
-    pub exercise_success_rate_in_period: f64,
+pub struct TimeClassStats {
+    /// "bullet", "blitz", "rapid" or "classical"
+    pub time_class: String,
+    pub games: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+    pub win_rate: f64,
+    /// Average centipawn loss over the class's analysed games, from each game's latest analysis
+    pub average_centipawn_loss: Option<f64>,
+    pub analysed_games: i32,
+    pub avg_mistakes: f64,
+    pub avg_blunders: f64,
+    /// Losses that included a blunder
+    pub blunder_losses: i32,
 }
 
-pub fn get_improvement_trend(conn: &Connection, profile_id: i64, days: i32) -> Result<ImprovementTrend> {
-    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
-    let cutoff_str = cutoff.to_rfc3339();
-
-    // Get games in period
-    let (games_count, wins): (i32, i32) = conn.query_row(
+/// The player's finished games split by time class, fastest first. Untimed games
+/// and those saved before time controls were tracked are left out.
+pub fn get_time_class_stats(conn: &Connection, profile_id: i64) -> Result<Vec<TimeClassStats>> {
+    let mut stmt = conn.prepare(
         r#"
         SELECT
+            g.time_class,
             COUNT(*),
-            SUM(CASE WHEN result = 'win' THEN 1 ELSE 0 END)
-        FROM games
-        WHERE profile_id = ?1 AND created_at >= ?2
+            SUM(CASE WHEN g.result = 'win' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN g.result = 'loss' THEN 1 ELSE 0 END),
+            SUM(CASE WHEN g.result = 'draw' THEN 1 ELSE 0 END),
+            AVG(latest.average_centipawn_loss),
+            COUNT(latest.game_id),
+            AVG(g.mistakes),
+            AVG(g.blunders),
+            SUM(CASE WHEN g.result = 'loss' AND g.blunders > 0 THEN 1 ELSE 0 END)
+        FROM games g
+        LEFT JOIN game_analyses latest ON latest.id = (
+            SELECT MAX(id) FROM game_analyses WHERE game_id = g.id
+        )
+        WHERE g.profile_id = ?1 AND g.deleted_at IS NULL AND g.time_class IS NOT NULL AND g.result IN ('win', 'loss', 'draw')
+        GROUP BY g.time_class
+        ORDER BY CASE g.time_class WHEN 'bullet' THEN 0 WHEN 'blitz' THEN 1 WHEN 'rapid' THEN 2 ELSE 3 END
         "#,
-        params![profile_id, cutoff_str],
-        |row| Ok((
-            row.get::<_, Option<i32>>(0)?.unwrap_or(0),
-            row.get::<_, Option<i32>>(1)?.unwrap_or(0),
-        )),
     )?;
+    let rows = stmt.query_map(params![profile_id], |row| {
+        let games: i32 = row.get(1)?;
+        let wins: i32 = row.get(2)?;
+        Ok(TimeClassStats {
+            time_class: row.get(0)?,
+            games,
+            wins,
+            losses: row.get(3)?,
+            draws: row.get(4)?,
+            win_rate: if games > 0 { (wins as f64 / games as f64) * 100.0 } else { 0.0 },
+            average_centipawn_loss: row.get(5)?,
+            analysed_games: row.get(6)?,
+            avg_mistakes: row.get(7)?,
+            avg_blunders: row.get(8)?,
+            blunder_losses: row.get(9)?,
+        })
+    })?;
+    rows.collect()
+}
 
-    let win_rate = if games_count > 0 { (wins as f64 / games_count as f64) * 100.0 } else { 0.0 };
+// ============================================================================
+// Progress Comparison
+// ============================================================================
 
-    // Get exercises in period
-    let (exercises_count, exercises_solved): (i32, i32) = conn.query_row(
+/// The player's finished games that have been analysed, newest first, each with its latest analysis
+pub fn get_analysed_games(conn: &Connection, profile_id: i64, limit: i32) -> Result<Vec<(Game, GameAnalysisRecord)>> {
+    let mut stmt = conn.prepare(
         r#"
-        SELECT
-            COUNT(*),
-            SUM(CASE WHEN solved = 1 THEN 1 ELSE 0 END)
-        FROM exercise_results
-        WHERE profile_id = ?1 AND created_at >= ?2
+        SELECT g.id
+        FROM games g
+        WHERE g.profile_id = ?1 AND g.deleted_at IS NULL AND g.result IN ('win', 'loss', 'draw')
+            AND EXISTS (SELECT 1 FROM game_analyses a WHERE a.game_id = g.id)
+        ORDER BY g.created_at DESC, g.id DESC
+        LIMIT ?2
         "#,
-        params![profile_id, cutoff_str],
-        |row| Ok((
-            row.get::<_, Option<i32>>(0)?.unwrap_or(0),
-            row.get::<_, Option<i32>>(1)?.unwrap_or(0),
-        )),
     )?;
+    let ids = stmt.query_map(params![profile_id, limit], |row| row.get::<_, i64>(0))?.collect::<Result<Vec<_>>>()?;
 
-    let exercise_success_rate = if exercises_count > 0 {
-        (exercises_solved as f64 / exercises_count as f64) * 100.0
-    } else {
-        0.0
-    };
-
-    // Calculate ELO change (simplified - based on win/loss ratio)
-    let elo_change = (wins - (games_count - wins)) * 15; // Rough estimate
-
-    Ok(ImprovementTrend {
-        elo_change,
-        games_in_period: games_count,
-        win_rate_in_period: win_rate,
-        exercises_in_period: exercises_count,
-        exercise_success_rate_in_period: exercise_success_rate,
-    })
+    let mut games = vec![];
+    for id in ids {
+        let Some(game) = get_game_by_id(conn, id)? else { continue };
+        if let Some(record) = get_game_analyses(conn, id)?.into_iter().next() {
+            games.push((game, record));
+        }
+    }
+    Ok(games)
 }
 
-// ============================================================================
-// Weakness History
-// ============================================================================
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WeaknessEntry {
+/// Exercise attempts on one theme and how many were missed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeOutcomes {
     pub exercise_type: String,
-    pub total_attempts: i32,
-    pub success_rate: f64,
-    pub recent_trend: String, // "improving", "stable", "declining"
+    pub attempts: i32,
+    pub missed: i32,
 }
 
-pub fn get_weakness_history(conn: &Connection, profile_id: i64, days: i32) -> Result<Vec<WeaknessEntry>> {
-    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
-    let cutoff_str = cutoff.to_rfc3339();
-
+/// Unflagged attempts per theme made from `from` up to, not including, `to`
+pub fn get_theme_outcomes(conn: &Connection, profile_id: i64, from: &str, to: Option<&str>) -> Result<Vec<ThemeOutcomes>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT
-            exercise_type,
-            COUNT(*) as attempts,
-            AVG(CASE WHEN solved = 1 THEN 1.0 ELSE 0.0 END) as success_rate
+        SELECT exercise_type, COUNT(*), SUM(CASE WHEN solved THEN 0 ELSE 1 END)
         FROM exercise_results
-        WHERE profile_id = ?1 AND created_at >= ?2
+        WHERE profile_id = ?1 AND flagged = 0 AND created_at >= ?2 AND (?3 IS NULL OR created_at < ?3)
         GROUP BY exercise_type
-        ORDER BY success_rate ASC
+        ORDER BY exercise_type
         "#,
     )?;
-
-    let entries = stmt.query_map(params![profile_id, cutoff_str], |row| {
-        let success_rate: f64 = row.get(2)?;
-        let trend = if success_rate < 0.5 {
-            "declining"
-        } else if success_rate < 0.75 {
-            "stable"
-        } else {
-            "improving"
-        };
-
-        Ok(WeaknessEntry {
-            exercise_type: row.get(0)?,
-            total_attempts: row.get(1)?,
-            success_rate: success_rate * 100.0,
-            recent_trend: trend.to_string(),
-        })
+    let rows = stmt.query_map(params![profile_id, from, to], |row| {
+        Ok(ThemeOutcomes { exercise_type: row.get(0)?, attempts: row.get(1)?, missed: row.get(2)? })
     })?;
-
-    entries.collect()
+    rows.collect()
 }
 
 #[cfg(test)]
@@ -735,12 +3735,47 @@ mod tests {
         // Update
         let mut updated = fetched.clone();
         updated.current_elo = 900;
-        update_profile(&conn, &updated).unwrap();
+        update_profile(&conn, &mut updated).unwrap();
 
         let refetched = get_profile_by_id(&conn, profile.id).unwrap().unwrap();
         assert_eq!(refetched.current_elo, 900);
     }
 
+    #[test]
+    fn test_stale_profile_update_conflicts() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+
+        let mut first = profile.clone();
+        let mut stale = profile.clone();
+        first.style = "Aggressive".to_string();
+        update_profile(&conn, &mut first).unwrap();
+
+        stale.current_elo = 1200;
+        let error = update_profile(&conn, &mut stale).unwrap_err();
+        assert!(is_profile_conflict(&error));
+
+        // Counters never read the profile, so none of them are lost
+        increment_exercises_completed(&conn, profile.id).unwrap();
+        increment_exercises_completed(&conn, profile.id).unwrap();
+        record_game_outcome(&conn, profile.id, 850, true).unwrap();
+        record_game_outcome(&conn, profile.id, 820, false).unwrap();
+
+        let saved = get_profile_by_id(&conn, profile.id).unwrap().unwrap();
+        assert_eq!(saved.style, "Aggressive");
+        assert_eq!(saved.exercises_completed, 2);
+        assert_eq!(saved.games_played, 2);
+        assert_eq!((saved.current_elo, saved.peak_elo, saved.streak), (820, 850, 0));
+
+        // The counters moved `updated_at` on, so `first` is stale now as well
+        first.name = "Renamed".to_string();
+        update_profile(&conn, &mut first).unwrap_err();
+        let mut fresh = saved;
+        fresh.name = "Renamed".to_string();
+        update_profile(&conn, &mut fresh).unwrap();
+        update_profile(&conn, &mut fresh).unwrap();
+    }
+
     #[test]
     fn test_settings() {
         let conn = setup_test_db();
@@ -754,4 +3789,519 @@ mod tests {
         let value = get_setting(&conn, "api_key").unwrap();
         assert_eq!(value, Some("new-key-456".to_string()));
     }
+
+    #[test]
+    fn test_quiz_scoring_and_mastery() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+
+        let question_id = insert_quiz_question(&conn, &QuizQuestion {
+            id: 0,
+            concept_id: "pin".to_string(),
+            question_type: "square".to_string(),
+            prompt: "Click the pinned piece".to_string(),
+            fen: Some("4k3/8/2n5/1B6/8/8/8/4K3 w - - 0 1".to_string()),
+            choices: vec![],
+            answer: "c6".to_string(),
+            explanation: "The knight cannot move without exposing the king.".to_string(),
+        })
+        .unwrap();
+
+        let session_id = create_quiz_session(&conn, profile.id, "pin", 1).unwrap();
+        assert!(record_quiz_answer(&conn, session_id, question_id, "c6", true).unwrap());
+        // Answering twice doesn't double count
+        assert!(!record_quiz_answer(&conn, session_id, question_id, "c6", true).unwrap());
+
+        let session = get_quiz_session(&conn, session_id).unwrap().unwrap();
+        assert_eq!((session.answered, session.score), (1, 1));
+
+        let mastery = update_concept_mastery(&conn, profile.id, "pin", true).unwrap();
+        assert!((mastery.mastery - MASTERY_LEARNING_RATE).abs() < 1e-9);
+        let mastery = update_concept_mastery(&conn, profile.id, "pin", false).unwrap();
+        assert_eq!((mastery.attempts, mastery.correct), (2, 1));
+        assert!(mastery.mastery < MASTERY_LEARNING_RATE);
+    }
+
+    #[test]
+    fn test_glossary_upsert() {
+        let conn = setup_test_db();
+
+        upsert_glossary_entry(&conn, "Zwischenzug", "An in-between move.", "builtin").unwrap();
+        let entry = get_glossary_entry(&conn, "zwischenzug").unwrap().unwrap();
+        assert_eq!(entry.definition, "An in-between move.");
+
+        upsert_glossary_entry(&conn, "zwischenzug", "An intermediate move.", "llm").unwrap();
+        let entry = get_glossary_entry(&conn, "ZWISCHENZUG").unwrap().unwrap();
+        assert_eq!(entry.source, "llm");
+        assert_eq!(count_glossary_entries(&conn, "builtin").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_activity_time_breakdown() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+
+        let session = start_activity(&conn, profile.id, "puzzles").unwrap();
+        let ten_minutes_ago = (chrono::Utc::now() - chrono::Duration::minutes(10)).to_rfc3339();
+        conn.execute(
+            "UPDATE activity_log SET started_at = ?1 WHERE id = ?2",
+            params![ten_minutes_ago, session],
+        )
+        .unwrap();
+
+        let duration = stop_activity(&conn, session).unwrap().unwrap();
+        assert!((599..=601).contains(&duration));
+        assert_eq!(stop_activity(&conn, session).unwrap(), None);
+
+        // Open sessions are excluded
+        let open = start_activity(&conn, profile.id, "playing").unwrap();
+
+        let breakdown = get_time_breakdown(&conn, profile.id, Some(7)).unwrap();
+        assert_eq!(breakdown.by_activity.len(), 1);
+        assert_eq!(breakdown.by_activity[0].activity, "puzzles");
+        assert_eq!(breakdown.total_seconds, duration);
+
+        // As are the ones left open by an earlier run, once closed at startup
+        assert_eq!(close_open_activities(&conn).unwrap(), 1);
+        assert_eq!(stop_activity(&conn, open).unwrap(), None);
+        assert_eq!(get_time_breakdown(&conn, profile.id, Some(7)).unwrap().total_seconds, duration);
+    }
+
+    #[test]
+    fn test_integrity_check() {
+        let conn = setup_test_db();
+        assert_eq!(integrity_check(&conn).unwrap(), vec!["ok".to_string()]);
+        assert_eq!(quick_check(&conn).unwrap(), vec!["ok".to_string()]);
+    }
+
+    #[test]
+    fn test_session_snapshots() {
+        let conn = setup_test_db();
+
+        upsert_session_snapshot(&conn, None, "game", "current", r#"{"fen":"a"}"#).unwrap();
+        upsert_session_snapshot(&conn, None, "game", "current", r#"{"fen":"b"}"#).unwrap();
+        upsert_session_snapshot(&conn, None, "coach_draft", "7", r#"{"text":"hi"}"#).unwrap();
+
+        let snapshots = get_session_snapshots(&conn).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        let game = snapshots.iter().find(|s| s.kind == "game").unwrap();
+        assert_eq!(game.payload, r#"{"fen":"b"}"#);
+
+        assert!(delete_session_snapshot(&conn, "game", "current").unwrap());
+        assert!(!delete_session_snapshot(&conn, "game", "current").unwrap());
+
+        let old = (chrono::Utc::now() - chrono::Duration::days(30)).to_rfc3339();
+        conn.execute("UPDATE session_snapshots SET updated_at = ?1", params![old]).unwrap();
+        assert_eq!(delete_stale_session_snapshots(&conn, 7).unwrap(), 1);
+        assert!(get_session_snapshots(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_game_analyses_are_versioned() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        let game = Game { result: "*".to_string(), ..Game::for_test(profile.id, &["e2e4"]) };
+        let game_id = create_game(&conn, &game).unwrap();
+
+        let mut record = GameAnalysisRecord {
+            id: 0,
+            game_id,
+            engine_name: "tacticus-eval".to_string(),
+            engine_version: "0.1.0".to_string(),
+            depth: 1,
+            analysis: "[]".to_string(),
+            mistakes: 2,
+            blunders: 1,
+            average_centipawn_loss: 80.0,
+            created_at: String::new(),
+        };
+        insert_game_analysis(&conn, &record).unwrap();
+        record.engine_version = "0.2.0".to_string();
+        record.blunders = 0;
+        insert_game_analysis(&conn, &record).unwrap();
+
+        let runs = get_game_analyses(&conn, game_id).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].engine_version, "0.2.0");
+        assert_eq!(runs[1].engine_version, "0.1.0");
+
+        let game = get_game_by_id(&conn, game_id).unwrap().unwrap();
+        assert_eq!(game.blunders, 0);
+        assert_eq!(game.mistakes, 2);
+    }
+
+    #[test]
+    fn test_opening_backfill_queries() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        let mut game = Game { result: "draw".to_string(), ..Game::for_test(profile.id, &["e2e4"]) };
+        let first = create_game(&conn, &game).unwrap();
+        let second = create_game(&conn, &game).unwrap();
+        game.opening_name = Some("French Defence".to_string());
+        create_game(&conn, &game).unwrap();
+
+        assert_eq!(count_games_missing_opening(&conn).unwrap(), 2);
+        let batch = get_games_missing_opening(&conn, 0, 1).unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].id, first);
+        assert_eq!(batch[0].moves, vec!["e2e4"]);
+        assert_eq!(get_games_missing_opening(&conn, first, 10).unwrap()[0].id, second);
+
+        let openings = vec![(first, "King's Pawn Game".to_string()), (second, "King's Pawn Game".to_string())];
+        assert_eq!(set_game_openings(&conn, &openings).unwrap(), 2);
+        assert_eq!(set_game_openings(&conn, &openings).unwrap(), 0);
+        assert_eq!(count_games_missing_opening(&conn).unwrap(), 0);
+        assert_eq!(get_game_by_id(&conn, first).unwrap().unwrap().opening_name.as_deref(), Some("King's Pawn Game"));
+    }
+
+    #[test]
+    fn test_opponent_strength_bands() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 1200).unwrap();
+        let game = |result: &str, opponent_elo: Option<i32>, blunders: i32, termination: Option<&str>| Game {
+            result: result.to_string(),
+            opponent_elo,
+            mistakes: 1,
+            blunders,
+            opening_name: Some("King's Pawn Game".to_string()),
+            termination: termination.map(str::to_string),
+            ..Game::for_test(profile.id, &["e2e4"])
+        };
+
+        create_game(&conn, &game("win", Some(900), 0, Some("checkmate"))).unwrap();
+        create_game(&conn, &game("win", Some(999), 0, None)).unwrap();
+        let analysed = create_game(&conn, &game("loss", Some(1500), 2, Some("checkmate"))).unwrap();
+        create_game(&conn, &game("loss", Some(1799), 1, Some("checkmate"))).unwrap();
+        create_game(&conn, &game("draw", Some(1400), 0, Some("stalemate"))).unwrap();
+        create_game(&conn, &game("win", Some(2400), 0, None)).unwrap();
+        create_game(&conn, &game("loss", None, 0, None)).unwrap();
+        create_game(&conn, &game("*", Some(1500), 0, None)).unwrap();
+
+        for acpl in [150.0, 90.0] {
+            let record = GameAnalysisRecord {
+                id: 0,
+                game_id: analysed,
+                engine_name: "tacticus-eval".to_string(),
+                engine_version: "0.1.0".to_string(),
+                depth: 1,
+                analysis: "[]".to_string(),
+                mistakes: 1,
+                blunders: 2,
+                average_centipawn_loss: acpl,
+                created_at: String::new(),
+            };
+            insert_game_analysis(&conn, &record).unwrap();
+        }
+
+        let stats = get_opponent_strength_stats(&conn, profile.id).unwrap();
+        assert_eq!(stats.player_elo, 1200);
+        assert_eq!(stats.unrated_games, 1);
+        let labels: Vec<&str> = stats.bands.iter().map(|b| b.band.as_str()).collect();
+        assert_eq!(labels, vec!["<1000", "1400-1800", "2200+"]);
+
+        let weaker = &stats.bands[0];
+        assert_eq!((weaker.games, weaker.wins), (2, 2));
+        assert_eq!(weaker.win_rate, 100.0);
+        assert!(weaker.losing_patterns.is_empty());
+        assert_eq!(weaker.average_centipawn_loss, None);
+
+        let stronger = &stats.bands[1];
+        assert_eq!((stronger.min_elo, stronger.max_elo), (Some(1400), Some(1800)));
+        assert_eq!((stronger.games, stronger.losses, stronger.draws), (3, 2, 1));
+        // Only the latest analysis of a game counts
+        assert_eq!(stronger.average_centipawn_loss, Some(90.0));
+        assert_eq!(stronger.analysed_games, 1);
+        assert_eq!(stronger.losing_patterns.len(), 3);
+        assert!(stronger.losing_patterns.iter().all(|p| p.games == 2));
+        assert!(stronger.losing_patterns.iter().any(|p| p.description == "Lost by checkmate"));
+    }
+
+    #[test]
+    fn test_time_class_stats_and_filters() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 1200).unwrap();
+        let game = |result: &str, blunders: i32, time_class: Option<&str>| Game {
+            result: result.to_string(),
+            opponent_elo: Some(1200),
+            blunders,
+            time_class: time_class.map(str::to_string),
+            ..Game::for_test(profile.id, &["e2e4"])
+        };
+
+        create_game(&conn, &game("loss", 2, Some("blitz"))).unwrap();
+        create_game(&conn, &game("win", 1, Some("blitz"))).unwrap();
+        create_game(&conn, &game("draw", 0, Some("classical"))).unwrap();
+        create_game(&conn, &game("win", 0, None)).unwrap();
+
+        let stats = get_time_class_stats(&conn, profile.id).unwrap();
+        let classes: Vec<&str> = stats.iter().map(|s| s.time_class.as_str()).collect();
+        assert_eq!(classes, vec!["blitz", "classical"]);
+        assert_eq!((stats[0].games, stats[0].wins, stats[0].losses), (2, 1, 1));
+        assert_eq!(stats[0].avg_blunders, 1.5);
+        assert_eq!(stats[0].blunder_losses, 1);
+        assert_eq!(stats[1].draws, 1);
+
+        assert_eq!(get_recent_games(&conn, profile.id, 10, None).unwrap().len(), 4);
+        assert_eq!(get_recent_games(&conn, profile.id, 10, Some("blitz")).unwrap().len(), 2);
+        let blitz_mistakes = get_games_with_mistakes(&conn, profile.id, 5, Some("blitz")).unwrap();
+        assert_eq!(blitz_mistakes.len(), 2);
+        assert!(get_games_with_mistakes(&conn, profile.id, 5, Some("classical")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_saved_positions_by_tag() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 1200).unwrap();
+        let position = |fen: &str, origin: &str, tags: &[&str]| SavedPosition {
+            id: 0,
+            profile_id: profile.id,
+            fen: fen.to_string(),
+            initial_fen: fen.to_string(),
+            moves: vec![],
+            source: "fen".to_string(),
+            origin: origin.to_string(),
+            title: None,
+            note: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: String::new(),
+            solution_line: vec![],
+        };
+
+        let endgame =
+            insert_saved_position(&conn, &position("8/8/8/4k3/8/8/4P3/4K3 w - - 0 1", "import", &["Endgame"])).unwrap();
+        insert_saved_position(&conn, &position("8/8/8/4k3/8/8/3PP3/4K3 w - - 0 1", "game", &["endgame", "study"])).unwrap();
+
+        assert_eq!(get_saved_positions(&conn, profile.id, None, None).unwrap().len(), 2);
+        assert_eq!(get_saved_positions(&conn, profile.id, Some("ENDGAME"), None).unwrap().len(), 2);
+        assert_eq!(get_saved_positions(&conn, profile.id, Some("endgame"), Some("game")).unwrap().len(), 1);
+        let study = get_saved_positions(&conn, profile.id, Some("study"), None).unwrap();
+        assert_eq!(study[0].tags, vec!["endgame", "study"]);
+
+        let tags = vec!["Endgame".to_string(), "Lucena".to_string()];
+        assert!(update_saved_position(&conn, profile.id, endgame, Some("Build a bridge"), &tags).unwrap());
+        let updated = get_saved_position(&conn, endgame).unwrap().unwrap();
+        assert_eq!((updated.note.as_deref(), updated.tags), (Some("Build a bridge"), tags));
+
+        assert!(!delete_saved_position(&conn, profile.id + 1, endgame).unwrap());
+        assert!(delete_saved_position(&conn, profile.id, endgame).unwrap());
+        assert_eq!(get_saved_positions(&conn, profile.id, None, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_player_stats_split_by_color() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 1200).unwrap();
+        let game = |color: &str, result: &str, opening: Option<&str>| Game {
+            result: result.to_string(),
+            player_color: color.to_string(),
+            opening_name: opening.map(str::to_string),
+            ..Game::for_test(profile.id, &["e2e4"])
+        };
+
+        create_game(&conn, &game("white", "win", Some("Italian Game"))).unwrap();
+        create_game(&conn, &game("white", "draw", Some("Italian Game"))).unwrap();
+        create_game(&conn, &game("white", "win", Some("London System"))).unwrap();
+        create_game(&conn, &game("white", "*", Some("London System"))).unwrap();
+        let lost = create_game(&conn, &game("black", "loss", Some("Sicilian Defence"))).unwrap();
+        create_game(&conn, &game("black", "loss", None)).unwrap();
+        insert_game_analysis(&conn, &GameAnalysisRecord {
+            id: 0,
+            game_id: lost,
+            engine_name: "tacticus-eval".to_string(),
+            engine_version: "0.1.0".to_string(),
+            depth: 1,
+            analysis: "[]".to_string(),
+            mistakes: 0,
+            blunders: 0,
+            average_centipawn_loss: 120.0,
+            created_at: String::new(),
+        })
+        .unwrap();
+
+        let stats = get_player_stats(&conn, profile.id).unwrap().unwrap();
+        let white = &stats.as_white;
+        assert_eq!((white.games, white.wins, white.draws, white.losses), (3, 2, 1, 0));
+        assert!((white.score - 250.0 / 3.0).abs() < 1e-9);
+        assert_eq!(white.average_centipawn_loss, None);
+        assert_eq!(white.favorite_openings[0].opening, "Italian Game");
+        assert_eq!(white.favorite_openings[0].score, 75.0);
+        assert_eq!(white.favorite_openings[1].opening, "London System");
+        assert_eq!(white.favorite_openings[1].games, 1);
+
+        let black = &stats.as_black;
+        assert_eq!((black.games, black.losses, black.score), (2, 2, 0.0));
+        assert_eq!(black.average_centipawn_loss, Some(120.0));
+        assert_eq!(black.analysed_games, 1);
+        assert_eq!(black.favorite_openings.len(), 1);
+    }
+
+    #[test]
+    fn test_theme_performance_ranks_and_trends() {
+        assert_eq!(solve_trend(&[true, false, true, true, false]).0, "new");
+        let improving = [false, false, false, true, true, true];
+        assert_eq!(solve_trend(&improving), ("improving", Some(100.0), Some(0.0)));
+        // Steady at 75% used to count as improving
+        assert_eq!(solve_trend(&[true, true, true, false, true, true, true, false]).0, "stable");
+        assert_eq!(solve_trend(&[true, true, true, true, false, false, true, false]).0, "declining");
+
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        let attempt = |exercise_type: &str, fen: &str, solved: bool, time_seconds: i32| {
+            let result = ExerciseResult {
+                id: 0,
+                profile_id: profile.id,
+                session_id: None,
+                exercise_type: exercise_type.to_string(),
+                difficulty: "Beginner".to_string(),
+                position_fen: fen.to_string(),
+                solved,
+                attempts: 1,
+                time_seconds,
+                hints_used: 0,
+                created_at: String::new(),
+                flagged: false,
+                flag_reason: None,
+                alternative_solution: false,
+                scaffolding: vec![],
+            };
+            record_exercise_result(&conn, &result).unwrap();
+        };
+        for solved in improving {
+            attempt("Tactics", "a", solved, 30);
+        }
+        attempt("Endgame", "b", true, 60);
+        attempt("Endgame", "c", false, 60);
+        attempt("Endgame", "b", false, 90);
+
+        let themes = get_theme_performance(&conn, profile.id, None).unwrap();
+        assert_eq!(themes[0].exercise_type, "Endgame");
+        assert_eq!(themes[0].trend, "new");
+        assert_eq!(themes[0].avg_time_seconds, 70.0);
+        assert_eq!(themes[1].exercise_type, "Tactics");
+        assert_eq!((themes[1].attempts, themes[1].solved), (6, 3));
+        assert_eq!(themes[1].trend, "improving");
+
+        let history = get_weakness_history(&conn, profile.id, 30).unwrap();
+        assert_eq!(history[1].recent_trend, "improving");
+
+        let latest = get_latest_position_results(&conn, profile.id, "Endgame").unwrap();
+        assert_eq!(latest.get("b"), Some(&false));
+        assert_eq!(latest.get("c"), Some(&false));
+        assert_eq!(latest.len(), 2);
+    }
+
+    #[test]
+    fn test_storage_maintenance() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+
+        for i in 0..5 {
+            let id = create_conversation(&conn, profile.id, Some(&format!("Chat {}", i)), None).unwrap();
+            let message_id = add_message(&conn, id, "user", "hello", None, None).unwrap();
+            let trace = CoachTrace {
+                id: 0,
+                conversation_id: Some(id),
+                message_id: Some(message_id),
+                model: "test".to_string(),
+                prompt: vec![],
+                tool_calls: vec![],
+                responses: vec![],
+                created_at: String::new(),
+            };
+            insert_coach_trace(&conn, &trace).unwrap();
+        }
+        assert_eq!(prune_conversations(&conn, 3).unwrap(), 2);
+        let traces: i64 = conn.query_row("SELECT COUNT(*) FROM coach_traces", [], |row| row.get(0)).unwrap();
+        assert_eq!(traces, 3);
+        assert_eq!(get_recent_conversations(&conn, profile.id, 10).unwrap().len(), 3);
+        let orphaned: i64 = conn
+            .query_row("SELECT COUNT(*) FROM messages WHERE conversation_id NOT IN (SELECT id FROM conversations)", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(orphaned, 0);
+
+        let sizes = table_sizes(&conn).unwrap();
+        let conversations = sizes.iter().find(|t| t.name == "conversations").unwrap();
+        assert_eq!(conversations.rows, 3);
+        assert!(database_size(&conn).unwrap() > 0);
+        assert!(vacuum(&conn).is_ok());
+    }
+
+    #[test]
+    fn test_selective_and_full_wipes() {
+        let conn = setup_test_db();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        let conversation = create_conversation(&conn, profile.id, Some("Chat"), None).unwrap();
+        add_message(&conn, conversation, "user", "hello", None, None).unwrap();
+        upsert_session_snapshot(&conn, Some(profile.id), "coach_draft", "chat", "{}").unwrap();
+        upsert_session_snapshot(&conn, Some(profile.id), "game", "current", "{}").unwrap();
+        set_setting(&conn, "api_key", "sk-secret").unwrap();
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+        };
+
+        assert_eq!(securely(&conn, wipe_conversations).unwrap(), 3);
+        assert_eq!((count("conversations"), count("messages"), count("session_snapshots")), (0, 0, 1));
+        assert_eq!(count("profiles"), 1);
+
+        assert_eq!(securely(&conn, wipe_games).unwrap(), 1);
+        assert_eq!(count("session_snapshots"), 0);
+
+        let conversation = create_conversation(&conn, profile.id, Some("Chat"), None).unwrap();
+        add_message(&conn, conversation, "assistant", "hello again", None, None).unwrap();
+        assert!(securely(&conn, wipe_all_tables).unwrap() >= 2);
+        assert_eq!((count("profiles"), count("settings")), (0, 0));
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM messages_fts WHERE messages_fts MATCH 'hello'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 0);
+        let fresh = create_profile(&conn, "Next User", "beginner", 800).unwrap();
+        assert_eq!(fresh.id, 1, "autoincrement counters are reset");
+    }
+
+    #[test]
+    fn test_training_sessions_group_results() {
+        let conn = setup_test_db();
+        let profile = create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        let session_id = create_training_session(&conn, profile.id, 3, &["Tactical Awareness".to_string()]).unwrap();
+
+        // The flagged solve doesn't count towards the session's totals
+        for (solved, hints, flagged) in [(true, 0, false), (false, 2, false), (true, 0, true)] {
+            let result = ExerciseResult {
+                id: 0,
+                profile_id: profile.id,
+                session_id: Some(session_id),
+                exercise_type: "Tactics".to_string(),
+                difficulty: "Beginner".to_string(),
+                position_fen: "8/8/8/8/8/3k4/3P4/3K4 w - - 0 1".to_string(),
+                solved,
+                attempts: 1,
+                time_seconds: 20,
+                hints_used: hints,
+                created_at: String::new(),
+                flagged,
+                flag_reason: None,
+                alternative_solution: false,
+                scaffolding: vec![],
+            };
+            record_exercise_result(&conn, &result).unwrap();
+        }
+
+        let session = finish_training_session(&conn, session_id).unwrap().unwrap();
+        assert_eq!(session.completed_exercises, 2);
+        assert_eq!(session.solved_exercises, 1);
+        assert_eq!(session.success_rate, 50.0);
+        assert_eq!(session.total_hints_used, 2);
+        assert!(session.finished_at.is_some());
+        assert_eq!(session.to_session_result().strategies_covered, vec!["Tactical Awareness"]);
+
+        let results = get_session_exercise_results(&conn, session_id).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].solved && !results[1].solved);
+        assert_eq!(session.performance_rating, None);
+        set_session_performance_rating(&conn, session_id, Some(600)).unwrap();
+        let sessions = get_training_sessions(&conn, profile.id, 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].performance_rating, Some(600));
+    }
 }
@@ -1,4 +1,7 @@
-use rusqlite::{Connection, Result};
+use chess_core::uid_at;
+use chess_trainer::{ExerciseDifficulty, ExerciseType};
+use rusqlite::{params, Connection, Result};
+use std::str::FromStr;
 
 /// Create all database tables
 pub fn create_tables(conn: &Connection) -> Result<()> {
@@ -42,6 +45,8 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
             opening_name TEXT,
             created_at TEXT NOT NULL,
             finished_at TEXT,
+            time_class TEXT,
+            deleted_at TEXT,
             FOREIGN KEY (profile_id) REFERENCES profiles(id)
         );
 
@@ -50,6 +55,35 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_games_opening_name ON games(opening_name);
         "#,
     )?;
+    // Games saved before results were checked on save have no termination reason
+    add_column_if_missing(conn, "games", "termination", "TEXT")?;
+    // Games saved before time controls were tracked have no class
+    add_column_if_missing(conn, "games", "time_class", "TEXT")?;
+    // Deleted games linger, hidden, while the deletion can still be undone
+    add_column_if_missing(conn, "games", "deleted_at", "TEXT")?;
+    // Cached coach summary (JSON), cleared whenever the game's analysis or opening changes
+    add_column_if_missing(conn, "games", "summary", "TEXT")?;
+
+    // Game analyses - every engine run over a game, tagged with the engine that produced it
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS game_analyses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id INTEGER NOT NULL,
+            engine_name TEXT NOT NULL,
+            engine_version TEXT NOT NULL,
+            depth INTEGER NOT NULL,
+            analysis TEXT NOT NULL,
+            mistakes INTEGER NOT NULL,
+            blunders INTEGER NOT NULL,
+            average_centipawn_loss REAL NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_game_analyses_game_id ON game_analyses(game_id);
+        "#,
+    )?;
 
     // Conversations table - chat sessions with coach
     conn.execute_batch(
@@ -61,12 +95,14 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
             context TEXT,
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL,
+            deleted_at TEXT,
             FOREIGN KEY (profile_id) REFERENCES profiles(id)
         );
 
         CREATE INDEX IF NOT EXISTS idx_conversations_profile_id ON conversations(profile_id);
         "#,
     )?;
+    add_column_if_missing(conn, "conversations", "deleted_at", "TEXT")?;
 
     // Messages table - individual chat messages
     conn.execute_batch(
@@ -86,12 +122,71 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         "#,
     )?;
 
-    // Exercise results table - training attempt records
+    // Full-text index over message content, kept in step with messages by triggers; filled
+    // from the existing messages the first time it is created
+    let indexed: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'messages_fts')",
+        [],
+        |row| row.get(0),
+    )?;
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+            content,
+            content = 'messages',
+            content_rowid = 'id',
+            tokenize = 'porter unicode61'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+            INSERT INTO messages_fts (rowid, content) VALUES (new.id, new.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+            INSERT INTO messages_fts (messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+            INSERT INTO messages_fts (messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+            INSERT INTO messages_fts (rowid, content) VALUES (new.id, new.content);
+        END;
+        "#,
+    )?;
+    if !indexed {
+        conn.execute_batch("INSERT INTO messages_fts (messages_fts) VALUES ('rebuild');")?;
+    }
+
+    // Training sessions table - one row per workout, mirroring chess-trainer's SessionResult
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS training_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            total_exercises INTEGER NOT NULL,
+            completed_exercises INTEGER NOT NULL DEFAULT 0,
+            solved_exercises INTEGER NOT NULL DEFAULT 0,
+            success_rate REAL NOT NULL DEFAULT 0,
+            total_attempts INTEGER NOT NULL DEFAULT 0,
+            total_hints_used INTEGER NOT NULL DEFAULT 0,
+            duration_seconds INTEGER NOT NULL DEFAULT 0,
+            strategies_covered TEXT NOT NULL DEFAULT '[]',
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            performance_rating INTEGER,
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_training_sessions_profile_id ON training_sessions(profile_id);
+        "#,
+    )?;
+    // Databases from before session performance ratings
+    add_column_if_missing(conn, "training_sessions", "performance_rating", "INTEGER")?;
+
+    // Exercise results table - training attempt records, grouped by training session when known
     conn.execute_batch(
         r#"
         CREATE TABLE IF NOT EXISTS exercise_results (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             profile_id INTEGER NOT NULL,
+            session_id INTEGER,
             exercise_type TEXT NOT NULL,
             difficulty TEXT NOT NULL,
             position_fen TEXT NOT NULL,
@@ -100,14 +195,414 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
             time_seconds INTEGER NOT NULL,
             hints_used INTEGER NOT NULL,
             created_at TEXT NOT NULL,
-            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+            flagged INTEGER NOT NULL DEFAULT 0,
+            flag_reason TEXT,
+            alternative_solution INTEGER NOT NULL DEFAULT 0,
+            scaffolding TEXT NOT NULL DEFAULT '[]',
+            FOREIGN KEY (profile_id) REFERENCES profiles(id),
+            FOREIGN KEY (session_id) REFERENCES training_sessions(id)
         );
-
+        "#,
+    )?;
+    // Databases created before sessions were tracked lack the link column
+    add_column_if_missing(conn, "exercise_results", "session_id", "INTEGER REFERENCES training_sessions(id)")?;
+    // Databases from before suspicious attempts were flagged
+    add_column_if_missing(conn, "exercise_results", "flagged", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "exercise_results", "flag_reason", "TEXT")?;
+    // Databases from before the engine accepted alternative solutions
+    add_column_if_missing(conn, "exercise_results", "alternative_solution", "INTEGER NOT NULL DEFAULT 0")?;
+    // Databases from before guided help recorded its questions
+    add_column_if_missing(conn, "exercise_results", "scaffolding", "TEXT NOT NULL DEFAULT '[]'")?;
+    conn.execute_batch(
+        r#"
         CREATE INDEX IF NOT EXISTS idx_exercise_results_profile_id ON exercise_results(profile_id);
         CREATE INDEX IF NOT EXISTS idx_exercise_results_type ON exercise_results(exercise_type);
+        CREATE INDEX IF NOT EXISTS idx_exercise_results_session_id ON exercise_results(session_id);
+        "#,
+    )?;
+    // Older builds stored whatever spelling the frontend sent
+    canonicalize_column::<ExerciseType>(conn, "exercise_results", "exercise_type")?;
+    canonicalize_column::<ExerciseDifficulty>(conn, "exercise_results", "difficulty")?;
+
+    // Activity log table - timed sessions per activity for study-time tracking
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS activity_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            activity TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            ended_at TEXT,
+            duration_seconds INTEGER,
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_activity_log_profile_id ON activity_log(profile_id);
+        CREATE INDEX IF NOT EXISTS idx_activity_log_started_at ON activity_log(started_at);
+        "#,
+    )?;
+
+    // Usage counts - opt-in, local-only feature usage tallied per day
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS usage_counts (
+            feature TEXT NOT NULL,
+            day TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (feature, day)
+        );
         "#,
     )?;
 
+    // Quiz tables - question banks per concept, quiz sessions, answers and mastery
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS quiz_questions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            concept_id TEXT NOT NULL,
+            question_type TEXT NOT NULL,
+            prompt TEXT NOT NULL,
+            fen TEXT,
+            choices TEXT NOT NULL DEFAULT '[]',
+            answer TEXT NOT NULL,
+            explanation TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_quiz_questions_concept_id ON quiz_questions(concept_id);
+
+        CREATE TABLE IF NOT EXISTS quiz_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            concept_id TEXT NOT NULL,
+            total_questions INTEGER NOT NULL,
+            answered INTEGER NOT NULL DEFAULT 0,
+            score INTEGER NOT NULL DEFAULT 0,
+            started_at TEXT NOT NULL,
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS quiz_answers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            question_id INTEGER NOT NULL,
+            answer TEXT NOT NULL,
+            correct INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            UNIQUE (session_id, question_id),
+            FOREIGN KEY (session_id) REFERENCES quiz_sessions(id),
+            FOREIGN KEY (question_id) REFERENCES quiz_questions(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS concept_mastery (
+            profile_id INTEGER NOT NULL,
+            concept_id TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            correct INTEGER NOT NULL DEFAULT 0,
+            mastery REAL NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (profile_id, concept_id),
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+        "#,
+    )?;
+
+    // Glossary table - term definitions (built-in and cached LLM definitions)
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS glossary (
+            term TEXT PRIMARY KEY,
+            definition TEXT NOT NULL,
+            source TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    // Session snapshots - autosaved in-flight games, training sessions and coach drafts
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS session_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER,
+            kind TEXT NOT NULL,
+            session_key TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE (kind, session_key),
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+        "#,
+    )?;
+
+    // Coach response cache - replies to deterministic coach requests, keyed by a request hash
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS coach_response_cache (
+            cache_key TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            response TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    // Engine bench runs - search speed and accuracy over the fixed bench suite, kept to spot regressions
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS bench_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            engine_name TEXT NOT NULL,
+            engine_version TEXT NOT NULL,
+            nps INTEGER NOT NULL,
+            agreement REAL NOT NULL,
+            report TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    // Coach traces - opt-in record of what was sent to and received from the model for each coach reply
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS coach_traces (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            conversation_id INTEGER,
+            message_id INTEGER,
+            model TEXT NOT NULL,
+            prompt TEXT NOT NULL,
+            tool_calls TEXT NOT NULL DEFAULT '[]',
+            responses TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (conversation_id) REFERENCES conversations(id),
+            FOREIGN KEY (message_id) REFERENCES messages(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_coach_traces_message_id ON coach_traces(message_id);
+
+        -- Traces go with their message or conversation, whichever path deletes it
+        CREATE TRIGGER IF NOT EXISTS coach_traces_message_delete BEFORE DELETE ON messages BEGIN
+            DELETE FROM coach_traces WHERE message_id = old.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS coach_traces_conversation_delete BEFORE DELETE ON conversations BEGIN
+            DELETE FROM coach_traces WHERE conversation_id = old.id;
+        END;
+        "#,
+    )?;
+
+    // Saved positions - the player's library of imported and bookmarked positions, tagged for finding again
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS saved_positions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            fen TEXT NOT NULL,
+            initial_fen TEXT NOT NULL,
+            moves TEXT NOT NULL DEFAULT '[]',
+            source TEXT NOT NULL,
+            origin TEXT NOT NULL DEFAULT 'import',
+            title TEXT,
+            note TEXT,
+            tags TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL,
+            solution_line TEXT NOT NULL DEFAULT '[]',
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_saved_positions_profile_id ON saved_positions(profile_id);
+        "#,
+    )?;
+    // Bookmarks from games, puzzles and coach chats came after imports
+    add_column_if_missing(conn, "saved_positions", "origin", "TEXT NOT NULL DEFAULT 'import'")?;
+    add_column_if_missing(conn, "saved_positions", "note", "TEXT")?;
+    // Imported puzzles keep their solution line
+    add_column_if_missing(conn, "saved_positions", "solution_line", "TEXT NOT NULL DEFAULT '[]'")?;
+
+    // Training plan - themes to train and games to review, scheduled by the player or by the coach
+    // once the player approves; flashcards are question/answer cards, optionally on a position
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS training_plan_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            title TEXT NOT NULL,
+            theme TEXT,
+            game_id INTEGER,
+            note TEXT,
+            due_date TEXT,
+            source TEXT NOT NULL,
+            completed_at TEXT,
+            created_at TEXT NOT NULL,
+            deleted_at TEXT,
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS flashcards (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            front TEXT NOT NULL,
+            back TEXT NOT NULL,
+            fen TEXT,
+            source TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_training_plan_items_profile_id ON training_plan_items(profile_id);
+        CREATE INDEX IF NOT EXISTS idx_flashcards_profile_id ON flashcards(profile_id);
+        "#,
+    )?;
+    add_column_if_missing(conn, "training_plan_items", "deleted_at", "TEXT")?;
+
+    // Pending coach actions - changes the coach proposed, held until the player approves or declines them
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS pending_coach_actions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            conversation_id INTEGER,
+            change TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at TEXT NOT NULL,
+            resolved_at TEXT,
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_pending_coach_actions_profile_id ON pending_coach_actions(profile_id);
+        "#,
+    )?;
+
+    // Opening progress - walkthroughs started and finished per opening line, keyed like "c50-italian-game"
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS opening_progress (
+            profile_id INTEGER NOT NULL,
+            opening_key TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            completions INTEGER NOT NULL DEFAULT 0,
+            best_mistakes INTEGER,
+            last_completed_at TEXT,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (profile_id, opening_key),
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+        "#,
+    )?;
+
+    // Exercise post-mortems - the card shown after an exercise, kept with its result for review;
+    // refutations is a JSON array
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS exercise_postmortems (
+            result_id INTEGER PRIMARY KEY,
+            solution TEXT,
+            explanation TEXT NOT NULL,
+            refutations TEXT NOT NULL DEFAULT '[]',
+            elaboration TEXT,
+            source TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (result_id) REFERENCES exercise_results(id)
+        );
+        "#,
+    )?;
+
+    // Opening deviations - where a saved game first left the player's repertoire;
+    // recommended is a JSON array of SAN moves
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS opening_deviations (
+            game_id INTEGER PRIMARY KEY,
+            profile_id INTEGER NOT NULL,
+            opening_key TEXT NOT NULL,
+            opening_name TEXT NOT NULL,
+            ply INTEGER NOT NULL,
+            by_player INTEGER NOT NULL,
+            played TEXT NOT NULL,
+            expected TEXT NOT NULL,
+            centipawn_loss INTEGER NOT NULL DEFAULT 0,
+            played_worse INTEGER NOT NULL DEFAULT 0,
+            recommended TEXT NOT NULL DEFAULT '[]',
+            fen TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id),
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_opening_deviations_profile_id ON opening_deviations(profile_id);
+        "#,
+    )?;
+
+    // Exercise queue - exercises generated from the player's games while the app sat idle;
+    // exercise is the serialized chess_trainer Exercise. Served rows stay so their ids keep
+    // resolving and the same position isn't generated twice
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS exercise_queue (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            game_id INTEGER NOT NULL,
+            ply INTEGER NOT NULL,
+            exercise TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            served_at TEXT,
+            UNIQUE (game_id, ply),
+            FOREIGN KEY (game_id) REFERENCES games(id),
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_exercise_queue_profile_id ON exercise_queue(profile_id);
+        "#,
+    )?;
+
+    // Operation journal - destructive actions, undoable until the window closes; the rows they
+    // removed stay soft-deleted until then. target_ids is a JSON array
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS operation_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            target_ids TEXT NOT NULL DEFAULT '[]',
+            label TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            undone_at TEXT,
+            FOREIGN KEY (profile_id) REFERENCES profiles(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_operation_journal_profile_id ON operation_journal(profile_id);
+        "#,
+    )?;
+
+    // Sync ids - rows merged from other devices are matched on a global uid instead of the local rowid
+    add_uid_column(conn, "games", "created_at")?;
+    add_uid_column(conn, "conversations", "created_at")?;
+    add_uid_column(conn, "messages", "created_at")?;
+    add_uid_column(conn, "training_sessions", "started_at")?;
+    add_uid_column(conn, "exercise_results", "created_at")?;
+    // Sync watermarks - a row whose synced columns change after another device last pulled
+    // is exported to it again
+    add_update_tracking(
+        conn,
+        "games",
+        "created_at",
+        &["final_fen", "moves", "result", "opponent_elo", "finished_at", "termination", "time_class"],
+    )?;
+    add_update_tracking(conn, "conversations", "created_at", &["title", "context"])?;
+    add_update_tracking(
+        conn,
+        "training_sessions",
+        "COALESCE(finished_at, started_at)",
+        &["total_exercises", "strategies_covered", "duration_seconds", "finished_at", "performance_rating"],
+    )?;
+    add_update_tracking(
+        conn,
+        "exercise_results",
+        "created_at",
+        &["solved", "attempts", "time_seconds", "hints_used", "flagged", "flag_reason", "alternative_solution", "scaffolding"],
+    )?;
+
     // Settings table - key-value store for app settings
     conn.execute_batch(
         r#"
@@ -122,6 +617,91 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Add a column to a table created by an older version of the schema
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+/// Add a unique `uid` column to `table` and give existing rows one stamped with
+/// their `created_column` time, so they keep their order when merged elsewhere
+fn add_uid_column(conn: &Connection, table: &str, created_column: &str) -> Result<()> {
+    add_column_if_missing(conn, table, "uid", "TEXT")?;
+
+    let missing: Vec<(i64, String)> = conn
+        .prepare(&format!("SELECT id, {} FROM {} WHERE uid IS NULL", created_column, table))?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+    if !missing.is_empty() {
+        let tx = conn.unchecked_transaction()?;
+        for (id, created_at) in missing {
+            let ms = chrono::DateTime::parse_from_rfc3339(&created_at).map_or(0, |t| t.timestamp_millis().max(0) as u64);
+            tx.execute(&format!("UPDATE {} SET uid = ?1 WHERE id = ?2", table), params![uid_at(ms), id])?;
+        }
+        tx.commit()?;
+    }
+
+    conn.execute_batch(&format!("CREATE UNIQUE INDEX IF NOT EXISTS idx_{0}_uid ON {0}(uid);", table))
+}
+
+/// Give `table` an `updated_at` column that triggers keep current: new and existing rows
+/// start at `created`, and an update to any of `columns` that doesn't set `updated_at`
+/// itself stamps the current time. Columns derived locally, like session totals, are left
+/// out so recomputing them doesn't send the row to other devices again.
+fn add_update_tracking(conn: &Connection, table: &str, created: &str, columns: &[&str]) -> Result<()> {
+    add_column_if_missing(conn, table, "updated_at", "TEXT")?;
+    conn.execute_batch(&format!(
+        r#"
+        UPDATE {0} SET updated_at = {1} WHERE updated_at IS NULL;
+
+        CREATE TRIGGER IF NOT EXISTS {0}_updated_at_insert AFTER INSERT ON {0} WHEN new.updated_at IS NULL BEGIN
+            UPDATE {0} SET updated_at = {1} WHERE id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS {0}_updated_at_update AFTER UPDATE OF {2} ON {0} WHEN new.updated_at IS old.updated_at BEGIN
+            UPDATE {0} SET updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now') WHERE id = new.id;
+        END;
+        CREATE INDEX IF NOT EXISTS idx_{0}_updated_at ON {0}(updated_at);
+        "#,
+        table,
+        created,
+        columns.join(", ")
+    ))
+}
+
+/// Rewrite each value in a column to its canonical `Display` form. Values that
+/// don't parse are left alone and logged.
+fn canonicalize_column<T>(conn: &Connection, table: &str, column: &str) -> Result<()>
+where
+    T: FromStr + ToString,
+{
+    let values: Vec<String> = conn
+        .prepare(&format!("SELECT DISTINCT {} FROM {}", column, table))?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+
+    for value in values {
+        match T::from_str(&value) {
+            Ok(parsed) if parsed.to_string() != value => {
+                conn.execute(
+                    &format!("UPDATE {} SET {} = ?1 WHERE {} = ?2", table, column, column),
+                    params![parsed.to_string(), value],
+                )?;
+            }
+            Ok(_) => {}
+            Err(_) => tracing::warn!(table, column, value = %value, "Leaving unrecognised value as is"),
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,6 +725,93 @@ mod tests {
         assert!(tables.contains(&"conversations".to_string()));
         assert!(tables.contains(&"messages".to_string()));
         assert!(tables.contains(&"exercise_results".to_string()));
+        assert!(tables.contains(&"training_sessions".to_string()));
+        assert!(tables.contains(&"activity_log".to_string()));
+        assert!(tables.contains(&"usage_counts".to_string()));
+        assert!(tables.contains(&"quiz_questions".to_string()));
+        assert!(tables.contains(&"concept_mastery".to_string()));
+        assert!(tables.contains(&"glossary".to_string()));
+        assert!(tables.contains(&"game_analyses".to_string()));
+        assert!(tables.contains(&"session_snapshots".to_string()));
+        assert!(tables.contains(&"coach_response_cache".to_string()));
+        assert!(tables.contains(&"bench_runs".to_string()));
+        assert!(tables.contains(&"coach_traces".to_string()));
         assert!(tables.contains(&"settings".to_string()));
     }
+
+    #[test]
+    fn test_adds_session_link_to_old_exercise_results() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE exercise_results (id INTEGER PRIMARY KEY, profile_id INTEGER NOT NULL, exercise_type TEXT NOT NULL, \
+             difficulty TEXT NOT NULL, position_fen TEXT NOT NULL, solved INTEGER NOT NULL, attempts INTEGER NOT NULL, \
+             time_seconds INTEGER NOT NULL, hints_used INTEGER NOT NULL, created_at TEXT NOT NULL)",
+        )
+        .unwrap();
+
+        create_tables(&conn).unwrap();
+        create_tables(&conn).unwrap();
+
+        conn.prepare("SELECT session_id FROM exercise_results").unwrap();
+    }
+
+    #[test]
+    fn test_backfills_uids_in_creation_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO profiles (name, initial_level, current_elo, peak_elo, created_at, updated_at) \
+             VALUES ('Test', 'beginner', 800, 800, '', '');
+             INSERT INTO games (profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type, created_at) \
+             VALUES (1, '', '', '[]', 'win', 'white', 'engine', '2024-06-05T10:00:00Z'), \
+                    (1, '', '', '[]', 'loss', 'white', 'engine', '2024-06-04T10:00:00Z');",
+        )
+        .unwrap();
+
+        create_tables(&conn).unwrap();
+
+        let uids: Vec<String> = conn
+            .prepare("SELECT uid FROM games ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(uids.len(), 2);
+        assert!(uids.iter().all(|uid| chess_core::is_uid(uid)));
+        assert!(uids[1] < uids[0], "uids follow created_at, not insertion order");
+        assert_eq!(chess_core::uid_timestamp_ms(&uids[1]), Some(1_717_495_200_000));
+    }
+
+    #[test]
+    fn test_canonicalizes_exercise_labels() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO profiles (name, initial_level, current_elo, peak_elo, created_at, updated_at) \
+             VALUES ('Test', 'beginner', 800, 800, '', '');
+             INSERT INTO exercise_results (profile_id, exercise_type, difficulty, position_fen, solved, attempts, time_seconds, hints_used, created_at) \
+             VALUES (1, 'tactics', 'beginner', '', 1, 1, 10, 0, ''), (1, 'Endgame', 'EXPERT', '', 0, 2, 60, 1, ''), \
+             (1, 'mystery', 'Advanced', '', 0, 1, 30, 0, '');",
+        )
+        .unwrap();
+
+        create_tables(&conn).unwrap();
+
+        let rows: Vec<(String, String)> = conn
+            .prepare("SELECT exercise_type, difficulty FROM exercise_results ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Tactics".to_string(), "Beginner".to_string()),
+                ("Endgame".to_string(), "Expert".to_string()),
+                ("mystery".to_string(), "Advanced".to_string()),
+            ]
+        );
+    }
 }
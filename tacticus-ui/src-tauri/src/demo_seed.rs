@@ -0,0 +1,395 @@
+use chess::{Board, BoardStatus, Color};
+use chess_core::{color_name, detect_opening, parse_legal_uci, Opening, SeededRng, OPENINGS};
+use chess_engine::{CancellationToken, Engine, Evaluator, GameAnalyzer, MoveAnalysis, MAX_ELO, MIN_ELO};
+use chess_trainer::ExerciseLibrary;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::analysis::analyze_stored_game_with;
+use crate::commands::user::calculate_new_elo;
+use crate::database::repositories::{self, ExerciseResult, Game};
+use crate::move_quality;
+use crate::DB;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const DEMO_NAME: &str = "Demo Player";
+const STARTING_ELO: i32 = 1150;
+/// Openings the demo player keeps coming back to, so per-opening stats have something to show
+const REPERTOIRE: usize = 6;
+/// Plies played after the opening before an even game is agreed drawn
+const MAX_GAME_PLIES: usize = 90;
+/// Centipawns behind at which a side resigns
+const RESIGN_CENTIPAWNS: i32 = 400;
+const EXERCISES_PER_SESSION: usize = 6;
+
+/// How much demo data to create. The same seed always gives the same data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedConfig {
+    pub games: usize,
+    /// Exercise history and games are spread over this many days before now
+    pub days: i64,
+    pub seed: u64,
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        Self { games: 30, days: 60, seed: 1 }
+    }
+}
+
+/// What a seed run created
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedReport {
+    pub profile_id: i64,
+    pub games: usize,
+    pub training_sessions: usize,
+    pub exercise_results: usize,
+    pub conversations: usize,
+}
+
+/// Fill an empty database with a demo profile, analysed games across a small
+/// repertoire, training history that improves over `config.days` and a few coach
+/// conversations. Refuses a database that already has a profile, so real data is
+/// never mixed with demo data.
+pub fn seed_demo_data(conn: &Connection, config: &SeedConfig, now: DateTime<Utc>) -> Result<SeedReport, String> {
+    let db_error = |e: rusqlite::Error| format!("Database error: {}", e);
+    if repositories::get_first_profile(conn).map_err(db_error)?.is_some() {
+        return Err("The database already has a profile; seed a fresh one".to_string());
+    }
+
+    let tx = conn.unchecked_transaction().map_err(db_error)?;
+    let mut rng = SeededRng::new(config.seed);
+    let profile = repositories::create_profile(&tx, DEMO_NAME, "intermediate", STARTING_ELO).map_err(db_error)?;
+    let mut report = SeedReport { profile_id: profile.id, ..SeedReport::default() };
+
+    let mut analyses = Vec::new();
+    let mut elo = STARTING_ELO;
+    let mut repertoire: Vec<&'static Opening> = OPENINGS.iter().collect();
+    rng.shuffle(&mut repertoire);
+    repertoire.truncate(REPERTOIRE);
+    for index in 0..config.games {
+        let at = moment(&mut rng, now, config.days * (config.games - index) as i64 / config.games.max(1) as i64);
+        let opening = repertoire[rng.index(repertoire.len())];
+        let player = if rng.index(2) == 0 { Color::White } else { Color::Black };
+        let opponent_elo = (elo + between(&mut rng, -200, 200) as i32) / 50 * 50;
+        let (game, player_moves) = play_game(&tx, profile.id, opening, player, elo, opponent_elo, &mut rng, at).map_err(db_error)?;
+        analyses.extend(player_moves);
+
+        let score = match game.result.as_str() {
+            "win" => 1.0,
+            "draw" => 0.5,
+            _ => 0.0,
+        };
+        elo = calculate_new_elo(elo, opponent_elo, score);
+        repositories::record_game_outcome(&tx, profile.id, elo, game.result == "win").map_err(db_error)?;
+        report.games += 1;
+    }
+
+    let library = ExerciseLibrary::get_all_exercises();
+    for days_ago in (0..config.days).rev() {
+        // Most days, not every day
+        if library.is_empty() || rng.index(3) == 0 {
+            continue;
+        }
+        let started = moment(&mut rng, now, days_ago);
+        let progress = 1.0 - days_ago as f64 / config.days.max(1) as f64;
+        let picked: Vec<_> = (0..EXERCISES_PER_SESSION).map(|_| &library[rng.index(library.len())]).collect();
+        let types: Vec<String> = picked.iter().map(|e| e.exercise_type.to_string()).collect();
+        let session_id = repositories::create_training_session(&tx, profile.id, picked.len() as i32, &types).map_err(db_error)?;
+
+        let mut elapsed = 0;
+        for exercise in picked {
+            // Accuracy climbs from about half to four in five over the period
+            let solved = (rng.index(100) as f64) < 50.0 + 30.0 * progress;
+            let time_seconds = between(&mut rng, 15, if solved { 90 } else { 180 }) as i32;
+            let result = ExerciseResult {
+                id: 0,
+                profile_id: profile.id,
+                session_id: Some(session_id),
+                exercise_type: exercise.exercise_type.to_string(),
+                difficulty: exercise.difficulty.to_string(),
+                position_fen: exercise.position.clone(),
+                solved,
+                attempts: 1,
+                time_seconds,
+                hints_used: if solved { 0 } else { rng.index(3) as i32 },
+                created_at: String::new(),
+                flagged: false,
+                flag_reason: None,
+                alternative_solution: false,
+                scaffolding: vec![],
+            };
+            let id = repositories::record_exercise_result(&tx, &result).map_err(db_error)?;
+            elapsed += time_seconds as i64;
+            tx.execute(
+                "UPDATE exercise_results SET created_at = ?1 WHERE id = ?2",
+                params![(started + Duration::seconds(elapsed)).to_rfc3339(), id],
+            )
+            .map_err(db_error)?;
+            repositories::increment_exercises_completed(&tx, profile.id).map_err(db_error)?;
+            report.exercise_results += 1;
+        }
+
+        repositories::refresh_training_session(&tx, session_id).map_err(db_error)?;
+        tx.execute(
+            "UPDATE training_sessions SET started_at = ?1, finished_at = ?2, duration_seconds = ?3 WHERE id = ?4",
+            params![started.to_rfc3339(), (started + Duration::seconds(elapsed)).to_rfc3339(), elapsed, session_id],
+        )
+        .map_err(db_error)?;
+        report.training_sessions += 1;
+    }
+
+    for &(days_ago, title, messages) in CONVERSATIONS {
+        let at = moment(&mut rng, now, days_ago.min(config.days));
+        let id = repositories::create_conversation(&tx, profile.id, Some(title), None).map_err(db_error)?;
+        for &(role, content) in messages {
+            repositories::add_message(&tx, id, role, content, None, None).map_err(db_error)?;
+        }
+        tx.execute("UPDATE conversations SET created_at = ?1, updated_at = ?1 WHERE id = ?2", params![at.to_rfc3339(), id])
+            .map_err(db_error)?;
+        tx.execute("UPDATE messages SET created_at = ?1 WHERE conversation_id = ?2", params![at.to_rfc3339(), id])
+            .map_err(db_error)?;
+        report.conversations += 1;
+    }
+
+    let mut profile = repositories::get_profile_by_id(&tx, profile.id).map_err(db_error)?.ok_or("Demo profile vanished")?;
+    profile.style = "Tactical".to_string();
+    profile.weaknesses = GameAnalyzer::identify_weaknesses(&analyses);
+    profile.strengths = vec!["Opening preparation".to_string()];
+    repositories::update_profile(&tx, &mut profile).map_err(db_error)?;
+
+    tx.commit().map_err(db_error)?;
+    Ok(report)
+}
+
+/// Play one game from `opening` between two engines at the players' strengths, store
+/// and analyse it, dated `at`. Returns the game and the analyses of the player's moves.
+#[allow(clippy::too_many_arguments)]
+fn play_game(
+    conn: &Connection,
+    profile_id: i64,
+    opening: &Opening,
+    player: Color,
+    player_elo: i32,
+    opponent_elo: i32,
+    rng: &mut SeededRng,
+    at: DateTime<Utc>,
+) -> rusqlite::Result<(Game, Vec<MoveAnalysis>)> {
+    let engine = |elo: i32, seed: u64| {
+        Engine::builder()
+            .elo((elo.max(0) as u32).clamp(MIN_ELO, MAX_ELO))
+            .seed(seed)
+            .build()
+            .expect("elo clamped to the engine's range")
+    };
+    let (mine, theirs) = (engine(player_elo, rng.next_u64()), engine(opponent_elo, rng.next_u64()));
+
+    let mut board = Board::default();
+    let mut moves = Vec::new();
+    for uci in opening.moves {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else { break };
+        board = board.make_move_new(chess_move);
+        moves.push(uci.to_string());
+    }
+    let mut resigned = None;
+    for _ in 0..MAX_GAME_PLIES {
+        if board.status() != BoardStatus::Ongoing {
+            break;
+        }
+        let side = board.side_to_move();
+        let lead = Evaluator::evaluate_position(&board).score.for_side(side);
+        if lead <= -RESIGN_CENTIPAWNS {
+            resigned = Some(side);
+            break;
+        }
+        let Some(reply) = (if side == player { &mine } else { &theirs }).best_move(&board) else { break };
+        moves.push(reply.chess_move.to_string());
+        board = board.make_move_new(reply.chess_move);
+    }
+
+    let (winner, termination) = match (board.status(), resigned) {
+        (BoardStatus::Checkmate, _) => (Some(!board.side_to_move()), Some("checkmate")),
+        (BoardStatus::Stalemate, _) => (None, Some("stalemate")),
+        (_, Some(side)) => (Some(!side), Some("resignation")),
+        // Long level games are agreed drawn, which has no termination of its own
+        _ => (None, None),
+    };
+    let mut game = Game {
+        id: 0,
+        profile_id,
+        initial_fen: START_FEN.to_string(),
+        final_fen: board.to_string(),
+        result: match winner {
+            None => "draw",
+            Some(color) if color == player => "win",
+            Some(_) => "loss",
+        }
+        .to_string(),
+        opening_name: detect_opening(START_FEN, &moves).map(|o| o.name.to_string()),
+        moves,
+        player_color: color_name(player).to_string(),
+        opponent_type: "engine".to_string(),
+        opponent_elo: Some(opponent_elo),
+        analysis: None,
+        mistakes: 0,
+        blunders: 0,
+        created_at: at.to_rfc3339(),
+        finished_at: Some(at.to_rfc3339()),
+        termination: termination.map(str::to_string),
+        time_class: Some(["blitz", "rapid", "rapid", "classical"][rng.index(4)].to_string()),
+    };
+    game.id = repositories::create_game(conn, &game)?;
+    conn.execute("UPDATE games SET created_at = ?1 WHERE id = ?2", params![game.created_at, game.id])?;
+
+    let mut player_moves = Vec::new();
+    let calibration = move_quality::calibration(conn)?;
+    if let Some(record) = analyze_stored_game_with(&game, &calibration, &CancellationToken::new()) {
+        repositories::insert_game_analysis(conn, &record)?;
+        conn.execute("UPDATE game_analyses SET created_at = ?1 WHERE game_id = ?2", params![game.created_at, game.id])?;
+        let analyses: Vec<MoveAnalysis> = serde_json::from_str(&record.analysis).unwrap_or_default();
+        // The player's moves are every other one, starting with their colour's first
+        let first = if player == Color::White { 0 } else { 1 };
+        player_moves.extend(analyses.into_iter().skip(first).step_by(2));
+        game.mistakes = record.mistakes;
+        game.blunders = record.blunders;
+    }
+    Ok((game, player_moves))
+}
+
+/// Some time of day `days_ago` days before `now`
+fn moment(rng: &mut SeededRng, now: DateTime<Utc>, days_ago: i64) -> DateTime<Utc> {
+    now - Duration::days(days_ago) - Duration::minutes(between(rng, 30, 12 * 60))
+}
+
+/// A number from `low` to `high`, both included
+fn between(rng: &mut SeededRng, low: i64, high: i64) -> i64 {
+    low + rng.index((high - low + 1) as usize) as i64
+}
+
+/// Coach conversations: days before now, title and messages
+const CONVERSATIONS: &[(i64, &str, &[(&str, &str)])] = &[
+    (
+        45,
+        "How do I stop hanging pieces?",
+        &[
+            ("user", "I keep losing pieces to simple captures. How do I stop?"),
+            (
+                "assistant",
+                "Before every move, ask what your opponent's last move attacks and what your move leaves undefended. \
+                 In your last few games most lost pieces were knights left on squares a pawn could hit.",
+            ),
+            ("user", "So a blunder check every move?"),
+            ("assistant", "Every move, even the obvious ones. It gets quick with practice. Let's drill some hanging piece puzzles."),
+        ],
+    ),
+    (
+        20,
+        "Opening repertoire",
+        &[
+            ("user", "Which openings should I stick to?"),
+            (
+                "assistant",
+                "You score best in open games after 1.e4 e5. Keep to two or three lines you know and learn their ideas \
+                 rather than long move lists.",
+            ),
+        ],
+    ),
+    (
+        3,
+        "Endgame practice",
+        &[
+            ("user", "I drew a rook endgame I think I was winning."),
+            (
+                "assistant",
+                "You were: the extra pawn wins with the rook behind it. Let's work through the Lucena position, \
+                 which is where that game was heading.",
+            ),
+        ],
+    ),
+];
+
+/// `tacticus seed [--games N] [--days N] [--seed N]`: fill the app database with demo
+/// data and print what was created. Returns the process exit code.
+pub fn run_cli(args: &[String]) -> i32 {
+    let config = match parse_args(args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}\nusage: tacticus seed [--games N] [--days N] [--seed N]", e);
+            return 2;
+        }
+    };
+    let seeded = DB
+        .with_conn(|conn| Ok(seed_demo_data(conn, &config, Utc::now())))
+        .unwrap_or_else(|e: rusqlite::Error| Err(e.to_string()));
+    match seeded {
+        Ok(report) => {
+            println!(
+                "seeded profile {}: {} games, {} training sessions with {} exercise results, {} conversations",
+                report.profile_id, report.games, report.training_sessions, report.exercise_results, report.conversations
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Parse `--games`, `--days` and `--seed` on top of the defaults
+fn parse_args(args: &[String]) -> Result<SeedConfig, String> {
+    let mut config = SeedConfig::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--games" => config.games = value.parse().map_err(|_| format!("invalid game count: {}", value))?,
+            "--days" => config.days = value.parse().map_err(|_| format!("invalid day count: {}", value))?,
+            "--seed" => config.seed = value.parse().map_err(|_| format!("invalid seed: {}", value))?,
+            _ => return Err(format!("unknown option: {}", flag)),
+        }
+    }
+    if config.days < 1 {
+        return Err("--days must be at least 1".to_string());
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_seeds_a_fresh_database_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let config = SeedConfig { games: 3, days: 9, seed: 7 };
+        let now = Utc::now();
+
+        let report = seed_demo_data(&conn, &config, now).unwrap();
+        assert_eq!(report.games, 3);
+        assert_eq!(report.conversations, CONVERSATIONS.len());
+        assert_eq!(report.exercise_results, report.training_sessions * EXERCISES_PER_SESSION);
+
+        let games = repositories::get_recent_games(&conn, report.profile_id, 10, None).unwrap();
+        assert_eq!(games.len(), 3);
+        assert!(games.iter().all(|g| g.analysis.is_some() && g.opening_name.is_some()));
+        let oldest = games.iter().map(|g| g.created_at.as_str()).min().unwrap();
+        assert!(oldest < (now - Duration::days(5)).to_rfc3339().as_str());
+
+        let profile = repositories::get_first_profile(&conn).unwrap().unwrap();
+        assert_eq!(profile.games_played, 3);
+        assert_eq!(profile.exercises_completed as usize, report.exercise_results);
+        assert!(seed_demo_data(&conn, &config, now).is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args: Vec<String> = ["--games", "5", "--seed", "9"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(parse_args(&args).unwrap(), SeedConfig { games: 5, days: 60, seed: 9 });
+        assert!(parse_args(&["--days".to_string(), "0".to_string()]).is_err());
+        assert!(parse_args(&["--fast".to_string(), "1".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+use chess::{Board, ChessMove, Color, File, Piece, Rank, Square};
+use chess_core::{to_san, ChessGame};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// DGT boards talk 9600 baud, 8N1
+pub const DGT_BAUD_RATE: u32 = 9600;
+
+// Commands sent to the board
+const DGT_SEND_RESET: u8 = 0x40;
+const DGT_SEND_BRD: u8 = 0x42;
+const DGT_SEND_UPDATE_BRD: u8 = 0x44;
+
+// Messages from the board have the high bit set
+const MESSAGE_BIT: u8 = 0x80;
+const DGT_BOARD_DUMP: u8 = MESSAGE_BIT | 0x06;
+const DGT_FIELD_UPDATE: u8 = MESSAGE_BIT | 0x0e;
+const HEADER_LEN: usize = 3;
+
+/// Contents of one physical square
+pub type SquareContent = Option<(Piece, Color)>;
+
+/// The 64 squares in board order: index 0 is a8, 7 is h8, 63 is h1
+pub type Placement = [SquareContent; 64];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DgtMessage {
+    BoardDump(Placement),
+    FieldUpdate { index: usize, content: SquareContent },
+    Other(u8),
+}
+
+fn decode_piece(code: u8) -> SquareContent {
+    match code {
+        0x01 => Some((Piece::Pawn, Color::White)),
+        0x02 => Some((Piece::Rook, Color::White)),
+        0x03 => Some((Piece::Knight, Color::White)),
+        0x04 => Some((Piece::Bishop, Color::White)),
+        0x05 => Some((Piece::King, Color::White)),
+        0x06 => Some((Piece::Queen, Color::White)),
+        0x07 => Some((Piece::Pawn, Color::Black)),
+        0x08 => Some((Piece::Rook, Color::Black)),
+        0x09 => Some((Piece::Knight, Color::Black)),
+        0x0a => Some((Piece::Bishop, Color::Black)),
+        0x0b => Some((Piece::King, Color::Black)),
+        0x0c => Some((Piece::Queen, Color::Black)),
+        _ => None,
+    }
+}
+
+fn index_to_square(index: usize) -> Square {
+    Square::make_square(Rank::from_index(7 - index / 8), File::from_index(index % 8))
+}
+
+/// Placement of a position in DGT square order
+pub fn placement_of(board: &Board) -> Placement {
+    let mut placement = [None; 64];
+    for (index, content) in placement.iter_mut().enumerate() {
+        let square = index_to_square(index);
+        *content = board.piece_on(square).zip(board.color_on(square));
+    }
+    placement
+}
+
+/// Reassembles DGT messages from the raw serial byte stream
+#[derive(Debug, Default)]
+pub struct DgtParser {
+    buffer: Vec<u8>,
+}
+
+impl DgtParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed bytes read from the port and return every complete message
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<DgtMessage> {
+        self.buffer.extend_from_slice(bytes);
+        let mut messages = Vec::new();
+
+        loop {
+            // Resynchronise on the next message id
+            match self.buffer.iter().position(|b| b & MESSAGE_BIT != 0) {
+                Some(start) => {
+                    self.buffer.drain(..start);
+                }
+                None => {
+                    self.buffer.clear();
+                    break;
+                }
+            }
+            if self.buffer.len() < HEADER_LEN {
+                break;
+            }
+
+            let len = ((self.buffer[1] as usize) << 7) | self.buffer[2] as usize;
+            if len < HEADER_LEN {
+                self.buffer.remove(0);
+                continue;
+            }
+            if self.buffer.len() < len {
+                break;
+            }
+
+            let message: Vec<u8> = self.buffer.drain(..len).collect();
+            let payload = &message[HEADER_LEN..];
+            messages.push(match message[0] {
+                DGT_BOARD_DUMP if payload.len() == 64 => {
+                    let mut placement = [None; 64];
+                    for (content, code) in placement.iter_mut().zip(payload) {
+                        *content = decode_piece(*code);
+                    }
+                    DgtMessage::BoardDump(placement)
+                }
+                DGT_FIELD_UPDATE if payload.len() == 2 && (payload[0] as usize) < 64 => DgtMessage::FieldUpdate {
+                    index: payload[0] as usize,
+                    content: decode_piece(payload[1]),
+                },
+                id => DgtMessage::Other(id),
+            });
+        }
+
+        messages
+    }
+}
+
+/// Whether the physical board agrees with the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DgtSyncStatus {
+    Synced,
+    /// Pieces are lifted or half-way through a legal move
+    MoveInProgress,
+    /// The board shows something no legal move explains
+    OutOfSync,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DgtEvent {
+    /// A legal move was completed on the board and played in the session
+    Move { uci: String, san: String, fen: String },
+    /// The board no longer matches; the player should restore `expected_fen`
+    Illegal { expected_fen: String },
+    /// The board matches the session again after being out of sync
+    Resynced { fen: String },
+}
+
+/// Keeps the game the app is playing in step with a physical board
+pub struct DgtSession {
+    game: ChessGame,
+    physical: Placement,
+    status: DgtSyncStatus,
+    events: Vec<DgtEvent>,
+}
+
+impl DgtSession {
+    pub fn new(game: ChessGame) -> Self {
+        let physical = placement_of(&game.board);
+        Self { game, physical, status: DgtSyncStatus::Synced, events: Vec::new() }
+    }
+
+    pub fn game(&self) -> &ChessGame {
+        &self.game
+    }
+
+    pub fn status(&self) -> DgtSyncStatus {
+        self.status
+    }
+
+    /// Replace the session's game, e.g. after the app played an engine move or started a new game
+    pub fn set_game(&mut self, game: ChessGame) {
+        self.game = game;
+        self.update();
+    }
+
+    pub fn drain_events(&mut self) -> Vec<DgtEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn handle(&mut self, message: DgtMessage) {
+        match message {
+            DgtMessage::BoardDump(placement) => self.physical = placement,
+            DgtMessage::FieldUpdate { index, content } => self.physical[index] = content,
+            DgtMessage::Other(_) => return,
+        }
+        self.update();
+    }
+
+    fn update(&mut self) {
+        let board = self.game.board;
+        let expected = placement_of(&board);
+
+        if self.physical == expected {
+            if self.status == DgtSyncStatus::OutOfSync {
+                self.events.push(DgtEvent::Resynced { fen: self.game.get_fen() });
+            }
+            self.status = DgtSyncStatus::Synced;
+            return;
+        }
+
+        let legal_moves = self.game.legal_moves();
+        let results: Vec<(ChessMove, Placement)> = legal_moves
+            .iter()
+            .map(|m| (*m, placement_of(&board.make_move_new(*m))))
+            .collect();
+
+        // Promotions are told apart by the piece actually placed on the last rank
+        if let Some((chess_move, _)) = results.iter().find(|(_, after)| *after == self.physical) {
+            let san = to_san(&board, *chess_move);
+            if self.game.make_move(*chess_move).is_ok() {
+                self.events.push(DgtEvent::Move {
+                    uci: chess_move.to_string(),
+                    san,
+                    fen: self.game.get_fen(),
+                });
+                self.status = DgtSyncStatus::Synced;
+                return;
+            }
+        }
+
+        // Every square shows either the old piece, the new piece or nothing
+        let in_progress = results.iter().any(|(_, after)| {
+            self.physical
+                .iter()
+                .zip(expected.iter().zip(after.iter()))
+                .all(|(p, (before, after))| p.is_none() || p == before || p == after)
+        });
+
+        if in_progress && self.status != DgtSyncStatus::OutOfSync {
+            self.status = DgtSyncStatus::MoveInProgress;
+        } else if self.status != DgtSyncStatus::OutOfSync {
+            self.status = DgtSyncStatus::OutOfSync;
+            self.events.push(DgtEvent::Illegal { expected_fen: self.game.get_fen() });
+        }
+    }
+}
+
+/// A serial connection feeding a shared `DgtSession` from a background reader thread
+pub struct DgtConnection {
+    pub port_name: String,
+    pub session: Arc<Mutex<DgtSession>>,
+    running: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl DgtConnection {
+    pub fn open(port_name: &str, game: ChessGame) -> Result<Self, String> {
+        let mut port = serialport::new(port_name, DGT_BAUD_RATE)
+            .timeout(Duration::from_millis(200))
+            .open()
+            .map_err(|e| format!("Failed to open {}: {}", port_name, e))?;
+
+        // Reset, ask for the full board, then switch to per-square updates
+        port.write_all(&[DGT_SEND_RESET, DGT_SEND_BRD, DGT_SEND_UPDATE_BRD])
+            .map_err(|e| format!("Failed to initialise board: {}", e))?;
+
+        let session = Arc::new(Mutex::new(DgtSession::new(game)));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let reader = {
+            let session = Arc::clone(&session);
+            let running = Arc::clone(&running);
+            let port_name = port_name.to_string();
+            std::thread::spawn(move || {
+                let mut parser = DgtParser::new();
+                let mut buf = [0u8; 256];
+                while running.load(Ordering::Relaxed) {
+                    match port.read(&mut buf) {
+                        Ok(0) => {}
+                        Ok(n) => {
+                            let messages = parser.push(&buf[..n]);
+                            if let Ok(mut session) = session.lock() {
+                                for message in messages {
+                                    session.handle(message);
+                                }
+                            }
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                        Err(e) => {
+                            tracing::error!(port = %port_name, "DGT board read failed: {}", e);
+                            break;
+                        }
+                    }
+                }
+                running.store(false, Ordering::Relaxed);
+            })
+        };
+
+        Ok(Self {
+            port_name: port_name.to_string(),
+            session,
+            running,
+            reader: Some(reader),
+        })
+    }
+
+    /// False once the reader thread has stopped, e.g. because the board was unplugged
+    pub fn is_connected(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for DgtConnection {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// Serial ports that could have a board attached
+pub fn available_ports() -> Result<Vec<String>, String> {
+    serialport::available_ports()
+        .map(|ports| ports.into_iter().map(|p| p.port_name).collect())
+        .map_err(|e| format!("Failed to list serial ports: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: u8, payload: &[u8]) -> Vec<u8> {
+        let len = payload.len() + HEADER_LEN;
+        let mut bytes = vec![id, (len >> 7) as u8, (len & 0x7f) as u8];
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn test_parser_reassembles_split_messages() {
+        let mut parser = DgtParser::new();
+        let bytes = message(DGT_FIELD_UPDATE, &[12, 0x07]);
+
+        assert!(parser.push(&bytes[..2]).is_empty());
+        assert_eq!(
+            parser.push(&bytes[2..]),
+            vec![DgtMessage::FieldUpdate { index: 12, content: Some((Piece::Pawn, Color::Black)) }]
+        );
+    }
+
+    #[test]
+    fn test_parser_skips_garbage_and_reads_dump() {
+        let mut parser = DgtParser::new();
+        let mut payload = [0u8; 64];
+        payload[4] = 0x0b;
+        payload[60] = 0x05;
+        let mut bytes = vec![0x01, 0x02];
+        bytes.extend(message(DGT_BOARD_DUMP, &payload));
+
+        let messages = parser.push(&bytes);
+        assert_eq!(messages.len(), 1);
+        match &messages[0] {
+            DgtMessage::BoardDump(placement) => {
+                assert_eq!(placement[4], Some((Piece::King, Color::Black)));
+                assert_eq!(placement[60], Some((Piece::King, Color::White)));
+                assert_eq!(placement.iter().filter(|c| c.is_some()).count(), 2);
+            }
+            other => panic!("expected board dump, got {:?}", other),
+        }
+    }
+}
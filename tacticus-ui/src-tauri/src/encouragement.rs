@@ -0,0 +1,258 @@
+use chess_trainer::ExerciseDifficulty;
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories::{self, TrainingSessionRecord};
+use crate::DB;
+
+const POLICY_SETTING_KEY: &str = "encouragement_policy";
+
+/// Exercise attempts and training sessions looked at for streaks and dips
+const RECENT_ATTEMPTS: i32 = 20;
+const RECENT_SESSIONS: i64 = 6;
+
+/// Solved exercises in a row that count as a streak worth stretching
+const ON_A_ROLL_STREAK: u32 = 5;
+
+/// Quits within this window count towards a rage-quit pattern
+const QUIT_WINDOW_HOURS: i64 = 24;
+/// A resignation this early is a quit rather than a lost position
+const EARLY_RESIGNATION_PLIES: i32 = 30;
+
+const FRUSTRATED_GUIDANCE: &str = r#"The student seems frustrated right now. Adjust your tone:
+- Acknowledge that this is hard before correcting anything
+- Name one thing they did well, specifically
+- Give one correction at a time, with the simplest version of the idea
+- If they keep struggling, suggest an easier exercise or a short break
+- Don't pile on with everything else they got wrong"#;
+
+const ON_A_ROLL_GUIDANCE: &str = r#"The student is on a good streak. Keep the momentum:
+- Praise the specific skill behind their recent solves, briefly
+- Nudge them towards a harder challenge
+- Hold back from long explanations they don't need right now"#;
+
+/// How the encouragement policy reads the student and what it may change.
+/// Stored per profile; off leaves the coach's tone and session difficulty alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncouragementSettings {
+    pub enabled: bool,
+    /// Failed exercises in a row that count as frustration
+    pub failure_streak: u32,
+    /// Performance rating drop, against the sessions before, that counts as a dip
+    pub rating_dip: i32,
+    /// Quits (abandoned sessions, early resignations) in a day that count as rage-quitting
+    pub quits: u32,
+    /// Serve easier exercises when frustrated and harder ones on a streak
+    pub adjust_difficulty: bool,
+}
+
+impl Default for EncouragementSettings {
+    fn default() -> Self {
+        Self { enabled: true, failure_streak: 3, rating_dip: 100, quits: 2, adjust_difficulty: true }
+    }
+}
+
+impl EncouragementSettings {
+    pub fn load(conn: &Connection, profile_id: i64) -> Result<Self> {
+        Ok(repositories::get_profile_setting(conn, profile_id, POLICY_SETTING_KEY)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn save(&self, conn: &Connection, profile_id: i64) -> Result<()> {
+        let json = serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string());
+        repositories::set_profile_setting(conn, profile_id, POLICY_SETTING_KEY, &json)
+    }
+}
+
+/// What the recent history says about how training is going
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signals {
+    pub failed_in_a_row: u32,
+    /// Solved without guided help
+    pub solved_in_a_row: u32,
+    /// Latest session's performance rating against the average of the ones before;
+    /// none until there are two rated sessions
+    pub rating_change: Option<i32>,
+    /// Sessions abandoned and games resigned early in the last day
+    pub recent_quits: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mood {
+    #[default]
+    Steady,
+    Frustrated,
+    OnARoll,
+}
+
+/// The policy's read of the student, and how the coach and sessions follow it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Encouragement {
+    pub mood: Mood,
+    pub signals: Signals,
+    /// Why the mood was picked, for settings and diagnostics
+    pub reasons: Vec<String>,
+    /// Added to coach system prompts; none when the tone stays as it is
+    pub guidance: Option<String>,
+    /// -1 to ease off, 1 to stretch, 0 to leave session difficulty alone
+    pub difficulty_shift: i32,
+}
+
+impl Encouragement {
+    /// What the policy does with nothing to go on, or when turned off
+    fn neutral(signals: Signals) -> Self {
+        Self { mood: Mood::Steady, signals, reasons: vec![], guidance: None, difficulty_shift: 0 }
+    }
+}
+
+/// Read `signals` under `settings`
+pub fn assess(signals: Signals, settings: &EncouragementSettings) -> Encouragement {
+    if !settings.enabled {
+        return Encouragement::neutral(signals);
+    }
+
+    let mut reasons = vec![];
+    if signals.failed_in_a_row >= settings.failure_streak {
+        reasons.push(format!("{} failed exercises in a row", signals.failed_in_a_row));
+    }
+    if let Some(change) = signals.rating_change.filter(|change| -change >= settings.rating_dip) {
+        reasons.push(format!("session rating down {} on the sessions before", -change));
+    }
+    if signals.recent_quits >= settings.quits {
+        reasons.push(format!("{} sessions or games abandoned today", signals.recent_quits));
+    }
+
+    let (mood, guidance, shift) = if !reasons.is_empty() {
+        (Mood::Frustrated, FRUSTRATED_GUIDANCE, -1)
+    } else if signals.solved_in_a_row >= ON_A_ROLL_STREAK {
+        reasons.push(format!("{} exercises solved in a row", signals.solved_in_a_row));
+        (Mood::OnARoll, ON_A_ROLL_GUIDANCE, 1)
+    } else {
+        return Encouragement::neutral(signals);
+    };
+    Encouragement {
+        mood,
+        signals,
+        reasons,
+        guidance: Some(guidance.to_string()),
+        difficulty_shift: if settings.adjust_difficulty { shift } else { 0 },
+    }
+}
+
+/// Sessions left unfinished since `since`, apart from the newest, which may still be going
+fn abandoned_sessions(sessions: &[TrainingSessionRecord], since: &str) -> u32 {
+    sessions
+        .iter()
+        .skip(1)
+        .filter(|s| s.finished_at.is_none() && s.completed_exercises < s.total_exercises && s.started_at.as_str() >= since)
+        .count() as u32
+}
+
+pub fn signals(conn: &Connection, profile_id: i64) -> Result<Signals> {
+    let solves = repositories::get_recent_solves(conn, profile_id, RECENT_ATTEMPTS)?;
+    let failed_in_a_row = solves.iter().take_while(|(solved, _)| !solved).count() as u32;
+    // A solve the coach guided them to is no sign the exercises are too easy
+    let solved_in_a_row = solves.iter().take_while(|(solved, assisted)| *solved && !assisted).count() as u32;
+
+    let sessions = repositories::get_training_sessions(conn, profile_id, RECENT_SESSIONS)?;
+    let ratings: Vec<i32> = sessions.iter().filter_map(|s| s.performance_rating).collect();
+    let rating_change = match ratings.split_first() {
+        Some((latest, earlier)) if !earlier.is_empty() => Some(latest - earlier.iter().sum::<i32>() / earlier.len() as i32),
+        _ => None,
+    };
+
+    let since = (chrono::Utc::now() - chrono::Duration::hours(QUIT_WINDOW_HOURS)).to_rfc3339();
+    let recent_quits = abandoned_sessions(&sessions, &since)
+        + repositories::count_early_resignations(conn, profile_id, &since, EARLY_RESIGNATION_PLIES)?;
+
+    Ok(Signals { failed_in_a_row, solved_in_a_row, rating_change, recent_quits })
+}
+
+pub fn for_profile(conn: &Connection, profile_id: i64) -> Result<Encouragement> {
+    Ok(assess(signals(conn, profile_id)?, &EncouragementSettings::load(conn, profile_id)?))
+}
+
+/// The policy's read of the active profile; neutral when there is none
+pub fn active() -> Encouragement {
+    DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => for_profile(conn, profile.id),
+        None => Ok(Encouragement::neutral(Signals::default())),
+    })
+    .unwrap_or_else(|_| Encouragement::neutral(Signals::default()))
+}
+
+/// Order key for serving exercises: easiest first when easing off, hardest first
+/// when stretching, all level otherwise so the library order stands
+pub fn difficulty_order(difficulty: &ExerciseDifficulty, shift: i32) -> i32 {
+    let level = difficulty.clone() as i32;
+    match shift {
+        s if s < 0 => level,
+        s if s > 0 => -level,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::ExerciseResult;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_assess_picks_mood_and_respects_settings() {
+        let settings = EncouragementSettings::default();
+
+        let struggling = Signals { failed_in_a_row: 4, rating_change: Some(-150), ..Default::default() };
+        let frustrated = assess(struggling.clone(), &settings);
+        assert_eq!(frustrated.mood, Mood::Frustrated);
+        assert_eq!(frustrated.reasons.len(), 2);
+        assert_eq!(frustrated.difficulty_shift, -1);
+        assert!(frustrated.guidance.is_some());
+
+        let streak = assess(Signals { solved_in_a_row: 6, ..Default::default() }, &settings);
+        assert_eq!((streak.mood, streak.difficulty_shift), (Mood::OnARoll, 1));
+
+        let steady = assess(Signals { failed_in_a_row: 1, rating_change: Some(-40), ..Default::default() }, &settings);
+        assert_eq!(steady, Encouragement::neutral(steady.signals.clone()));
+
+        let tone_only = EncouragementSettings { adjust_difficulty: false, ..settings };
+        assert_eq!(assess(struggling.clone(), &tone_only).difficulty_shift, 0);
+        let off = EncouragementSettings { enabled: false, ..settings };
+        assert_eq!(assess(struggling, &off).mood, Mood::Steady);
+
+        assert!(difficulty_order(&ExerciseDifficulty::Beginner, -1) < difficulty_order(&ExerciseDifficulty::Expert, -1));
+        assert!(difficulty_order(&ExerciseDifficulty::Beginner, 1) > difficulty_order(&ExerciseDifficulty::Expert, 1));
+    }
+
+    #[test]
+    fn test_guided_solves_break_a_streak() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap();
+        for scaffolding in [vec![], vec!["Threats".to_string()], vec![], vec![]] {
+            let result = ExerciseResult {
+                id: 0,
+                profile_id: profile.id,
+                session_id: None,
+                exercise_type: "Tactics".to_string(),
+                difficulty: "Beginner".to_string(),
+                position_fen: "a".to_string(),
+                solved: true,
+                attempts: 1,
+                time_seconds: 30,
+                hints_used: 0,
+                created_at: String::new(),
+                flagged: false,
+                flag_reason: None,
+                alternative_solution: false,
+                scaffolding,
+            };
+            repositories::record_exercise_result(&conn, &result).unwrap();
+        }
+        let read = signals(&conn, profile.id).unwrap();
+        assert_eq!((read.solved_in_a_row, read.failed_in_a_row), (2, 0));
+    }
+}
@@ -0,0 +1,220 @@
+use chess_engine::{run_bench, BenchConfig, BenchReport, CancellationToken};
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories::{self, BenchRunRecord};
+use crate::DB;
+
+/// Share of the previous run's speed a new run may lose before it counts as a regression
+const NPS_TOLERANCE: f64 = 0.15;
+/// Runs shown by default and by `tacticus bench`
+pub const HISTORY_LIMIT: i64 = 20;
+
+/// A stored bench run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchRun {
+    pub id: i64,
+    pub created_at: String,
+    pub report: BenchReport,
+}
+
+/// A fresh run compared against the last one with the same settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchOutcome {
+    pub run: BenchRun,
+    pub previous: Option<BenchRun>,
+    /// Why this run looks worse than `previous`, empty when it does not
+    pub regressions: Vec<String>,
+}
+
+/// What got worse from `previous` to `current`
+pub fn regressions(current: &BenchReport, previous: &BenchReport) -> Vec<String> {
+    let mut found = Vec::new();
+    if (current.nps as f64) < previous.nps as f64 * (1.0 - NPS_TOLERANCE) {
+        found.push(format!(
+            "NPS fell from {} to {} ({:.0}%)",
+            previous.nps,
+            current.nps,
+            (current.nps as f64 / previous.nps.max(1) as f64 - 1.0) * 100.0
+        ));
+    }
+    if current.agreement < previous.agreement {
+        found.push(format!(
+            "best-move agreement fell from {:.0}% to {:.0}%",
+            previous.agreement * 100.0,
+            current.agreement * 100.0
+        ));
+    }
+    for position in &current.positions {
+        let before = previous.positions.iter().find(|p| p.name == position.name);
+        if let Some(before) = before.filter(|b| position.depth < b.depth) {
+            found.push(format!("{} reached depth {} instead of {}", position.name, position.depth, before.depth));
+        }
+    }
+    found
+}
+
+/// Stored runs, newest first. Runs whose report no longer parses are skipped.
+pub fn history(conn: &Connection, limit: i64) -> Result<Vec<BenchRun>> {
+    Ok(repositories::get_bench_runs(conn, limit)?
+        .into_iter()
+        .filter_map(|record| {
+            Some(BenchRun {
+                report: serde_json::from_str(&record.report).ok()?,
+                id: record.id,
+                created_at: record.created_at,
+            })
+        })
+        .collect())
+}
+
+/// Store `report` and compare it with the latest earlier run made with the same settings
+pub fn record(conn: &Connection, report: BenchReport) -> Result<BenchOutcome> {
+    let previous = history(conn, HISTORY_LIMIT)?
+        .into_iter()
+        .find(|run| run.report.config == report.config);
+
+    let id = repositories::insert_bench_run(
+        conn,
+        &BenchRunRecord {
+            id: 0,
+            engine_name: report.engine_name.clone(),
+            engine_version: report.engine_version.clone(),
+            nps: report.nps as i64,
+            agreement: report.agreement as f64,
+            report: serde_json::to_string(&report).unwrap_or_default(),
+            created_at: String::new(),
+        },
+    )?;
+    let run = history(conn, 1)?.into_iter().next().filter(|run| run.id == id).unwrap_or(BenchRun {
+        id,
+        created_at: String::new(),
+        report,
+    });
+
+    Ok(BenchOutcome {
+        regressions: previous.as_ref().map_or_else(Vec::new, |p| regressions(&run.report, &p.report)),
+        run,
+        previous,
+    })
+}
+
+/// Run the bench suite and store the result in the app database
+pub fn run_and_record(config: &BenchConfig, token: &CancellationToken) -> std::result::Result<BenchOutcome, String> {
+    let report = run_bench(config, token).map_err(|e| e.to_string())?;
+    tracing::info!(nps = report.nps, agreement = report.agreement, "engine bench finished");
+    DB.with_conn(|conn| record(conn, report))
+        .map_err(|e| format!("Failed to save bench run: {}", e))
+}
+
+/// Parse `--depth N` and `--time-ms N` on top of the defaults
+fn parse_args(args: &[String]) -> std::result::Result<BenchConfig, String> {
+    let mut config = BenchConfig::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--depth" => config.max_depth = value.parse().map_err(|_| format!("invalid depth: {}", value))?,
+            "--time-ms" => {
+                config.time_per_position_ms = value.parse().map_err(|_| format!("invalid time: {}", value))?
+            }
+            _ => return Err(format!("unknown option: {}", flag)),
+        }
+    }
+    Ok(config)
+}
+
+fn print_outcome(outcome: &BenchOutcome) {
+    let report = &outcome.run.report;
+    println!("{} {} bench (depth {}, {} ms per position)", report.engine_name, report.engine_version, report.config.max_depth, report.config.time_per_position_ms);
+    println!("{:<26} {:>10} {:>8} {:>6} {:>7}  {}", "position", "nodes", "ms", "depth", "move", "expected");
+    for position in &report.positions {
+        println!(
+            "{:<26} {:>10} {:>8} {:>6} {:>7}  {}{}",
+            position.name,
+            position.nodes,
+            position.elapsed_ms,
+            position.depth,
+            position.best_move,
+            position.expected,
+            if position.agrees { "" } else { "  MISMATCH" }
+        );
+    }
+    println!(
+        "total: {} nodes in {} ms, {} nps, {:.0}% agreement",
+        report.nodes,
+        report.elapsed_ms,
+        report.nps,
+        report.agreement * 100.0
+    );
+    match &outcome.previous {
+        Some(previous) => println!("previous: {} nps on {} ({})", previous.report.nps, previous.report.engine_version, previous.created_at),
+        None => println!("previous: none with these settings"),
+    }
+    for regression in &outcome.regressions {
+        println!("REGRESSION: {}", regression);
+    }
+}
+
+/// `tacticus bench [--depth N] [--time-ms N]`: run, store and print a bench run.
+/// Returns the process exit code, non-zero on a regression so scripts can fail on it.
+pub fn run_cli(args: &[String]) -> i32 {
+    let config = match parse_args(args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}\nusage: tacticus bench [--depth N] [--time-ms N]", e);
+            return 2;
+        }
+    };
+    match run_and_record(&config, &CancellationToken::new()) {
+        Ok(outcome) => {
+            print_outcome(&outcome);
+            i32::from(!outcome.regressions.is_empty())
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn quick_report() -> BenchReport {
+        let config = BenchConfig {
+            max_depth: 1,
+            time_per_position_ms: 0,
+        };
+        run_bench(&config, &CancellationToken::new()).unwrap()
+    }
+
+    #[test]
+    fn test_runs_are_compared_with_the_previous_one() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+
+        let first = record(&conn, quick_report()).unwrap();
+        assert!(first.previous.is_none());
+        assert!(first.regressions.is_empty());
+
+        let mut slow = quick_report();
+        slow.nps = first.run.report.nps / 2;
+        slow.agreement = 0.5;
+        let second = record(&conn, slow).unwrap();
+        assert_eq!(second.previous.map(|p| p.id), Some(first.run.id));
+        assert_eq!(second.regressions.len(), 2, "{:?}", second.regressions);
+        assert_eq!(history(&conn, HISTORY_LIMIT).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args: Vec<String> = ["--depth", "2", "--time-ms", "500"].iter().map(|s| s.to_string()).collect();
+        let config = parse_args(&args).unwrap();
+        assert_eq!((config.max_depth, config.time_per_position_ms), (2, 500));
+        assert!(parse_args(&["--depth".to_string()]).is_err());
+        assert!(parse_args(&["--fast".to_string(), "1".to_string()]).is_err());
+    }
+}
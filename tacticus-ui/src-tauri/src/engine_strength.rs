@@ -0,0 +1,96 @@
+use chess_engine::{
+    assess_strength, CancellationToken, Cancelled, Engine, Personality, StrengthAssessment, ENGINE_VERSION, MAX_ELO,
+    MIN_ELO,
+};
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::app_config;
+use crate::database::repositories;
+use crate::DB;
+
+/// Settings row the latest report is kept in
+const REPORT_SETTING: &str = "engine_strength";
+
+/// Elo settings assessed, from the weakest the engine plays to full strength
+pub const LADDER: &[u32] = &[MIN_ELO, 800, 1200, 1600, 2000, 2400, MAX_ELO];
+
+/// What each Elo setting amounts to on rated puzzles, for one engine and its settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrengthReport {
+    pub engine_version: String,
+    pub depth: u32,
+    pub personality: Personality,
+    pub created_at: String,
+    /// One per `LADDER` setting, weakest first
+    pub assessments: Vec<StrengthAssessment>,
+}
+
+/// Assess every `LADDER` setting with the engine built the way games build it
+pub fn measure(depth: u32, personality: Personality, token: &CancellationToken) -> std::result::Result<StrengthReport, Cancelled> {
+    let assessments = LADDER
+        .iter()
+        .map(|&elo| {
+            let engine = Engine::builder()
+                .depth(depth)
+                .personality(personality)
+                .elo(elo)
+                .build()
+                .expect("configured depth and ladder Elo are in range");
+            assess_strength(&engine, token)
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(StrengthReport {
+        engine_version: ENGINE_VERSION.to_string(),
+        depth,
+        personality,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        assessments,
+    })
+}
+
+pub fn save(conn: &Connection, report: &StrengthReport) -> Result<()> {
+    repositories::set_setting(conn, REPORT_SETTING, &serde_json::to_string(report).unwrap_or_default())
+}
+
+/// The stored report, unless it was measured with another engine version or other
+/// engine settings and no longer describes how games are played
+pub fn latest(conn: &Connection, depth: u32, personality: Personality) -> Result<Option<StrengthReport>> {
+    Ok(repositories::get_setting(conn, REPORT_SETTING)?
+        .and_then(|json| serde_json::from_str::<StrengthReport>(&json).ok())
+        .filter(|r| r.engine_version == ENGINE_VERSION && r.depth == depth && r.personality == personality))
+}
+
+/// Measure with the configured engine settings and store the report
+pub fn measure_and_record(token: &CancellationToken) -> std::result::Result<StrengthReport, String> {
+    let settings = app_config::current().engine;
+    let report = measure(settings.depth, settings.personality, token).map_err(|e| e.to_string())?;
+    tracing::info!(depth = report.depth, "engine strength measured");
+    DB.with_conn(|conn| save(conn, &report))
+        .map_err(|e| format!("Failed to save engine strength: {}", e))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_reports_are_dropped_when_settings_change() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        assert!(latest(&conn, 1, Personality::Balanced).unwrap().is_none());
+
+        let report = measure(1, Personality::Balanced, &CancellationToken::new()).unwrap();
+        assert_eq!(report.assessments.len(), LADDER.len());
+        assert_eq!(report.assessments[0].elo, Some(MIN_ELO));
+        save(&conn, &report).unwrap();
+
+        let stored = latest(&conn, 1, Personality::Balanced).unwrap().unwrap();
+        assert_eq!(stored.assessments, report.assessments);
+        assert!(latest(&conn, 2, Personality::Balanced).unwrap().is_none());
+        assert!(latest(&conn, 1, Personality::Aggressive).unwrap().is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use chess::{Board, BoardStatus, ChessMove};
+use chess_core::notation::piece_name;
+use chess_core::{parse_move, to_san};
+use chess_engine::Engine;
+use chess_trainer::{Exercise, WrongMove};
+
+use crate::commands::coach::{complete_once, CoachTask};
+use crate::database::repositories::{ExercisePostMortem, Refutation};
+
+/// Plies searched for each try: the reply and the solver's next move, as the solution checker does
+const REPLY_DEPTH: u32 = 2;
+
+/// Wrong tries refuted on one card; later ones repeat the lesson
+const MAX_REFUTATIONS: usize = 3;
+
+/// Scores past this are mates
+const MATE_THRESHOLD: i32 = 50_000;
+
+const POSTMORTEM_SYSTEM_PROMPT: &str = "You are Gurgeh, a chess coach, going over a puzzle the student has just \
+    finished. In two or three sentences, explain the idea behind the solution and, for any moves they tried \
+    instead, why the engine's reply refutes them. Use algebraic notation and stick to the moves you are given.";
+
+/// Why `tried` fails in `board`, from the engine's best reply; `None` for a move that
+/// isn't legal there or mates
+pub fn refute(engine: &Engine, board: &Board, solution: Option<ChessMove>, tried: &str) -> Option<Refutation> {
+    let chess_move = parse_move(board, tried).ok()?;
+    let tried_san = to_san(board, chess_move);
+    let after = board.make_move_new(chess_move);
+    let score = |m: ChessMove| -engine.evaluate(&board.make_move_new(m)).centipawns();
+    let centipawn_loss = solution.map_or(0, |s| (score(s) - score(chess_move)).max(0));
+
+    match after.status() {
+        // Mate can't be refuted; it is a solution the stored one missed
+        BoardStatus::Checkmate => return None,
+        BoardStatus::Stalemate => {
+            return Some(Refutation {
+                summary: format!("{} is stalemate, and the game is drawn.", tried_san),
+                tried: tried_san,
+                reply: None,
+                centipawn_loss,
+            })
+        }
+        BoardStatus::Ongoing => {}
+    }
+
+    let reply = engine.rank_moves(&after).into_iter().next()?;
+    let reply_san = to_san(&after, reply.chess_move);
+    let taken = after.piece_on(reply.chess_move.get_dest());
+    let summary = if after.make_move_new(reply.chess_move).status() == BoardStatus::Checkmate {
+        format!("{} allows {}, checkmate.", tried_san, reply_san)
+    } else if reply.score.centipawns() >= MATE_THRESHOLD {
+        format!("{} walks into a mating attack starting with {}.", tried_san, reply_san)
+    } else if let Some(piece) = taken {
+        format!("{} is met by {}, winning the {}.", tried_san, reply_san, piece_name(piece))
+    } else if centipawn_loss >= 100 {
+        format!(
+            "{} is met by {}, leaving you {:.1} pawns worse off than the solution.",
+            tried_san,
+            reply_san,
+            centipawn_loss as f32 / 100.0
+        )
+    } else {
+        format!("After {}, {} holds and the chance is gone.", tried_san, reply_san)
+    };
+
+    Some(Refutation { tried: tried_san, reply: Some(reply_san), centipawn_loss, summary })
+}
+
+/// The move the solution plays in `board`, which is the exercise's start or a position
+/// along its line; `None` anywhere else
+fn expected_move(exercise: &Exercise, board: &Board) -> Option<ChessMove> {
+    let mut position = exercise.get_board().ok()?;
+    if position == *board {
+        return exercise.solution_moves.first().and_then(|s| parse_move(board, s).ok());
+    }
+    for text in &exercise.solution_line {
+        let next = parse_move(&position, text).ok()?;
+        if position == *board {
+            return Some(next);
+        }
+        position = position.make_move_new(next);
+    }
+    None
+}
+
+/// The post-mortem card for `result_id`: the exercise's explanation, a refutation of
+/// each distinct wrong try, judged where it was played, and the coach's elaboration.
+/// Only attempts that failed, went wrong first or took hints (`assisted`) get an
+/// elaboration; clean solves, and any attempt when the coach can't be reached, keep the first two alone.
+pub async fn build(
+    exercise: &Exercise,
+    result_id: i64,
+    solved: bool,
+    assisted: bool,
+    wrong_moves: &[WrongMove],
+) -> ExercisePostMortem {
+    let board = exercise.get_board().ok();
+    let solution = board.as_ref().and_then(|board| expected_move(exercise, board));
+    let engine = Engine::builder().depth(REPLY_DEPTH).build().expect("depth within MAX_DEPTH");
+    let mut refutations: Vec<Refutation> = Vec::new();
+    for wrong in wrong_moves {
+        if refutations.len() == MAX_REFUTATIONS {
+            break;
+        }
+        let Some(played_from) = wrong.board(board.as_ref()) else { continue };
+        match refute(&engine, &played_from, expected_move(exercise, &played_from), &wrong.tried) {
+            Some(r) if !refutations.iter().any(|seen| seen.tried == r.tried) => refutations.push(r),
+            _ => {}
+        }
+    }
+    let solution_san = match (&board, solution) {
+        (Some(board), Some(solution)) => Some(to_san(board, solution)),
+        _ => exercise.solution_moves.first().cloned(),
+    };
+
+    let (elaboration, source) = if needs_elaboration(solved, assisted, wrong_moves) {
+        let prompt = postmortem_prompt(exercise, solved, solution_san.as_deref(), &refutations);
+        match complete_once(CoachTask::Chat, POSTMORTEM_SYSTEM_PROMPT, &prompt, 200).await {
+            Ok(text) if !text.trim().is_empty() => (Some(text.trim().to_string()), "coach"),
+            Ok(_) => (None, "engine"),
+            Err(e) => {
+                tracing::debug!(result_id, "post-mortem without elaboration: {}", e);
+                (None, "engine")
+            }
+        }
+    } else {
+        (None, "engine")
+    };
+
+    ExercisePostMortem {
+        result_id,
+        solution: solution_san,
+        explanation: exercise.explanation.clone(),
+        refutations,
+        elaboration,
+        source: source.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// A clean first-try solve is covered by the written explanation; the coach is only
+/// asked about attempts that failed, went wrong first or took help
+fn needs_elaboration(solved: bool, assisted: bool, wrong_moves: &[WrongMove]) -> bool {
+    !solved || assisted || !wrong_moves.is_empty()
+}
+
+fn postmortem_prompt(exercise: &Exercise, solved: bool, solution: Option<&str>, refutations: &[Refutation]) -> String {
+    let mut lines = vec![
+        format!("Exercise: {} ({}).", exercise.title, exercise.exercise_type),
+        format!("Position (FEN): {}", exercise.position),
+        format!("Solution: {}", solution.unwrap_or("unknown")),
+        format!("Explanation: {}", exercise.explanation),
+        format!("The student {}.", if solved { "solved it" } else { "did not solve it" }),
+    ];
+    for refutation in refutations {
+        lines.push(format!("They tried {}: {}", refutation.tried, refutation.summary));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_core::parse_fen;
+
+    #[test]
+    fn test_expected_move_follows_the_line() {
+        let exercise = Exercise::new(
+            chess_trainer::ExerciseType::Checkmate,
+            chess_trainer::ExerciseDifficulty::Beginner,
+            "5rk1/5ppp/8/8/8/8/4RPPP/4R1K1 w - - 0 1".to_string(),
+            "Back rank".to_string(),
+            "Mate in two.".to_string(),
+            vec![],
+            "The doubled rooks overload the back rank.".to_string(),
+        )
+        .with_line(vec!["Re8".to_string(), "Rxe8".to_string(), "Rxe8#".to_string()]);
+        let start = exercise.get_board().unwrap();
+        assert_eq!(expected_move(&exercise, &start), parse_move(&start, "Re8").ok());
+
+        let mid_line = parse_fen("4r1k1/5ppp/8/8/8/8/5PPP/4R1K1 w - - 0 2").unwrap();
+        assert_eq!(expected_move(&exercise, &mid_line), parse_move(&mid_line, "Rxe8#").ok());
+        assert_eq!(expected_move(&exercise, &parse_fen("6k1/8/8/8/8/8/8/6K1 w - - 0 1").unwrap()), None);
+    }
+
+    #[test]
+    fn test_only_unclean_attempts_are_elaborated() {
+        assert!(!needs_elaboration(true, false, &[]));
+        assert!(needs_elaboration(false, false, &[]));
+        assert!(needs_elaboration(true, true, &[]));
+        assert!(needs_elaboration(true, false, &[WrongMove { tried: "e2e4".to_string(), fen: None }]));
+    }
+
+    #[test]
+    fn test_refutes_wrong_tries() {
+        let engine = Engine::builder().depth(REPLY_DEPTH).build().unwrap();
+
+        // Back-rank mate in one with Re8#
+        let board = parse_fen("6k1/r4ppp/8/8/8/8/5PPP/4R1K1 w - - 0 1").unwrap();
+        let solution = parse_move(&board, "Re8").ok();
+        let refutation = refute(&engine, &board, solution, "e1e2").unwrap();
+        assert_eq!(refutation.tried, "Re2");
+        assert!(refutation.reply.is_some());
+        assert!(refutation.centipawn_loss > 0);
+        assert!(refute(&engine, &board, solution, "e1e9").is_none());
+
+        // Leaving the back rank to trade rooks is mated by the capture
+        let board = parse_fen("6k1/5ppp/8/8/8/8/r4PPP/4R1K1 w - - 0 1").unwrap();
+        let refutation = refute(&engine, &board, None, "e1a1").unwrap();
+        assert_eq!(refutation.reply.as_deref(), Some("Rxa1#"));
+        assert!(refutation.summary.contains("checkmate"));
+    }
+}
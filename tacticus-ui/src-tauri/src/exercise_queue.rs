@@ -0,0 +1,350 @@
+use chess::{Board, Color};
+use chess_core::{parse_fen, parse_legal_uci, to_san, MoveQuality};
+use chess_engine::{Cancelled, CancellationToken, Engine};
+use chess_trainer::{Exercise, ExerciseDifficulty, ExerciseType};
+use rusqlite::{Connection, Result};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::analysis_scheduler::SCHEDULER;
+use crate::database::repositories::{self, Game, Profile};
+use crate::game_debrief::{key_moments, recommended_theme};
+use crate::mate_drills;
+use crate::DB;
+
+/// Queued exercises take ids from here up, between bookmark exercises and mate pattern drills
+pub const EXERCISE_ID_BASE: usize = 1_500_000_000;
+
+/// Unserved exercises kept per profile; the worker stops generating at this many
+pub const MAX_READY: u32 = 20;
+
+/// How long without input before the worker starts generating
+const IDLE_AFTER: Duration = Duration::from_secs(120);
+/// How often the worker checks whether the user has gone idle
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Recent games searched for positions to generate from
+const SCANNED_GAMES: i32 = 30;
+
+/// Search depth for validating a position
+const VALIDATION_DEPTH: u32 = 3;
+/// The best move must beat the next one by this much, so the exercise has one answer
+const UNIQUE_MARGIN: i32 = 100;
+
+lazy_static! {
+    static ref WORKER: IdleWorker = IdleWorker::new();
+}
+
+struct IdleWorker {
+    last_activity: Mutex<Instant>,
+    /// Token of the fill under way, cancelled as soon as the user is back
+    running: Mutex<Option<CancellationToken>>,
+    /// Whether anything may have changed since the last fill ran to the end.
+    /// New games only arrive while the user is active.
+    pending: Mutex<bool>,
+    /// Positions the engine turned down, so they aren't searched again every idle spell
+    rejected: Mutex<HashSet<(i64, usize)>>,
+}
+
+impl IdleWorker {
+    fn new() -> Self {
+        Self {
+            last_activity: Mutex::new(Instant::now()),
+            running: Mutex::new(None),
+            pending: Mutex::new(true),
+            rejected: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn idle(&self) -> bool {
+        self.last_activity.lock().unwrap().elapsed() >= IDLE_AFTER && !SCHEDULER.interactive_running()
+    }
+}
+
+/// Note user input: a fill under way stops before its next position, and the
+/// worker waits for the next idle spell
+pub fn mark_active() {
+    *WORKER.last_activity.lock().unwrap() = Instant::now();
+    *WORKER.pending.lock().unwrap() = true;
+    if let Some(token) = WORKER.running.lock().unwrap().take() {
+        token.cancel();
+    }
+}
+
+/// Start the idle-time worker; it runs for the life of the app
+pub fn start_worker() {
+    std::thread::spawn(|| loop {
+        std::thread::sleep(POLL_INTERVAL);
+        if !*WORKER.pending.lock().unwrap() || !WORKER.idle() {
+            continue;
+        }
+
+        let token = CancellationToken::new();
+        *WORKER.running.lock().unwrap() = Some(token.clone());
+        match fill(&token) {
+            Ok(0) => {}
+            Ok(queued) => tracing::debug!(queued, "pre-generated exercises"),
+            Err(e) => tracing::warn!("exercise pre-generation failed: {}", e),
+        }
+        WORKER.running.lock().unwrap().take();
+        if !token.is_cancelled() {
+            *WORKER.pending.lock().unwrap() = false;
+        }
+    });
+}
+
+pub fn is_queue_id(exercise_id: usize) -> bool {
+    (EXERCISE_ID_BASE..mate_drills::EXERCISE_ID_BASE).contains(&exercise_id)
+}
+
+pub fn exercise_id(queue_id: i64) -> usize {
+    EXERCISE_ID_BASE + queue_id.max(0) as usize
+}
+
+/// A position where the player went wrong, waiting for the engine to check it
+struct Candidate {
+    game_id: i64,
+    ply: usize,
+    /// "16." or "16...", counted from the game's starting position
+    move_number: String,
+    board: Board,
+    /// The player's move in the game, in SAN
+    played: String,
+    centipawn_loss: i32,
+    quality: MoveQuality,
+    tactical: bool,
+    theme: ExerciseType,
+}
+
+/// The position after the first `ply` half-moves of `game`
+fn position_at(game: &Game, ply: usize) -> Option<Board> {
+    let mut board = parse_fen(&game.initial_fen).ok()?;
+    for uci in game.moves.get(..ply)? {
+        board = board.make_move_new(parse_legal_uci(&board, uci).ok()?);
+    }
+    Some(board)
+}
+
+/// Mistakes from the profile's recent analysed games not queued or turned down
+/// before, those on the profile's weak themes first, newest games first otherwise
+fn candidates(conn: &Connection, profile: &Profile, skip: &HashSet<(i64, usize)>) -> Result<Vec<Candidate>> {
+    let queued = repositories::get_queued_positions(conn, profile.id)?;
+    let mut candidates = vec![];
+    for game in repositories::get_recent_games(conn, profile.id, SCANNED_GAMES, None)? {
+        let Some(record) = repositories::get_game_analyses(conn, game.id)?.into_iter().next() else {
+            continue;
+        };
+        for moment in key_moments(&game, &record) {
+            let position = (game.id, moment.ply);
+            if queued.contains(&position) || skip.contains(&position) {
+                continue;
+            }
+            let Some(board) = position_at(&game, moment.ply) else {
+                continue;
+            };
+            candidates.push(Candidate {
+                game_id: game.id,
+                ply: moment.ply,
+                move_number: moment.move_number.clone(),
+                board,
+                theme: recommended_theme(&game, std::slice::from_ref(&moment)),
+                played: moment.san,
+                centipawn_loss: moment.centipawn_loss,
+                quality: moment.quality,
+                tactical: moment.tactical,
+            });
+        }
+    }
+
+    let weak: Vec<ExerciseType> = profile.weaknesses.iter().filter_map(|w| w.parse().ok()).collect();
+    // Stable, so recency breaks ties
+    candidates.sort_by_key(|c| !weak.contains(&c.theme));
+    Ok(candidates)
+}
+
+/// The exercise on `candidate` if the engine finds one clear best move there
+fn validate(candidate: &Candidate, engine: &Engine, token: &CancellationToken) -> std::result::Result<Option<Exercise>, Cancelled> {
+    let board = &candidate.board;
+    let ranked = engine.rank_moves_cancellable(board, token)?;
+    let Some(best) = ranked.first() else {
+        return Ok(None);
+    };
+    if ranked.get(1).is_some_and(|next| best.score.centipawns() - next.score.centipawns() < UNIQUE_MARGIN) {
+        return Ok(None);
+    }
+
+    let side = if board.side_to_move() == Color::White { "White" } else { "Black" };
+    // A blunder leaves a bigger gap to the best move, so it is easier to spot
+    let difficulty = if candidate.quality == MoveQuality::Blunder {
+        ExerciseDifficulty::Beginner
+    } else {
+        ExerciseDifficulty::Intermediate
+    };
+    let hints = if candidate.tactical {
+        vec!["There is a tactic here. Look at checks, captures and threats first.".to_string()]
+    } else {
+        vec![]
+    };
+    Ok(Some(
+        Exercise::new(
+            candidate.theme.clone(),
+            difficulty,
+            board.to_string(),
+            format!("From your game, move {}", candidate.move_number),
+            format!("{} to move. In the game you played {}; find the stronger move.", side, candidate.played),
+            vec![best.chess_move.to_string()],
+            format!(
+                "{} was the move here. {} cost about {:.1} pawns.",
+                to_san(board, best.chess_move),
+                candidate.played,
+                candidate.centipawn_loss as f64 / 100.0
+            ),
+        )
+        .with_hints(hints),
+    ))
+}
+
+/// Generate exercises for the active profile until its queue is full, the
+/// candidates run out or `token` is cancelled. Returns how many were queued.
+/// The engine runs without the database held.
+fn fill(token: &CancellationToken) -> std::result::Result<usize, String> {
+    let skip = WORKER.rejected.lock().unwrap().clone();
+    let loaded = DB
+        .with_conn(|conn| {
+            let Some(profile) = repositories::get_first_profile(conn)? else {
+                return Ok(None);
+            };
+            let ready = repositories::count_ready_exercises(conn, profile.id)?;
+            if ready >= MAX_READY {
+                return Ok(None);
+            }
+            Ok(Some((profile.id, (MAX_READY - ready) as usize, candidates(conn, &profile, &skip)?)))
+        })
+        .map_err(|e| format!("Failed to load games: {}", e))?;
+    let Some((profile_id, room, candidates)) = loaded else {
+        return Ok(0);
+    };
+
+    let engine = Engine::builder().depth(VALIDATION_DEPTH).build().expect("depth within MAX_DEPTH");
+    let mut queued = 0;
+    for candidate in candidates {
+        if queued >= room {
+            break;
+        }
+        // Interactive analysis means the user is back even without input yet
+        if SCHEDULER.interactive_running() {
+            token.cancel();
+        }
+        let Ok(exercise) = validate(&candidate, &engine, token) else {
+            break;
+        };
+        let Some(exercise) = exercise else {
+            WORKER.rejected.lock().unwrap().insert((candidate.game_id, candidate.ply));
+            continue;
+        };
+        let json = serde_json::to_string(&exercise).map_err(|e| format!("Failed to serialize exercise: {}", e))?;
+        let inserted = DB
+            .with_conn(|conn| repositories::enqueue_exercise(conn, profile_id, candidate.game_id, candidate.ply, &json))
+            .map_err(|e| format!("Failed to queue exercise: {}", e))?;
+        if inserted {
+            queued += 1;
+        }
+    }
+    Ok(queued)
+}
+
+/// Up to `count` ready exercises that pass `keep`, with their ids, taken out of
+/// the queue. Ones that don't pass stay for a later session.
+pub fn take(conn: &Connection, profile_id: i64, count: usize, keep: impl Fn(&Exercise) -> bool) -> Result<Vec<(usize, Exercise)>> {
+    let mut taken = vec![];
+    let mut served = vec![];
+    for queued in repositories::get_ready_exercises(conn, profile_id)? {
+        if taken.len() >= count {
+            break;
+        }
+        let Ok(exercise) = serde_json::from_str::<Exercise>(&queued.exercise) else {
+            continue;
+        };
+        if keep(&exercise) {
+            served.push(queued.id);
+            taken.push((exercise_id(queued.id), exercise));
+        }
+    }
+    repositories::mark_exercises_served(conn, &served)?;
+    Ok(taken)
+}
+
+/// The queued exercise behind an id, served or not, if it belongs to this profile
+pub fn find_exercise(conn: &Connection, profile_id: i64, exercise_id: usize) -> Result<Option<Exercise>> {
+    if !is_queue_id(exercise_id) {
+        return Ok(None);
+    }
+    let queued = repositories::get_queued_exercise(conn, (exercise_id - EXERCISE_ID_BASE) as i64)?;
+    Ok(queued
+        .filter(|q| q.profile_id == profile_id)
+        .and_then(|q| serde_json::from_str(&q.exercise).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+    use chess_engine::MatePattern;
+
+    fn candidate(fen: &str) -> Candidate {
+        Candidate {
+            game_id: 1,
+            ply: 20,
+            board: parse_fen(fen).unwrap(),
+            played: "Rb1".to_string(),
+            centipawn_loss: 900,
+            quality: MoveQuality::Blunder,
+            tactical: true,
+            theme: ExerciseType::Tactics,
+        }
+    }
+
+    #[test]
+    fn test_validates_and_serves_queued_exercises() {
+        let engine = Engine::builder().depth(2).build().unwrap();
+        let token = CancellationToken::new();
+
+        // Only Nf7 mates; the opening position has no single answer
+        let mate = validate(&candidate(MatePattern::SmotheredMate.example_fen()), &engine, &token).unwrap().unwrap();
+        assert_eq!(mate.solution_moves, vec!["g5f7"]);
+        assert_eq!(mate.difficulty, ExerciseDifficulty::Beginner);
+        assert_eq!(mate.title, "From your game, move 11.");
+        let start = candidate("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert!(validate(&start, &engine, &token).unwrap().is_none());
+        token.cancel();
+        assert!(validate(&start, &engine, &token).is_err());
+
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile_id = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap().id;
+        let game = Game {
+            initial_fen: MatePattern::SmotheredMate.example_fen().to_string(),
+            result: "loss".to_string(),
+            blunders: 1,
+            ..Game::for_test(profile_id, &[])
+        };
+        let game_id = repositories::create_game(&conn, &game).unwrap();
+        let json = serde_json::to_string(&mate).unwrap();
+        assert!(repositories::enqueue_exercise(&conn, profile_id, game_id, 0, &json).unwrap());
+        assert!(!repositories::enqueue_exercise(&conn, profile_id, game_id, 0, &json).unwrap());
+        assert_eq!(repositories::count_ready_exercises(&conn, profile_id).unwrap(), 1);
+
+        // Filtered out exercises stay queued
+        assert!(take(&conn, profile_id, 5, |e| e.exercise_type == ExerciseType::Endgame).unwrap().is_empty());
+        let taken = take(&conn, profile_id, 5, |_| true).unwrap();
+        assert_eq!(taken.len(), 1);
+        assert!(is_queue_id(taken[0].0) && !mate_drills::is_drill_id(taken[0].0));
+        assert_eq!(repositories::count_ready_exercises(&conn, profile_id).unwrap(), 0);
+
+        // Served exercises still resolve for the session, for their own profile only
+        let found = find_exercise(&conn, profile_id, taken[0].0).unwrap().unwrap();
+        assert_eq!(found.solution_moves, vec!["g5f7"]);
+        assert!(find_exercise(&conn, profile_id + 1, taken[0].0).unwrap().is_none());
+    }
+}
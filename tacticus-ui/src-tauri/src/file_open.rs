@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::position_import::MAX_SOURCE_LEN;
+
+/// Emitted when the OS hands a running app more files; take them with `take_opened_files`
+pub const FILES_OPENED_EVENT: &str = "files-opened";
+
+lazy_static! {
+    /// Files opened from the OS that the frontend hasn't picked up yet
+    static ref PENDING: Mutex<Vec<OpenedFile>> = Mutex::new(Vec::new());
+}
+
+/// Extensions the bundle registers Tacticus for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    Pgn,
+    Fen,
+}
+
+impl FileKind {
+    fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "pgn" => Some(FileKind::Pgn),
+            "fen" => Some(FileKind::Fen),
+            _ => None,
+        }
+    }
+}
+
+/// A .pgn or .fen file the OS asked Tacticus to open, read and ready to import
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenedFile {
+    pub path: String,
+    /// File name without its directory, for notes in the UI
+    pub name: String,
+    pub kind: FileKind,
+    /// The file's text, or a reading error in `error`
+    pub source: String,
+    pub error: Option<String>,
+}
+
+/// The first game of a PGN file; databases of many games import one at a time
+fn first_game(text: &str) -> &str {
+    let start = text.find("[Event ").map_or(0, |i| i + 1);
+    match text.get(start..).and_then(|rest| rest.find("\n[Event ")) {
+        Some(next) => &text[..start + next],
+        None => text,
+    }
+}
+
+/// Read `path` as the import pipeline expects it
+pub fn read(path: &Path, kind: FileKind) -> OpenedFile {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", name, e));
+    let source = text.and_then(|text| {
+        let source = match kind {
+            FileKind::Pgn => first_game(&text),
+            FileKind::Fen => text.lines().find(|line| !line.trim().is_empty()).unwrap_or_default(),
+        };
+        if source.len() > MAX_SOURCE_LEN {
+            return Err(format!("{} is too large to import", name));
+        }
+        Ok(source.trim().to_string())
+    });
+    let (source, error) = match source {
+        Ok(source) => (source, None),
+        Err(e) => (String::new(), Some(e)),
+    };
+    OpenedFile { path: path.to_string_lossy().into_owned(), name, kind, source, error }
+}
+
+/// Queue the .pgn and .fen files among `paths` for the frontend. Returns how many were queued.
+pub fn open_paths(paths: impl IntoIterator<Item = PathBuf>) -> usize {
+    let opened: Vec<OpenedFile> = paths
+        .into_iter()
+        .filter_map(|path| FileKind::of(&path).map(|kind| read(&path, kind)))
+        .collect();
+    let count = opened.len();
+    if count > 0 {
+        tracing::info!(count, "opening files from the OS");
+        PENDING.lock().unwrap().extend(opened);
+    }
+    count
+}
+
+/// Tell a running frontend that files are waiting in the queue
+pub fn announce<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    use tauri::Emitter;
+    if let Err(e) = app.emit(FILES_OPENED_EVENT, ()) {
+        tracing::warn!("failed to announce opened files: {}", e);
+    }
+}
+
+/// Files passed on the command line, as Windows and Linux do for a double-clicked file.
+/// Some Linux launchers pass file:// URIs instead of paths.
+pub fn open_launch_args(args: impl IntoIterator<Item = OsString>) -> usize {
+    open_paths(args.into_iter().skip(1).map(|arg| {
+        let path = PathBuf::from(&arg);
+        match arg.to_str().and_then(|a| a.strip_prefix("file://")) {
+            Some(rest) => PathBuf::from(percent_decode(rest)),
+            None => path,
+        }
+    }))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Hand over and forget the files opened since the last call
+pub fn take_pending() -> Vec<OpenedFile> {
+    std::mem::take(&mut *PENDING.lock().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_opened_files() {
+        let dir = std::env::temp_dir().join(format!("tacticus-file-open-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pgn = dir.join("Two Games.PGN");
+        std::fs::write(&pgn, "[Event \"One\"]\n\n1. e4 e5 1-0\n\n[Event \"Two\"]\n\n1. d4 d5 0-1\n").unwrap();
+        let fen = dir.join("position.fen");
+        std::fs::write(&fen, "\n6rk/6pp/8/6N1/8/8/8/K7 w - - 0 1\n").unwrap();
+
+        let args = [
+            OsString::from("tacticus"),
+            pgn.clone().into_os_string(),
+            OsString::from(format!("file://{}", fen.to_string_lossy().replace(' ', "%20"))),
+            dir.join("notes.txt").into_os_string(),
+        ];
+        assert_eq!(open_launch_args(args), 2);
+        let opened = take_pending();
+        assert!(take_pending().is_empty());
+
+        assert_eq!((opened[0].kind, opened[0].name.as_str()), (FileKind::Pgn, "Two Games.PGN"));
+        assert_eq!(opened[0].source, "[Event \"One\"]\n\n1. e4 e5 1-0");
+        assert_eq!(opened[1].kind, FileKind::Fen);
+        assert_eq!(opened[1].source, "6rk/6pp/8/6N1/8/8/8/K7 w - - 0 1");
+
+        let missing = read(&dir.join("gone.pgn"), FileKind::Pgn);
+        assert!(missing.error.is_some_and(|e| e.starts_with("Could not read gone.pgn")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
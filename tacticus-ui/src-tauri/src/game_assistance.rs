@@ -0,0 +1,175 @@
+use chess::{Board, ChessMove};
+use chess_core::{parse_legal_uci, to_san, MoveQuality};
+use chess_engine::{Engine, MoveEvaluation, QualityCalibration};
+use chess_trainer::{ExerciseDifficulty, ExerciseType};
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories;
+use crate::DB;
+
+const ASSISTANCE_SETTING_KEY: &str = "game_assistance";
+
+/// Search depth for judging a game move and the retries on it; shallow so the game isn't held up
+const RETRY_DEPTH: u32 = 3;
+
+/// How much help casual games give. Calibration games get none whatever is chosen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameAssistance {
+    Off,
+    /// The no-spoiler "what's my opponent planning?" help
+    #[default]
+    Hints,
+    /// Hints, and a chance to retry a blunder before the engine replies
+    Retries,
+}
+
+impl GameAssistance {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameAssistance::Off => "off",
+            GameAssistance::Hints => "hints",
+            GameAssistance::Retries => "retries",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(GameAssistance::Off),
+            "hints" => Some(GameAssistance::Hints),
+            "retries" => Some(GameAssistance::Retries),
+            _ => None,
+        }
+    }
+}
+
+pub fn load(conn: &Connection, profile_id: i64) -> Result<GameAssistance> {
+    Ok(repositories::get_profile_setting(conn, profile_id, ASSISTANCE_SETTING_KEY)?
+        .and_then(|v| GameAssistance::parse(&v))
+        .unwrap_or_default())
+}
+
+pub fn save(conn: &Connection, profile_id: i64, assistance: GameAssistance) -> Result<()> {
+    repositories::set_profile_setting(conn, profile_id, ASSISTANCE_SETTING_KEY, assistance.as_str())
+}
+
+/// Assistance for the active profile; the default without one
+pub fn active() -> GameAssistance {
+    DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => load(conn, profile.id),
+        None => Ok(GameAssistance::default()),
+    })
+    .unwrap_or_default()
+}
+
+/// A blunder the player may take back and try to improve on, as an exercise
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryDrill {
+    /// Position before the blunder, with the player to move
+    pub fen: String,
+    /// The blunder, in UCI and SAN
+    pub played: String,
+    pub played_san: String,
+    pub centipawn_loss: i32,
+    /// Canonical exercise type and difficulty the attempt is recorded under
+    pub exercise_type: String,
+    pub difficulty: String,
+}
+
+/// How a retry went. The best move is only revealed once the player has found a good one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryVerdict {
+    pub accepted: bool,
+    pub quality: MoveQuality,
+    /// The retry in SAN
+    pub san: String,
+    pub best_san: Option<String>,
+}
+
+fn engine() -> Engine {
+    Engine::builder().depth(RETRY_DEPTH).build().expect("depth within MAX_DEPTH")
+}
+
+/// The best move at `board` and `chess_move`'s evaluation there
+fn rank(board: &Board, chess_move: ChessMove) -> Option<(MoveEvaluation, MoveEvaluation)> {
+    let ranked = engine().rank_moves(board);
+    let played = ranked.iter().find(|e| e.chess_move == chess_move)?.clone();
+    Some((ranked.into_iter().next()?, played))
+}
+
+/// The retry drill for `played` at `board` if the engine judges it a blunder
+pub fn check_move(board: &Board, played: &str, calibration: &QualityCalibration) -> std::result::Result<Option<RetryDrill>, String> {
+    let chess_move = parse_legal_uci(board, played).map_err(|e| e.to_string())?;
+    let Some((best, played)) = rank(board, chess_move) else {
+        return Ok(None);
+    };
+    let (best_score, played_score) = (best.score.centipawns(), played.score.centipawns());
+    if calibration.judge(best_score, played_score) != MoveQuality::Blunder {
+        return Ok(None);
+    }
+
+    // A forcing answer is a tactic and easier to see; a quiet one needs calculating
+    let forcing = best.is_capture || best.is_check || best.is_promotion;
+    let (exercise_type, difficulty) = if forcing {
+        (ExerciseType::Tactics, ExerciseDifficulty::Beginner)
+    } else {
+        (ExerciseType::Calculation, ExerciseDifficulty::Intermediate)
+    };
+    Ok(Some(RetryDrill {
+        fen: board.to_string(),
+        played: chess_move.to_string(),
+        played_san: to_san(board, chess_move),
+        centipawn_loss: best_score - played_score,
+        exercise_type: exercise_type.to_string(),
+        difficulty: difficulty.to_string(),
+    }))
+}
+
+/// Judge a retry at `board`. Anything the calibration calls good or better is accepted,
+/// so the player needn't find the engine's exact choice.
+pub fn check_retry(board: &Board, attempt: &str, calibration: &QualityCalibration) -> std::result::Result<RetryVerdict, String> {
+    let chess_move = parse_legal_uci(board, attempt).map_err(|e| e.to_string())?;
+    let (best, played) = rank(board, chess_move).ok_or_else(|| "No legal moves to retry".to_string())?;
+    let quality = calibration.judge(best.score.centipawns(), played.score.centipawns());
+    let accepted = matches!(quality, MoveQuality::Brilliant | MoveQuality::Great | MoveQuality::Good);
+    Ok(RetryVerdict {
+        accepted,
+        quality,
+        san: to_san(board, chess_move),
+        best_san: accepted.then(|| to_san(board, best.chess_move)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+    use chess_core::parse_fen;
+    use chess_engine::MatePattern;
+
+    #[test]
+    fn test_retry_drill_for_a_blunder() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile_id = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap().id;
+        assert_eq!(load(&conn, profile_id).unwrap(), GameAssistance::Hints);
+        save(&conn, profile_id, GameAssistance::Retries).unwrap();
+        assert_eq!(load(&conn, profile_id).unwrap(), GameAssistance::Retries);
+
+        // Nf7 mates; a king move throws it away
+        let board = parse_fen(MatePattern::SmotheredMate.example_fen()).unwrap();
+        let calibration = QualityCalibration::for_rating(800);
+        let drill = check_move(&board, "a1b1", &calibration).unwrap().unwrap();
+        assert_eq!((drill.played_san.as_str(), drill.exercise_type.as_str()), ("Kb1", "Tactics"));
+        assert!(drill.centipawn_loss > 0);
+        assert!(check_move(&board, "g5f7", &calibration).unwrap().is_none());
+        assert!(check_move(&board, "g5g6", &calibration).is_err());
+
+        let miss = check_retry(&board, "a1a2", &calibration).unwrap();
+        assert!(!miss.accepted && miss.best_san.is_none());
+        let found = check_retry(&board, "g5f7", &calibration).unwrap();
+        assert!(found.accepted);
+        assert_eq!((found.san.as_str(), found.best_san.as_deref()), ("Nf7#", Some("Nf7#")));
+    }
+}
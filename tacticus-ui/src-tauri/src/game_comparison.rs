@@ -0,0 +1,247 @@
+use chess::Color;
+use chess_core::{detect_opening, format_move, move_label, parse_fen, parse_legal_uci, MoveQuality, NotationPrefs};
+use chess_engine::MoveAnalysis;
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories::{Game, GameAnalysisRecord};
+use crate::game_report;
+
+/// Games shown side by side at most; more won't fit next to each other
+pub const MAX_COMPARED_GAMES: usize = 4;
+
+/// Evaluations beyond this (centipawns) are clamped so a mate doesn't flatten the other curves
+const EVAL_CLAMP: i32 = 1000;
+
+/// One half-move in a compared game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparedMove {
+    /// In the user's notation
+    pub san: String,
+    pub uci: String,
+    /// Played by the player rather than the opponent
+    pub by_player: bool,
+    /// Evaluation after the move from the player's point of view, clamped; none before analysis
+    pub eval: Option<i32>,
+    pub quality: Option<MoveQuality>,
+}
+
+/// One game's side of the comparison
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparedGame {
+    pub game_id: i64,
+    pub result: String,
+    pub player_color: String,
+    pub opening_name: Option<String>,
+    pub played_at: String,
+    pub plies: usize,
+    /// Plies this game has in common with the closest of the others
+    pub follows_until: usize,
+    /// Evaluation after each ply from the player's point of view; empty before analysis
+    pub eval_curve: Vec<i32>,
+    /// 0-100 for the player and the opponent; none before analysis
+    pub player_accuracy: Option<f64>,
+    pub opponent_accuracy: Option<f64>,
+}
+
+/// The moves every game played at one ply
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineRow {
+    pub ply: usize,
+    /// "1." before White's moves, "1..." before Black's
+    pub move_number: String,
+    /// Every game played the same moves up to and including this ply
+    pub shared: bool,
+    /// One entry per game, in the order of `games`; none once that game is over
+    pub moves: Vec<Option<ComparedMove>>,
+}
+
+/// Games lined up ply by ply for a side-by-side view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameComparison {
+    pub games: Vec<ComparedGame>,
+    /// Plies every game shares from the start
+    pub shared_plies: usize,
+    /// First ply where the games part ways; none when one game is just a longer version of another
+    pub divergence_ply: Option<usize>,
+    /// Opening of the shared moves; none when the games start from different positions
+    pub shared_opening: Option<String>,
+    pub timeline: Vec<TimelineRow>,
+}
+
+/// Check the ids asked for: at least two distinct games, at most `MAX_COMPARED_GAMES`
+pub fn check_ids(game_ids: &[i64]) -> Result<(), String> {
+    if game_ids.len() < 2 {
+        return Err("Pick at least two games to compare".to_string());
+    }
+    if game_ids.len() > MAX_COMPARED_GAMES {
+        return Err(format!("At most {} games can be compared at once", MAX_COMPARED_GAMES));
+    }
+    if let Some(id) = game_ids.iter().enumerate().find_map(|(i, id)| game_ids[..i].contains(id).then_some(*id)) {
+        return Err(format!("Game {} was picked twice", id));
+    }
+    Ok(())
+}
+
+/// Line up `games` with their latest analysis, if any, keeping their order. Moves are
+/// written the way `notation` asks.
+pub fn compare(games: &[(Game, Option<GameAnalysisRecord>)], notation: &NotationPrefs) -> GameComparison {
+    let same_start = games.windows(2).all(|pair| same_position(&pair[0].0.initial_fen, &pair[1].0.initial_fen));
+    let lines: Vec<&[String]> = games.iter().map(|(game, _)| game.moves.as_slice()).collect();
+    let shared_plies = if same_start { common_prefix(&lines) } else { 0 };
+    let shortest = lines.iter().map(|line| line.len()).min().unwrap_or(0);
+
+    let replayed: Vec<Vec<ComparedMove>> = games.iter().map(|(game, record)| replay(game, record.as_ref(), notation)).collect();
+    let compared = games
+        .iter()
+        .zip(&replayed)
+        .enumerate()
+        .map(|(index, ((game, record), moves))| {
+            let follows_until = if same_start {
+                (0..lines.len())
+                    .filter(|other| *other != index)
+                    .map(|other| common_prefix(&[lines[index], lines[other]]))
+                    .max()
+                    .unwrap_or(0)
+            } else {
+                0
+            };
+            let card = record.as_ref().map(|record| game_report::report_card(game, record));
+            let (player_accuracy, opponent_accuracy) = match &card {
+                Some(card) if game.player_color == "black" => (Some(card.black_accuracy), Some(card.white_accuracy)),
+                Some(card) => (Some(card.white_accuracy), Some(card.black_accuracy)),
+                None => (None, None),
+            };
+            ComparedGame {
+                game_id: game.id,
+                result: game.result.clone(),
+                player_color: game.player_color.clone(),
+                opening_name: game.opening_name.clone(),
+                played_at: game.finished_at.clone().unwrap_or_else(|| game.created_at.clone()),
+                plies: game.moves.len(),
+                follows_until,
+                eval_curve: moves.iter().map_while(|m| m.eval).collect(),
+                player_accuracy,
+                opponent_accuracy,
+            }
+        })
+        .collect();
+
+    let longest = replayed.iter().map(Vec::len).max().unwrap_or(0);
+    // Move numbers follow the first game; they only differ when the starts do
+    let initial_fen = games.first().map(|(game, _)| game.initial_fen.as_str()).unwrap_or_default();
+    let timeline = (0..longest)
+        .map(|ply| TimelineRow {
+            ply,
+            move_number: move_label(initial_fen, ply),
+            shared: ply < shared_plies,
+            moves: replayed.iter().map(|moves| moves.get(ply).cloned()).collect(),
+        })
+        .collect();
+
+    GameComparison {
+        games: compared,
+        shared_plies,
+        divergence_ply: (same_start && shared_plies < shortest).then_some(shared_plies),
+        shared_opening: games
+            .first()
+            .filter(|_| same_start)
+            .and_then(|(game, _)| detect_opening(&game.initial_fen, &game.moves[..shared_plies]))
+            .map(|opening| opening.name.to_string()),
+        timeline,
+    }
+}
+
+/// Number of leading moves all `lines` have in common
+pub fn common_prefix(lines: &[&[String]]) -> usize {
+    let Some((first, rest)) = lines.split_first() else {
+        return 0;
+    };
+    (0..first.len())
+        .take_while(|ply| rest.iter().all(|line| line.get(*ply) == Some(&first[*ply])))
+        .count()
+}
+
+/// Same position, ignoring the move counters
+fn same_position(a: &str, b: &str) -> bool {
+    a.split_whitespace().take(4).eq(b.split_whitespace().take(4))
+}
+
+fn replay(game: &Game, record: Option<&GameAnalysisRecord>, notation: &NotationPrefs) -> Vec<ComparedMove> {
+    let analyses: Vec<MoveAnalysis> = record
+        .and_then(|record| serde_json::from_str(&record.analysis).ok())
+        .unwrap_or_default();
+    let Ok(mut board) = parse_fen(&game.initial_fen) else {
+        return vec![];
+    };
+    let player = if game.player_color == "black" { Color::Black } else { Color::White };
+
+    let mut moves = Vec::with_capacity(game.moves.len());
+    for (ply, uci) in game.moves.iter().enumerate() {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else {
+            break;
+        };
+        let analysis = analyses.get(ply);
+        moves.push(ComparedMove {
+            san: format_move(&board, chess_move, notation),
+            uci: uci.clone(),
+            by_player: board.side_to_move() == player,
+            eval: analysis.map(|a| a.evaluation_after.for_side(player).clamp(-EVAL_CLAMP, EVAL_CLAMP)),
+            quality: analysis.map(|a| a.quality),
+        });
+        board = board.make_move_new(chess_move);
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(id: i64, moves: &[&str]) -> (Game, Option<GameAnalysisRecord>) {
+        let game = Game {
+            id,
+            result: "loss".to_string(),
+            player_color: "black".to_string(),
+            created_at: "2024-06-01T10:00:00Z".to_string(),
+            termination: Some("resignation".to_string()),
+            ..Game::for_test(1, moves)
+        };
+        (game, None)
+    }
+
+    #[test]
+    fn test_lines_games_up_at_the_divergence() {
+        let french = ["e2e4", "e7e6", "d2d4", "d7d5"];
+        let games = vec![
+            game(1, &[&french[..], &["b1c3", "g8f6"][..]].concat()),
+            game(2, &[&french[..], &["e4e5", "c7c5", "c2c3"][..]].concat()),
+            game(3, &[&french[..], &["b1c3", "f8b4"][..]].concat()),
+        ];
+
+        let comparison = compare(&games, &NotationPrefs::default());
+        assert_eq!((comparison.shared_plies, comparison.divergence_ply), (4, Some(4)));
+        assert!(comparison.shared_opening.is_some());
+        let follows: Vec<usize> = comparison.games.iter().map(|g| g.follows_until).collect();
+        assert_eq!(follows, vec![5, 4, 5]);
+
+        assert_eq!(comparison.timeline.len(), 7);
+        let row = &comparison.timeline[5];
+        assert_eq!((row.move_number.as_str(), row.shared), ("3...", false));
+        let played: Vec<Option<&str>> = row.moves.iter().map(|m| m.as_ref().map(|m| m.san.as_str())).collect();
+        assert_eq!(played, vec![Some("Nf6"), Some("c5"), Some("Bb4")]);
+        assert!(row.moves[0].as_ref().unwrap().by_player);
+        assert_eq!(comparison.timeline[6].moves[0], None);
+
+        // Without analysis there is nothing to plot, but the moves still line up
+        assert!(comparison.games[0].eval_curve.is_empty());
+        assert_eq!(comparison.games[0].player_accuracy, None);
+
+        // A game that just carries on from another hasn't diverged from it
+        let extended = compare(&[game(1, &french[..2]), game(2, &french)], &NotationPrefs::default());
+        assert_eq!((extended.shared_plies, extended.divergence_ply), (2, None));
+
+        assert!(check_ids(&[1]).is_err());
+        assert!(check_ids(&[1, 2, 1]).unwrap_err().contains("twice"));
+        assert!(check_ids(&[1, 2, 3, 4, 5]).is_err());
+    }
+}
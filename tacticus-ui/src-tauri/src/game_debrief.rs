@@ -0,0 +1,366 @@
+use chess::Color;
+use chess_core::{move_label, parse_fen, parse_legal_uci, to_san, MoveQuality};
+use chess_engine::{CancellationToken, MoveAnalysis, TacticalPattern};
+use chess_trainer::ExerciseType;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::analysis::analyze_stored_game;
+use crate::commands::coach::{complete_once, CoachTask};
+use crate::conversation_mode::ConversationMode;
+use crate::database::repositories::{self, Game, GameAnalysisRecord};
+use crate::prompt_privacy;
+use crate::DB;
+
+/// Emitted with a `GameDebrief` once a saved game has been analysed and debriefed
+pub const DEBRIEF_READY_EVENT: &str = "game-debrief-ready";
+
+/// Bullets in every debrief
+pub const DEBRIEF_BULLETS: usize = 3;
+
+/// Key moments handed to the coach, worst first
+const MAX_KEY_MOMENTS: usize = 3;
+
+/// Plies after which a slip counts as an endgame one, and before which an opening one
+const ENDGAME_PLY: usize = 60;
+const OPENING_PLY: usize = 20;
+
+const DEBRIEF_SYSTEM_PROMPT: &str = "You are Gurgeh, a chess coach, debriefing the student straight after a game. \
+    Reply with JSON only, in the form {\"bullets\": [\"...\", \"...\", \"...\"], \"puzzle_theme\": \"...\"}. \
+    Write exactly three short bullets, one sentence each, addressed to the student: what decided the game, \
+    the most instructive moment with the better move, and one habit to take into the next game. \
+    puzzle_theme is one of Tactics, Endgame, Opening, Positional, Calculation, Strategy or Defense, \
+    whichever would have helped most in this game.";
+
+/// Short structured coach summary of a finished game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameDebrief {
+    pub game_id: i64,
+    /// Game review conversation the debrief was saved to
+    pub conversation_id: i64,
+    pub result: String,
+    /// Where the game ended, for opening it on the analysis board
+    pub final_fen: String,
+    pub bullets: Vec<String>,
+    /// Canonical exercise type name to train next
+    pub puzzle_theme: String,
+    /// "coach" when written by the model, "engine" when built from the analysis alone
+    pub source: String,
+}
+
+/// One of the player's mistakes, as the coach is told about it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct KeyMoment {
+    pub ply: usize,
+    /// "16." or "16...", counted from the game's starting position
+    pub move_number: String,
+    pub san: String,
+    pub best: String,
+    pub centipawn_loss: i32,
+    pub quality: MoveQuality,
+    pub tactical: bool,
+}
+
+impl KeyMoment {
+    pub(crate) fn describe(&self) -> String {
+        let label = if self.quality == MoveQuality::Blunder { "blunder" } else { "mistake" };
+        format!(
+            "move {} {} ({}, {:.1} pawns lost; better was {})",
+            self.move_number,
+            self.san,
+            label,
+            self.centipawn_loss as f64 / 100.0,
+            self.best
+        )
+    }
+}
+
+/// Analyse the new game, debrief it and tell the UI, all off the calling thread.
+/// Failures are logged; the game itself is already saved.
+pub fn spawn(app: AppHandle, game_id: i64) {
+    tauri::async_runtime::spawn(async move {
+        match run(game_id).await {
+            Ok(debrief) => {
+                if let Err(e) = app.emit(DEBRIEF_READY_EVENT, debrief) {
+                    tracing::warn!(game_id, "failed to emit game debrief: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!(game_id, "game debrief failed: {}", e),
+        }
+    });
+}
+
+async fn run(game_id: i64) -> Result<GameDebrief, String> {
+    let game = DB
+        .with_conn(|conn| repositories::get_game_by_id(conn, game_id))
+        .map_err(|e| format!("Failed to load game: {}", e))?
+        .ok_or_else(|| format!("Game {} not found", game_id))?;
+
+    let analysed = game.clone();
+    let record = tokio::task::spawn_blocking(move || analyze_stored_game(&analysed, &CancellationToken::new()))
+        .await
+        .map_err(|e| format!("Analysis failed: {}", e))?
+        .ok_or_else(|| format!("Could not replay game {}", game_id))?;
+    DB.with_conn(|conn| repositories::insert_game_analysis(conn, &record))
+        .map_err(|e| format!("Failed to save analysis: {}", e))?;
+
+    let moments = key_moments(&game, &record);
+    // A player who keeps their games from the model gets the engine's debrief
+    let (privacy, _) = prompt_privacy::active();
+    let coached = if privacy.share_games {
+        complete_once(CoachTask::Chat, DEBRIEF_SYSTEM_PROMPT, &debrief_prompt(&game, &record, &moments, privacy.share_ratings), 300)
+            .await
+            .and_then(|reply| parse_debrief(&reply))
+    } else {
+        Err("games are not shared with the coach".to_string())
+    };
+    let (bullets, theme, source) = match coached {
+        Ok((bullets, theme)) => (bullets, theme, "coach"),
+        Err(e) => {
+            tracing::debug!(game_id, "using engine debrief: {}", e);
+            let (bullets, theme) = engine_debrief(&game, &record, &moments);
+            (bullets, theme, "engine")
+        }
+    };
+
+    let content = debrief_message(&bullets, &theme);
+    let mode = ConversationMode::GameReview { game_id };
+    let conversation_id = DB
+        .with_conn(|conn| {
+            let id = repositories::create_conversation(
+                conn,
+                game.profile_id,
+                Some(&format!("Debrief: game #{}", game_id)),
+                Some(&mode.to_context()),
+            )?;
+            repositories::add_message(conn, id, "assistant", &content, None, None)?;
+            Ok(id)
+        })
+        .map_err(|e| format!("Failed to save debrief: {}", e))?;
+
+    Ok(GameDebrief {
+        game_id,
+        conversation_id,
+        result: game.result,
+        final_fen: game.final_fen,
+        bullets,
+        puzzle_theme: theme.as_str().to_string(),
+        source: source.to_string(),
+    })
+}
+
+/// The player's mistakes and blunders, worst first
+pub(crate) fn key_moments(game: &Game, record: &GameAnalysisRecord) -> Vec<KeyMoment> {
+    let analyses: Vec<MoveAnalysis> = serde_json::from_str(&record.analysis).unwrap_or_default();
+    let player = if game.player_color == "black" { Color::Black } else { Color::White };
+    let Ok(mut board) = parse_fen(&game.initial_fen) else {
+        return vec![];
+    };
+
+    let mut moments = vec![];
+    for (ply, (uci, analysis)) in game.moves.iter().zip(&analyses).enumerate() {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else {
+            break;
+        };
+        if board.side_to_move() == player && matches!(analysis.quality, MoveQuality::Mistake | MoveQuality::Blunder) {
+            moments.push(KeyMoment {
+                ply,
+                move_number: move_label(&game.initial_fen, ply),
+                san: to_san(&board, chess_move),
+                best: to_san(&board, analysis.best_move),
+                centipawn_loss: analysis.centipawn_loss,
+                quality: analysis.quality,
+                tactical: analysis.tactical_pattern != TacticalPattern::None,
+            });
+        }
+        board = board.make_move_new(chess_move);
+    }
+    moments.sort_by_key(|m| std::cmp::Reverse(m.centipawn_loss));
+    moments
+}
+
+pub(crate) fn result_sentence(game: &Game) -> String {
+    let outcome = match game.result.as_str() {
+        "win" => "You won",
+        "loss" => "You lost",
+        "draw" => "You drew",
+        _ => "The game ended",
+    };
+    let termination = game
+        .termination
+        .as_deref()
+        .map(|t| format!(" by {}", t.replace('_', " ")))
+        .unwrap_or_default();
+    let opening = game.opening_name.as_deref().map(|o| format!(" in the {}", o)).unwrap_or_default();
+    format!("{}{} as {}{}.", outcome, termination, game.player_color, opening)
+}
+
+fn debrief_prompt(game: &Game, record: &GameAnalysisRecord, moments: &[KeyMoment], share_ratings: bool) -> String {
+    let mut prompt = format!(
+        "{} Opponent: {}{}. {} half-moves.\nAverage centipawn loss: {:.0}. Mistakes: {}. Blunders: {}.\n",
+        result_sentence(game),
+        game.opponent_type,
+        game.opponent_elo
+            .filter(|_| share_ratings)
+            .map(|e| format!(" rated {}", e))
+            .unwrap_or_default(),
+        game.moves.len(),
+        record.average_centipawn_loss,
+        record.mistakes,
+        record.blunders
+    );
+    if moments.is_empty() {
+        prompt.push_str("The engine found no mistakes by the student.\n");
+    } else {
+        prompt.push_str("Key moments:\n");
+        for moment in moments.iter().take(MAX_KEY_MOMENTS) {
+            prompt.push_str(&format!("- {}\n", moment.describe()));
+        }
+    }
+    prompt
+}
+
+/// Read the model's JSON reply, tolerating text around it
+fn parse_debrief(reply: &str) -> Result<(Vec<String>, ExerciseType), String> {
+    #[derive(Deserialize)]
+    struct Reply {
+        bullets: Vec<String>,
+        puzzle_theme: String,
+    }
+
+    let (Some(start), Some(end)) = (reply.find('{'), reply.rfind('}')) else {
+        return Err("Debrief reply has no JSON object".to_string());
+    };
+    let parsed: Reply = serde_json::from_str(&reply[start..=end]).map_err(|e| format!("Invalid debrief JSON: {}", e))?;
+    let bullets: Vec<String> = parsed
+        .bullets
+        .iter()
+        .map(|b| b.trim().trim_start_matches(['-', '*']).trim().to_string())
+        .filter(|b| !b.is_empty())
+        .take(DEBRIEF_BULLETS)
+        .collect();
+    if bullets.len() < DEBRIEF_BULLETS {
+        return Err(format!("Debrief has {} bullets, not {}", bullets.len(), DEBRIEF_BULLETS));
+    }
+    let theme = parsed.puzzle_theme.parse().map_err(|e: chess_trainer::ParseNameError| e.to_string())?;
+    Ok((bullets, theme))
+}
+
+/// Debrief from the analysis alone, for when no model is available
+fn engine_debrief(game: &Game, record: &GameAnalysisRecord, moments: &[KeyMoment]) -> (Vec<String>, ExerciseType) {
+    let accuracy = format!(
+        "Your average centipawn loss was {:.0}, with {} mistakes and {} blunders.",
+        record.average_centipawn_loss, record.mistakes, record.blunders
+    );
+    let Some(worst) = moments.first() else {
+        return (
+            vec![
+                result_sentence(game),
+                accuracy,
+                "The engine found no serious slips, so look for stronger plans rather than fewer errors.".to_string(),
+            ],
+            ExerciseType::Strategy,
+        );
+    };
+
+    let theme = recommended_theme(game, moments);
+    let mut moment = worst.describe();
+    if let Some(first) = moment.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    (vec![result_sentence(game), accuracy, format!("Biggest slip: {}.", moment)], theme)
+}
+
+/// Exercise type that addresses the game's worst slip
+pub(crate) fn recommended_theme(game: &Game, moments: &[KeyMoment]) -> ExerciseType {
+    let Some(worst) = moments.first() else {
+        return ExerciseType::Strategy;
+    };
+    if worst.ply < OPENING_PLY {
+        ExerciseType::Opening
+    } else if worst.ply >= ENDGAME_PLY {
+        ExerciseType::Endgame
+    } else if worst.tactical {
+        ExerciseType::Tactics
+    } else if game.result == "loss" && game.termination.as_deref() == Some("checkmate") {
+        ExerciseType::Defense
+    } else {
+        ExerciseType::Calculation
+    }
+}
+
+/// How the debrief reads in the game review conversation
+fn debrief_message(bullets: &[String], theme: &ExerciseType) -> String {
+    let mut message = "Post-game debrief:\n\n".to_string();
+    for bullet in bullets {
+        message.push_str(&format!("- {}\n", bullet));
+    }
+    message.push_str(&format!("\nSuggested puzzles: {}", theme));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(result: &str, termination: Option<&str>) -> Game {
+        Game {
+            id: 7,
+            result: result.to_string(),
+            opponent_elo: Some(1200),
+            opening_name: Some("Italian Game".to_string()),
+            termination: termination.map(str::to_string),
+            ..Game::for_test(1, &[])
+        }
+    }
+
+    fn record(mistakes: i32, blunders: i32) -> GameAnalysisRecord {
+        GameAnalysisRecord {
+            id: 0,
+            game_id: 7,
+            engine_name: "tacticus-eval".to_string(),
+            engine_version: "0.1.0".to_string(),
+            depth: 1,
+            analysis: "[]".to_string(),
+            mistakes,
+            blunders,
+            average_centipawn_loss: 42.4,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parses_and_falls_back() {
+        let reply = "Here you go:\n{\"bullets\": [\"- You won on time.\", \"Move 12 dropped a pawn.\", \"Check captures first.\", \"Extra\"], \"puzzle_theme\": \"tactics\"}";
+        let (bullets, theme) = parse_debrief(reply).unwrap();
+        assert_eq!(bullets, vec!["You won on time.", "Move 12 dropped a pawn.", "Check captures first."]);
+        assert_eq!(theme, ExerciseType::Tactics);
+        assert!(parse_debrief("{\"bullets\": [\"one\"], \"puzzle_theme\": \"Tactics\"}").is_err());
+        assert!(parse_debrief("{\"bullets\": [\"a\", \"b\", \"c\"], \"puzzle_theme\": \"juggling\"}").is_err());
+        assert!(parse_debrief("no json").is_err());
+
+        let lost = game("loss", Some("checkmate"));
+        let slip = KeyMoment {
+            ply: 31,
+            move_number: "16...".to_string(),
+            san: "Qxb7".to_string(),
+            best: "Nf3".to_string(),
+            centipawn_loss: 450,
+            quality: MoveQuality::Blunder,
+            tactical: false,
+        };
+        let (bullets, theme) = engine_debrief(&lost, &record(0, 1), &[slip]);
+        assert_eq!(bullets.len(), DEBRIEF_BULLETS);
+        assert_eq!(bullets[0], "You lost by checkmate as white in the Italian Game.");
+        assert_eq!(bullets[2], "Biggest slip: Move 16... Qxb7 (blunder, 4.5 pawns lost; better was Nf3).");
+        assert_eq!(theme, ExerciseType::Defense);
+
+        let (clean, theme) = engine_debrief(&game("win", None), &record(0, 0), &[]);
+        assert_eq!(clean.len(), DEBRIEF_BULLETS);
+        assert_eq!(theme, ExerciseType::Strategy);
+        assert_eq!(
+            debrief_message(&clean[..1], &theme),
+            "Post-game debrief:\n\n- You won as white in the Italian Game.\n\nSuggested puzzles: Strategy"
+        );
+    }
+}
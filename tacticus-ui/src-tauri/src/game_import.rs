@@ -0,0 +1,500 @@
+use chess::{BoardStatus, Color};
+use chess_core::{detect_opening, parse_fen, parse_move};
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories::Game;
+use crate::game_result::{self, GameSubmission};
+use crate::position_import::{normalize_fen, strip_pgn, StrippedPgn, MAX_SOURCE_LEN, START_FEN};
+use crate::time_control::TimeClass;
+
+/// Opponent name for imported games whose PGN leaves the players out
+const UNNAMED_OPPONENT: &str = "online";
+
+/// Where a pasted game came from, as far as the text tells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameSource {
+    Lichess,
+    ChessCom,
+    /// PGN with tags from anywhere else
+    Pgn,
+    /// Just the moves, e.g. "1. e4 e5 2. Nf3"
+    MoveList,
+}
+
+impl GameSource {
+    fn describe(self) -> &'static str {
+        match self {
+            GameSource::Lichess => "a Lichess game",
+            GameSource::ChessCom => "a Chess.com game",
+            GameSource::Pgn => "a PGN",
+            GameSource::MoveList => "a bare move list",
+        }
+    }
+}
+
+/// A pasted game read as far as its moves allow, with notes on what was
+/// recovered, guessed or dropped along the way
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedGame {
+    pub source: GameSource,
+    /// Link back to the game on the site it was played on
+    pub url: Option<String>,
+    pub white: Option<String>,
+    pub black: Option<String>,
+    pub white_elo: Option<i32>,
+    pub black_elo: Option<i32>,
+    /// "1-0", "0-1" or "1/2-1/2"; none when neither the text nor the moves settle it
+    pub result: Option<String>,
+    /// "white" or "black" when the moves stop before mate in a decisive game
+    pub resigned_by: Option<String>,
+    /// PGN-style time control, e.g. "180+2"
+    pub time_control: Option<String>,
+    /// When the game was played, RFC 3339; none when the text has no date
+    pub played_at: Option<String>,
+    pub initial_fen: String,
+    pub final_fen: String,
+    /// UCI moves from `initial_fen`
+    pub moves: Vec<String>,
+    pub opening_name: Option<String>,
+    /// What was read, guessed or left out, in the order it was found
+    pub diagnostics: Vec<String>,
+    /// Why the game can't be saved as it stands; none when it can
+    pub save_blocker: Option<String>,
+}
+
+/// Read a game from whatever was pasted: a PGN from Lichess or Chess.com with
+/// clock and eval comments, a move list with chat around it, and the like.
+/// Only fails when no moves can be found at all.
+pub fn parse(text: &str) -> Result<ImportedGame, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Nothing to import".to_string());
+    }
+    if text.len() > MAX_SOURCE_LEN {
+        return Err(format!("Import is longer than {} KB", MAX_SOURCE_LEN / 1024));
+    }
+
+    let mut diagnostics = Vec::new();
+    let text = tidy(text, &mut diagnostics);
+    let stripped = strip_pgn(&text);
+    let url = game_url(&stripped, &text);
+    let source = detect_source(&stripped, url.as_deref());
+
+    let initial_fen = match stripped.tag("FEN") {
+        Some(fen) => normalize_fen(fen).map_err(|e| format!("Invalid FEN tag: {}", e))?,
+        None => START_FEN.to_string(),
+    };
+    let mut board = parse_fen(&initial_fen).map_err(|e| e.to_string())?;
+    let mut moves = Vec::new();
+    let mut movetext_result = None;
+    let mut stopped_at = None;
+    let mut skipped_words = 0;
+    let mut nags = 0;
+
+    for token in stripped.movetext.split_whitespace() {
+        if let Some(result) = parse_result(token) {
+            movetext_result = Some(result);
+            break;
+        }
+        if token.starts_with('$') {
+            nags += 1;
+            continue;
+        }
+        if token.contains("://") || token.contains("lichess.org") || token.contains("chess.com") {
+            continue;
+        }
+        let token = strip_move_number(token.trim_end_matches(','));
+        if token.is_empty() || token == "e.p." {
+            continue;
+        }
+        match parse_move(&board, token) {
+            Ok(chess_move) => {
+                moves.push(chess_move.to_string());
+                board = board.make_move_new(chess_move);
+            }
+            // Text before the game, e.g. "check out my win:"
+            Err(_) if moves.is_empty() => skipped_words += 1,
+            Err(_) => {
+                stopped_at = Some(token.to_string());
+                break;
+            }
+        }
+    }
+
+    if moves.is_empty() {
+        return Err(match source {
+            GameSource::Lichess => {
+                "The Lichess link has no moves in it. On the game page open Share & export and copy the PGN.".to_string()
+            }
+            GameSource::ChessCom => "The Chess.com link has no moves in it. Use Share, then PGN, and paste that.".to_string(),
+            _ => "No moves found; paste a PGN or a move list like 1. e4 e5 2. Nf3".to_string(),
+        });
+    }
+
+    diagnostics.push(match stripped.tags.len() {
+        0 => format!("Read as {}", source.describe()),
+        n => format!("Read as {} with {} tags", source.describe(), n),
+    });
+    if stripped.tag("FEN").is_some() {
+        diagnostics.push("Started from the position in the FEN tag".to_string());
+    }
+    diagnostics.push(format!("Recovered {} moves ({} plies)", moves.len().div_ceil(2), moves.len()));
+    if skipped_words > 0 {
+        diagnostics.push(format!("Ignored {} words before the first move", skipped_words));
+    }
+    let dropped = [
+        (stripped.clock_comments, "clock times"),
+        (stripped.comments, "comments"),
+        (stripped.variations, "side variations"),
+        (nags, "annotation symbols"),
+    ];
+    for (count, what) in dropped {
+        if count > 0 {
+            diagnostics.push(format!("Dropped {} {}", count, what));
+        }
+    }
+
+    let mut save_blocker = stopped_at.map(|token| {
+        diagnostics.push(format!(
+            "Stopped at '{}' after {} plies: it isn't a legal move there, so the rest was left out",
+            token,
+            moves.len()
+        ));
+        format!("The moves stop early at '{}'; fix that move to import the whole game", token)
+    });
+
+    let tag_result = stripped.tag("Result").and_then(parse_result);
+    let mut result = match (tag_result, movetext_result) {
+        (Some(tag), Some(movetext)) if tag != movetext => {
+            diagnostics.push(format!("The Result tag says {} but the moves end with {}; kept the tag", tag, movetext));
+            Some(tag)
+        }
+        (tag, movetext) => tag.or(movetext),
+    }
+    .filter(|result| *result != "*");
+
+    let mut resigned_by = None;
+    match board.status() {
+        BoardStatus::Checkmate => {
+            let by_moves = if board.side_to_move() == Color::White { "0-1" } else { "1-0" };
+            if result.is_some_and(|r| r != by_moves) {
+                diagnostics.push(format!("The moves end in checkmate, so the result was corrected to {}", by_moves));
+            }
+            result = Some(by_moves);
+        }
+        BoardStatus::Stalemate => {
+            if result.is_some_and(|r| r != "1/2-1/2") {
+                diagnostics.push("The moves end in stalemate, so the result was corrected to a draw".to_string());
+            }
+            result = Some("1/2-1/2");
+        }
+        BoardStatus::Ongoing => match result {
+            Some(decisive @ ("1-0" | "0-1")) => {
+                let loser = if decisive == "1-0" { "black" } else { "white" };
+                let on_time = stripped.tag("Termination").is_some_and(|t| t.to_lowercase().contains("time"));
+                diagnostics.push(if on_time {
+                    format!("{} lost on time; kept as a resignation", capitalized(loser))
+                } else {
+                    format!("{} resigned", capitalized(loser))
+                });
+                resigned_by = Some(loser.to_string());
+            }
+            Some(_) => {}
+            None => {
+                diagnostics.push("No result given and the moves don't end the game".to_string());
+                save_blocker.get_or_insert_with(|| {
+                    "The game has no result; add one such as 1-0 at the end to import it".to_string()
+                });
+            }
+        },
+    }
+
+    let time_control = stripped.tag("TimeControl").filter(|tc| !matches!(*tc, "-" | "?")).map(str::to_string);
+    if let Some(class) = time_control.as_deref().and_then(TimeClass::from_time_control) {
+        diagnostics.push(format!("Played as {}", class.as_str()));
+    }
+    let played_at = played_at(&stripped);
+    if played_at.is_none() {
+        diagnostics.push("No date given; it will be dated today".to_string());
+    }
+
+    Ok(ImportedGame {
+        source,
+        url,
+        white: player(&stripped, "White"),
+        black: player(&stripped, "Black"),
+        white_elo: stripped.tag("WhiteElo").and_then(|elo| elo.parse().ok()),
+        black_elo: stripped.tag("BlackElo").and_then(|elo| elo.parse().ok()),
+        result: result.map(str::to_string),
+        resigned_by,
+        time_control,
+        played_at,
+        final_fen: board.to_string(),
+        opening_name: detect_opening(&initial_fen, &moves).map(|o| o.name.to_string()),
+        initial_fen,
+        moves,
+        diagnostics,
+        save_blocker,
+    })
+}
+
+impl ImportedGame {
+    /// Side `name` played, matching the White and Black tags without regard to case
+    pub fn side_of(&self, name: &str) -> Option<&'static str> {
+        let name = name.trim();
+        let matches = |player: &Option<String>| player.as_deref().is_some_and(|p| p.eq_ignore_ascii_case(name));
+        if matches(&self.white) {
+            Some("white")
+        } else if matches(&self.black) {
+            Some("black")
+        } else {
+            None
+        }
+    }
+
+    /// The game as a row for the games table, seen from `player_color`'s side
+    pub fn to_record(&self, profile_id: i64, player_color: &str) -> Result<Game, String> {
+        if let Some(blocker) = &self.save_blocker {
+            return Err(blocker.clone());
+        }
+        let submission = GameSubmission {
+            initial_fen: &self.initial_fen,
+            moves: &self.moves,
+            player_color,
+            result: "",
+            final_fen: &self.final_fen,
+            resigned_by: self.resigned_by.as_deref(),
+        };
+        let (result, termination) = match game_result::verify_game(&submission) {
+            Ok(verified) => (verified.result, Some(verified.termination.as_str().to_string())),
+            // A draw by agreement ends wherever the players stopped, which the moves alone can't show
+            Err(_) if self.result.as_deref() == Some("1/2-1/2") && matches!(player_color, "white" | "black") => {
+                ("draw".to_string(), None)
+            }
+            Err(e) => return Err(format!("Invalid game: {}", e)),
+        };
+
+        let (opponent, opponent_elo) = if player_color == "white" {
+            (&self.black, self.black_elo)
+        } else {
+            (&self.white, self.white_elo)
+        };
+        Ok(Game {
+            id: 0,
+            profile_id,
+            initial_fen: self.initial_fen.clone(),
+            final_fen: self.final_fen.clone(),
+            moves: self.moves.clone(),
+            result,
+            player_color: player_color.to_string(),
+            opponent_type: opponent.clone().unwrap_or_else(|| UNNAMED_OPPONENT.to_string()),
+            opponent_elo,
+            analysis: None,
+            mistakes: 0,
+            blunders: 0,
+            opening_name: self.opening_name.clone(),
+            // Dated games sort and count by when they were played, not when they were imported
+            created_at: self.played_at.clone().unwrap_or_default(),
+            finished_at: Some(self.played_at.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339())),
+            termination,
+            time_class: self
+                .time_control
+                .as_deref()
+                .and_then(TimeClass::from_time_control)
+                .map(|class| class.as_str().to_string()),
+        })
+    }
+}
+
+/// Undo what chat apps and word processors do to pasted PGN: curly quotes,
+/// odd spaces, ellipses, "½" and figurine piece symbols
+fn tidy(text: &str, diagnostics: &mut Vec<String>) -> String {
+    let mut tidied = String::with_capacity(text.len());
+    let mut figurines = false;
+    for c in text.chars() {
+        let piece = match c {
+            '♔' | '♚' => Some("K"),
+            '♕' | '♛' => Some("Q"),
+            '♖' | '♜' => Some("R"),
+            '♗' | '♝' => Some("B"),
+            '♘' | '♞' => Some("N"),
+            '♙' | '♟' => Some(""),
+            _ => None,
+        };
+        if let Some(letter) = piece {
+            figurines = true;
+            tidied.push_str(letter);
+            continue;
+        }
+        match c {
+            '\u{201C}' | '\u{201D}' | '\u{201E}' => tidied.push('"'),
+            '\u{00A0}' | '\u{2009}' | '\u{202F}' => tidied.push(' '),
+            '\u{2026}' => tidied.push_str("..."),
+            '½' => tidied.push_str("1/2"),
+            _ => tidied.push(c),
+        }
+    }
+    if figurines {
+        diagnostics.push("Read piece symbols as letters".to_string());
+    }
+    tidied
+}
+
+/// Link to the game: Chess.com's `Link` tag, Lichess's `Site` tag, or a link pasted with the moves
+fn game_url(stripped: &StrippedPgn, text: &str) -> Option<String> {
+    let is_game_link = |s: &str| s.starts_with("http") && (s.contains("lichess.org/") || s.contains("chess.com/"));
+    [stripped.tag("Link"), stripped.tag("Site")]
+        .into_iter()
+        .flatten()
+        .find(|s| is_game_link(s))
+        .map(str::to_string)
+        .or_else(|| {
+            text.split_whitespace()
+                .map(|word| word.trim_matches(|c: char| matches!(c, '"' | '(' | ')' | '[' | ']' | ',' | '<' | '>')))
+                .find(|word| is_game_link(word))
+                .map(str::to_string)
+        })
+}
+
+fn detect_source(stripped: &StrippedPgn, url: Option<&str>) -> GameSource {
+    let site = stripped.tag("Site").unwrap_or_default().to_lowercase();
+    let url = url.unwrap_or_default();
+    if site.contains("lichess.org") || url.contains("lichess.org/") {
+        GameSource::Lichess
+    } else if site.contains("chess.com") || url.contains("chess.com/") {
+        GameSource::ChessCom
+    } else if stripped.tags.is_empty() {
+        GameSource::MoveList
+    } else {
+        GameSource::Pgn
+    }
+}
+
+fn parse_result(token: &str) -> Option<&'static str> {
+    match token {
+        "1-0" => Some("1-0"),
+        "0-1" => Some("0-1"),
+        "1/2-1/2" | "1/2" => Some("1/2-1/2"),
+        "*" => Some("*"),
+        _ => None,
+    }
+}
+
+/// Move numbers may be written apart ("12. Nf3", "12... Nf6") or attached ("12.Nf3")
+fn strip_move_number(token: &str) -> &str {
+    match token.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) if token[i..].starts_with('.') => token[i..].trim_start_matches('.'),
+        Some(_) => token,
+        None => "",
+    }
+}
+
+fn player(stripped: &StrippedPgn, tag: &str) -> Option<String> {
+    stripped.tag(tag).filter(|name| *name != "?").map(str::to_string)
+}
+
+/// `UTCDate` and `UTCTime` as Lichess writes them, falling back to `Date`
+fn played_at(stripped: &StrippedPgn) -> Option<String> {
+    let date = stripped.tag("UTCDate").or_else(|| stripped.tag("Date"))?;
+    let date = NaiveDate::parse_from_str(date, "%Y.%m.%d").ok()?;
+    let time = stripped
+        .tag("UTCTime")
+        .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M:%S").ok())
+        .unwrap_or_default();
+    Some(date.and_time(time).and_utc().to_rfc3339())
+}
+
+fn capitalized(color: &str) -> String {
+    let mut chars = color.chars();
+    chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories;
+
+    const CHESS_COM: &str = r#"[Event "Live Chess"]
+[Site "Chess.com"]
+[Date "2024.03.05"]
+[White "ada_l"]
+[Black "Kasparov_Fan"]
+[Result "0-1"]
+[WhiteElo "1312"]
+[BlackElo "1350"]
+[TimeControl "600"]
+[Termination "Kasparov_Fan won by checkmate"]
+[Link "https://www.chess.com/game/live/10293847"]
+
+1. f3 {[%clk 0:09:58.5]} 1... e5 {[%clk 0:09:57.1]} 2. g4 {[%clk 0:09:55]} 2... Qh4# {[%clk 0:09:50.2]} 0-1"#;
+
+    #[test]
+    fn test_reads_share_text_from_each_site() {
+        let game = parse(CHESS_COM).unwrap();
+        assert_eq!(game.source, GameSource::ChessCom);
+        assert_eq!(game.url.as_deref(), Some("https://www.chess.com/game/live/10293847"));
+        assert_eq!(game.moves, vec!["f2f3", "e7e5", "g2g4", "d8h4"]);
+        assert_eq!(game.result.as_deref(), Some("0-1"));
+        assert_eq!(game.played_at.as_deref(), Some("2024-03-05T00:00:00+00:00"));
+        assert!(game.diagnostics.contains(&"Dropped 4 clock times".to_string()));
+        assert_eq!(game.side_of("kasparov_fan"), Some("black"));
+
+        let record = game.to_record(1, "white").unwrap();
+        assert_eq!((record.result.as_str(), record.termination.as_deref()), ("loss", Some("checkmate")));
+        assert_eq!((record.opponent_type.as_str(), record.opponent_elo), ("Kasparov_Fan", Some(1350)));
+        assert_eq!(record.time_class.as_deref(), Some("rapid"));
+        assert_eq!(record.created_at, "2024-03-05T00:00:00+00:00");
+
+        let lichess = parse(
+            "[Site \"https://lichess.org/AbCd1234\"]\n[Result \"1-0\"]\n[UTCDate \"2025.01.02\"]\n[UTCTime \"18:04:05\"]\n\
+             [Termination \"Time forfeit\"]\n\n1. e4 { [%eval 0.3] [%clk 0:03:00] } 1... e5 (1... c5) 2. Nf3 $1 1-0",
+        )
+        .unwrap();
+        assert_eq!(lichess.source, GameSource::Lichess);
+        assert_eq!(lichess.played_at.as_deref(), Some("2025-01-02T18:04:05+00:00"));
+        assert_eq!(lichess.resigned_by.as_deref(), Some("black"));
+        assert!(lichess.diagnostics.contains(&"Black lost on time; kept as a resignation".to_string()));
+        assert_eq!(lichess.to_record(1, "white").unwrap().result, "win");
+    }
+
+    #[test]
+    fn test_imports_are_saved_once() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "ada_l", "beginner", 1300).unwrap();
+        let record = parse(CHESS_COM).unwrap().to_record(profile.id, "white").unwrap();
+        assert_eq!(repositories::find_duplicate_game(&conn, &record).unwrap(), None);
+
+        let id = repositories::create_game(&conn, &record).unwrap();
+        assert_eq!(repositories::find_duplicate_game(&conn, &record).unwrap(), Some(id));
+        let stored = repositories::get_game_by_id(&conn, id).unwrap().unwrap();
+        assert_eq!(stored.created_at, "2024-03-05T00:00:00+00:00");
+
+        // The same moves on another day are another game
+        let rematch = Game { created_at: "2024-03-06T00:00:00+00:00".to_string(), ..record };
+        assert_eq!(repositories::find_duplicate_game(&conn, &rematch).unwrap(), None);
+    }
+
+    #[test]
+    fn test_recovers_what_it_can_from_loose_text() {
+        let game = parse("check out my game\u{a0}1.e4 e5 2. ♘f3 ♞c6 3.Bb5 a6 ½-½ gg").unwrap();
+        assert_eq!(game.source, GameSource::MoveList);
+        assert_eq!(game.moves, vec!["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6"]);
+        assert!(game.diagnostics.contains(&"Ignored 4 words before the first move".to_string()));
+        // Nothing in the moves ends the game, so the draw was agreed
+        let record = game.to_record(1, "black").unwrap();
+        assert_eq!((record.result.as_str(), record.termination), ("draw", None));
+        assert_eq!(record.opponent_type, UNNAMED_OPPONENT);
+
+        let truncated = parse("1. e4 e5 2. Nf3 Nc6 3. Bb6 a6 1-0").unwrap();
+        assert_eq!(truncated.moves.len(), 4);
+        assert!(truncated.to_record(1, "white").unwrap_err().contains("'Bb6'"));
+        let unfinished = parse("1. e4 e5 2. Nf3").unwrap();
+        assert!(unfinished.save_blocker.unwrap().contains("no result"));
+
+        assert!(parse("https://lichess.org/AbCd1234").unwrap_err().contains("Share & export"));
+        assert!(parse("hello there").unwrap_err().starts_with("No moves found"));
+    }
+}
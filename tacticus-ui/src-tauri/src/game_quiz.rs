@@ -0,0 +1,310 @@
+use chess::Board;
+use chess_core::{move_label, parse_fen, parse_legal_uci, parse_move, to_san, GamePhase, MoveQuality, SeededRng};
+use chess_trainer::ExerciseDifficulty;
+use rusqlite::{Connection, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::conversation_mode::{self, ConversationMode};
+use crate::database::repositories::{self, ExerciseResult, Game, GameAnalysisRecord};
+use crate::game_debrief::{key_moments, recommended_theme};
+use crate::prompt_privacy;
+use crate::session_seed;
+use crate::usage_analytics;
+use crate::DB;
+
+/// Recent games searched for quiz positions
+const QUIZ_GAMES: i32 = 30;
+
+/// A position where the player went wrong in one of their games. Carries no
+/// answer, so the coach cannot give it away before the player has tried.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuizPosition {
+    pub game_id: i64,
+    /// Half-moves played before the position
+    pub ply: usize,
+    pub fen: String,
+    /// e.g. "14." or "14..."
+    pub move_number: String,
+    pub played_at: String,
+    pub player_color: String,
+    pub opponent: String,
+    pub opening: Option<String>,
+    pub phase: GamePhase,
+}
+
+/// A checked quiz answer, recorded as an exercise attempt
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuizAnswer {
+    /// Whether the answer was the engine's move
+    pub correct: bool,
+    pub answer: String,
+    /// What the player chose in the game
+    pub played: String,
+    pub best: String,
+    pub centipawn_loss: i32,
+    pub quality: MoveQuality,
+    /// Exercise theme the attempt counts towards
+    pub theme: String,
+    /// Running score of the quiz conversation, if the answer was given in one
+    pub answered: Option<u32>,
+    pub correct_so_far: Option<u32>,
+}
+
+/// The position after the first `ply` half-moves of `game`
+fn position_at(game: &Game, ply: usize) -> Option<Board> {
+    let mut board = parse_fen(&game.initial_fen).ok()?;
+    for uci in game.moves.get(..ply)? {
+        board = board.make_move_new(parse_legal_uci(&board, uci).ok()?);
+    }
+    Some(board)
+}
+
+/// Every mistake and blunder the player made in `game`, as quiz positions. The
+/// opponent's rating is left out unless the student shares ratings.
+pub fn game_positions(game: &Game, record: &GameAnalysisRecord, share_ratings: bool) -> Vec<QuizPosition> {
+    key_moments(game, record)
+        .into_iter()
+        .filter_map(|moment| {
+            let board = position_at(game, moment.ply)?;
+            Some(QuizPosition {
+                game_id: game.id,
+                ply: moment.ply,
+                fen: board.to_string(),
+                move_number: moment.move_number,
+                played_at: game.created_at.clone(),
+                player_color: game.player_color.clone(),
+                opponent: match game.opponent_elo.filter(|_| share_ratings) {
+                    Some(elo) => format!("{} ({})", game.opponent_type, elo),
+                    None => game.opponent_type.clone(),
+                },
+                opening: game.opening_name.clone(),
+                phase: GamePhase::of(&board),
+            })
+        })
+        .collect()
+}
+
+/// Quiz positions from the player's recent analysed games
+pub fn critical_positions(conn: &Connection, profile_id: i64) -> Result<Vec<QuizPosition>> {
+    let share_ratings = prompt_privacy::load(conn, profile_id)?.share_ratings;
+    let mut positions = vec![];
+    for game in repositories::get_recent_games(conn, profile_id, QUIZ_GAMES, None)? {
+        if let Some(record) = repositories::get_game_analyses(conn, game.id)?.into_iter().next() {
+            positions.extend(game_positions(&game, &record, share_ratings));
+        }
+    }
+    Ok(positions)
+}
+
+/// A random position, preferring ones the player has not solved yet
+pub fn pick(positions: Vec<QuizPosition>, solved: &HashSet<String>, rng: &mut SeededRng) -> Option<QuizPosition> {
+    let (mut fresh, seen): (Vec<_>, Vec<_>) = positions.into_iter().partition(|p| !solved.contains(&p.fen));
+    if fresh.is_empty() {
+        fresh = seen;
+    }
+    if fresh.is_empty() {
+        return None;
+    }
+    let index = rng.index(fresh.len());
+    Some(fresh.swap_remove(index))
+}
+
+/// Check `answer` (SAN or UCI) for the quiz position at `ply` of `game`. Returns the
+/// verdict and the exercise attempt to record for it.
+pub fn grade(game: &Game, record: &GameAnalysisRecord, ply: usize, answer: &str) -> std::result::Result<(QuizAnswer, ExerciseResult), String> {
+    let moments = key_moments(game, record);
+    let moment = moments
+        .iter()
+        .find(|m| m.ply == ply)
+        .ok_or_else(|| format!("Move {} of game {} is not a quiz position", move_label(&game.initial_fen, ply), game.id))?;
+    let board = position_at(game, ply).ok_or_else(|| format!("Could not replay game {}", game.id))?;
+    let chess_move = parse_move(&board, answer).map_err(|e| format!("'{}' is not a legal move here: {}", answer.trim(), e))?;
+    let answer = to_san(&board, chess_move);
+    let correct = answer == moment.best;
+
+    let theme = recommended_theme(game, std::slice::from_ref(moment));
+    // A blunder leaves a bigger gap to the best move, so it is easier to spot
+    let difficulty = if moment.quality == MoveQuality::Blunder {
+        ExerciseDifficulty::Beginner
+    } else {
+        ExerciseDifficulty::Intermediate
+    };
+    let attempt = ExerciseResult {
+        id: 0,
+        profile_id: game.profile_id,
+        session_id: None,
+        exercise_type: theme.to_string(),
+        difficulty: difficulty.to_string(),
+        position_fen: board.to_string(),
+        solved: correct,
+        attempts: 1,
+        time_seconds: 0,
+        hints_used: 0,
+        created_at: String::new(),
+        flagged: false,
+        flag_reason: None,
+        alternative_solution: false,
+        scaffolding: vec![],
+    };
+
+    Ok((
+        QuizAnswer {
+            correct,
+            answer,
+            played: moment.san.clone(),
+            best: moment.best.clone(),
+            centipawn_loss: moment.centipawn_loss,
+            quality: moment.quality,
+            theme: theme.to_string(),
+            answered: None,
+            correct_so_far: None,
+        },
+        attempt,
+    ))
+}
+
+/// Next position for the active profile, from `phase` only if given. In a quiz
+/// conversation the pick varies with the questions answered, so deterministic mode still moves on.
+pub fn next_position(conversation_id: Option<i64>, phase: Option<GamePhase>) -> std::result::Result<QuizPosition, String> {
+    DB.with_conn(|conn| {
+        let Some(profile) = repositories::get_first_profile(conn)? else {
+            return Ok(None);
+        };
+        let answered = match conversation_id {
+            Some(id) => match conversation_mode::load(conn, profile.id, id)? {
+                ConversationMode::QuizMe { answered, .. } => answered,
+                _ => 0,
+            },
+            None => 0,
+        };
+        let seed = session_seed::get(conn)?.unwrap_or_else(|| chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64);
+        let mut positions = critical_positions(conn, profile.id)?;
+        positions.retain(|p| phase.is_none_or(|phase| p.phase == phase));
+        let solved = repositories::get_solved_positions(conn, profile.id)?;
+        Ok(pick(positions, &solved, &mut SeededRng::derive(seed, answered as u64)))
+    })
+    .map_err(|e| format!("Failed to find a quiz position: {}", e))?
+    .ok_or_else(|| match phase {
+        Some(phase) => format!("No analysed mistakes in the {} of recent games yet.", phase.as_str().to_lowercase()),
+        None => "No analysed mistakes in recent games yet. Play and save a few games first.".to_string(),
+    })
+}
+
+/// Grade an answer, record it as an exercise attempt and score it in the quiz conversation
+pub fn submit_answer(conversation_id: Option<i64>, game_id: i64, ply: usize, answer: &str) -> std::result::Result<QuizAnswer, String> {
+    let profile = DB
+        .with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No user profile found".to_string())?;
+    let game = DB
+        .with_conn(|conn| repositories::get_game_by_id(conn, game_id))
+        .map_err(|e| format!("Failed to load game: {}", e))?
+        .filter(|g| g.profile_id == profile.id)
+        .ok_or_else(|| format!("Game {} not found", game_id))?;
+    let record = DB
+        .with_conn(|conn| repositories::get_game_analyses(conn, game_id))
+        .map_err(|e| format!("Failed to load analysis: {}", e))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Game {} has not been analysed", game_id))?;
+
+    let (mut result, attempt) = grade(&game, &record, ply, answer)?;
+    let score = DB
+        .with_conn(|conn| {
+            repositories::record_exercise_result(conn, &attempt)?;
+            repositories::increment_exercises_completed(conn, profile.id)?;
+            match conversation_id {
+                Some(id) => conversation_mode::record_quiz_answer(conn, profile.id, id, result.correct),
+                None => Ok(None),
+            }
+        })
+        .map_err(|e| format!("Failed to record quiz answer: {}", e))?;
+    usage_analytics::record_usage(usage_analytics::QUIZ_ANSWERED);
+
+    if let Some((answered, correct)) = score {
+        result.answered = Some(answered);
+        result.correct_so_far = Some(correct);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_engine::{MoveAnalysis, ScoreFromWhite, TacticalPattern};
+
+    fn analysis(board: &Board, uci: &str, best: &str, loss: i32, quality: MoveQuality) -> MoveAnalysis {
+        MoveAnalysis {
+            move_number: 0,
+            chess_move: parse_legal_uci(board, uci).unwrap(),
+            evaluation_before: ScoreFromWhite::default(),
+            evaluation_after: ScoreFromWhite::default(),
+            best_move: parse_legal_uci(board, best).unwrap(),
+            best_move_eval: ScoreFromWhite::default(),
+            quality,
+            centipawn_loss: loss,
+            tactical_pattern: TacticalPattern::None,
+            comment: String::new(),
+            sacrifice: None,
+        }
+    }
+
+    #[test]
+    fn test_positions_and_grading() {
+        let start = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let after_e4 = start.make_move_new(parse_legal_uci(&start, "e2e4").unwrap());
+        let analyses = vec![
+            analysis(&start, "e2e4", "e2e4", 0, MoveQuality::Good),
+            analysis(&after_e4, "f7f6", "e7e5", 280, MoveQuality::Blunder),
+        ];
+        let game = Game {
+            id: 3,
+            result: "loss".to_string(),
+            player_color: "black".to_string(),
+            opponent_elo: Some(1500),
+            blunders: 1,
+            created_at: "2024-06-04T18:00:00Z".to_string(),
+            ..Game::for_test(1, &["e2e4", "f7f6"])
+        };
+        let record = GameAnalysisRecord {
+            id: 1,
+            game_id: 3,
+            engine_name: "tacticus-eval".to_string(),
+            engine_version: "0.1.0".to_string(),
+            depth: 1,
+            analysis: serde_json::to_string(&analyses).unwrap(),
+            mistakes: 0,
+            blunders: 1,
+            average_centipawn_loss: 140.0,
+            created_at: String::new(),
+        };
+
+        let positions = game_positions(&game, &record, true);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].ply, 1);
+        assert_eq!(positions[0].move_number, "1...");
+        assert_eq!(positions[0].fen, after_e4.to_string());
+        assert_eq!(positions[0].opponent, "engine (1500)");
+        assert_eq!(game_positions(&game, &record, false)[0].opponent, "engine");
+        assert_eq!(positions[0].phase, GamePhase::Opening);
+
+        let (right, attempt) = grade(&game, &record, 1, "e5").unwrap();
+        assert!(right.correct);
+        assert_eq!((right.played.as_str(), right.best.as_str()), ("f6", "e5"));
+        assert_eq!(attempt.exercise_type, "Opening");
+        assert_eq!(attempt.difficulty, "Beginner");
+        assert!(attempt.solved);
+        let (wrong, attempt) = grade(&game, &record, 1, "g8f6").unwrap();
+        assert!(!wrong.correct);
+        assert_eq!(wrong.answer, "Nf6");
+        assert!(!attempt.solved);
+        assert!(grade(&game, &record, 0, "e4").is_err());
+        assert!(grade(&game, &record, 1, "Ke2").is_err());
+
+        let mut rng = SeededRng::new(7);
+        let solved = HashSet::from([positions[0].fen.clone()]);
+        assert_eq!(pick(positions.clone(), &solved, &mut rng), Some(positions[0].clone()));
+        assert_eq!(pick(vec![], &HashSet::new(), &mut rng), None);
+    }
+}
@@ -0,0 +1,432 @@
+use chess::{Board, Color};
+use chess_core::{board_to_svg_styled, format_move, move_label, parse_fen, parse_legal_uci, DiagramStyle, MoveQuality, NotationPrefs};
+use chess_engine::MoveAnalysis;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::game_debrief::{key_moments, recommended_theme, result_sentence};
+use crate::database::repositories::{Game, GameAnalysisRecord};
+use crate::transcript::escape_html;
+
+/// Key moments drawn as diagrams, worst first
+const REPORT_DIAGRAMS: usize = 4;
+
+/// Eval graph size in pixels and the evaluation (centipawns) at its top and bottom edge
+const GRAPH_WIDTH: f64 = 640.0;
+const GRAPH_HEIGHT: f64 = 160.0;
+const GRAPH_CLAMP: i32 = 1000;
+
+const REPORT_STYLE: &str = "body { font-family: sans-serif; max-width: 760px; margin: 2em auto; color: #222; } \
+    h1 { margin-bottom: 0.2em; } .meta { color: #555; } \
+    table.moves { border-collapse: collapse; width: 100%; font-size: 0.9em; } \
+    table.moves td, table.moves th { border-bottom: 1px solid #ddd; padding: 2px 6px; text-align: left; } \
+    .mistake { color: #b36b00; } .blunder { color: #c00; } .good { color: #060; } \
+    figure { display: inline-block; width: 45%; margin: 1em 2%; vertical-align: top; } \
+    figure svg { width: 100%; height: auto; } .notes { white-space: pre-wrap; } \
+    footer { margin-top: 2em; padding-top: 1em; border-top: 2px solid #222; } \
+    @media print { body { margin: 0; max-width: none; } figure, table.moves tr, .graph { page-break-inside: avoid; } }";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "html" => Ok(ReportFormat::Html),
+            "pdf" => Ok(ReportFormat::Pdf),
+            other => Err(format!("Unknown report format: {}", other)),
+        }
+    }
+}
+
+/// Annotation glyph for a move, empty for ordinary ones
+pub fn quality_glyph(quality: MoveQuality) -> &'static str {
+    match quality {
+        MoveQuality::Brilliant => "!!",
+        MoveQuality::Great => "!",
+        MoveQuality::Good => "",
+        MoveQuality::Inaccuracy => "?!",
+        MoveQuality::Mistake => "?",
+        MoveQuality::Blunder => "??",
+    }
+}
+
+fn quality_class(quality: MoveQuality) -> &'static str {
+    match quality {
+        MoveQuality::Brilliant | MoveQuality::Great => "good",
+        MoveQuality::Good | MoveQuality::Inaccuracy => "",
+        MoveQuality::Mistake => "mistake",
+        MoveQuality::Blunder => "blunder",
+    }
+}
+
+/// One replayed half-move with the position it was played from
+struct Ply {
+    board: Board,
+    analysis: MoveAnalysis,
+}
+
+fn replay(game: &Game, analyses: &[MoveAnalysis]) -> Vec<Ply> {
+    let Ok(mut board) = parse_fen(&game.initial_fen) else {
+        return vec![];
+    };
+    let mut plies = vec![];
+    for (uci, analysis) in game.moves.iter().zip(analyses) {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else {
+            break;
+        };
+        plies.push(Ply { board, analysis: analysis.clone() });
+        board = board.make_move_new(chess_move);
+    }
+    plies
+}
+
+/// Evaluation after each ply from White's point of view, clamped for the graph
+fn white_evals(plies: &[Ply]) -> Vec<i32> {
+    plies
+        .iter()
+        .map(|ply| ply.analysis.evaluation_after.centipawns().clamp(-GRAPH_CLAMP, GRAPH_CLAMP))
+        .collect()
+}
+
+fn eval_graph(evals: &[i32]) -> String {
+    let step = GRAPH_WIDTH / evals.len().max(1) as f64;
+    let y = |eval: i32| GRAPH_HEIGHT / 2.0 - eval as f64 / GRAPH_CLAMP as f64 * (GRAPH_HEIGHT / 2.0);
+    let mut points = format!("0,{:.1}", y(0));
+    for (i, eval) in evals.iter().enumerate() {
+        points.push_str(&format!(" {:.1},{:.1}", (i + 1) as f64 * step, y(*eval)));
+    }
+    format!(
+        "<svg class=\"graph\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\">\
+         <rect width=\"{w}\" height=\"{half}\" fill=\"#f4f4f4\"/><rect y=\"{half}\" width=\"{w}\" height=\"{half}\" fill=\"#555\"/>\
+         <line x1=\"0\" y1=\"{half}\" x2=\"{w}\" y2=\"{half}\" stroke=\"#999\"/>\
+         <polyline points=\"{points}\" fill=\"none\" stroke=\"#d33\" stroke-width=\"2\"/></svg>",
+        w = GRAPH_WIDTH,
+        h = GRAPH_HEIGHT,
+        half = GRAPH_HEIGHT / 2.0,
+        points = points,
+    )
+}
+
+fn move_table(initial_fen: &str, plies: &[Ply], notation: &NotationPrefs) -> String {
+    let mut out = "<table class=\"moves\">\n<tr><th>#</th><th>Move</th><th>Eval (White)</th><th>Best</th><th>Loss</th></tr>\n".to_string();
+    for (index, (ply, eval)) in plies.iter().zip(white_evals(plies)).enumerate() {
+        let quality = ply.analysis.quality;
+        let number = move_label(initial_fen, index);
+        let best = if ply.analysis.best_move == ply.analysis.chess_move {
+            String::new()
+        } else {
+            format_move(&ply.board, ply.analysis.best_move, notation)
+        };
+        out.push_str(&format!(
+            "<tr class=\"{}\"><td>{}</td><td>{}{}</td><td>{:+.1}</td><td>{}</td><td>{}</td></tr>\n",
+            quality_class(quality),
+            number,
+            escape_html(&format_move(&ply.board, ply.analysis.chess_move, notation)),
+            quality_glyph(quality),
+            eval as f64 / 100.0,
+            escape_html(&best),
+            if ply.analysis.centipawn_loss > 0 { ply.analysis.centipawn_loss.to_string() } else { String::new() }
+        ));
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// Printable HTML report of an analysed game: eval graph, annotated move table,
+/// diagrams of the player's worst moments, the coach's notes and what to train next.
+/// `coach_notes` are the coach's messages about the game, oldest first. Moves are
+/// written the way `notation` asks.
+pub fn render_report(
+    game: &Game,
+    record: &GameAnalysisRecord,
+    coach_notes: &[String],
+    style: &DiagramStyle,
+    notation: &NotationPrefs,
+) -> String {
+    let analyses: Vec<MoveAnalysis> = serde_json::from_str(&record.analysis).unwrap_or_default();
+    let plies = replay(game, &analyses);
+    let moments = key_moments(game, record);
+    let player = if game.player_color == "black" { Color::Black } else { Color::White };
+    let date = game.finished_at.as_deref().unwrap_or(&game.created_at);
+    let title = format!(
+        "Game #{}: {} vs {}",
+        game.id,
+        game.player_color,
+        match game.opponent_elo {
+            Some(elo) => format!("{} ({})", game.opponent_type, elo),
+            None => game.opponent_type.clone(),
+        }
+    );
+
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{style}</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n<p class=\"meta\">{date} - {result}</p>\n",
+        title = escape_html(&title),
+        style = REPORT_STYLE,
+        date = escape_html(date.get(..10).unwrap_or(date)),
+        result = escape_html(&result_sentence(game)),
+    );
+    out.push_str(&format!(
+        "<p>Average centipawn loss {:.0}, {} mistakes, {} blunders. Analysed by {} {} at depth {}.</p>\n",
+        record.average_centipawn_loss,
+        record.mistakes,
+        record.blunders,
+        escape_html(&record.engine_name),
+        escape_html(&record.engine_version),
+        record.depth
+    ));
+
+    out.push_str("<h2>Evaluation</h2>\n");
+    out.push_str(&eval_graph(&white_evals(&plies)));
+    out.push('\n');
+
+    if !moments.is_empty() {
+        out.push_str("<h2>Key moments</h2>\n");
+        for moment in moments.iter().take(REPORT_DIAGRAMS) {
+            let Some(ply) = plies.get(moment.ply) else {
+                continue;
+            };
+            let comment = ply.analysis.comment.trim();
+            out.push_str(&format!(
+                "<figure>{}<figcaption>{}{}</figcaption></figure>\n",
+                board_to_svg_styled(&ply.board, player, style),
+                escape_html(&moment.describe()),
+                if comment.is_empty() { String::new() } else { format!("<br><em>{}</em>", escape_html(comment)) }
+            ));
+        }
+    }
+
+    out.push_str("<h2>Moves</h2>\n");
+    out.push_str(&move_table(&game.initial_fen, &plies, notation));
+
+    if !coach_notes.is_empty() {
+        out.push_str("<h2>Coach commentary</h2>\n");
+        for note in coach_notes {
+            out.push_str(&format!("<div class=\"notes\">{}</div>\n", escape_html(note.trim())));
+        }
+    }
+
+    let theme = recommended_theme(game, &moments);
+    let reason = match moments.first() {
+        Some(worst) => format!("Your costliest slip was {}.", worst.describe()),
+        None => "The engine found no serious slips in this game.".to_string(),
+    };
+    out.push_str(&format!(
+        "<footer><strong>Recommended training:</strong> {} puzzles. {}</footer>\n</body>\n</html>\n",
+        theme,
+        escape_html(&reason)
+    ));
+    out
+}
+
+/// Moves of each quality played by one side
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityCounts {
+    pub brilliant: u32,
+    pub great: u32,
+    pub good: u32,
+    pub inaccuracy: u32,
+    pub mistake: u32,
+    pub blunder: u32,
+}
+
+impl QualityCounts {
+    fn add(&mut self, quality: MoveQuality) {
+        match quality {
+            MoveQuality::Brilliant => self.brilliant += 1,
+            MoveQuality::Great => self.great += 1,
+            MoveQuality::Good => self.good += 1,
+            MoveQuality::Inaccuracy => self.inaccuracy += 1,
+            MoveQuality::Mistake => self.mistake += 1,
+            MoveQuality::Blunder => self.blunder += 1,
+        }
+    }
+}
+
+/// Compact summary of an analysed game, enough for a game list to draw a rich card
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameReportCard {
+    pub game_id: i64,
+    pub result: String,
+    pub player_color: String,
+    pub opening_name: Option<String>,
+    /// 0-100, from the winning chances each move gave away
+    pub white_accuracy: f64,
+    pub black_accuracy: f64,
+    pub white_counts: QualityCounts,
+    pub black_counts: QualityCounts,
+    /// Positions before the player's worst moves, worst first
+    pub key_moment_fens: Vec<String>,
+    /// Rough rating the player's play was worth in this game
+    pub performance_rating: i32,
+}
+
+/// Winning chances in percent for the side `centipawns` is measured for
+fn win_percent(centipawns: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * centipawns as f64).exp()) - 1.0)
+}
+
+/// Accuracy of one move from the winning chances before and after it, mover's point of view
+fn move_accuracy(before: i32, after: i32) -> f64 {
+    let lost = (win_percent(before) - win_percent(after)).max(0.0);
+    (103.1668 * (-0.04354 * lost).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+/// Rating estimate from the player's average centipawn loss, averaged with the
+/// result-based performance against a rated opponent when there is one
+fn performance_rating(game: &Game, average_centipawn_loss: f64) -> i32 {
+    let from_play = 3100.0 * (-0.01 * average_centipawn_loss.max(0.0)).exp();
+    let estimate = match game.opponent_elo {
+        Some(elo) => {
+            let from_result = elo as f64
+                + match game.result.as_str() {
+                    "win" => 400.0,
+                    "loss" => -400.0,
+                    _ => 0.0,
+                };
+            (from_play + from_result) / 2.0
+        }
+        None => from_play,
+    };
+    (estimate / 10.0).round() as i32 * 10
+}
+
+/// Summarise a game from its stored analysis without rendering anything
+pub fn report_card(game: &Game, record: &GameAnalysisRecord) -> GameReportCard {
+    let analyses: Vec<MoveAnalysis> = serde_json::from_str(&record.analysis).unwrap_or_default();
+    let plies = replay(game, &analyses);
+    let player = if game.player_color == "black" { Color::Black } else { Color::White };
+
+    let (mut white_counts, mut black_counts) = (QualityCounts::default(), QualityCounts::default());
+    let (mut white_accuracy, mut black_accuracy) = (vec![], vec![]);
+    let mut player_loss = vec![];
+    for ply in &plies {
+        let mover = ply.board.side_to_move();
+        let accuracy = move_accuracy(
+            ply.analysis.evaluation_before.for_side(mover),
+            ply.analysis.evaluation_after.for_side(mover),
+        );
+        match mover {
+            Color::White => {
+                white_counts.add(ply.analysis.quality);
+                white_accuracy.push(accuracy);
+            }
+            Color::Black => {
+                black_counts.add(ply.analysis.quality);
+                black_accuracy.push(accuracy);
+            }
+        }
+        if mover == player {
+            player_loss.push(ply.analysis.centipawn_loss as f64);
+        }
+    }
+    let mean = |values: &[f64]| if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 };
+
+    GameReportCard {
+        game_id: game.id,
+        result: game.result.clone(),
+        player_color: game.player_color.clone(),
+        opening_name: game.opening_name.clone(),
+        white_accuracy: (mean(&white_accuracy) * 10.0).round() / 10.0,
+        black_accuracy: (mean(&black_accuracy) * 10.0).round() / 10.0,
+        white_counts,
+        black_counts,
+        key_moment_fens: key_moments(game, record)
+            .iter()
+            .take(REPORT_DIAGRAMS)
+            .filter_map(|moment| plies.get(moment.ply).map(|ply| ply.board.to_string()))
+            .collect(),
+        performance_rating: performance_rating(game, mean(&player_loss)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_engine::{ScoreFromWhite, TacticalPattern};
+
+    fn analysis(board: &Board, uci: &str, best: &str, after: i32, loss: i32, quality: MoveQuality) -> MoveAnalysis {
+        MoveAnalysis {
+            move_number: 0,
+            chess_move: parse_legal_uci(board, uci).unwrap(),
+            evaluation_before: ScoreFromWhite::default(),
+            evaluation_after: ScoreFromWhite::new(after),
+            best_move: parse_legal_uci(board, best).unwrap(),
+            best_move_eval: ScoreFromWhite::default(),
+            quality,
+            centipawn_loss: loss,
+            tactical_pattern: TacticalPattern::None,
+            comment: String::new(),
+            sacrifice: None,
+        }
+    }
+
+    #[test]
+    fn test_render_report() {
+        let start = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let after_e4 = start.make_move_new(parse_legal_uci(&start, "e2e4").unwrap());
+        let analyses = vec![
+            analysis(&start, "e2e4", "e2e4", 30, 0, MoveQuality::Good),
+            analysis(&after_e4, "f7f6", "e7e5", 250, 280, MoveQuality::Blunder),
+        ];
+        let game = Game {
+            id: 3,
+            result: "loss".to_string(),
+            player_color: "black".to_string(),
+            opponent_elo: Some(1500),
+            blunders: 1,
+            created_at: "2024-06-01T10:00:00Z".to_string(),
+            termination: Some("resignation".to_string()),
+            ..Game::for_test(1, &["e2e4", "f7f6"])
+        };
+        let record = GameAnalysisRecord {
+            id: 1,
+            game_id: 3,
+            engine_name: "tacticus-eval".to_string(),
+            engine_version: "0.1.0".to_string(),
+            depth: 1,
+            analysis: serde_json::to_string(&analyses).unwrap(),
+            mistakes: 0,
+            blunders: 1,
+            average_centipawn_loss: 280.0,
+            created_at: String::new(),
+        };
+
+        let notes = ["Watch your <king>.".to_string()];
+        let html = render_report(&game, &record, &notes, &DiagramStyle::default(), &NotationPrefs::default());
+        assert!(html.contains("<td>1...</td><td>f6??</td><td>+2.5</td><td>e5</td><td>280</td>"));
+        assert!(html.contains("<td>1.</td><td>e4</td><td>+0.3</td>"));
+        assert!(html.contains("<figure><svg"));
+        assert!(html.contains("Watch your &lt;king&gt;."));
+        let long = NotationPrefs { moves: chess_core::MoveStyle::LongAlgebraic, ..NotationPrefs::default() };
+        let html = render_report(&game, &record, &notes, &DiagramStyle::default(), &long);
+        assert!(html.contains("<td>1...</td><td>f7-f6??</td><td>+2.5</td><td>e7-e5</td>"));
+        assert!(html.contains("Recommended training:</strong> Opening puzzles."));
+        assert_eq!("PDF".parse::<ReportFormat>(), Ok(ReportFormat::Pdf));
+        assert!("docx".parse::<ReportFormat>().is_err());
+
+        let card = report_card(&game, &record);
+        assert_eq!(card.white_accuracy, 100.0);
+        assert!(card.black_accuracy > 30.0 && card.black_accuracy < 45.0);
+        assert_eq!(card.white_counts.good, 1);
+        assert_eq!(card.black_counts.blunder, 1);
+        assert_eq!(card.key_moment_fens, vec![after_e4.to_string()]);
+        // 280 centipawns lost a move is beginner play, and losing to a 1500 is worth 1100
+        assert_eq!(card.performance_rating, 640);
+    }
+}
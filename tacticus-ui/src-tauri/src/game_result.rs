@@ -0,0 +1,196 @@
+use chess::{Board, BoardStatus, Color, Piece};
+use chess_core::{parse_fen, parse_legal_uci};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Half-moves without a capture or pawn move before either side may claim a draw
+const FIFTY_MOVE_PLIES: u32 = 100;
+
+/// Why a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Resignation,
+    InsufficientMaterial,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+}
+
+impl Termination {
+    /// Stored in the games table
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::Resignation => "resignation",
+            Termination::InsufficientMaterial => "insufficient_material",
+            Termination::ThreefoldRepetition => "threefold_repetition",
+            Termination::FiftyMoveRule => "fifty_move_rule",
+        }
+    }
+}
+
+/// A finished game as the frontend reports it. Empty `result` and `final_fen`
+/// are filled in from the moves rather than checked.
+pub struct GameSubmission<'a> {
+    pub initial_fen: &'a str,
+    pub moves: &'a [String],
+    /// "white" or "black"
+    pub player_color: &'a str,
+    /// "win", "loss" or "draw" for the player
+    pub result: &'a str,
+    pub final_fen: &'a str,
+    /// Colour that resigned, if the game ended that way
+    pub resigned_by: Option<&'a str>,
+}
+
+/// What the moves say happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedGame {
+    pub result: String,
+    pub final_fen: String,
+    pub termination: Termination,
+}
+
+/// Replay `submission` from its starting position and work out how it ended.
+/// Illegal moves, moves after the game was over, unfinished games and any
+/// result or final position that disagrees with the moves are rejected.
+pub fn verify_game(submission: &GameSubmission) -> Result<VerifiedGame, String> {
+    let player = parse_color(submission.player_color).ok_or_else(|| format!("Invalid player color '{}'", submission.player_color))?;
+    let resigned_by = submission
+        .resigned_by
+        .map(|c| parse_color(c).ok_or_else(|| format!("Invalid resigning color '{}'", c)))
+        .transpose()?;
+
+    let mut board = parse_fen(submission.initial_fen).map_err(|e| e.to_string())?;
+    let mut halfmove_clock = submission
+        .initial_fen
+        .split_whitespace()
+        .nth(4)
+        .and_then(|n| n.parse::<u32>().ok())
+        .unwrap_or(0);
+    let mut seen: HashMap<u64, u32> = HashMap::new();
+    let mut repeated = false;
+    *seen.entry(board.get_hash()).or_insert(0) += 1;
+
+    for (index, uci) in submission.moves.iter().enumerate() {
+        if board.status() != BoardStatus::Ongoing {
+            return Err(format!("Move {} ({}) was played after the game was over", index + 1, uci));
+        }
+        let chess_move = parse_legal_uci(&board, uci).map_err(|e| format!("Move {}: {}", index + 1, e))?;
+
+        let resets_clock =
+            board.piece_on(chess_move.get_source()) == Some(Piece::Pawn) || board.piece_on(chess_move.get_dest()).is_some();
+        halfmove_clock = if resets_clock { 0 } else { halfmove_clock + 1 };
+        board = board.make_move_new(chess_move);
+
+        let count = seen.entry(board.get_hash()).or_insert(0);
+        *count += 1;
+        repeated |= *count >= 3;
+    }
+
+    let (winner, termination) = match board.status() {
+        BoardStatus::Checkmate if resigned_by.is_some() => {
+            return Err("The game ended in checkmate, so nobody could resign".to_string());
+        }
+        BoardStatus::Checkmate => (Some(!board.side_to_move()), Termination::Checkmate),
+        BoardStatus::Stalemate => (None, Termination::Stalemate),
+        BoardStatus::Ongoing => match resigned_by {
+            Some(color) => (Some(!color), Termination::Resignation),
+            None if insufficient_material(&board) => (None, Termination::InsufficientMaterial),
+            None if repeated => (None, Termination::ThreefoldRepetition),
+            None if halfmove_clock >= FIFTY_MOVE_PLIES => (None, Termination::FiftyMoveRule),
+            None => return Err("The game is not over; record a resignation or keep playing".to_string()),
+        },
+    };
+
+    let result = match winner {
+        None => "draw",
+        Some(color) if color == player => "win",
+        Some(_) => "loss",
+    };
+    if !submission.result.is_empty() && submission.result != result {
+        return Err(format!(
+            "Result '{}' does not match the moves, which end in a {} by {}",
+            submission.result,
+            result,
+            termination.as_str().replace('_', " ")
+        ));
+    }
+
+    let final_fen = if submission.final_fen.trim().is_empty() {
+        board.to_string()
+    } else {
+        let claimed = parse_fen(submission.final_fen).map_err(|e| format!("Final position: {}", e))?;
+        if claimed != board {
+            return Err("Final position does not match the moves".to_string());
+        }
+        submission.final_fen.trim().to_string()
+    };
+
+    Ok(VerifiedGame {
+        result: result.to_string(),
+        final_fen,
+        termination,
+    })
+}
+
+fn parse_color(color: &str) -> Option<Color> {
+    match color {
+        "white" => Some(Color::White),
+        "black" => Some(Color::Black),
+        _ => None,
+    }
+}
+
+/// Neither side can mate: bare kings, or kings and a single minor piece
+fn insufficient_material(board: &Board) -> bool {
+    let heavy = *board.pieces(Piece::Pawn) | *board.pieces(Piece::Rook) | *board.pieces(Piece::Queen);
+    let minors = *board.pieces(Piece::Knight) | *board.pieces(Piece::Bishop);
+    heavy.popcnt() == 0 && minors.popcnt() <= 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    fn submission<'a>(moves: &'a [String], result: &'a str, resigned_by: Option<&'a str>) -> GameSubmission<'a> {
+        GameSubmission {
+            initial_fen: START,
+            moves,
+            player_color: "white",
+            result,
+            final_fen: "",
+            resigned_by,
+        }
+    }
+
+    fn moves(uci: &[&str]) -> Vec<String> {
+        uci.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infers_and_checks_result() {
+        // Fool's mate: White is mated
+        let fools_mate = moves(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+        let verified = verify_game(&submission(&fools_mate, "", None)).unwrap();
+        assert_eq!(verified.result, "loss");
+        assert_eq!(verified.termination, Termination::Checkmate);
+        assert!(verify_game(&submission(&fools_mate, "win", None)).is_err());
+        assert!(verify_game(&submission(&fools_mate, "loss", Some("black"))).is_err());
+
+        let resigned = moves(&["e2e4", "e7e5"]);
+        let verified = verify_game(&submission(&resigned, "win", Some("black"))).unwrap();
+        assert_eq!(verified.termination, Termination::Resignation);
+        assert!(verify_game(&submission(&resigned, "draw", None)).is_err());
+
+        assert!(verify_game(&submission(&moves(&["e2e4", "e2e4"]), "", Some("white"))).is_err());
+        let mut after_mate = fools_mate.clone();
+        after_mate.push("e1f2".to_string());
+        assert!(verify_game(&submission(&after_mate, "", None)).is_err());
+    }
+}
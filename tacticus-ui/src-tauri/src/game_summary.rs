@@ -0,0 +1,141 @@
+use chess_core::detect_opening;
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories::{self, Game};
+
+/// Games the coach is told about at the start of a chat
+const RECENT_GAMES: i32 = 5;
+
+/// One game as the coach's context block describes it, cached on the game row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub game_id: i64,
+    pub result: String,
+    pub player_color: String,
+    pub opening: String,
+    /// Full moves played
+    pub move_count: usize,
+    pub blunders: u32,
+    pub mistakes: u32,
+    /// From the game's current analysis; none until it has been analysed
+    pub average_centipawn_loss: Option<i32>,
+}
+
+/// Summarise `game` from its current analysis, if it has one
+pub fn build(conn: &Connection, game: &Game) -> Result<GameSummary> {
+    let analysis = repositories::get_game_analyses(conn, game.id)?.into_iter().next();
+    let opening = game
+        .opening_name
+        .clone()
+        .or_else(|| detect_opening(&game.initial_fen, &game.moves).map(|o| o.name.to_string()))
+        .unwrap_or_else(|| "an unnamed opening".to_string());
+    Ok(GameSummary {
+        game_id: game.id,
+        result: game.result.clone(),
+        player_color: game.player_color.clone(),
+        opening,
+        move_count: game.moves.len().div_ceil(2),
+        blunders: analysis.as_ref().map_or(game.blunders, |a| a.blunders).max(0) as u32,
+        mistakes: analysis.as_ref().map_or(game.mistakes, |a| a.mistakes).max(0) as u32,
+        average_centipawn_loss: analysis.map(|a| a.average_centipawn_loss.round() as i32),
+    })
+}
+
+/// The summary cached on the game row, building and caching it when there is none
+pub fn for_game(conn: &Connection, game: &Game) -> Result<GameSummary> {
+    if let Some(cached) = repositories::get_game_summary(conn, game.id)?.and_then(|json| serde_json::from_str(&json).ok()) {
+        return Ok(cached);
+    }
+    let summary = build(conn, game)?;
+    if let Ok(json) = serde_json::to_string(&summary) {
+        repositories::set_game_summary(conn, game.id, &json)?;
+    }
+    Ok(summary)
+}
+
+/// Summaries of the profile's latest games, newest first
+pub fn recent(conn: &Connection, profile_id: i64) -> Result<Vec<GameSummary>> {
+    repositories::get_recent_games(conn, profile_id, RECENT_GAMES, None)?
+        .iter()
+        .map(|game| for_game(conn, game))
+        .collect()
+}
+
+fn describe(summary: &GameSummary) -> String {
+    let accuracy = match summary.average_centipawn_loss {
+        Some(acpl) => format!("blunders {}, mistakes {}, average centipawn loss {}", summary.blunders, summary.mistakes, acpl),
+        None => "not analysed".to_string(),
+    };
+    format!(
+        "- {} as {}, {}, {} moves; {}",
+        summary.result, summary.player_color, summary.opening, summary.move_count, accuracy
+    )
+}
+
+/// The coach's context block on the student's recent games; none before they have played any
+pub fn recent_form_prompt(summaries: &[GameSummary]) -> Option<String> {
+    if summaries.is_empty() {
+        return None;
+    }
+    let count = |result: &str| summaries.iter().filter(|s| s.result == result).count();
+    let mut prompt = format!(
+        "The student's recent form, newest game first: {} won, {} drawn and {} lost of the last {}.",
+        count("win"),
+        count("draw"),
+        count("loss"),
+        summaries.len()
+    );
+    for summary in summaries {
+        prompt.push('\n');
+        prompt.push_str(&describe(summary));
+    }
+    Some(prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::GameAnalysisRecord;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_summaries_are_cached_until_reanalysed() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile_id = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap().id;
+        let game = Game {
+            created_at: chrono::Utc::now().to_rfc3339(),
+            ..Game::for_test(profile_id, &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4"])
+        };
+        let game_id = repositories::create_game(&conn, &game).unwrap();
+
+        let first = recent(&conn, profile_id).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!((first[0].opening.as_str(), first[0].move_count), ("Italian Game", 3));
+        assert_eq!(first[0].average_centipawn_loss, None);
+        assert!(repositories::get_game_summary(&conn, game_id).unwrap().is_some());
+
+        let record = GameAnalysisRecord {
+            id: 0,
+            game_id,
+            engine_name: "test".to_string(),
+            engine_version: "1".to_string(),
+            depth: 2,
+            analysis: "[]".to_string(),
+            mistakes: 2,
+            blunders: 1,
+            average_centipawn_loss: 41.6,
+            created_at: String::new(),
+        };
+        repositories::insert_game_analysis(&conn, &record).unwrap();
+        assert!(repositories::get_game_summary(&conn, game_id).unwrap().is_none());
+
+        let analysed = recent(&conn, profile_id).unwrap();
+        assert_eq!((analysed[0].blunders, analysed[0].mistakes, analysed[0].average_centipawn_loss), (1, 2, Some(42)));
+        let prompt = recent_form_prompt(&analysed).unwrap();
+        assert!(prompt.contains("1 won, 0 drawn and 0 lost of the last 1"));
+        assert!(prompt.contains("- win as white, Italian Game, 3 moves; blunders 1, mistakes 2, average centipawn loss 42"));
+        assert!(recent_form_prompt(&[]).is_none());
+    }
+}
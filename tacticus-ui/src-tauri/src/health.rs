@@ -0,0 +1,228 @@
+use chess_core::parse_fen;
+use chess_engine::Engine;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::board_assets;
+use crate::database::repositories;
+use crate::DB;
+
+/// White mates with Ra8#; the engine must find it at the sanity-check depth
+const SANITY_FEN: &str = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+const SANITY_MOVE: &str = "a1a8";
+const SANITY_DEPTH: u32 = 2;
+
+/// Seconds to wait for the model provider to confirm the API key
+#[cfg(feature = "llm")]
+const KEY_CHECK_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Works, but something the player may want to fix
+    Warning,
+    Failed,
+    /// Nothing to check, e.g. no API key set
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// "database", "engine", "api_key" or "assets"
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl HealthCheck {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status, detail: detail.into() }
+    }
+}
+
+/// What the startup self-test found, for the status indicator and the repair screen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub checks: Vec<HealthCheck>,
+    /// The worst status among the checks
+    pub overall: CheckStatus,
+    /// The database could not be used; the UI opens the repair screen
+    pub needs_repair: bool,
+    pub checked_at: String,
+}
+
+impl HealthStatus {
+    fn from_checks(checks: Vec<HealthCheck>) -> Self {
+        let has = |status: CheckStatus| checks.iter().any(|c| c.status == status);
+        let overall = if has(CheckStatus::Failed) {
+            CheckStatus::Failed
+        } else if has(CheckStatus::Warning) {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        };
+        let needs_repair = checks.iter().any(|c| c.name == "database" && c.status == CheckStatus::Failed);
+        Self { checks, overall, needs_repair, checked_at: chrono::Utc::now().to_rfc3339() }
+    }
+}
+
+lazy_static! {
+    /// Why the database file couldn't be opened at startup, while the app runs on a stand-in
+    static ref DATABASE_ERROR: Mutex<Option<String>> = Mutex::new(None);
+    static ref LAST_STATUS: Mutex<Option<HealthStatus>> = Mutex::new(None);
+    /// The database check, run once at startup and again after a repair
+    static ref DATABASE_CHECK: Mutex<Option<HealthCheck>> = Mutex::new(None);
+}
+
+/// Note that the database file failed to open, so the checks report it
+pub fn record_database_error(error: String) {
+    *DATABASE_ERROR.lock().unwrap() = Some(error);
+}
+
+/// Forget the startup failure once the database has been repaired
+pub fn clear_database_error() {
+    *DATABASE_ERROR.lock().unwrap() = None;
+    *DATABASE_CHECK.lock().unwrap() = None;
+}
+
+/// The database check, from the cache once it has run
+fn database_check() -> HealthCheck {
+    if let Some(check) = DATABASE_CHECK.lock().unwrap().clone() {
+        return check;
+    }
+    let check = check_database();
+    *DATABASE_CHECK.lock().unwrap() = Some(check.clone());
+    check
+}
+
+fn check_database() -> HealthCheck {
+    if let Some(error) = DATABASE_ERROR.lock().unwrap().clone() {
+        return HealthCheck::new(
+            "database",
+            CheckStatus::Failed,
+            format!("The database could not be opened ({}). Nothing you do now will be saved.", error),
+        );
+    }
+    match DB.with_conn(repositories::quick_check) {
+        Ok(problems) if problems == ["ok"] => HealthCheck::new("database", CheckStatus::Ok, "Opened and up to date"),
+        Ok(problems) => HealthCheck::new("database", CheckStatus::Failed, format!("Quick check: {}", problems.join("; "))),
+        Err(e) => HealthCheck::new("database", CheckStatus::Failed, format!("Quick check failed: {}", e)),
+    }
+}
+
+/// A shallow search of a mate in one, timed
+fn check_engine() -> HealthCheck {
+    let started = Instant::now();
+    let engine = match Engine::builder().depth(SANITY_DEPTH).build() {
+        Ok(engine) => engine,
+        Err(e) => return HealthCheck::new("engine", CheckStatus::Failed, format!("Engine would not start: {}", e)),
+    };
+    let board = parse_fen(SANITY_FEN).expect("sanity position is valid");
+    match engine.rank_moves(&board).first() {
+        Some(best) if best.chess_move.to_string() == SANITY_MOVE => HealthCheck::new(
+            "engine",
+            CheckStatus::Ok,
+            format!("Found the test mate in {} ms", started.elapsed().as_millis()),
+        ),
+        Some(best) => HealthCheck::new(
+            "engine",
+            CheckStatus::Failed,
+            format!("Missed the test mate, playing {} instead of {}", best.chess_move, SANITY_MOVE),
+        ),
+        None => HealthCheck::new("engine", CheckStatus::Failed, "Found no move in the test position"),
+    }
+}
+
+/// The selected piece set and board theme load; the board falls back to the defaults otherwise
+fn check_assets() -> HealthCheck {
+    let selection = match DB.with_conn(board_assets::selection) {
+        Ok(selection) => selection,
+        Err(e) => return HealthCheck::new("assets", CheckStatus::Warning, format!("Couldn't read the board settings: {}", e)),
+    };
+    match board_assets::check(&selection.0, &selection.1) {
+        Ok(()) => HealthCheck::new("assets", CheckStatus::Ok, format!("{} pieces on the {} board", selection.0, selection.1)),
+        Err(e) => HealthCheck::new("assets", CheckStatus::Warning, format!("{}; the standard board is shown instead", e)),
+    }
+}
+
+/// Ask the provider whether the key is accepted. A provider without a key
+/// endpoint, or one that can't be reached, only gives a warning.
+#[cfg(feature = "llm")]
+async fn check_api_key() -> HealthCheck {
+    let llm = crate::app_config::current().llm;
+    let Some(api_key) = llm.api_key else {
+        return HealthCheck::new("api_key", CheckStatus::Skipped, "No API key; the coach is offline");
+    };
+    let response = crate::commands::coach::HTTP_CLIENT
+        .get(format!("{}/key", llm.base_url))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .timeout(std::time::Duration::from_secs(KEY_CHECK_TIMEOUT_SECS))
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => HealthCheck::new("api_key", CheckStatus::Ok, "Accepted"),
+        Ok(response) if matches!(response.status().as_u16(), 401 | 403) => {
+            HealthCheck::new("api_key", CheckStatus::Failed, "The API key was rejected; check it in Settings")
+        }
+        Ok(response) => HealthCheck::new(
+            "api_key",
+            CheckStatus::Warning,
+            format!("Couldn't confirm the API key ({})", response.status()),
+        ),
+        Err(e) => HealthCheck::new("api_key", CheckStatus::Warning, format!("Couldn't reach the model provider: {}", e)),
+    }
+}
+
+#[cfg(not(feature = "llm"))]
+async fn check_api_key() -> HealthCheck {
+    HealthCheck::new("api_key", CheckStatus::Skipped, "This build has no online coach")
+}
+
+/// Run every check and keep the result for `last_status`. The database check is
+/// the cached one; it only runs again after a repair.
+pub async fn run_checks() -> HealthStatus {
+    let mut checks = vec![database_check(), check_engine(), check_assets()];
+    checks.push(check_api_key().await);
+    let status = HealthStatus::from_checks(checks);
+    for check in status.checks.iter().filter(|c| matches!(c.status, CheckStatus::Warning | CheckStatus::Failed)) {
+        tracing::warn!(check = %check.name, status = ?check.status, "{}", check.detail);
+    }
+    *LAST_STATUS.lock().unwrap() = Some(status.clone());
+    status
+}
+
+/// Result of the latest run, if the checks have finished
+pub fn last_status() -> Option<HealthStatus> {
+    LAST_STATUS.lock().unwrap().clone()
+}
+
+/// The latest full run if it has finished, otherwise a status from the database
+/// check alone. Enough to decide on the repair screen without waiting for the
+/// engine or the model provider.
+pub fn quick_status() -> HealthStatus {
+    last_status().unwrap_or_else(|| HealthStatus::from_checks(vec![database_check()]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_check_and_overall_status() {
+        assert_eq!(check_engine().status, CheckStatus::Ok);
+
+        let status = HealthStatus::from_checks(vec![
+            HealthCheck::new("engine", CheckStatus::Ok, ""),
+            HealthCheck::new("api_key", CheckStatus::Skipped, ""),
+            HealthCheck::new("assets", CheckStatus::Warning, ""),
+        ]);
+        assert_eq!(status.overall, CheckStatus::Warning);
+        assert!(!status.needs_repair);
+
+        let broken = HealthStatus::from_checks(vec![HealthCheck::new("database", CheckStatus::Failed, "")]);
+        assert_eq!(broken.overall, CheckStatus::Failed);
+        assert!(broken.needs_repair);
+    }
+}
@@ -0,0 +1,93 @@
+use chess_trainer::ExerciseDifficulty;
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories;
+use crate::moderation::ModerationPolicy;
+use crate::DB;
+
+const KID_MODE_SETTING_KEY: &str = "kid_mode";
+
+/// Extra system prompt appended to the coach prompt when kid mode is on
+pub use chess_trainer::kid_mode::PROMPT_GUIDANCE;
+
+/// Response length cap for kid mode, keeping explanations short
+pub const MAX_TOKENS: u32 = 300;
+
+/// Multiplier applied to time allowances in timed modes
+pub const TIME_ALLOWANCE_MULTIPLIER: u32 = 2;
+
+pub fn is_enabled(conn: &Connection, profile_id: i64) -> Result<bool> {
+    Ok(repositories::get_profile_setting(conn, profile_id, KID_MODE_SETTING_KEY)?
+        .map(|v| v == "true")
+        .unwrap_or(false))
+}
+
+/// Toggle kid mode, keeping the profile's moderation policy in step
+pub fn set_enabled(conn: &Connection, profile_id: i64, enabled: bool) -> Result<()> {
+    repositories::set_profile_setting(
+        conn,
+        profile_id,
+        KID_MODE_SETTING_KEY,
+        if enabled { "true" } else { "false" },
+    )?;
+
+    let mut policy = ModerationPolicy::load(conn, profile_id)?;
+    policy.kid_mode = enabled;
+    policy.save(conn, profile_id)
+}
+
+/// Whether kid mode is on for the active profile
+pub fn active() -> bool {
+    DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => is_enabled(conn, profile.id),
+        None => Ok(false),
+    })
+    .unwrap_or(false)
+}
+
+/// Concepts shown in kid mode are limited to the beginner library
+pub fn allows_concept(difficulty: &str) -> bool {
+    difficulty.parse::<ExerciseDifficulty>().is_ok_and(|d| d == ExerciseDifficulty::Beginner)
+}
+
+/// Puzzles served in kid mode stop at intermediate difficulty
+pub fn allows_exercise(difficulty: &ExerciseDifficulty) -> bool {
+    *difficulty <= ExerciseDifficulty::Intermediate
+}
+
+/// Scale a timed-mode allowance for kid mode
+pub fn time_allowance(base_seconds: u32, kid_mode: bool) -> u32 {
+    if kid_mode {
+        base_seconds * TIME_ALLOWANCE_MULTIPLIER
+    } else {
+        base_seconds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_toggle_updates_moderation_policy() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Kid", "beginner", 600).unwrap();
+
+        assert!(!is_enabled(&conn, profile.id).unwrap());
+        set_enabled(&conn, profile.id, true).unwrap();
+        assert!(is_enabled(&conn, profile.id).unwrap());
+        assert!(ModerationPolicy::load(&conn, profile.id).unwrap().kid_mode);
+    }
+
+    #[test]
+    fn test_restrictions() {
+        assert!(allows_exercise(&ExerciseDifficulty::Intermediate));
+        assert!(!allows_exercise(&ExerciseDifficulty::Advanced));
+        assert!(allows_concept("Beginner"));
+        assert!(!allows_concept("Advanced"));
+        assert_eq!(time_allowance(60, true), 120);
+        assert_eq!(time_allowance(60, false), 60);
+    }
+}
@@ -1,77 +1,344 @@
+pub mod analysis_scheduler;
+pub mod app_config;
+pub mod attempt_flags;
+pub mod board_assets;
+pub mod board_context;
+pub mod board_recognition;
+pub mod coach_actions;
+pub mod coach_cache;
+pub mod coach_greeting;
+pub mod coach_throttle;
+pub mod coach_trace;
+pub mod coach_verbosity;
+pub mod coach_tools;
 mod commands;
+pub mod conversation_mode;
+pub mod daily_workout;
+pub mod data_wipe;
 pub mod database;
+pub mod demo_seed;
+pub mod dgt_board;
+pub mod encouragement;
+pub mod engine_bench;
+pub mod engine_strength;
+pub mod exercise_postmortem;
+pub mod exercise_queue;
+pub mod file_open;
+pub mod game_assistance;
+pub mod game_comparison;
+pub mod game_debrief;
+pub mod game_import;
+pub mod game_quiz;
+pub mod game_report;
+pub mod game_result;
+pub mod game_summary;
+pub mod health;
+pub mod kid_mode;
+pub mod logging;
+pub mod mate_drills;
+pub mod mock_llm;
+pub mod moderation;
+pub mod move_mentions;
+pub mod move_quality;
+pub mod notation_prefs;
+pub mod onboarding;
+pub mod opening_deviation;
+pub mod opening_walkthrough;
+pub mod operation_journal;
+pub mod past_advice;
+pub mod position_import;
+pub mod position_library;
+pub mod progress_diff;
+pub mod prompt_privacy;
+pub mod quick_answers;
+pub mod session_seed;
+pub mod sync_merge;
+pub mod time_control;
+pub mod training_plan;
+pub mod transcript;
+pub mod usage_analytics;
 
 #[macro_use]
 extern crate lazy_static;
 
 use commands::*;
+pub use commands::coach::{chat_with_coach, CoachMessage, CoachResponse};
 use database::Database;
 use std::sync::Arc;
 
 lazy_static! {
-    pub static ref DB: Arc<Database> = Arc::new(
-        Database::new().expect("Failed to initialize database")
-    );
+    pub static ref DB: Arc<Database> = Arc::new(Database::open_or_fallback());
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize stored data on startup
-    commands::user::init_api_key();
-    commands::user::init_profile();
-    
-    tauri::Builder::default()
+    logging::init(logging::stored_level());
+    app_config::log_problems();
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "starting Tacticus");
+
+    let mut builder = tauri::Builder::default();
+    // A file double-clicked while Tacticus runs starts a second process on Windows and
+    // Linux. It hands its arguments to the running window and exits before setup, so it
+    // never opens the database or starts the workers.
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            if file_open::open_launch_args(argv.into_iter().map(std::ffi::OsString::from)) > 0 {
+                file_open::announce(app);
+            }
+            if let Some(window) = tauri::Manager::get_webview_window(app, "main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }));
+    }
+
+    builder
         .plugin(tauri_plugin_opener::init())
+        .setup(|_app| {
+            // Initialize stored data on startup
+            commands::user::init_api_key();
+            commands::user::init_profile();
+            commands::session::init_session_recovery();
+            commands::storage::init_storage_maintenance();
+            // Self-test in the background; the UI asks for the result with get_health_status
+            tauri::async_runtime::spawn(health::run_checks());
+            // Generates exercises from recent games while the user is away
+            exercise_queue::start_worker();
+            // A double-clicked .pgn or .fen arrives as an argument on Windows and Linux
+            file_open::open_launch_args(std::env::args_os());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Game commands
             get_initial_position,
             get_legal_moves,
             make_move,
             get_engine_move,
+            check_for_blunder,
+            check_retry_move,
+            format_move_list,
             evaluate_position,
             get_position_from_fen,
+            explain_evaluation,
+            get_threats,
+            get_resilient_defenses,
+            fen_from_image,
+            parse_voice_move,
             // Training commands
             get_training_exercises,
+            generate_theme_drill,
+            start_bookmark_exercise,
+            start_exercise_set,
+            report_user_activity,
+            start_mate_pattern_drill,
+            detect_mate_patterns,
             check_exercise_solution,
+            restart_exercise_line,
             get_exercise_hint,
+            build_exercise_postmortem,
+            get_exercise_postmortem,
             get_all_exercise_types,
+            generate_daily_workout,
             // Coach commands
             get_coach_greeting,
             chat_with_coach,
+            get_quick_answer,
+            resolve_coach_actions,
+            get_coach_setup,
+            prepare_coach_prompt,
+            search_past_advice,
+            next_guided_question,
+            reserve_coach_call,
             get_position_feedback,
             analyze_position_with_coach,
+            explain_opponent_plan,
             check_api_key_configured,
+            get_llm_base_url,
+            llm_enabled,
+            get_moderation_policy,
+            update_moderation_policy,
+            get_model_routing,
+            set_model_override,
+            clear_coach_cache,
+            get_coach_transparency,
+            set_coach_transparency,
+            record_coach_trace,
+            get_message_trace,
+            set_board_context,
+            get_board_context_prompt,
+            check_move_mentions,
             // User commands
             get_user_profile,
-            create_user_profile,
             update_user_elo,
             get_user_stats,
             save_api_key,
             get_api_key,
             has_completed_onboarding,
+            get_kid_mode,
+            set_kid_mode,
+            get_coach_verbosity,
+            set_coach_verbosity,
+            get_game_assistance,
+            set_game_assistance,
+            get_prompt_privacy,
+            set_prompt_privacy,
+            get_encouragement_settings,
+            set_encouragement_settings,
+            get_encouragement,
+            get_flag_suspicious_attempts,
+            set_flag_suspicious_attempts,
+            // Onboarding commands
+            start_onboarding,
+            submit_onboarding_step,
+            finish_onboarding,
             // Learning commands
             get_all_concepts,
             get_concept,
+            lookup_concept,
             get_concepts_by_category,
             search_concepts,
             get_concept_categories,
             define_term,
             get_related_concepts,
+            get_opening_lines,
+            start_opening_walkthrough,
+            answer_opening_quiz,
+            explain_opening_step,
+            get_model_games,
+            get_model_games_by_concept,
+            get_guided_replay_step,
+            // Quiz commands
+            start_concept_quiz,
+            submit_quiz_answer,
+            get_quiz_position,
+            check_quiz_answer,
+            get_concept_mastery,
             // Data commands (for AI agent and persistence)
             save_game,
             get_recent_games,
             search_games_by_opening,
             get_games_with_mistakes,
+            delete_game,
+            get_recent_deviations,
+            import_game,
+            import_position,
+            take_opened_files,
+            bookmark_position,
+            get_saved_positions,
+            update_saved_position,
+            delete_saved_position,
             record_exercise_result,
             get_training_progress,
+            start_training_session,
+            finish_training_session,
+            get_session_history,
+            get_session_details,
             get_player_stats,
             get_improvement_trend,
             get_weakness_history,
+            get_theme_performance,
+            get_opponent_strength_stats,
+            get_time_class_stats,
+            get_progress_diff,
             create_conversation,
             add_message,
             get_conversation_messages,
             get_recent_conversations,
+            delete_conversation,
+            undo_last_operation,
+            export_conversation,
+            export_game_report,
+            get_game_report_card,
+            compare_games,
+            get_game_replay,
+            // Activity tracking
+            start_activity,
+            stop_activity,
+            get_time_breakdown,
+            export_sync_batch,
+            merge_sync_batch,
+            get_usage_analytics_enabled,
+            set_usage_analytics_enabled,
+            get_usage_stats,
+            // Analysis commands
+            get_mistake_heatmap,
+            narrate_game,
+            render_narration_audio,
+            request_analysis,
+            start_game_analysis,
+            get_analysis_job,
+            cancel_analysis_job,
+            reanalyze_games,
+            get_game_analysis_history,
+            get_move_quality_settings,
+            set_move_quality_settings,
+            // Electronic board
+            list_dgt_ports,
+            connect_dgt_board,
+            disconnect_dgt_board,
+            sync_dgt_board,
+            get_dgt_status,
+            poll_dgt_events,
+            // Diagnostics
+            get_log_level,
+            set_log_level,
+            get_session_seed,
+            set_session_seed,
+            collect_diagnostics,
+            run_engine_bench,
+            get_bench_history,
+            assess_engine_strength,
+            get_engine_strength,
+            // Storage maintenance
+            get_storage_report,
+            vacuum_database,
+            check_database_integrity,
+            get_health_status,
+            get_quick_health_status,
+            repair_database,
+            set_pruning_policy,
+            prune_storage,
+            request_wipe_token,
+            wipe_all_data,
+            wipe_conversations,
+            wipe_games,
+            start_opening_backfill,
+            get_opening_backfill_progress,
+            // Session autosave and crash recovery
+            save_session_snapshot,
+            discard_session_snapshot,
+            get_recoverable_sessions,
+            // Piece sets and board themes
+            list_board_assets,
+            get_board_appearance,
+            set_board_appearance,
+            reload_board_assets,
+            get_notation_prefs,
+            set_notation_prefs,
+            // Training plan, flashcards and coach proposals
+            propose_training_plan_item,
+            propose_game_review,
+            propose_flashcard,
+            get_pending_coach_actions,
+            resolve_pending_coach_action,
+            get_training_plan,
+            complete_training_plan_item,
+            delete_training_plan_item,
+            reset_training_plan,
+            get_flashcards,
+            delete_flashcard,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // macOS hands over double-clicked files as an event, also while the app is running
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                let paths = urls.into_iter().filter_map(|url| url.to_file_path().ok());
+                if file_open::open_paths(paths) > 0 {
+                    file_open::announce(_app);
+                }
+            }
+        });
 }
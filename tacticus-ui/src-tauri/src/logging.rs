@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use crate::app_config;
+
+pub const LOG_LEVEL_SETTING_KEY: &str = "log_level";
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+pub const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+const LOG_FILE_PREFIX: &str = "tacticus";
+/// Daily log files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+/// Lines of recent log output included in a diagnostics bundle
+const MAX_EXCERPT_LINES: usize = 500;
+const REDACTED: &str = "[REDACTED]";
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// Keeps the background log writer alive; dropping it flushes and stops file logging
+static FILE_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+/// Directory holding the rotating log files
+pub fn log_dir() -> PathBuf {
+    chess_config::Config::app_dir().join("logs")
+}
+
+/// Canonical form of a user-supplied level, or None if it isn't one of `LOG_LEVELS`
+pub fn normalize_level(level: &str) -> Option<&'static str> {
+    let level = level.trim().to_lowercase();
+    LOG_LEVELS.iter().copied().find(|l| *l == level)
+}
+
+fn filter_for(level: &str) -> EnvFilter {
+    // Keep HTTP and TLS internals quiet unless explicitly tracing
+    EnvFilter::new(format!("{level},hyper=warn,reqwest=warn,rustls=warn"))
+}
+
+/// Level from settings or the config, falling back to `DEFAULT_LOG_LEVEL`
+pub fn stored_level() -> &'static str {
+    normalize_level(&app_config::current().log_level).unwrap_or(DEFAULT_LOG_LEVEL)
+}
+
+/// Install the global subscriber: stderr plus a daily-rotated file in `log_dir()`.
+/// Safe to call more than once; only the first call takes effect.
+pub fn init(level: &str) {
+    let (filter, handle) = reload::Layer::new(filter_for(level));
+
+    let file_layer = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir())
+        .map_err(|e| eprintln!("File logging disabled: {}", e))
+        .ok()
+        .map(|appender| {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = FILE_GUARD.set(guard);
+            fmt::layer().with_ansi(false).with_writer(writer)
+        });
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .try_init()
+        .is_ok();
+
+    if installed {
+        let _ = FILTER_HANDLE.set(handle);
+    }
+}
+
+/// Change the active level without restarting
+pub fn set_level(level: &str) -> Result<&'static str, String> {
+    let level = normalize_level(level).ok_or_else(|| format!("Unknown log level: {}", level))?;
+    if let Some(handle) = FILTER_HANDLE.get() {
+        handle
+            .reload(filter_for(level))
+            .map_err(|e| format!("Failed to change log level: {}", e))?;
+    }
+    tracing::info!(level, "log level changed");
+    Ok(level)
+}
+
+/// Replace known secrets and anything shaped like an API key (`sk-...`) or bearer token.
+/// Markers only count at the start of a word, so "task-42" is left alone.
+pub fn scrub_secrets(text: &str, secrets: &[String]) -> String {
+    let mut scrubbed = text.to_string();
+    for secret in secrets.iter().filter(|s| s.len() >= 8) {
+        scrubbed = scrubbed.replace(secret.as_str(), REDACTED);
+    }
+
+    for marker in ["sk-", "Bearer "] {
+        let mut result = String::with_capacity(scrubbed.len());
+        let mut rest = scrubbed.as_str();
+        while let Some(start) = rest.find(marker) {
+            result.push_str(&rest[..start]);
+            let token_start = start + marker.len();
+            let token_len = rest[token_start..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'))
+                .unwrap_or(rest.len() - token_start);
+            let word_start = result.chars().next_back().is_none_or(|c| !(c.is_alphanumeric() || c == '_'));
+            if token_len > 0 && word_start {
+                result.push_str(REDACTED);
+                rest = &rest[token_start + token_len..];
+            } else {
+                result.push_str(marker);
+                rest = &rest[token_start..];
+            }
+        }
+        result.push_str(rest);
+        scrubbed = result;
+    }
+
+    scrubbed
+}
+
+/// The last `MAX_EXCERPT_LINES` lines across the rotated log files, oldest first
+pub fn recent_log_lines() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(log_dir()) else {
+        return Vec::new();
+    };
+
+    // Rotated names embed the date, so name order is chronological
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(LOG_FILE_PREFIX)))
+        .collect();
+    files.sort();
+
+    let mut lines: Vec<String> = Vec::new();
+    for file in files.iter().rev() {
+        let Ok(content) = std::fs::read_to_string(file) else { continue };
+        let mut file_lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+        file_lines.append(&mut lines);
+        lines = file_lines;
+        if lines.len() >= MAX_EXCERPT_LINES {
+            break;
+        }
+    }
+
+    let skip = lines.len().saturating_sub(MAX_EXCERPT_LINES);
+    lines.split_off(skip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_level() {
+        assert_eq!(normalize_level(" DEBUG "), Some("debug"));
+        assert_eq!(normalize_level("warn"), Some("warn"));
+        assert_eq!(normalize_level("verbose"), None);
+    }
+
+    #[test]
+    fn test_scrub_secrets() {
+        let text = "key=sk-or-v1-abc123 header: Bearer tok.en_9 custom hunter2hunter2 end sk-";
+        let scrubbed = scrub_secrets(text, &["hunter2hunter2".to_string(), "short".to_string()]);
+
+        assert_eq!(scrubbed, "key=[REDACTED] header: [REDACTED] custom [REDACTED] end sk-");
+        assert_eq!(scrub_secrets("task-42 and disk-usage", &[]), "task-42 and disk-usage");
+    }
+}
@@ -1,6 +1,41 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+/// Release builds on Windows start without a console, so command-line tools write
+/// to the one they were run from
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // SAFETY: AttachConsole takes a process id by value and fails harmlessly without a parent console
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli: Option<fn(&[String]) -> i32> = match args.first().map(String::as_str) {
+        Some("bench") => Some(tacticus_ui_lib::engine_bench::run_cli),
+        Some("seed") => Some(tacticus_ui_lib::demo_seed::run_cli),
+        Some("board") => Some(tacticus_ui_lib::notation_prefs::run_cli),
+        _ => None,
+    };
+    if let Some(run_cli) = cli {
+        attach_parent_console();
+        std::process::exit(run_cli(&args[1..]));
+    }
+    if args.iter().any(|arg| arg == "--mock-llm") {
+        attach_parent_console();
+        if let Err(e) = tacticus_ui_lib::mock_llm::install() {
+            eprintln!("Failed to start the mock LLM: {}", e);
+            std::process::exit(1);
+        }
+    }
     tacticus_ui_lib::run()
 }
@@ -0,0 +1,230 @@
+use chess::{Board, Color};
+use chess_core::{parse_fen, parse_legal_uci, to_san};
+use chess_engine::{MatePattern, MatePatternFinder, PatternSighting};
+use chess_trainer::{Exercise, ExerciseDifficulty, ExerciseType};
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories::{self, Game};
+
+/// Mate pattern drills take ids from here up, clear of bookmark exercises. The
+/// offset encodes the game, the ply and the pattern; game 0 stands for the
+/// pattern's example position.
+pub const EXERCISE_ID_BASE: usize = 2_000_000_000;
+
+/// Plies per game an id can point at
+const PLY_SLOTS: usize = 1024;
+
+/// Recent games searched for positions where a pattern was on the board
+const SCANNED_GAMES: i32 = 20;
+
+pub fn is_drill_id(exercise_id: usize) -> bool {
+    exercise_id >= EXERCISE_ID_BASE
+}
+
+pub fn exercise_id(game_id: i64, ply: usize, pattern: MatePattern) -> usize {
+    let index = MatePattern::ALL.iter().position(|p| *p == pattern).unwrap_or(0);
+    EXERCISE_ID_BASE + (game_id.max(0) as usize * PLY_SLOTS + ply.min(PLY_SLOTS - 1)) * MatePattern::ALL.len() + index
+}
+
+fn decode(exercise_id: usize) -> Option<(i64, usize, MatePattern)> {
+    let offset = exercise_id.checked_sub(EXERCISE_ID_BASE)?;
+    let pattern = MatePattern::ALL[offset % MatePattern::ALL.len()];
+    let position = offset / MatePattern::ALL.len();
+    Some(((position / PLY_SLOTS) as i64, position % PLY_SLOTS, pattern))
+}
+
+/// An exercise on `board` for `pattern`, if the side to move can play it there
+pub fn to_exercise(board: &Board, pattern: MatePattern) -> Option<Exercise> {
+    let sightings: Vec<PatternSighting> =
+        MatePatternFinder::find(board).into_iter().filter(|s| s.pattern == pattern).collect();
+    let shortest = sightings.iter().map(|s| s.mate_in).min()?;
+    let sighting = sightings.iter().find(|s| s.mate_in == shortest)?;
+
+    // Any mate solves a mate in one; longer mates need a first move that forces the pattern
+    let mut solutions: Vec<String> = sightings
+        .iter()
+        .filter(|s| s.mate_in == shortest)
+        .map(|s| s.first_move().to_string())
+        .collect();
+    if shortest == Some(1) {
+        for chess_move in chess::MoveGen::new_legal(board) {
+            let uci = chess_move.to_string();
+            if board.make_move_new(chess_move).status() == chess::BoardStatus::Checkmate && !solutions.contains(&uci) {
+                solutions.push(uci);
+            }
+        }
+    }
+
+    let side = if board.side_to_move() == Color::White { "White" } else { "Black" };
+    let (difficulty, description, mut hints) = match shortest {
+        Some(1) => (ExerciseDifficulty::Beginner, format!("{} to move and mate in one.", side), vec![]),
+        Some(_) => (
+            ExerciseDifficulty::Intermediate,
+            format!("{} to move and mate in two.", side),
+            vec!["Start with a check that leaves the king no good reply.".to_string()],
+        ),
+        None => (
+            ExerciseDifficulty::Advanced,
+            format!("{} to move. The pieces are set for a classic attack; start it.", side),
+            vec![],
+        ),
+    };
+    hints.insert(0, pattern.hint().to_string());
+
+    let explanation = match shortest {
+        Some(_) => format!("{}: {}.", pattern.name(), san_line(board, &sighting.line)),
+        None => format!(
+            "{}: {} opens the king. After the king takes, the knight checks and the queen joins on the h-file. \
+             Before playing it in a game, check that the defender can't bring a piece back to guard h7 or h5 in time.",
+            pattern.name(),
+            san_line(board, &sighting.line)
+        ),
+    };
+
+    Some(
+        Exercise::new(
+            ExerciseType::Tactics,
+            difficulty,
+            board.to_string(),
+            pattern.name().to_string(),
+            description,
+            solutions,
+            explanation,
+        )
+        .with_hints(hints),
+    )
+}
+
+/// The drill exercise behind an id, from a game of this profile or a pattern's example
+pub fn find_exercise(conn: &Connection, profile_id: i64, exercise_id: usize) -> Result<Option<Exercise>> {
+    let Some((game_id, ply, pattern)) = decode(exercise_id) else {
+        return Ok(None);
+    };
+    let board = if game_id == 0 {
+        parse_fen(pattern.example_fen()).ok()
+    } else {
+        repositories::get_game_by_id(conn, game_id)?
+            .filter(|game| game.profile_id == profile_id)
+            .and_then(|game| position_at(&game, ply))
+    };
+    Ok(board.and_then(|board| to_exercise(&board, pattern)))
+}
+
+/// Up to `count` drill exercises with their ids: positions from the profile's
+/// recent games where they could have played `pattern` (any pattern when none),
+/// newest first, topped up with the patterns' example positions
+pub fn drill(
+    conn: &Connection,
+    profile_id: i64,
+    pattern: Option<MatePattern>,
+    count: usize,
+) -> Result<Vec<(usize, Exercise)>> {
+    let wanted = |p: MatePattern| pattern.is_none_or(|wanted| wanted == p);
+    let mut exercises = Vec::new();
+
+    for game in repositories::get_recent_games(conn, profile_id, SCANNED_GAMES, None)? {
+        let player = if game.player_color == "black" { Color::Black } else { Color::White };
+        for (ply, board) in positions(&game).into_iter().enumerate() {
+            if exercises.len() >= count {
+                return Ok(exercises);
+            }
+            if board.side_to_move() != player {
+                continue;
+            }
+            let mut seen = Vec::new();
+            for sighting in MatePatternFinder::find(&board) {
+                if !wanted(sighting.pattern) || seen.contains(&sighting.pattern) {
+                    continue;
+                }
+                seen.push(sighting.pattern);
+                if let Some(exercise) = to_exercise(&board, sighting.pattern) {
+                    exercises.push((exercise_id(game.id, ply, sighting.pattern), exercise));
+                }
+            }
+        }
+    }
+
+    for example in MatePattern::ALL.into_iter().filter(|p| wanted(*p)) {
+        if exercises.len() >= count {
+            break;
+        }
+        let board = parse_fen(example.example_fen()).ok();
+        if let Some(exercise) = board.and_then(|board| to_exercise(&board, example)) {
+            exercises.push((exercise_id(0, 0, example), exercise));
+        }
+    }
+    exercises.truncate(count);
+    Ok(exercises)
+}
+
+/// The position before each move of `game`, stopping at the first unreadable one
+fn positions(game: &Game) -> Vec<Board> {
+    let Ok(mut board) = parse_fen(&game.initial_fen) else {
+        return vec![];
+    };
+    let mut boards = Vec::with_capacity(game.moves.len());
+    for uci in game.moves.iter().take(PLY_SLOTS) {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else {
+            break;
+        };
+        boards.push(board);
+        board = board.make_move_new(chess_move);
+    }
+    boards
+}
+
+fn position_at(game: &Game, ply: usize) -> Option<Board> {
+    positions(game).get(ply).copied()
+}
+
+fn san_line(board: &Board, line: &[String]) -> String {
+    let mut board = *board;
+    let mut moves = Vec::new();
+    for uci in line {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else {
+            break;
+        };
+        moves.push(to_san(&board, chess_move));
+        board = board.make_move_new(chess_move);
+    }
+    moves.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_drills_from_games_and_examples() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+
+        // White could have played Nf7# on the first move but didn't
+        let game = Game {
+            initial_fen: MatePattern::SmotheredMate.example_fen().to_string(),
+            result: "draw".to_string(),
+            ..Game::for_test(profile.id, &["a1b1", "h7h6"])
+        };
+        let game_id = repositories::create_game(&conn, &game).unwrap();
+
+        let drill = drill(&conn, profile.id, Some(MatePattern::SmotheredMate), 5).unwrap();
+        assert_eq!(drill.len(), 2);
+        assert_eq!(drill[0].0, exercise_id(game_id, 0, MatePattern::SmotheredMate));
+        assert_eq!(drill[0].1.solution_moves, vec!["g5f7"]);
+        assert!(drill[0].1.explanation.starts_with("Smothered Mate: Nf7"));
+        // The example position tops the drill up
+        assert_eq!(drill[1].0, exercise_id(0, 0, MatePattern::SmotheredMate));
+
+        let found = find_exercise(&conn, profile.id, drill[0].0).unwrap().unwrap();
+        assert_eq!(found.position, drill[0].1.position);
+        assert!(find_exercise(&conn, profile.id + 1, drill[0].0).unwrap().is_none());
+        assert_eq!(decode(exercise_id(7, 31, MatePattern::GreekGift)), Some((7, 31, MatePattern::GreekGift)));
+
+        for pattern in MatePattern::ALL {
+            let exercise = find_exercise(&conn, profile.id, exercise_id(0, 0, pattern)).unwrap();
+            assert!(exercise.is_some_and(|e| e.title == pattern.name()), "no example exercise for {:?}", pattern);
+        }
+    }
+}
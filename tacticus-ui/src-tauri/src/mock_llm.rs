@@ -0,0 +1,304 @@
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use crate::coach_tools::LOOKUP_CONCEPT;
+use crate::commands::learning::get_concept_library;
+
+/// Key the app is given when running against the mock; any key is accepted
+pub const MOCK_API_KEY: &str = "mock-llm";
+
+/// A reply the mock gives to the next request
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockReply {
+    Text(String),
+    /// Ask for one tool call with these arguments
+    ToolCall { name: String, arguments: Value },
+    /// Fail the request with this HTTP status
+    Error(u16),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    script: VecDeque<MockReply>,
+    requests: Vec<Value>,
+}
+
+/// An OpenRouter-compatible chat completions server on localhost. Replies come from a
+/// script, in order, and once it runs out from canned demo replies, so a run is the
+/// same every time. Requests with `"stream": true` get server-sent events like
+/// OpenRouter's. Serves until the process exits.
+pub struct MockLlm {
+    base_url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockLlm {
+    pub fn start() -> io::Result<MockLlm> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let base_url = format!("http://{}/api/v1", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(State::default()));
+        let shared = Arc::clone(&state);
+        std::thread::Builder::new().name("mock-llm".to_string()).spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = Arc::clone(&shared);
+                std::thread::spawn(move || {
+                    if let Err(e) = serve(stream, &state) {
+                        tracing::debug!("mock LLM connection failed: {}", e);
+                    }
+                });
+            }
+        })?;
+        tracing::info!(%base_url, "mock LLM listening");
+        Ok(MockLlm { base_url, state })
+    }
+
+    /// Use as `llm.base_url`; requests go to `{base_url}/chat/completions`
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Queue `reply` after any already scripted
+    pub fn push(&self, reply: MockReply) {
+        self.state.lock().unwrap().script.push_back(reply);
+    }
+
+    /// Bodies of the requests received so far, oldest first
+    pub fn requests(&self) -> Vec<Value> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Forget the script and the requests received
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.script.clear();
+        state.requests.clear();
+    }
+}
+
+/// Start a mock and send every model call the app makes to it, for demos without a key
+pub fn install() -> io::Result<MockLlm> {
+    let mock = MockLlm::start()?;
+    crate::app_config::override_llm_endpoint(mock.base_url(), MOCK_API_KEY);
+    Ok(mock)
+}
+
+fn serve(stream: TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let mut stream = stream;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    // The webview checks cross-origin requests before sending them
+    if method == "OPTIONS" {
+        return respond(&mut stream, 204, "text/plain", "");
+    }
+    if method != "POST" || !path.ends_with("/chat/completions") {
+        return respond(&mut stream, 404, "application/json", &error_body("No such endpoint"));
+    }
+    let request: Value = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return respond(&mut stream, 400, "application/json", &error_body(&e.to_string())),
+    };
+
+    let (reply, id) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request.clone());
+        (state.script.pop_front(), state.requests.len())
+    };
+    let reply = reply.unwrap_or_else(|| demo_reply(&request));
+    let model = request["model"].as_str().unwrap_or("mock").to_string();
+    match reply {
+        MockReply::Error(status) => {
+            respond(&mut stream, status, "application/json", &error_body(&format!("Scripted failure for {}", model)))
+        }
+        reply if request["stream"] == json!(true) => {
+            respond(&mut stream, 200, "text/event-stream", &stream_body(&model, id, &reply))
+        }
+        reply => respond(&mut stream, 200, "application/json", &completion(&model, id, &reply).to_string()),
+    }
+}
+
+/// Without a script: look up a concept the user names, then explain it from the
+/// tool result; otherwise say that no model was asked
+fn demo_reply(request: &Value) -> MockReply {
+    let messages = request["messages"].as_array().cloned().unwrap_or_default();
+    let Some(last) = messages.last() else {
+        return MockReply::Text("Mock coach here. Ask me anything about your chess.".to_string());
+    };
+    let content = last["content"].as_str().unwrap_or_default();
+
+    if last["role"] == "tool" {
+        let lookup: Value = serde_json::from_str(content).unwrap_or_default();
+        return match (lookup["name"].as_str(), lookup["explanation"].as_str()) {
+            (Some(name), Some(explanation)) => MockReply::Text(format!("Here is the library on {}: {}", name, explanation)),
+            _ => MockReply::Text("I couldn't find that in the library, but let's look at it on the board.".to_string()),
+        };
+    }
+
+    let offers_lookup = request["tools"]
+        .as_array()
+        .is_some_and(|tools| tools.iter().any(|t| t["function"]["name"] == LOOKUP_CONCEPT));
+    let asked = content.to_lowercase();
+    // Longest name first, so "knight fork" wins over "fork"
+    let mut concepts = get_concept_library();
+    concepts.sort_by_key(|c| std::cmp::Reverse(c.name.len()));
+    if let Some(concept) = concepts.iter().find(|c| offers_lookup && asked.contains(&c.name.to_lowercase())) {
+        return MockReply::ToolCall { name: LOOKUP_CONCEPT.to_string(), arguments: json!({ "name": concept.name }) };
+    }
+
+    MockReply::Text(format!(
+        "This is the mock coach, so no model read \"{}\". Add an OpenRouter API key and start Tacticus without \
+         --mock-llm for real coaching.",
+        content.trim()
+    ))
+}
+
+fn message(id: usize, reply: &MockReply) -> Value {
+    match reply {
+        MockReply::ToolCall { name, arguments } => json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": format!("call_{}", id),
+                "type": "function",
+                "function": { "name": name, "arguments": arguments.to_string() }
+            }]
+        }),
+        MockReply::Text(text) => json!({ "role": "assistant", "content": text }),
+        MockReply::Error(_) => unreachable!("errors are answered with their status"),
+    }
+}
+
+fn finish_reason(reply: &MockReply) -> &'static str {
+    match reply {
+        MockReply::ToolCall { .. } => "tool_calls",
+        _ => "stop",
+    }
+}
+
+fn completion(model: &str, id: usize, reply: &MockReply) -> Value {
+    json!({
+        "id": format!("mock-{}", id),
+        "object": "chat.completion",
+        "model": model,
+        "choices": [{ "index": 0, "message": message(id, reply), "finish_reason": finish_reason(reply) }]
+    })
+}
+
+/// `reply` as server-sent events: text a word at a time, a tool call's name first and
+/// its arguments in two pieces, then the finish reason and `[DONE]`
+fn stream_body(model: &str, id: usize, reply: &MockReply) -> String {
+    let mut deltas = vec![];
+    match reply {
+        MockReply::Text(text) => {
+            deltas.extend(text.split_inclusive(' ').map(|word| json!({ "content": word })));
+        }
+        MockReply::ToolCall { name, arguments } => {
+            let arguments = arguments.to_string();
+            let (head, tail) = arguments.split_at(arguments.len() / 2);
+            deltas.push(json!({ "tool_calls": [{
+                "index": 0,
+                "id": format!("call_{}", id),
+                "type": "function",
+                "function": { "name": name, "arguments": "" }
+            }] }));
+            for piece in [head, tail] {
+                deltas.push(json!({ "tool_calls": [{ "index": 0, "function": { "arguments": piece } }] }));
+            }
+        }
+        MockReply::Error(_) => unreachable!("errors are answered with their status"),
+    }
+
+    let chunk = |delta: Value, finish_reason: Option<&str>| {
+        let chunk = json!({
+            "id": format!("mock-{}", id),
+            "object": "chat.completion.chunk",
+            "model": model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }]
+        });
+        format!("data: {}\n\n", chunk)
+    };
+    let mut body: String = deltas.into_iter().map(|delta| chunk(delta, None)).collect();
+    body.push_str(&chunk(json!({}), Some(finish_reason(reply))));
+    body.push_str("data: [DONE]\n\n");
+    body
+}
+
+fn error_body(message: &str) -> String {
+    json!({ "error": { "message": message } }).to_string()
+}
+
+fn respond(stream: &mut TcpStream, status: u16, content_type: &str, body: &str) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Authorization, Content-Type, HTTP-Referer, X-Title\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_replies_look_up_named_concepts() {
+        let tools = json!([{ "type": "function", "function": { "name": LOOKUP_CONCEPT } }]);
+        let asked = json!({ "messages": [{ "role": "user", "content": "What is a knight fork?" }], "tools": tools });
+        match demo_reply(&asked) {
+            MockReply::ToolCall { name, arguments } => {
+                assert_eq!(name, LOOKUP_CONCEPT);
+                assert_eq!(arguments["name"], "Knight Fork");
+            }
+            other => panic!("expected a lookup, got {:?}", other),
+        }
+
+        let without_tools = json!({ "messages": [{ "role": "user", "content": "What is a knight fork?" }] });
+        assert!(matches!(demo_reply(&without_tools), MockReply::Text(_)));
+
+        let looked_up = json!({ "messages": [
+            { "role": "tool", "content": r#"{"name":"Pin","explanation":"A piece can't move."}"# }
+        ] });
+        assert_eq!(demo_reply(&looked_up), MockReply::Text("Here is the library on Pin: A piece can't move.".to_string()));
+
+        let body = stream_body("m", 1, &MockReply::Text("Play e4 now".to_string()));
+        assert_eq!(body.matches("\"content\"").count(), 3);
+        assert!(body.ends_with("data: [DONE]\n\n"));
+    }
+}
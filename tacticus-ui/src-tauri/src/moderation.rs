@@ -0,0 +1,213 @@
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories;
+use crate::DB;
+
+const POLICY_SETTING_KEY: &str = "moderation_policy";
+
+/// Words masked whenever profanity filtering is enabled
+const PROFANITY: &[&str] = &[
+    "damn", "damned", "dammit", "crap", "shit", "shitty", "bullshit", "fuck", "fucking",
+    "fucked", "bitch", "bastard", "ass", "asshole", "piss", "pissed", "dick", "bloody",
+];
+
+/// Additional words masked in kid mode
+const KID_MODE_WORDS: &[&str] = &[
+    "hell", "stupid", "idiot", "idiotic", "moron", "dumb", "suck", "sucks", "kill", "killer",
+    "murder", "slaughter", "butcher", "butchered", "beer", "wine", "drunk",
+];
+
+/// Topics the coach should never give advice on. Matched as whole words against the response.
+const OFF_TOPIC_KEYWORDS: &[(&str, &[&str])] = &[
+    ("financial", &["invest", "investing", "investment", "stocks", "crypto", "bitcoin", "loan", "mortgage", "gambling", "betting"]),
+    ("medical", &["diagnosis", "diagnose", "medication", "prescription", "dosage", "antidepressant", "symptoms", "therapy"]),
+    ("legal", &["lawsuit", "attorney", "lawyer", "sue"]),
+];
+
+const OFF_TOPIC_REPLACEMENT: &str = "That is outside what I can help with - I'm a chess coach. \
+    Let's get back to the board: is there a position or a game you'd like to look at?";
+
+/// Per-profile output moderation settings for coach responses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModerationPolicy {
+    /// Mask profanity in coach output
+    pub filter_profanity: bool,
+    /// Replace responses that give financial, medical, or legal advice
+    pub block_off_topic: bool,
+    /// Stricter word list and always block off-topic advice
+    pub kid_mode: bool,
+}
+
+impl Default for ModerationPolicy {
+    fn default() -> Self {
+        Self {
+            filter_profanity: true,
+            block_off_topic: false,
+            kid_mode: false,
+        }
+    }
+}
+
+impl ModerationPolicy {
+    pub fn load(conn: &Connection, profile_id: i64) -> Result<Self> {
+        let policy = repositories::get_profile_setting(conn, profile_id, POLICY_SETTING_KEY)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Ok(policy)
+    }
+
+    pub fn save(&self, conn: &Connection, profile_id: i64) -> Result<()> {
+        let json = serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string());
+        repositories::set_profile_setting(conn, profile_id, POLICY_SETTING_KEY, &json)
+    }
+
+    /// Whether any output can be changed, in which case a reply must be checked whole
+    /// before it is shown rather than streamed
+    pub fn filters_anything(&self) -> bool {
+        self.filter_profanity || self.blocks_off_topic()
+    }
+
+    fn blocks_off_topic(&self) -> bool {
+        self.block_off_topic || self.kid_mode
+    }
+
+    fn is_masked(&self, word: &str) -> bool {
+        let lower = word.to_lowercase();
+        (self.filter_profanity || self.kid_mode) && PROFANITY.contains(&lower.as_str())
+            || self.kid_mode && KID_MODE_WORDS.contains(&lower.as_str())
+    }
+}
+
+/// Policy for the active profile, falling back to defaults when none is stored
+pub fn active_policy() -> ModerationPolicy {
+    DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => ModerationPolicy::load(conn, profile.id),
+        None => Ok(ModerationPolicy::default()),
+    })
+    .unwrap_or_default()
+}
+
+/// Result of running coach output through a moderation policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeratedText {
+    pub content: String,
+    pub masked_words: usize,
+    pub blocked_topic: Option<String>,
+}
+
+impl ModeratedText {
+    pub fn was_modified(&self) -> bool {
+        self.masked_words > 0 || self.blocked_topic.is_some()
+    }
+}
+
+/// Apply a moderation policy to a piece of coach output
+pub fn moderate(text: &str, policy: &ModerationPolicy) -> ModeratedText {
+    if policy.blocks_off_topic() {
+        if let Some(topic) = detect_off_topic(text) {
+            return ModeratedText {
+                content: OFF_TOPIC_REPLACEMENT.to_string(),
+                masked_words: 0,
+                blocked_topic: Some(topic.to_string()),
+            };
+        }
+    }
+
+    let mut content = String::with_capacity(text.len());
+    let mut masked_words = 0;
+
+    for (is_word, segment) in split_words(text) {
+        if is_word && policy.is_masked(segment) {
+            content.push_str(&"*".repeat(segment.chars().count()));
+            masked_words += 1;
+        } else {
+            content.push_str(segment);
+        }
+    }
+
+    ModeratedText {
+        content,
+        masked_words,
+        blocked_topic: None,
+    }
+}
+
+fn detect_off_topic(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = split_words(text)
+        .filter(|(is_word, _)| *is_word)
+        .map(|(_, w)| w.to_lowercase())
+        .collect();
+
+    OFF_TOPIC_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| words.iter().any(|w| keywords.contains(&w.as_str())))
+        .map(|(topic, _)| *topic)
+}
+
+/// Split text into alternating word / non-word segments, preserving everything
+fn split_words(text: &str) -> impl Iterator<Item = (bool, &str)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut in_word = false;
+
+    for (i, c) in text.char_indices() {
+        let is_word_char = c.is_alphanumeric();
+        if i > start && is_word_char != in_word {
+            segments.push((in_word, &text[start..i]));
+            start = i;
+        }
+        in_word = is_word_char;
+    }
+
+    if start < text.len() {
+        segments.push((in_word, &text[start..]));
+    }
+
+    segments.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masks_profanity_on_word_boundaries() {
+        let policy = ModerationPolicy::default();
+        let result = moderate("Damn, that bishop is strong. Hello class.", &policy);
+        assert_eq!(result.content, "****, that bishop is strong. Hello class.");
+        assert_eq!(result.masked_words, 1);
+    }
+
+    #[test]
+    fn test_kid_mode_uses_stricter_list() {
+        let policy = ModerationPolicy {
+            kid_mode: true,
+            ..ModerationPolicy::default()
+        };
+        let result = moderate("Your knight will kill the attack.", &policy);
+        assert_eq!(result.content, "Your knight will **** the attack.");
+
+        let relaxed = moderate("Your knight will kill the attack.", &ModerationPolicy::default());
+        assert!(!relaxed.was_modified());
+    }
+
+    #[test]
+    fn test_filters_anything() {
+        assert!(ModerationPolicy::default().filters_anything());
+        let off = ModerationPolicy { filter_profanity: false, ..ModerationPolicy::default() };
+        assert!(!off.filters_anything());
+        assert!(ModerationPolicy { kid_mode: true, ..off.clone() }.filters_anything());
+    }
+
+    #[test]
+    fn test_blocks_off_topic_advice() {
+        let policy = ModerationPolicy {
+            block_off_topic: true,
+            ..ModerationPolicy::default()
+        };
+        let result = moderate("You should invest your prize money in crypto.", &policy);
+        assert_eq!(result.blocked_topic.as_deref(), Some("financial"));
+        assert_eq!(result.content, OFF_TOPIC_REPLACEMENT);
+    }
+}
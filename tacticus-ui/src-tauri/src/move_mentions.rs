@@ -0,0 +1,361 @@
+use chess::{Board, ChessMove, File, MoveGen, Piece, Rank, Square};
+use chess_core::notation::piece_name;
+use chess_core::{color_name, parse_fen, to_san};
+use chess_engine::{Engine, MoveEvaluation};
+use serde::{Deserialize, Serialize};
+
+/// Moves checked per message; more than this is a pasted game, not a question
+const MAX_MENTIONS: usize = 3;
+
+/// Plies searched for each mentioned move and the engine's own choice
+const MENTION_DEPTH: u32 = 2;
+
+/// Scores past this are mates
+const MATE_THRESHOLD: i32 = 50_000;
+
+/// Words before a bare square that make it a square rather than a pawn move, as in
+/// "the knight on e5"
+const SQUARE_WORDS: &[&str] = &["on", "to", "at", "from", "onto", "towards", "square", "the", "of"];
+
+/// A move the user wrote in chat, checked against the board on screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MoveMention {
+    Legal {
+        /// As the user wrote it
+        text: String,
+        san: String,
+        uci: String,
+        /// After the move, in pawns from White's point of view. This and the fields
+        /// below are none while a solution is locked, since any score would give it away.
+        evaluation: Option<f32>,
+        /// The engine's choice and its evaluation
+        best: Option<String>,
+        best_evaluation: Option<f32>,
+        /// Centipawns the move trails the engine's choice by
+        centipawn_loss: Option<i32>,
+    },
+    /// Fits more than one legal move, e.g. "Nd2" with both knights able to go there
+    Ambiguous { text: String, options: Vec<String> },
+    Illegal { text: String, reason: String },
+}
+
+/// What a move-like word asks for; anything left out matches any legal move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Pattern {
+    piece: Option<Piece>,
+    from_file: Option<File>,
+    from_rank: Option<Rank>,
+    to: Option<Square>,
+    promotion: Option<Piece>,
+    /// Kingside when true
+    castle: Option<bool>,
+}
+
+impl Pattern {
+    fn matches(&self, board: &Board, chess_move: ChessMove) -> bool {
+        let source = chess_move.get_source();
+        let piece = board.piece_on(source);
+        if let Some(kingside) = self.castle {
+            let file_diff = chess_move.get_dest().get_file().to_index() as i32 - source.get_file().to_index() as i32;
+            return piece == Some(Piece::King) && file_diff == if kingside { 2 } else { -2 };
+        }
+        self.piece.is_none_or(|p| piece == Some(p))
+            && self.from_file.is_none_or(|f| source.get_file() == f)
+            && self.from_rank.is_none_or(|r| source.get_rank() == r)
+            && self.to.is_none_or(|to| chess_move.get_dest() == to)
+            // An unstated promotion is taken to be a queen, as everyone means
+            && match (self.promotion, chess_move.get_promotion()) {
+                (None, promotion) => promotion.is_none() || promotion == Some(Piece::Queen),
+                (wanted, promotion) => wanted == promotion,
+            }
+    }
+
+    fn matching(&self, board: &Board) -> Vec<ChessMove> {
+        MoveGen::new_legal(board).filter(|m| self.matches(board, *m)).collect()
+    }
+}
+
+fn file_of(c: char) -> Option<File> {
+    ('a'..='h').contains(&c).then(|| File::from_index(c as usize - 'a' as usize))
+}
+
+fn rank_of(c: char) -> Option<Rank> {
+    ('1'..='8').contains(&c).then(|| Rank::from_index(c as usize - '1' as usize))
+}
+
+fn piece_of(c: char) -> Option<Piece> {
+    match c.to_ascii_uppercase() {
+        'K' => Some(Piece::King),
+        'Q' => Some(Piece::Queen),
+        'R' => Some(Piece::Rook),
+        'B' => Some(Piece::Bishop),
+        'N' => Some(Piece::Knight),
+        _ => None,
+    }
+}
+
+/// Read a word as a move in SAN, long algebraic or UCI. A bare square counts only
+/// when `previous` doesn't make it a place on the board.
+fn pattern(word: &str, previous: Option<&str>) -> Option<Pattern> {
+    let word = word.trim_end_matches(['+', '#', '!', '?']);
+    match word.replace('0', "O").to_ascii_uppercase().as_str() {
+        "O-O" => return Some(Pattern { castle: Some(true), ..Pattern::default() }),
+        "O-O-O" => return Some(Pattern { castle: Some(false), ..Pattern::default() }),
+        _ => {}
+    }
+
+    let mut chars: Vec<char> = word.chars().collect();
+    let mut pattern = Pattern::default();
+    // Only capitals name pieces; a lower-case b is the b-file
+    if let Some(piece) = chars.first().filter(|c| c.is_ascii_uppercase()).and_then(|c| piece_of(*c)) {
+        pattern.piece = Some(piece);
+        chars.remove(0);
+    }
+    if let Some(&last) = chars.last() {
+        let promoted = piece_of(last).filter(|p| *p != Piece::King);
+        let before = chars.len().checked_sub(2).map(|i| chars[i]);
+        let after_rank = matches!(before, Some('1' | '8' | '='));
+        // Lower case only after "=" or in UCI, so "e8b" isn't read as a bishop
+        if promoted.is_some() && after_rank && (last.is_ascii_uppercase() || before == Some('=') || chars.len() == 5) {
+            pattern.promotion = promoted;
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+    }
+    if chars.len() < 2 {
+        return None;
+    }
+    let (middle, square) = chars.split_at(chars.len() - 2);
+    pattern.to = Some(Square::make_square(rank_of(square[1])?, file_of(square[0])?));
+
+    let coordinates: Vec<char> = middle.iter().copied().filter(|c| !matches!(c, 'x' | '-' | ':')).collect();
+    if coordinates.len() > 2 || middle.len() > coordinates.len() + 1 {
+        return None;
+    }
+    for c in coordinates {
+        match (file_of(c), rank_of(c)) {
+            (Some(file), _) if pattern.from_file.is_none() => pattern.from_file = Some(file),
+            (_, Some(rank)) if pattern.from_rank.is_none() => pattern.from_rank = Some(rank),
+            _ => return None,
+        }
+    }
+
+    let bare_square = pattern.piece.is_none() && middle.is_empty() && pattern.promotion.is_none();
+    if bare_square && previous.is_some_and(|p| SQUARE_WORDS.contains(&p.to_lowercase().as_str())) {
+        return None;
+    }
+    // Without a piece letter it's a pawn move, unless it is UCI and says nothing of the piece
+    let uci = middle.len() == 2 && pattern.from_file.is_some() && pattern.from_rank.is_some();
+    if pattern.piece.is_none() && !uci {
+        pattern.piece = Some(Piece::Pawn);
+    }
+    Some(pattern)
+}
+
+/// Strip sentence punctuation, quotes and annotation marks around a word
+fn trim_word(word: &str) -> &str {
+    word.trim_matches(|c: char| matches!(c, '(' | ')' | '[' | ']' | ',' | '.' | ';' | ':' | '"' | '\'' | '`'))
+        .trim_end_matches(['?', '!'])
+}
+
+fn format_eval(score_from_white: i32) -> String {
+    match score_from_white {
+        s if s >= MATE_THRESHOLD => "a forced mate for White".to_string(),
+        s if s <= -MATE_THRESHOLD => "a forced mate for Black".to_string(),
+        s => format!("{:+.2}", s as f32 / 100.0),
+    }
+}
+
+/// Why nothing legal fits `pattern` on `board`
+fn illegal_reason(board: &Board, pattern: &Pattern, text: &str) -> String {
+    let side = color_name(board.side_to_move());
+    let other_side = board.null_move().filter(|other| !pattern.matching(other).is_empty());
+    if other_side.is_some() {
+        return format!("{} would be {}'s move, but it is {} to move", text, color_name(!board.side_to_move()), side);
+    }
+    let in_check = if board.checkers().popcnt() > 0 { format!(", and {} is in check", side) } else { String::new() };
+    match (pattern.castle, pattern.piece, pattern.to) {
+        (Some(_), _, _) => format!("{} can't castle that way here{}", side, in_check),
+        (None, Some(piece), _) if (board.pieces(piece) & board.color_combined(board.side_to_move())).popcnt() == 0 => {
+            format!("{} has no {} left", side, piece_name(piece).to_lowercase())
+        }
+        (None, Some(piece), Some(to)) => {
+            format!("no {} of {}'s can legally go to {}{}", piece_name(piece).to_lowercase(), side, to, in_check)
+        }
+        _ => format!("{} is not a legal move for {} here{}", text, side, in_check),
+    }
+}
+
+/// Moves the user names in `message`, each resolved on `board`: the legal move with
+/// the engine's view of it, the moves it could mean, or why it can't be played.
+/// `reveal_scores` adds the evaluations and the engine's own choice, which a locked
+/// solution must not.
+pub fn check(board: &Board, message: &str, reveal_scores: bool) -> Vec<MoveMention> {
+    let words: Vec<&str> = message.split_whitespace().map(trim_word).collect();
+    let mut found: Vec<(String, Pattern)> = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| words[i]);
+        if let Some(pattern) = pattern(word, previous) {
+            if !found.iter().any(|(text, _)| text == word) {
+                found.push((word.to_string(), pattern));
+            }
+        }
+        if found.len() == MAX_MENTIONS {
+            break;
+        }
+    }
+    if found.is_empty() {
+        return vec![];
+    }
+
+    let engine = Engine::builder().depth(MENTION_DEPTH).build().expect("depth within MAX_DEPTH");
+    let mut ranked: Option<Vec<MoveEvaluation>> = None;
+    let side = board.side_to_move();
+    found
+        .into_iter()
+        .map(|(text, pattern)| match pattern.matching(board).as_slice() {
+            [] => MoveMention::Illegal { reason: illegal_reason(board, &pattern, &text), text },
+            [chess_move] if !reveal_scores => MoveMention::Legal {
+                text,
+                san: to_san(board, *chess_move),
+                uci: chess_move.to_string(),
+                evaluation: None,
+                best: None,
+                best_evaluation: None,
+                centipawn_loss: None,
+            },
+            [chess_move] => {
+                let ranked = ranked.get_or_insert_with(|| engine.rank_moves(board));
+                let score = |m: ChessMove| ranked.iter().find(|e| e.chess_move == m).map(|e| e.score).unwrap_or_default();
+                let best = ranked.first().map(|e| e.chess_move).unwrap_or(*chess_move);
+                MoveMention::Legal {
+                    text,
+                    san: to_san(board, *chess_move),
+                    uci: chess_move.to_string(),
+                    evaluation: Some(score(*chess_move).to_white(side).pawns()),
+                    best: Some(to_san(board, best)),
+                    best_evaluation: Some(score(best).to_white(side).pawns()),
+                    centipawn_loss: Some((score(best) - score(*chess_move)).max(0)),
+                }
+            }
+            options => {
+                let mut options: Vec<String> = options.iter().map(|m| to_san(board, *m)).collect();
+                options.sort();
+                MoveMention::Ambiguous { text, options }
+            }
+        })
+        .collect()
+}
+
+/// Prompt block telling the coach what the moves in the user's message amount to
+pub fn to_prompt(mentions: &[MoveMention]) -> String {
+    let mut lines = vec![
+        "The player's message names moves. They were checked on the board above; trust these results over your \
+         own reading of the position, discuss only legal moves, and never analyse a move listed as illegal."
+            .to_string(),
+    ];
+    for mention in mentions {
+        lines.push(match mention {
+            MoveMention::Legal { text, san, uci, evaluation, best, best_evaluation, centipawn_loss } => {
+                let mut line = format!("- {}: legal, {} ({}).", text, san, uci);
+                match (evaluation, best, best_evaluation, centipawn_loss) {
+                    (Some(evaluation), Some(best), Some(best_evaluation), Some(centipawn_loss)) => {
+                        line.push_str(&format!(
+                            " Evaluation after it: {} (positive favours White).",
+                            format_eval((evaluation * 100.0).round() as i32)
+                        ));
+                        if best == san {
+                            line.push_str(" It is also the engine's choice.");
+                        } else {
+                            line.push_str(&format!(
+                                " The engine prefers {} at {}, {} centipawns better.",
+                                best,
+                                format_eval((best_evaluation * 100.0).round() as i32),
+                                centipawn_loss
+                            ));
+                        }
+                    }
+                    _ => line.push_str(" Its evaluation is withheld until the exercise is over; don't judge it."),
+                }
+                line
+            }
+            MoveMention::Ambiguous { text, options } => format!(
+                "- {}: ambiguous; it could be {}. Ask which one they mean before analysing it.",
+                text,
+                options.join(" or ")
+            ),
+            MoveMention::Illegal { text, reason } => {
+                format!("- {}: illegal, {}. Tell the player it can't be played and why.", text, reason)
+            }
+        });
+    }
+    lines.join("\n")
+}
+
+/// The moves in one message and the prompt block describing them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveCheck {
+    pub mentions: Vec<MoveMention>,
+    pub prompt: String,
+}
+
+/// Moves in `message` checked against the board at `fen`; none when the message
+/// names no moves or the FEN doesn't parse
+pub fn check_fen(fen: &str, message: &str, reveal_scores: bool) -> Option<MoveCheck> {
+    let board = parse_fen(fen).ok()?;
+    let mentions = check(&board, message, reveal_scores);
+    if mentions.is_empty() {
+        return None;
+    }
+    let prompt = to_prompt(&mentions);
+    Some(MoveCheck { mentions, prompt })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mentions_are_resolved_flagged_or_rejected() {
+        let start = Board::default();
+        let mentions = check(&start, "What about Nf3? Or maybe Qh5, or 1. e4 e5", true);
+        assert_eq!(mentions.len(), 3);
+        match &mentions[0] {
+            MoveMention::Legal { san, uci, best, .. } => {
+                assert_eq!((san.as_str(), uci.as_str()), ("Nf3", "g1f3"));
+                assert!(best.is_some());
+            }
+            other => panic!("expected Nf3 to be legal, got {:?}", other),
+        }
+        assert!(matches!(&mentions[1], MoveMention::Illegal { text, reason } if text == "Qh5" && reason.contains("h5")));
+        assert!(matches!(&mentions[2], MoveMention::Legal { san, .. } if san == "e4"));
+
+        // Both knights reach d2 here
+        let board = parse_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPP2PPP/RNBQKB1R w KQkq - 0 4").unwrap();
+        let mentions = check(&board, "is Nd2 any good?", false);
+        match &mentions[..] {
+            [MoveMention::Ambiguous { options, .. }] => assert_eq!(options, &vec!["Nbd2".to_string(), "Nfd2".to_string()]),
+            other => panic!("expected an ambiguous knight move, got {:?}", other),
+        }
+
+        // Black's move named on White's turn, and squares that aren't moves
+        let mentions = check(&start, "why not e5, with the pawn on e4?", true);
+        match &mentions[..] {
+            [MoveMention::Illegal { reason, .. }] => assert!(reason.contains("black's move")),
+            other => panic!("expected only e5, got {:?}", other),
+        }
+        assert!(check(&start, "Hello there, coach", true).is_empty());
+
+        let locked = check(&start, "e2e4", false);
+        assert!(matches!(
+            &locked[0],
+            MoveMention::Legal { evaluation: None, best: None, best_evaluation: None, centipawn_loss: None, .. }
+        ));
+        let prompt = to_prompt(&locked);
+        assert!(prompt.contains("- e2e4: legal, e4 (e2e4)."));
+        assert!(!prompt.contains("Evaluation after it"));
+    }
+}
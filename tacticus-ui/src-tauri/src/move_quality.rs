@@ -0,0 +1,89 @@
+use chess_engine::QualityCalibration;
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories;
+use crate::DB;
+
+/// Move quality settings, stored as JSON
+const MOVE_QUALITY_SETTING_KEY: &str = "move_quality";
+
+/// How game analysis judges moves. Games analysed earlier keep their judgements
+/// until they are re-analysed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveQualitySettings {
+    /// Largest centipawn loss still judged Brilliant, Great, Good, Inaccuracy and Mistake
+    pub thresholds: [i32; 5],
+    /// Widen the buckets to the player's current rating
+    pub scale_by_rating: bool,
+    /// Count a loss for less when the position was already decided
+    pub account_for_sharpness: bool,
+}
+
+impl Default for MoveQualitySettings {
+    fn default() -> Self {
+        let calibration = QualityCalibration::default();
+        Self {
+            thresholds: calibration.thresholds,
+            scale_by_rating: true,
+            account_for_sharpness: calibration.account_for_sharpness,
+        }
+    }
+}
+
+impl MoveQualitySettings {
+    pub fn calibration(&self, rating: Option<i32>) -> QualityCalibration {
+        QualityCalibration {
+            thresholds: self.thresholds,
+            rating: rating.filter(|_| self.scale_by_rating).map(|r| r.max(0) as u32),
+            account_for_sharpness: self.account_for_sharpness,
+        }
+    }
+}
+
+pub fn get(conn: &Connection) -> Result<MoveQualitySettings> {
+    Ok(repositories::get_setting(conn, MOVE_QUALITY_SETTING_KEY)?
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default())
+}
+
+pub fn set(conn: &Connection, settings: &MoveQualitySettings) -> Result<()> {
+    let value = serde_json::to_string(settings).unwrap_or_default();
+    repositories::set_setting(conn, MOVE_QUALITY_SETTING_KEY, &value)
+}
+
+/// Calibration for the active profile's rating
+pub fn calibration(conn: &Connection) -> Result<QualityCalibration> {
+    let rating = repositories::get_first_profile(conn)?.map(|p| p.current_elo);
+    Ok(get(conn)?.calibration(rating))
+}
+
+/// `calibration` against the app database, falling back to the defaults
+pub fn active() -> QualityCalibration {
+    DB.with_conn(calibration).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        assert_eq!(get(&conn).unwrap(), MoveQualitySettings::default());
+        repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        assert_eq!(calibration(&conn).unwrap().rating, Some(900));
+
+        let settings = MoveQualitySettings {
+            thresholds: [30, 60, 120, 250, 500],
+            scale_by_rating: false,
+            account_for_sharpness: false,
+        };
+        set(&conn, &settings).unwrap();
+        assert_eq!(get(&conn).unwrap(), settings);
+        let calibration = calibration(&conn).unwrap();
+        assert_eq!((calibration.rating, calibration.thresholds[4]), (None, 500));
+    }
+}
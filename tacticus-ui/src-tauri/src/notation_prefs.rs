@@ -0,0 +1,97 @@
+use chess::Color;
+use chess_core::{board_to_text, color_from_name, parse_fen, NotationPrefs};
+use rusqlite::{Connection, Result};
+use serde::Serialize;
+
+use crate::app_config;
+use crate::database::repositories;
+
+/// Settings rows of each preference, read back through `app_config`
+const MOVES_SETTING: &str = "notation_moves";
+const PIECES_SETTING: &str = "notation_pieces";
+const LANGUAGE_SETTING: &str = "notation_language";
+const COORDINATES_SETTING: &str = "notation_coordinates";
+
+/// Name a preference is stored under, as the config file spells it
+fn name(value: impl Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+pub fn load(conn: &Connection) -> NotationPrefs {
+    app_config::with_settings(conn).notation
+}
+
+pub fn save(conn: &Connection, prefs: &NotationPrefs) -> Result<()> {
+    repositories::set_setting(conn, MOVES_SETTING, &name(prefs.moves))?;
+    repositories::set_setting(conn, PIECES_SETTING, &name(prefs.pieces))?;
+    repositories::set_setting(conn, LANGUAGE_SETTING, &name(prefs.language))?;
+    repositories::set_setting(conn, COORDINATES_SETTING, &prefs.coordinates.to_string())
+}
+
+/// Preferences in effect, for code not already holding the database
+pub fn active() -> NotationPrefs {
+    app_config::current().notation
+}
+
+/// Guidance for the coach when the user reads moves in anything but English SAN
+pub fn prompt_guidance(prefs: &NotationPrefs) -> Option<String> {
+    if prefs.is_standard() {
+        return None;
+    }
+    Some(format!(
+        "{} The board context and tool results you are given use standard English notation; convert moves \
+         from them when you quote them.",
+        prefs.describe()
+    ))
+}
+
+/// `tacticus board <FEN> [white|black]`: print a position with the stored preferences
+pub fn run_cli(args: &[String]) -> i32 {
+    let usage = "usage: tacticus board <FEN> [white|black]";
+    let (fen, orientation) = match args {
+        [] => {
+            eprintln!("{}", usage);
+            return 2;
+        }
+        // Only the full names; "w" and "b" are the FEN's own side to move
+        [fen @ .., side] if side == "white" || side == "black" => (fen.join(" "), color_from_name(side)),
+        fen => (fen.join(" "), None),
+    };
+    let board = match parse_fen(&fen) {
+        Ok(board) => board,
+        Err(e) => {
+            eprintln!("{}\n{}", e, usage);
+            return 2;
+        }
+    };
+    println!("{}", board_to_text(&board, orientation.unwrap_or(Color::White), &active()));
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+    use chess_core::{MoveStyle, PieceLanguage, PieceStyle};
+
+    #[test]
+    fn test_saved_preferences_are_read_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        assert_eq!(load(&conn), NotationPrefs::default());
+        assert!(prompt_guidance(&load(&conn)).is_none());
+
+        let prefs = NotationPrefs {
+            moves: MoveStyle::LongAlgebraic,
+            pieces: PieceStyle::Figurine,
+            language: PieceLanguage::Es,
+            coordinates: false,
+        };
+        save(&conn, &prefs).unwrap();
+        assert_eq!(load(&conn), prefs);
+        assert!(prompt_guidance(&prefs).unwrap().contains("long algebraic"));
+    }
+}
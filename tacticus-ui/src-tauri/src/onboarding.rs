@@ -0,0 +1,181 @@
+use chess_trainer::{Exercise, ExerciseDifficulty, ExerciseLibrary, ExerciseType};
+use serde::{Deserialize, Serialize};
+
+/// Calibration puzzles served per theme
+const PUZZLES_PER_THEME: usize = 2;
+
+/// Rating assumed when every calibration step was skipped
+pub const DEFAULT_RATING: i32 = 800;
+
+const MIN_RATING: i32 = 400;
+const MAX_RATING: i32 = 2400;
+
+/// Rating a puzzle of this difficulty is pitched at
+pub fn difficulty_rating(difficulty: &ExerciseDifficulty) -> i32 {
+    match difficulty {
+        ExerciseDifficulty::Beginner => 600,
+        ExerciseDifficulty::Intermediate => 1000,
+        ExerciseDifficulty::Advanced => 1400,
+        ExerciseDifficulty::Expert => 1800,
+    }
+}
+
+/// Weakness/strength label for a puzzle theme, phrased so the strategy library recognises it
+pub fn theme_label(theme: &ExerciseType) -> &'static str {
+    match theme {
+        ExerciseType::Tactics | ExerciseType::Calculation => "Tactics",
+        ExerciseType::Opening => "Opening principles",
+        ExerciseType::Endgame | ExerciseType::Defense => "Endgame technique",
+        ExerciseType::Positional | ExerciseType::Strategy => "Positional play",
+    }
+}
+
+/// Calibration puzzles as (library index, exercise): a few per theme, easiest first
+pub fn calibration_set() -> Vec<(usize, Exercise)> {
+    let mut per_theme: Vec<(ExerciseType, usize)> = Vec::new();
+    let mut set: Vec<(usize, Exercise)> = Vec::new();
+
+    for (index, exercise) in ExerciseLibrary::get_all_exercises().into_iter().enumerate() {
+        match per_theme.iter_mut().find(|(theme, _)| *theme == exercise.exercise_type) {
+            Some((_, count)) if *count >= PUZZLES_PER_THEME => continue,
+            Some((_, count)) => *count += 1,
+            None => per_theme.push((exercise.exercise_type.clone(), 1)),
+        }
+        set.push((index, exercise));
+    }
+
+    set.sort_by_key(|(_, exercise)| difficulty_rating(&exercise.difficulty));
+    set
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PuzzleOutcome {
+    pub theme: ExerciseType,
+    pub difficulty: ExerciseDifficulty,
+    pub solved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameOutcome {
+    pub opponent_elo: i32,
+    /// 1.0 for a win, 0.5 for a draw, 0.0 for a loss
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationEstimate {
+    pub rating: i32,
+    pub weaknesses: Vec<String>,
+    pub strengths: Vec<String>,
+}
+
+/// Linear performance rating: the average opposition, plus or minus 400 for a perfect or zero score
+fn performance(average_opposition: f32, score_fraction: f32) -> f32 {
+    average_opposition + 400.0 * (2.0 * score_fraction - 1.0)
+}
+
+/// Rating from the puzzles alone, used to pitch the calibration game
+pub fn provisional_rating(puzzles: &[PuzzleOutcome]) -> i32 {
+    performance_rating(puzzles).unwrap_or(DEFAULT_RATING)
+}
+
+/// Rating the puzzles were solved at, like a tournament performance against
+/// opponents of their difficulty. `None` without any puzzles.
+pub fn performance_rating(puzzles: &[PuzzleOutcome]) -> Option<i32> {
+    puzzle_performance(puzzles).map(|rating| (rating.round() as i32).clamp(MIN_RATING, MAX_RATING))
+}
+
+fn puzzle_performance(puzzles: &[PuzzleOutcome]) -> Option<f32> {
+    if puzzles.is_empty() {
+        return None;
+    }
+    let count = puzzles.len() as f32;
+    let opposition = puzzles.iter().map(|p| difficulty_rating(&p.difficulty) as f32).sum::<f32>() / count;
+    let solved = puzzles.iter().filter(|p| p.solved).count() as f32 / count;
+    Some(performance(opposition, solved))
+}
+
+/// Initial rating and weaknesses from the calibration results.
+/// The game counts for a third of the rating when it was played.
+pub fn estimate(puzzles: &[PuzzleOutcome], game: Option<&GameOutcome>) -> CalibrationEstimate {
+    let game_performance = game.map(|g| performance(g.opponent_elo as f32, g.score.clamp(0.0, 1.0)));
+    let rating = match (puzzle_performance(puzzles), game_performance) {
+        (Some(p), Some(g)) => (2.0 * p + g) / 3.0,
+        (Some(p), None) => p,
+        (None, Some(g)) => g,
+        (None, None) => DEFAULT_RATING as f32,
+    };
+
+    let mut weaknesses: Vec<String> = Vec::new();
+    let mut strengths: Vec<String> = Vec::new();
+    for outcome in puzzles {
+        let label = theme_label(&outcome.theme).to_string();
+        if !outcome.solved && !weaknesses.contains(&label) {
+            weaknesses.push(label);
+        }
+    }
+    for outcome in puzzles {
+        let label = theme_label(&outcome.theme).to_string();
+        if outcome.solved && !weaknesses.contains(&label) && !strengths.contains(&label) {
+            strengths.push(label);
+        }
+    }
+
+    CalibrationEstimate {
+        rating: (rating.round() as i32).clamp(MIN_RATING, MAX_RATING),
+        weaknesses,
+        strengths,
+    }
+}
+
+/// Level name stored on the profile for a calibrated rating
+pub fn level_for_rating(rating: i32) -> &'static str {
+    match rating {
+        r if r < 800 => "beginner",
+        r if r < 1400 => "intermediate",
+        _ => "advanced",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle(theme: ExerciseType, difficulty: ExerciseDifficulty, solved: bool) -> PuzzleOutcome {
+        PuzzleOutcome { theme, difficulty, solved }
+    }
+
+    #[test]
+    fn test_calibration_set_covers_themes_easiest_first() {
+        let set = calibration_set();
+        assert!(!set.is_empty());
+        for theme in [ExerciseType::Tactics, ExerciseType::Opening, ExerciseType::Endgame, ExerciseType::Positional] {
+            let count = set.iter().filter(|(_, e)| e.exercise_type == theme).count();
+            assert!((1..=PUZZLES_PER_THEME).contains(&count), "{:?} appears {} times", theme, count);
+        }
+        assert!(set.windows(2).all(|w| w[0].1.difficulty <= w[1].1.difficulty));
+    }
+
+    #[test]
+    fn test_estimate_rating_and_weaknesses() {
+        let puzzles = vec![
+            puzzle(ExerciseType::Tactics, ExerciseDifficulty::Beginner, true),
+            puzzle(ExerciseType::Opening, ExerciseDifficulty::Beginner, true),
+            puzzle(ExerciseType::Endgame, ExerciseDifficulty::Beginner, false),
+            puzzle(ExerciseType::Tactics, ExerciseDifficulty::Intermediate, false),
+        ];
+
+        // Opposition 700, half solved
+        assert_eq!(provisional_rating(&puzzles), 700);
+        assert_eq!(performance_rating(&puzzles[..1]), Some(1000));
+        assert_eq!(performance_rating(&[]), None);
+
+        let result = estimate(&puzzles, Some(&GameOutcome { opponent_elo: 800, score: 1.0 }));
+        assert_eq!(result.rating, 867);
+        assert_eq!(result.weaknesses, vec!["Endgame technique", "Tactics"]);
+        assert_eq!(result.strengths, vec!["Opening principles"]);
+
+        assert_eq!(estimate(&[], None).rating, DEFAULT_RATING);
+        assert_eq!(level_for_rating(result.rating), "intermediate");
+    }
+}
@@ -0,0 +1,161 @@
+use chess::{ChessMove, Color};
+use chess_core::{deviation_ply, parse_fen, parse_legal_uci, position_key, to_san};
+use chess_engine::Engine;
+use chess_trainer::OpeningWalkthrough;
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories::{self, Game, OpeningDeviation};
+use crate::opening_walkthrough;
+use crate::DB;
+
+/// Search depth for comparing the played move with the line's
+const DEVIATION_DEPTH: u32 = 3;
+
+/// A deviation costing at least this much against the line's move counts as worse
+pub const WORSE_CENTIPAWNS: i32 = 50;
+
+/// Moves of the line shown as the way back after the player's own deviation
+const CONTINUATION_PLIES: usize = 4;
+
+/// The lines the player has walked through to the end at least once
+pub fn repertoire(conn: &Connection, profile_id: i64) -> Result<Vec<OpeningWalkthrough>> {
+    Ok(repositories::get_opening_progress(conn, profile_id)?
+        .into_iter()
+        .filter(|p| p.completions > 0)
+        .filter_map(|p| opening_walkthrough::find(&p.opening_key).ok())
+        .collect())
+}
+
+/// Where `game` first left the repertoire: the line it followed longest, at the
+/// move that left it. None when the game never entered a line, stayed inside
+/// one until it ended, or left one line for another that is also in the repertoire.
+pub fn find(game: &Game, repertoire: &[OpeningWalkthrough], engine: &Engine) -> Option<OpeningDeviation> {
+    let mut followed = 0;
+    let mut left: Option<(usize, &OpeningWalkthrough)> = None;
+    for line in repertoire {
+        match deviation_ply(line.opening, &game.initial_fen, &game.moves) {
+            Some(ply) if ply > 0 && left.is_none_or(|(longest, _)| ply > longest) => left = Some((ply, line)),
+            Some(_) => {}
+            None => followed = followed.max(line.steps.len()),
+        }
+    }
+    let (ply, line) = left.filter(|(ply, _)| *ply >= followed)?;
+
+    let mut board = parse_fen(&game.initial_fen).ok()?;
+    for uci in &game.moves[..ply] {
+        board = board.make_move_new(parse_legal_uci(&board, uci).ok()?);
+    }
+    let played = parse_legal_uci(&board, &game.moves[ply]).ok()?;
+    let key = position_key(&board);
+    let index = line
+        .steps
+        .iter()
+        .position(|step| parse_fen(&step.fen).is_ok_and(|b| position_key(&b) == key))?;
+    let expected = parse_legal_uci(&board, &line.steps[index].uci).ok()?;
+
+    let ranked = engine.rank_moves(&board);
+    let score = |chess_move: ChessMove| ranked.iter().find(|m| m.chess_move == chess_move).map(|m| m.score.centipawns());
+    let centipawn_loss = match (score(expected), score(played)) {
+        (Some(expected), Some(played)) => (expected - played).max(0),
+        _ => 0,
+    };
+
+    let player = if game.player_color == "black" { Color::Black } else { Color::White };
+    let by_player = board.side_to_move() == player;
+    let recommended = if by_player {
+        line.steps[index..].iter().take(CONTINUATION_PLIES).map(|step| step.san.clone()).collect()
+    } else {
+        let after = board.make_move_new(played);
+        engine.best_move(&after).map(|best| to_san(&after, best.chess_move)).into_iter().collect()
+    };
+
+    Some(OpeningDeviation {
+        game_id: game.id,
+        profile_id: game.profile_id,
+        opening_key: line.opening.key(),
+        opening_name: line.opening.name.to_string(),
+        ply,
+        move_number: chess_core::move_label(&game.initial_fen, ply),
+        by_player,
+        played: to_san(&board, played),
+        expected: line.steps[index].san.clone(),
+        centipawn_loss,
+        played_worse: centipawn_loss >= WORSE_CENTIPAWNS,
+        recommended,
+        fen: board.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Find and store the deviation in a saved game. The engine runs without the
+/// database held, so this belongs off the calling thread.
+pub fn record(game_id: i64) -> std::result::Result<Option<OpeningDeviation>, String> {
+    let loaded = DB
+        .with_conn(|conn| {
+            let Some(game) = repositories::get_game_by_id(conn, game_id)? else {
+                return Ok(None);
+            };
+            let repertoire = repertoire(conn, game.profile_id)?;
+            Ok(Some((game, repertoire)))
+        })
+        .map_err(|e| format!("Failed to load game: {}", e))?;
+    let Some((game, repertoire)) = loaded.filter(|(_, repertoire)| !repertoire.is_empty()) else {
+        return Ok(None);
+    };
+
+    let engine = Engine::builder().depth(DEVIATION_DEPTH).build().expect("depth within MAX_DEPTH");
+    let Some(deviation) = find(&game, &repertoire, &engine) else {
+        return Ok(None);
+    };
+    DB.with_conn(|conn| repositories::save_opening_deviation(conn, &deviation))
+        .map_err(|e| format!("Failed to save opening deviation: {}", e))?;
+    Ok(Some(deviation))
+}
+
+/// Check a newly saved game against the repertoire in the background
+pub fn spawn(game_id: i64) {
+    tauri::async_runtime::spawn_blocking(move || match record(game_id) {
+        Ok(Some(deviation)) => tracing::debug!(game_id, ply = deviation.ply, "game left {}", deviation.opening_name),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(game_id, "opening deviation check failed: {}", e),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_core::OPENINGS;
+
+    fn lines(names: &[&str]) -> Vec<OpeningWalkthrough> {
+        names
+            .iter()
+            .map(|name| OpeningWalkthrough::new(OPENINGS.iter().find(|o| o.name == *name).unwrap()).unwrap())
+            .collect()
+    }
+
+    fn game(moves: &[&str]) -> Game {
+        Game { id: 7, ..Game::for_test(1, moves) }
+    }
+
+    #[test]
+    fn test_finds_first_deviation_from_repertoire() {
+        let engine = Engine::builder().depth(2).build().unwrap();
+
+        // The player leaves the Italian with 3.Bb5
+        let own = find(&game(&["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6"]), &lines(&["Italian Game"]), &engine).unwrap();
+        assert_eq!((own.ply, own.by_player), (4, true));
+        assert_eq!((own.played.as_str(), own.expected.as_str()), ("Bb5", "Bc4"));
+        assert_eq!(own.recommended, vec!["Bc4".to_string()]);
+
+        // The opponent answers the Italian with 3...Nf6 instead of the Giuoco Piano's 3...Bc5
+        let italian = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "d2d3"];
+        let theirs = find(&game(&italian), &lines(&["Italian Game", "Giuoco Piano"]), &engine).unwrap();
+        assert_eq!((theirs.ply, theirs.by_player), (5, false));
+        assert_eq!((theirs.played.as_str(), theirs.expected.as_str()), ("Nf6", "Bc5"));
+        assert_eq!(theirs.recommended.len(), 1);
+
+        // Leaving the Ruy Lopez for the Italian stays inside the repertoire
+        assert!(find(&game(&italian), &lines(&["Italian Game", "Ruy Lopez"]), &engine).is_none());
+        assert!(find(&game(&["d2d4", "d7d5"]), &lines(&["Italian Game"]), &engine).is_none());
+    }
+}
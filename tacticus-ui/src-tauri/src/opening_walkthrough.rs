@@ -0,0 +1,192 @@
+use chess::Color;
+use chess_core::{opening_by_key, Opening, OPENINGS};
+use chess_trainer::{OpeningWalkthrough, QuizAnswer, WalkthroughStep};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::coach::{complete_once, CoachTask};
+use crate::database::repositories::OpeningProgress;
+
+const ELABORATION_SYSTEM_PROMPT: &str = "You are Gurgeh, a chess coach, walking the student through an opening \
+    one move at a time. In two or three sentences, explain the idea behind the move you are given: what it aims \
+    for and what it prepares. Use algebraic notation. Do not suggest other moves and do not go beyond the move.";
+
+/// An opening line the student can walk through, with how far they have got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningLine {
+    pub key: String,
+    pub eco: String,
+    pub name: String,
+    /// The line in SAN with move numbers, e.g. "1.e4 e5 2.Nf3"
+    pub moves: String,
+    pub plies: usize,
+    pub progress: Option<OpeningProgress>,
+}
+
+/// A walkthrough as handed to the UI. The student plays `color`; when that is
+/// Black, `played` already holds White's first move.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkthroughSession {
+    pub key: String,
+    pub eco: String,
+    pub name: String,
+    pub color: String,
+    pub steps: Vec<WalkthroughStep>,
+    pub played: Vec<String>,
+    /// Position after `played`
+    pub fen: String,
+    pub progress: Option<OpeningProgress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningQuizResult {
+    #[serde(flatten)]
+    pub answer: QuizAnswer,
+    /// What the student's move and the reply do, from the line's own steps
+    pub explanations: Vec<String>,
+    /// Updated once the line is completed
+    pub progress: Option<OpeningProgress>,
+}
+
+/// The coach's take on one step, or the line's own explanation when the coach can't be reached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepElaboration {
+    pub ply: usize,
+    pub text: String,
+    /// "coach" when written by the model, "data" when taken from the line
+    pub source: String,
+}
+
+pub fn find(key: &str) -> Result<OpeningWalkthrough, String> {
+    let opening = opening_by_key(key).ok_or_else(|| format!("Unknown opening line: {}", key))?;
+    OpeningWalkthrough::new(opening)
+}
+
+/// Every line, in ECO order, with the profile's progress where they have started it
+pub fn lines(progress: &[OpeningProgress]) -> Vec<OpeningLine> {
+    let mut lines: Vec<OpeningLine> = OPENINGS
+        .iter()
+        .filter_map(|opening| OpeningWalkthrough::new(opening).ok())
+        .map(|walkthrough| {
+            let key = walkthrough.opening.key();
+            OpeningLine {
+                progress: progress.iter().find(|p| p.opening_key == key).cloned(),
+                key,
+                eco: walkthrough.opening.eco.to_string(),
+                name: walkthrough.opening.name.to_string(),
+                moves: numbered(&walkthrough.steps),
+                plies: walkthrough.steps.len(),
+            }
+        })
+        .collect();
+    lines.sort_by(|a, b| a.eco.cmp(&b.eco).then(a.plies.cmp(&b.plies)));
+    lines
+}
+
+pub fn session(walkthrough: &OpeningWalkthrough, color: Color, progress: Option<OpeningProgress>) -> WalkthroughSession {
+    let played = match color {
+        Color::White => vec![],
+        Color::Black => walkthrough.steps.first().map(|s| vec![s.uci.clone()]).unwrap_or_default(),
+    };
+    let fen = walkthrough.replay(&played).map(|board| board.to_string()).unwrap_or_default();
+    WalkthroughSession {
+        key: walkthrough.opening.key(),
+        eco: walkthrough.opening.eco.to_string(),
+        name: walkthrough.opening.name.to_string(),
+        color: if color == Color::White { "white" } else { "black" }.to_string(),
+        steps: walkthrough.steps.clone(),
+        played,
+        fen,
+        progress,
+    }
+}
+
+/// Check `answer` after `played`, explaining the moves it adds from the steps that
+/// play them; a transposed move is explained as the line plays it
+pub fn quiz(walkthrough: &OpeningWalkthrough, played: &[String], answer: &str) -> Result<OpeningQuizResult, String> {
+    let answer = walkthrough.answer(played, answer)?;
+    let explanations = answer.played[played.len()..]
+        .iter()
+        .enumerate()
+        .filter_map(|(i, uci)| {
+            let parity = (played.len() + i) % 2;
+            walkthrough.steps.iter().find(|s| s.ply % 2 == parity && s.uci == *uci).map(|s| s.explanation.clone())
+        })
+        .collect();
+    Ok(OpeningQuizResult { answer, explanations, progress: None })
+}
+
+/// The coach's explanation of step `ply`, falling back to the line's own
+pub async fn elaborate(walkthrough: &OpeningWalkthrough, ply: usize) -> Result<StepElaboration, String> {
+    let step = walkthrough.steps.get(ply).ok_or_else(|| format!("The line has no move {}", ply + 1))?;
+    let prompt = elaboration_prompt(walkthrough.opening, &walkthrough.steps, ply);
+    let (text, source) = match complete_once(CoachTask::Chat, ELABORATION_SYSTEM_PROMPT, &prompt, 160).await {
+        Ok(text) if !text.trim().is_empty() => (text.trim().to_string(), "coach"),
+        Ok(_) => (step.explanation.clone(), "data"),
+        Err(e) => {
+            tracing::debug!(ply, "using the line's own explanation: {}", e);
+            (step.explanation.clone(), "data")
+        }
+    };
+    Ok(StepElaboration { ply, text, source: source.to_string() })
+}
+
+fn elaboration_prompt(opening: &Opening, steps: &[WalkthroughStep], ply: usize) -> String {
+    let side = if ply.is_multiple_of(2) { "White" } else { "Black" };
+    let before = if ply == 0 { "none".to_string() } else { numbered(&steps[..ply]) };
+    format!(
+        "Opening: {} ({}).\nMoves so far: {}.\nMove to explain: {} for {}.\nWhat it does on the board: {}",
+        opening.name, opening.eco, before, steps[ply].san, side, steps[ply].explanation
+    )
+}
+
+/// "1.e4 e5 2.Nf3" for steps from the start
+pub(crate) fn numbered(steps: &[WalkthroughStep]) -> String {
+    steps
+        .iter()
+        .map(|s| if s.ply.is_multiple_of(2) { format!("{}.{}", s.ply / 2 + 1, s.san) } else { s.san.clone() })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories;
+    use crate::database::schema::create_tables;
+    use chess_trainer::QuizVerdict;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_walkthrough_progress() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+
+        let ruy = find("c60-ruy-lopez").unwrap();
+        assert!(find("c60").is_err());
+        let session = session(&ruy, Color::Black, None);
+        assert_eq!(session.played, vec!["e2e4"]);
+        assert!(elaboration_prompt(ruy.opening, &ruy.steps, 4).contains("Moves so far: 1.e4 e5 2.Nf3 Nc6."));
+
+        repositories::record_opening_attempt(&conn, profile.id, &session.key).unwrap();
+        let mut played = session.played.clone();
+        for answer in ["e5", "Nc6"] {
+            let result = quiz(&ruy, &played, answer).unwrap();
+            assert_ne!(result.answer.verdict, QuizVerdict::Wrong);
+            assert_eq!(result.explanations.len(), 2);
+            played = result.answer.played;
+        }
+        assert!(ruy.is_complete(&played));
+
+        let progress = repositories::record_opening_completion(&conn, profile.id, &session.key, 2).unwrap();
+        assert_eq!((progress.attempts, progress.completions, progress.best_mistakes), (1, 1, Some(2)));
+        let progress = repositories::record_opening_completion(&conn, profile.id, &session.key, 0).unwrap();
+        assert_eq!((progress.completions, progress.best_mistakes), (2, Some(0)));
+
+        let lines = lines(&repositories::get_opening_progress(&conn, profile.id).unwrap());
+        assert_eq!(lines.len(), OPENINGS.len());
+        let line = lines.iter().find(|l| l.key == "c60-ruy-lopez").unwrap();
+        assert_eq!(line.moves, "1.e4 e5 2.Nf3 Nc6 3.Bb5");
+        assert_eq!(line.progress.as_ref().map(|p| p.completions), Some(2));
+    }
+}
@@ -0,0 +1,152 @@
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories::{self, OperationRecord};
+
+/// How long a destructive action can be undone
+pub const UNDO_WINDOW_SECONDS: i64 = 60;
+
+/// Actions that remove the player's data; each is journalled and undoable for
+/// `UNDO_WINDOW_SECONDS`, so a stray click costs nothing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    DeleteGame,
+    DeleteConversation,
+    /// Clear every open item from the training plan
+    ResetPlan,
+}
+
+impl OperationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OperationKind::DeleteGame => "delete_game",
+            OperationKind::DeleteConversation => "delete_conversation",
+            OperationKind::ResetPlan => "reset_plan",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        [OperationKind::DeleteGame, OperationKind::DeleteConversation, OperationKind::ResetPlan]
+            .into_iter()
+            .find(|k| k.as_str() == kind)
+    }
+
+    /// Table whose rows the operation soft-deletes
+    fn table(self) -> &'static str {
+        match self {
+            OperationKind::DeleteGame => "games",
+            OperationKind::DeleteConversation => "conversations",
+            OperationKind::ResetPlan => "training_plan_items",
+        }
+    }
+}
+
+/// Operations made before this (RFC 3339) can no longer be undone
+fn window_start() -> String {
+    (chrono::Utc::now() - chrono::Duration::seconds(UNDO_WINDOW_SECONDS)).to_rfc3339()
+}
+
+/// Soft-delete `ids` and journal it; `None` when none of them was there to remove.
+/// Whatever has dropped out of the undo window is purged for good first.
+fn perform(conn: &Connection, profile_id: i64, kind: OperationKind, ids: &[i64], label: String) -> Result<Option<OperationRecord>> {
+    repositories::purge_soft_deleted(conn, &window_start())?;
+
+    let tx = conn.unchecked_transaction()?;
+    let removed = repositories::set_soft_deleted(&tx, kind.table(), profile_id, ids, true)?;
+    if removed.is_empty() {
+        return Ok(None);
+    }
+    let id = repositories::insert_operation(&tx, profile_id, kind.as_str(), &removed, &label)?;
+    tx.commit()?;
+    repositories::get_operation(conn, id)
+}
+
+pub fn delete_game(conn: &Connection, profile_id: i64, game_id: i64) -> Result<Option<OperationRecord>> {
+    let label = match repositories::get_game_by_id(conn, game_id)? {
+        Some(game) => format!("Deleted the {} game against {}", game.result, game.opponent_type),
+        None => return Ok(None),
+    };
+    perform(conn, profile_id, OperationKind::DeleteGame, &[game_id], label)
+}
+
+pub fn delete_conversation(conn: &Connection, profile_id: i64, conversation_id: i64) -> Result<Option<OperationRecord>> {
+    let label = match repositories::get_conversation(conn, conversation_id)? {
+        Some(conversation) => match conversation.title {
+            Some(title) => format!("Deleted the conversation \"{}\"", title),
+            None => "Deleted a conversation".to_string(),
+        },
+        None => return Ok(None),
+    };
+    perform(conn, profile_id, OperationKind::DeleteConversation, &[conversation_id], label)
+}
+
+/// Clear the open items from the training plan; completed ones stay as history
+pub fn reset_plan(conn: &Connection, profile_id: i64) -> Result<Option<OperationRecord>> {
+    let ids: Vec<i64> = repositories::get_training_plan(conn, profile_id, false)?.iter().map(|item| item.id).collect();
+    let label = format!("Cleared {} item(s) from the training plan", ids.len());
+    perform(conn, profile_id, OperationKind::ResetPlan, &ids, label)
+}
+
+/// Bring back what the newest operation still in its window removed.
+/// Returns the operation undone, or `None` when there is nothing left to undo.
+pub fn undo_last(conn: &Connection, profile_id: i64) -> Result<Option<OperationRecord>> {
+    let Some(operation) = repositories::get_last_undoable_operation(conn, profile_id, &window_start())? else {
+        return Ok(None);
+    };
+    let Some(kind) = OperationKind::parse(&operation.kind) else {
+        tracing::warn!("unknown operation kind {} in the journal", operation.kind);
+        return Ok(None);
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    repositories::set_soft_deleted(&tx, kind.table(), profile_id, &operation.target_ids, false)?;
+    repositories::mark_operation_undone(&tx, operation.id)?;
+    tx.commit()?;
+    repositories::get_operation(conn, operation.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_deletions_undo_newest_first() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile_id = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap().id;
+        let conversation_id = repositories::create_conversation(&conn, profile_id, Some("Openings"), None).unwrap();
+        repositories::add_message(&conn, conversation_id, "user", "Which openings?", None, None).unwrap();
+        conn.execute(
+            "INSERT INTO training_plan_items (profile_id, kind, title, source, created_at) VALUES (?1, 'theme', 'Forks', 'player', '2026-01-01T00:00:00+00:00')",
+            [profile_id],
+        )
+        .unwrap();
+
+        let deleted = delete_conversation(&conn, profile_id, conversation_id).unwrap().unwrap();
+        assert_eq!(deleted.label, "Deleted the conversation \"Openings\"");
+        assert!(repositories::get_conversation(&conn, conversation_id).unwrap().is_none());
+        assert!(repositories::get_conversation_messages(&conn, conversation_id).unwrap().is_empty());
+        assert!(delete_conversation(&conn, profile_id, conversation_id).unwrap().is_none());
+
+        let reset = reset_plan(&conn, profile_id).unwrap().unwrap();
+        assert_eq!(reset.target_ids.len(), 1);
+        assert!(repositories::get_training_plan(&conn, profile_id, true).unwrap().is_empty());
+
+        assert_eq!(undo_last(&conn, profile_id).unwrap().map(|op| op.kind), Some("reset_plan".to_string()));
+        assert_eq!(repositories::get_training_plan(&conn, profile_id, false).unwrap().len(), 1);
+        assert!(undo_last(&conn, profile_id).unwrap().unwrap().undone_at.is_some());
+        assert!(repositories::get_conversation(&conn, conversation_id).unwrap().is_some());
+        assert_eq!(repositories::get_conversation_messages(&conn, conversation_id).unwrap().len(), 1);
+        assert!(undo_last(&conn, profile_id).unwrap().is_none());
+
+        // Once the window has passed the rows go for good
+        delete_conversation(&conn, profile_id, conversation_id).unwrap().unwrap();
+        let later = (chrono::Utc::now() + chrono::Duration::seconds(1)).to_rfc3339();
+        repositories::purge_soft_deleted(&conn, &later).unwrap();
+        let left: i64 = conn.query_row("SELECT COUNT(*) FROM conversations", [], |row| row.get(0)).unwrap();
+        assert_eq!(left, 0);
+        assert!(undo_last(&conn, profile_id).unwrap().is_none());
+    }
+}
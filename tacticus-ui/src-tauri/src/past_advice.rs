@@ -0,0 +1,93 @@
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories;
+use crate::DB;
+
+/// Earlier replies handed back to the coach per search
+const MAX_RESULTS: i32 = 5;
+
+/// Something the coach told the player in an earlier conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PastAdvice {
+    pub conversation_id: i64,
+    pub conversation_title: Option<String>,
+    /// The part of the reply that matched, matched words in [brackets]
+    pub excerpt: String,
+    pub given_at: String,
+    pub days_ago: i64,
+}
+
+/// Turn free text into an FTS5 query matching any of its words, so punctuation
+/// or stray operators in what the model asks for can't break the search.
+/// `None` when no word is long enough to search for.
+fn fts_query(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 2)
+        .map(|w| format!("\"{}\"", w.to_lowercase()))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" OR "))
+}
+
+/// The coach's earlier replies to `profile_id` about `query`, best matches first.
+/// `exclude_conversation` is the conversation asking, which the model already has.
+pub fn search(conn: &Connection, profile_id: i64, query: &str, exclude_conversation: Option<i64>) -> Result<Vec<PastAdvice>> {
+    let Some(fts) = fts_query(query) else {
+        return Ok(vec![]);
+    };
+    let now = chrono::Utc::now();
+    let matches = repositories::search_messages(conn, profile_id, "assistant", &fts, exclude_conversation, MAX_RESULTS)?;
+    Ok(matches
+        .into_iter()
+        .map(|m| PastAdvice {
+            days_ago: chrono::DateTime::parse_from_rfc3339(&m.created_at).map_or(0, |at| (now - at.with_timezone(&chrono::Utc)).num_days()),
+            conversation_id: m.conversation_id,
+            conversation_title: m.conversation_title,
+            excerpt: m.snippet,
+            given_at: m.created_at,
+        })
+        .collect())
+}
+
+/// `search` for the active profile, for the coach tool
+pub fn search_past_advice(conversation_id: Option<i64>, query: &str) -> std::result::Result<Vec<PastAdvice>, String> {
+    DB.with_conn(|conn| {
+        let Some(profile) = repositories::get_first_profile(conn)? else {
+            return Ok(vec![]);
+        };
+        search(conn, profile.id, query, conversation_id)
+    })
+    .map_err(|e| format!("Failed to search past conversations: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_search_finds_earlier_advice() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile_id = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap().id;
+        let earlier = repositories::create_conversation(&conn, profile_id, Some("Endgames"), None).unwrap();
+        repositories::add_message(&conn, earlier, "user", "What should I study? Rook endgames?", None, None).unwrap();
+        repositories::add_message(&conn, earlier, "assistant", "Study rook endgames: start with the Lucena position.", None, None).unwrap();
+        let current = repositories::create_conversation(&conn, profile_id, None, None).unwrap();
+        repositories::add_message(&conn, current, "assistant", "Rook endgames are still your weak spot.", None, None).unwrap();
+
+        let found = search(&conn, profile_id, "rook endgame", Some(current)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].conversation_title.as_deref(), Some("Endgames"));
+        assert!(found[0].excerpt.contains("[rook]"), "{}", found[0].excerpt);
+        assert_eq!(found[0].days_ago, 0);
+
+        assert_eq!(search(&conn, profile_id, "rook", None).unwrap().len(), 2);
+        assert!(search(&conn, profile_id, "\"(* OR", None).unwrap().is_empty());
+        assert!(search(&conn, profile_id, "sicilian", None).unwrap().is_empty());
+
+        repositories::set_soft_deleted(&conn, "conversations", profile_id, &[earlier], true).unwrap();
+        assert!(search(&conn, profile_id, "lucena", None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+use chess_core::{detect_opening, parse_fen, parse_move};
+use chess_trainer::puzzle_line;
+use serde::{Deserialize, Serialize};
+
+/// Longest paste accepted; a long annotated game is a few tens of KB
+pub const MAX_SOURCE_LEN: usize = 64 * 1024;
+
+/// Side to move, castling and en passant for a FEN given as a bare placement
+const DEFAULT_FEN_FIELDS: [&str; 3] = ["w", "-", "-"];
+
+pub(crate) const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// What the pasted text turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    Fen,
+    LichessUrl,
+    Pgn,
+    /// A FEN and its solution line, as in the Lichess puzzle database
+    Puzzle,
+}
+
+impl ImportSource {
+    /// Stored with saved positions
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImportSource::Fen => "fen",
+            ImportSource::LichessUrl => "lichess_url",
+            ImportSource::Pgn => "pgn",
+            ImportSource::Puzzle => "puzzle",
+        }
+    }
+}
+
+/// A pasted position, normalised so the analysis board can open it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedPosition {
+    pub source: ImportSource,
+    /// Position to analyse: the FEN as given, or where the PGN moves end
+    pub fen: String,
+    /// Where `moves` start; the same as `fen` when there are none
+    pub initial_fen: String,
+    /// UCI moves leading from `initial_fen` to `fen`
+    pub moves: Vec<String>,
+    pub opening_name: Option<String>,
+    /// For puzzles, the answer from `fen` in UCI with the opponent's replies between
+    #[serde(default)]
+    pub solution_line: Vec<String>,
+}
+
+/// Read a FEN, a Lichess analysis or editor link, a puzzle (a FEN followed by its
+/// moves) or a PGN (whole game or just the movetext)
+pub fn import(source: &str) -> Result<ImportedPosition, String> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err("Nothing to import".to_string());
+    }
+    if source.len() > MAX_SOURCE_LEN {
+        return Err(format!("Import is longer than {} KB", MAX_SOURCE_LEN / 1024));
+    }
+
+    // Puzzle database rows end in a game link, so puzzles are tried before links
+    if !source.contains('\n') {
+        if let Some(puzzle) = import_puzzle(source) {
+            return puzzle;
+        }
+    }
+    if source.contains("lichess.org/") {
+        let fen = fen_from_lichess_url(source)?;
+        return Ok(position_only(ImportSource::LichessUrl, fen));
+    }
+    if !source.contains('\n') {
+        if let Ok(fen) = normalize_fen(source) {
+            return Ok(position_only(ImportSource::Fen, fen));
+        }
+    }
+    import_pgn(source)
+}
+
+fn position_only(source: ImportSource, fen: String) -> ImportedPosition {
+    ImportedPosition {
+        source,
+        initial_fen: fen.clone(),
+        fen,
+        moves: Vec::new(),
+        opening_name: None,
+        solution_line: Vec::new(),
+    }
+}
+
+/// A puzzle the way the Lichess puzzle database writes it: a CSV row
+/// (`PuzzleId,FEN,Moves,...`) or a FEN followed by the moves. The first move is the
+/// opponent's, leading to the position the solver sees. `None` when the text
+/// doesn't start with a FEN.
+fn import_puzzle(source: &str) -> Option<Result<ImportedPosition, String>> {
+    let fields: Vec<&str> = source.split(',').collect();
+    let (fen, moves) = match fields.as_slice() {
+        [_, fen, moves, ..] => (fen.to_string(), moves.split_whitespace().map(str::to_string).collect()),
+        _ => {
+            let tokens: Vec<&str> = source.split_whitespace().collect();
+            if tokens.len() <= 6 {
+                return None;
+            }
+            (tokens[..6].join(" "), tokens[6..].iter().map(|t| t.to_string()).collect::<Vec<_>>())
+        }
+    };
+    let initial_fen = normalize_fen(&fen).ok()?;
+    Some(puzzle_line::from_lichess(&initial_fen, &moves).map(|(fen, solution_line)| {
+        let board = parse_fen(&initial_fen).expect("normalized above");
+        let setup = parse_move(&board, &moves[0]).expect("checked by from_lichess");
+        ImportedPosition {
+            source: ImportSource::Puzzle,
+            initial_fen,
+            fen,
+            moves: vec![setup.to_string()],
+            opening_name: None,
+            solution_line,
+        }
+    }))
+}
+
+/// Check a FEN, filling in side to move, castling and en passant when only the placement was given
+pub(crate) fn normalize_fen(fen: &str) -> Result<String, String> {
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    if (1..4).contains(&fields.len()) {
+        fields.extend(&DEFAULT_FEN_FIELDS[fields.len() - 1..]);
+    }
+    let board = parse_fen(&fields.join(" ")).map_err(|e| e.to_string())?;
+    Ok(format!("{}", board))
+}
+
+/// Lichess puts the FEN in the path with underscores for spaces, e.g.
+/// `https://lichess.org/analysis/standard/<placement>_b_KQkq_-_0_1?color=black#3`
+fn fen_from_lichess_url(url: &str) -> Result<String, String> {
+    let path = url.split("lichess.org/").nth(1).unwrap_or_default();
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let fen = ["analysis/", "editor/"]
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix).or_else(|| (path == prefix.trim_end_matches('/')).then_some("")))
+        .ok_or_else(|| {
+            "Only Lichess analysis and board editor links carry a position. For a game, copy its PGN instead.".to_string()
+        })?;
+    let fen = fen.strip_prefix("standard/").or_else(|| fen.strip_prefix("standard")).unwrap_or(fen);
+    if fen.is_empty() {
+        return Ok(START_FEN.to_string());
+    }
+    normalize_fen(&fen.replace("%20", " ").replace('_', " ")).map_err(|e| format!("Invalid position in Lichess link: {}", e))
+}
+
+/// PGN text split into its movetext and what was set aside
+pub(crate) struct StrippedPgn {
+    /// Moves, move numbers, NAGs and results, with everything else blanked out
+    pub movetext: String,
+    /// Tag pairs in order, values unquoted
+    pub tags: Vec<(String, String)>,
+    /// `{...}` and `;` comments that carried a `%clk` clock time
+    pub clock_comments: usize,
+    /// Every other comment, engine evaluations included
+    pub comments: usize,
+    /// Top-level `(...)` variations
+    pub variations: usize,
+}
+
+impl StrippedPgn {
+    /// Value of the first tag named `name`, if it has one
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Drop comments, variations and tag pairs from PGN text, keeping the tags and
+/// counting what else was dropped
+pub(crate) fn strip_pgn(pgn: &str) -> StrippedPgn {
+    let mut stripped = StrippedPgn {
+        movetext: String::with_capacity(pgn.len()),
+        tags: Vec::new(),
+        clock_comments: 0,
+        comments: 0,
+        variations: 0,
+    };
+    let mut chars = pgn.chars();
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | ';' => {
+                let end = if c == '{' { '}' } else { '\n' };
+                let comment: String = chars.by_ref().take_while(|c| *c != end).collect();
+                if depth > 0 {
+                    continue;
+                }
+                if comment.contains("%clk") {
+                    stripped.clock_comments += 1;
+                } else {
+                    stripped.comments += 1;
+                }
+                stripped.movetext.push(' ');
+            }
+            '[' if depth == 0 => {
+                let tag: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                if let Some((name, value)) = tag.trim().split_once(char::is_whitespace) {
+                    let value = value.trim().trim_matches('"').replace("\\\"", "\"");
+                    stripped.tags.push((name.to_string(), value));
+                }
+                stripped.movetext.push(' ');
+            }
+            '(' => {
+                if depth == 0 {
+                    stripped.variations += 1;
+                }
+                depth += 1;
+            }
+            ')' => {
+                depth = (depth - 1).max(0);
+                stripped.movetext.push(' ');
+            }
+            _ if depth > 0 => {}
+            _ => stripped.movetext.push(c),
+        }
+    }
+    stripped
+}
+
+fn import_pgn(pgn: &str) -> Result<ImportedPosition, String> {
+    let stripped = strip_pgn(pgn);
+    let start_fen = stripped.tag("FEN");
+    let initial_fen = match start_fen {
+        Some(fen) => normalize_fen(fen).map_err(|e| format!("Invalid FEN tag: {}", e))?,
+        None => START_FEN.to_string(),
+    };
+    let mut board = parse_fen(&initial_fen).map_err(|e| e.to_string())?;
+    let mut moves = Vec::new();
+
+    for token in stripped.movetext.split_whitespace() {
+        if token.starts_with('$') || matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+            continue;
+        }
+        // Move numbers may be written apart ("12. Nf3", "12... Nf6") or attached ("12.Nf3")
+        let token = match token.find(|c: char| !c.is_ascii_digit()) {
+            Some(i) if token[i..].starts_with('.') => token[i..].trim_start_matches('.'),
+            Some(_) => token,
+            None => "",
+        };
+        if token.is_empty() {
+            continue;
+        }
+        let chess_move = parse_move(&board, token).map_err(|_| {
+            if moves.is_empty() && start_fen.is_none() {
+                "Not a FEN, Lichess analysis link or PGN".to_string()
+            } else {
+                format!("Illegal move '{}' after {} plies", token, moves.len())
+            }
+        })?;
+        moves.push(format!("{}", chess_move));
+        board = board.make_move_new(chess_move);
+    }
+
+    if moves.is_empty() && start_fen.is_none() {
+        return Err("Not a FEN, Lichess analysis link or PGN".to_string());
+    }
+    Ok(ImportedPosition {
+        source: ImportSource::Pgn,
+        fen: format!("{}", board),
+        opening_name: detect_opening(&initial_fen, &moves).map(|o| o.name.to_string()),
+        initial_fen,
+        moves,
+        solution_line: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFTER_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+
+    #[test]
+    fn test_imports_each_source() {
+        let fen = import(&format!("  {}\n", AFTER_E4)).unwrap();
+        assert_eq!((fen.source, fen.fen.as_str(), fen.moves.len()), (ImportSource::Fen, AFTER_E4, 0));
+        // A bare placement is read as White to move with no castling rights
+        assert_eq!(import("8/8/8/4k3/8/8/4P3/4K3").unwrap().fen, "8/8/8/4k3/8/8/4P3/4K3 w - - 0 1");
+
+        let url = import("https://lichess.org/analysis/standard/rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR_b_KQkq_-_0_1?color=black#1")
+            .unwrap();
+        assert_eq!((url.source, url.fen.as_str()), (ImportSource::LichessUrl, AFTER_E4));
+        assert_eq!(import("https://lichess.org/analysis").unwrap().fen, START_FEN);
+        assert!(import("https://lichess.org/abcd1234").unwrap_err().contains("PGN"));
+
+        let pgn = import(
+            "[Event \"Casual\"]\n[White \"Me\"]\n\n1. e4 {best by test} e5 (1... c5 2. Nf3) 2.Nf3 Nc6 $1 3. Bb5 a6 ; Morphy\n4. Ba4 *",
+        )
+        .unwrap();
+        assert_eq!(pgn.source, ImportSource::Pgn);
+        assert_eq!(pgn.moves, vec!["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4"]);
+        assert_eq!(pgn.initial_fen, START_FEN);
+        assert!(pgn.opening_name.is_some());
+
+        let fragment = import(&format!("[FEN \"{}\"]\n1... e5 2. Nf3 Nc6 3. Bc4 Nf6 4. 0-0 1-0", AFTER_E4)).unwrap();
+        assert_eq!(fragment.moves, vec!["e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"]);
+        assert_eq!(fragment.initial_fen, AFTER_E4);
+
+        let puzzle = import("00sHx,r3k3/pp1n1ppp/8/1N6/8/8/5PPP/6K1 b - - 0 1,d7f6 b5c7 e8e7 c7a8,1100,75,90,300,fork,https://lichess.org/x")
+            .unwrap();
+        assert_eq!(puzzle.source, ImportSource::Puzzle);
+        assert_eq!((puzzle.moves, puzzle.solution_line.len()), (vec!["d7f6".to_string()], 3));
+        assert_eq!(puzzle.fen, "r3k3/pp3ppp/5n2/1N6/8/8/5PPP/6K1 w - - 0 1");
+        let spaced = import("r3k3/pp1n1ppp/8/1N6/8/8/5PPP/6K1 b - - 0 1 Nf6 Nc7+ Ke7 Nxa8").unwrap();
+        assert_eq!(spaced.solution_line, vec!["b5c7", "e8e7", "c7a8"]);
+        assert!(import("r3k3/pp1n1ppp/8/1N6/8/8/5PPP/6K1 b - - 0 1 Nf6 Nc7+ Ke6").unwrap_err().starts_with("Move 2"));
+
+        assert!(import("1. e4 e5 2. Ke3").unwrap_err().contains("Illegal move 'Ke3' after 2 plies"));
+        assert_eq!(import("hello there").unwrap_err(), "Not a FEN, Lichess analysis link or PGN");
+        assert!(import("").is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use chess::Board;
+use chess_core::{format_moves, parse_fen, parse_uci, to_san, GamePhase, NotationPrefs};
+use chess_engine::Engine;
+use chess_trainer::puzzle_line::validate_line;
+use chess_trainer::{underpromotion_exercise, Exercise, ExerciseDifficulty, ExerciseLibrary, ExerciseType};
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories::{self, SavedPosition};
+use crate::exercise_queue;
+use crate::mate_drills;
+
+/// Where a position can be bookmarked from
+pub const ORIGINS: [&str; 5] = ["import", "game", "puzzle", "coach", "analysis"];
+
+/// Exercises built from bookmarks take ids from here up, clear of the exercise library's indices
+pub const EXERCISE_ID_BASE: usize = 1_000_000;
+
+/// Moves this close to the engine's best also solve a bookmark exercise; unlike
+/// library puzzles, a bookmarked position was never checked for a single answer
+const SOLUTION_TOLERANCE: i32 = 30;
+
+pub fn check_origin(origin: &str) -> std::result::Result<(), String> {
+    if ORIGINS.contains(&origin) {
+        Ok(())
+    } else {
+        Err(format!("Unknown bookmark origin: {} (expected one of {})", origin, ORIGINS.join(", ")))
+    }
+}
+
+/// Trimmed, non-empty tags without case-insensitive repeats, in the order given
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = vec![];
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !unique.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            unique.push(tag.to_string());
+        }
+    }
+    unique
+}
+
+pub fn exercise_id(position_id: i64) -> usize {
+    EXERCISE_ID_BASE + position_id.max(0) as usize
+}
+
+/// The bookmark an exercise id was built from, if it was
+pub fn position_id(exercise_id: usize) -> Option<i64> {
+    if exercise_queue::is_queue_id(exercise_id) || mate_drills::is_drill_id(exercise_id) {
+        return None;
+    }
+    exercise_id.checked_sub(EXERCISE_ID_BASE).map(|id| id as i64)
+}
+
+/// A "find the best move" exercise on a bookmarked position, solved by any move
+/// the engine rates within `SOLUTION_TOLERANCE` of its best. Where only an
+/// underpromotion wins, only those solve it; the tolerance would let a queen through.
+/// Imported puzzles are played out along their own line instead.
+pub fn to_exercise(position: &SavedPosition) -> std::result::Result<Exercise, String> {
+    let board = parse_fen(&position.fen).map_err(|e| format!("Bad bookmarked position: {}", e))?;
+    if !position.solution_line.is_empty() {
+        return puzzle_exercise(position, &board);
+    }
+    if let Some(mut exercise) = underpromotion_exercise(&board) {
+        exercise.title = position.title.clone().unwrap_or(exercise.title);
+        if !position.tags.is_empty() {
+            exercise.hints.push(format!("You tagged this position: {}.", position.tags.join(", ")));
+        }
+        return Ok(exercise);
+    }
+    let ranked = Engine::default().rank_moves(&board);
+    let best = ranked.first().ok_or_else(|| "The bookmarked position has no legal moves".to_string())?;
+    let solutions: Vec<String> = ranked
+        .iter()
+        .filter(|e| best.score - e.score <= SOLUTION_TOLERANCE)
+        .map(|e| format!("{}", e.chess_move))
+        .collect();
+
+    let exercise_type = match GamePhase::of(&board) {
+        GamePhase::Endgame => ExerciseType::Endgame,
+        _ => ExerciseType::Tactics,
+    };
+    let title = position.title.clone().unwrap_or_else(|| "Bookmarked Position".to_string());
+    let description = match &position.note {
+        Some(note) => format!("Find the best move. Your note: {}", note),
+        None => "Find the best move.".to_string(),
+    };
+    let explanation = format!("The engine's choice here is {}.", to_san(&board, best.chess_move));
+
+    let mut hints = vec![];
+    if best.is_check || best.is_capture {
+        hints.push("Look at the forcing moves first: checks and captures.".to_string());
+    }
+    if !position.tags.is_empty() {
+        hints.push(format!("You tagged this position: {}.", position.tags.join(", ")));
+    }
+    Ok(Exercise::new(
+        exercise_type,
+        ExerciseDifficulty::Intermediate,
+        position.fen.clone(),
+        title,
+        description,
+        solutions,
+        explanation,
+    )
+    .with_hints(hints))
+}
+
+/// An imported puzzle, checked move by move against its stored line
+fn puzzle_exercise(position: &SavedPosition, board: &Board) -> std::result::Result<Exercise, String> {
+    let line = validate_line(&position.fen, &position.solution_line)?;
+    let san = format_moves(board, &line.iter().filter_map(|m| parse_uci(m).ok()).collect::<Vec<_>>(), &NotationPrefs::default());
+    let exercise_type = match GamePhase::of(board) {
+        GamePhase::Endgame => ExerciseType::Endgame,
+        _ => ExerciseType::Tactics,
+    };
+    let mut hints = vec![];
+    if !position.tags.is_empty() {
+        hints.push(format!("You tagged this position: {}.", position.tags.join(", ")));
+    }
+    Ok(Exercise::new(
+        exercise_type,
+        ExerciseDifficulty::Intermediate,
+        position.fen.clone(),
+        position.title.clone().unwrap_or_else(|| "Imported Puzzle".to_string()),
+        match &position.note {
+            Some(note) => format!("Find the best move, then keep going. Your note: {}", note),
+            None => "Find the best move, then keep going until the line ends.".to_string(),
+        },
+        vec![],
+        format!("The full line: {}.", san.join(" ")),
+    )
+    .with_line(line)
+    .with_hints(hints))
+}
+
+/// The exercise behind an id handed to the trainer: a library index, a bookmark
+/// of this profile, a pre-generated exercise or a mate pattern drill
+pub fn find_exercise(conn: &Connection, profile_id: i64, exercise_id: usize) -> Result<Option<Exercise>> {
+    if mate_drills::is_drill_id(exercise_id) {
+        return mate_drills::find_exercise(conn, profile_id, exercise_id);
+    }
+    if exercise_queue::is_queue_id(exercise_id) {
+        return exercise_queue::find_exercise(conn, profile_id, exercise_id);
+    }
+    let Some(position_id) = position_id(exercise_id) else {
+        return Ok(ExerciseLibrary::get_all_exercises().get(exercise_id).cloned());
+    };
+    let position = repositories::get_saved_position(conn, position_id)?.filter(|p| p.profile_id == profile_id);
+    Ok(position.and_then(|p| match to_exercise(&p) {
+        Ok(exercise) => Some(exercise),
+        Err(e) => {
+            tracing::warn!(position_id, "Could not build a bookmark exercise: {}", e);
+            None
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_bookmark_exercises() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        let bookmark = SavedPosition {
+            id: 0,
+            profile_id: profile.id,
+            // The queen on d8 is loose
+            fen: "3q4/8/8/8/8/8/k7/3RK3 w - - 0 1".to_string(),
+            initial_fen: "3q4/8/8/8/8/8/k7/3RK3 w - - 0 1".to_string(),
+            moves: vec![],
+            source: "fen".to_string(),
+            origin: "game".to_string(),
+            title: None,
+            note: Some("Free queen?".to_string()),
+            tags: normalize_tags(vec![" hanging ".to_string(), "Hanging".to_string(), "".to_string()]),
+            created_at: String::new(),
+            solution_line: vec![],
+        };
+        assert_eq!(bookmark.tags, vec!["hanging"]);
+        let id = repositories::insert_saved_position(&conn, &bookmark).unwrap();
+
+        let exercise = find_exercise(&conn, profile.id, exercise_id(id)).unwrap().unwrap();
+        assert_eq!(exercise.solution_moves, vec!["d1d8"]);
+        assert!(exercise.description.ends_with("Free queen?"));
+        assert_eq!(exercise.hints.len(), 2);
+
+        // Other profiles' bookmarks and library indices resolve as before
+        assert!(find_exercise(&conn, profile.id + 1, exercise_id(id)).unwrap().is_none());
+        assert_eq!(
+            find_exercise(&conn, profile.id, 0).unwrap().map(|e| e.position),
+            ExerciseLibrary::get_all_exercises().first().map(|e| e.position.clone())
+        );
+        // Imported puzzles keep their line
+        let puzzle = SavedPosition {
+            fen: "r3k3/pp3ppp/5n2/1N6/8/8/5PPP/6K1 w - - 0 1".to_string(),
+            source: "puzzle".to_string(),
+            solution_line: vec!["b5c7".to_string(), "e8e7".to_string(), "c7a8".to_string()],
+            ..bookmark.clone()
+        };
+        let id = repositories::insert_saved_position(&conn, &puzzle).unwrap();
+        let exercise = find_exercise(&conn, profile.id, exercise_id(id)).unwrap().unwrap();
+        assert_eq!((exercise.solution_moves[0].as_str(), exercise.moves_to_find()), ("b5c7", 2));
+        assert_eq!(exercise.explanation, "The full line: Nc7+ Ke7 Nxa8.");
+
+        assert!(check_origin("coach").is_ok());
+        assert!(check_origin("dream").is_err());
+    }
+}
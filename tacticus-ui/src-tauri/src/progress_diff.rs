@@ -0,0 +1,396 @@
+use chess::Color;
+use chess_core::{parse_fen, parse_legal_uci, GamePhase, MoveQuality};
+use chess_engine::MoveAnalysis;
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::database::repositories::{self, Game, GameAnalysisRecord, ThemeOutcomes};
+
+/// Games in each period when the caller doesn't say
+pub const DEFAULT_GAMES: usize = 10;
+pub const MAX_GAMES: usize = 50;
+
+/// Games with moves in a phase each period needs before that phase is compared
+const MIN_GAMES: usize = 3;
+
+/// Attempts on a theme each period needs before its miss rate is compared
+const MIN_ATTEMPTS: i32 = 5;
+
+/// Losses above this are mate scores; capped so one missed mate doesn't swamp an average
+const LOSS_CAP: i32 = 1000;
+
+/// Standard errors a change has to clear to count as clear (about 95%) or only suggestive
+const CLEAR_Z: f64 = 1.96;
+const SUGGESTIVE_Z: f64 = 1.0;
+
+/// How far a change can be told apart from chance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    Clear,
+    Suggestive,
+    WithinNoise,
+    TooLittleData,
+}
+
+/// One metric measured the same way over both periods; lower is better for all of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricChange {
+    /// e.g. "acpl_endgame", "blunders_per_40" or "miss_rate_tactics"
+    pub key: String,
+    /// e.g. "endgame ACPL"
+    pub label: String,
+    pub before: f64,
+    pub after: f64,
+    /// What `before_samples` and `after_samples` count: "games" or "attempts"
+    pub unit: String,
+    pub before_samples: usize,
+    pub after_samples: usize,
+    /// Share of `before` that went away; negative when it got worse, none when `before` is zero
+    pub improvement_percent: Option<f64>,
+    pub confidence: Confidence,
+    /// One sentence with the numbers and how much to trust them
+    pub summary: String,
+}
+
+impl MetricChange {
+    pub fn improved(&self) -> bool {
+        self.after < self.before
+    }
+}
+
+/// The player's latest games against the ones before them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressDiff {
+    pub games_per_period: usize,
+    pub recent_games: usize,
+    pub earlier_games: usize,
+    /// When the oldest game of each period was played
+    pub recent_since: Option<String>,
+    pub earlier_since: Option<String>,
+    /// Clear changes first, then suggestive ones, then the rest
+    pub metrics: Vec<MetricChange>,
+    pub summary: String,
+}
+
+impl ProgressDiff {
+    /// Biggest clear improvement, for the coach to mention
+    pub fn best_improvement(&self) -> Option<&MetricChange> {
+        self.metrics.iter().find(|m| m.confidence == Confidence::Clear && m.improved())
+    }
+}
+
+/// The player's moves in one game, reduced to what the metrics need
+#[derive(Debug, Clone, Default, PartialEq)]
+struct GameSample {
+    /// Summed centipawn loss and moves per phase, in `GamePhase::ALL` order
+    phase_loss: [(f64, usize); 3],
+    moves: usize,
+    blunders: usize,
+}
+
+fn phase_index(phase: GamePhase) -> usize {
+    GamePhase::ALL.iter().position(|p| *p == phase).unwrap_or_default()
+}
+
+fn sample(game: &Game, record: &GameAnalysisRecord) -> GameSample {
+    let analyses: Vec<MoveAnalysis> = serde_json::from_str(&record.analysis).unwrap_or_default();
+    let player = if game.player_color == "black" { Color::Black } else { Color::White };
+    let mut sample = GameSample::default();
+    let Ok(mut board) = parse_fen(&game.initial_fen) else {
+        return sample;
+    };
+    for (uci, analysis) in game.moves.iter().zip(&analyses) {
+        let Ok(chess_move) = parse_legal_uci(&board, uci) else { break };
+        if board.side_to_move() == player {
+            let phase = &mut sample.phase_loss[phase_index(GamePhase::of(&board))];
+            phase.0 += analysis.centipawn_loss.clamp(0, LOSS_CAP) as f64;
+            phase.1 += 1;
+            sample.moves += 1;
+            sample.blunders += usize::from(analysis.quality == MoveQuality::Blunder);
+        }
+        board = board.make_move_new(chess_move);
+    }
+    sample
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Standard error of the mean of `values`
+fn standard_error(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    let variance = values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    (variance / values.len() as f64).sqrt()
+}
+
+/// Standard errors between the two values; any difference over no spread at all counts as clear
+fn z_score(before: f64, after: f64, standard_error: f64) -> f64 {
+    let diff = (before - after).abs();
+    if standard_error > 0.0 {
+        diff / standard_error
+    } else if diff > 0.0 {
+        f64::INFINITY
+    } else {
+        0.0
+    }
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+struct Measured {
+    key: String,
+    label: String,
+    unit: &'static str,
+    before: f64,
+    after: f64,
+    before_samples: usize,
+    after_samples: usize,
+    z: f64,
+    enough: bool,
+}
+
+impl Measured {
+    fn into_change(self) -> MetricChange {
+        let confidence = if !self.enough {
+            Confidence::TooLittleData
+        } else if self.z >= CLEAR_Z {
+            Confidence::Clear
+        } else if self.z >= SUGGESTIVE_Z {
+            Confidence::Suggestive
+        } else {
+            Confidence::WithinNoise
+        };
+        let improvement_percent = (self.before > 0.0).then(|| round1((self.before - self.after) / self.before * 100.0));
+        let samples = if self.before_samples == self.after_samples {
+            format!("{} {} each", self.after_samples, self.unit)
+        } else {
+            format!("{} {} before, {} since", self.before_samples, self.unit, self.after_samples)
+        };
+        let (before, after) = (round1(self.before), round1(self.after));
+        let movement = match improvement_percent {
+            Some(percent) if percent >= 0.0 => format!("improved {:.0}%", percent),
+            Some(percent) => format!("got {:.0}% worse", -percent),
+            None if after > before => "got worse".to_string(),
+            None => "stayed the same".to_string(),
+        };
+        let summary = match confidence {
+            Confidence::TooLittleData => format!("Too little to compare your {} yet ({}).", self.label, samples),
+            Confidence::Clear => format!("Your {} {} ({} to {}, {}).", self.label, movement, before, after, samples),
+            Confidence::Suggestive => format!(
+                "Your {} {} ({} to {}, {}), but with this few {} it could still be chance.",
+                self.label, movement, before, after, samples, self.unit
+            ),
+            Confidence::WithinNoise => format!(
+                "Your {} went from {} to {} ({}), within what chance alone would give.",
+                self.label, before, after, samples
+            ),
+        };
+        MetricChange {
+            key: self.key,
+            label: self.label,
+            before,
+            after,
+            unit: self.unit.to_string(),
+            before_samples: self.before_samples,
+            after_samples: self.after_samples,
+            improvement_percent,
+            confidence,
+            summary,
+        }
+    }
+}
+
+/// Average of per-game values, so every game counts once however long it ran
+fn per_game(key: String, label: String, earlier: &[f64], recent: &[f64]) -> Measured {
+    let (se_before, se_after) = (standard_error(earlier), standard_error(recent));
+    let (before, after) = (mean(earlier), mean(recent));
+    Measured {
+        key,
+        label,
+        unit: "games",
+        before,
+        after,
+        before_samples: earlier.len(),
+        after_samples: recent.len(),
+        z: z_score(before, after, (se_before.powi(2) + se_after.powi(2)).sqrt()),
+        enough: earlier.len() >= MIN_GAMES && recent.len() >= MIN_GAMES,
+    }
+}
+
+fn miss_rate(earlier: &ThemeOutcomes, recent: &ThemeOutcomes) -> Measured {
+    let rate = |t: &ThemeOutcomes| if t.attempts > 0 { t.missed as f64 / t.attempts as f64 } else { 0.0 };
+    // Two-proportion test on the pooled rate
+    let attempts = (earlier.attempts + recent.attempts) as f64;
+    let pooled = (earlier.missed + recent.missed) as f64 / attempts.max(1.0);
+    let spread = (pooled * (1.0 - pooled) * (1.0 / earlier.attempts.max(1) as f64 + 1.0 / recent.attempts.max(1) as f64)).sqrt();
+    let (before, after) = (rate(earlier) * 100.0, rate(recent) * 100.0);
+    Measured {
+        key: format!("miss_rate_{}", recent.exercise_type.to_lowercase().replace(' ', "_")),
+        label: format!("{} miss rate", recent.exercise_type.to_lowercase()),
+        unit: "attempts",
+        before,
+        after,
+        before_samples: earlier.attempts as usize,
+        after_samples: recent.attempts as usize,
+        z: z_score(before, after, spread * 100.0),
+        enough: earlier.attempts >= MIN_ATTEMPTS && recent.attempts >= MIN_ATTEMPTS,
+    }
+}
+
+fn compare(
+    earlier: &[GameSample],
+    recent: &[GameSample],
+    earlier_themes: &[ThemeOutcomes],
+    recent_themes: &[ThemeOutcomes],
+) -> Vec<MetricChange> {
+    let mut measured = vec![];
+    for phase in GamePhase::ALL {
+        let index = phase_index(phase);
+        let acpl = |games: &[GameSample]| -> Vec<f64> {
+            games
+                .iter()
+                .map(|g| g.phase_loss[index])
+                .filter(|(_, moves)| *moves > 0)
+                .map(|(loss, moves)| loss / moves as f64)
+                .collect()
+        };
+        let name = phase.as_str().to_lowercase();
+        measured.push(per_game(format!("acpl_{}", name), format!("{} ACPL", name), &acpl(earlier), &acpl(recent)));
+    }
+
+    let blunder_rate = |games: &[GameSample]| -> Vec<f64> {
+        games.iter().filter(|g| g.moves > 0).map(|g| g.blunders as f64 * 40.0 / g.moves as f64).collect()
+    };
+    measured.push(per_game(
+        "blunders_per_40".to_string(),
+        "blunders per 40 moves".to_string(),
+        &blunder_rate(earlier),
+        &blunder_rate(recent),
+    ));
+
+    for theme in recent_themes {
+        if let Some(before) = earlier_themes.iter().find(|t| t.exercise_type == theme.exercise_type) {
+            measured.push(miss_rate(before, theme));
+        }
+    }
+
+    let mut changes: Vec<MetricChange> = measured.into_iter().map(Measured::into_change).collect();
+    changes.sort_by(|a, b| {
+        a.confidence.cmp(&b.confidence).then_with(|| {
+            let size = |m: &MetricChange| m.improvement_percent.map(f64::abs).unwrap_or_default();
+            size(b).total_cmp(&size(a))
+        })
+    });
+    changes
+}
+
+fn summarize(diff: &ProgressDiff) -> String {
+    if diff.earlier_games == 0 {
+        return format!(
+            "Only {} analysed games so far; play and analyse a few more to have an earlier period to compare with.",
+            diff.recent_games
+        );
+    }
+    let mut lines: Vec<&str> = diff
+        .metrics
+        .iter()
+        .filter(|m| matches!(m.confidence, Confidence::Clear | Confidence::Suggestive))
+        .map(|m| m.summary.as_str())
+        .collect();
+    if lines.is_empty() {
+        lines.push("Nothing has changed by more than chance would explain yet.");
+    }
+    format!(
+        "{} Based on your last {} analysed games against the {} before them; a handful of games can swing these numbers a lot.",
+        lines.join(" "),
+        diff.recent_games,
+        diff.earlier_games
+    )
+}
+
+/// Compare the player's last `games` analysed games, and the exercises done since the
+/// first of them, with the `games` before that
+pub fn progress_diff(conn: &Connection, profile_id: i64, games: usize) -> Result<ProgressDiff> {
+    let games = games.clamp(1, MAX_GAMES);
+    let analysed = repositories::get_analysed_games(conn, profile_id, (games * 2) as i32)?;
+    let (recent, earlier) = analysed.split_at(analysed.len().min(games));
+    let recent_since = recent.last().map(|(g, _)| g.created_at.clone());
+    let earlier_since = earlier.last().map(|(g, _)| g.created_at.clone());
+
+    let (recent_themes, earlier_themes) = match (&recent_since, &earlier_since) {
+        (Some(recent_from), Some(earlier_from)) => (
+            repositories::get_theme_outcomes(conn, profile_id, recent_from, None)?,
+            repositories::get_theme_outcomes(conn, profile_id, earlier_from, Some(recent_from))?,
+        ),
+        _ => (vec![], vec![]),
+    };
+    let samples = |games: &[(Game, GameAnalysisRecord)]| -> Vec<GameSample> {
+        games.iter().map(|(game, record)| sample(game, record)).collect()
+    };
+
+    let mut diff = ProgressDiff {
+        games_per_period: games,
+        recent_games: recent.len(),
+        earlier_games: earlier.len(),
+        recent_since,
+        earlier_since,
+        metrics: if earlier.is_empty() {
+            vec![]
+        } else {
+            compare(&samples(earlier), &samples(recent), &earlier_themes, &recent_themes)
+        },
+        summary: String::new(),
+    };
+    diff.summary = summarize(&diff);
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(endgame_loss: f64, blunders: usize) -> GameSample {
+        GameSample { phase_loss: [(60.0, 3), (200.0, 5), (endgame_loss * 4.0, 4)], moves: 12, blunders }
+    }
+
+    fn theme(attempts: i32, missed: i32) -> ThemeOutcomes {
+        ThemeOutcomes { exercise_type: "Tactics".to_string(), attempts, missed }
+    }
+
+    #[test]
+    fn test_changes_are_weighed_against_noise() {
+        let earlier = [game(60.0, 1), game(66.0, 1), game(64.0, 0), game(70.0, 1)];
+        let recent = [game(50.0, 1), game(48.0, 0), game(52.0, 1), game(50.0, 1)];
+        let changes = compare(&earlier, &recent, &[theme(20, 10)], &[theme(20, 9)]);
+
+        let endgame = changes.iter().find(|m| m.key == "acpl_endgame").unwrap();
+        assert_eq!(endgame.confidence, Confidence::Clear);
+        assert_eq!((endgame.before, endgame.after), (65.0, 50.0));
+        assert_eq!(endgame.improvement_percent, Some(23.1));
+        assert_eq!(endgame.summary, "Your endgame ACPL improved 23% (65 to 50, 4 games each).");
+        assert_eq!(changes[0], *endgame);
+
+        // Identical every game, so no change at all
+        let opening = changes.iter().find(|m| m.key == "acpl_opening").unwrap();
+        assert_eq!(opening.confidence, Confidence::WithinNoise);
+        let blunders = changes.iter().find(|m| m.key == "blunders_per_40").unwrap();
+        assert_eq!(blunders.confidence, Confidence::WithinNoise);
+        let tactics = changes.iter().find(|m| m.key == "miss_rate_tactics").unwrap();
+        assert_eq!((tactics.before, tactics.after, tactics.confidence), (50.0, 45.0, Confidence::WithinNoise));
+
+        let too_few = compare(&earlier[..2], &recent, &[theme(3, 3)], &[theme(20, 0)]);
+        assert!(too_few.iter().all(|m| m.confidence == Confidence::TooLittleData));
+        assert!(too_few[0].summary.starts_with("Too little to compare"));
+    }
+}
@@ -0,0 +1,231 @@
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::database::repositories;
+use crate::DB;
+
+/// Privacy settings, stored per profile as JSON
+const PRIVACY_SETTING_KEY: &str = "prompt_privacy";
+
+/// Earlier chat messages sent with each request by default
+pub const DEFAULT_HISTORY_MESSAGES: u32 = 20;
+
+/// What the profile name is replaced with when it is redacted
+const NAME_STANDIN: &str = "the student";
+
+/// Tools whose results are the student's saved games, or stats built from them:
+/// results, per-colour records and the weaknesses games showed
+pub const GAME_TOOLS: &[&str] = &[
+    "get_recent_games",
+    "search_games_by_opening",
+    "get_games_with_mistakes",
+    "get_player_stats",
+    "get_improvement_trend",
+    "get_weakness_history",
+    "get_progress_diff",
+    "get_opponent_strength_stats",
+    "get_time_class_stats",
+    "propose_game_review",
+    "get_quiz_position",
+    "check_quiz_answer",
+];
+
+/// Tools whose results are organised by rating, so there is nothing left to send without them
+pub const RATING_TOOLS: &[&str] = &["get_opponent_strength_stats"];
+
+/// What coach requests may carry about the student. Games and ratings are kept
+/// out where prompts and tool results are built; history and the name are
+/// enforced on every prompt before it leaves the app, by the backend and by
+/// clients that talk to the model themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptPrivacy {
+    /// Saved games: recent form, game reviews, game quizzes and the game tools
+    pub share_games: bool,
+    /// The student's and their opponents' ratings, in prompts and tool results
+    pub share_ratings: bool,
+    /// Earlier chat messages sent with each request; the newest message always goes
+    pub history_messages: u32,
+    /// Replace the profile name with "the student"
+    pub redact_name: bool,
+}
+
+impl Default for PromptPrivacy {
+    fn default() -> Self {
+        Self {
+            share_games: true,
+            share_ratings: true,
+            history_messages: DEFAULT_HISTORY_MESSAGES,
+            redact_name: false,
+        }
+    }
+}
+
+impl PromptPrivacy {
+    /// Whether a tool may be offered to the model
+    pub fn allows_tool(&self, name: &str) -> bool {
+        (self.share_games || !GAME_TOOLS.contains(&name)) && (self.share_ratings || !RATING_TOOLS.contains(&name))
+    }
+}
+
+pub fn load(conn: &Connection, profile_id: i64) -> Result<PromptPrivacy> {
+    Ok(repositories::get_profile_setting(conn, profile_id, PRIVACY_SETTING_KEY)?
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default())
+}
+
+pub fn save(conn: &Connection, profile_id: i64, privacy: &PromptPrivacy) -> Result<()> {
+    let value = serde_json::to_string(privacy).unwrap_or_default();
+    repositories::set_profile_setting(conn, profile_id, PRIVACY_SETTING_KEY, &value)
+}
+
+/// Settings and profile name of the active profile; the defaults without one
+pub fn active() -> (PromptPrivacy, Option<String>) {
+    DB.with_conn(|conn| match repositories::get_first_profile(conn)? {
+        Some(profile) => Ok((load(conn, profile.id)?, Some(profile.name))),
+        None => Ok((PromptPrivacy::default(), None)),
+    })
+    .unwrap_or_default()
+}
+
+/// A message as it goes to the model, in the OpenAI chat format. Fields other
+/// than the role and text, such as tool calls, pass through untouched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub content: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn null_as_empty<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// The parts of a chat message the privacy rules look at
+pub trait PromptEntry {
+    fn role(&self) -> &str;
+    fn content_mut(&mut self) -> &mut String;
+    /// The model asking for tools, as opposed to saying something
+    fn calls_tools(&self) -> bool;
+}
+
+impl PromptEntry for PromptMessage {
+    fn role(&self) -> &str {
+        &self.role
+    }
+
+    fn content_mut(&mut self) -> &mut String {
+        &mut self.content
+    }
+
+    fn calls_tools(&self) -> bool {
+        self.extra.get("tool_calls").is_some_and(|calls| !calls.is_null())
+    }
+}
+
+/// `text` with `name` replaced wherever it stands as a whole word, in any case
+fn redact_name(text: &str, name: &str) -> String {
+    let name: Vec<char> = name.trim().to_lowercase().chars().collect();
+    if name.len() < 2 {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut redacted = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let end = i + name.len();
+        let matches = end <= chars.len() && chars[i..end].iter().zip(&name).all(|(c, n)| c.to_lowercase().eq(n.to_lowercase()));
+        let bounded = (i == 0 || !chars[i - 1].is_alphanumeric()) && chars.get(end).is_none_or(|c| !c.is_alphanumeric());
+        if matches && bounded {
+            redacted.push_str(NAME_STANDIN);
+            i = end;
+        } else {
+            redacted.push(chars[i]);
+            i += 1;
+        }
+    }
+    redacted
+}
+
+/// Apply `privacy` to a prompt about to be sent: earlier chat beyond the history
+/// limit is dropped with the tool exchanges of earlier turns and the profile
+/// `name` is redacted when asked.
+/// Running it again on its own output changes nothing.
+pub fn apply<M: PromptEntry>(privacy: &PromptPrivacy, name: Option<&str>, messages: Vec<M>) -> Vec<M> {
+    let latest = messages.iter().rposition(|m| m.role() == "user").unwrap_or(messages.len());
+    let earlier_chat: Vec<usize> = (0..latest)
+        .filter(|&i| matches!(messages[i].role(), "user" | "assistant") && !messages[i].calls_tools())
+        .collect();
+    let keep_from = earlier_chat.len().saturating_sub(privacy.history_messages as usize);
+    let kept_chat = &earlier_chat[keep_from..];
+
+    let mut kept = Vec::with_capacity(messages.len());
+    for (i, mut message) in messages.into_iter().enumerate() {
+        if i < latest && message.role() != "system" && !kept_chat.contains(&i) {
+            continue;
+        }
+        if let Some(name) = name.filter(|_| privacy.redact_name) {
+            let content = message.content_mut();
+            *content = redact_name(content, name);
+        }
+        kept.push(message);
+    }
+    kept
+}
+
+/// `apply` with the active profile's settings
+pub fn apply_active<M: PromptEntry>(messages: Vec<M>) -> Vec<M> {
+    let (privacy, name) = active();
+    apply(&privacy, name.as_deref(), messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn message(role: &str, content: &str) -> PromptMessage {
+        PromptMessage { role: role.to_string(), content: content.to_string(), extra: Map::new() }
+    }
+
+    #[test]
+    fn test_prompt_privacy() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile_id = repositories::create_profile(&conn, "Test User", "beginner", 800).unwrap().id;
+        assert_eq!(load(&conn, profile_id).unwrap(), PromptPrivacy::default());
+        let privacy = PromptPrivacy { share_games: false, share_ratings: false, history_messages: 1, redact_name: true };
+        save(&conn, profile_id, &privacy).unwrap();
+        assert_eq!(load(&conn, profile_id).unwrap(), privacy);
+        assert!(!privacy.allows_tool("get_player_stats") && privacy.allows_tool("lookup_concept"));
+        let games_only = PromptPrivacy { share_ratings: false, ..Default::default() };
+        assert!(games_only.allows_tool("get_recent_games") && !games_only.allows_tool("get_opponent_strength_stats"));
+
+        let mut call = message("assistant", "");
+        call.extra.insert("tool_calls".to_string(), serde_json::json!([{ "id": "1" }]));
+        let prompt = vec![
+            message("system", "You are Gurgeh."),
+            message("user", "Hi, I'm Ana"),
+            message("assistant", "Hello Ana."),
+            message("user", "How am I doing? ANA wants to know"),
+            call,
+            message("tool", r#"{"games":[{"result":"win"}],"name":"Ana"}"#),
+        ];
+        let sent = apply(&privacy, Some("ana"), prompt);
+        let contents: Vec<&str> = sent.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec![
+                "You are Gurgeh.",
+                "Hello the student.",
+                "How am I doing? the student wants to know",
+                "",
+                r#"{"games":[{"result":"win"}],"name":"the student"}"#,
+            ]
+        );
+        assert_eq!(apply(&privacy, Some("ana"), sent.clone()), sent);
+        assert_eq!(redact_name("Banana and Ana's", "ana"), "Banana and the student's");
+    }
+}
@@ -0,0 +1,338 @@
+use chess_core::{format_move, parse_fen, parse_legal_uci, NotationPrefs, Opening, Piece, Position, OPENINGS};
+use chess_trainer::{OpeningWalkthrough, WalkthroughStep};
+
+use crate::coach_actions::{ActionTarget, CoachAction};
+use crate::coach_tools;
+use crate::commands::learning::{available_concepts, ChessConcept};
+use crate::kid_mode;
+use crate::notation_prefs;
+
+/// Longer messages are rarely simple lookups, so they go to the model
+const MAX_WORDS: usize = 12;
+
+/// Words that tie a question to a position, a game or a judgement the local data can't make
+const MODEL_WORDS: &[&str] = &[
+    "this", "that", "here", "now", "my", "mine", "position", "why", "should", "better", "worse", "than",
+    "against", "vs", "versus", "or", "idea", "ideas", "plan", "plans", "strategy", "recommend",
+];
+
+const VALUE_WORDS: &[&str] = &["worth", "value", "valued", "point", "points"];
+
+/// A named opening is only looked up when the message asks how it goes
+const OPENING_WORDS: &[&str] = &["what", "how", "show", "move", "moves", "line", "go", "goes", "play", "order"];
+
+/// A rule is only explained when the message asks about it
+const RULE_WORDS: &[&str] = &["what", "how", "when", "can", "does", "is", "explain", "rule", "rules", "work"];
+
+const PIECES: &[(&str, Piece)] = &[
+    ("pawn", Piece::Pawn),
+    ("knight", Piece::Knight),
+    ("bishop", Piece::Bishop),
+    ("rook", Piece::Rook),
+    ("queen", Piece::Queen),
+    ("king", Piece::King),
+];
+
+/// Openings are often called without their last word: "the Sicilian", "the Italian"
+const GENERIC_ENDINGS: &[&str] = &["defence", "game", "opening"];
+
+/// Questions that open with these ask what something is
+const DEFINITION_PREFIXES: &[&str] = &["what is an", "what is a", "what is the", "what is", "whats an", "whats a", "whats", "define", "explain"];
+
+/// Rules of the game, with the words that ask about them
+const RULES: &[(&[&str], &str)] = &[
+    (
+        &["castle", "castling", "castles"],
+        "Castling moves the king two squares towards a rook, and that rook jumps to the square the king crossed. \
+         It is allowed only if neither piece has moved yet, the squares between them are empty, and the king is not in \
+         check, does not pass through an attacked square and does not land on one.",
+    ),
+    (
+        &["en passant"],
+        "En passant: when a pawn moves two squares from its starting square and lands beside an enemy pawn, that enemy \
+         pawn may capture it as if it had moved only one square. The capture must be made on the very next move or the \
+         chance is gone.",
+    ),
+    (
+        &["promote", "promotion", "promoting", "promotes"],
+        "A pawn that reaches the last rank must be promoted, as part of the same move, to a queen, rook, bishop or \
+         knight of its own colour. Promotion doesn't depend on what has been captured, so you can have two queens or more.",
+    ),
+    (
+        &["stalemate", "stalemated"],
+        "Stalemate: the side to move is not in check but has no legal move. The game ends at once as a draw, however \
+         much material either side has.",
+    ),
+    (
+        &["fifty move", "50 move"],
+        "The fifty-move rule: if fifty moves by each side pass without a capture or a pawn move, either player may claim \
+         a draw. After seventy-five such moves the game is drawn automatically.",
+    ),
+    (
+        &["threefold", "three fold", "repetition"],
+        "Threefold repetition: when the same position occurs three times, with the same side to move and the same \
+         castling and en passant rights, either player may claim a draw. The moves don't have to repeat in a row.",
+    ),
+];
+
+/// What a quick question asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Intent {
+    PieceValue(Piece),
+    OpeningMoves(&'static Opening),
+    Rule(&'static str),
+    Concept(String),
+}
+
+/// A reply written from local data instead of the model
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickAnswer {
+    pub content: String,
+    /// Position the answer is about, for the board
+    pub fen: Option<String>,
+    pub actions: Vec<CoachAction>,
+}
+
+/// Lowercase words with apostrophes dropped and other punctuation as gaps
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace(['\'', '\u{2019}'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether `phrase` appears in `text` as whole words; both are space-joined `words`
+fn has_phrase(text: &str, phrase: &str) -> bool {
+    format!(" {} ", text).contains(&format!(" {} ", phrase))
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    PIECES.iter().find(|(_, p)| *p == piece).map_or("piece", |(name, _)| name)
+}
+
+fn piece_value(text: &str) -> Option<Piece> {
+    if !VALUE_WORDS.iter().any(|w| has_phrase(text, w)) {
+        return None;
+    }
+    let mut mentioned = PIECES
+        .iter()
+        .filter(|(name, _)| has_phrase(text, name) || has_phrase(text, &format!("{}s", name)))
+        .map(|(_, piece)| *piece);
+    // Comparisons between pieces depend on the position
+    match (mentioned.next(), mentioned.next()) {
+        (Some(piece), None) => Some(piece),
+        _ => None,
+    }
+}
+
+/// The opening named in `text`, the longest name winning so "Queen's Gambit Declined"
+/// isn't taken for the Queen's Gambit. Lines sharing a name give the shortest.
+fn named_opening(text: &str) -> Option<&'static Opening> {
+    let spelled = text.replace("defense", "defence");
+    OPENINGS
+        .iter()
+        .filter_map(|opening| {
+            let name = words(opening.name).join(" ");
+            let short = name
+                .rsplit_once(' ')
+                .filter(|(_, last)| GENERIC_ENDINGS.contains(last))
+                .map(|(short, _)| short.to_string());
+            [Some(name), short]
+                .into_iter()
+                .flatten()
+                .filter(|n| n != "open" && has_phrase(&spelled, n))
+                .map(|n| n.len())
+                .max()
+                .map(|len| (opening, len))
+        })
+        .max_by(|(a, a_len), (b, b_len)| a_len.cmp(b_len).then(b.moves.len().cmp(&a.moves.len())))
+        .map(|(opening, _)| opening)
+}
+
+fn rule(text: &str) -> Option<&'static str> {
+    RULES
+        .iter()
+        .find(|(keywords, _)| keywords.iter().any(|k| has_phrase(text, k)))
+        .map(|(_, answer)| *answer)
+}
+
+/// The subject of a "what is ..." question, if `text` is one
+fn definition_subject(text: &str) -> Option<String> {
+    if let Some(rest) = text.strip_prefix("what does ") {
+        return rest.strip_suffix(" mean").map(str::to_string);
+    }
+    let rest = DEFINITION_PREFIXES.iter().find_map(|p| text.strip_prefix(p).and_then(|r| r.strip_prefix(' ')))?;
+    Some(rest.strip_suffix(" in chess").unwrap_or(rest).to_string())
+}
+
+/// What `message` asks, if it is a simple factual question the app can answer itself.
+/// Anything about a position, a game or a choice between options is left to the model.
+pub fn classify(concepts: &[ChessConcept], message: &str) -> Option<Intent> {
+    let words = words(message);
+    if words.is_empty() || words.len() > MAX_WORDS || words.iter().any(|w| MODEL_WORDS.contains(&w.as_str())) {
+        return None;
+    }
+    let text = words.join(" ");
+
+    if let Some(piece) = piece_value(&text) {
+        return Some(Intent::PieceValue(piece));
+    }
+    let asks = |keywords: &[&str]| words.iter().any(|w| keywords.contains(&w.as_str()));
+    if let Some(opening) = named_opening(&text).filter(|_| asks(OPENING_WORDS)) {
+        return Some(Intent::OpeningMoves(opening));
+    }
+    if let Some(answer) = rule(&text).filter(|_| asks(RULE_WORDS)) {
+        return Some(Intent::Rule(answer));
+    }
+    let subject = coach_tools::normalize(&definition_subject(&text)?);
+    concepts
+        .iter()
+        .find(|c| coach_tools::normalize(&c.id) == subject || coach_tools::normalize(&c.name) == subject)
+        .map(|c| Intent::Concept(c.id.clone()))
+}
+
+fn piece_value_answer(piece: Piece) -> String {
+    if piece == Piece::King {
+        return "The king has no point value: it can never be traded, and losing it loses the game. In the endgame it \
+                fights about as well as a minor piece."
+            .to_string();
+    }
+    let value = Position::piece_value(piece);
+    let scale = "The usual scale is pawn 1, knight 3, bishop 3, rook 5 and queen 9.";
+    if piece == Piece::Pawn {
+        return format!("A pawn is worth 1 point, the unit the other pieces are measured in. {}", scale);
+    }
+    format!(
+        "A {} is worth about {} pawns. {} These are rules of thumb: an active piece can be worth more than its points, a shut-in one less.",
+        piece_name(piece),
+        value,
+        scale
+    )
+}
+
+/// The line's moves numbered and written in `notation`, e.g. "1.e4 e5 2.Nf3"
+fn numbered_line(steps: &[WalkthroughStep], notation: &NotationPrefs) -> Option<String> {
+    let moves = steps
+        .iter()
+        .map(|step| {
+            let board = parse_fen(&step.fen).ok()?;
+            let text = format_move(&board, parse_legal_uci(&board, &step.uci).ok()?, notation);
+            Some(if step.ply.is_multiple_of(2) { format!("{}.{}", step.ply / 2 + 1, text) } else { text })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(moves.join(" "))
+}
+
+fn opening_answer(opening: &'static Opening, notation: &NotationPrefs) -> Option<QuickAnswer> {
+    let walkthrough = OpeningWalkthrough::new(opening).ok()?;
+    let played: Vec<String> = walkthrough.steps.iter().map(|s| s.uci.clone()).collect();
+    let fen = walkthrough.replay(&played).ok()?.to_string();
+    Some(QuickAnswer {
+        content: format!(
+            "The {} (ECO {}) goes {}.",
+            opening.name,
+            opening.eco,
+            numbered_line(&walkthrough.steps, notation)?
+        ),
+        actions: vec![CoachAction::new("Set up the position", ActionTarget::SetupPosition { fen: fen.clone() })],
+        fen: Some(fen),
+    })
+}
+
+/// The answer to `intent` from the concept library, the opening book or the rules,
+/// with moves written in `notation`
+pub fn answer_with(concepts: &[ChessConcept], intent: &Intent, notation: &NotationPrefs) -> Option<QuickAnswer> {
+    match intent {
+        Intent::PieceValue(piece) => Some(QuickAnswer { content: piece_value_answer(*piece), fen: None, actions: vec![] }),
+        Intent::OpeningMoves(opening) => opening_answer(opening, notation),
+        Intent::Rule(answer) => Some(QuickAnswer { content: answer.to_string(), fen: None, actions: vec![] }),
+        Intent::Concept(id) => {
+            let concept = concepts.iter().find(|c| &c.id == id)?;
+            Some(QuickAnswer {
+                content: format!("{}: {}", concept.name, concept.short_description),
+                fen: concept.example_fen.clone(),
+                actions: vec![CoachAction::new(
+                    format!("Open {}", concept.name),
+                    ActionTarget::OpenConcept { concept_id: concept.id.clone() },
+                )],
+            })
+        }
+    }
+}
+
+/// A local answer to `message` when it is a simple factual question. In kid mode
+/// every question goes to the model, which words its answers for children.
+pub fn answer(message: &str) -> Option<QuickAnswer> {
+    if kid_mode::active() {
+        return None;
+    }
+    let concepts = available_concepts();
+    let intent = classify(&concepts, message)?;
+    let answer = answer_with(&concepts, &intent, &notation_prefs::active());
+    if answer.is_some() {
+        tracing::debug!(?intent, "coach question answered locally");
+    }
+    answer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_core::PieceLanguage;
+
+    fn concept(id: &str, name: &str) -> ChessConcept {
+        ChessConcept {
+            id: id.to_string(),
+            name: name.to_string(),
+            category: "Tactics".to_string(),
+            difficulty: "Beginner".to_string(),
+            short_description: format!("Short note on the {}", name.to_lowercase()),
+            full_explanation: String::new(),
+            example_fen: Some("4k3/8/8/8/8/8/4R3/4K3 w - - 0 1".to_string()),
+            example_arrows: vec![],
+            example_highlights: vec![],
+            related_concepts: vec![],
+            practice_exercises: vec![],
+            model_games: vec![],
+        }
+    }
+
+    #[test]
+    fn test_classifies_quick_questions() {
+        let concepts = vec![concept("pin", "Pin"), concept("knight_fork", "Knight Fork")];
+        let intent = |message: &str| classify(&concepts, message);
+
+        assert_eq!(intent("What's the value of a bishop?"), Some(Intent::PieceValue(Piece::Bishop)));
+        assert_eq!(intent("how many points are rooks worth"), Some(Intent::PieceValue(Piece::Rook)));
+        let italian = intent("Show me the Italian Game moves").unwrap();
+        assert!(matches!(italian, Intent::OpeningMoves(o) if o.name == "Italian Game"));
+        assert!(matches!(intent("what is the sicilian defense?"), Some(Intent::OpeningMoves(o)) if o.eco == "B20"));
+        assert!(matches!(intent("Queen's Gambit Declined line"), Some(Intent::OpeningMoves(o)) if o.eco == "D30"));
+        assert!(matches!(intent("How does en passant work?"), Some(Intent::Rule(r)) if r.starts_with("En passant")));
+        assert_eq!(intent("What is a knight fork in chess?"), Some(Intent::Concept("knight_fork".to_string())));
+        assert_eq!(intent("what does pin mean"), Some(Intent::Concept("pin".to_string())));
+
+        // Position-bound, comparative and open questions go to the model
+        assert_eq!(intent("Can I castle here?"), None);
+        assert_eq!(intent("Is a bishop worth more than a knight?"), None);
+        assert_eq!(intent("Why is the Italian Game good for beginners?"), None);
+        assert_eq!(intent("What is zugzwang?"), None);
+        assert_eq!(intent("I lost with the French Defence again"), None);
+        assert_eq!(intent("Tell me a story about a rook"), None);
+
+        let english = NotationPrefs::default();
+        let answer = answer_with(&concepts, &italian, &english).unwrap();
+        assert_eq!(answer.content, "The Italian Game (ECO C50) goes 1.e4 e5 2.Nf3 Nc6 3.Bc4.");
+        let german = NotationPrefs { language: PieceLanguage::De, ..NotationPrefs::default() };
+        let answer_de = answer_with(&concepts, &italian, &german).unwrap();
+        assert_eq!(answer_de.content, "The Italian Game (ECO C50) goes 1.e4 e5 2.Sf3 Sc6 3.Lc4.");
+        assert!(answer.fen.unwrap().starts_with("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq"));
+        let value = answer_with(&concepts, &Intent::PieceValue(Piece::Bishop), &english).unwrap();
+        assert!(value.content.starts_with("A bishop is worth about 3 pawns."));
+        let pin = answer_with(&concepts, &Intent::Concept("pin".to_string()), &english).unwrap();
+        assert_eq!(pin.content, "Pin: Short note on the pin");
+        assert_eq!(pin.actions[0].target, ActionTarget::OpenConcept { concept_id: "pin".to_string() });
+    }
+}
@@ -0,0 +1,54 @@
+use chess_core::SeededRng;
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories;
+use crate::DB;
+
+/// Seed for deterministic mode. While set, the engine, exercise selection and
+/// daily workout repeat exactly, so a session can be reproduced from a bug report.
+const SESSION_SEED_SETTING_KEY: &str = "session_seed";
+
+pub fn get(conn: &Connection) -> Result<Option<u64>> {
+    Ok(repositories::get_setting(conn, SESSION_SEED_SETTING_KEY)?.and_then(|v| v.parse().ok()))
+}
+
+/// `None` turns deterministic mode off
+pub fn set(conn: &Connection, seed: Option<u64>) -> Result<()> {
+    let value = seed.map(|s| s.to_string()).unwrap_or_default();
+    repositories::set_setting(conn, SESSION_SEED_SETTING_KEY, &value)
+}
+
+/// The seed in force, if deterministic mode is on
+pub fn active() -> Option<u64> {
+    DB.with_conn(get).ok().flatten()
+}
+
+/// Workout rotation for `day`, fixed by the seed when there is one so the
+/// workout no longer changes with the date
+pub fn workout_day(seed: Option<u64>, day: usize) -> usize {
+    match seed {
+        Some(seed) => SeededRng::new(seed).next_u64() as usize,
+        None => day,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_seed_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        assert_eq!(get(&conn).unwrap(), None);
+
+        set(&conn, Some(1234)).unwrap();
+        assert_eq!(get(&conn).unwrap(), Some(1234));
+        assert_eq!(workout_day(Some(1234), 10), workout_day(Some(1234), 200));
+        assert_eq!(workout_day(None, 10), 10);
+
+        set(&conn, None).unwrap();
+        assert_eq!(get(&conn).unwrap(), None);
+    }
+}
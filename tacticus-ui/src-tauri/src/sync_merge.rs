@@ -0,0 +1,722 @@
+//! Merging history (games, chat messages and exercise attempts) between devices.
+//! Rows are matched on their global `uid`, never on the local rowid, so a batch can be
+//! merged any number of times, in any order, without duplicates. Rows edited after
+//! they were created carry an `updated_at` time; the newer copy of a row wins.
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::conversation_mode::ConversationMode;
+use crate::database::repositories;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncGame {
+    pub uid: String,
+    pub initial_fen: String,
+    pub final_fen: String,
+    pub moves: Vec<String>,
+    pub result: String,
+    pub player_color: String,
+    pub opponent_type: String,
+    pub opponent_elo: Option<i32>,
+    pub mistakes: i32,
+    pub blunders: i32,
+    pub opening_name: Option<String>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub termination: Option<String>,
+    pub time_class: Option<String>,
+    /// Absent from batches written before edits were tracked
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+/// Parent of synced messages; the copy edited last wins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConversation {
+    pub uid: String,
+    pub title: Option<String>,
+    pub context: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+    /// The game a review conversation is about. `context` names it by the sender's
+    /// rowid, which means nothing here, so the receiver relinks it through this uid.
+    #[serde(default)]
+    pub game_uid: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncMessage {
+    pub uid: String,
+    pub conversation_uid: String,
+    pub role: String,
+    pub content: String,
+    pub tool_calls: Option<String>,
+    pub tool_results: Option<String>,
+    pub created_at: String,
+}
+
+/// Parent of synced exercise attempts. Its totals are recomputed from the attempts after a merge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncTrainingSession {
+    pub uid: String,
+    pub total_exercises: i32,
+    pub strategies_covered: Vec<String>,
+    pub duration_seconds: i32,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Absent from batches written before sessions were rated
+    #[serde(default)]
+    pub performance_rating: Option<i32>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncExerciseResult {
+    pub uid: String,
+    pub session_uid: Option<String>,
+    pub exercise_type: String,
+    pub difficulty: String,
+    pub position_fen: String,
+    pub solved: bool,
+    pub attempts: i32,
+    pub time_seconds: i32,
+    pub hints_used: i32,
+    pub created_at: String,
+    /// Absent from batches written before suspicious attempts were flagged
+    #[serde(default)]
+    pub flagged: bool,
+    #[serde(default)]
+    pub flag_reason: Option<String>,
+    /// Absent from batches written before alternative solutions were accepted
+    #[serde(default)]
+    pub alternative_solution: bool,
+    /// Absent from batches written before guided help was recorded
+    #[serde(default)]
+    pub scaffolding: Vec<String>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+/// One profile's history as exchanged between devices. Game analyses are left out;
+/// the receiving device analyses merged games itself.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncBatch {
+    pub games: Vec<SyncGame>,
+    pub conversations: Vec<SyncConversation>,
+    pub messages: Vec<SyncMessage>,
+    pub training_sessions: Vec<SyncTrainingSession>,
+    pub exercise_results: Vec<SyncExerciseResult>,
+    /// Newest change in the batch; pass it as `after` on the next export
+    #[serde(default)]
+    pub watermark: String,
+}
+
+/// New rows added by a merge; `duplicates` counts rows that were already present
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeReport {
+    pub games: usize,
+    pub conversations: usize,
+    pub messages: usize,
+    pub training_sessions: usize,
+    pub exercise_results: usize,
+    /// Rows already present that the batch held a newer copy of
+    pub updated: usize,
+    pub duplicates: usize,
+    /// Children whose parent was in neither the batch nor this database
+    pub orphans: usize,
+}
+
+/// `profile_id`'s history changed since the watermark `after`, or all of it. Passing
+/// the previous batch's `watermark` gives just what was added or edited since. Parents
+/// of included rows always come along so the receiving side can link them.
+pub fn export(conn: &Connection, profile_id: i64, after: Option<&str>) -> Result<SyncBatch> {
+    let after = after.unwrap_or("");
+
+    let messages: Vec<SyncMessage> = conn
+        .prepare(
+            r#"
+            SELECT m.uid, c.uid, m.role, m.content, m.tool_calls, m.tool_results, m.created_at
+            FROM messages m JOIN conversations c ON c.id = m.conversation_id
+            WHERE c.profile_id = ?1 AND c.deleted_at IS NULL AND substr(m.created_at, 1, 23) >= substr(?2, 1, 23)
+            ORDER BY m.created_at, m.uid
+            "#,
+        )?
+        .query_map(params![profile_id, after], |row| {
+            Ok(SyncMessage {
+                uid: row.get(0)?,
+                conversation_uid: row.get(1)?,
+                role: row.get(2)?,
+                content: row.get(3)?,
+                tool_calls: row.get(4)?,
+                tool_results: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?
+        .collect::<Result<_>>()?;
+    let conversation_uids: HashSet<&str> = messages.iter().map(|m| m.conversation_uid.as_str()).collect();
+    let conversations: Vec<SyncConversation> = conn
+        .prepare(
+            "SELECT uid, title, context, created_at, updated_at FROM conversations \
+             WHERE profile_id = ?1 AND deleted_at IS NULL ORDER BY updated_at, uid",
+        )?
+        .query_map(params![profile_id], |row| {
+            Ok(SyncConversation {
+                uid: row.get(0)?,
+                title: row.get(1)?,
+                context: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+                game_uid: None,
+            })
+        })?
+        .collect::<Result<_>>()?;
+    let mut conversations: Vec<SyncConversation> = conversations
+        .into_iter()
+        .filter(|c| changed_since(&c.updated_at, after) || conversation_uids.contains(c.uid.as_str()))
+        .collect();
+    for conversation in &mut conversations {
+        if let ConversationMode::GameReview { game_id } = ConversationMode::parse(conversation.context.as_deref()) {
+            conversation.game_uid = conn
+                .query_row("SELECT uid FROM games WHERE id = ?1 AND deleted_at IS NULL", params![game_id], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+        }
+    }
+
+    let game_uids: HashSet<&str> = conversations.iter().filter_map(|c| c.game_uid.as_deref()).collect();
+    let games: Vec<SyncGame> = conn
+        .prepare(
+            r#"
+            SELECT uid, initial_fen, final_fen, moves, result, player_color, opponent_type, opponent_elo,
+                   mistakes, blunders, opening_name, created_at, finished_at, termination, time_class, updated_at
+            FROM games WHERE profile_id = ?1 AND deleted_at IS NULL ORDER BY updated_at, uid
+            "#,
+        )?
+        .query_map(params![profile_id], |row| {
+            Ok(SyncGame {
+                uid: row.get(0)?,
+                initial_fen: row.get(1)?,
+                final_fen: row.get(2)?,
+                moves: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                result: row.get(4)?,
+                player_color: row.get(5)?,
+                opponent_type: row.get(6)?,
+                opponent_elo: row.get(7)?,
+                mistakes: row.get(8)?,
+                blunders: row.get(9)?,
+                opening_name: row.get(10)?,
+                created_at: row.get(11)?,
+                finished_at: row.get(12)?,
+                termination: row.get(13)?,
+                time_class: row.get(14)?,
+                updated_at: row.get(15)?,
+            })
+        })?
+        .collect::<Result<_>>()?;
+    let games = games
+        .into_iter()
+        .filter(|g| changed_since(&g.updated_at, after) || game_uids.contains(g.uid.as_str()))
+        .collect();
+
+    let exercise_results: Vec<SyncExerciseResult> = conn
+        .prepare(
+            r#"
+            SELECT r.uid, s.uid, r.exercise_type, r.difficulty, r.position_fen, r.solved, r.attempts,
+                   r.time_seconds, r.hints_used, r.created_at, r.flagged, r.flag_reason,
+                   r.alternative_solution, r.scaffolding, r.updated_at
+            FROM exercise_results r LEFT JOIN training_sessions s ON s.id = r.session_id
+            WHERE r.profile_id = ?1 AND substr(r.updated_at, 1, 23) >= substr(?2, 1, 23) ORDER BY r.updated_at, r.uid
+            "#,
+        )?
+        .query_map(params![profile_id, after], |row| {
+            Ok(SyncExerciseResult {
+                uid: row.get(0)?,
+                session_uid: row.get(1)?,
+                exercise_type: row.get(2)?,
+                difficulty: row.get(3)?,
+                position_fen: row.get(4)?,
+                solved: row.get::<_, i32>(5)? != 0,
+                attempts: row.get(6)?,
+                time_seconds: row.get(7)?,
+                hints_used: row.get(8)?,
+                created_at: row.get(9)?,
+                flagged: row.get::<_, i32>(10)? != 0,
+                flag_reason: row.get(11)?,
+                alternative_solution: row.get::<_, i32>(12)? != 0,
+                scaffolding: serde_json::from_str(&row.get::<_, String>(13)?).unwrap_or_default(),
+                updated_at: row.get(14)?,
+            })
+        })?
+        .collect::<Result<_>>()?;
+    let session_uids: HashSet<&str> = exercise_results.iter().filter_map(|r| r.session_uid.as_deref()).collect();
+    let training_sessions: Vec<SyncTrainingSession> = conn
+        .prepare(
+            r#"
+            SELECT uid, total_exercises, strategies_covered, duration_seconds, started_at, finished_at, performance_rating,
+                   updated_at
+            FROM training_sessions WHERE profile_id = ?1 ORDER BY updated_at, uid
+            "#,
+        )?
+        .query_map(params![profile_id], |row| {
+            Ok(SyncTrainingSession {
+                uid: row.get(0)?,
+                total_exercises: row.get(1)?,
+                strategies_covered: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                duration_seconds: row.get(3)?,
+                started_at: row.get(4)?,
+                finished_at: row.get(5)?,
+                performance_rating: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        })?
+        .collect::<Result<_>>()?;
+    let training_sessions: Vec<SyncTrainingSession> = training_sessions
+        .into_iter()
+        .filter(|s| changed_since(&s.updated_at, after) || session_uids.contains(s.uid.as_str()))
+        .collect();
+
+    let watermark = games
+        .iter()
+        .map(|g| &g.updated_at)
+        .chain(conversations.iter().map(|c| &c.updated_at))
+        .chain(messages.iter().map(|m| &m.created_at))
+        .chain(training_sessions.iter().map(|s| &s.updated_at))
+        .chain(exercise_results.iter().map(|r| &r.updated_at))
+        .map(String::as_str)
+        .fold(after, |newest, t| newest.max(t))
+        .to_string();
+
+    Ok(SyncBatch {
+        games,
+        conversations,
+        messages,
+        training_sessions,
+        exercise_results,
+        watermark,
+    })
+}
+
+/// Whether a row stamped `changed` is new since the watermark `after`. Times are compared
+/// to the millisecond, the precision of the update triggers, so a row changed in the same
+/// millisecond as the watermark is sent again rather than missed; merging it twice is harmless.
+fn changed_since(changed: &str, after: &str) -> bool {
+    changed.get(..23).unwrap_or(changed) >= after.get(..23).unwrap_or(after)
+}
+
+/// Local rowid of the row with `uid` in `table`
+fn local_id(conn: &Connection, table: &str, uid: &str) -> Result<Option<i64>> {
+    conn.query_row(&format!("SELECT id FROM {} WHERE uid = ?1", table), params![uid], |row| row.get(0))
+        .optional()
+}
+
+/// When a synced row last changed; batches from before edits were tracked only say when it was made
+fn changed_at<'a>(updated_at: &'a str, created_at: &'a str) -> &'a str {
+    if updated_at.is_empty() {
+        created_at
+    } else {
+        updated_at
+    }
+}
+
+/// `conversation`'s context with any reviewed game named by its local rowid. A review
+/// whose game isn't here becomes a general chat.
+fn local_context(conn: &Connection, conversation: &SyncConversation) -> Result<Option<String>> {
+    if !matches!(ConversationMode::parse(conversation.context.as_deref()), ConversationMode::GameReview { .. }) {
+        return Ok(conversation.context.clone());
+    }
+    let game_id = match &conversation.game_uid {
+        Some(uid) => local_id(conn, "games", uid)?,
+        None => None,
+    };
+    Ok(game_id.map(|game_id| ConversationMode::GameReview { game_id }.to_context()))
+}
+
+/// Add the rows of `batch` this database does not have yet to `profile_id`'s history,
+/// and take the batch's copy of rows it has an older copy of. Runs as one transaction;
+/// merging the same batch again changes nothing.
+pub fn merge(conn: &Connection, profile_id: i64, batch: &SyncBatch) -> Result<MergeReport> {
+    let tx = conn.unchecked_transaction()?;
+    let mut report = MergeReport::default();
+
+    for game in &batch.games {
+        let existed = local_id(&tx, "games", &game.uid)?.is_some();
+        let changed = tx.execute(
+            r#"
+            INSERT INTO games (uid, profile_id, initial_fen, final_fen, moves, result, player_color, opponent_type,
+                               opponent_elo, mistakes, blunders, opening_name, created_at, finished_at, termination,
+                               time_class, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            ON CONFLICT(uid) DO UPDATE SET
+                final_fen = excluded.final_fen,
+                moves = excluded.moves,
+                result = excluded.result,
+                opponent_elo = excluded.opponent_elo,
+                finished_at = excluded.finished_at,
+                termination = excluded.termination,
+                time_class = excluded.time_class,
+                updated_at = excluded.updated_at
+            WHERE excluded.updated_at > games.updated_at
+            "#,
+            params![
+                game.uid,
+                profile_id,
+                game.initial_fen,
+                game.final_fen,
+                serde_json::to_string(&game.moves).unwrap_or_else(|_| "[]".to_string()),
+                game.result,
+                game.player_color,
+                game.opponent_type,
+                game.opponent_elo,
+                game.mistakes,
+                game.blunders,
+                game.opening_name,
+                game.created_at,
+                game.finished_at,
+                game.termination,
+                game.time_class,
+                changed_at(&game.updated_at, &game.created_at),
+            ],
+        )?;
+        if existed {
+            report.updated += changed;
+        } else {
+            report.games += changed;
+        }
+    }
+
+    for conversation in &batch.conversations {
+        let existed = local_id(&tx, "conversations", &conversation.uid)?.is_some();
+        let changed = tx.execute(
+            r#"
+            INSERT INTO conversations (uid, profile_id, title, context, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(uid) DO UPDATE SET
+                title = excluded.title,
+                context = excluded.context,
+                updated_at = excluded.updated_at
+            WHERE excluded.updated_at > conversations.updated_at
+            "#,
+            params![
+                conversation.uid,
+                profile_id,
+                conversation.title,
+                local_context(&tx, conversation)?,
+                conversation.created_at,
+                changed_at(&conversation.updated_at, &conversation.created_at),
+            ],
+        )?;
+        if existed {
+            report.updated += changed;
+        } else {
+            report.conversations += changed;
+        }
+    }
+    let mut conversation_ids: HashMap<&str, Option<i64>> = HashMap::new();
+    for message in &batch.messages {
+        let conversation_id = match conversation_ids.get(message.conversation_uid.as_str()) {
+            Some(id) => *id,
+            None => {
+                let id = local_id(&tx, "conversations", &message.conversation_uid)?;
+                conversation_ids.insert(&message.conversation_uid, id);
+                id
+            }
+        };
+        let Some(conversation_id) = conversation_id else {
+            report.orphans += 1;
+            continue;
+        };
+        let added = tx.execute(
+            r#"
+            INSERT INTO messages (uid, conversation_id, role, content, tool_calls, tool_results, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(uid) DO NOTHING
+            "#,
+            params![
+                message.uid,
+                conversation_id,
+                message.role,
+                message.content,
+                message.tool_calls,
+                message.tool_results,
+                message.created_at,
+            ],
+        )?;
+        if added > 0 {
+            tx.execute(
+                "UPDATE conversations SET updated_at = MAX(updated_at, ?1) WHERE id = ?2",
+                params![message.created_at, conversation_id],
+            )?;
+        }
+        report.messages += added;
+    }
+
+    for session in &batch.training_sessions {
+        let existed = local_id(&tx, "training_sessions", &session.uid)?.is_some();
+        let changed = tx.execute(
+            r#"
+            INSERT INTO training_sessions (uid, profile_id, total_exercises, strategies_covered, duration_seconds,
+                                           started_at, finished_at, performance_rating, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT(uid) DO UPDATE SET
+                total_exercises = excluded.total_exercises,
+                strategies_covered = excluded.strategies_covered,
+                duration_seconds = excluded.duration_seconds,
+                finished_at = excluded.finished_at,
+                performance_rating = excluded.performance_rating,
+                updated_at = excluded.updated_at
+            WHERE excluded.updated_at > training_sessions.updated_at
+            "#,
+            params![
+                session.uid,
+                profile_id,
+                session.total_exercises,
+                serde_json::to_string(&session.strategies_covered).unwrap_or_else(|_| "[]".to_string()),
+                session.duration_seconds,
+                session.started_at,
+                session.finished_at,
+                session.performance_rating,
+                changed_at(&session.updated_at, &session.started_at),
+            ],
+        )?;
+        if existed {
+            report.updated += changed;
+        } else {
+            report.training_sessions += changed;
+        }
+    }
+    let mut touched_sessions = HashSet::new();
+    for result in &batch.exercise_results {
+        let session_id = match &result.session_uid {
+            Some(uid) => match local_id(&tx, "training_sessions", uid)? {
+                Some(id) => Some(id),
+                None => {
+                    report.orphans += 1;
+                    None
+                }
+            },
+            None => None,
+        };
+        let existed = local_id(&tx, "exercise_results", &result.uid)?.is_some();
+        let changed = tx.execute(
+            r#"
+            INSERT INTO exercise_results (uid, profile_id, session_id, exercise_type, difficulty, position_fen,
+                                          solved, attempts, time_seconds, hints_used, created_at, flagged, flag_reason,
+                                          alternative_solution, scaffolding, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+            ON CONFLICT(uid) DO UPDATE SET
+                solved = excluded.solved,
+                attempts = excluded.attempts,
+                time_seconds = excluded.time_seconds,
+                hints_used = excluded.hints_used,
+                flagged = excluded.flagged,
+                flag_reason = excluded.flag_reason,
+                alternative_solution = excluded.alternative_solution,
+                scaffolding = excluded.scaffolding,
+                updated_at = excluded.updated_at
+            WHERE excluded.updated_at > exercise_results.updated_at
+            "#,
+            params![
+                result.uid,
+                profile_id,
+                session_id,
+                result.exercise_type,
+                result.difficulty,
+                result.position_fen,
+                result.solved as i32,
+                result.attempts,
+                result.time_seconds,
+                result.hints_used,
+                result.created_at,
+                result.flagged as i32,
+                result.flag_reason,
+                result.alternative_solution as i32,
+                serde_json::to_string(&result.scaffolding).unwrap_or_else(|_| "[]".to_string()),
+                changed_at(&result.updated_at, &result.created_at),
+            ],
+        )?;
+        if changed > 0 {
+            touched_sessions.extend(session_id);
+        }
+        if existed {
+            report.updated += changed;
+        } else {
+            report.exercise_results += changed;
+        }
+    }
+    // Session totals and profile counters follow the rows actually added, so re-merging never double-counts
+    for session_id in touched_sessions {
+        repositories::refresh_training_session(&tx, session_id)?;
+    }
+    tx.execute(
+        "UPDATE profiles SET games_played = games_played + ?1, exercises_completed = exercises_completed + ?2 WHERE id = ?3",
+        params![report.games, report.exercise_results, profile_id],
+    )?;
+
+    tx.commit()?;
+    let received = batch.games.len()
+        + batch.conversations.len()
+        + batch.messages.len()
+        + batch.training_sessions.len()
+        + batch.exercise_results.len();
+    let added = report.games + report.conversations + report.messages + report.training_sessions + report.exercise_results;
+    report.duplicates = received.saturating_sub(added + report.updated + report.orphans);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{ExerciseResult, Game};
+    use crate::database::schema::create_tables;
+
+    fn device() -> (Connection, i64) {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        (conn, profile.id)
+    }
+
+    fn play(conn: &Connection, profile_id: i64) {
+        repositories::create_game(conn, &Game::for_test(profile_id, &["e2e4"])).unwrap();
+        let conversation = repositories::create_conversation(conn, profile_id, Some("Chat"), None).unwrap();
+        repositories::add_message(conn, conversation, "user", "Hi", None, None).unwrap();
+        let session = repositories::create_training_session(conn, profile_id, 1, &[]).unwrap();
+        repositories::record_exercise_result(
+            conn,
+            &ExerciseResult {
+                id: 0,
+                profile_id,
+                session_id: Some(session),
+                exercise_type: "Tactics".to_string(),
+                difficulty: "Beginner".to_string(),
+                position_fen: String::new(),
+                solved: true,
+                attempts: 1,
+                time_seconds: 10,
+                hints_used: 0,
+                created_at: String::new(),
+                flagged: false,
+                flag_reason: None,
+                alternative_solution: false,
+                scaffolding: vec![],
+            },
+        )
+        .unwrap();
+        repositories::refresh_training_session(conn, session).unwrap();
+    }
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_merging_is_idempotent_in_both_directions() {
+        let (laptop, laptop_profile) = device();
+        let (desktop, desktop_profile) = device();
+        play(&laptop, laptop_profile);
+        play(&desktop, desktop_profile);
+
+        let batch = export(&laptop, laptop_profile, None).unwrap();
+        let first = merge(&desktop, desktop_profile, &batch).unwrap();
+        assert_eq!((first.games, first.messages, first.exercise_results), (1, 1, 1));
+        assert_eq!((first.duplicates, first.orphans), (0, 0));
+
+        let again = merge(&desktop, desktop_profile, &batch).unwrap();
+        assert_eq!((again.games, again.messages, again.exercise_results), (0, 0, 0));
+        assert_eq!(again.duplicates, 5);
+
+        // The desktop now holds both devices' history; sending it all back adds only the desktop's own rows
+        let back = merge(&laptop, laptop_profile, &export(&desktop, desktop_profile, None).unwrap()).unwrap();
+        assert_eq!((back.games, back.messages, back.exercise_results, back.duplicates), (1, 1, 1, 5));
+        for table in ["games", "conversations", "messages", "training_sessions", "exercise_results"] {
+            assert_eq!(count(&laptop, table), 2, "{}", table);
+            assert_eq!(count(&desktop, table), 2, "{}", table);
+        }
+
+        let profile = repositories::get_first_profile(&desktop).unwrap().unwrap();
+        assert_eq!(profile.exercises_completed, 1);
+        let sessions = repositories::get_training_sessions(&desktop, desktop_profile, 10).unwrap();
+        assert!(sessions.iter().all(|s| s.completed_exercises == 1));
+    }
+
+    #[test]
+    fn test_export_after_watermark_sends_only_changes() {
+        let (conn, profile_id) = device();
+        play(&conn, profile_id);
+        let first = export(&conn, profile_id, None).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        // Past the first batch's last millisecond, which a later export sends again
+        let watermark = chrono::Utc::now().to_rfc3339();
+        assert!(watermark > first.watermark);
+
+        let conversation = conn.query_row("SELECT id FROM conversations", [], |row| row.get(0)).unwrap();
+        repositories::add_message(&conn, conversation, "assistant", "Hello", None, None).unwrap();
+        let result = conn.query_row("SELECT id FROM exercise_results", [], |row| row.get(0)).unwrap();
+        repositories::flag_exercise_results(&conn, &[result], "too fast").unwrap();
+
+        let batch = export(&conn, profile_id, Some(&watermark)).unwrap();
+        assert!(batch.games.is_empty());
+        assert_eq!(batch.messages.len(), 1);
+        assert_eq!(batch.conversations.len(), 1, "the parent travels with its new message");
+        assert_eq!(batch.exercise_results.len(), 1, "an edited attempt is sent again");
+        assert!(batch.exercise_results[0].flagged);
+        assert_eq!(batch.training_sessions.len(), 1);
+        assert!(batch.watermark > watermark);
+    }
+
+    #[test]
+    fn test_newer_copies_win_and_older_ones_are_ignored() {
+        let (laptop, laptop_profile) = device();
+        let (desktop, desktop_profile) = device();
+        play(&laptop, laptop_profile);
+        let first = export(&laptop, laptop_profile, None).unwrap();
+        merge(&desktop, desktop_profile, &first).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        let result = laptop.query_row("SELECT id FROM exercise_results", [], |row| row.get(0)).unwrap();
+        repositories::flag_exercise_results(&laptop, &[result], "too fast").unwrap();
+        laptop.execute("UPDATE training_sessions SET performance_rating = 1100", []).unwrap();
+
+        let report = merge(&desktop, desktop_profile, &export(&laptop, laptop_profile, Some(&first.watermark)).unwrap()).unwrap();
+        assert_eq!(report.updated, 2);
+        assert_eq!(report.exercise_results, 0);
+        // The stale first batch arriving late changes nothing back
+        assert_eq!(merge(&desktop, desktop_profile, &first).unwrap().updated, 0);
+
+        let flagged: bool = desktop.query_row("SELECT flagged FROM exercise_results", [], |row| row.get(0)).unwrap();
+        let rating: Option<i32> = desktop.query_row("SELECT performance_rating FROM training_sessions", [], |row| row.get(0)).unwrap();
+        assert!(flagged);
+        assert_eq!(rating, Some(1100));
+        assert_eq!(repositories::get_first_profile(&desktop).unwrap().unwrap().exercises_completed, 1);
+    }
+
+    #[test]
+    fn test_reviews_are_relinked_to_the_local_game() {
+        let (laptop, laptop_profile) = device();
+        let (desktop, desktop_profile) = device();
+        play(&desktop, desktop_profile);
+        play(&laptop, laptop_profile);
+        let game_id = laptop.query_row("SELECT id FROM games", [], |row| row.get(0)).unwrap();
+        let review = ConversationMode::GameReview { game_id }.to_context();
+        repositories::create_conversation(&laptop, laptop_profile, Some("Review"), Some(&review)).unwrap();
+
+        merge(&desktop, desktop_profile, &export(&laptop, laptop_profile, None).unwrap()).unwrap();
+
+        let (context, game_uid): (String, String) = desktop
+            .query_row(
+                "SELECT c.context, g.uid FROM conversations c JOIN games g ON g.id = json_extract(c.context, '$.game_id') \
+                 WHERE c.title = 'Review'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        let laptop_uid: String = laptop.query_row("SELECT uid FROM games", [], |row| row.get(0)).unwrap();
+        assert_eq!(game_uid, laptop_uid);
+        assert_ne!(ConversationMode::parse(Some(&context)), ConversationMode::GameReview { game_id });
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Moves a game is assumed to last when weighing the increment, as Lichess does
+const ESTIMATED_MOVES: u32 = 40;
+
+/// Estimated game length in seconds below which each class starts the next one
+const BULLET_BELOW: u32 = 180;
+const BLITZ_BELOW: u32 = 480;
+const RAPID_BELOW: u32 = 1500;
+
+/// How fast a game was played. Blitz blunders and classical planning problems
+/// call for different training, so stats keep the classes apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeClass {
+    Bullet,
+    Blitz,
+    Rapid,
+    Classical,
+}
+
+impl TimeClass {
+    pub const ALL: [TimeClass; 4] = [TimeClass::Bullet, TimeClass::Blitz, TimeClass::Rapid, TimeClass::Classical];
+
+    /// Stored in the games table
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimeClass::Bullet => "bullet",
+            TimeClass::Blitz => "blitz",
+            TimeClass::Rapid => "rapid",
+            TimeClass::Classical => "classical",
+        }
+    }
+
+    /// Class of a PGN-style time control, base seconds plus increment, e.g. "300+3" or "600".
+    /// `None` for untimed games ("-") and anything unreadable.
+    pub fn from_time_control(time_control: &str) -> Option<TimeClass> {
+        let (base, increment) = time_control.trim().split_once('+').unwrap_or((time_control.trim(), "0"));
+        let base: u32 = base.trim().parse().ok()?;
+        let increment: u32 = increment.trim().parse().ok()?;
+        let estimated = base.saturating_add(increment.saturating_mul(ESTIMATED_MOVES));
+        Some(match estimated {
+            s if s < BULLET_BELOW => TimeClass::Bullet,
+            s if s < BLITZ_BELOW => TimeClass::Blitz,
+            s if s < RAPID_BELOW => TimeClass::Rapid,
+            _ => TimeClass::Classical,
+        })
+    }
+}
+
+impl FromStr for TimeClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TimeClass::ALL
+            .into_iter()
+            .find(|class| class.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("Unknown time class: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classifies_time_controls() {
+        assert_eq!(TimeClass::from_time_control("60"), Some(TimeClass::Bullet));
+        // 2+1 lasts about 160 seconds, so it is still bullet
+        assert_eq!(TimeClass::from_time_control("120+1"), Some(TimeClass::Bullet));
+        assert_eq!(TimeClass::from_time_control("180+2"), Some(TimeClass::Blitz));
+        assert_eq!(TimeClass::from_time_control("600+0"), Some(TimeClass::Rapid));
+        assert_eq!(TimeClass::from_time_control("900+10"), Some(TimeClass::Rapid));
+        assert_eq!(TimeClass::from_time_control("1800+0"), Some(TimeClass::Classical));
+        assert_eq!(TimeClass::from_time_control("-"), None);
+        assert_eq!(TimeClass::from_time_control("40/7200"), None);
+        assert_eq!("Blitz".parse::<TimeClass>(), Ok(TimeClass::Blitz));
+        assert!("armageddon".parse::<TimeClass>().is_err());
+    }
+}
@@ -0,0 +1,373 @@
+use chess_core::parse_fen;
+use chess_trainer::ExerciseType;
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::coach_actions::{self, ActionTarget, CoachAction};
+use crate::database::repositories::{self, Flashcard, Game, PendingCoachAction, TrainingPlanItem};
+use crate::DB;
+
+pub const TRAINING: &str = "training";
+pub const GAME_REVIEW: &str = "game_review";
+
+/// Furthest ahead the coach may schedule anything
+pub const MAX_SCHEDULE_DAYS: i64 = 90;
+
+/// Longest title, note or flashcard side taken from the coach
+pub const MAX_TEXT_LEN: usize = 500;
+
+/// Proposals that can wait on the player at once, so a runaway reply can't bury them in prompts
+pub const MAX_PENDING: usize = 10;
+
+/// A change to the training plan or flashcards. The coach only ever proposes
+/// these; nothing is written until the player approves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlanChange {
+    AddTraining {
+        /// Exercise type name, canonical once validated
+        theme: String,
+        #[serde(default)]
+        title: Option<String>,
+        /// `YYYY-MM-DD`
+        #[serde(default)]
+        due_date: Option<String>,
+        #[serde(default)]
+        note: Option<String>,
+    },
+    ScheduleGameReview {
+        game_id: i64,
+        /// Filled in from the game when validated
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        due_date: Option<String>,
+        #[serde(default)]
+        note: Option<String>,
+    },
+    CreateFlashcard {
+        front: String,
+        back: String,
+        #[serde(default)]
+        fen: Option<String>,
+    },
+}
+
+impl PlanChange {
+    /// One line for the approval prompt
+    pub fn summary(&self) -> String {
+        match self {
+            PlanChange::AddTraining { theme, title, due_date, .. } => match title {
+                Some(title) => format!("Add \"{}\" ({} training) to your plan{}", title, theme, due_suffix(due_date)),
+                None => format!("Add {} training to your plan{}", theme, due_suffix(due_date)),
+            },
+            PlanChange::ScheduleGameReview { game_id, title, due_date, .. } => {
+                let title = title.clone().unwrap_or_else(|| format!("Review game #{}", game_id));
+                format!("Schedule \"{}\"{}", title, due_suffix(due_date))
+            }
+            PlanChange::CreateFlashcard { front, .. } => format!("Create a flashcard: \"{}\"", front),
+        }
+    }
+}
+
+fn due_suffix(due_date: &Option<String>) -> String {
+    due_date.as_ref().map(|d| format!(" for {}", d)).unwrap_or_default()
+}
+
+/// Trimmed text, none when blank
+fn clean_text(field: &str, text: Option<String>) -> Result<Option<String>, String> {
+    let Some(text) = text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) else {
+        return Ok(None);
+    };
+    if text.chars().count() > MAX_TEXT_LEN {
+        return Err(format!("The {} is longer than {} characters", field, MAX_TEXT_LEN));
+    }
+    Ok(Some(text))
+}
+
+fn check_due_date(due_date: Option<String>, today: NaiveDate) -> Result<Option<String>, String> {
+    let Some(due_date) = due_date.map(|d| d.trim().to_string()).filter(|d| !d.is_empty()) else {
+        return Ok(None);
+    };
+    let date = NaiveDate::parse_from_str(&due_date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid due date '{}', expected YYYY-MM-DD", due_date))?;
+    if date < today {
+        return Err(format!("Due date {} is in the past; today is {}", due_date, today));
+    }
+    if (date - today).num_days() > MAX_SCHEDULE_DAYS {
+        return Err(format!("Due date {} is more than {} days away", due_date, MAX_SCHEDULE_DAYS));
+    }
+    Ok(Some(date.format("%Y-%m-%d").to_string()))
+}
+
+/// Check a change as the model wrote it and normalise it. `game` is the
+/// profile's game a review refers to, if there is one.
+pub fn validate(change: PlanChange, today: NaiveDate, game: Option<&Game>) -> Result<PlanChange, String> {
+    match change {
+        PlanChange::AddTraining { theme, title, due_date, note } => {
+            let theme: ExerciseType = theme.parse().map_err(|e: chess_trainer::ParseNameError| e.to_string())?;
+            Ok(PlanChange::AddTraining {
+                theme: theme.as_str().to_string(),
+                title: clean_text("title", title)?,
+                due_date: check_due_date(due_date, today)?,
+                note: clean_text("note", note)?,
+            })
+        }
+        PlanChange::ScheduleGameReview { game_id, due_date, note, .. } => {
+            let game = game.filter(|g| g.id == game_id).ok_or_else(|| format!("No saved game {}", game_id))?;
+            let title = match &game.opening_name {
+                Some(opening) => format!("Review game #{}: {}", game.id, opening),
+                None => format!("Review game #{}", game.id),
+            };
+            Ok(PlanChange::ScheduleGameReview {
+                game_id,
+                title: Some(title),
+                due_date: check_due_date(due_date, today)?,
+                note: clean_text("note", note)?,
+            })
+        }
+        PlanChange::CreateFlashcard { front, back, fen } => {
+            let front = clean_text("question", Some(front))?.ok_or_else(|| "A flashcard needs a question".to_string())?;
+            let back = clean_text("answer", Some(back))?.ok_or_else(|| "A flashcard needs an answer".to_string())?;
+            let fen = match fen.map(|f| f.trim().to_string()).filter(|f| !f.is_empty()) {
+                Some(fen) => Some(format!("{}", parse_fen(&fen).map_err(|e| format!("Invalid FEN: {}", e))?)),
+                None => None,
+            };
+            Ok(PlanChange::CreateFlashcard { front, back, fen })
+        }
+    }
+}
+
+/// Write an approved change, returning the new plan item or flashcard id
+pub fn apply(conn: &Connection, profile_id: i64, change: &PlanChange) -> rusqlite::Result<i64> {
+    let item = |kind: &str,
+                title: String,
+                theme: Option<String>,
+                game_id: Option<i64>,
+                due_date: Option<String>,
+                note: Option<String>| TrainingPlanItem {
+        id: 0,
+        profile_id,
+        kind: kind.to_string(),
+        title,
+        theme,
+        game_id,
+        note,
+        due_date,
+        source: "coach".to_string(),
+        completed_at: None,
+        created_at: String::new(),
+    };
+    match change.clone() {
+        PlanChange::AddTraining { theme, title, due_date, note } => {
+            let title = title.unwrap_or_else(|| format!("{} training", theme));
+            repositories::insert_training_plan_item(conn, &item(TRAINING, title, Some(theme), None, due_date, note))
+        }
+        PlanChange::ScheduleGameReview { game_id, title, due_date, note } => {
+            let title = title.unwrap_or_else(|| format!("Review game #{}", game_id));
+            repositories::insert_training_plan_item(conn, &item(GAME_REVIEW, title, None, Some(game_id), due_date, note))
+        }
+        PlanChange::CreateFlashcard { front, back, fen } => repositories::insert_flashcard(
+            conn,
+            &Flashcard {
+                id: 0,
+                profile_id,
+                front,
+                back,
+                fen,
+                source: "coach".to_string(),
+                created_at: String::new(),
+            },
+        ),
+    }
+}
+
+fn active_profile_id() -> Result<i64, String> {
+    DB.with_conn(repositories::get_first_profile)
+        .map_err(|e| format!("Database error: {}", e))?
+        .map(|p| p.id)
+        .ok_or_else(|| "No user profile found".to_string())
+}
+
+/// Hold a change the coach proposed until the player approves or declines it
+pub fn propose(conversation_id: Option<i64>, change: PlanChange) -> Result<PendingCoachAction, String> {
+    let profile_id = active_profile_id()?;
+    let waiting = DB
+        .with_conn(|conn| repositories::get_pending_coach_actions(conn, profile_id, None))
+        .map_err(|e| format!("Database error: {}", e))?;
+    if waiting.len() >= MAX_PENDING {
+        return Err(format!(
+            "{} proposed changes are already waiting for the player; ask them to approve or decline those first",
+            waiting.len()
+        ));
+    }
+
+    let game = match &change {
+        PlanChange::ScheduleGameReview { game_id, .. } => DB
+            .with_conn(|conn| repositories::get_game_by_id(conn, *game_id))
+            .map_err(|e| format!("Failed to load game: {}", e))?
+            .filter(|g| g.profile_id == profile_id),
+        _ => None,
+    };
+    let change = validate(change, chrono::Local::now().date_naive(), game.as_ref())?;
+    let json = serde_json::to_string(&change).map_err(|e| e.to_string())?;
+
+    DB.with_conn(|conn| {
+        let id = repositories::insert_pending_coach_action(conn, profile_id, conversation_id, &json, &change.summary())?;
+        repositories::get_pending_coach_action(conn, id)
+    })
+    .map_err(|e| format!("Failed to store proposed change: {}", e))?
+    .ok_or_else(|| "Failed to store proposed change".to_string())
+}
+
+/// Settle a pending change; approving it writes it to the plan or flashcards
+pub fn resolve(id: i64, approve: bool) -> Result<PendingCoachAction, String> {
+    let profile_id = active_profile_id()?;
+    let pending = DB
+        .with_conn(|conn| repositories::get_pending_coach_action(conn, id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .filter(|a| a.profile_id == profile_id)
+        .ok_or_else(|| format!("No proposed change {}", id))?;
+    if pending.status != "pending" {
+        return Err(format!("This change was already {}", pending.status));
+    }
+    let change: PlanChange =
+        serde_json::from_str(&pending.change).map_err(|e| format!("Unreadable proposed change: {}", e))?;
+
+    let settled = DB
+        .with_conn(|conn| settle(conn, profile_id, id, &change, approve))
+        .map_err(|e| format!("Failed to update proposed change: {}", e))?;
+    let pending = DB
+        .with_conn(|conn| repositories::get_pending_coach_action(conn, id))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("No proposed change {}", id))?;
+    if !settled {
+        return Err(format!("This change was already {}", pending.status));
+    }
+    Ok(pending)
+}
+
+/// Mark pending change `id` approved or declined and, when approving, apply it, in one
+/// transaction. The status is claimed first, so of two approvals racing each other only
+/// the one that moved it off "pending" applies the change. False if it was already settled.
+fn settle(conn: &Connection, profile_id: i64, id: i64, change: &PlanChange, approve: bool) -> rusqlite::Result<bool> {
+    let tx = conn.unchecked_transaction()?;
+    let claimed = repositories::resolve_pending_coach_action(&tx, id, if approve { "approved" } else { "declined" })?;
+    if claimed && approve {
+        apply(&tx, profile_id, change)?;
+    }
+    tx.commit()?;
+    Ok(claimed)
+}
+
+/// A plan item with the button that starts it
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanEntry {
+    #[serde(flatten)]
+    pub item: TrainingPlanItem,
+    /// None when a reviewed game has since been deleted
+    pub action: Option<CoachAction>,
+}
+
+/// Start a training item's theme, or open a review item's game
+pub fn item_action(conn: &Connection, item: &TrainingPlanItem) -> rusqlite::Result<Option<CoachAction>> {
+    match (item.kind.as_str(), &item.theme, item.game_id) {
+        (TRAINING, Some(theme), _) => Ok(Some(CoachAction::new(
+            format!("Train {}", theme),
+            ActionTarget::StartTraining { theme: Some(theme.clone()) },
+        ))),
+        (GAME_REVIEW, _, Some(game_id)) => Ok(repositories::get_game_by_id(conn, game_id)?
+            .filter(|g| g.profile_id == item.profile_id)
+            .and_then(|game| coach_actions::validate_game(&game, 0).ok())),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_validate_and_apply() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let training = validate(
+            PlanChange::AddTraining {
+                theme: "endgame".to_string(),
+                title: Some("  ".to_string()),
+                due_date: Some("2026-03-12".to_string()),
+                note: Some(" Rook endings ".to_string()),
+            },
+            today,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            training,
+            PlanChange::AddTraining {
+                theme: "Endgame".to_string(),
+                title: None,
+                due_date: Some("2026-03-12".to_string()),
+                note: Some("Rook endings".to_string()),
+            }
+        );
+        assert_eq!(training.summary(), "Add Endgame training to your plan for 2026-03-12");
+
+        let late = |due: &str| PlanChange::AddTraining {
+            theme: "Tactics".to_string(),
+            title: None,
+            due_date: Some(due.to_string()),
+            note: None,
+        };
+        assert!(validate(late("2026-03-09"), today, None).unwrap_err().contains("past"));
+        assert!(validate(late("2026-12-01"), today, None).unwrap_err().contains("90 days"));
+        assert!(validate(late("next week"), today, None).is_err());
+
+        let review = PlanChange::ScheduleGameReview { game_id: 4, title: None, due_date: None, note: None };
+        assert_eq!(validate(review, today, None).unwrap_err(), "No saved game 4");
+
+        let card = PlanChange::CreateFlashcard {
+            front: "What wins here?".to_string(),
+            back: "Rd8+".to_string(),
+            fen: Some("3q4/8/8/8/8/8/k7/3RK3 w - - 0 1".to_string()),
+        };
+        assert!(validate(card.clone(), today, None).is_ok());
+        let bad_card = PlanChange::CreateFlashcard { front: "?".to_string(), back: " ".to_string(), fen: None };
+        assert!(validate(bad_card, today, None).is_err());
+
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        apply(&conn, profile.id, &training).unwrap();
+        apply(&conn, profile.id, &late("2026-03-11")).unwrap();
+        apply(&conn, profile.id, &card).unwrap();
+
+        let plan = repositories::get_training_plan(&conn, profile.id, false).unwrap();
+        let titles: Vec<&str> = plan.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["Tactics training", "Endgame training"]);
+        let action = item_action(&conn, &plan[0]).unwrap().unwrap();
+        assert_eq!(action.target, ActionTarget::StartTraining { theme: Some("Tactics".to_string()) });
+
+        assert!(repositories::complete_training_plan_item(&conn, profile.id, plan[0].id).unwrap());
+        assert_eq!(repositories::get_training_plan(&conn, profile.id, false).unwrap().len(), 1);
+        assert_eq!(repositories::get_training_plan(&conn, profile.id, true).unwrap().len(), 2);
+        assert_eq!(repositories::get_flashcards(&conn, profile.id).unwrap()[0].back, "Rd8+");
+    }
+
+    #[test]
+    fn test_a_change_is_applied_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let profile = repositories::create_profile(&conn, "Ana", "beginner", 900).unwrap();
+        let card = PlanChange::CreateFlashcard { front: "What wins here?".to_string(), back: "Rd8+".to_string(), fen: None };
+        let json = serde_json::to_string(&card).unwrap();
+        let id = repositories::insert_pending_coach_action(&conn, profile.id, None, &json, &card.summary()).unwrap();
+
+        assert!(settle(&conn, profile.id, id, &card, true).unwrap());
+        assert!(!settle(&conn, profile.id, id, &card, true).unwrap());
+        assert!(!settle(&conn, profile.id, id, &card, false).unwrap());
+        assert_eq!(repositories::get_flashcards(&conn, profile.id).unwrap().len(), 1);
+        assert_eq!(repositories::get_pending_coach_action(&conn, id).unwrap().unwrap().status, "approved");
+    }
+}
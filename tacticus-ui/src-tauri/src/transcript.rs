@@ -0,0 +1,141 @@
+use chess::Color;
+use chess_core::{board_to_svg_styled, extract_fens, parse_fen, DiagramStyle};
+use std::str::FromStr;
+
+use crate::database::repositories::{Conversation, Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            other => Err(format!("Unknown export format: {}", other)),
+        }
+    }
+}
+
+fn speaker(role: &str) -> &'static str {
+    match role {
+        "user" => "You",
+        "system" => "Context",
+        _ => "Gurgeh",
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Diagrams for every position a message mentions, with the FEN as caption
+fn diagrams(content: &str, style: &DiagramStyle) -> Vec<(String, String)> {
+    extract_fens(content)
+        .into_iter()
+        .filter_map(|fen| {
+            let board = parse_fen(&fen).ok()?;
+            Some((fen, board_to_svg_styled(&board, Color::White, style)))
+        })
+        .collect()
+}
+
+/// Readable transcript of a coach conversation. Positions given as FEN are drawn
+/// as inline SVG diagrams after the message that mentions them, drawn in `style`.
+pub fn render_transcript(conversation: &Conversation, messages: &[Message], format: ExportFormat, style: &DiagramStyle) -> String {
+    let title = conversation.title.as_deref().unwrap_or("Conversation with Gurgeh");
+    let date = conversation.created_at.get(..10).unwrap_or(&conversation.created_at);
+
+    match format {
+        ExportFormat::Markdown => {
+            let mut out = format!("# {}\n\n_{}_\n", title, date);
+            for message in messages {
+                out.push_str(&format!("\n### {}\n\n{}\n", speaker(&message.role), message.content.trim()));
+                for (fen, svg) in diagrams(&message.content, style) {
+                    out.push_str(&format!("\n{}\n\n`{}`\n", svg, fen));
+                }
+            }
+            out
+        }
+        ExportFormat::Html => {
+            let mut out = format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+                 <style>body {{ font-family: sans-serif; max-width: 720px; margin: 2em auto; }} \
+                 .message {{ white-space: pre-wrap; }} figure {{ margin: 1em 0; }}</style>\n\
+                 </head>\n<body>\n<h1>{title}</h1>\n<p><em>{date}</em></p>\n",
+                title = escape_html(title),
+                date = escape_html(date),
+            );
+            for message in messages {
+                out.push_str(&format!(
+                    "<h3>{}</h3>\n<div class=\"message\">{}</div>\n",
+                    speaker(&message.role),
+                    escape_html(message.content.trim())
+                ));
+                for (fen, svg) in diagrams(&message.content, style) {
+                    out.push_str(&format!("<figure>{}<figcaption><code>{}</code></figcaption></figure>\n", svg, escape_html(&fen)));
+                }
+            }
+            out.push_str("</body>\n</html>\n");
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            id: 0,
+            conversation_id: 1,
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_results: None,
+            created_at: "2024-06-01T10:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_markdown_and_html() {
+        let conversation = Conversation {
+            id: 1,
+            profile_id: 1,
+            title: Some("Pins & skewers".to_string()),
+            context: None,
+            created_at: "2024-06-01T10:00:00Z".to_string(),
+            updated_at: "2024-06-01T10:05:00Z".to_string(),
+        };
+        let messages = vec![message("user", "What is a pin?"), message("assistant", "A pin <attacks> through a piece.")];
+
+        let markdown = render_transcript(&conversation, &messages, ExportFormat::Markdown, &DiagramStyle::default());
+        assert!(markdown.starts_with("# Pins & skewers\n\n_2024-06-01_\n"));
+        assert!(markdown.contains("### You\n\nWhat is a pin?"));
+        assert!(markdown.contains("### Gurgeh\n\nA pin <attacks> through a piece."));
+
+        let html = render_transcript(&conversation, &messages, ExportFormat::Html, &DiagramStyle::default());
+        assert!(html.contains("<h1>Pins &amp; skewers</h1>"));
+        assert!(html.contains("A pin &lt;attacks&gt; through a piece."));
+        assert_eq!("md".parse::<ExportFormat>(), Ok(ExportFormat::Markdown));
+        assert!("pdf".parse::<ExportFormat>().is_err());
+    }
+}
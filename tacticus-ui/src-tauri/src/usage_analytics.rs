@@ -0,0 +1,130 @@
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{Connection, Result};
+
+use crate::database::repositories::{self, DailyUsage, FeatureUsage};
+use crate::DB;
+
+/// Usage analytics are off until the user opts in. Counts never leave this machine.
+const USAGE_ANALYTICS_SETTING_KEY: &str = "usage_analytics";
+
+pub const PUZZLE_ATTEMPTED: &str = "puzzle_attempted";
+pub const COACH_MESSAGE: &str = "coach_message";
+pub const ANALYSIS_RUN: &str = "analysis_run";
+pub const GAME_PLAYED: &str = "game_played";
+pub const QUIZ_ANSWERED: &str = "quiz_answered";
+
+pub fn is_enabled(conn: &Connection) -> Result<bool> {
+    Ok(repositories::get_setting(conn, USAGE_ANALYTICS_SETTING_KEY)?
+        .map(|v| v == "true")
+        .unwrap_or(false))
+}
+
+/// Opting out also deletes everything recorded so far
+pub fn set_enabled(conn: &Connection, enabled: bool) -> Result<()> {
+    repositories::set_setting(conn, USAGE_ANALYTICS_SETTING_KEY, if enabled { "true" } else { "false" })?;
+    if !enabled {
+        repositories::clear_usage(conn)?;
+    }
+    Ok(())
+}
+
+/// Count one use of `feature` today, if the user has opted in
+pub fn record(conn: &Connection, feature: &str) -> Result<()> {
+    if !is_enabled(conn)? {
+        return Ok(());
+    }
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    repositories::increment_usage(conn, feature, &today)
+}
+
+/// `record` against the app database. Failures are logged, never surfaced to the feature being used.
+pub fn record_usage(feature: &str) {
+    if let Err(e) = DB.with_conn(|conn| record(conn, feature)) {
+        tracing::warn!(feature, "Failed to record usage: {}", e);
+    }
+}
+
+/// Plain-language observations about training habits
+pub fn habit_insights(features: &[FeatureUsage], daily: &[DailyUsage], period_days: Option<i32>) -> Vec<String> {
+    let mut insights = Vec::new();
+    if daily.is_empty() {
+        return insights;
+    }
+
+    let active_days = daily.len();
+    match period_days {
+        Some(days) => insights.push(format!("You trained on {} of the last {} days", active_days, days)),
+        None => insights.push(format!("You have trained on {} different days", active_days)),
+    }
+
+    let mut by_weekday = [0i64; 7];
+    for entry in daily {
+        if let Ok(date) = NaiveDate::parse_from_str(&entry.day, "%Y-%m-%d") {
+            by_weekday[date.weekday().num_days_from_monday() as usize] += entry.total;
+        }
+    }
+    const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+    if let Some((index, _)) = by_weekday.iter().enumerate().filter(|(_, n)| **n > 0).max_by_key(|(_, n)| **n) {
+        insights.push(format!("{} is your busiest day", WEEKDAYS[index]));
+    }
+
+    let count = |feature: &str| features.iter().find(|f| f.feature == feature).map_or(0, |f| f.total);
+    let (puzzles, games) = (count(PUZZLE_ATTEMPTED), count(GAME_PLAYED));
+    let (analyses, coach) = (count(ANALYSIS_RUN), count(COACH_MESSAGE));
+
+    if games > 0 && analyses == 0 {
+        insights.push("You play games but never analyse them - reviewing mistakes is where most improvement comes from".to_string());
+    }
+    if puzzles > 0 && games == 0 {
+        insights.push("You solve puzzles but haven't played a game - try using those tactics over the board".to_string());
+    } else if games > 0 && puzzles == 0 {
+        insights.push("You haven't tried any puzzles - a few a day sharpens tactical vision".to_string());
+    }
+    if coach > 0 && coach > puzzles + games {
+        insights.push("You ask Gurgeh a lot - great, but make sure to practise what you learn".to_string());
+    }
+
+    insights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_records_only_after_opt_in() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+
+        record(&conn, PUZZLE_ATTEMPTED).unwrap();
+        assert!(repositories::get_feature_usage(&conn, None).unwrap().is_empty());
+
+        set_enabled(&conn, true).unwrap();
+        record(&conn, PUZZLE_ATTEMPTED).unwrap();
+        record(&conn, PUZZLE_ATTEMPTED).unwrap();
+        record(&conn, COACH_MESSAGE).unwrap();
+        let usage = repositories::get_feature_usage(&conn, Some(7)).unwrap();
+        assert_eq!(usage[0].feature, PUZZLE_ATTEMPTED);
+        assert_eq!(usage[0].total, 2);
+        assert_eq!(usage[0].active_days, 1);
+
+        set_enabled(&conn, false).unwrap();
+        assert!(repositories::get_feature_usage(&conn, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_habit_insights() {
+        let features = vec![FeatureUsage { feature: GAME_PLAYED.to_string(), total: 4, active_days: 2 }];
+        let daily = vec![
+            DailyUsage { day: "2024-06-03".to_string(), total: 1 },
+            DailyUsage { day: "2024-06-08".to_string(), total: 3 },
+        ];
+
+        let insights = habit_insights(&features, &daily, Some(7));
+        assert_eq!(insights[0], "You trained on 2 of the last 7 days");
+        assert_eq!(insights[1], "Saturday is your busiest day");
+        assert!(insights.iter().any(|i| i.contains("never analyse")));
+        assert!(insights.iter().any(|i| i.contains("puzzles")));
+    }
+}
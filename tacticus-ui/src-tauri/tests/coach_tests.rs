@@ -0,0 +1,165 @@
+//! Integration tests for the coach against the mock LLM server
+//!
+//! Every test scripts the mock's replies, so the backend flow (model routing, tool
+//! calls and fallbacks) runs the same way each time, with no key or network. The
+//! streaming test checks only the mock's event stream; the frontend's parsing of it
+//! in agent.ts is not covered here.
+
+#![cfg(feature = "llm")]
+
+use serde_json::{json, Value};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use tacticus_ui_lib::chat_with_coach;
+use tacticus_ui_lib::coach_actions::ActionTarget;
+use tacticus_ui_lib::mock_llm::{self, MockLlm, MockReply, MOCK_API_KEY};
+
+/// The mock, with the app's coach and a scratch database pointed at it. Tests share
+/// one mock, so each holds the guard while it scripts and checks it.
+fn mock() -> (MutexGuard<'static, ()>, &'static MockLlm) {
+    static MOCK: OnceLock<MockLlm> = OnceLock::new();
+    static SERIAL: Mutex<()> = Mutex::new(());
+    let guard = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let mock = MOCK.get_or_init(|| {
+        let db_path = std::env::temp_dir().join(format!("tacticus-coach-tests-{}.db", std::process::id()));
+        std::env::set_var("TACTICUS_DB_PATH", &db_path);
+        mock_llm::install().expect("mock LLM starts")
+    });
+    mock.reset();
+    (guard, mock)
+}
+
+fn messages(request: &Value) -> &Vec<Value> {
+    request["messages"].as_array().expect("request has messages")
+}
+
+fn lookup(name: &str) -> MockReply {
+    MockReply::ToolCall { name: "lookup_concept".to_string(), arguments: json!({ "name": name }) }
+}
+
+fn opens_concept(concept_id: &str) -> ActionTarget {
+    ActionTarget::OpenConcept { concept_id: concept_id.to_string() }
+}
+
+#[tokio::test]
+async fn test_tool_results_are_sent_back_before_the_answer() {
+    let (_guard, mock) = mock();
+    mock.push(lookup("pin"));
+    mock.push(MockReply::Text("A pinned piece shields a more valuable one behind it.".to_string()));
+
+    let response = chat_with_coach("What is a pin?".to_string(), None, None, None).await.unwrap();
+    assert_eq!(response.message.content, "A pinned piece shields a more valuable one behind it.");
+    assert!(response.message.actions.iter().any(|a| a.target == opens_concept("pin")));
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(messages(&requests[0]).last().unwrap()["content"], "What is a pin?");
+    assert!(requests[0]["tools"].as_array().is_some_and(|tools| !tools.is_empty()));
+
+    let sent_back = messages(&requests[1]);
+    let call = sent_back.iter().find(|m| m["role"] == "assistant").expect("tool call echoed");
+    assert_eq!(call["tool_calls"][0]["function"]["name"], "lookup_concept");
+    let result = sent_back.iter().find(|m| m["role"] == "tool").expect("tool result sent");
+    assert_eq!(result["tool_call_id"], call["tool_calls"][0]["id"]);
+    let concept: Value = serde_json::from_str(result["content"].as_str().unwrap()).unwrap();
+    assert_eq!(concept["id"], "pin");
+}
+
+#[tokio::test]
+async fn test_tool_rounds_are_capped() {
+    let (_guard, mock) = mock();
+    for _ in 0..3 {
+        mock.push(lookup("fork"));
+    }
+    mock.push(MockReply::Text("Enough research; a fork attacks two pieces at once.".to_string()));
+
+    let response = chat_with_coach("Tell me about forks".to_string(), None, None, None).await.unwrap();
+    assert_eq!(response.message.content, "Enough research; a fork attacks two pieces at once.");
+
+    // The last round offers no tools, so the model has to answer
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(requests[..3].iter().all(|r| r.get("tools").is_some()));
+    assert!(requests[3].get("tools").is_none());
+    assert_eq!(messages(&requests[3]).iter().filter(|m| m["role"] == "tool").count(), 3);
+}
+
+#[tokio::test]
+async fn test_failed_models_fall_back_to_the_next() {
+    let (_guard, mock) = mock();
+    mock.push(MockReply::Error(503));
+    mock.push(MockReply::Text("The fallback model answered.".to_string()));
+
+    let response = chat_with_coach("Hello".to_string(), None, None, None).await.unwrap();
+    assert_eq!(response.message.content, "The fallback model answered.");
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    assert_ne!(requests[0]["model"], requests[1]["model"]);
+    assert_eq!(messages(&requests[0]), messages(&requests[1]));
+}
+
+#[tokio::test]
+async fn test_every_model_failing_is_an_error() {
+    let (_guard, mock) = mock();
+    for _ in 0..8 {
+        mock.push(MockReply::Error(500));
+    }
+
+    let error = chat_with_coach("Hello".to_string(), None, None, None).await.unwrap_err();
+    assert!(error.contains("500"), "{}", error);
+    assert!(mock.requests().len() >= 2);
+}
+
+#[tokio::test]
+async fn test_demo_replies_walk_through_a_lookup() {
+    let (_guard, mock) = mock();
+
+    let response = chat_with_coach("What is a knight fork?".to_string(), None, None, None).await.unwrap();
+    assert!(response.message.content.starts_with("Here is the library on Knight Fork:"), "{}", response.message.content);
+    assert!(response.message.actions.iter().any(|a| a.target == opens_concept("knight_fork")));
+    assert_eq!(mock.requests().len(), 2);
+}
+
+/// Data lines of an event stream up to `[DONE]`, parsed
+async fn stream(mock: &MockLlm, prompt: &str) -> (Vec<Value>, String) {
+    let body = reqwest::Client::new()
+        .post(format!("{}/chat/completions", mock.base_url()))
+        .bearer_auth(MOCK_API_KEY)
+        .json(&json!({ "model": "mock", "messages": [{ "role": "user", "content": prompt }], "stream": true }))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let chunks = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .take_while(|data| *data != "[DONE]")
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+    (chunks, body)
+}
+
+#[tokio::test]
+async fn test_mock_streams_replies_in_pieces() {
+    let (_guard, mock) = mock();
+    mock.push(MockReply::Text("Develop your knights before your bishops.".to_string()));
+    mock.push(lookup("skewer"));
+
+    let (chunks, body) = stream(mock, "How should I start?").await;
+    assert!(chunks.len() > 2);
+    let text: String = chunks.iter().filter_map(|c| c["choices"][0]["delta"]["content"].as_str()).collect();
+    assert_eq!(text, "Develop your knights before your bishops.");
+    assert_eq!(chunks.last().unwrap()["choices"][0]["finish_reason"], "stop");
+    assert!(body.ends_with("data: [DONE]\n\n"));
+
+    // Tool calls stream their name first, then the arguments in pieces to be joined
+    let (chunks, _) = stream(mock, "And skewers?").await;
+    let calls: Vec<&Value> = chunks.iter().filter_map(|c| c["choices"][0]["delta"]["tool_calls"].get(0)).collect();
+    assert_eq!(calls[0]["function"]["name"], "lookup_concept");
+    assert!(calls[0]["id"].is_string());
+    let arguments: String = calls.iter().filter_map(|c| c["function"]["arguments"].as_str()).collect();
+    assert_eq!(serde_json::from_str::<Value>(&arguments).unwrap(), json!({ "name": "skewer" }));
+    assert_eq!(chunks.last().unwrap()["choices"][0]["finish_reason"], "tool_calls");
+}
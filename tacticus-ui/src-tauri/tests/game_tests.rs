@@ -79,3 +79,14 @@ fn test_stalemate_detection() {
     // A true stalemate is hard to construct simply
     assert!(board.side_to_move() == chess::Color::Black);
 }
+
+#[test]
+fn test_malformed_input_is_rejected() {
+    // Sloppy or hostile input from the frontend must come back as an error, never a panic
+    for fen in ["", "not a fen", "8/8/8/8/8/8/8/8 w - - 0 1", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 extra"] {
+        assert!(chess_core::parse_fen(fen).is_err(), "accepted {:?}", fen);
+    }
+    for uci in ["", "e", "e2e4e4", "z9z9", "\u{00e9}\u{00e9}"] {
+        assert!(chess_core::parse_uci(uci).is_err(), "accepted {:?}", uci);
+    }
+}
@@ -0,0 +1,35 @@
+//! Integration tests for the bundled model games library
+
+use chess::{Board, ChessMove};
+use std::str::FromStr;
+
+const MODEL_GAMES_JSON: &str = include_str!("../assets/model_games.json");
+
+fn load_games() -> Vec<serde_json::Value> {
+    serde_json::from_str(MODEL_GAMES_JSON).expect("model_games.json should parse")
+}
+
+#[test]
+fn test_model_games_not_empty() {
+    let games = load_games();
+    assert!(!games.is_empty());
+
+    for game in &games {
+        assert!(!game["concepts"].as_array().unwrap().is_empty(),
+            "Model game '{}' is not linked to any concept", game["id"]);
+    }
+}
+
+#[test]
+fn test_model_game_moves_are_legal() {
+    for game in load_games() {
+        let mut board = Board::from_str(game["initial_fen"].as_str().unwrap()).unwrap();
+
+        for m in game["moves"].as_array().unwrap() {
+            let uci = m["uci"].as_str().unwrap();
+            let chess_move = ChessMove::from_str(uci).unwrap();
+            assert!(board.legal(chess_move), "Illegal move {} in '{}'", uci, game["id"]);
+            board = board.make_move_new(chess_move);
+        }
+    }
+}
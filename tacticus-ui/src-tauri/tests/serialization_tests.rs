@@ -0,0 +1,217 @@
+//! Integration tests for serialized models
+//!
+//! Each file in `tests/golden` pins the JSON a model is written as. A model has
+//! to load its golden file and write it back unchanged, so a refactor that
+//! changes the format fails here instead of breaking saves from older versions.
+//! After a deliberate, additive format change, rerun with `UPDATE_GOLDEN=1` to
+//! rewrite the files, and add a compat test for the old shape.
+
+use chess::{Board, Color};
+use chess_core::{ChessGame, GamePhase, GameState, MoveHistory, MoveQuality};
+use chess_engine::{MoveAnalysis, Sacrifice, TacticalPattern};
+use chess_trainer::{ExerciseDifficulty, ExerciseType, StrategyPattern, TrainingSession};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::str::FromStr;
+use tacticus_ui_lib::coach_actions::ActionTarget;
+use tacticus_ui_lib::database::repositories::Profile;
+use tacticus_ui_lib::CoachResponse;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.json", name))
+}
+
+fn golden_value(name: &str) -> Value {
+    let path = golden_path(name);
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+    serde_json::from_str(&text).unwrap_or_else(|e| panic!("{} is not valid JSON: {}", path.display(), e))
+}
+
+/// Load a golden file and check the model writes it back unchanged
+fn round_trip<T: Serialize + DeserializeOwned>(name: &str) -> T {
+    let golden = golden_value(name);
+    let parsed: T =
+        serde_json::from_value(golden.clone()).unwrap_or_else(|e| panic!("{}.json no longer loads: {}", name, e));
+    let written = serde_json::to_value(&parsed).unwrap();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let pretty = serde_json::to_string_pretty(&written).unwrap();
+        std::fs::write(golden_path(name), pretty + "\n").unwrap();
+    } else {
+        assert_eq!(
+            written, golden,
+            "{}.json is now written differently; rerun with UPDATE_GOLDEN=1 if the change is intended",
+            name
+        );
+    }
+    parsed
+}
+
+#[test]
+fn test_chess_game_golden() {
+    let game: ChessGame = round_trip("chess_game");
+
+    assert_eq!(game.id, Some(42));
+    assert_eq!(game.state, GameState::InProgress);
+    assert_eq!(game.player_color, Color::White);
+    assert_eq!(
+        game.board,
+        Board::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 1").unwrap()
+    );
+    assert_eq!(game.move_history.len(), 4);
+    let third = game.move_history.get_move(2).unwrap();
+    assert_eq!(third.chess_move.to_string(), "g1f3");
+    assert_eq!(third.quality, Some(MoveQuality::Great));
+    assert_eq!(third.comment.as_deref(), Some("Develops with tempo"));
+    assert!(game.finished_at.is_none());
+}
+
+#[test]
+fn test_game_state_shapes() {
+    // The custom serializer writes a tagged object; every variant has to keep
+    // the shape games were saved with
+    let shapes = [
+        (json!({"type": "InProgress"}), GameState::InProgress),
+        (json!({"type": "Checkmate", "winner": "White"}), GameState::Checkmate(Color::White)),
+        (json!({"type": "Checkmate", "winner": "Black"}), GameState::Checkmate(Color::Black)),
+        (json!({"type": "Stalemate"}), GameState::Stalemate),
+        (json!({"type": "DrawByRepetition"}), GameState::DrawByRepetition),
+        (json!({"type": "DrawByInsufficientMaterial"}), GameState::DrawByInsufficientMaterial),
+        (json!({"type": "DrawBy50MoveRule"}), GameState::DrawBy50MoveRule),
+    ];
+    for (value, state) in shapes {
+        assert_eq!(serde_json::from_value::<GameState>(value.clone()).unwrap(), state);
+        assert_eq!(serde_json::to_value(&state).unwrap(), value);
+    }
+
+    // Fields this version doesn't know about are skipped, not rejected
+    let with_extra = json!({"type": "Checkmate", "winner": "Black", "moves": 4});
+    assert_eq!(serde_json::from_value::<GameState>(with_extra).unwrap(), GameState::Checkmate(Color::Black));
+    assert!(serde_json::from_value::<GameState>(json!({"type": "Checkmate"})).is_err());
+    assert!(serde_json::from_value::<GameState>(json!({"type": "Resigned"})).is_err());
+}
+
+#[test]
+fn test_move_history_golden() {
+    let history: MoveHistory = round_trip("move_history");
+
+    assert_eq!(history.len(), 4);
+    let blunder = history.get_move(2).unwrap();
+    assert_eq!(blunder.quality, Some(MoveQuality::Blunder));
+    assert_eq!(blunder.evaluation, Some(-99.0));
+    let mate = history.get_move(3).unwrap();
+    assert_eq!(mate.chess_move.to_string(), "d8h4");
+    assert_eq!(mate.evaluation, None);
+}
+
+#[test]
+fn test_move_analysis_golden() {
+    let analyses: Vec<MoveAnalysis> = round_trip("move_analysis");
+
+    assert_eq!(analyses.len(), 2);
+    assert_eq!(analyses[0].centipawn_loss, 10);
+    assert_eq!(analyses[0].best_move.to_string(), "f1b5");
+    assert_eq!(analyses[0].sacrifice, None);
+    assert_eq!(analyses[1].evaluation_after.centipawns(), 180);
+    assert_eq!(analyses[1].tactical_pattern, TacticalPattern::Sacrifice);
+    assert_eq!(analyses[1].quality, MoveQuality::Brilliant);
+    assert_eq!(analyses[1].sacrifice, Some(Sacrifice { material: 330, sound: true }));
+}
+
+#[test]
+fn test_move_analysis_before_sacrifices() {
+    // Analyses saved before sacrifice detection have no `sacrifice` field
+    let mut analysis = golden_value("move_analysis")[1].clone();
+    analysis.as_object_mut().unwrap().remove("sacrifice");
+
+    let analysis: MoveAnalysis = serde_json::from_value(analysis).unwrap();
+    assert_eq!(analysis.sacrifice, None);
+    assert_eq!(analysis.tactical_pattern, TacticalPattern::Sacrifice);
+}
+
+#[test]
+fn test_training_session_golden() {
+    let session: TrainingSession = round_trip("training_session");
+
+    assert_eq!(session.id, Some(7));
+    assert_eq!(session.difficulty, ExerciseDifficulty::Intermediate);
+    assert_eq!(session.exercises.len(), 1);
+    assert_eq!(session.exercises[0].exercise_type, ExerciseType::Tactics);
+    assert_eq!(session.exercises[0].phase, GamePhase::Opening);
+    assert_eq!(session.exercises[0].solution_moves, vec!["h5f7".to_string()]);
+    assert!(session.results[0].solved);
+    assert_eq!(session.results[0].hints_used, 1);
+    assert_eq!(session.strategies[0].pattern, StrategyPattern::TacticalAwareness);
+    assert!(session.finished_at.is_none());
+}
+
+#[test]
+fn test_training_session_from_older_versions() {
+    let mut session = golden_value("training_session");
+
+    // Phases, tie-break seeds and weakness codes came later and default when missing
+    session["exercises"][0].as_object_mut().unwrap().remove("phase");
+    session["strategies"][0].as_object_mut().unwrap().remove("weakness_codes");
+    let selector = session["selector"].as_object_mut().unwrap();
+    selector.remove("seed");
+    selector.remove("phases");
+    selector["strategies"][0].as_object_mut().unwrap().remove("weakness_codes");
+
+    // Names were once written in lowercase and snake_case
+    session["difficulty"] = json!("intermediate");
+    session["exercises"][0]["exercise_type"] = json!("tactics");
+    session["strategies"][0]["pattern"] = json!("tactical_awareness");
+
+    let session: TrainingSession = serde_json::from_value(session).unwrap();
+    assert_eq!(session.difficulty, ExerciseDifficulty::Intermediate);
+    assert_eq!(session.exercises[0].exercise_type, ExerciseType::Tactics);
+    assert_eq!(session.exercises[0].phase, GamePhase::default());
+    assert_eq!(session.strategies[0].pattern, StrategyPattern::TacticalAwareness);
+    assert!(session.strategies[0].weakness_codes.is_empty());
+
+    // Rewritten in the current shape, with the defaults filled in
+    let written = serde_json::to_value(&session).unwrap();
+    assert_eq!(written["difficulty"], json!("Intermediate"));
+    assert_eq!(written["selector"]["phases"], json!([]));
+    assert_eq!(written["selector"]["seed"], Value::Null);
+}
+
+#[test]
+fn test_profile_golden() {
+    let profile: Profile = round_trip("profile");
+
+    assert_eq!(profile.name, "Ada");
+    assert_eq!(profile.current_elo, 1340);
+    assert_eq!(profile.weaknesses, vec!["Blunders in time trouble", "Endgame technique"]);
+    assert_eq!(profile.strengths, vec!["Tactics"]);
+}
+
+#[test]
+fn test_coach_response_golden() {
+    let response: CoachResponse = round_trip("coach_response");
+
+    assert_eq!(response.message.role, "gurgeh");
+    assert_eq!(response.message.actions.len(), 5);
+    assert_eq!(
+        response.message.actions[0].target,
+        ActionTarget::StartTraining { theme: Some("Tactics".to_string()) }
+    );
+    assert_eq!(response.message.actions[1].target, ActionTarget::StartTraining { theme: None });
+    assert_eq!(
+        response.message.actions[2].target,
+        ActionTarget::LoadGame {
+            game_id: 42,
+            ply: 23,
+            fen: "r1bqk2r/pppp1ppp/2n2n2/1Bb1p3/4P3/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1".to_string(),
+        }
+    );
+    assert_eq!(response.message.actions[4].target, ActionTarget::QuizMe);
+    assert_eq!(response.highlights, vec!["c6", "b5"]);
+    assert_eq!(response.arrows[1], ("e8".to_string(), "g8".to_string()));
+    assert_eq!(response.trace_id, Some(7));
+}
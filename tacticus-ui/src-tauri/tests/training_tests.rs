@@ -72,3 +72,15 @@ fn test_exercise_types_varied() {
     
     assert!(tactical > 0, "Should have tactical exercises");
 }
+
+#[test]
+fn test_exercise_lines_are_legal() {
+    let exercises = ExerciseLibrary::get_all_exercises();
+
+    for exercise in exercises.iter().filter(|e| e.is_multi_move()) {
+        let line = chess_trainer::puzzle_line::validate_line(&exercise.position, &exercise.solution_line);
+        assert!(line.is_ok(), "Exercise '{}' has an illegal line: {:?}", exercise.title, line);
+        assert!(exercise.check_solution(&exercise.solution_line[0]));
+    }
+    assert!(exercises.iter().any(|e| e.moves_to_find() > 1), "Should have multi-move puzzles");
+}
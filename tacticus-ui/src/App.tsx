@@ -1,4 +1,6 @@
 import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { Onboarding } from './components/Onboarding';
 import { Hub } from './components/Hub';
 import { PlayMode } from './components/PlayMode';
@@ -8,12 +10,38 @@ import { AnalyzeMode } from './components/AnalyzeMode';
 import { GurgrehChat } from './components/gurgeh/GurgrehChat';
 import { XPTaskbar } from './components/xp/XPTaskbar';
 import { Settings } from './components/Settings';
+import { DebriefCard } from './components/DebriefCard';
+import { RepairScreen } from './components/RepairScreen';
 import { useUserStore } from './stores/userStore';
+import { useGameStore } from './stores/gameStore';
+import { useTrainingStore } from './stores/trainingStore';
+import {
+  discardSnapshot,
+  getRecoverableSessions,
+  startAutosave,
+  type RecoverableSession,
+} from './lib/autosave';
+import { getHealthStatus, getQuickHealthStatus, type HealthStatus } from './lib/health';
+import { setActivity, startActivityReporting, startActivityTracking, type TrackedActivity } from './lib/activity';
+import { onFilesOpened, takeOpenedFiles, type OpenedFile } from './lib/openedFiles';
+import type { CoachAction, ConversationMode, GameDebrief } from './lib/ai/types';
 import './styles/xp-theme.css';
 import './App.css';
 
 type View = 'hub' | 'train' | 'play' | 'analyze' | 'learn';
 
+/** What time spent in each view counts as in the study-time breakdown */
+const VIEW_ACTIVITIES: Record<View, TrackedActivity | null> = {
+  hub: null,
+  train: 'puzzles',
+  play: 'playing',
+  analyze: 'analysis',
+  learn: null,
+};
+
+// Concepts whose practice is a checkmate pattern drill
+const MATE_PATTERN_CONCEPTS = ['smothered_mate', 'anastasias_mate', 'arabian_mate', 'greek_gift'];
+
 function App() {
   const [currentView, setCurrentView] = useState<View>('hub');
   const [showChat, setShowChat] = useState(false);
@@ -21,18 +49,127 @@ function App() {
   const [isCalibration, setIsCalibration] = useState(false);
   const { hasOnboarded, checkOnboarding, loadProfile, loadStats, loadApiKey, apiKey } = useUserStore();
   const [loading, setLoading] = useState(true);
+  const [recoverable, setRecoverable] = useState<RecoverableSession[]>([]);
+  const [resumeGame, setResumeGame] = useState(false);
+  const [coachDraft, setCoachDraft] = useState('');
+  const [learnConceptId, setLearnConceptId] = useState<string | null>(null);
+  const [analyzeFen, setAnalyzeFen] = useState<string | null>(null);
+  // Stored game to open on the analysis board, and the ply to open it at
+  const [analyzeGame, setAnalyzeGame] = useState<{ gameId: number; ply: number } | null>(null);
+  const [trainingTheme, setTrainingTheme] = useState<string | null>(null);
+  // Saved position to train instead of a themed session
+  const [trainingPositionId, setTrainingPositionId] = useState<number | null>(null);
+  // Checkmate pattern concept to drill, from the concept library
+  const [trainingMatePattern, setTrainingMatePattern] = useState<string | null>(null);
+  // Exact exercises to train, from a daily workout item
+  const [trainingExerciseSet, setTrainingExerciseSet] = useState<{ ids: number[]; focus: string } | null>(null);
+  const [chatMode, setChatMode] = useState<ConversationMode>({ mode: 'general' });
+  // Stored conversation the chat continues, e.g. a post-game debrief
+  const [chatConversationId, setChatConversationId] = useState<number | undefined>(undefined);
+  const [debrief, setDebrief] = useState<GameDebrief | null>(null);
+  const [health, setHealth] = useState<HealthStatus | null>(null);
+  // A .pgn or .fen double-clicked in the OS, to import on the analysis board
+  const [openedFile, setOpenedFile] = useState<OpenedFile | null>(null);
+
+  // Several files at once open the last; each import replaces the one before anyway
+  const openFiles = (files: OpenedFile[]) => {
+    setOpenedFile(files[files.length - 1]);
+    setIsCalibration(false);
+    setCurrentView('analyze');
+  };
 
   useEffect(() => {
     const init = async () => {
+      // A database that failed to open leaves nothing to load; offer a repair instead
+      const status = await getQuickHealthStatus();
+      setHealth(status);
+      if (status?.needs_repair) {
+        setLoading(false);
+        return;
+      }
+      // The engine and API key checks finish in the background
+      getHealthStatus().then((full) => full && setHealth(full));
       const onboarded = await checkOnboarding();
       if (onboarded) {
         await Promise.all([loadProfile(), loadStats(), loadApiKey()]);
+        setRecoverable(await getRecoverableSessions());
+        // Launched by double-clicking a file
+        const files = await takeOpenedFiles();
+        if (files.length > 0) openFiles(files);
       }
       setLoading(false);
     };
     init();
+    const stopAutosave = startAutosave();
+    const stopActivityReporting = startActivityReporting();
+    const stopActivityTracking = startActivityTracking();
+    return () => {
+      stopAutosave();
+      stopActivityReporting();
+      stopActivityTracking();
+    };
+  }, []);
+
+  // The chat window counts as chatting whatever mode is behind it
+  useEffect(() => {
+    setActivity(showChat ? 'coach_chat' : VIEW_ACTIVITIES[currentView]);
+  }, [currentView, showChat]);
+
+  // The backend debriefs every saved game in the background
+  useEffect(() => {
+    const unlisten = listen<GameDebrief>('game-debrief-ready', (event) => setDebrief(event.payload));
+    return () => { unlisten.then((stop) => stop()); };
   }, []);
 
+  useEffect(() => onFilesOpened(openFiles), []);
+
+  const openChat = (mode: ConversationMode, conversationId?: number) => {
+    setChatMode(mode);
+    setChatConversationId(conversationId);
+    setShowChat(true);
+  };
+
+  const handleReviewDebrief = (d: GameDebrief) => {
+    setDebrief(null);
+    setAnalyzeFen(d.final_fen);
+    setCurrentView('analyze');
+    openChat({ mode: 'game_review', game_id: d.game_id }, d.conversation_id);
+  };
+
+  const handleTrainDebrief = (d: GameDebrief) => {
+    setDebrief(null);
+    setTrainingTheme(d.puzzle_theme);
+    setCurrentView('train');
+  };
+
+  const handleRestore = async () => {
+    for (const snapshot of recoverable) {
+      if (snapshot.kind === 'game') {
+        await useGameStore.getState().restoreGame(snapshot.payload);
+        setResumeGame(true);
+        setCurrentView('play');
+      } else if (snapshot.kind === 'training') {
+        useTrainingStore.getState().restoreSession(snapshot.payload);
+        setCurrentView('train');
+      } else if (snapshot.kind === 'coach_draft') {
+        setCoachDraft(snapshot.payload.text ?? '');
+        setShowChat(true);
+      }
+    }
+    setRecoverable([]);
+  };
+
+  const handleDiscardRecovery = () => {
+    recoverable.forEach((s) => discardSnapshot(s.kind, s.session_key));
+    setRecoverable([]);
+  };
+
+  const recoveryLabels: Record<string, string> = {
+    game: 'a game in progress',
+    training: 'a training session',
+    coach_draft: 'an unsent message to Gurgeh',
+  };
+
   const handleOnboardingComplete = async () => {
     await loadProfile();
     await loadStats();
@@ -42,6 +179,7 @@ function App() {
 
   const handleNavigate = (view: View) => {
     setIsCalibration(false);
+    setResumeGame(false);
     setCurrentView(view);
   };
 
@@ -50,15 +188,62 @@ function App() {
     setCurrentView('play');
   };
 
-  const handleChatAction = (action: { action_type: string }) => {
-    if (action.action_type === 'start_training') {
-      setCurrentView('train');
-      setShowChat(false);
-    } else if (action.action_type === 'play_game') {
-      setCurrentView('play');
-      setShowChat(false);
-    } else if (action.action_type === 'open_settings') {
-      setShowSettings(true);
+  /** Continue a stored training session from its autosave, or close it and start afresh
+   * on its theme when the autosave is gone */
+  const resumeTraining = async (sessionId: number, theme?: string) => {
+    const snapshot = (await getRecoverableSessions()).find(
+      (s) => s.kind === 'training' && s.payload?.sessionId === sessionId,
+    );
+    if (snapshot) {
+      useTrainingStore.getState().restoreSession(snapshot.payload);
+      setTrainingTheme(null);
+      setRecoverable((pending) => pending.filter((s) => s.kind !== 'training'));
+    } else {
+      invoke('finish_training_session', { sessionId }).catch((err) =>
+        console.error('Failed to finish training session:', err)
+      );
+      setTrainingTheme(theme ?? null);
+    }
+    setCurrentView('train');
+    setShowChat(false);
+  };
+
+  const handleChatAction = (action: CoachAction) => {
+    switch (action.action_type) {
+      case 'start_training':
+        setTrainingTheme(action.theme ?? null);
+        setCurrentView('train');
+        setShowChat(false);
+        break;
+      case 'resume_training':
+        resumeTraining(action.session_id, action.theme);
+        break;
+      case 'play_game':
+        setCurrentView('play');
+        setShowChat(false);
+        break;
+      case 'quiz_me':
+        openChat({ mode: 'quiz_me' });
+        break;
+      case 'open_settings':
+        setShowSettings(true);
+        break;
+      // Keep the chat open for these so the coach's explanation stays alongside
+      case 'open_concept':
+        setLearnConceptId(action.concept_id);
+        setCurrentView('learn');
+        break;
+      case 'load_game':
+        setAnalyzeFen(action.fen);
+        setAnalyzeGame({ gameId: action.game_id, ply: action.ply });
+        setCurrentView('analyze');
+        // Talk the game through in its own review conversation
+        openChat({ mode: 'game_review', game_id: action.game_id });
+        break;
+      case 'setup_position':
+        setAnalyzeFen(action.fen);
+        setCurrentView('analyze');
+        break;
     }
   };
 
@@ -80,6 +265,14 @@ function App() {
     );
   }
 
+  if (health?.needs_repair) {
+    return (
+      <div className="xp-desktop">
+        <RepairScreen status={health} onRepaired={() => window.location.reload()} />
+      </div>
+    );
+  }
+
   return (
     <div className="xp-desktop">
       <div className="app-content">
@@ -90,12 +283,23 @@ function App() {
             {currentView === 'hub' && (
               <Hub 
                 onNavigate={handleNavigate} 
-                onChatClick={() => setShowChat(true)}
+                onChatClick={() => openChat({ mode: 'general' })}
+                onDrillTheme={(theme) => { setTrainingTheme(theme); setCurrentView('train'); }}
+                onAnalyzePosition={(fen) => { setAnalyzeFen(fen); setCurrentView('analyze'); }}
+                onTrainPosition={(id) => { setTrainingPositionId(id); setCurrentView('train'); }}
+                onTrainExercises={(ids, focus) => { setTrainingExerciseSet({ ids, focus }); setCurrentView('train'); }}
+                onPlanAction={handleChatAction}
               />
             )}
             {currentView === 'train' && (
               <TrainMode 
-                onBack={() => setCurrentView('hub')}
+                theme={trainingTheme}
+                positionId={trainingPositionId}
+                matePattern={trainingMatePattern}
+                exerciseSet={trainingExerciseSet}
+                onAskCoach={(exerciseId) => openChat({ mode: 'exercise_help', exercise_id: exerciseId })}
+                onGuideMe={(exerciseId) => openChat({ mode: 'guided_help', exercise_id: exerciseId })}
+                onBack={() => { setTrainingTheme(null); setTrainingPositionId(null); setTrainingMatePattern(null); setTrainingExerciseSet(null); setCurrentView('hub'); }}
                 onCalibrationNeeded={handleCalibrationNeeded}
               />
             )}
@@ -103,21 +307,41 @@ function App() {
               <PlayMode 
                 onBack={() => setCurrentView('hub')}
                 isCalibration={isCalibration}
+                resumeGame={resumeGame}
               />
             )}
             {currentView === 'analyze' && (
-              <AnalyzeMode onBack={() => setCurrentView('hub')} />
+              <AnalyzeMode
+                initialFen={analyzeFen}
+                game={analyzeGame}
+                openedFile={openedFile}
+                onBack={() => { setAnalyzeFen(null); setAnalyzeGame(null); setOpenedFile(null); setCurrentView('hub'); }}
+              />
             )}
             {currentView === 'learn' && (
-              <LearnMode onBack={() => setCurrentView('hub')} />
+              <LearnMode
+                initialConceptId={learnConceptId}
+                onBack={() => { setLearnConceptId(null); setCurrentView('hub'); }}
+                onPractice={(conceptId) => {
+                  if (!MATE_PATTERN_CONCEPTS.includes(conceptId)) return;
+                  setLearnConceptId(null);
+                  setTrainingMatePattern(conceptId);
+                  setCurrentView('train');
+                }}
+              />
             )}
           </>
         )}
 
         {showChat && hasOnboarded && (
           <GurgrehChat
+            // A new mode starts a fresh conversation
+            key={`${JSON.stringify(chatMode)}-${chatConversationId ?? 'new'}`}
+            mode={chatMode}
+            storedConversationId={chatConversationId}
             onClose={() => setShowChat(false)}
             onAction={handleChatAction}
+            initialDraft={coachDraft}
           />
         )}
 
@@ -138,8 +362,27 @@ function App() {
         }}
         onStartClick={() => setShowChat(!showChat)}
         onSettingsClick={() => setShowSettings(true)}
+        health={health}
       />
 
+      {hasOnboarded && debrief && (
+        <DebriefCard
+          debrief={debrief}
+          onReview={() => handleReviewDebrief(debrief)}
+          onTrain={() => handleTrainDebrief(debrief)}
+          onDismiss={() => setDebrief(null)}
+        />
+      )}
+
+      {/* Offer to restore sessions interrupted by a crash */}
+      {hasOnboarded && recoverable.length > 0 && (
+        <div className="recovery-banner">
+          [!] Tacticus closed unexpectedly. Restore {recoverable.map((s) => recoveryLabels[s.kind] ?? s.kind).join(', ')}?
+          <button className="recovery-button" onClick={handleRestore}>Restore</button>
+          <button className="recovery-button" onClick={handleDiscardRecovery}>Discard</button>
+        </div>
+      )}
+
       {/* Show API key prompt banner if not configured */}
       {hasOnboarded && !apiKey && !showSettings && (
         <div className="api-key-banner" onClick={() => setShowSettings(true)}>
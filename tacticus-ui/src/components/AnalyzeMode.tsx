@@ -1,4 +1,4 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { XPWindow } from './xp/XPWindow';
 import { XPButton } from './xp/XPButton';
@@ -6,6 +6,7 @@ import { XPPanel } from './xp/XPPanel';
 import { XPInput } from './xp/XPInput';
 import { ChessBoard } from './board/ChessBoard';
 import { useUserStore } from '../stores/userStore';
+import type { OpenedFile } from '../lib/openedFiles';
 import './AnalyzeMode.css';
 
 interface CoachResponse {
@@ -14,19 +15,119 @@ interface CoachResponse {
   };
 }
 
+interface PositionAnalysis {
+  fen: string;
+  evaluation: number;
+  best_move: string | null;
+  best_move_eval: number | null;
+}
+
+/** A pasted FEN, Lichess link, puzzle or PGN as the backend read it */
+interface PositionImport {
+  source: 'fen' | 'lichess_url' | 'pgn' | 'puzzle';
+  fen: string;
+  moves: string[];
+  opening_name: string | null;
+  saved_id: number | null;
+}
+
+/** A pasted game as the backend read it, with notes on what it recovered */
+interface GameImport {
+  game: {
+    white: string | null;
+    black: string | null;
+    result: string | null;
+    final_fen: string;
+    moves: string[];
+    opening_name: string | null;
+    diagnostics: string[];
+    save_blocker: string | null;
+  };
+  player_color: 'white' | 'black' | null;
+  saved_id: number | null;
+  /** Already saved before; `saved_id` is that copy */
+  duplicate: boolean;
+}
+
+/** A stored game to step through; `positions[ply]` follows `ply` half-moves */
+interface GameReplay {
+  game_id: number;
+  positions: string[];
+  /** Each move with its number, e.g. "12...Nf6" */
+  moves: string[];
+}
+
 interface AnalyzeModeProps {
   onBack: () => void;
+  /** Position to start from, e.g. from a coach deep link; validated by the backend */
+  initialFen?: string | null;
+  /** Stored game to open at `ply`, e.g. from a coach deep link; takes over from `initialFen` */
+  game?: { gameId: number; ply: number } | null;
+  /** A .pgn or .fen file double-clicked in the OS, imported as if it were pasted */
+  openedFile?: OpenedFile | null;
 }
 
-export const AnalyzeMode: React.FC<AnalyzeModeProps> = ({ onBack }) => {
-  const [fen, setFen] = useState('rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1');
+export const AnalyzeMode: React.FC<AnalyzeModeProps> = ({ onBack, initialFen, game, openedFile }) => {
+  const [fen, setFen] = useState(initialFen || 'rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1');
   const [evaluation, setEvaluation] = useState<number | null>(null);
   const [arrows, setArrows] = useState<{ from: string; to: string; color?: string }[]>([]);
   const [highlights] = useState<{ square: string; color?: string }[]>([]);
   const [analysisText, setAnalysisText] = useState<string>('');
   const [loading, setLoading] = useState(false);
+  const [askedGurgeh, setAskedGurgeh] = useState(false);
+  // What was pasted, so saving keeps the moves that led to the position
+  const [importedSource, setImportedSource] = useState<string | null>(null);
+  const [importNote, setImportNote] = useState('');
+  const [tags, setTags] = useState('');
+  // A pasted game waiting to be saved, and the side the player says they had
+  const [gameSource, setGameSource] = useState<string | null>(null);
+  const [gameImport, setGameImport] = useState<GameImport | null>(null);
+  const [gameColor, setGameColor] = useState<'white' | 'black' | ''>('');
+  const [gameNote, setGameNote] = useState('');
+  // A stored game being stepped through, and how many moves in the board is
+  const [replay, setReplay] = useState<GameReplay | null>(null);
+  const [replayPly, setReplayPly] = useState(0);
   const { apiKey } = useUserStore();
 
+  // The chat stays open beside the board, so later links replace the position
+  useEffect(() => {
+    if (initialFen) {
+      setFen(initialFen);
+      setReplay(null);
+      setEvaluation(null);
+      setArrows([]);
+      setAnalysisText('');
+    }
+  }, [initialFen]);
+
+  useEffect(() => {
+    if (!game) return;
+    invoke<GameReplay>('get_game_replay', { gameId: game.gameId })
+      .then((loaded) => {
+        setReplay(loaded);
+        showPly(loaded, game.ply);
+      })
+      .catch((err) => setImportNote(`[!] ${err}`));
+  }, [game]);
+
+  const showPly = (from: GameReplay, ply: number) => {
+    const clamped = Math.max(0, Math.min(ply, from.positions.length - 1));
+    setReplayPly(clamped);
+    handleFenChange(from.positions[clamped]);
+  };
+
+  // A .pgn opens like a pasted game, so it can be saved; a .fen like a pasted position
+  useEffect(() => {
+    if (!openedFile) return;
+    if (openedFile.error) {
+      setImportNote(`[!] ${openedFile.error}`);
+    } else if (openedFile.kind === 'pgn') {
+      importGame(openedFile.source);
+    } else {
+      importPosition(openedFile.source, openedFile.name);
+    }
+  }, [openedFile]);
+
   const handleEvaluate = async () => {
     setLoading(true);
     try {
@@ -47,18 +148,21 @@ export const AnalyzeMode: React.FC<AnalyzeModeProps> = ({ onBack }) => {
   const handleBestMove = async () => {
     setLoading(true);
     try {
-      const move = await invoke<{ uci: string; evaluation: number }>('get_engine_move', { 
-        fen, 
-        engineElo: 2000 
+      // Superseded requests resolve to null; a newer one is already updating the board
+      const result = await invoke<PositionAnalysis | null>('request_analysis', {
+        fen,
+        channel: 'analyze_board',
       });
-      
+      if (!result || !result.best_move || result.best_move_eval === null) return;
+      const { best_move: uci, best_move_eval: evaluation } = result;
+
       setArrows([{
-        from: move.uci.slice(0, 2),
-        to: move.uci.slice(2, 4),
+        from: uci.slice(0, 2),
+        to: uci.slice(2, 4),
         color: 'rgba(0, 200, 0, 0.7)'
       }]);
-      
-      setAnalysisText(`Best move: ${move.uci}\nEvaluation: ${move.evaluation >= 0 ? '+' : ''}${move.evaluation.toFixed(2)}`);
+
+      setAnalysisText(`Best move: ${uci}\nEvaluation: ${evaluation >= 0 ? '+' : ''}${evaluation.toFixed(2)}`);
     } catch (err) {
       setAnalysisText(`Error: ${err}`);
     } finally {
@@ -66,7 +170,8 @@ export const AnalyzeMode: React.FC<AnalyzeModeProps> = ({ onBack }) => {
     }
   };
 
-  const handleGurgrehAnalysis = async () => {
+  // Repeat analyses of a position are served from cache unless `fresh` is set
+  const handleGurgrehAnalysis = async (fresh = false) => {
     if (!apiKey) {
       setAnalysisText('API key required. Please configure your OpenRouter API key in Settings.');
       return;
@@ -78,28 +183,116 @@ export const AnalyzeMode: React.FC<AnalyzeModeProps> = ({ onBack }) => {
     try {
       const response = await invoke<CoachResponse>('analyze_position_with_coach', { 
         fen,
-        apiKey 
+        apiKey,
+        bypassCache: fresh,
       });
       setAnalysisText(response.message.content);
+      setAskedGurgeh(true);
+      setLoading(false);
     } catch (err) {
-      setAnalysisText(`Error: ${err}`);
-    } finally {
+      // A newer request replaced this one and will fill in the analysis
+      if (String(err).startsWith('superseded')) return;
+      setAnalysisText(String(err).startsWith('cooling_down') ? `[!] ${String(err).replace(/^cooling_down: /, '')}` : `Error: ${err}`);
       setLoading(false);
     }
   };
 
+  // `from` names the file the text came from, when it wasn't pasted
+  const importPosition = async (text: string, from?: string) => {
+    try {
+      const imported = await invoke<PositionImport>('import_position', { source: text });
+      handleFenChange(imported.fen);
+      setImportedSource(text);
+      setImportNote(
+        (imported.moves.length > 0
+          ? `Imported ${imported.moves.length} plies${imported.opening_name ? ` (${imported.opening_name})` : ''}`
+          : 'Imported position') + (from ? ` from ${from}` : ''),
+      );
+    } catch (err) {
+      setImportNote(`[!] ${err}`);
+    }
+  };
+
+  const handlePaste = async () => {
+    try {
+      await importPosition(await navigator.clipboard.readText());
+    } catch (err) {
+      setImportNote(`[!] ${err}`);
+    }
+  };
+
+  const handleSave = async () => {
+    try {
+      if (importedSource) {
+        await invoke<PositionImport>('import_position', { source: importedSource, save: true, tags: tags.split(',') });
+      } else {
+        await invoke<number>('bookmark_position', { fen, origin: 'analysis', tags: tags.split(',') });
+      }
+      setImportNote('Saved to your position library');
+      setTags('');
+    } catch (err) {
+      setImportNote(`[!] ${err}`);
+    }
+  };
+
+  const importGame = async (text: string) => {
+    try {
+      const imported = await invoke<GameImport>('import_game', { source: text });
+      handleFenChange(imported.game.final_fen);
+      setGameSource(text);
+      setGameImport(imported);
+      setGameColor(imported.player_color ?? '');
+      setGameNote(imported.game.save_blocker ? `[!] ${imported.game.save_blocker}` : '');
+    } catch (err) {
+      setGameSource(null);
+      setGameImport(null);
+      setGameNote(`[!] ${err}`);
+    }
+  };
+
+  const handlePasteGame = async () => {
+    try {
+      await importGame(await navigator.clipboard.readText());
+    } catch (err) {
+      setGameNote(`[!] ${err}`);
+    }
+  };
+
+  const handleSaveGame = async () => {
+    try {
+      const saved = await invoke<GameImport>('import_game', {
+        source: gameSource,
+        playerColor: gameColor || null,
+        save: true,
+      });
+      setGameImport(null);
+      setGameSource(null);
+      setGameNote(saved.duplicate
+        ? `Already saved as game #${saved.saved_id}`
+        : `Saved as game #${saved.saved_id}; Gurgeh will debrief it shortly`);
+    } catch (err) {
+      setGameNote(`[!] ${err}`);
+    }
+  };
+
   const handleFenChange = async (newFen: string) => {
     setFen(newFen);
+    setImportedSource(null);
+    setImportNote('');
     setArrows([]);
     setEvaluation(null);
     setAnalysisText('');
+    setAskedGurgeh(false);
   };
 
   const handleReset = () => {
     setFen('rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1');
+    setImportedSource(null);
+    setImportNote('');
     setArrows([]);
     setEvaluation(null);
     setAnalysisText('');
+    setAskedGurgeh(false);
   };
 
   return (
@@ -131,7 +324,63 @@ export const AnalyzeMode: React.FC<AnalyzeModeProps> = ({ onBack }) => {
                 <XPButton onClick={() => navigator.clipboard.writeText(fen)}>
                   Copy FEN
                 </XPButton>
+                <XPButton onClick={handlePaste}>Paste FEN / PGN</XPButton>
               </div>
+              <div className="position-actions">
+                <XPInput value={tags} onChange={setTags} placeholder="Tags, comma separated" />
+                <XPButton onClick={handleSave}>Save</XPButton>
+              </div>
+              {importNote && <div className="import-note">{importNote}</div>}
+            </div>
+          </XPWindow>
+
+          {replay && (
+            <XPWindow title={`Game #${replay.game_id}`} icon="[R]" width={350} height="auto">
+              <div className="position-section">
+                <div className="import-summary">
+                  {replayPly === 0 ? 'Starting position' : `After ${replay.moves[replayPly - 1]}`}
+                </div>
+                <div className="position-actions">
+                  <XPButton onClick={() => showPly(replay, 0)} disabled={replayPly === 0}>|&lt;</XPButton>
+                  <XPButton onClick={() => showPly(replay, replayPly - 1)} disabled={replayPly === 0}>&lt;</XPButton>
+                  <XPButton onClick={() => showPly(replay, replayPly + 1)} disabled={replayPly === replay.moves.length}>&gt;</XPButton>
+                  <XPButton onClick={() => showPly(replay, replay.moves.length)} disabled={replayPly === replay.moves.length}>&gt;|</XPButton>
+                </div>
+              </div>
+            </XPWindow>
+          )}
+
+          {/* Game Import */}
+          <XPWindow title="Import Game" icon="[G]" width={350} height="auto">
+            <div className="position-section">
+              <div className="position-actions">
+                <XPButton onClick={handlePasteGame}>Paste Game</XPButton>
+              </div>
+              {gameImport && (
+                <>
+                  <div className="import-summary">
+                    {gameImport.game.white ?? '?'} vs {gameImport.game.black ?? '?'}
+                    {gameImport.game.result ? ` - ${gameImport.game.result}` : ''}
+                    {gameImport.game.opening_name ? ` (${gameImport.game.opening_name})` : ''}
+                  </div>
+                  <ul className="import-diagnostics">
+                    {gameImport.game.diagnostics.map((line, i) => (
+                      <li key={i}>{line}</li>
+                    ))}
+                  </ul>
+                  <div className="position-actions">
+                    <select value={gameColor} onChange={(e) => setGameColor(e.target.value as 'white' | 'black' | '')}>
+                      <option value="">I played...</option>
+                      <option value="white">White</option>
+                      <option value="black">Black</option>
+                    </select>
+                    <XPButton onClick={handleSaveGame} disabled={!gameColor || !!gameImport.game.save_blocker}>
+                      Save Game
+                    </XPButton>
+                  </div>
+                </>
+              )}
+              {gameNote && <div className="import-note">{gameNote}</div>}
             </div>
           </XPWindow>
 
@@ -145,9 +394,14 @@ export const AnalyzeMode: React.FC<AnalyzeModeProps> = ({ onBack }) => {
                 <XPButton onClick={handleBestMove} disabled={loading}>
                   Find Best Move
                 </XPButton>
-                <XPButton onClick={handleGurgrehAnalysis} disabled={loading || !apiKey} primary>
+                <XPButton onClick={() => handleGurgrehAnalysis()} disabled={loading || !apiKey} primary>
                   Ask Gurgeh
                 </XPButton>
+                {askedGurgeh && (
+                  <XPButton onClick={() => handleGurgrehAnalysis(true)} disabled={loading}>
+                    Fresh Analysis
+                  </XPButton>
+                )}
               </div>
 
               {evaluation !== null && (
@@ -0,0 +1,61 @@
+import React, { useEffect, useState } from 'react';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+import { useAppearanceStore } from '../stores/appearanceStore';
+
+/** Piece set and board theme for the board, exported diagrams and game reports */
+export const AppearancePanel: React.FC = () => {
+  const { appearance, assets, loadAppearance, loadAssets, setAppearance } = useAppearanceStore();
+  const [status, setStatus] = useState<string | null>(null);
+
+  useEffect(() => {
+    loadAssets();
+    loadAppearance();
+  }, [loadAssets, loadAppearance]);
+
+  const handleChange = async (pieceSet: string, boardTheme: string) => {
+    try {
+      await setAppearance(pieceSet, boardTheme);
+      setStatus(null);
+    } catch (err) {
+      setStatus(`[!] ${err}`);
+    }
+  };
+
+  if (!appearance || !assets) return null;
+
+  return (
+    <XPPanel label="Board Appearance" className="appearance-section">
+      <div className="diagnostics-row">
+        <label htmlFor="piece-set">Pieces</label>
+        <select
+          id="piece-set"
+          value={appearance.piece_set}
+          onChange={(e) => handleChange(e.target.value, appearance.board_theme.name)}
+        >
+          {assets.piece_sets.map((set) => (
+            <option key={set.name} value={set.name}>{set.name}</option>
+          ))}
+        </select>
+        <label htmlFor="board-theme">Board</label>
+        <select
+          id="board-theme"
+          value={appearance.board_theme.name}
+          onChange={(e) => handleChange(appearance.piece_set, e.target.value)}
+        >
+          {assets.board_themes.map((theme) => (
+            <option key={theme.name} value={theme.name}>{theme.name}</option>
+          ))}
+        </select>
+        <XPButton onClick={() => loadAssets(true)}>Reload</XPButton>
+      </div>
+      {assets.user_dir && (
+        <p className="settings-description">
+          Add piece sets as <code>pieces/&lt;name&gt;/wK.svg</code> ... <code>bP.svg</code> and board themes
+          as <code>boards/&lt;name&gt;.json</code> under <code>{assets.user_dir}</code>.
+        </p>
+      )}
+      {status && <p className="settings-description">{status}</p>}
+    </XPPanel>
+  );
+};
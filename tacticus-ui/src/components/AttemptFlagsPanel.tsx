@@ -0,0 +1,43 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+
+/** Opt-in flagging of puzzle attempts that look engine-assisted */
+export const AttemptFlagsPanel: React.FC = () => {
+  const [enabled, setEnabled] = useState(false);
+
+  useEffect(() => {
+    invoke<boolean>('get_flag_suspicious_attempts')
+      .then(setEnabled)
+      .catch((err) => console.error('Failed to load attempt flagging setting:', err));
+  }, []);
+
+  const handleToggle = async (value: boolean) => {
+    try {
+      await invoke('set_flag_suspicious_attempts', { enabled: value });
+      setEnabled(value);
+    } catch (err) {
+      console.error('Failed to update attempt flagging setting:', err);
+    }
+  };
+
+  return (
+    <XPPanel label="Fair Play" className="attempt-flags-section">
+      <p className="settings-description">
+        Flag puzzle attempts that look engine-assisted: advanced puzzles solved in a couple of seconds, or a
+        long run of fast perfect solves that breaks from your usual accuracy. Flagged attempts stay in your
+        session history but don't count toward progress, weaknesses or session ratings.
+      </p>
+      <div className="diagnostics-row">
+        <label>
+          <input
+            type="checkbox"
+            checked={enabled}
+            onChange={(e) => handleToggle(e.target.checked)}
+          />
+          Flag suspicious attempts
+        </label>
+      </div>
+    </XPPanel>
+  );
+};
@@ -0,0 +1,56 @@
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPInput } from './xp/XPInput';
+import type { SavedPosition } from '../lib/ai/types';
+import './BookmarkButton.css';
+
+interface BookmarkButtonProps {
+  fen: string | null | undefined;
+  /** Where the board is, stored with the bookmark */
+  origin: SavedPosition['origin'];
+}
+
+/** Save the board's position to the position library, with an optional note and tags */
+export const BookmarkButton: React.FC<BookmarkButtonProps> = ({ fen, origin }) => {
+  const [open, setOpen] = useState(false);
+  const [note, setNote] = useState('');
+  const [tags, setTags] = useState('');
+  const [status, setStatus] = useState('');
+
+  const handleSave = async () => {
+    if (!fen) return;
+    try {
+      await invoke<number>('bookmark_position', { fen, origin, note, tags: tags.split(',') });
+      setStatus('Bookmarked');
+      setOpen(false);
+      setNote('');
+      setTags('');
+    } catch (err) {
+      setStatus(`[!] ${err}`);
+    }
+  };
+
+  if (!open) {
+    return (
+      <div className="bookmark-button">
+        <XPButton onClick={() => { setOpen(true); setStatus(''); }} disabled={!fen}>
+          Bookmark Position
+        </XPButton>
+        {status && <span className="bookmark-status">{status}</span>}
+      </div>
+    );
+  }
+
+  return (
+    <div className="bookmark-button">
+      <XPInput value={note} onChange={setNote} placeholder="Note (optional)" />
+      <XPInput value={tags} onChange={setTags} placeholder="Tags, comma separated" />
+      <div className="bookmark-actions">
+        <XPButton primary onClick={handleSave}>Save</XPButton>
+        <XPButton onClick={() => setOpen(false)}>Cancel</XPButton>
+      </div>
+      {status && <span className="bookmark-status">{status}</span>}
+    </div>
+  );
+};
@@ -0,0 +1,42 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+
+/** Opt-in log of exactly what Gurgeh sent to the model and got back */
+export const CoachTransparencyPanel: React.FC = () => {
+  const [enabled, setEnabled] = useState(false);
+
+  useEffect(() => {
+    invoke<boolean>('get_coach_transparency')
+      .then(setEnabled)
+      .catch((err) => console.error('Failed to load coach transparency setting:', err));
+  }, []);
+
+  const handleToggle = async (value: boolean) => {
+    try {
+      await invoke('set_coach_transparency', { enabled: value });
+      setEnabled(value);
+    } catch (err) {
+      console.error('Failed to update coach transparency setting:', err);
+    }
+  };
+
+  return (
+    <XPPanel label="Coach Transparency" className="coach-transparency-section">
+      <p className="settings-description">
+        Keep the full prompt, tool calls and raw model responses behind each coach reply, to see
+        what context was shared with the API. Traces stay on this computer; turning this off deletes them.
+      </p>
+      <div className="diagnostics-row">
+        <label>
+          <input
+            type="checkbox"
+            checked={enabled}
+            onChange={(e) => handleToggle(e.target.checked)}
+          />
+          Log coach prompts and responses
+        </label>
+      </div>
+    </XPPanel>
+  );
+};
@@ -0,0 +1,51 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import type { CoachVerbosity } from '../lib/ai/types';
+
+const OPTIONS: { value: CoachVerbosity; label: string }[] = [
+  { value: 'terse', label: 'Terse - the point and nothing else' },
+  { value: 'normal', label: 'Normal' },
+  { value: 'deep_dive', label: 'Deep dive - every line explained' },
+];
+
+/** How much Gurgeh says in chat, analyses, definitions and debriefs */
+export const CoachVerbosityPanel: React.FC = () => {
+  const [verbosity, setVerbosity] = useState<CoachVerbosity>('normal');
+
+  useEffect(() => {
+    invoke<CoachVerbosity>('get_coach_verbosity')
+      .then(setVerbosity)
+      .catch((err) => console.error('Failed to load coaching verbosity:', err));
+  }, []);
+
+  const handleChange = async (value: CoachVerbosity) => {
+    try {
+      await invoke('set_coach_verbosity', { verbosity: value });
+      setVerbosity(value);
+    } catch (err) {
+      console.error('Failed to update coaching verbosity:', err);
+    }
+  };
+
+  return (
+    <XPPanel label="Coaching Verbosity" className="coach-verbosity-section">
+      <p className="settings-description">
+        How long Gurgeh's answers are across chat, position analysis, definitions and game debriefs.
+        Kid mode keeps answers short whatever is chosen here.
+      </p>
+      <div className="diagnostics-row">
+        <label htmlFor="coach-verbosity">Answers</label>
+        <select
+          id="coach-verbosity"
+          value={verbosity}
+          onChange={(e) => handleChange(e.target.value as CoachVerbosity)}
+        >
+          {OPTIONS.map((o) => (
+            <option key={o.value} value={o.value}>{o.label}</option>
+          ))}
+        </select>
+      </div>
+    </XPPanel>
+  );
+};
@@ -0,0 +1,105 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+import { loadWorkoutDone, setWorkoutItemDone, startWorkoutItem } from '../lib/workoutProgress';
+
+type View = 'hub' | 'train' | 'play' | 'analyze' | 'learn';
+
+type WorkoutSegment =
+  | { kind: 'tactics'; exercise_ids: number[] }
+  | { kind: 'endgame_drill'; exercise_id: number }
+  | { kind: 'guess_the_move'; game_id: string }
+  | { kind: 'repertoire_review'; concept_ids: string[]; exercise_ids: number[] };
+
+interface WorkoutItem {
+  title: string;
+  reason: string;
+  minutes: number;
+  segment: WorkoutSegment;
+}
+
+interface DailyWorkout {
+  date: string;
+  total_minutes: number;
+  focus_areas: string[];
+  items: WorkoutItem[];
+}
+
+const SEGMENT_VIEWS: Record<WorkoutSegment['kind'], View> = {
+  tactics: 'train',
+  endgame_drill: 'train',
+  guess_the_move: 'learn',
+  repertoire_review: 'learn',
+};
+
+/** Exercises a segment trains, for those played out on the training board */
+function segmentExercises(segment: WorkoutSegment): number[] {
+  switch (segment.kind) {
+    case 'tactics':
+      return segment.exercise_ids;
+    case 'endgame_drill':
+      return [segment.exercise_id];
+    default:
+      return [];
+  }
+}
+
+interface DailyWorkoutProps {
+  onNavigate: (view: View) => void;
+  /** Train exactly these exercises */
+  onTrainExercises: (exerciseIds: number[], focus: string) => void;
+}
+
+/** Today's mixed-mode playlist. Exercise items are ticked off when their session
+ * finishes; the player ticks off the rest. Progress is remembered for the day. */
+export const DailyWorkoutPanel: React.FC<DailyWorkoutProps> = ({ onNavigate, onTrainExercises }) => {
+  const [workout, setWorkout] = useState<DailyWorkout | null>(null);
+  const [done, setDone] = useState<number[]>([]);
+
+  useEffect(() => {
+    invoke<DailyWorkout>('generate_daily_workout')
+      .then((w) => {
+        setWorkout(w);
+        setDone(loadWorkoutDone(w.date));
+      })
+      .catch((err) => console.error('Failed to generate daily workout:', err));
+  }, []);
+
+  if (!workout || workout.items.length === 0) return null;
+
+  const start = (index: number) => {
+    const item = workout.items[index];
+    const exerciseIds = segmentExercises(item.segment);
+    if (exerciseIds.length > 0) {
+      startWorkoutItem(workout.date, index, exerciseIds);
+      onTrainExercises(exerciseIds, item.title);
+    } else {
+      onNavigate(SEGMENT_VIEWS[item.segment.kind]);
+    }
+  };
+
+  const toggle = (index: number) => setDone(setWorkoutItemDone(workout.date, index, !done.includes(index)));
+
+  return (
+    <XPPanel label={`Daily Workout (${workout.total_minutes} min)`} className="daily-workout">
+      {workout.focus_areas.length > 0 && (
+        <p className="workout-focus">Focus: {workout.focus_areas.join(', ')}</p>
+      )}
+      <ol className="workout-items">
+        {workout.items.map((item, index) => (
+          <li key={index} className={done.includes(index) ? 'done' : ''}>
+            <span className="workout-check" role="checkbox" aria-checked={done.includes(index)} onClick={() => toggle(index)}>
+              {done.includes(index) ? '[x]' : '[ ]'}
+            </span>
+            <span className="workout-title">
+              {item.title} <em>- {item.minutes} min</em>
+              <small>{item.reason}</small>
+            </span>
+            <XPButton onClick={() => start(index)}>Start</XPButton>
+          </li>
+        ))}
+      </ol>
+    </XPPanel>
+  );
+};
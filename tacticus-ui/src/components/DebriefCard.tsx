@@ -0,0 +1,92 @@
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import type { GameDebrief, OperationRecord } from '../lib/ai/types';
+
+interface DebriefCardProps {
+  debrief: GameDebrief;
+  onReview: () => void;
+  onTrain: () => void;
+  onDismiss: () => void;
+}
+
+const RESULT_LABELS: Record<string, string> = {
+  win: 'You won',
+  loss: 'You lost',
+  draw: 'Draw',
+};
+
+/** Pops up once a finished game has been analysed, with Gurgeh's three takeaways */
+export const DebriefCard: React.FC<DebriefCardProps> = ({ debrief, onReview, onTrain, onDismiss }) => {
+  const [reportStatus, setReportStatus] = useState<string | null>(null);
+  /** The game's deletion, offered for undo while the card is open */
+  const [deleted, setDeleted] = useState<OperationRecord | null>(null);
+
+  const handleReport = async (format: 'html' | 'pdf') => {
+    setReportStatus('[i] Writing report...');
+    try {
+      const result = await invoke<{ path: string }>('export_game_report', { gameId: debrief.game_id, format });
+      setReportStatus(`[OK] Saved to ${result.path}`);
+    } catch (err) {
+      setReportStatus(`[!] Report failed: ${err}`);
+    }
+  };
+
+  const handleDelete = async () => {
+    try {
+      setDeleted(await invoke<OperationRecord | null>('delete_game', { gameId: debrief.game_id }));
+      setReportStatus(null);
+    } catch (err) {
+      setReportStatus(`[!] Delete failed: ${err}`);
+    }
+  };
+
+  const handleUndo = async () => {
+    try {
+      const undone = await invoke<OperationRecord | null>('undo_last_operation');
+      setDeleted(null);
+      if (!undone) setReportStatus('[!] Too late to undo');
+    } catch (err) {
+      setReportStatus(`[!] Undo failed: ${err}`);
+    }
+  };
+
+  if (deleted) {
+    return (
+      <div className="debrief-card">
+        <div className="debrief-title">[G] {deleted.label}.</div>
+        <div className="debrief-actions">
+          <XPButton primary onClick={handleUndo}>Undo</XPButton>
+          <XPButton onClick={onDismiss}>Close</XPButton>
+        </div>
+      </div>
+    );
+  }
+
+  return (
+    <div className="debrief-card">
+      <div className="debrief-title">
+        [G] Review your game - {RESULT_LABELS[debrief.result] ?? debrief.result}
+      </div>
+      <ul className="debrief-bullets">
+        {debrief.bullets.map((bullet) => (
+          <li key={bullet}>{bullet}</li>
+        ))}
+      </ul>
+      {debrief.source === 'engine' && (
+        <p className="debrief-note">[i] Engine summary - add an API key for Gurgeh's own debrief.</p>
+      )}
+      <div className="debrief-actions">
+        <XPButton primary onClick={onReview}>Review with Gurgeh</XPButton>
+        <XPButton onClick={onTrain}>Train {debrief.puzzle_theme}</XPButton>
+        <XPButton onClick={onDismiss}>Later</XPButton>
+      </div>
+      <div className="debrief-actions">
+        <XPButton onClick={() => handleReport('html')}>Report (HTML)</XPButton>
+        <XPButton onClick={() => handleReport('pdf')}>Report (PDF)</XPButton>
+        <XPButton onClick={handleDelete}>Delete game</XPButton>
+      </div>
+      {reportStatus && <p className="debrief-note">{reportStatus}</p>}
+    </div>
+  );
+};
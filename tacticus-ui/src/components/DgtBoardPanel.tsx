@@ -0,0 +1,166 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { useGameStore } from '../stores/gameStore';
+
+type DgtSyncStatus = 'synced' | 'move_in_progress' | 'out_of_sync';
+
+interface DgtBoardStatus {
+  connected: boolean;
+  port: string | null;
+  sync: DgtSyncStatus | null;
+  fen: string | null;
+}
+
+type DgtEvent =
+  | { kind: 'move'; uci: string; san: string; fen: string }
+  | { kind: 'illegal'; expected_fen: string }
+  | { kind: 'resynced'; fen: string };
+
+const POLL_MS = 250;
+
+const SYNC_LABELS: Record<DgtSyncStatus, string> = {
+  synced: 'In sync',
+  move_in_progress: 'Move in progress...',
+  out_of_sync: 'Out of sync',
+};
+
+/** The position the board should show: the retry drill's while one is open, otherwise the game's */
+function displayedFen(): string | null {
+  const { retry, gameState } = useGameStore.getState();
+  return retry ? retry.drill.fen : gameState?.fen ?? null;
+}
+
+/** Play the game on a DGT electronic board. Moves made on the board go through the game store
+ * like clicks would; the board is told about every other position change and reports when the
+ * pieces don't match. */
+export const DgtBoardPanel: React.FC = () => {
+  const [ports, setPorts] = useState<string[]>([]);
+  const [port, setPort] = useState('');
+  const [status, setStatus] = useState<DgtBoardStatus | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const fen = useGameStore((state) => (state.retry ? state.retry.drill.fen : state.gameState?.fen));
+  const playerColor = useGameStore((state) => state.playerColor);
+  const connected = !!status?.connected;
+
+  useEffect(() => {
+    invoke<DgtBoardStatus>('get_dgt_status')
+      .then(setStatus)
+      .catch((err) => console.error('Failed to load board status:', err));
+  }, []);
+
+  const refreshPorts = () => {
+    invoke<string[]>('list_dgt_ports')
+      .then((found) => {
+        setPorts(found);
+        setPort((current) => (found.includes(current) ? current : found[0] ?? ''));
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  useEffect(refreshPorts, []);
+
+  const handleConnect = async () => {
+    if (!fen || !port) return;
+    setError(null);
+    try {
+      setStatus(await invoke<DgtBoardStatus>('connect_dgt_board', { port, fen, playerColor }));
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleDisconnect = async () => {
+    try {
+      await invoke('disconnect_dgt_board');
+    } catch (err) {
+      console.error('Failed to disconnect board:', err);
+    }
+    setStatus(null);
+  };
+
+  // Engine replies, takebacks and retry drills change the position without the board
+  useEffect(() => {
+    if (!connected || !fen) return;
+    invoke<DgtBoardStatus>('sync_dgt_board', { fen, playerColor })
+      .then(setStatus)
+      .catch((err) => console.error('Failed to sync board:', err));
+  }, [connected, fen, playerColor]);
+
+  useEffect(() => {
+    if (!connected) return;
+
+    const handleEvent = (event: DgtEvent) => {
+      switch (event.kind) {
+        case 'move': {
+          const { gameState, playerColor: color, isThinking, retry, makeMove, retryMove } = useGameStore.getState();
+          const from = event.uci.slice(0, 2);
+          const to = event.uci.slice(2, 4);
+          const promotion = event.uci.slice(4) || undefined;
+          setStatus((current) => current && { ...current, sync: 'synced', fen: event.fen });
+          if (retry) {
+            retryMove(from, to, promotion);
+          } else if (gameState && gameState.turn === color && !isThinking) {
+            makeMove(from, to, promotion);
+            return;
+          }
+          // Not the player's move to make: put the board session back on the displayed position
+          const expected = displayedFen();
+          if (expected) {
+            invoke<DgtBoardStatus>('sync_dgt_board', { fen: expected, playerColor: color })
+              .then(setStatus)
+              .catch((err) => console.error('Failed to sync board:', err));
+          }
+          break;
+        }
+        case 'illegal':
+          setStatus((current) => current && { ...current, sync: 'out_of_sync', fen: event.expected_fen });
+          break;
+        case 'resynced':
+          setStatus((current) => current && { ...current, sync: 'synced', fen: event.fen });
+          break;
+      }
+    };
+
+    const timer = setInterval(() => {
+      invoke<DgtEvent[]>('poll_dgt_events')
+        .then((events) => events.forEach(handleEvent))
+        .catch((err) => {
+          setError(String(err));
+          setStatus(null);
+        });
+    }, POLL_MS);
+    return () => clearInterval(timer);
+  }, [connected]);
+
+  return (
+    <div className="dgt-board">
+      <label>DGT Board</label>
+      {connected ? (
+        <>
+          <div className="dgt-board-row">
+            <span>{status?.port}: {status?.sync ? SYNC_LABELS[status.sync] : 'Connecting...'}</span>
+            <XPButton onClick={handleDisconnect}>Disconnect</XPButton>
+          </div>
+          {status?.sync === 'out_of_sync' && (
+            <div className="dgt-board-banner">
+              [!] The board doesn't match the game. Set the pieces up as shown on screen to carry on.
+            </div>
+          )}
+        </>
+      ) : (
+        <div className="dgt-board-row">
+          <select value={port} onChange={(e) => setPort(e.target.value)} disabled={ports.length === 0}>
+            {ports.length === 0 && <option value="">No boards found</option>}
+            {ports.map((p) => (
+              <option key={p} value={p}>{p}</option>
+            ))}
+          </select>
+          <XPButton onClick={refreshPorts}>Refresh</XPButton>
+          <XPButton primary onClick={handleConnect} disabled={!port || !fen}>Connect</XPButton>
+        </div>
+      )}
+      {error && <p className="dgt-board-error">{error}</p>}
+    </div>
+  );
+};
@@ -0,0 +1,113 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import type { Encouragement, EncouragementSettings } from '../lib/ai/types';
+
+const MOOD_LABELS: Record<Encouragement['mood'], string> = {
+  steady: 'Steady',
+  frustrated: 'Struggling - Gurgeh is going gently',
+  on_a_roll: 'On a roll - Gurgeh is raising the bar',
+};
+
+/** Tuning for how Gurgeh's tone and session difficulty follow frustration and streaks */
+export const EncouragementPanel: React.FC = () => {
+  const [settings, setSettings] = useState<EncouragementSettings | null>(null);
+  const [current, setCurrent] = useState<Encouragement | null>(null);
+
+  const loadCurrent = () => {
+    invoke<Encouragement>('get_encouragement')
+      .then(setCurrent)
+      .catch((err) => console.error('Failed to read encouragement signals:', err));
+  };
+
+  useEffect(() => {
+    invoke<EncouragementSettings>('get_encouragement_settings')
+      .then(setSettings)
+      .catch((err) => console.error('Failed to load encouragement settings:', err));
+    loadCurrent();
+  }, []);
+
+  const update = async (changes: Partial<EncouragementSettings>) => {
+    if (!settings) return;
+    const next = { ...settings, ...changes };
+    try {
+      await invoke('set_encouragement_settings', { settings: next });
+      setSettings(next);
+      loadCurrent();
+    } catch (err) {
+      console.error('Failed to update encouragement settings:', err);
+    }
+  };
+
+  if (!settings) return null;
+
+  return (
+    <XPPanel label="Encouragement" className="encouragement-section">
+      <p className="settings-description">
+        Gurgeh watches for frustration - failed puzzles in a row, a dip in your session rating, sessions
+        abandoned or games resigned early - and goes gentler, with easier puzzles, until things pick up.
+        On a winning streak it raises the bar instead.
+      </p>
+      <div className="diagnostics-row">
+        <label>
+          <input type="checkbox" checked={settings.enabled} onChange={(e) => update({ enabled: e.target.checked })} />
+          Adapt to how training is going
+        </label>
+      </div>
+      {settings.enabled && (
+        <>
+          <div className="diagnostics-row">
+            <label>
+              <input
+                type="checkbox"
+                checked={settings.adjust_difficulty}
+                onChange={(e) => update({ adjust_difficulty: e.target.checked })}
+              />
+              Change puzzle difficulty too, not just tone
+            </label>
+          </div>
+          <div className="diagnostics-row">
+            <label htmlFor="encouragement-failures">Failed puzzles in a row</label>
+            <input
+              id="encouragement-failures"
+              type="number"
+              min={1}
+              max={10}
+              value={settings.failure_streak}
+              onChange={(e) => update({ failure_streak: Number(e.target.value) })}
+            />
+          </div>
+          <div className="diagnostics-row">
+            <label htmlFor="encouragement-dip">Rating dip</label>
+            <input
+              id="encouragement-dip"
+              type="number"
+              min={25}
+              max={500}
+              step={25}
+              value={settings.rating_dip}
+              onChange={(e) => update({ rating_dip: Number(e.target.value) })}
+            />
+          </div>
+          <div className="diagnostics-row">
+            <label htmlFor="encouragement-quits">Quits in a day</label>
+            <input
+              id="encouragement-quits"
+              type="number"
+              min={1}
+              max={10}
+              value={settings.quits}
+              onChange={(e) => update({ quits: Number(e.target.value) })}
+            />
+          </div>
+          {current && (
+            <p className="settings-description">
+              Right now: {MOOD_LABELS[current.mood]}
+              {current.reasons.length > 0 && ` (${current.reasons.join(', ')})`}
+            </p>
+          )}
+        </>
+      )}
+    </XPPanel>
+  );
+};
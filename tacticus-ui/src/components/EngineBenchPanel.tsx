@@ -0,0 +1,86 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+
+interface BenchReport {
+  engine_name: string;
+  engine_version: string;
+  config: { max_depth: number; time_per_position_ms: number };
+  nodes: number;
+  elapsed_ms: number;
+  nps: number;
+  agreement: number;
+}
+
+interface BenchRun {
+  id: number;
+  created_at: string;
+  report: BenchReport;
+}
+
+interface BenchOutcome {
+  run: BenchRun;
+  previous: BenchRun | null;
+  regressions: string[];
+}
+
+/** Engine speed and accuracy over a fixed suite, kept across runs */
+export const EngineBenchPanel: React.FC = () => {
+  const [history, setHistory] = useState<BenchRun[]>([]);
+  const [running, setRunning] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const loadHistory = () => {
+    invoke<BenchRun[]>('get_bench_history', { limit: 5 })
+      .then(setHistory)
+      .catch((err) => console.error('Failed to load bench history:', err));
+  };
+
+  useEffect(loadHistory, []);
+
+  const handleRun = async () => {
+    setRunning(true);
+    try {
+      const outcome = await invoke<BenchOutcome>('run_engine_bench');
+      setStatus(
+        outcome.regressions.length > 0
+          ? `[!] ${outcome.regressions.join('; ')}`
+          : `[OK] ${outcome.run.report.nps.toLocaleString()} nodes/s`
+      );
+      loadHistory();
+    } catch (err) {
+      setStatus(`[!] ${err}`);
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <XPPanel label="Engine Benchmark" className="engine-bench-section">
+      <p className="settings-description">
+        Searches a fixed set of positions to measure speed. Deeper analysis settings need more nodes per second.
+      </p>
+      {history.length > 0 && (
+        <table className="storage-table">
+          <tbody>
+            {history.map((run) => (
+              <tr key={run.id}>
+                <td>{new Date(run.created_at).toLocaleString()}</td>
+                <td>{run.report.engine_version}</td>
+                <td>{run.report.nps.toLocaleString()} nps</td>
+                <td>{Math.round(run.report.agreement * 100)}% best moves</td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+      <div className="diagnostics-row">
+        <XPButton onClick={handleRun} disabled={running}>
+          {running ? 'Running...' : 'Run Benchmark'}
+        </XPButton>
+      </div>
+      {status && <p className="settings-description">{status}</p>}
+    </XPPanel>
+  );
+};
@@ -0,0 +1,114 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+
+interface RatingBand {
+  from: number;
+  to: number;
+  attempted: number;
+  solved: number;
+}
+
+type StrengthEstimate =
+  | { kind: 'below'; rating: number }
+  | { kind: 'rated'; rating: number }
+  | { kind: 'above'; rating: number };
+
+interface StrengthAssessment {
+  elo: number | null;
+  depth: number;
+  puzzles: { name: string; rating: number; played: string; solved: boolean }[];
+  bands: RatingBand[];
+  solved: number;
+  estimate: StrengthEstimate;
+  fifty_percent_band: RatingBand | null;
+}
+
+interface StrengthReport {
+  engine_version: string;
+  depth: number;
+  personality: string;
+  created_at: string;
+  assessments: StrengthAssessment[];
+}
+
+const describeEstimate = (assessment: StrengthAssessment): string => {
+  const { estimate, fifty_percent_band: band } = assessment;
+  switch (estimate.kind) {
+    case 'below':
+      return `below ${estimate.rating}`;
+    case 'above':
+      return `above ${estimate.rating}`;
+    case 'rated':
+      return band ? `about ${estimate.rating} (${band.from}-${band.to})` : `about ${estimate.rating}`;
+  }
+};
+
+/** What each engine Elo setting plays like on rated puzzles */
+export const EngineStrengthPanel: React.FC = () => {
+  const [report, setReport] = useState<StrengthReport | null>(null);
+  const [running, setRunning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<StrengthReport | null>('get_engine_strength')
+      .then(setReport)
+      .catch((err) => console.error('Failed to load engine strength:', err));
+  }, []);
+
+  const handleMeasure = async () => {
+    setRunning(true);
+    setError(null);
+    try {
+      setReport(await invoke<StrengthReport>('assess_engine_strength'));
+    } catch (err) {
+      setError(`[!] ${err}`);
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <XPPanel label="Engine Strength" className="engine-strength-section">
+      <p className="settings-description">
+        Plays each engine Elo setting on a set of rated puzzles and reports the puzzle rating it
+        scores about half against, so you know what an opponent setting means.
+      </p>
+      {report ? (
+        <table className="storage-table">
+          <thead>
+            <tr>
+              <th>Setting</th>
+              <th>Plays puzzles like</th>
+              <th>Solved</th>
+            </tr>
+          </thead>
+          <tbody>
+            {report.assessments.map((assessment) => (
+              <tr key={assessment.elo ?? 'full'}>
+                <td>{assessment.elo ?? 'Full strength'}</td>
+                <td>{describeEstimate(assessment)}</td>
+                <td>{assessment.solved}/{assessment.puzzles.length}</td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      ) : (
+        <p className="settings-description">Not measured for the current engine settings yet.</p>
+      )}
+      {report && (
+        <p className="settings-description">
+          Depth {report.depth}, {report.personality} personality, engine {report.engine_version},
+          measured {new Date(report.created_at).toLocaleString()}.
+        </p>
+      )}
+      <div className="diagnostics-row">
+        <XPButton onClick={handleMeasure} disabled={running}>
+          {running ? 'Measuring...' : report ? 'Measure Again' : 'Measure Strength'}
+        </XPButton>
+      </div>
+      {error && <p className="settings-description">{error}</p>}
+    </XPPanel>
+  );
+};
@@ -0,0 +1,51 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import type { GameAssistance } from '../stores/gameStore';
+
+const OPTIONS: { value: GameAssistance; label: string }[] = [
+  { value: 'off', label: 'Off - play on your own' },
+  { value: 'hints', label: 'Hints - ask what the opponent is planning' },
+  { value: 'retries', label: 'Retries - hints, and a second try after a blunder' },
+];
+
+/** How much help casual games give */
+export const GameAssistancePanel: React.FC = () => {
+  const [assistance, setAssistance] = useState<GameAssistance>('hints');
+
+  useEffect(() => {
+    invoke<GameAssistance>('get_game_assistance')
+      .then(setAssistance)
+      .catch((err) => console.error('Failed to load game assistance:', err));
+  }, []);
+
+  const handleChange = async (value: GameAssistance) => {
+    try {
+      await invoke('set_game_assistance', { assistance: value });
+      setAssistance(value);
+    } catch (err) {
+      console.error('Failed to update game assistance:', err);
+    }
+  };
+
+  return (
+    <XPPanel label="Game Assistance" className="game-assistance-section">
+      <p className="settings-description">
+        Help offered during casual games. With retries, a blunder pauses the game so you can look for a better
+        move; each retry is recorded as an exercise. Calibration games are always played without help.
+      </p>
+      <div className="diagnostics-row">
+        <label htmlFor="game-assistance">Assistance</label>
+        <select
+          id="game-assistance"
+          value={assistance}
+          onChange={(e) => handleChange(e.target.value as GameAssistance)}
+        >
+          {OPTIONS.map((o) => (
+            <option key={o.value} value={o.value}>{o.label}</option>
+          ))}
+        </select>
+      </div>
+    </XPPanel>
+  );
+};
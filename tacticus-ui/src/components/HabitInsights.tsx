@@ -0,0 +1,85 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+
+interface FeatureUsage {
+  feature: string;
+  total: number;
+  active_days: number;
+}
+
+interface UsageStats {
+  enabled: boolean;
+  period_days: number | null;
+  features: FeatureUsage[];
+  insights: string[];
+}
+
+const FEATURE_LABELS: Record<string, string> = {
+  puzzle_attempted: 'Puzzles attempted',
+  coach_message: 'Coach messages',
+  analysis_run: 'Analyses run',
+  game_played: 'Games played',
+  quiz_answered: 'Quiz answers',
+};
+
+/** Opt-in usage counts kept on this computer, summarised as training-habit insights */
+export const HabitInsights: React.FC = () => {
+  const [stats, setStats] = useState<UsageStats | null>(null);
+
+  const load = async () => {
+    try {
+      setStats(await invoke<UsageStats>('get_usage_stats', { period: 'month' }));
+    } catch (err) {
+      console.error('Failed to load usage stats:', err);
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, []);
+
+  const setEnabled = async (enabled: boolean) => {
+    try {
+      await invoke('set_usage_analytics_enabled', { enabled });
+      await load();
+    } catch (err) {
+      console.error('Failed to update usage analytics:', err);
+    }
+  };
+
+  if (!stats) return null;
+
+  if (!stats.enabled) {
+    return (
+      <XPPanel label="Training Habits" className="habit-insights">
+        <p className="habit-note">
+          Count which features you use to get insights about your training habits.
+          Counts stay on this computer and are never sent anywhere.
+        </p>
+        <XPButton onClick={() => setEnabled(true)}>Turn On</XPButton>
+      </XPPanel>
+    );
+  }
+
+  return (
+    <XPPanel label="Training Habits (last 30 days)" className="habit-insights">
+      {stats.features.length === 0 ? (
+        <p className="habit-note">Nothing recorded yet - check back after a few sessions.</p>
+      ) : (
+        <div className="habit-counts">
+          {stats.features.map((f) => (
+            <span key={f.feature}>
+              {FEATURE_LABELS[f.feature] ?? f.feature}: <strong>{f.total}</strong>
+            </span>
+          ))}
+        </div>
+      )}
+      {stats.insights.map((insight) => (
+        <p key={insight} className="habit-insight">[i] {insight}</p>
+      ))}
+      <XPButton onClick={() => setEnabled(false)}>Turn Off &amp; Delete</XPButton>
+    </XPPanel>
+  );
+};
@@ -3,7 +3,18 @@ import { XPWindow } from './xp/XPWindow';
 import { XPCard } from './xp/XPCard';
 import { XPButton } from './xp/XPButton';
 import { XPProgress } from './xp/XPProgress';
+import { HabitInsights } from './HabitInsights';
+import { DailyWorkoutPanel } from './DailyWorkout';
+import { OpeningLeaksPanel } from './OpeningLeaks';
+import { OpponentStrengthPanel } from './OpponentStrength';
+import { TimeClassHeatmapPanel } from './TimeClassHeatmap';
+import { PositionLibraryPanel } from './PositionLibrary';
+import { ProgressDiffPanel } from './ProgressDiff';
+import { SessionPerformancePanel } from './SessionPerformance';
+import { ThemePerformancePanel } from './ThemePerformance';
+import { TrainingPlanPanel } from './TrainingPlan';
 import { useUserStore } from '../stores/userStore';
+import type { CoachAction } from '../lib/ai/types';
 import './Hub.css';
 
 type View = 'hub' | 'train' | 'play' | 'analyze' | 'learn';
@@ -11,9 +22,19 @@ type View = 'hub' | 'train' | 'play' | 'analyze' | 'learn';
 interface HubProps {
   onNavigate: (view: View) => void;
   onChatClick: () => void;
+  /** Start a training session on one exercise theme */
+  onDrillTheme: (theme: string) => void;
+  /** Open a saved position on the analysis board */
+  onAnalyzePosition: (fen: string) => void;
+  /** Train a saved position as a one-exercise session */
+  onTrainPosition: (positionId: number) => void;
+  /** Train exactly these exercises, e.g. a daily workout item */
+  onTrainExercises: (exerciseIds: number[], focus: string) => void;
+  /** Start a training plan item, as the matching coach button would */
+  onPlanAction: (action: CoachAction) => void;
 }
 
-export const Hub: React.FC<HubProps> = ({ onNavigate, onChatClick }) => {
+export const Hub: React.FC<HubProps> = ({ onNavigate, onChatClick, onDrillTheme, onAnalyzePosition, onTrainPosition, onTrainExercises, onPlanAction }) => {
   const { profile, stats } = useUserStore();
 
   const exercisesUntilCalibration = stats?.exercises_until_calibration ?? 10;
@@ -163,6 +184,17 @@ export const Hub: React.FC<HubProps> = ({ onNavigate, onChatClick }) => {
           </div>
           <XPButton onClick={onChatClick}>Chat with Gurgeh</XPButton>
         </div>
+
+        <DailyWorkoutPanel onNavigate={onNavigate} onTrainExercises={onTrainExercises} />
+        <TrainingPlanPanel onAction={onPlanAction} />
+        <ThemePerformancePanel onDrill={onDrillTheme} />
+        <SessionPerformancePanel />
+        <ProgressDiffPanel />
+        <OpeningLeaksPanel onAction={onPlanAction} />
+        <PositionLibraryPanel onAnalyze={onAnalyzePosition} onTrain={onTrainPosition} />
+        <OpponentStrengthPanel />
+        <TimeClassHeatmapPanel />
+        <HabitInsights />
       </XPWindow>
     </div>
   );
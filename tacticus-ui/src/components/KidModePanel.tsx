@@ -0,0 +1,38 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+
+/** Child-friendly coaching: simpler words, shorter answers, easier puzzles and more time */
+export const KidModePanel: React.FC = () => {
+  const [enabled, setEnabled] = useState(false);
+
+  useEffect(() => {
+    invoke<boolean>('get_kid_mode')
+      .then(setEnabled)
+      .catch((err) => console.error('Failed to load kid mode:', err));
+  }, []);
+
+  const handleChange = async (value: boolean) => {
+    try {
+      await invoke('set_kid_mode', { enabled: value });
+      setEnabled(value);
+    } catch (err) {
+      console.error('Failed to update kid mode:', err);
+    }
+  };
+
+  return (
+    <XPPanel label="Kid Mode" className="kid-mode-section">
+      <p className="settings-description">
+        Gurgeh uses simple words and short answers, keeps to chess and filters its replies more strictly.
+        Puzzles stop at intermediate difficulty and timed exercises allow twice the time.
+      </p>
+      <div className="diagnostics-row">
+        <label>
+          <input type="checkbox" checked={enabled} onChange={(e) => handleChange(e.target.checked)} />
+          Coach a child
+        </label>
+      </div>
+    </XPPanel>
+  );
+};
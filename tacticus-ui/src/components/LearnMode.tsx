@@ -5,6 +5,7 @@ import { XPButton } from './xp/XPButton';
 import { XPPanel } from './xp/XPPanel';
 import { XPInput } from './xp/XPInput';
 import { ChessBoard } from './board/ChessBoard';
+import { OpeningWalkthroughPanel } from './OpeningWalkthrough';
 import './LearnMode.css';
 
 interface ChessConcept {
@@ -28,9 +29,13 @@ interface ConceptCategory {
 
 interface LearnModeProps {
   onBack: () => void;
+  /** Concept to open straight away, e.g. from a coach deep link */
+  initialConceptId?: string | null;
+  /** Start training on a concept's practice exercises */
+  onPractice?: (conceptId: string) => void;
 }
 
-export const LearnMode: React.FC<LearnModeProps> = ({ onBack }) => {
+export const LearnMode: React.FC<LearnModeProps> = ({ onBack, initialConceptId, onPractice }) => {
   const [categories, setCategories] = useState<ConceptCategory[]>([]);
   const [concepts, setConcepts] = useState<ChessConcept[]>([]);
   const [selectedCategory, setSelectedCategory] = useState<string | null>(null);
@@ -42,6 +47,18 @@ export const LearnMode: React.FC<LearnModeProps> = ({ onBack }) => {
     loadConcepts();
   }, []);
 
+  useEffect(() => {
+    if (!initialConceptId) return;
+    invoke<ChessConcept | null>('get_concept', { conceptId: initialConceptId })
+      .then(concept => {
+        if (concept) {
+          setSelectedCategory(null);
+          setSelectedConcept(concept);
+        }
+      })
+      .catch(err => console.error('Failed to open concept:', err));
+  }, [initialConceptId]);
+
   const loadConcepts = async () => {
     try {
       const [cats, cons] = await Promise.all([
@@ -212,13 +229,19 @@ export const LearnMode: React.FC<LearnModeProps> = ({ onBack }) => {
 
                 <div className="concept-actions">
                   <XPButton onClick={onBack}>Back to Hub</XPButton>
-                  {selectedConcept.practice_exercises.length > 0 && (
-                    <XPButton primary>Practice This Concept</XPButton>
+                  {selectedConcept.practice_exercises.length > 0 && onPractice && (
+                    <XPButton primary onClick={() => onPractice(selectedConcept.id)}>Practice This Concept</XPButton>
                   )}
                 </div>
               </div>
             </XPWindow>
           </div>
+        ) : selectedCategory === 'Openings' ? (
+          <div className="concept-detail-container">
+            <XPWindow title="Learn an Opening" icon="[O]" width={500} height="auto">
+              <OpeningWalkthroughPanel />
+            </XPWindow>
+          </div>
         ) : (
           <div className="concept-placeholder">
             <XPWindow title="Learn Chess" icon="[L]" width={500} height={300}>
@@ -230,7 +253,7 @@ export const LearnMode: React.FC<LearnModeProps> = ({ onBack }) => {
                 <ul>
                   <li><strong>Tactics</strong> - Forks, pins, skewers, and more</li>
                   <li><strong>Strategy</strong> - Positional play and planning</li>
-                  <li><strong>Openings</strong> - Opening principles and common lines</li>
+                  <li><strong>Openings</strong> - Opening principles, and common lines to walk through move by move</li>
                   <li><strong>Endgames</strong> - Technical positions and key concepts</li>
                 </ul>
               </div>
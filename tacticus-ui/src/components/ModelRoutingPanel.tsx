@@ -0,0 +1,69 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+
+interface ModelRoute {
+  task: string;
+  override_model: string | null;
+  default_models: string[];
+}
+
+const TASK_LABELS: Record<string, string> = {
+  chat: 'Chat',
+  definition: 'Definitions',
+  position_analysis: 'Position analysis',
+};
+
+/** Per-task model overrides for Gurgeh; blank uses the defaults */
+export const ModelRoutingPanel: React.FC = () => {
+  const [routes, setRoutes] = useState<ModelRoute[]>([]);
+  const [drafts, setDrafts] = useState<Record<string, string>>({});
+  const [saved, setSaved] = useState(false);
+
+  useEffect(() => {
+    invoke<ModelRoute[]>('get_model_routing')
+      .then((result) => {
+        setRoutes(result);
+        setDrafts(Object.fromEntries(result.map((r) => [r.task, r.override_model ?? ''])));
+      })
+      .catch((err) => console.error('Failed to load model routing:', err));
+  }, []);
+
+  const handleSave = async () => {
+    try {
+      for (const route of routes) {
+        const model = drafts[route.task]?.trim();
+        await invoke('set_model_override', { task: route.task, model: model || null });
+      }
+      setSaved(true);
+      setTimeout(() => setSaved(false), 1500);
+    } catch (err) {
+      console.error('Failed to save model overrides:', err);
+    }
+  };
+
+  return (
+    <XPPanel label="Coach Models" className="model-routing-section">
+      <p className="settings-description">
+        Quick replies use a fast model and analysis uses a stronger one. If a model fails,
+        the next default is tried.
+      </p>
+      {routes.map((route) => (
+        <div className="diagnostics-row" key={route.task}>
+          <label htmlFor={`model-${route.task}`} className="model-routing-label">
+            {TASK_LABELS[route.task] ?? route.task}
+          </label>
+          <input
+            id={`model-${route.task}`}
+            className="model-routing-input"
+            value={drafts[route.task] ?? ''}
+            placeholder={route.default_models[0]}
+            onChange={(e) => setDrafts({ ...drafts, [route.task]: e.target.value })}
+          />
+        </div>
+      ))}
+      <XPButton onClick={handleSave}>{saved ? 'Saved!' : 'Save Models'}</XPButton>
+    </XPPanel>
+  );
+};
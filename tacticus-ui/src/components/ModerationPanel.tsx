@@ -0,0 +1,57 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import type { ModerationPolicy } from '../lib/ai/types';
+
+/** What Gurgeh's replies are filtered for before they are shown */
+export const ModerationPanel: React.FC = () => {
+  const [policy, setPolicy] = useState<ModerationPolicy | null>(null);
+
+  useEffect(() => {
+    invoke<ModerationPolicy>('get_moderation_policy')
+      .then(setPolicy)
+      .catch((err) => console.error('Failed to load moderation policy:', err));
+  }, []);
+
+  const update = async (changes: Partial<ModerationPolicy>) => {
+    if (!policy) return;
+    const next = { ...policy, ...changes };
+    try {
+      await invoke('update_moderation_policy', { policy: next });
+      setPolicy(next);
+    } catch (err) {
+      console.error('Failed to update moderation policy:', err);
+    }
+  };
+
+  if (!policy) return null;
+
+  return (
+    <XPPanel label="Reply Filtering" className="moderation-section">
+      <p className="settings-description">
+        Filtered replies appear once they are complete rather than word by word. Kid mode always masks a
+        stricter word list and keeps the coach to chess.
+      </p>
+      <div className="diagnostics-row">
+        <label>
+          <input
+            type="checkbox"
+            checked={policy.filter_profanity}
+            onChange={(e) => update({ filter_profanity: e.target.checked })}
+          />
+          Mask profanity
+        </label>
+      </div>
+      <div className="diagnostics-row">
+        <label>
+          <input
+            type="checkbox"
+            checked={policy.block_off_topic}
+            onChange={(e) => update({ block_off_topic: e.target.checked })}
+          />
+          Keep replies off financial, medical and legal advice
+        </label>
+      </div>
+    </XPPanel>
+  );
+};
@@ -0,0 +1,84 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+
+interface MoveQualitySettings {
+  thresholds: number[];
+  scale_by_rating: boolean;
+  account_for_sharpness: boolean;
+}
+
+const BUCKETS = ['Brilliant', 'Great', 'Good', 'Inaccuracy', 'Mistake'];
+
+/** Centipawn-loss buckets game analysis judges moves by */
+export const MoveQualityPanel: React.FC = () => {
+  const [settings, setSettings] = useState<MoveQualitySettings | null>(null);
+  const [status, setStatus] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<MoveQualitySettings>('get_move_quality_settings')
+      .then(setSettings)
+      .catch((err) => console.error('Failed to load move quality settings:', err));
+  }, []);
+
+  if (!settings) return null;
+
+  const setThreshold = (index: number, value: string) => {
+    const thresholds = [...settings.thresholds];
+    thresholds[index] = Number(value);
+    setSettings({ ...settings, thresholds });
+  };
+
+  const handleSave = async () => {
+    try {
+      setSettings(await invoke<MoveQualitySettings>('set_move_quality_settings', { settings }));
+      setStatus('Saved. Re-analyze games to apply it to games already reviewed.');
+    } catch (err) {
+      setStatus(`[!] ${err}`);
+    }
+  };
+
+  return (
+    <XPPanel label="Move Quality" className="move-quality-section">
+      <p className="settings-description">
+        Largest centipawn loss for each judgement; anything beyond Mistake is a blunder.
+      </p>
+      {BUCKETS.map((bucket, index) => (
+        <div className="diagnostics-row" key={bucket}>
+          <label htmlFor={`quality-${bucket}`} className="model-routing-label">{bucket}</label>
+          <input
+            id={`quality-${bucket}`}
+            type="number"
+            min={0}
+            className="storage-limit"
+            value={settings.thresholds[index]}
+            onChange={(e) => setThreshold(index, e.target.value)}
+          />
+        </div>
+      ))}
+      <div className="diagnostics-row">
+        <label>
+          <input
+            type="checkbox"
+            checked={settings.scale_by_rating}
+            onChange={(e) => setSettings({ ...settings, scale_by_rating: e.target.checked })}
+          />
+          Go easier at lower ratings
+        </label>
+      </div>
+      <div className="diagnostics-row">
+        <label>
+          <input
+            type="checkbox"
+            checked={settings.account_for_sharpness}
+            onChange={(e) => setSettings({ ...settings, account_for_sharpness: e.target.checked })}
+          />
+          Go easier in positions that are already decided
+        </label>
+      </div>
+      <XPButton onClick={handleSave}>Save Move Quality</XPButton>
+      {status && <p className="settings-description">{status}</p>}
+    </XPPanel>
+  );
+};
@@ -0,0 +1,82 @@
+import React, { useEffect, useState } from 'react';
+import { XPPanel } from './xp/XPPanel';
+import { NotationPrefs, useAppearanceStore } from '../stores/appearanceStore';
+
+const LANGUAGES: { value: NotationPrefs['language']; label: string }[] = [
+  { value: 'en', label: 'English (K Q R B N)' },
+  { value: 'de', label: 'German (K D T L S)' },
+  { value: 'fr', label: 'French (R D T F C)' },
+  { value: 'es', label: 'Spanish (R D T A C)' },
+  { value: 'it', label: 'Italian (R D T A C)' },
+];
+
+/** How moves are written in move lists, coach replies and reports, and board coordinates */
+export const NotationPanel: React.FC = () => {
+  const { notation, loadNotation, setNotation } = useAppearanceStore();
+  const [status, setStatus] = useState<string | null>(null);
+
+  useEffect(() => {
+    loadNotation();
+  }, [loadNotation]);
+
+  const handleChange = async (change: Partial<NotationPrefs>) => {
+    if (!notation) return;
+    try {
+      await setNotation({ ...notation, ...change });
+      setStatus(null);
+    } catch (err) {
+      setStatus(`[!] ${err}`);
+    }
+  };
+
+  if (!notation) return null;
+
+  return (
+    <XPPanel label="Notation" className="notation-section">
+      <div className="diagnostics-row">
+        <label htmlFor="notation-moves">Moves</label>
+        <select
+          id="notation-moves"
+          value={notation.moves}
+          onChange={(e) => handleChange({ moves: e.target.value as NotationPrefs['moves'] })}
+        >
+          <option value="san">Short (Nf3)</option>
+          <option value="long_algebraic">Long (Ng1-f3)</option>
+        </select>
+        <label htmlFor="notation-pieces">Pieces</label>
+        <select
+          id="notation-pieces"
+          value={notation.pieces}
+          onChange={(e) => handleChange({ pieces: e.target.value as NotationPrefs['pieces'] })}
+        >
+          <option value="letters">Letters</option>
+          <option value="figurine">Figurines (♘f3)</option>
+        </select>
+        <select
+          aria-label="Piece letters"
+          value={notation.language}
+          disabled={notation.pieces === 'figurine'}
+          onChange={(e) => handleChange({ language: e.target.value as NotationPrefs['language'] })}
+        >
+          {LANGUAGES.map((language) => (
+            <option key={language.value} value={language.value}>{language.label}</option>
+          ))}
+        </select>
+      </div>
+      <div className="diagnostics-row">
+        <label>
+          <input
+            type="checkbox"
+            checked={notation.coordinates}
+            onChange={(e) => handleChange({ coordinates: e.target.checked })}
+          />
+          Show board coordinates
+        </label>
+      </div>
+      <p className="settings-description">
+        PGN exports and what Tacticus sends the coach stay in standard English notation.
+      </p>
+      {status && <p className="settings-description">{status}</p>}
+    </XPPanel>
+  );
+};
@@ -1,7 +1,9 @@
 import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { XPWindow } from './xp/XPWindow';
 import { XPButton } from './xp/XPButton';
 import { XPInput } from './xp/XPInput';
+import { ChessBoard } from './board/ChessBoard';
 import { useUserStore } from '../stores/userStore';
 import './Onboarding.css';
 
@@ -9,45 +11,168 @@ interface OnboardingProps {
   onComplete: () => void;
 }
 
+interface ExerciseData {
+  id: number;
+  title: string;
+  description: string;
+  difficulty: string;
+  exercise_type: string;
+  fen: string;
+}
+
+interface CalibrationEstimate {
+  rating: number;
+  weaknesses: string[];
+  strengths: string[];
+}
+
+type OnboardingStep =
+  | { kind: 'puzzle'; number: number; total: number; exercise: ExerciseData }
+  | { kind: 'game'; opponent_elo: number }
+  | { kind: 'complete'; estimate: CalibrationEstimate };
+
+interface ExerciseResult {
+  correct: boolean;
+  explanation: string;
+  correct_move: string | null;
+}
+
+interface OnboardingProgress {
+  feedback: ExerciseResult | null;
+  next: OnboardingStep;
+}
+
+interface GameState {
+  fen: string;
+  turn: string;
+  is_checkmate: boolean;
+  is_stalemate: boolean;
+}
+
+interface MoveResult {
+  success: boolean;
+  new_state: GameState | null;
+  error: string | null;
+}
+
+interface EngineMove {
+  uci: string;
+}
+
 export const Onboarding: React.FC<OnboardingProps> = ({ onComplete }) => {
-  const [step, setStep] = useState(0);
   const [name, setName] = useState('');
-  const [level, setLevel] = useState<string | null>(null);
-  const [customElo, setCustomElo] = useState('');
-  const { createProfile, loading } = useUserStore();
+  const [step, setStep] = useState<OnboardingStep | null>(null);
+  const [pendingStep, setPendingStep] = useState<OnboardingStep | null>(null);
+  const [feedback, setFeedback] = useState<ExerciseResult | null>(null);
+  const [game, setGame] = useState<GameState | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+  const { finishOnboarding, loading } = useUserStore();
 
-  const handleLevelSelect = (selectedLevel: string) => {
-    setLevel(selectedLevel);
+  const handleStart = async () => {
+    setError(null);
+    try {
+      setStep(await invoke<OnboardingStep>('start_onboarding', { name }));
+    } catch (err) {
+      setError(String(err));
+    }
   };
 
-  const handleComplete = async () => {
-    const finalLevel = level === 'custom' ? customElo : level;
-    if (name && finalLevel) {
-      await createProfile(name, finalLevel);
-      onComplete();
+  const submit = async (answer: object) => {
+    setBusy(true);
+    setError(null);
+    try {
+      const progress = await invoke<OnboardingProgress>('submit_onboarding_step', { answer });
+      if (progress.feedback) {
+        // Hold the next step until the player has read the explanation
+        setFeedback(progress.feedback);
+        setPendingStep(progress.next);
+      } else {
+        setStep(progress.next);
+      }
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
     }
   };
 
+  const handlePuzzleMove = (from: string, to: string, promotion?: string): boolean => {
+    if (busy || feedback) return false;
+    submit({ kind: 'puzzle', user_move: from + to + (promotion ?? '') });
+    return true;
+  };
+
+  const handleNextPuzzle = () => {
+    setFeedback(null);
+    setStep(pendingStep);
+    setPendingStep(null);
+  };
+
+  const startGame = async () => {
+    setGame(await invoke<GameState>('get_initial_position'));
+  };
+
+  const finishGame = (result: string | null) => {
+    setGame(null);
+    submit({ kind: 'game', result });
+  };
+
+  const handleGameMove = (from: string, to: string, promotion?: string): boolean => {
+    if (!game || busy || step?.kind !== 'game') return false;
+    const opponentElo = step.opponent_elo;
+
+    (async () => {
+      setBusy(true);
+      try {
+        const played = await invoke<MoveResult>('make_move', { fen: game.fen, uciMove: from + to + (promotion ?? '') });
+        if (!played.success || !played.new_state) return;
+        let state = played.new_state;
+        setGame(state);
+        if (state.is_checkmate) return finishGame('win');
+        if (state.is_stalemate) return finishGame('draw');
+
+        const reply = await invoke<EngineMove>('get_engine_move', { fen: state.fen, engineElo: opponentElo });
+        const answered = await invoke<MoveResult>('make_move', { fen: state.fen, uciMove: reply.uci });
+        if (!answered.new_state) return;
+        state = answered.new_state;
+        setGame(state);
+        if (state.is_checkmate) return finishGame('loss');
+        if (state.is_stalemate) return finishGame('draw');
+      } catch (err) {
+        setError(String(err));
+      } finally {
+        setBusy(false);
+      }
+    })();
+    return true;
+  };
+
+  const handleFinish = async () => {
+    await finishOnboarding();
+    onComplete();
+  };
+
   return (
     <div className="onboarding-container">
       <XPWindow
         title="Welcome to Tacticus"
         icon="[K]"
-        width={500}
+        width={step && step.kind !== 'complete' ? 560 : 500}
         height="auto"
       >
-        {step === 0 && (
+        {!step && (
           <div className="onboarding-step">
             <div className="onboarding-gurgeh">
               <div className="gurgeh-avatar">[G]</div>
               <div className="gurgeh-speech">
                 <p>"Welcome to Tacticus. I'm <strong>Gurgeh</strong>, your chess coach.</p>
-                <p>Named after the legendary game player from the Culture, I'll guide you 
+                <p>Named after the legendary game player from the Culture, I'll guide you
                    from wherever you are to wherever you want to be in chess.</p>
                 <p>Together, we'll master this ancient game."</p>
               </div>
             </div>
-            
+
             <div className="onboarding-form">
               <label>What should I call you?</label>
               <XPInput
@@ -58,10 +183,15 @@ export const Onboarding: React.FC<OnboardingProps> = ({ onComplete }) => {
               />
             </div>
 
+            <div className="onboarding-note">
+              <span>[i]</span>
+              <span>Next you'll solve a few short puzzles so I can estimate your rating. Skip any you don't know.</span>
+            </div>
+
             <div className="onboarding-actions">
               <XPButton
                 primary
-                onClick={() => setStep(1)}
+                onClick={handleStart}
                 disabled={!name.trim()}
               >
                 Continue
@@ -70,86 +200,96 @@ export const Onboarding: React.FC<OnboardingProps> = ({ onComplete }) => {
           </div>
         )}
 
-        {step === 1 && (
+        {step?.kind === 'puzzle' && (
           <div className="onboarding-step">
-            <div className="onboarding-gurgeh">
-              <div className="gurgeh-avatar">[G]</div>
-              <div className="gurgeh-speech">
-                <p>"Nice to meet you, <strong>{name}</strong>.</p>
-                <p>What's your current chess experience? This helps me tailor 
-                   exercises and opponents to your level."</p>
+            <div className="calibration-header">
+              <strong>Puzzle {step.number} of {step.total}: {step.exercise.title}</strong>
+              <span className="calibration-meta">{step.exercise.exercise_type} - {step.exercise.difficulty}</span>
+            </div>
+            <p className="calibration-description">{step.exercise.description}</p>
+
+            <ChessBoard
+              fen={step.exercise.fen}
+              onMove={handlePuzzleMove}
+              interactive={!feedback && !busy}
+            />
+
+            {feedback && (
+              <div className={`calibration-feedback ${feedback.correct ? 'correct' : 'incorrect'}`}>
+                <strong>{feedback.correct ? '[OK] Correct' : `[X] The answer was ${feedback.correct_move ?? 'different'}`}</strong>
+                <span>{feedback.explanation}</span>
               </div>
+            )}
+
+            <div className="onboarding-actions">
+              {feedback ? (
+                <XPButton primary onClick={handleNextPuzzle}>Next</XPButton>
+              ) : (
+                <XPButton onClick={() => submit({ kind: 'puzzle', user_move: null })} disabled={busy}>
+                  Skip
+                </XPButton>
+              )}
             </div>
+          </div>
+        )}
 
-            <div className="level-grid">
-              <button
-                className={`level-card ${level === 'beginner' ? 'selected' : ''}`}
-                onClick={() => handleLevelSelect('beginner')}
-              >
-                <span className="level-icon">[B]</span>
-                <span className="level-name">Beginner</span>
-                <span className="level-elo">&lt; 800 ELO</span>
-                <span className="level-desc">I know how pieces move</span>
-              </button>
-
-              <button
-                className={`level-card ${level === 'intermediate' ? 'selected' : ''}`}
-                onClick={() => handleLevelSelect('intermediate')}
-              >
-                <span className="level-icon">[I]</span>
-                <span className="level-name">Intermediate</span>
-                <span className="level-elo">800-1400 ELO</span>
-                <span className="level-desc">I know basic tactics</span>
-              </button>
-
-              <button
-                className={`level-card ${level === 'advanced' ? 'selected' : ''}`}
-                onClick={() => handleLevelSelect('advanced')}
-              >
-                <span className="level-icon">[A]</span>
-                <span className="level-name">Advanced</span>
-                <span className="level-elo">1400-2000 ELO</span>
-                <span className="level-desc">I study openings & endgames</span>
-              </button>
-
-              <button
-                className={`level-card ${level === 'custom' ? 'selected' : ''}`}
-                onClick={() => handleLevelSelect('custom')}
-              >
-                <span className="level-icon">[C]</span>
-                <span className="level-name">Custom</span>
-                <span className="level-elo">Enter your ELO</span>
-                {level === 'custom' && (
-                  <XPInput
-                    value={customElo}
-                    onChange={setCustomElo}
-                    placeholder="e.g. 1200"
-                    type="number"
-                    className="custom-elo-input"
-                  />
+        {step?.kind === 'game' && (
+          <div className="onboarding-step">
+            {!game ? (
+              <>
+                <div className="onboarding-gurgeh">
+                  <div className="gurgeh-avatar">[G]</div>
+                  <div className="gurgeh-speech">
+                    <p>"Good work. If you have a few minutes, play a quick game against me.</p>
+                    <p>I'll play at about <strong>{step.opponent_elo}</strong> based on your puzzles."</p>
+                  </div>
+                </div>
+                <div className="onboarding-actions">
+                  <XPButton onClick={() => finishGame(null)} disabled={busy}>Skip Game</XPButton>
+                  <XPButton primary onClick={startGame} disabled={busy}>Play Quick Game</XPButton>
+                </div>
+              </>
+            ) : (
+              <>
+                <ChessBoard fen={game.fen} onMove={handleGameMove} interactive={!busy && game.turn === 'white'} />
+                <div className="onboarding-actions">
+                  <XPButton onClick={() => finishGame('draw')} disabled={busy}>Offer Draw</XPButton>
+                  <XPButton onClick={() => finishGame('loss')} disabled={busy}>Resign</XPButton>
+                </div>
+              </>
+            )}
+          </div>
+        )}
+
+        {step?.kind === 'complete' && (
+          <div className="onboarding-step">
+            <div className="onboarding-gurgeh">
+              <div className="gurgeh-avatar">[G]</div>
+              <div className="gurgeh-speech">
+                <p>"Thanks, <strong>{name}</strong>. I'd put you at about <strong>{step.estimate.rating}</strong> to start.</p>
+                {step.estimate.weaknesses.length > 0 && (
+                  <p>We'll focus first on: {step.estimate.weaknesses.join(', ')}.</p>
+                )}
+                {step.estimate.strengths.length > 0 && (
+                  <p>You're already solid at: {step.estimate.strengths.join(', ')}."</p>
                 )}
-              </button>
+              </div>
             </div>
 
             <div className="onboarding-note">
               <span>[i]</span>
-              <span>Don't worry - after this we'll play a calibration game to fine-tune your rating.</span>
+              <span>Your rating keeps adjusting as you train and play.</span>
             </div>
 
             <div className="onboarding-actions">
-              <XPButton onClick={() => setStep(0)}>
-                Back
-              </XPButton>
-              <XPButton
-                primary
-                onClick={handleComplete}
-                disabled={!level || (level === 'custom' && !customElo) || loading}
-              >
+              <XPButton primary onClick={handleFinish} disabled={loading}>
                 {loading ? 'Starting...' : 'Begin Training'}
               </XPButton>
             </div>
           </div>
         )}
+
+        {error && <div className="calibration-error">[!] {error}</div>}
       </XPWindow>
     </div>
   );
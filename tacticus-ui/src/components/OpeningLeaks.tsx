@@ -0,0 +1,51 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import { XPButton } from './xp/XPButton';
+import type { CoachAction, OpeningDeviation } from '../lib/ai/types';
+
+interface OpeningLeaksProps {
+  /** Open the game at the deviation */
+  onAction: (action: CoachAction) => void;
+}
+
+const moveLabel = (d: OpeningDeviation, san: string) => `${d.move_number}${san}`;
+
+/** Where recent games left the player's repertoire, and how to carry on next time */
+export const OpeningLeaksPanel: React.FC<OpeningLeaksProps> = ({ onAction }) => {
+  const [deviations, setDeviations] = useState<OpeningDeviation[]>([]);
+
+  useEffect(() => {
+    invoke<OpeningDeviation[]>('get_recent_deviations', { limit: 5 })
+      .then(setDeviations)
+      .catch((err) => console.error('Failed to load opening deviations:', err));
+  }, []);
+
+  if (deviations.length === 0) return null;
+
+  return (
+    <XPPanel label="Fix your opening leaks" className="opening-leaks">
+      <ul className="position-library-list">
+        {deviations.map((d) => (
+          <li key={d.game_id}>
+            <div>
+              <strong>{d.opening_name}</strong>:{' '}
+              {d.by_player ? 'you played' : 'your opponent played'} {moveLabel(d, d.played)} instead of {moveLabel(d, d.expected)}
+              {d.played_worse && <span className="habit-note"> ({(d.centipawn_loss / 100).toFixed(1)} pawns worse)</span>}
+              {d.recommended.length > 0 && (
+                <div className="habit-note">
+                  {d.by_player ? 'The line goes on' : 'Best reply'}: {d.recommended.join(' ')}
+                </div>
+              )}
+            </div>
+            <div className="position-actions">
+              <XPButton onClick={() => onAction({ action_type: 'load_game', game_id: d.game_id, ply: d.ply, fen: d.fen })}>
+                Open game
+              </XPButton>
+            </div>
+          </li>
+        ))}
+      </ul>
+    </XPPanel>
+  );
+};
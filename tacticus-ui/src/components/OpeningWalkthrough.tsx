@@ -0,0 +1,160 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import { XPButton } from './xp/XPButton';
+import { ChessBoard } from './board/ChessBoard';
+import type {
+  OpeningLine,
+  OpeningQuizResult,
+  StepElaboration,
+  WalkthroughSession,
+} from '../lib/ai/types';
+
+interface LogEntry {
+  /** Step of the line the move is explained by, for asking the coach */
+  ply: number | null;
+  text: string;
+}
+
+/** "Learn this opening": play through a line move by move, quizzed on one side's moves */
+export const OpeningWalkthroughPanel: React.FC = () => {
+  const [lines, setLines] = useState<OpeningLine[]>([]);
+  const [selectedKey, setSelectedKey] = useState('');
+  const [color, setColor] = useState<'white' | 'black'>('white');
+  const [session, setSession] = useState<WalkthroughSession | null>(null);
+  const [played, setPlayed] = useState<string[]>([]);
+  const [fen, setFen] = useState('');
+  const [log, setLog] = useState<LogEntry[]>([]);
+  const [mistakes, setMistakes] = useState(0);
+  const [completed, setCompleted] = useState(false);
+  const [explaining, setExplaining] = useState<number | null>(null);
+
+  const loadLines = () => {
+    invoke<OpeningLine[]>('get_opening_lines')
+      .then((loaded) => {
+        setLines(loaded);
+        setSelectedKey((key) => key || loaded[0]?.key || '');
+      })
+      .catch((err) => console.error('Failed to load opening lines:', err));
+  };
+
+  useEffect(loadLines, []);
+
+  const stepFor = (s: WalkthroughSession, uci: string, index: number) =>
+    s.steps.find((step) => step.ply % 2 === index % 2 && step.uci === uci);
+
+  const start = async () => {
+    try {
+      const started = await invoke<WalkthroughSession>('start_opening_walkthrough', { key: selectedKey, color });
+      setSession(started);
+      setPlayed(started.played);
+      setFen(started.fen);
+      setMistakes(0);
+      setCompleted(false);
+      setLog(
+        started.played.map((uci, i) => {
+          const step = stepFor(started, uci, i);
+          return { ply: step?.ply ?? null, text: step?.explanation ?? uci };
+        })
+      );
+    } catch (err) {
+      console.error('Failed to start walkthrough:', err);
+    }
+  };
+
+  const handleMove = (from: string, to: string, promotion?: string): boolean => {
+    if (!session || completed) return false;
+    invoke<OpeningQuizResult>('answer_opening_quiz', {
+      key: session.key,
+      played,
+      answer: `${from}${to}${promotion ?? ''}`,
+      mistakes,
+    })
+      .then((result) => {
+        if (result.verdict === 'wrong') {
+          setMistakes((m) => m + 1);
+          setLog((entries) => [
+            ...entries,
+            { ply: null, text: `That leaves the line. ${result.expected ? `Try ${result.expected}.` : ''}` },
+          ]);
+          return;
+        }
+        const added = result.played.slice(played.length).map((uci, i) => ({
+          ply: stepFor(session, uci, played.length + i)?.ply ?? null,
+          text: result.explanations[i] ?? uci,
+        }));
+        if (result.verdict === 'transposition') {
+          added.unshift({ ply: null, text: 'A different move order, but it reaches the same position.' });
+        }
+        setPlayed(result.played);
+        setFen(result.fen);
+        setLog((entries) => [...entries, ...added]);
+        if (result.completed) {
+          setCompleted(true);
+          loadLines();
+        }
+      })
+      .catch((err) => console.error('Failed to check move:', err));
+    return false;
+  };
+
+  const explain = async (ply: number) => {
+    if (!session) return;
+    setExplaining(ply);
+    try {
+      const elaboration = await invoke<StepElaboration>('explain_opening_step', { key: session.key, ply });
+      setLog((entries) => [...entries, { ply: null, text: `Gurgeh: ${elaboration.text}` }]);
+    } catch (err) {
+      console.error('Failed to explain step:', err);
+    } finally {
+      setExplaining(null);
+    }
+  };
+
+  const selected = lines.find((l) => l.key === selectedKey);
+
+  return (
+    <XPPanel label="Learn an Opening" className="opening-walkthrough">
+      <div className="opening-walkthrough-controls">
+        <select value={selectedKey} onChange={(e) => setSelectedKey(e.target.value)}>
+          {lines.map((line) => (
+            <option key={line.key} value={line.key}>
+              {line.eco} {line.name}
+              {line.progress && line.progress.completions > 0 ? ` (done ${line.progress.completions}x)` : ''}
+            </option>
+          ))}
+        </select>
+        <select value={color} onChange={(e) => setColor(e.target.value as 'white' | 'black')}>
+          <option value="white">Play White</option>
+          <option value="black">Play Black</option>
+        </select>
+        <XPButton primary onClick={start} disabled={!selectedKey}>Start</XPButton>
+      </div>
+      {selected && <div className="habit-note">{selected.moves}</div>}
+
+      {session && (
+        <div className="opening-walkthrough-body">
+          <ChessBoard fen={fen} orientation={session.color} onMove={handleMove} interactive={!completed} />
+          <ul className="opening-walkthrough-log">
+            {log.map((entry, i) => (
+              <li key={i}>
+                {entry.text}
+                {entry.ply !== null && (
+                  <XPButton onClick={() => explain(entry.ply!)} disabled={explaining !== null}>
+                    {explaining === entry.ply ? 'Asking...' : 'Why?'}
+                  </XPButton>
+                )}
+              </li>
+            ))}
+          </ul>
+          {completed && (
+            <div className="opening-walkthrough-done">
+              Line complete with {mistakes} {mistakes === 1 ? 'mistake' : 'mistakes'}.
+              <XPButton onClick={start}>Again</XPButton>
+            </div>
+          )}
+        </div>
+      )}
+    </XPPanel>
+  );
+};
@@ -0,0 +1,53 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import type { OpponentStrengthStats } from '../lib/ai/types';
+
+/** Results against each opponent rating band, next to the player's own rating */
+export const OpponentStrengthPanel: React.FC = () => {
+  const [stats, setStats] = useState<OpponentStrengthStats | null>(null);
+
+  useEffect(() => {
+    invoke<OpponentStrengthStats>('get_opponent_strength_stats')
+      .then(setStats)
+      .catch((err) => console.error('Failed to load opponent strength stats:', err));
+  }, []);
+
+  if (!stats || stats.bands.length === 0) return null;
+
+  return (
+    <XPPanel label={`Results by Opponent Rating (you: ${stats.player_elo})`} className="opponent-strength">
+      <table className="opponent-bands">
+        <thead>
+          <tr>
+            <th>Opponent</th>
+            <th>Games</th>
+            <th>W / D / L</th>
+            <th>Win rate</th>
+            <th>Avg. CP loss</th>
+            <th>How you lose</th>
+          </tr>
+        </thead>
+        <tbody>
+          {stats.bands.map((band) => (
+            <tr key={band.band}>
+              <td>{band.band}</td>
+              <td>{band.games}</td>
+              <td>{band.wins} / {band.draws} / {band.losses}</td>
+              <td>{band.win_rate.toFixed(0)}%</td>
+              <td>{band.average_centipawn_loss === null ? '-' : Math.round(band.average_centipawn_loss)}</td>
+              <td>
+                {band.losing_patterns.length === 0
+                  ? '-'
+                  : band.losing_patterns.map((p) => `${p.description} (${p.games})`).join(', ')}
+              </td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+      {stats.unrated_games > 0 && (
+        <p className="habit-note">[i] {stats.unrated_games} games against unrated opponents are not shown.</p>
+      )}
+    </XPPanel>
+  );
+};
@@ -1,18 +1,29 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { XPWindow } from './xp/XPWindow';
 import { XPButton } from './xp/XPButton';
 import { ChessBoard } from './board/ChessBoard';
-import { useGameStore } from '../stores/gameStore';
+import { BookmarkButton } from './BookmarkButton';
+import { DgtBoardPanel } from './DgtBoardPanel';
+import { useGameStore, toPgnTimeControl, type GameAssistance } from '../stores/gameStore';
 import { useUserStore } from '../stores/userStore';
+import { reportBoard, reportOrientation } from '../lib/boardContext';
 import './PlayMode.css';
 
+interface CoachResponse {
+  message: { content: string };
+  highlights: string[];
+}
+
 interface PlayModeProps {
   onBack: () => void;
   isCalibration?: boolean;
+  /** Skip the setup screen and continue the game already in the store */
+  resumeGame?: boolean;
 }
 
-export const PlayMode: React.FC<PlayModeProps> = ({ onBack, isCalibration = false }) => {
-  const [showConfig, setShowConfig] = useState(true);
+export const PlayMode: React.FC<PlayModeProps> = ({ onBack, isCalibration = false, resumeGame = false }) => {
+  const [showConfig, setShowConfig] = useState(!resumeGame);
   const [selectedColor, setSelectedColor] = useState<'white' | 'black' | 'random'>('random');
   const [timeControl, setTimeControl] = useState('10+0');
   
@@ -23,20 +34,55 @@ export const PlayMode: React.FC<PlayModeProps> = ({ onBack, isCalibration = fals
     makeMove,
     playerColor,
     engineElo,
+    retry,
+    retryMove,
+    resumeFromRetry,
+    setRetriesEnabled,
   } = useGameStore();
   
-  const { stats } = useUserStore();
+  const { stats, apiKey } = useUserStore();
+  const [opponentPlan, setOpponentPlan] = useState<CoachResponse | null>(null);
+  const [planLoading, setPlanLoading] = useState(false);
+  const [assistance, setAssistance] = useState<GameAssistance>('hints');
+
+  // Calibration games are played without help
+  useEffect(() => {
+    if (isCalibration) {
+      setAssistance('off');
+      setRetriesEnabled(false);
+      return;
+    }
+    invoke<GameAssistance>('get_game_assistance')
+      .then((level) => {
+        setAssistance(level);
+        setRetriesEnabled(level === 'retries');
+      })
+      .catch((err) => console.error('Failed to load game assistance:', err));
+    return () => setRetriesEnabled(false);
+  }, [isCalibration, setRetriesEnabled]);
+
+  // The coach can see this board while Play is open
+  useEffect(() => {
+    const { gameState: current, gameHistory, playerColor: color } = useGameStore.getState();
+    if (current) reportBoard({ fen: current.fen, moves: gameHistory, player_color: color });
+    return () => { reportBoard(null); };
+  }, []);
 
   const handleStartGame = async () => {
     const color = selectedColor === 'random' 
       ? (Math.random() > 0.5 ? 'white' : 'black') 
       : selectedColor;
     
-    await startNewGame(color);
+    await startNewGame(color, toPgnTimeControl(timeControl));
     setShowConfig(false);
   };
 
   const handleMove = (from: string, to: string, promotion?: string): boolean => {
+    // During a retry drill moves are attempts at the pre-blunder position; the board stays put
+    if (retry) {
+      retryMove(from, to, promotion);
+      return false;
+    }
     if (!gameState || isThinking) return false;
     if (gameState.turn !== playerColor) return false;
     
@@ -48,6 +94,24 @@ export const PlayMode: React.FC<PlayModeProps> = ({ onBack, isCalibration = fals
     setShowConfig(true);
   };
 
+  // No-spoiler help: explains the engine's threats without suggesting a move
+  const handleOpponentPlan = async () => {
+    if (!gameState) return;
+    setPlanLoading(true);
+    try {
+      setOpponentPlan(await invoke<CoachResponse>('explain_opponent_plan', { fen: gameState.fen, apiKey }));
+    } catch (err) {
+      console.error('Failed to explain opponent plan:', err);
+    } finally {
+      setPlanLoading(false);
+    }
+  };
+
+  // Threats refer to the position they were asked about
+  useEffect(() => {
+    setOpponentPlan(null);
+  }, [gameState?.fen]);
+
   const handleNewGame = () => {
     setShowConfig(true);
   };
@@ -166,11 +230,15 @@ export const PlayMode: React.FC<PlayModeProps> = ({ onBack, isCalibration = fals
           height="auto"
         >
           <ChessBoard
-            fen={gameState?.fen}
+            fen={retry ? retry.drill.fen : gameState?.fen}
             orientation={playerColor}
+            onOrientationChange={reportOrientation}
             onMove={handleMove}
             lastMove={lastMove}
-            interactive={!gameState?.is_checkmate && !gameState?.is_stalemate && !isThinking}
+            highlights={opponentPlan?.highlights.map((square) => ({ square, color: 'rgba(220, 60, 60, 0.45)' }))}
+            interactive={retry
+              ? !retry.verdict?.accepted
+              : !gameState?.is_checkmate && !gameState?.is_stalemate && !isThinking}
           />
         </XPWindow>
 
@@ -221,6 +289,57 @@ export const PlayMode: React.FC<PlayModeProps> = ({ onBack, isCalibration = fals
               <XPButton onClick={handleResign}>Resign</XPButton>
               <XPButton onClick={handleNewGame}>New Game</XPButton>
             </div>
+
+            {retry && (
+              <>
+                <div className="xp-divider" />
+                <div className="retry-drill">
+                  {retry.verdict?.accepted ? (
+                    <>
+                      <p>[+] {retry.verdict.san} works. The engine's choice was {retry.verdict.best_san}.</p>
+                      <div className="retry-actions">
+                        <XPButton primary onClick={() => resumeFromRetry('corrected')}>
+                          Play {retry.verdict.san}
+                        </XPButton>
+                        <XPButton onClick={() => resumeFromRetry('original')}>Keep {retry.drill.played_san}</XPButton>
+                      </div>
+                    </>
+                  ) : (
+                    <>
+                      <p>
+                        [!] {retry.drill.played_san} was a blunder, costing about
+                        {' '}{(retry.drill.centipawn_loss / 100).toFixed(1)} pawns. Find a better move.
+                      </p>
+                      {retry.verdict && <p>{retry.verdict.san} isn't it either. Try again.</p>}
+                      <div className="retry-actions">
+                        <XPButton onClick={() => resumeFromRetry('original')}>Keep my move</XPButton>
+                      </div>
+                    </>
+                  )}
+                </div>
+              </>
+            )}
+
+            {assistance !== 'off' && (
+              <>
+                <div className="xp-divider" />
+
+                <div className="opponent-plan">
+                  <XPButton onClick={handleOpponentPlan} disabled={!gameState || planLoading || isThinking || !!retry}>
+                    {planLoading ? 'Thinking...' : "What's my opponent planning?"}
+                  </XPButton>
+                  {opponentPlan && <p className="opponent-plan-text">[i] {opponentPlan.message.content}</p>}
+                </div>
+              </>
+            )}
+
+            <div className="xp-divider" />
+
+            <BookmarkButton fen={gameState?.fen} origin="game" />
+
+            <div className="xp-divider" />
+
+            <DgtBoardPanel />
           </XPWindow>
 
           <XPWindow title="Move Log" icon="[#]" width={280} height={200}>
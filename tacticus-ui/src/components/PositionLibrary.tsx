@@ -0,0 +1,67 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import { XPButton } from './xp/XPButton';
+import { XPInput } from './xp/XPInput';
+import type { SavedPosition } from '../lib/ai/types';
+
+interface PositionLibraryProps {
+  onAnalyze: (fen: string) => void;
+  onTrain: (positionId: number) => void;
+}
+
+const ORIGINS: Array<SavedPosition['origin']> = ['import', 'game', 'puzzle', 'coach', 'analysis'];
+
+/** Bookmarked and imported positions, filterable by tag and where they were saved from */
+export const PositionLibraryPanel: React.FC<PositionLibraryProps> = ({ onAnalyze, onTrain }) => {
+  const [positions, setPositions] = useState<SavedPosition[]>([]);
+  const [tag, setTag] = useState('');
+  const [origin, setOrigin] = useState('');
+
+  const load = () => {
+    invoke<SavedPosition[]>('get_saved_positions', { tag: tag || null, origin: origin || null })
+      .then(setPositions)
+      .catch((err) => console.error('Failed to load saved positions:', err));
+  };
+
+  useEffect(load, [tag, origin]);
+
+  const handleDelete = async (id: number) => {
+    await invoke<boolean>('delete_saved_position', { id }).catch((err) =>
+      console.error('Failed to delete saved position:', err)
+    );
+    load();
+  };
+
+  if (positions.length === 0 && !tag && !origin) return null;
+
+  return (
+    <XPPanel label={`Position Library (${positions.length})`} className="position-library">
+      <div className="position-library-filters">
+        <XPInput value={tag} onChange={setTag} placeholder="Filter by tag" />
+        <select value={origin} onChange={(e) => setOrigin(e.target.value)}>
+          <option value="">Saved from anywhere</option>
+          {ORIGINS.map((o) => (
+            <option key={o} value={o}>{o}</option>
+          ))}
+        </select>
+      </div>
+      <ul className="position-library-list">
+        {positions.map((p) => (
+          <li key={p.id}>
+            <div>
+              <strong>{p.title ?? 'Untitled position'}</strong> <span className="habit-note">({p.origin})</span>
+              {p.note && <div className="habit-note">{p.note}</div>}
+              {p.tags.length > 0 && <div className="habit-note">Tags: {p.tags.join(', ')}</div>}
+            </div>
+            <div className="position-actions">
+              <XPButton onClick={() => onAnalyze(p.fen)}>Analyze</XPButton>
+              <XPButton onClick={() => onTrain(p.id)}>Train</XPButton>
+              <XPButton onClick={() => handleDelete(p.id)}>Delete</XPButton>
+            </div>
+          </li>
+        ))}
+      </ul>
+    </XPPanel>
+  );
+};
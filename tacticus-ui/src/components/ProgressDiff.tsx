@@ -0,0 +1,54 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import type { ProgressConfidence, ProgressDiff } from '../lib/ai/types';
+
+const CONFIDENCE_LABELS: Record<ProgressConfidence, string> = {
+  clear: '[!] clear',
+  suggestive: '[?] maybe',
+  within_noise: '[=] noise',
+  too_little_data: '[i] too few',
+};
+
+/** Latest analysed games against the ones before them, with how far to trust each change */
+export const ProgressDiffPanel: React.FC = () => {
+  const [diff, setDiff] = useState<ProgressDiff | null>(null);
+
+  useEffect(() => {
+    invoke<ProgressDiff>('get_progress_diff')
+      .then(setDiff)
+      .catch((err) => console.error('Failed to compare progress:', err));
+  }, []);
+
+  if (!diff || diff.recent_games === 0) return null;
+
+  return (
+    <XPPanel label={`Progress: last ${diff.recent_games} games vs the ${diff.earlier_games} before`} className="progress-diff">
+      <p className="habit-note">{diff.summary}</p>
+      {diff.metrics.length > 0 && (
+        <table className="opponent-bands">
+          <thead>
+            <tr>
+              <th>Metric</th>
+              <th>Before</th>
+              <th>Since</th>
+              <th>Improved</th>
+              <th>Confidence</th>
+            </tr>
+          </thead>
+          <tbody>
+            {diff.metrics.map((m) => (
+              <tr key={m.key}>
+                <td>{m.label}</td>
+                <td>{m.before} <small>({m.before_samples} {m.unit})</small></td>
+                <td>{m.after} <small>({m.after_samples} {m.unit})</small></td>
+                <td>{m.improvement_percent === null ? '-' : `${m.improvement_percent > 0 ? '+' : ''}${m.improvement_percent.toFixed(0)}%`}</td>
+                <td>{CONFIDENCE_LABELS[m.confidence]}</td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </XPPanel>
+  );
+};
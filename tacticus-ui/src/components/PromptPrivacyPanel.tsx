@@ -0,0 +1,90 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+import { previewCoachRequest } from '../lib/ai/agent';
+import type { CoachSetup, PromptPreview, PromptPrivacy } from '../lib/ai/types';
+
+/** What Gurgeh's prompts may share with the model, with a preview of a request as it would be sent */
+export const PromptPrivacyPanel: React.FC = () => {
+  const [privacy, setPrivacy] = useState<PromptPrivacy | null>(null);
+  const [preview, setPreview] = useState<PromptPreview | null>(null);
+
+  useEffect(() => {
+    invoke<PromptPrivacy>('get_prompt_privacy')
+      .then(setPrivacy)
+      .catch((err) => console.error('Failed to load prompt privacy:', err));
+  }, []);
+
+  const update = async (changes: Partial<PromptPrivacy>) => {
+    if (!privacy) return;
+    const next = { ...privacy, ...changes };
+    try {
+      await invoke('set_prompt_privacy', { privacy: next });
+      setPrivacy(next);
+      setPreview(null);
+    } catch (err) {
+      console.error('Failed to update prompt privacy:', err);
+    }
+  };
+
+  const handlePreview = async () => {
+    try {
+      // A new general chat with nothing on the board; the chat window previews its own next message
+      const setup = await invoke<CoachSetup>('get_coach_setup', { conversationId: null });
+      setPreview(await previewCoachRequest([{ role: 'user', content: '(your message)' }], null, setup));
+    } catch (err) {
+      console.error('Failed to preview coach prompt:', err);
+    }
+  };
+
+  if (!privacy) return null;
+
+  return (
+    <XPPanel label="Prompt Privacy" className="prompt-privacy-section">
+      <p className="settings-description">
+        Choose what Gurgeh's requests to the model may include. Keeping games back also turns off game
+        reviews, game quizzes and AI debriefs; the engine's debrief is used instead. The preview shows the first
+        request of a new chat; the chat window's Preview button shows what your next message would send.
+      </p>
+      <div className="diagnostics-row">
+        <label>
+          <input type="checkbox" checked={privacy.share_games} onChange={(e) => update({ share_games: e.target.checked })} />
+          Share my saved games
+        </label>
+      </div>
+      <div className="diagnostics-row">
+        <label>
+          <input type="checkbox" checked={privacy.share_ratings} onChange={(e) => update({ share_ratings: e.target.checked })} />
+          Share my and my opponents' ratings
+        </label>
+      </div>
+      <div className="diagnostics-row">
+        <label>
+          <input type="checkbox" checked={privacy.redact_name} onChange={(e) => update({ redact_name: e.target.checked })} />
+          Replace my name with "the student"
+        </label>
+      </div>
+      <div className="diagnostics-row">
+        <label htmlFor="prompt-history">Earlier messages sent</label>
+        <input
+          id="prompt-history"
+          type="number"
+          min={0}
+          max={100}
+          value={privacy.history_messages}
+          onChange={(e) => update({ history_messages: Math.max(0, Number(e.target.value)) })}
+        />
+      </div>
+      <div className="diagnostics-row">
+        <XPButton onClick={handlePreview}>Preview a Request</XPButton>
+      </div>
+      {preview && (
+        <pre className="prompt-preview">
+          {preview.messages.map((m) => `[${m.role}]\n${m.content}`).join('\n\n')}
+          {`\n\n[tools]\n${preview.tools.join(', ') || 'none'}`}
+        </pre>
+      )}
+    </XPPanel>
+  );
+};
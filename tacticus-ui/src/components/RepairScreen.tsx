@@ -0,0 +1,67 @@
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPWindow } from './xp/XPWindow';
+import { XPButton } from './xp/XPButton';
+import type { HealthStatus } from '../lib/health';
+
+interface RepairScreenProps {
+  status: HealthStatus;
+  /** The database works again; the app can start */
+  onRepaired: () => void;
+}
+
+const CHECK_LABELS: Record<string, string> = {
+  database: 'Database',
+  engine: 'Engine',
+  api_key: 'API key',
+  assets: 'Board assets',
+};
+
+/** Shown instead of the app when the database could not be opened at startup */
+export const RepairScreen: React.FC<RepairScreenProps> = ({ status, onRepaired }) => {
+  const [current, setCurrent] = useState(status);
+  const [error, setError] = useState<string | null>(null);
+  const [working, setWorking] = useState(false);
+
+  const repair = async (startFresh: boolean) => {
+    setWorking(true);
+    setError(null);
+    try {
+      const repaired = await invoke<HealthStatus>('repair_database', { startFresh });
+      setCurrent(repaired);
+      if (!repaired.needs_repair) onRepaired();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setWorking(false);
+    }
+  };
+
+  return (
+    <div className="loading-container">
+      <XPWindow title="Tacticus could not start" icon="[!]" width={480} height="auto">
+        <p>Something went wrong while starting up. Your data has not been touched.</p>
+        <ul className="position-library-list">
+          {current.checks.map((check) => (
+            <li key={check.name}>
+              <strong>{CHECK_LABELS[check.name] ?? check.name}: {check.status}</strong>
+              <div className="habit-note">{check.detail}</div>
+            </li>
+          ))}
+        </ul>
+        {error && <p className="habit-note">{error}</p>}
+        <div className="position-actions">
+          <XPButton primary disabled={working} onClick={() => repair(false)}>
+            Try again
+          </XPButton>
+          <XPButton disabled={working} onClick={() => repair(true)}>
+            Start with a new database
+          </XPButton>
+        </div>
+        <p className="habit-note">
+          Starting over keeps the old database file next to the new one, renamed, so it can still be recovered.
+        </p>
+      </XPWindow>
+    </div>
+  );
+};
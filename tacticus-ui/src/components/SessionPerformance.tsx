@@ -0,0 +1,54 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import { useUserStore } from '../stores/userStore';
+
+interface SessionRecord {
+  id: number;
+  finished_at: string | null;
+  completed_exercises: number;
+  performance_rating: number | null;
+}
+
+const WIDTH = 600;
+const HEIGHT = 120;
+/** Sessions charted, newest last */
+const SESSIONS = 30;
+
+/** Puzzle performance rating of each finished training session, with the game rating for reference */
+export const SessionPerformancePanel: React.FC = () => {
+  const { stats } = useUserStore();
+  const [sessions, setSessions] = useState<SessionRecord[]>([]);
+
+  useEffect(() => {
+    invoke<SessionRecord[]>('get_session_history', { limit: SESSIONS })
+      .then((history) => setSessions(history.filter((s) => s.performance_rating !== null).reverse()))
+      .catch((err) => console.error('Failed to load session history:', err));
+  }, []);
+
+  if (sessions.length < 2) return null;
+
+  const ratings = sessions.map((s) => s.performance_rating as number);
+  const elo = stats?.current_elo;
+  const low = Math.min(...ratings, elo ?? Infinity) - 50;
+  const high = Math.max(...ratings, elo ?? -Infinity) + 50;
+  const x = (i: number) => (i / (ratings.length - 1)) * WIDTH;
+  const y = (rating: number) => HEIGHT - ((rating - low) / (high - low)) * HEIGHT;
+  const points = ratings.map((r, i) => `${x(i).toFixed(1)},${y(r).toFixed(1)}`).join(' ');
+  const latest = ratings[ratings.length - 1];
+
+  return (
+    <XPPanel label={`Training Performance (last session: ${latest})`} className="session-performance">
+      <svg viewBox={`0 0 ${WIDTH} ${HEIGHT}`} width="100%" height={HEIGHT} preserveAspectRatio="none">
+        {elo !== undefined && (
+          <line x1={0} y1={y(elo)} x2={WIDTH} y2={y(elo)} stroke="#999" strokeDasharray="4 4" />
+        )}
+        <polyline points={points} fill="none" stroke="#245edb" strokeWidth={2} />
+      </svg>
+      <p className="habit-note">
+        [i] Rated from the puzzles solved and missed in each session.
+        {elo !== undefined && ` Dashed line: your game rating (${elo}).`}
+      </p>
+    </XPPanel>
+  );
+};
@@ -1,8 +1,24 @@
 import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { XPWindow } from './xp/XPWindow';
 import { XPButton } from './xp/XPButton';
 import { XPInput } from './xp/XPInput';
 import { XPPanel } from './xp/XPPanel';
+import { AppearancePanel } from './AppearancePanel';
+import { AttemptFlagsPanel } from './AttemptFlagsPanel';
+import { CoachTransparencyPanel } from './CoachTransparencyPanel';
+import { CoachVerbosityPanel } from './CoachVerbosityPanel';
+import { EncouragementPanel } from './EncouragementPanel';
+import { EngineBenchPanel } from './EngineBenchPanel';
+import { EngineStrengthPanel } from './EngineStrengthPanel';
+import { GameAssistancePanel } from './GameAssistancePanel';
+import { KidModePanel } from './KidModePanel';
+import { ModelRoutingPanel } from './ModelRoutingPanel';
+import { ModerationPanel } from './ModerationPanel';
+import { MoveQualityPanel } from './MoveQualityPanel';
+import { NotationPanel } from './NotationPanel';
+import { PromptPrivacyPanel } from './PromptPrivacyPanel';
+import { StoragePanel } from './StoragePanel';
 import { useUserStore } from '../stores/userStore';
 import './Settings.css';
 
@@ -16,9 +32,15 @@ export const Settings: React.FC<SettingsProps> = ({ onClose, onSave }) => {
   const [keyInput, setKeyInput] = useState('');
   const [saving, setSaving] = useState(false);
   const [saved, setSaved] = useState(false);
+  const [logLevel, setLogLevel] = useState('info');
+  const [diagnosticsPath, setDiagnosticsPath] = useState<string | null>(null);
+  const [collecting, setCollecting] = useState(false);
+  const [reanalyzing, setReanalyzing] = useState(false);
+  const [reanalysisSummary, setReanalysisSummary] = useState<string | null>(null);
 
   useEffect(() => {
     loadApiKey();
+    invoke<string>('get_log_level').then(setLogLevel).catch(() => {});
   }, []);
 
   useEffect(() => {
@@ -47,6 +69,47 @@ export const Settings: React.FC<SettingsProps> = ({ onClose, onSave }) => {
     }
   };
 
+  const handleLogLevelChange = async (level: string) => {
+    try {
+      setLogLevel(await invoke<string>('set_log_level', { level }));
+    } catch (err) {
+      console.error('Failed to set log level:', err);
+    }
+  };
+
+  const handleCollectDiagnostics = async () => {
+    setCollecting(true);
+    try {
+      const bundle = await invoke<{ path: string }>('collect_diagnostics');
+      setDiagnosticsPath(bundle.path);
+    } catch (err) {
+      console.error('Failed to collect diagnostics:', err);
+    } finally {
+      setCollecting(false);
+    }
+  };
+
+  const handleReanalyze = async () => {
+    setReanalyzing(true);
+    try {
+      const report = await invoke<{
+        engine_name: string;
+        engine_version: string;
+        reanalyzed: number;
+        skipped_up_to_date: number;
+        failed: number[];
+      }>('reanalyze_games', { filter: {}, settings: { force: false } });
+      setReanalysisSummary(
+        `Re-analyzed ${report.reanalyzed} games with ${report.engine_name} ${report.engine_version}` +
+        ` (${report.skipped_up_to_date} already up to date, ${report.failed.length} failed).`
+      );
+    } catch (err) {
+      setReanalysisSummary(`[!] Re-analysis failed: ${err}`);
+    } finally {
+      setReanalyzing(false);
+    }
+  };
+
   const handleInputChange = (value: string) => {
     setKeyInput(value);
     setSaved(false);
@@ -100,6 +163,66 @@ export const Settings: React.FC<SettingsProps> = ({ onClose, onSave }) => {
             </div>
           </XPPanel>
 
+          <ModelRoutingPanel />
+
+          <KidModePanel />
+
+          <CoachVerbosityPanel />
+
+          <EncouragementPanel />
+
+          <GameAssistancePanel />
+
+          <CoachTransparencyPanel />
+
+          <PromptPrivacyPanel />
+
+          <ModerationPanel />
+
+          <AppearancePanel />
+
+          <NotationPanel />
+
+          <MoveQualityPanel />
+
+          <AttemptFlagsPanel />
+
+          <XPPanel label="Diagnostics" className="diagnostics-section">
+            <div className="diagnostics-row">
+              <label htmlFor="log-level">Log level</label>
+              <select
+                id="log-level"
+                value={logLevel}
+                onChange={(e) => handleLogLevelChange(e.target.value)}
+              >
+                {['error', 'warn', 'info', 'debug', 'trace'].map((level) => (
+                  <option key={level} value={level}>{level}</option>
+                ))}
+              </select>
+              <XPButton onClick={handleCollectDiagnostics} disabled={collecting}>
+                {collecting ? 'Collecting...' : 'Collect Diagnostics'}
+              </XPButton>
+            </div>
+            {diagnosticsPath && (
+              <p className="settings-description">
+                Saved to <code>{diagnosticsPath}</code> - attach it to your bug report. API keys are removed.
+              </p>
+            )}
+            <div className="diagnostics-row">
+              <XPButton onClick={handleReanalyze} disabled={reanalyzing}>
+                {reanalyzing ? 'Re-analyzing...' : 'Re-analyze Games'}
+              </XPButton>
+              <span>Refresh stored analysis after an engine upgrade</span>
+            </div>
+            {reanalysisSummary && <p className="settings-description">{reanalysisSummary}</p>}
+          </XPPanel>
+
+          <EngineBenchPanel />
+
+          <EngineStrengthPanel />
+
+          <StoragePanel />
+
           <div className="settings-status">
             {apiKey ? (
               <span className="status-ok">[OK] API key configured - Gurgeh is ready</span>
@@ -0,0 +1,192 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+
+interface TableSize {
+  name: string;
+  rows: number;
+  bytes: number | null;
+}
+
+interface PruningPolicy {
+  max_conversations: number | null;
+  archive_analyses_after_days: number | null;
+}
+
+interface StorageReport {
+  database_bytes: number;
+  tables: TableSize[];
+  policy: PruningPolicy;
+}
+
+type WipeScope = 'all' | 'conversations' | 'games';
+
+interface WipeReport {
+  rows_deleted: number;
+  files_deleted: number;
+  failures: string[];
+}
+
+const WIPES: Record<WipeScope, { command: string; label: string; warning: string }> = {
+  conversations: {
+    command: 'wipe_conversations',
+    label: 'Delete Conversations',
+    warning: 'Delete every coach conversation and exported transcript?',
+  },
+  games: {
+    command: 'wipe_games',
+    label: 'Delete Games',
+    warning: 'Delete every saved game, its analysis, exported reports and narrations?',
+  },
+  all: {
+    command: 'wipe_all_data',
+    label: 'Delete All My Data',
+    warning: 'Delete everything Tacticus has stored on this computer, including your profile and API key? This cannot be undone.',
+  },
+};
+
+const formatBytes = (bytes: number) => {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+};
+
+/** Parse a limit input; blank means the policy is off */
+const parseLimit = (value: string): number | null => {
+  const n = parseInt(value, 10);
+  return Number.isNaN(n) ? null : n;
+};
+
+export const StoragePanel: React.FC = () => {
+  const [report, setReport] = useState<StorageReport | null>(null);
+  const [maxConversations, setMaxConversations] = useState('');
+  const [archiveDays, setArchiveDays] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const loadReport = async () => {
+    try {
+      const result = await invoke<StorageReport>('get_storage_report');
+      setReport(result);
+      setMaxConversations(result.policy.max_conversations?.toString() ?? '');
+      setArchiveDays(result.policy.archive_analyses_after_days?.toString() ?? '');
+    } catch (err) {
+      setStatus(`[!] ${err}`);
+    }
+  };
+
+  useEffect(() => {
+    loadReport();
+  }, []);
+
+  const run = async (action: () => Promise<string>) => {
+    setBusy(true);
+    try {
+      setStatus(await action());
+      await loadReport();
+    } catch (err) {
+      setStatus(`[!] ${err}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleVacuum = () => run(async () => {
+    const reclaimed = await invoke<number>('vacuum_database');
+    return `[OK] Compacted database, reclaimed ${formatBytes(reclaimed)}`;
+  });
+
+  const handleIntegrityCheck = () => run(async () => {
+    const rows = await invoke<string[]>('check_database_integrity');
+    return rows.length === 1 && rows[0] === 'ok'
+      ? '[OK] Database integrity check passed'
+      : `[!] Integrity problems: ${rows.join('; ')}`;
+  });
+
+  const handleApplyPolicy = () => run(async () => {
+    await invoke('set_pruning_policy', {
+      policy: {
+        max_conversations: parseLimit(maxConversations),
+        archive_analyses_after_days: parseLimit(archiveDays),
+      },
+    });
+    const pruned = await invoke<{ conversations_deleted: number; analyses_archived: number }>('prune_storage');
+    return `[OK] Removed ${pruned.conversations_deleted} conversations, archived ${pruned.analyses_archived} analyses`;
+  });
+
+  const handleWipe = (scope: WipeScope) => {
+    const wipe = WIPES[scope];
+    if (!window.confirm(wipe.warning)) return;
+    return run(async () => {
+      const confirmToken = await invoke<string>('request_wipe_token', { scope });
+      const result = await invoke<WipeReport>(wipe.command, { confirmToken });
+      if (scope === 'all') {
+        // Nothing is left to show; start over as a fresh install
+        window.location.reload();
+      }
+      return result.failures.length > 0
+        ? `[!] Deleted ${result.rows_deleted} records, but could not remove: ${result.failures.join('; ')}`
+        : `[OK] Deleted ${result.rows_deleted} records and ${result.files_deleted} files`;
+    });
+  };
+
+  return (
+    <XPPanel label="Storage" className="storage-section">
+      {report && (
+        <>
+          <p className="settings-description">Database size: {formatBytes(report.database_bytes)}</p>
+          <table className="storage-table">
+            <tbody>
+              {report.tables.map((table) => (
+                <tr key={table.name}>
+                  <td>{table.name}</td>
+                  <td>{table.rows} rows</td>
+                  <td>{table.bytes !== null ? formatBytes(table.bytes) : '-'}</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </>
+      )}
+
+      <div className="diagnostics-row">
+        <label htmlFor="max-conversations">Keep conversations</label>
+        <input
+          id="max-conversations"
+          className="storage-limit"
+          value={maxConversations}
+          onChange={(e) => setMaxConversations(e.target.value)}
+          placeholder="all"
+        />
+        <label htmlFor="archive-days">Archive analyses after (days)</label>
+        <input
+          id="archive-days"
+          className="storage-limit"
+          value={archiveDays}
+          onChange={(e) => setArchiveDays(e.target.value)}
+          placeholder="never"
+        />
+      </div>
+
+      <div className="diagnostics-row">
+        <XPButton onClick={handleApplyPolicy} disabled={busy}>Apply &amp; Prune</XPButton>
+        <XPButton onClick={handleVacuum} disabled={busy}>Compact</XPButton>
+        <XPButton onClick={handleIntegrityCheck} disabled={busy}>Check Integrity</XPButton>
+      </div>
+
+      <p className="settings-description">
+        Sharing this computer? Remove your data for good. Deleted records are overwritten, not just hidden.
+      </p>
+      <div className="diagnostics-row">
+        {(['conversations', 'games', 'all'] as WipeScope[]).map((scope) => (
+          <XPButton key={scope} onClick={() => handleWipe(scope)} disabled={busy}>
+            {WIPES[scope].label}
+          </XPButton>
+        ))}
+      </div>
+
+      {status && <p className="settings-description">{status}</p>}
+    </XPPanel>
+  );
+};
@@ -0,0 +1,59 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPButton } from './xp/XPButton';
+import { XPPanel } from './xp/XPPanel';
+
+interface ThemePerformance {
+  exercise_type: string;
+  attempts: number;
+  solved: number;
+  solve_rate: number;
+  avg_time_seconds: number;
+  avg_hints_used: number;
+  recent_solve_rate: number | null;
+  previous_solve_rate: number | null;
+  trend: 'improving' | 'stable' | 'declining' | 'new';
+}
+
+const TREND_LABELS: Record<ThemePerformance['trend'], string> = {
+  improving: '[+] improving',
+  stable: '[=] stable',
+  declining: '[-] declining',
+  new: '[i] too few attempts',
+};
+
+interface ThemePerformanceProps {
+  onDrill: (theme: string) => void;
+}
+
+/** Exercise themes ranked weakest first, each with a button to drill it */
+export const ThemePerformancePanel: React.FC<ThemePerformanceProps> = ({ onDrill }) => {
+  const [themes, setThemes] = useState<ThemePerformance[]>([]);
+
+  useEffect(() => {
+    invoke<ThemePerformance[]>('get_theme_performance', { days: 90 })
+      .then(setThemes)
+      .catch((err) => console.error('Failed to load theme performance:', err));
+  }, []);
+
+  if (themes.length === 0) return null;
+
+  return (
+    <XPPanel label="Themes by Weakness (last 90 days)" className="theme-performance">
+      <ol className="workout-items">
+        {themes.map((theme) => (
+          <li key={theme.exercise_type}>
+            <span className="workout-title">
+              {theme.exercise_type} <em>- {theme.solve_rate.toFixed(0)}% of {theme.attempts} solved</em>
+              <small>
+                {TREND_LABELS[theme.trend]}, {Math.round(theme.avg_time_seconds)}s and{' '}
+                {theme.avg_hints_used.toFixed(1)} hints on average
+              </small>
+            </span>
+            <XPButton onClick={() => onDrill(theme.exercise_type)}>Drill</XPButton>
+          </li>
+        ))}
+      </ol>
+    </XPPanel>
+  );
+};
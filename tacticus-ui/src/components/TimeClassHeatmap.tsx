@@ -0,0 +1,72 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import type { TimeClassStats } from '../lib/ai/types';
+
+interface Metric {
+  label: string;
+  value: (stats: TimeClassStats) => number | null;
+  format: (value: number) => string;
+  /** Shaded red the higher it is; otherwise green */
+  higherIsWorse: boolean;
+}
+
+const METRICS: Metric[] = [
+  { label: 'Win rate', value: (s) => s.win_rate, format: (v) => `${v.toFixed(0)}%`, higherIsWorse: false },
+  { label: 'Avg. CP loss', value: (s) => s.average_centipawn_loss, format: (v) => `${Math.round(v)}`, higherIsWorse: true },
+  { label: 'Mistakes / game', value: (s) => s.avg_mistakes, format: (v) => v.toFixed(1), higherIsWorse: true },
+  { label: 'Blunders / game', value: (s) => s.avg_blunders, format: (v) => v.toFixed(1), higherIsWorse: true },
+];
+
+/** Cell colour for `value`, as strong as it is relative to the largest in its column */
+function shade(metric: Metric, value: number, max: number): string {
+  const strength = max > 0 ? Math.min(value / max, 1) * 0.6 : 0;
+  return metric.higherIsWorse ? `rgba(200, 40, 40, ${strength})` : `rgba(40, 160, 40, ${strength})`;
+}
+
+/** Results and accuracy per time class, shaded so the weakest speed stands out */
+export const TimeClassHeatmapPanel: React.FC = () => {
+  const [classes, setClasses] = useState<TimeClassStats[]>([]);
+
+  useEffect(() => {
+    invoke<TimeClassStats[]>('get_time_class_stats')
+      .then(setClasses)
+      .catch((err) => console.error('Failed to load time class stats:', err));
+  }, []);
+
+  if (classes.length === 0) return null;
+
+  const maxima = METRICS.map((metric) =>
+    Math.max(0, ...classes.map((c) => metric.value(c) ?? 0)),
+  );
+
+  return (
+    <XPPanel label="By Time Control" className="time-class-heatmap">
+      <table className="opponent-bands">
+        <thead>
+          <tr>
+            <th>Speed</th>
+            <th>Games</th>
+            {METRICS.map((metric) => <th key={metric.label}>{metric.label}</th>)}
+          </tr>
+        </thead>
+        <tbody>
+          {classes.map((stats) => (
+            <tr key={stats.time_class}>
+              <td className="time-class-name">{stats.time_class}</td>
+              <td>{stats.games}</td>
+              {METRICS.map((metric, i) => {
+                const value = metric.value(stats);
+                return (
+                  <td key={metric.label} style={value === null ? undefined : { background: shade(metric, value, metric.higherIsWorse ? maxima[i] : 100) }}>
+                    {value === null ? '-' : metric.format(value)}
+                  </td>
+                );
+              })}
+            </tr>
+          ))}
+        </tbody>
+      </table>
+    </XPPanel>
+  );
+};
@@ -4,27 +4,49 @@ import { XPButton } from './xp/XPButton';
 import { XPProgress } from './xp/XPProgress';
 import { XPPanel } from './xp/XPPanel';
 import { ChessBoard } from './board/ChessBoard';
+import { BookmarkButton } from './BookmarkButton';
 import { useTrainingStore } from '../stores/trainingStore';
+import { reportBoard, reportOrientation, sideToMove } from '../lib/boardContext';
 import './TrainMode.css';
 
 interface TrainModeProps {
   onBack: () => void;
   onCalibrationNeeded: () => void;
+  /** Exercise type to train, e.g. from a coach deep link; starts a fresh session */
+  theme?: string | null;
+  /** Saved position to train as a single exercise; takes precedence over `theme` */
+  positionId?: number | null;
+  /** Checkmate pattern concept to drill, e.g. 'greek_gift'; takes precedence over `theme` */
+  matePattern?: string | null;
+  /** Exact exercises to train, e.g. a daily workout item; takes precedence over `theme` */
+  exerciseSet?: { ids: number[]; focus: string } | null;
+  /** Open an exercise-help chat with Gurgeh for this exercise */
+  onAskCoach?: (exerciseId: number) => void;
+  /** Have the coach lead the way with questions instead of answers */
+  onGuideMe?: (exerciseId: number) => void;
 }
 
-export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeeded }) => {
+const PHASES = ['Opening', 'Middlegame', 'Endgame'];
+
+export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeeded, theme, positionId, matePattern, exerciseSet, onAskCoach, onGuideMe }) => {
   const [hintText, setHintText] = useState<string | null>(null);
+  const [phase, setPhase] = useState('');
 
   const {
     session,
     currentExercise,
     currentExerciseIndex,
     exerciseResult,
+    postMortem,
+    linePosition,
     score,
     streak,
     hintsUsed,
     loading,
     startTrainingSession,
+    startBookmarkExercise,
+    startExerciseSet,
+    startMatePatternDrill,
     checkSolution,
     nextExercise,
     getHint,
@@ -33,15 +55,25 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
   } = useTrainingStore();
 
   useEffect(() => {
-    if (!session) {
-      startTrainingSession(10);
+    if (positionId) {
+      startBookmarkExercise(positionId);
+    } else if (matePattern) {
+      startMatePatternDrill(matePattern);
+    } else if (exerciseSet) {
+      startExerciseSet(exerciseSet.ids, exerciseSet.focus);
+    } else if (!session || theme) {
+      startTrainingSession(10, theme ?? undefined);
+    } else if (currentExercise) {
+      reportBoard({ fen: currentExercise.fen, start_fen: currentExercise.fen, player_color: sideToMove(currentExercise.fen), exercise_goal: `${currentExercise.title}: ${currentExercise.description}`, exercise_id: currentExercise.id });
     }
+    return () => { reportBoard(null); };
   }, []);
 
-  const handleMove = (from: string, to: string): boolean => {
+  const handleMove = (from: string, to: string, promotion?: string): boolean => {
     if (!currentExercise || exerciseResult) return false;
     
-    const move = from + to;
+    // The piece chosen matters: underpromotion puzzles only accept the right one
+    const move = from + to + (promotion ?? '');
     checkSolution(move);
     return true;
   };
@@ -63,6 +95,12 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
     }
   };
 
+  const handlePhaseChange = (value: string) => {
+    setPhase(value);
+    setHintText(null);
+    startTrainingSession(10, theme ?? undefined, value || undefined);
+  };
+
   const handleRetry = () => {
     setHintText(null);
     resetExercise();
@@ -90,7 +128,9 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
         >
           {currentExercise && (
             <ChessBoard
-              fen={currentExercise.fen}
+              fen={linePosition?.fen ?? currentExercise.fen}
+              orientation={sideToMove(currentExercise.fen)}
+              onOrientationChange={reportOrientation}
               onMove={handleMove}
               interactive={!exerciseResult}
             />
@@ -113,6 +153,10 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
                     )} {currentExercise.difficulty}
                   </span>
                   <span className="exercise-type">{currentExercise.exercise_type}</span>
+                  <span className="exercise-type">{currentExercise.phase}</span>
+                  {currentExercise.moves_to_find > 1 && (
+                    <span className="exercise-type">{currentExercise.moves_to_find} moves</span>
+                  )}
                 </div>
                 
                 <XPPanel className="exercise-description">
@@ -126,6 +170,13 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
                   </div>
                 )}
 
+                {linePosition && !exerciseResult && (
+                  <div className="hint-box">
+                    <span>[OK]</span>
+                    <span>{linePosition.note}</span>
+                  </div>
+                )}
+
                 {exerciseResult && (
                   <div className={`result-box ${exerciseResult.correct ? 'correct' : 'incorrect'}`}>
                     <span>{exerciseResult.correct ? '[OK]' : '[X]'}</span>
@@ -138,12 +189,31 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
                   </div>
                 )}
 
+                {exerciseResult && postMortem && (postMortem.refutations.length > 0 || postMortem.elaboration) && (
+                  <XPPanel label="Post-mortem" className="postmortem-card">
+                    {postMortem.refutations.map((r) => (
+                      <p key={r.tried}>{r.summary}</p>
+                    ))}
+                    {postMortem.elaboration && <p className="postmortem-coach">Gurgeh: {postMortem.elaboration}</p>}
+                  </XPPanel>
+                )}
+
                 <div className="exercise-actions">
                   {!exerciseResult && (
                     <XPButton onClick={handleHint} disabled={hintsUsed >= 2}>
                       Hint ({2 - hintsUsed})
                     </XPButton>
                   )}
+                  {onAskCoach && (
+                    <XPButton onClick={() => onAskCoach(currentExercise.id)}>
+                      Ask Gurgeh
+                    </XPButton>
+                  )}
+                  {onGuideMe && !exerciseResult?.correct && (
+                    <XPButton onClick={() => onGuideMe(currentExercise.id)}>
+                      Guide Me
+                    </XPButton>
+                  )}
                   {exerciseResult && !exerciseResult.correct && (
                     <XPButton onClick={handleRetry}>
                       Retry
@@ -155,6 +225,8 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
                     </XPButton>
                   )}
                 </div>
+
+                <BookmarkButton fen={currentExercise.fen} origin="puzzle" />
               </div>
             )}
           </XPWindow>
@@ -188,6 +260,16 @@ export const TrainMode: React.FC<TrainModeProps> = ({ onBack, onCalibrationNeede
                 </div>
               </div>
 
+              <div className="focus-areas">
+                <label htmlFor="train-phase">Phase:</label>
+                <select id="train-phase" value={phase} onChange={(e) => handlePhaseChange(e.target.value)}>
+                  <option value="">All phases</option>
+                  {PHASES.map((p) => (
+                    <option key={p} value={p}>{p} only</option>
+                  ))}
+                </select>
+              </div>
+
               <div className="session-actions">
                 <XPButton onClick={() => { endSession(); onBack(); }}>
                   End Session
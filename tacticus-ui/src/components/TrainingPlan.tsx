@@ -0,0 +1,133 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { XPPanel } from './xp/XPPanel';
+import { XPButton } from './xp/XPButton';
+import type { CoachAction, Flashcard, OperationRecord, PendingCoachAction, TrainingPlanEntry } from '../lib/ai/types';
+
+interface TrainingPlanProps {
+  /** Run a plan item's button: start training or open the game */
+  onAction: (action: CoachAction) => void;
+}
+
+/** Scheduled training and game reviews, flashcards, and coach proposals waiting for approval */
+export const TrainingPlanPanel: React.FC<TrainingPlanProps> = ({ onAction }) => {
+  const [plan, setPlan] = useState<TrainingPlanEntry[]>([]);
+  const [cards, setCards] = useState<Flashcard[]>([]);
+  const [pending, setPending] = useState<PendingCoachAction[]>([]);
+  const [revealed, setRevealed] = useState<number | null>(null);
+  /** The last reset, offered for undo until the player moves on */
+  const [undoable, setUndoable] = useState<OperationRecord | null>(null);
+
+  const load = () => {
+    invoke<TrainingPlanEntry[]>('get_training_plan', { includeCompleted: false })
+      .then(setPlan)
+      .catch((err) => console.error('Failed to load training plan:', err));
+    invoke<Flashcard[]>('get_flashcards')
+      .then(setCards)
+      .catch((err) => console.error('Failed to load flashcards:', err));
+    invoke<PendingCoachAction[]>('get_pending_coach_actions', { conversationId: null })
+      .then(setPending)
+      .catch((err) => console.error('Failed to load coach proposals:', err));
+  };
+
+  useEffect(load, []);
+
+  const run = async (command: string, args: Record<string, unknown>) => {
+    await invoke(command, args).catch((err) => console.error(`Failed to run ${command}:`, err));
+    load();
+  };
+
+  const resetPlan = async () => {
+    const operation = await invoke<OperationRecord | null>('reset_training_plan').catch((err) => {
+      console.error('Failed to reset training plan:', err);
+      return null;
+    });
+    setUndoable(operation);
+    load();
+  };
+
+  const undo = async () => {
+    setUndoable(null);
+    await run('undo_last_operation', {});
+  };
+
+  if (plan.length === 0 && cards.length === 0 && pending.length === 0 && !undoable) return null;
+
+  return (
+    <XPPanel label={`Training Plan (${plan.length})`} className="training-plan">
+      {undoable && (
+        <div className="position-actions">
+          <span className="habit-note">{undoable.label}.</span>
+          <XPButton onClick={undo}>Undo</XPButton>
+        </div>
+      )}
+      {pending.length > 0 && (
+        <>
+          <div className="habit-note">Proposed by Gurgeh, waiting for your approval:</div>
+          <ul className="position-library-list">
+            {pending.map((p) => (
+              <li key={p.id}>
+                <span>{p.summary}</span>
+                <div className="position-actions">
+                  <XPButton primary onClick={() => run('resolve_pending_coach_action', { id: p.id, approve: true })}>
+                    Approve
+                  </XPButton>
+                  <XPButton onClick={() => run('resolve_pending_coach_action', { id: p.id, approve: false })}>
+                    Decline
+                  </XPButton>
+                </div>
+              </li>
+            ))}
+          </ul>
+        </>
+      )}
+      <ul className="position-library-list">
+        {plan.map((item) => (
+          <li key={item.id}>
+            <div>
+              <strong>{item.title}</strong>
+              {item.due_date && <span className="habit-note"> (due {item.due_date})</span>}
+              {item.note && <div className="habit-note">{item.note}</div>}
+            </div>
+            <div className="position-actions">
+              {item.action && <XPButton onClick={() => onAction(item.action!)}>{item.action.label}</XPButton>}
+              <XPButton onClick={() => run('complete_training_plan_item', { id: item.id })}>Done</XPButton>
+              <XPButton onClick={() => run('delete_training_plan_item', { id: item.id })}>Remove</XPButton>
+            </div>
+          </li>
+        ))}
+      </ul>
+      {plan.length > 0 && (
+        <div className="position-actions">
+          <XPButton onClick={resetPlan}>Reset plan</XPButton>
+        </div>
+      )}
+      {cards.length > 0 && (
+        <>
+          <div className="habit-note">Flashcards ({cards.length})</div>
+          <ul className="position-library-list">
+            {cards.map((card) => (
+              <li key={card.id}>
+                <div>
+                  <strong>{card.front}</strong>
+                  {revealed === card.id && <div className="habit-note">{card.back}</div>}
+                </div>
+                <div className="position-actions">
+                  <XPButton onClick={() => setRevealed(revealed === card.id ? null : card.id)}>
+                    {revealed === card.id ? 'Hide' : 'Show answer'}
+                  </XPButton>
+                  {card.fen && (
+                    <XPButton onClick={() => onAction({ action_type: 'setup_position', label: 'Set up position', fen: card.fen! })}>
+                      Set up
+                    </XPButton>
+                  )}
+                  <XPButton onClick={() => run('delete_flashcard', { id: card.id })}>Delete</XPButton>
+                </div>
+              </li>
+            ))}
+          </ul>
+        </>
+      )}
+    </XPPanel>
+  );
+};
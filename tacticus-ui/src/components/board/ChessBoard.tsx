@@ -1,10 +1,14 @@
-import React, { useState } from 'react';
+import React, { useEffect, useMemo, useState } from 'react';
 import { Chessboard } from 'react-chessboard';
+import { useAppearanceStore } from '../../stores/appearanceStore';
 import './ChessBoard.css';
 
 interface ChessBoardProps {
   fen?: string;
+  /** Side at the bottom until the player flips the board */
   orientation?: 'white' | 'black';
+  /** Called with the side now at the bottom when the player flips the board */
+  onOrientationChange?: (orientation: 'white' | 'black') => void;
   onMove?: (from: string, to: string, promotion?: string) => boolean;
   selectedSquare?: string | null;
   legalMoves?: string[];
@@ -51,6 +55,7 @@ const isPromotionMove = (fen: string, from: string, to: string): boolean => {
 export const ChessBoard: React.FC<ChessBoardProps> = ({
   fen = 'rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1',
   orientation = 'white',
+  onOrientationChange,
   onMove,
   selectedSquare,
   legalMoves = [],
@@ -61,6 +66,38 @@ export const ChessBoard: React.FC<ChessBoardProps> = ({
 }) => {
   const [showPromotion, setShowPromotion] = useState(false);
   const [pendingMove, setPendingMove] = useState<{ from: string; to: string } | null>(null);
+  const [flipped, setFlipped] = useState(false);
+  const { appearance, loadAppearance, notation, loadNotation } = useAppearanceStore();
+
+  // A new side to play resets the player's flip, so the board is shown from that side again
+  useEffect(() => {
+    setFlipped(false);
+    onOrientationChange?.(orientation);
+  }, [orientation]);
+
+  const shown: 'white' | 'black' = flipped === (orientation === 'white') ? 'black' : 'white';
+
+  const flip = () => {
+    setFlipped(!flipped);
+    onOrientationChange?.(shown === 'white' ? 'black' : 'white');
+  };
+
+  useEffect(() => {
+    if (!appearance) loadAppearance();
+  }, [appearance, loadAppearance]);
+
+  useEffect(() => {
+    if (!notation) loadNotation();
+  }, [notation, loadNotation]);
+
+  // Images from the selected piece set; the standard set keeps react-chessboard's own pieces
+  const pieces = useMemo(() => {
+    const images = Object.entries(appearance?.pieces ?? {});
+    if (images.length === 0) return undefined;
+    return Object.fromEntries(
+      images.map(([key, src]) => [key, () => <img src={src} alt={key} draggable={false} style={{ width: '100%', height: '100%' }} />])
+    );
+  }, [appearance]);
 
   const squareStyles: Record<string, React.CSSProperties> = {};
 
@@ -126,6 +163,9 @@ export const ChessBoard: React.FC<ChessBoardProps> = ({
     setPendingMove(null);
   };
 
+  // Pieces in the colour of the side promoting, whichever way the board faces
+  const promotingWhite = fen.split(' ')[1] !== 'b';
+
   const cancelPromotion = () => {
     setShowPromotion(false);
     setPendingMove(null);
@@ -137,20 +177,25 @@ export const ChessBoard: React.FC<ChessBoardProps> = ({
         <Chessboard
           options={{
             position: fen,
-            boardOrientation: orientation,
+            boardOrientation: shown,
             onPieceDrop: handlePieceDrop,
             squareStyles: squareStyles,
             arrows: arrowsData,
             allowDragging: interactive,
+            showNotation: notation?.coordinates ?? true,
             boardStyle: {
               borderRadius: '4px',
               boxShadow: 'inset 0 0 4px rgba(0,0,0,0.3)',
             },
-            darkSquareStyle: { backgroundColor: '#b58863' },
-            lightSquareStyle: { backgroundColor: '#f0d9b5' },
+            darkSquareStyle: { backgroundColor: appearance?.board_theme.dark_square ?? '#b58863' },
+            lightSquareStyle: { backgroundColor: appearance?.board_theme.light_square ?? '#f0d9b5' },
+            ...(pieces && { pieces }),
           }}
         />
       </div>
+      <button className="flip-board" onClick={flip} title="Flip board">
+        Flip
+      </button>
       
       {/* Promotion Dialog */}
       {showPromotion && (
@@ -159,16 +204,16 @@ export const ChessBoard: React.FC<ChessBoardProps> = ({
             <div className="promotion-title">Promote pawn to:</div>
             <div className="promotion-pieces">
               <button className="promotion-piece" onClick={() => handlePromotion('q')} title="Queen">
-                {orientation === 'white' || fen.includes(' b ') ? 'Q' : 'q'}
+                {promotingWhite ? 'Q' : 'q'}
               </button>
               <button className="promotion-piece" onClick={() => handlePromotion('r')} title="Rook">
-                {orientation === 'white' || fen.includes(' b ') ? 'R' : 'r'}
+                {promotingWhite ? 'R' : 'r'}
               </button>
               <button className="promotion-piece" onClick={() => handlePromotion('b')} title="Bishop">
-                {orientation === 'white' || fen.includes(' b ') ? 'B' : 'b'}
+                {promotingWhite ? 'B' : 'b'}
               </button>
               <button className="promotion-piece" onClick={() => handlePromotion('n')} title="Knight">
-                {orientation === 'white' || fen.includes(' b ') ? 'N' : 'n'}
+                {promotingWhite ? 'N' : 'n'}
               </button>
             </div>
           </div>
@@ -1,10 +1,13 @@
 import React, { useState, useRef, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { XPWindow } from '../xp/XPWindow';
 import { XPButton } from '../xp/XPButton';
 import { useUserStore } from '../../stores/userStore';
-import { streamCoachResponse, getPersonalizedGreeting } from '../../lib/ai/agent';
+import { streamCoachResponse, getPersonalizedGreeting, previewCoachRequest } from '../../lib/ai/agent';
 import { GREETING_PROMPT } from '../../lib/ai/prompts';
-import type { ChatMessage, CoachAction } from '../../lib/ai/types';
+import { discardSnapshot, registerSnapshotSource } from '../../lib/autosave';
+import { getBoardContextPrompt, getMoveCheckPrompt } from '../../lib/boardContext';
+import type { ChatMessage, CoachAction, CoachSetup, CoachTrace, ConversationMode, Message, OperationRecord, PendingCoachAction, PromptPreview } from '../../lib/ai/types';
 import './GurgrehChat.css';
 
 interface GurgrehChatProps {
@@ -12,13 +15,58 @@ interface GurgrehChatProps {
   onAction?: (action: CoachAction) => void;
   initialGreeting?: boolean;
   position?: { x: number; y: number };
+  /** Unsent message recovered after a crash */
+  initialDraft?: string;
+  /** What this conversation is about; general chat when omitted */
+  mode?: ConversationMode;
+  /** Stored conversation to continue, e.g. a post-game debrief, instead of greeting */
+  storedConversationId?: number;
 }
 
+const MODE_TITLES: Record<ConversationMode['mode'], string> = {
+  general: 'Chess Coach',
+  game_review: 'Game Review',
+  exercise_help: 'Exercise Help',
+  guided_help: 'Guided Help',
+  quiz_me: 'Quiz',
+};
+
+const GREETING_ACTIONS: CoachAction[] = [
+  { action_type: 'start_training', label: 'Start Training' },
+  { action_type: 'play_game', label: 'Play a Game' },
+  { action_type: 'quiz_me', label: 'Quiz Me' },
+];
+
+const QUIZ_INTRO = "Let's see what you remember from your own games. I'll show you a position where you went wrong: "
+  + 'tell me what you played and what would have been better. Say "go" when you\'re ready.';
+
+const GUIDED_INTRO = "Let's work through this one together. I won't give you the answer; I'll ask you questions, "
+  + 'one at a time, until you find it yourself. To start: what do you notice about the position?';
+
 interface DisplayMessage {
   role: 'user' | 'assistant';
   content: string;
   actions: CoachAction[];
+  /** Plan items and flashcards the coach proposed in this reply, for the player to approve */
+  pendingActions?: PendingCoachAction[];
   isStreaming?: boolean;
+  /** Coach transparency trace behind this reply, linked when the message is saved */
+  traceId?: number | null;
+}
+
+/** Reply shown when a request fails. Rate limiting is not a key problem, so it gets no settings button. */
+function errorMessage(message: string): DisplayMessage {
+  if (message.startsWith('cooling_down')) {
+    return { role: 'assistant', content: `[!] ${message.replace(/^cooling_down: /, '')}`, actions: [], isStreaming: false };
+  }
+  return {
+    role: 'assistant',
+    content: `I encountered an error: ${message}. Please check your API key in Settings.`,
+    actions: [
+      { action_type: 'open_settings', label: 'Open Settings' },
+    ],
+    isStreaming: false,
+  };
 }
 
 export const GurgrehChat: React.FC<GurgrehChatProps> = ({
@@ -26,11 +74,20 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
   onAction,
   initialGreeting = true,
   position,
+  initialDraft = '',
+  mode = { mode: 'general' },
+  storedConversationId,
 }) => {
   const [messages, setMessages] = useState<DisplayMessage[]>([]);
-  const [input, setInput] = useState('');
+  const [input, setInput] = useState(initialDraft);
   const [isLoading, setIsLoading] = useState(false);
   const [toolActivity, setToolActivity] = useState<string | null>(null);
+  const [exportStatus, setExportStatus] = useState<string | null>(null);
+  const [preview, setPreview] = useState<PromptPreview | null>(null);
+  // Stored conversation backing exports, and how many messages it already holds
+  const savedConversation = useRef<{ id: number; saved: number } | null>(null);
+  // The conversation's deletion and what was on screen, kept so Undo can put it back
+  const [deleted, setDeleted] = useState<{ operation: OperationRecord; messages: DisplayMessage[]; conversation: { id: number; saved: number } } | null>(null);
   const messagesEndRef = useRef<HTMLDivElement>(null);
   const { apiKey, profile, stats } = useUserStore();
 
@@ -38,22 +95,46 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
     messagesEndRef.current?.scrollIntoView({ behavior: 'smooth' });
   }, [messages]);
 
+  // Autosave the unsent draft
+  const inputRef = useRef(input);
+  inputRef.current = input;
+  useEffect(() => registerSnapshotSource(() =>
+    inputRef.current.trim()
+      ? { kind: 'coach_draft', sessionKey: 'gurgeh', payload: { text: inputRef.current } }
+      : null
+  ), []);
+
   const loadGreeting = useCallback(async () => {
-    // If no API key, show static greeting
-    if (!apiKey) {
-      const greeting = GREETING_PROMPT(
+    // A quiz opens with its rules rather than a greeting
+    if (mode.mode === 'quiz_me') {
+      setMessages([{ role: 'assistant', content: QUIZ_INTRO, actions: [] }]);
+      return;
+    }
+    if (mode.mode === 'guided_help') {
+      setMessages([{ role: 'assistant', content: GUIDED_INTRO, actions: [] }]);
+      return;
+    }
+
+    // Briefing from the player's history; its buttons go under any greeting. The static
+    // greeting and buttons are only for when it can't be built.
+    const briefing = await invoke<{ message: { content: string; actions: CoachAction[] } }>('get_coach_greeting')
+      .then((b) => b.message)
+      .catch((error) => {
+        console.error('Failed to load coach briefing:', error);
+        return null;
+      });
+    const actions = briefing?.actions ?? GREETING_ACTIONS;
+    const showBriefing = () => {
+      const content = briefing?.content ?? GREETING_PROMPT(
         profile?.name || 'Player',
         stats?.current_elo || 800,
         stats?.exercises_completed || 0
       );
-      setMessages([{
-        role: 'assistant',
-        content: greeting,
-        actions: [
-          { action_type: 'start_training', label: 'Start Training', data: '' },
-          { action_type: 'play_game', label: 'Play a Game', data: '' },
-        ],
-      }]);
+      setMessages([{ role: 'assistant', content, actions }]);
+    };
+
+    if (!apiKey) {
+      showBriefing();
       return;
     }
 
@@ -66,37 +147,34 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
       setMessages([{
         role: 'assistant',
         content: greeting,
-        actions: [
-          { action_type: 'start_training', label: 'Start Training', data: '' },
-          { action_type: 'play_game', label: 'Play a Game', data: '' },
-        ],
+        actions,
       }]);
     } catch (error) {
       console.error('Failed to load greeting:', error);
-      // Fallback to static greeting
-      const greeting = GREETING_PROMPT(
-        profile?.name || 'Player',
-        stats?.current_elo || 800,
-        stats?.exercises_completed || 0
-      );
-      setMessages([{
-        role: 'assistant',
-        content: greeting,
-        actions: [
-          { action_type: 'start_training', label: 'Start Training', data: '' },
-          { action_type: 'play_game', label: 'Play a Game', data: '' },
-        ],
-      }]);
+      showBriefing();
     } finally {
       setIsLoading(false);
     }
-  }, [apiKey, profile?.name, stats?.current_elo, stats?.exercises_completed]);
+  }, [apiKey, profile?.name, stats?.current_elo, stats?.exercises_completed, mode.mode]);
 
   useEffect(() => {
-    if (initialGreeting && messages.length === 0) {
+    if (storedConversationId === undefined && initialGreeting && messages.length === 0) {
       loadGreeting();
     }
-  }, [initialGreeting, messages.length, loadGreeting]);
+  }, [storedConversationId, initialGreeting, messages.length, loadGreeting]);
+
+  useEffect(() => {
+    if (storedConversationId === undefined) return;
+    invoke<Message[]>('get_conversation_messages', { conversationId: storedConversationId })
+      .then((stored) => {
+        const restored = stored
+          .filter((m) => m.role === 'user' || m.role === 'assistant')
+          .map((m): DisplayMessage => ({ role: m.role as DisplayMessage['role'], content: m.content, actions: [] }));
+        savedConversation.current = { id: storedConversationId, saved: restored.length };
+        setMessages(restored);
+      })
+      .catch((err) => console.error('Failed to load conversation:', err));
+  }, [storedConversationId]);
 
   const sendMessage = async () => {
     if (!input.trim() || !apiKey) return;
@@ -110,6 +188,7 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
     // Add user message and prepare for assistant response
     setMessages(prev => [...prev, userMessage]);
     setInput('');
+    discardSnapshot('coach_draft', 'gurgeh');
     setIsLoading(true);
 
     // Create streaming assistant message
@@ -130,12 +209,61 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
       }));
     chatHistory.push({ role: 'user', content: input });
 
+    // Concepts the coach looked up become buttons that open their cards
+    const conceptActions: CoachAction[] = [];
+    const pendingActions: PendingCoachAction[] = [];
+
+    // Review, exercise help and quizzes run under the backend's rules for their mode
+    let conversationId: number | null = null;
+    let setup: CoachSetup | null = null;
+    if (mode.mode === 'general') {
+      // Simple factual questions are answered from the app's own data, without the model
+      const quick = await invoke<{ message: { content: string; actions: CoachAction[] } } | null>('get_quick_answer', {
+        message: input,
+      }).catch(() => null);
+      if (quick) {
+        setMessages(prev => [
+          ...prev.slice(0, -1),
+          { role: 'assistant', content: quick.message.content, actions: quick.message.actions },
+        ]);
+        setIsLoading(false);
+        return;
+      }
+      // Only the profile's reply style applies; chat still works without it
+      setup = await invoke<CoachSetup>('get_coach_setup', { conversationId: null }).catch((err) => {
+        console.error('Failed to load coach settings:', err);
+        return null;
+      });
+    } else {
+      try {
+        conversationId = (await ensureConversation()).id;
+        setup = await invoke<CoachSetup>('get_coach_setup', { conversationId });
+      } catch (err) {
+        console.error('Failed to load conversation mode:', err);
+        setMessages(prev => [
+          ...prev.slice(0, -1),
+          { role: 'assistant', content: `[!] Could not start this conversation: ${err}`, actions: [] },
+        ]);
+        setIsLoading(false);
+        return;
+      }
+    }
+
+    // A locked solution or a moderated word could slip out mid-stream, so such replies wait
+    // for the checked text; without a setup the default moderation policy applies
+    const checkBeforeShowing = !setup || setup.solution_locked || setup.moderated;
+
+    // Moves the player names are checked on the board before the model sees them
+    const boardContext = await getBoardContextPrompt();
+    const moveCheck = boardContext ? await getMoveCheckPrompt(input, conversationId) : null;
+
     try {
       await streamCoachResponse(
         apiKey,
         chatHistory,
         {
           onChunk: (chunk) => {
+            if (checkBeforeShowing) return;
             setMessages(prev => {
               const updated = [...prev];
               const lastIdx = updated.length - 1;
@@ -148,16 +276,38 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
               return updated;
             });
           },
-          onComplete: (fullText) => {
+          onComplete: async (fullText, trace: CoachTrace) => {
+            // Kept only while coach transparency is on; the backend returns null otherwise
+            const traceId = await invoke<number | null>('record_coach_trace', { trace }).catch((err) => {
+              console.error('Failed to record coach trace:', err);
+              return null;
+            });
+            // Action markers only become buttons once the backend has validated them
+            const resolved = await invoke<{ content: string; actions: CoachAction[] }>('resolve_coach_actions', {
+              content: fullText,
+              conversationId,
+            }).catch((err) => {
+              console.error('Failed to resolve coach actions:', err);
+              const content = checkBeforeShowing ? '[!] This reply could not be checked, so it was hidden.' : fullText;
+              return { content, actions: [] as CoachAction[] };
+            });
+            const actions = [...conceptActions];
+            for (const action of resolved.actions) {
+              if (!actions.some(a => JSON.stringify(a) === JSON.stringify(action))) {
+                actions.push(action);
+              }
+            }
             setMessages(prev => {
               const updated = [...prev];
               const lastIdx = updated.length - 1;
               if (updated[lastIdx].isStreaming) {
                 updated[lastIdx] = {
                   role: 'assistant',
-                  content: fullText,
-                  actions: [],
+                  content: resolved.content,
+                  actions,
+                  pendingActions,
                   isStreaming: false,
+                  traceId,
                 };
               }
               return updated;
@@ -171,14 +321,7 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
               const updated = [...prev];
               const lastIdx = updated.length - 1;
               if (updated[lastIdx].isStreaming) {
-                updated[lastIdx] = {
-                  role: 'assistant',
-                  content: `I encountered an error: ${error.message}. Please check your API key in Settings.`,
-                  actions: [
-                    { action_type: 'open_settings', label: 'Open Settings', data: '' },
-                  ],
-                  isStreaming: false,
-                };
+                updated[lastIdx] = errorMessage(error.message);
               }
               return updated;
             });
@@ -188,10 +331,22 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
           onToolCall: (toolName) => {
             setToolActivity(`Querying ${formatToolName(toolName)}...`);
           },
-          onToolResult: () => {
+          onToolResult: (toolName, result) => {
             setToolActivity(null);
+            const concept = (result as { concept?: { id: string; name: string } }).concept;
+            const seen = conceptActions.some(a => a.action_type === 'open_concept' && a.concept_id === concept?.id);
+            if (toolName === 'lookupConcept' && concept && !seen) {
+              conceptActions.push({ action_type: 'open_concept', label: `Open ${concept.name}`, concept_id: concept.id });
+            }
+            const pending = (result as { pendingAction?: PendingCoachAction }).pendingAction;
+            if (pending) {
+              pendingActions.push(pending);
+            }
           },
-        }
+        },
+        undefined,
+        moveCheck ? `${boardContext}\n\n${moveCheck}` : boardContext,
+        setup,
       );
     } catch (error) {
       console.error('Failed to send message:', error);
@@ -199,14 +354,7 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
         const updated = [...prev];
         const lastIdx = updated.length - 1;
         if (updated[lastIdx].isStreaming) {
-          updated[lastIdx] = {
-            role: 'assistant',
-            content: `I encountered an error: ${error}. Please check your API key in Settings.`,
-            actions: [
-              { action_type: 'open_settings', label: 'Open Settings', data: '' },
-            ],
-            isStreaming: false,
-          };
+          updated[lastIdx] = errorMessage(String(error));
         }
         return updated;
       });
@@ -223,6 +371,12 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
       getGamesWithMistakes: 'games with mistakes',
       getTrainingProgress: 'training progress',
       getImprovementTrend: 'improvement trend',
+      addToTrainingPlan: 'your training plan',
+      scheduleGameReview: 'your training plan',
+      createFlashcard: 'your flashcards',
+      lookupConcept: 'the concept library',
+      getQuizPosition: 'your past games',
+      checkQuizAnswer: 'the engine',
     };
     return names[name] || name;
   };
@@ -238,10 +392,94 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
     onAction?.(action);
   };
 
+  const handleResolvePending = async (pending: PendingCoachAction, approve: boolean) => {
+    try {
+      const resolved = await invoke<PendingCoachAction>('resolve_pending_coach_action', { id: pending.id, approve });
+      setMessages(prev => prev.map(m => ({
+        ...m,
+        pendingActions: m.pendingActions?.map(p => (p.id === resolved.id ? resolved : p)),
+      })));
+    } catch (err) {
+      console.error('Failed to resolve coach proposal:', err);
+    }
+  };
+
+  const ensureConversation = async () => {
+    if (!savedConversation.current) {
+      const id = await invoke<number>('create_conversation', { title: `${MODE_TITLES[mode.mode]} with Gurgeh`, mode });
+      savedConversation.current = { id, saved: 0 };
+    }
+    return savedConversation.current;
+  };
+
+  // What sending the current input would put in front of the model, built the way sendMessage builds it
+  const handlePreview = async () => {
+    try {
+      const conversationId = mode.mode === 'general' ? null : (await ensureConversation()).id;
+      const setup = await invoke<CoachSetup>('get_coach_setup', { conversationId });
+      const history: ChatMessage[] = messages
+        .filter(m => !m.isStreaming)
+        .map(m => ({ role: m.role, content: m.content }));
+      history.push({ role: 'user', content: input || '(your message)' });
+      const boardContext = await getBoardContextPrompt();
+      const moveCheck = boardContext && input ? await getMoveCheckPrompt(input, conversationId) : null;
+      setPreview(await previewCoachRequest(history, moveCheck ? `${boardContext}\n\n${moveCheck}` : boardContext, setup));
+    } catch (err) {
+      setExportStatus(`[!] Preview failed: ${err}`);
+    }
+  };
+
+  const handleExport = async (format: 'markdown' | 'html') => {
+    try {
+      const conversation = await ensureConversation();
+      const finished = messages.filter((m) => !m.isStreaming);
+      for (const msg of finished.slice(conversation.saved)) {
+        await invoke('add_message', { conversationId: conversation.id, role: msg.role, content: msg.content, traceId: msg.traceId ?? null });
+      }
+      conversation.saved = finished.length;
+
+      const result = await invoke<{ path: string }>('export_conversation', { conversationId: conversation.id, format });
+      setExportStatus(`[OK] Saved to ${result.path}`);
+    } catch (err) {
+      setExportStatus(`[!] Export failed: ${err}`);
+    }
+  };
+
+  const handleDelete = async () => {
+    const conversation = savedConversation.current;
+    if (!conversation) return;
+    try {
+      const operation = await invoke<OperationRecord | null>('delete_conversation', { conversationId: conversation.id });
+      if (!operation) return;
+      setDeleted({ operation, messages, conversation });
+      savedConversation.current = null;
+      setMessages([]);
+      setExportStatus(null);
+    } catch (err) {
+      setExportStatus(`[!] Delete failed: ${err}`);
+    }
+  };
+
+  const handleUndoDelete = async () => {
+    if (!deleted) return;
+    try {
+      const undone = await invoke<OperationRecord | null>('undo_last_operation');
+      if (undone?.id === deleted.operation.id) {
+        savedConversation.current = deleted.conversation;
+        setMessages(deleted.messages);
+      } else {
+        setExportStatus('[!] Too late to undo');
+      }
+      setDeleted(null);
+    } catch (err) {
+      setExportStatus(`[!] Undo failed: ${err}`);
+    }
+  };
+
   return (
     <div className="gurgeh-chat-container" style={position ? { left: position.x, top: position.y } : {}}>
       <XPWindow
-        title="Gurgeh - Chess Coach"
+        title={`Gurgeh - ${MODE_TITLES[mode.mode]}`}
         icon="[G]"
         onClose={onClose}
         width={400}
@@ -270,6 +508,23 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
                     ))}
                   </div>
                 )}
+                {msg.pendingActions && msg.pendingActions.length > 0 && (
+                  <div className="gurgeh-pending-actions">
+                    {msg.pendingActions.map((pending) => (
+                      <div key={pending.id} className="gurgeh-pending-action">
+                        <span>{pending.summary}</span>
+                        {pending.status === 'pending' ? (
+                          <span className="gurgeh-pending-buttons">
+                            <XPButton primary onClick={() => handleResolvePending(pending, true)}>Approve</XPButton>
+                            <XPButton onClick={() => handleResolvePending(pending, false)}>Decline</XPButton>
+                          </span>
+                        ) : (
+                          <span className="gurgeh-pending-status">[{pending.status === 'approved' ? 'OK' : 'x'}] {pending.status}</span>
+                        )}
+                      </div>
+                    ))}
+                  </div>
+                )}
               </div>
             </div>
           ))}
@@ -308,6 +563,37 @@ export const GurgrehChat: React.FC<GurgrehChatProps> = ({
           </XPButton>
         </div>
 
+        <div className="gurgeh-export">
+          <XPButton onClick={() => handleExport('markdown')} disabled={isLoading || messages.length === 0}>
+            Export Markdown
+          </XPButton>
+          <XPButton onClick={() => handleExport('html')} disabled={isLoading || messages.length === 0}>
+            Export HTML
+          </XPButton>
+          <XPButton onClick={() => (preview ? setPreview(null) : handlePreview())} disabled={isLoading}>
+            {preview ? 'Hide Request' : 'Preview Request'}
+          </XPButton>
+          {savedConversation.current && (
+            <XPButton onClick={handleDelete} disabled={isLoading}>
+              Delete
+            </XPButton>
+          )}
+          {deleted && (
+            <>
+              <span className="gurgeh-export-status">{deleted.operation.label}.</span>
+              <XPButton onClick={handleUndoDelete}>Undo</XPButton>
+            </>
+          )}
+          {exportStatus && <span className="gurgeh-export-status">{exportStatus}</span>}
+        </div>
+
+        {preview && (
+          <pre className="gurgeh-prompt-preview">
+            {preview.messages.map((m) => `[${m.role}]\n${m.content}`).join('\n\n')}
+            {`\n\n[tools]\n${preview.tools.join(', ') || 'none'}`}
+          </pre>
+        )}
+
         {!apiKey && (
           <div className="gurgeh-api-warning">
             [!] No API key configured. Add one in Settings to enable AI coaching.
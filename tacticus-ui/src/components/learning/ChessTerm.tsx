@@ -17,7 +17,7 @@ export const ChessTerm: React.FC<ChessTermProps> = ({ term, children }) => {
     if (!definition && !loading) {
       setLoading(true);
       try {
-        const def = await invoke<string | null>('define_term', { term: term.toLowerCase() });
+        const def = await invoke<string | null>('define_term', { term: term.toLowerCase(), allowLlm: true });
         setDefinition(def);
       } catch (err) {
         console.error('Failed to fetch definition:', err);
@@ -1,4 +1,5 @@
 import React, { useState, useEffect } from 'react';
+import type { HealthStatus } from '../../lib/health';
 
 interface TaskbarItem {
   id: string;
@@ -12,13 +13,18 @@ interface XPTaskbarProps {
   onItemClick?: (id: string) => void;
   onStartClick?: () => void;
   onSettingsClick?: () => void;
+  /** Startup self-test result, shown as a tray indicator */
+  health?: HealthStatus | null;
 }
 
+const HEALTH_ICONS: Record<string, string> = { ok: '[ok]', warning: '[!]', failed: '[X]', skipped: '[ok]' };
+
 export const XPTaskbar: React.FC<XPTaskbarProps> = ({
   items = [],
   onItemClick,
   onStartClick,
   onSettingsClick,
+  health,
 }) => {
   const [time, setTime] = useState(new Date());
 
@@ -56,6 +62,14 @@ export const XPTaskbar: React.FC<XPTaskbarProps> = ({
       </div>
 
       <div className="xp-system-tray">
+        {health && (
+          <span
+            className="xp-tray-time"
+            title={health.checks.map((c) => `${c.name}: ${c.status} - ${c.detail}`).join('\n')}
+          >
+            {HEALTH_ICONS[health.overall]}
+          </span>
+        )}
         <button 
           className="xp-tray-button"
           onClick={onSettingsClick}
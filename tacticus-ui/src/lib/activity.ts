@@ -0,0 +1,72 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** Tell the backend about input at most this often; its idle worker waits minutes */
+const REPORT_INTERVAL_MS = 10_000;
+
+const ACTIVITY_EVENTS = ['pointerdown', 'keydown', 'wheel'] as const;
+
+/**
+ * Report user input so background exercise generation stops while the user is
+ * around. Returns a function that stops reporting.
+ */
+export function startActivityReporting(intervalMs = REPORT_INTERVAL_MS): () => void {
+  let lastReport = 0;
+  const onActivity = () => {
+    const now = Date.now();
+    if (now - lastReport < intervalMs) return;
+    lastReport = now;
+    invoke('report_user_activity').catch((err) => console.error('Failed to report activity:', err));
+  };
+  ACTIVITY_EVENTS.forEach((event) => window.addEventListener(event, onActivity, { passive: true }));
+  return () => {
+    ACTIVITY_EVENTS.forEach((event) => window.removeEventListener(event, onActivity));
+  };
+}
+
+/** Activities the backend times for the study-time breakdown */
+export type TrackedActivity = 'playing' | 'puzzles' | 'analysis' | 'coach_chat';
+
+let activity: TrackedActivity | null = null;
+/** The open session's id, once the backend has started it */
+let session: Promise<number | null> | null = null;
+
+function beginSession() {
+  if (!activity || session || !document.hasFocus()) return;
+  session = invoke<number>('start_activity', { activity }).catch((err) => {
+    console.error('Failed to start activity:', err);
+    return null;
+  });
+}
+
+function endSession() {
+  const ending = session;
+  session = null;
+  ending
+    ?.then((id) => id !== null && invoke('stop_activity', { sessionId: id }))
+    .catch((err) => console.error('Failed to stop activity:', err));
+}
+
+/** Switch the timed activity, `null` when the screen shows none */
+export function setActivity(next: TrackedActivity | null): void {
+  if (next === activity) return;
+  endSession();
+  activity = next;
+  beginSession();
+}
+
+/**
+ * Time the current activity while the window has focus, ending the session when it
+ * loses focus or closes. Sessions a crash leaves open are closed at the next start.
+ * Returns a function that stops tracking.
+ */
+export function startActivityTracking(): () => void {
+  window.addEventListener('focus', beginSession);
+  window.addEventListener('blur', endSession);
+  window.addEventListener('beforeunload', endSession);
+  return () => {
+    window.removeEventListener('focus', beginSession);
+    window.removeEventListener('blur', endSession);
+    window.removeEventListener('beforeunload', endSession);
+    endSession();
+  };
+}
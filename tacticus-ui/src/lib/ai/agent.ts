@@ -1,18 +1,25 @@
 import { invoke } from '@tauri-apps/api/core';
 import { GURGEH_SYSTEM_PROMPT } from './prompts';
-import type { ChatMessage, PlayerStats, Game, TrainingProgress, ImprovementTrend, WeaknessEntry } from './types';
+import type { ChatMessage, PlayerStats, ColorStats, Game, TrainingProgress, ImprovementTrend, WeaknessEntry, OpponentStrengthStats, TimeClassStats, ProgressDiff, SavedPosition, PendingCoachAction, ConceptLookup, CoachSetup, CoachTrace, PromptPreview, QuizPosition, QuizAnswer, PastAdvice, GuidedQuestion } from './types';
 
 // Default model to use
 const DEFAULT_MODEL = 'anthropic/claude-3-haiku';
 
 export interface StreamCallbacks {
   onChunk: (chunk: string) => void;
-  onComplete: (fullText: string) => void;
+  /** `trace` records the full exchange with the model, for coach transparency */
+  onComplete: (fullText: string, trace: CoachTrace) => void;
   onError: (error: Error) => void;
   onToolCall?: (toolName: string, args: unknown) => void;
   onToolResult?: (toolName: string, result: unknown) => void;
 }
 
+const TIME_CLASS_PARAMETER = {
+  type: 'string',
+  enum: ['bullet', 'blitz', 'rapid', 'classical'],
+  description: 'Only games of this time control class (optional)'
+};
+
 // Tool definitions for the LLM
 const TOOL_DEFINITIONS = [
   {
@@ -23,7 +30,8 @@ const TOOL_DEFINITIONS = [
       parameters: {
         type: 'object',
         properties: {
-          count: { type: 'number', description: 'Number of recent games to retrieve (1-20)' }
+          count: { type: 'number', description: 'Number of recent games to retrieve (1-20)' },
+          timeClass: TIME_CLASS_PARAMETER
         },
         required: ['count']
       }
@@ -33,7 +41,7 @@ const TOOL_DEFINITIONS = [
     type: 'function',
     function: {
       name: 'getPlayerStats',
-      description: "Get comprehensive player statistics including ELO, win rate, and identified weaknesses",
+      description: "Get comprehensive player statistics including ELO, win rate, identified weaknesses, and results, accuracy and favorite openings split by White and Black",
       parameters: { type: 'object', properties: {} }
     }
   },
@@ -41,7 +49,7 @@ const TOOL_DEFINITIONS = [
     type: 'function',
     function: {
       name: 'getWeaknessHistory',
-      description: "Get the player's weakness history showing exercise types where they struggle",
+      description: "Get the player's weakness history: exercise types weakest first, with solve rate and whether their latest attempts are improving, stable or declining compared with the ones before (new when there are too few attempts)",
       parameters: {
         type: 'object',
         properties: {
@@ -73,7 +81,8 @@ const TOOL_DEFINITIONS = [
       parameters: {
         type: 'object',
         properties: {
-          minMistakes: { type: 'number', description: 'Minimum number of mistakes to filter by (1-10)' }
+          minMistakes: { type: 'number', description: 'Minimum number of mistakes to filter by (1-10)' },
+          timeClass: TIME_CLASS_PARAMETER
         },
         required: ['minMistakes']
       }
@@ -87,7 +96,7 @@ const TOOL_DEFINITIONS = [
       parameters: {
         type: 'object',
         properties: {
-          exerciseType: { type: 'string', description: 'Optional exercise type to filter by' }
+          exerciseType: { type: 'string', enum: ['Tactics', 'Endgame', 'Opening', 'Positional', 'Calculation', 'Strategy', 'Defense'], description: 'Optional exercise type to filter by' }
         }
       }
     }
@@ -105,14 +114,255 @@ const TOOL_DEFINITIONS = [
         required: ['days']
       }
     }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'getOpponentStrengthStats',
+      description: "Get the player's results split by opponent rating band: win rate, average centipawn loss and the most common ways they lose in each band. Use it to see whether they play differently against stronger or weaker opponents",
+      parameters: { type: 'object', properties: {} }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'getTimeClassStats',
+      description: "Get the player's results split by time control class (bullet, blitz, rapid, classical): win rate, average centipawn loss, blunders per game and losses that included a blunder. Use it to tell fast-game blunders apart from slow-game planning problems, which need different training",
+      parameters: { type: 'object', properties: {} }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'getProgressDiff',
+      description: "Compare the player's latest analysed games with the same number before them: average centipawn loss in the opening, middlegame and endgame, blunders per 40 moves and miss rate per training theme. Each change says whether it is clear, suggestive or within noise. Use it before praising progress, and only call a change real when it is clear",
+      parameters: {
+        type: 'object',
+        properties: {
+          games: { type: 'number', description: 'Games in each period (1-50, default 10)' }
+        }
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'getSavedPositions',
+      description: "Get the positions the player bookmarked or imported into their position library, newest first, with their notes and tags. Use it when they ask about a position they saved",
+      parameters: {
+        type: 'object',
+        properties: {
+          tag: { type: 'string', description: 'Only positions carrying this tag (optional)' },
+          origin: { type: 'string', enum: ['import', 'game', 'puzzle', 'coach', 'analysis'], description: 'Only positions saved from here (optional)' }
+        }
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'bookmarkPosition',
+      description: "Save a position to the player's position library so they can analyse or train it later. Only do this when the player asks you to",
+      parameters: {
+        type: 'object',
+        properties: {
+          fen: { type: 'string', description: 'The position to save, as FEN' },
+          note: { type: 'string', description: 'Short note on what to look at in the position' },
+          tags: { type: 'array', items: { type: 'string' }, description: "Tags such as a theme or opening name, e.g. ['pin', 'Sicilian']" }
+        },
+        required: ['fen']
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'addToTrainingPlan',
+      description: "Propose adding training on a theme to the player's training plan. Nothing changes until the player approves it in the chat, so tell them what you proposed and why",
+      parameters: {
+        type: 'object',
+        properties: {
+          theme: { type: 'string', enum: ['Tactics', 'Endgame', 'Opening', 'Positional', 'Calculation', 'Strategy', 'Defense'], description: 'Theme to train' },
+          title: { type: 'string', description: "Short title, e.g. 'Rook endgame drills' (optional)" },
+          dueDate: { type: 'string', description: 'When to do it, as YYYY-MM-DD, within the next 90 days (optional)' },
+          note: { type: 'string', description: 'What to focus on (optional)' }
+        },
+        required: ['theme']
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'scheduleGameReview',
+      description: "Propose scheduling a review of one of the player's saved games in their training plan. Nothing changes until the player approves it in the chat",
+      parameters: {
+        type: 'object',
+        properties: {
+          gameId: { type: 'number', description: 'Id of the game, e.g. from getRecentGames' },
+          dueDate: { type: 'string', description: 'When to review it, as YYYY-MM-DD, within the next 90 days (optional)' },
+          note: { type: 'string', description: 'What to look at in the game (optional)' }
+        },
+        required: ['gameId']
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'createFlashcard',
+      description: "Propose a flashcard with a question on the front and the answer on the back, optionally about a position. Nothing changes until the player approves it in the chat",
+      parameters: {
+        type: 'object',
+        properties: {
+          front: { type: 'string', description: "The question, e.g. 'How does White win this rook ending?'" },
+          back: { type: 'string', description: 'The answer' },
+          fen: { type: 'string', description: 'Position the card is about, as FEN (optional)' }
+        },
+        required: ['front', 'back']
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'lookupConcept',
+      description: "Look up a concept in the learning library: the explanation the app shows, an example position (FEN) and related concepts. Use it before teaching a concept so you match the concept pages",
+      parameters: {
+        type: 'object',
+        properties: {
+          name: { type: 'string', description: "Concept name or id, e.g. 'knight fork' or 'opposition'" }
+        },
+        required: ['name']
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'getQuizPosition',
+      description: "Get a position where the player went wrong in one of their recent games: FEN, move number, when it was played and against whom. The answer is not included; check the player's reply with checkQuizAnswer. Pass a phase to quiz one part of the game, e.g. endgames from their own games",
+      parameters: {
+        type: 'object',
+        properties: {
+          phase: { type: 'string', enum: ['Opening', 'Middlegame', 'Endgame'], description: 'Only positions from this phase (optional)' }
+        }
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'checkQuizAnswer',
+      description: "Check the move the player suggests for a quiz position and record the attempt. Returns whether it was the engine's move, what they played in the game, the better move and the score so far",
+      parameters: {
+        type: 'object',
+        properties: {
+          gameId: { type: 'number', description: 'gameId from getQuizPosition' },
+          ply: { type: 'number', description: 'ply from getQuizPosition' },
+          answer: { type: 'string', description: "The player's move in SAN or UCI, e.g. 'Nf3'" }
+        },
+        required: ['gameId', 'ply', 'answer']
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'searchPastAdvice',
+      description: "Search what you told the player in earlier conversations, best matches first, with how many days ago you said it. Use it before recommending what to study or how to play, so you build on your earlier advice instead of contradicting it",
+      parameters: {
+        type: 'object',
+        properties: {
+          query: { type: 'string', description: "Words to look for, e.g. 'rook endgames' or 'Sicilian'" }
+        },
+        required: ['query']
+      }
+    }
+  },
+  {
+    type: 'function',
+    function: {
+      name: 'nextGuidedQuestion',
+      description: "Move a guided exercise on to its next leading question. Returns the question, what the position shows to check the answer against, and on the last question the solution. Call it only once the player has answered the question they are on or is plainly stuck, never for replies like 'ok' or 'thanks'",
+      parameters: { type: 'object', properties: {} }
+    }
   }
 ];
 
-// Execute a tool by name
-async function executeTool(name: string, args: Record<string, unknown>): Promise<unknown> {
+// Backend command behind each tool, as named in conversation mode whitelists
+const TOOL_COMMANDS: Record<string, string> = {
+  getRecentGames: 'get_recent_games',
+  getPlayerStats: 'get_player_stats',
+  getWeaknessHistory: 'get_weakness_history',
+  searchGamesByOpening: 'search_games_by_opening',
+  getGamesWithMistakes: 'get_games_with_mistakes',
+  getTrainingProgress: 'get_training_progress',
+  getImprovementTrend: 'get_improvement_trend',
+  getOpponentStrengthStats: 'get_opponent_strength_stats',
+  getTimeClassStats: 'get_time_class_stats',
+  getProgressDiff: 'get_progress_diff',
+  getSavedPositions: 'get_saved_positions',
+  bookmarkPosition: 'bookmark_position',
+  addToTrainingPlan: 'propose_training_plan_item',
+  scheduleGameReview: 'propose_game_review',
+  createFlashcard: 'propose_flashcard',
+  lookupConcept: 'lookup_concept',
+  getQuizPosition: 'get_quiz_position',
+  checkQuizAnswer: 'check_quiz_answer',
+  searchPastAdvice: 'search_past_advice',
+  nextGuidedQuestion: 'next_guided_question',
+};
+
+// Without a setup the player's privacy settings are unknown, so no tools are offered
+const toolAllowed = (name: string, setup: CoachSetup | null) =>
+  setup !== null && setup.allowed_tools.includes(TOOL_COMMANDS[name]);
+
+const summarizeColor = (c: ColorStats) => ({
+  games: c.games,
+  wins: c.wins,
+  losses: c.losses,
+  draws: c.draws,
+  score: c.score.toFixed(1) + '%',
+  averageCentipawnLoss: c.average_centipawn_loss === null ? null : Math.round(c.average_centipawn_loss),
+  favoriteOpenings: c.favorite_openings.map(o => `${o.opening}: ${o.games} games, ${o.score.toFixed(0)}%`),
+});
+
+// Write tools only propose a change; the player approves it before anything is saved
+function proposeChange(name: string, args: Record<string, unknown>, conversationId: number | null): Promise<PendingCoachAction> {
+  const dueDate = args.dueDate as string | undefined;
+  const note = args.note as string | undefined;
+  switch (name) {
+    case 'addToTrainingPlan':
+      return invoke<PendingCoachAction>('propose_training_plan_item', {
+        theme: args.theme as string,
+        title: args.title as string | undefined,
+        dueDate,
+        note,
+        conversationId,
+      });
+    case 'scheduleGameReview':
+      return invoke<PendingCoachAction>('propose_game_review', { gameId: args.gameId as number, dueDate, note, conversationId });
+    default:
+      return invoke<PendingCoachAction>('propose_flashcard', {
+        front: args.front as string,
+        back: args.back as string,
+        fen: args.fen as string | undefined,
+        conversationId,
+      });
+  }
+}
+
+// Execute a tool by name. Ratings are only put in the result when the player shares them.
+async function executeTool(name: string, args: Record<string, unknown>, setup: CoachSetup): Promise<unknown> {
+  const conversationId = setup.conversation_id;
+  const shareRatings = setup.privacy.share_ratings;
   switch (name) {
     case 'getRecentGames': {
-      const games = await invoke<Game[]>('get_recent_games', { count: args.count as number });
+      const games = await invoke<Game[]>('get_recent_games', {
+        count: args.count as number,
+        timeClass: (args.timeClass as string) || null,
+      });
       return {
         success: true,
         games: games.map(g => ({
@@ -120,11 +370,12 @@ async function executeTool(name: string, args: Record<string, unknown>): Promise
           result: g.result,
           playerColor: g.player_color,
           opponentType: g.opponent_type,
-          opponentElo: g.opponent_elo,
+          ...(shareRatings ? { opponentElo: g.opponent_elo } : {}),
           moves: g.moves.length,
           mistakes: g.mistakes,
           blunders: g.blunders,
           opening: g.opening_name,
+          timeClass: g.time_class,
           playedAt: g.created_at,
         })),
       };
@@ -134,8 +385,7 @@ async function executeTool(name: string, args: Record<string, unknown>): Promise
       return {
         success: true,
         stats: {
-          currentElo: stats.current_elo,
-          peakElo: stats.peak_elo,
+          ...(shareRatings ? { currentElo: stats.current_elo, peakElo: stats.peak_elo } : {}),
           gamesPlayed: stats.games_played,
           wins: stats.wins,
           losses: stats.losses,
@@ -147,6 +397,8 @@ async function executeTool(name: string, args: Record<string, unknown>): Promise
           style: stats.style,
           weaknesses: stats.weaknesses,
           strengths: stats.strengths,
+          asWhite: summarizeColor(stats.as_white),
+          asBlack: summarizeColor(stats.as_black),
         },
       };
     }
@@ -179,7 +431,10 @@ async function executeTool(name: string, args: Record<string, unknown>): Promise
       };
     }
     case 'getGamesWithMistakes': {
-      const games = await invoke<Game[]>('get_games_with_mistakes', { minMistakes: args.minMistakes as number });
+      const games = await invoke<Game[]>('get_games_with_mistakes', {
+        minMistakes: args.minMistakes as number,
+        timeClass: (args.timeClass as string) || null,
+      });
       return {
         success: true,
         totalGames: games.length,
@@ -214,7 +469,7 @@ async function executeTool(name: string, args: Record<string, unknown>): Promise
       return {
         success: true,
         trend: {
-          eloChange: trend.elo_change,
+          ...(shareRatings ? { eloChange: trend.elo_change } : {}),
           gamesPlayed: trend.games_in_period,
           winRate: trend.win_rate_in_period.toFixed(1) + '%',
           exercisesCompleted: trend.exercises_in_period,
@@ -222,6 +477,173 @@ async function executeTool(name: string, args: Record<string, unknown>): Promise
         },
       };
     }
+    case 'getOpponentStrengthStats': {
+      const stats = await invoke<OpponentStrengthStats>('get_opponent_strength_stats');
+      return {
+        success: true,
+        playerElo: stats.player_elo,
+        unratedGames: stats.unrated_games,
+        bands: stats.bands.map(b => ({
+          opponentElo: b.band,
+          games: b.games,
+          wins: b.wins,
+          losses: b.losses,
+          draws: b.draws,
+          winRate: b.win_rate.toFixed(1) + '%',
+          averageCentipawnLoss: b.average_centipawn_loss === null ? null : Math.round(b.average_centipawn_loss),
+          avgBlunders: b.avg_blunders.toFixed(1),
+          losingPatterns: b.losing_patterns.map(p => `${p.description} (${p.games})`),
+        })),
+      };
+    }
+    case 'getTimeClassStats': {
+      const classes = await invoke<TimeClassStats[]>('get_time_class_stats');
+      return {
+        success: true,
+        timeClasses: classes.map(c => ({
+          timeClass: c.time_class,
+          games: c.games,
+          wins: c.wins,
+          losses: c.losses,
+          draws: c.draws,
+          winRate: c.win_rate.toFixed(1) + '%',
+          averageCentipawnLoss: c.average_centipawn_loss === null ? null : Math.round(c.average_centipawn_loss),
+          avgBlunders: c.avg_blunders.toFixed(1),
+          blunderLosses: c.blunder_losses,
+        })),
+      };
+    }
+    case 'getProgressDiff': {
+      const diff = await invoke<ProgressDiff>('get_progress_diff', { games: args.games as number | undefined });
+      return {
+        success: true,
+        recentGames: diff.recent_games,
+        earlierGames: diff.earlier_games,
+        summary: diff.summary,
+        metrics: diff.metrics.map(m => ({
+          metric: m.label,
+          before: m.before,
+          after: m.after,
+          improvementPercent: m.improvement_percent,
+          confidence: m.confidence,
+          samples: `${m.before_samples} ${m.unit} before, ${m.after_samples} since`,
+        })),
+      };
+    }
+    case 'getSavedPositions': {
+      const positions = await invoke<SavedPosition[]>('get_saved_positions', {
+        tag: args.tag as string | undefined,
+        origin: args.origin as string | undefined,
+      });
+      return {
+        success: true,
+        positions: positions.slice(0, 20).map(p => ({
+          id: p.id,
+          fen: p.fen,
+          title: p.title,
+          note: p.note,
+          tags: p.tags,
+          savedFrom: p.origin,
+          savedAt: p.created_at,
+        })),
+      };
+    }
+    case 'bookmarkPosition': {
+      const id = await invoke<number>('bookmark_position', {
+        fen: args.fen as string,
+        origin: 'coach',
+        note: args.note as string | undefined,
+        tags: args.tags as string[] | undefined,
+      });
+      return { success: true, id };
+    }
+    case 'addToTrainingPlan':
+    case 'scheduleGameReview':
+    case 'createFlashcard': {
+      const pending = await proposeChange(name, args, conversationId);
+      return {
+        success: true,
+        pending: true,
+        actionId: pending.id,
+        summary: pending.summary,
+        note: 'Waiting for the player to approve or decline this in the chat',
+        // Shown as approve / decline buttons under the reply
+        pendingAction: pending,
+      };
+    }
+    case 'lookupConcept': {
+      const concept = await invoke<ConceptLookup>('lookup_concept', { name: args.name as string });
+      return {
+        success: true,
+        concept: {
+          id: concept.id,
+          name: concept.name,
+          category: concept.category,
+          difficulty: concept.difficulty,
+          explanation: concept.explanation,
+          exampleFen: concept.example_fen,
+          relatedConcepts: concept.related_concepts.map(c => c.name),
+        },
+      };
+    }
+    case 'getQuizPosition': {
+      const position = await invoke<QuizPosition>('get_quiz_position', { conversationId, phase: args.phase as string | undefined });
+      return {
+        success: true,
+        position: {
+          gameId: position.game_id,
+          ply: position.ply,
+          fen: position.fen,
+          moveNumber: position.move_number,
+          playedAt: position.played_at,
+          playerColor: position.player_color,
+          opponent: position.opponent,
+          opening: position.opening,
+          phase: position.phase,
+        },
+      };
+    }
+    case 'checkQuizAnswer': {
+      const answer = await invoke<QuizAnswer>('check_quiz_answer', {
+        conversationId,
+        gameId: args.gameId as number,
+        ply: args.ply as number,
+        answer: args.answer as string,
+      });
+      return {
+        success: true,
+        correct: answer.correct,
+        answer: answer.answer,
+        playedInGame: answer.played,
+        bestMove: answer.best,
+        pawnsLost: (answer.centipawn_loss / 100).toFixed(1),
+        quality: answer.quality,
+        theme: answer.theme,
+        score: answer.answered === null ? null : `${answer.correct_so_far} of ${answer.answered}`,
+      };
+    }
+    case 'searchPastAdvice': {
+      const advice = await invoke<PastAdvice[]>('search_past_advice', { query: args.query as string, conversationId });
+      return {
+        success: true,
+        count: advice.length,
+        advice: advice.map(a => ({
+          conversation: a.conversation_title,
+          excerpt: a.excerpt,
+          daysAgo: a.days_ago,
+        })),
+      };
+    }
+    case 'nextGuidedQuestion': {
+      const next = await invoke<GuidedQuestion>('next_guided_question', { conversationId });
+      return {
+        success: true,
+        step: next.step,
+        question: next.question,
+        facts: next.facts,
+        solution: next.solution,
+      };
+    }
     default:
       return { success: false, error: `Unknown tool: ${name}` };
   }
@@ -244,26 +666,82 @@ interface OpenRouterChoice {
   finish_reason?: string;
 }
 
+// The prompt for a coach request, before the player's privacy settings are applied
+function buildCoachPrompt(messages: ChatMessage[], boardContext: string | null, setup: CoachSetup | null): OpenRouterMessage[] {
+  return [
+    { role: 'system', content: GURGEH_SYSTEM_PROMPT },
+    // Due dates for proposed plan items are checked against this
+    { role: 'system', content: `Today's date is ${new Date().toLocaleDateString('en-CA')}.` },
+    // The conversation's mode: the game under review, the exercise being helped with
+    ...(setup?.system_prompts ?? []).map(content => ({ role: 'system' as const, content })),
+    // The board on screen, so questions like "why is this move bad?" need no FEN
+    ...(boardContext ? [{ role: 'system' as const, content: boardContext }] : []),
+    ...messages.map(m => ({ role: m.role as 'user' | 'assistant', content: m.content })),
+  ];
+}
+
+const coachTools = (setup: CoachSetup | null) => TOOL_DEFINITIONS.filter(t => toolAllowed(t.function.name, setup));
+
+// Exactly what `streamCoachResponse` would send first for the same arguments
+export async function previewCoachRequest(
+  messages: ChatMessage[],
+  boardContext: string | null,
+  setup: CoachSetup | null,
+): Promise<PromptPreview> {
+  const prompt = await invoke<OpenRouterMessage[]>('prepare_coach_prompt', {
+    messages: buildCoachPrompt(messages, boardContext, setup),
+  });
+  return {
+    messages: prompt.map(m => ({ role: m.role, content: m.content })),
+    tools: coachTools(setup).map(t => t.function.name),
+  };
+}
+
 // Stream a response from the AI coach with tool execution
 export async function streamCoachResponse(
   apiKey: string,
   messages: ChatMessage[],
   callbacks: StreamCallbacks,
   model: string = DEFAULT_MODEL,
+  boardContext: string | null = null,
+  setup: CoachSetup | null = null,
 ): Promise<void> {
-  const openRouterMessages: OpenRouterMessage[] = [
-    { role: 'system', content: GURGEH_SYSTEM_PROMPT },
-    ...messages.map(m => ({ role: m.role as 'user' | 'assistant', content: m.content })),
-  ];
+  const tools = coachTools(setup);
+  const openRouterMessages = buildCoachPrompt(messages, boardContext, setup);
 
   let currentToolCalls: Array<{ id: string; name: string; arguments: string }> = [];
   let maxIterations = 5;
+  const trace: CoachTrace = {
+    conversation_id: setup?.conversation_id ?? null,
+    model,
+    // The first request as sent, once privacy settings have been applied
+    prompt: [],
+    tool_calls: [],
+    responses: [],
+  };
 
   try {
+    // Offline builds send nothing, whichever side makes the call
+    if (!(await invoke<boolean>('llm_enabled'))) {
+      throw new Error('This build of Tacticus has no online coach');
+    }
+
+    // OpenRouter unless configured otherwise, or the mock server under --mock-llm
+    const baseUrl = await invoke<string>('get_llm_base_url');
+
     while (maxIterations > 0) {
       maxIterations--;
 
-      const response = await fetch('https://openrouter.ai/api/v1/chat/completions', {
+      // Shares the backend's per-minute cap, so a runaway loop or double-click can't burn tokens
+      await invoke('reserve_coach_call');
+
+      // What the player's privacy settings let leave the app: history limit, ratings, name
+      const prompt = await invoke<OpenRouterMessage[]>('prepare_coach_prompt', { messages: openRouterMessages });
+      if (trace.prompt.length === 0) {
+        trace.prompt = prompt.map(m => ({ role: m.role, content: m.content }));
+      }
+
+      const response = await fetch(`${baseUrl}/chat/completions`, {
         method: 'POST',
         headers: {
           'Authorization': `Bearer ${apiKey}`,
@@ -273,8 +751,9 @@ export async function streamCoachResponse(
         },
         body: JSON.stringify({
           model,
-          messages: openRouterMessages,
-          tools: TOOL_DEFINITIONS,
+          messages: prompt,
+          ...(tools.length > 0 ? { tools } : {}),
+          ...(setup ? { max_tokens: setup.max_tokens } : {}),
           stream: true,
         }),
       });
@@ -336,9 +815,11 @@ export async function streamCoachResponse(
         }
       }
 
+      trace.responses.push(fullText);
+
       // If no tool calls, we're done
       if (!hasToolCalls || currentToolCalls.length === 0) {
-        callbacks.onComplete(fullText);
+        callbacks.onComplete(fullText, trace);
         return;
       }
 
@@ -357,7 +838,9 @@ export async function streamCoachResponse(
         callbacks.onToolCall?.(tc.name, JSON.parse(tc.arguments || '{}'));
 
         try {
-          const result = await executeTool(tc.name, JSON.parse(tc.arguments || '{}'));
+          const result = setup && toolAllowed(tc.name, setup)
+            ? await executeTool(tc.name, JSON.parse(tc.arguments || '{}'), setup)
+            : { success: false, error: `Tool ${tc.name} is not available in this conversation` };
           callbacks.onToolResult?.(tc.name, result);
 
           openRouterMessages.push({
@@ -372,10 +855,11 @@ export async function streamCoachResponse(
             content: JSON.stringify({ success: false, error: String(error) }),
           });
         }
+        trace.tool_calls.push({ name: tc.name, arguments: tc.arguments, result: openRouterMessages[openRouterMessages.length - 1].content });
       }
     }
 
-    callbacks.onComplete('');
+    callbacks.onComplete('', trace);
   } catch (error) {
     callbacks.onError(error instanceof Error ? error : new Error(String(error)));
   }
@@ -16,6 +16,8 @@ export interface Game {
   opening_name: string | null;
   created_at: string;
   finished_at: string | null;
+  /** 'bullet', 'blitz', 'rapid' or 'classical'; null for untimed games */
+  time_class: string | null;
 }
 
 export interface PlayerStats {
@@ -33,6 +35,30 @@ export interface PlayerStats {
   style: string;
   weaknesses: string[];
   strengths: string[];
+  as_white: ColorStats;
+  as_black: ColorStats;
+}
+
+export interface OpeningScore {
+  opening: string;
+  games: number;
+  wins: number;
+  losses: number;
+  draws: number;
+  score: number;
+}
+
+export interface ColorStats {
+  color: string;
+  games: number;
+  wins: number;
+  losses: number;
+  draws: number;
+  win_rate: number;
+  score: number;
+  average_centipawn_loss: number | null;
+  analysed_games: number;
+  favorite_openings: OpeningScore[];
 }
 
 export interface TrainingProgress {
@@ -58,6 +84,171 @@ export interface WeaknessEntry {
   recent_trend: string;
 }
 
+export interface LosingPattern {
+  description: string;
+  games: number;
+}
+
+export interface OpponentBandStats {
+  band: string;
+  min_elo: number | null;
+  max_elo: number | null;
+  games: number;
+  wins: number;
+  losses: number;
+  draws: number;
+  win_rate: number;
+  average_centipawn_loss: number | null;
+  analysed_games: number;
+  avg_mistakes: number;
+  avg_blunders: number;
+  losing_patterns: LosingPattern[];
+}
+
+export interface OpponentStrengthStats {
+  player_elo: number;
+  bands: OpponentBandStats[];
+  unrated_games: number;
+}
+
+export interface TimeClassStats {
+  time_class: string;
+  games: number;
+  wins: number;
+  losses: number;
+  draws: number;
+  win_rate: number;
+  average_centipawn_loss: number | null;
+  analysed_games: number;
+  avg_mistakes: number;
+  avg_blunders: number;
+  /** Losses that included a blunder */
+  blunder_losses: number;
+}
+
+/** How far a change between two periods can be told apart from chance */
+export type ProgressConfidence = 'clear' | 'suggestive' | 'within_noise' | 'too_little_data';
+
+/** One metric over the earlier and the recent period; lower is better */
+export interface MetricChange {
+  key: string;
+  label: string;
+  before: number;
+  after: number;
+  /** "games" or "attempts" */
+  unit: string;
+  before_samples: number;
+  after_samples: number;
+  /** Negative when it got worse, null when the earlier value was zero */
+  improvement_percent: number | null;
+  confidence: ProgressConfidence;
+  summary: string;
+}
+
+/** The player's latest analysed games against the ones before them */
+export interface ProgressDiff {
+  games_per_period: number;
+  recent_games: number;
+  earlier_games: number;
+  recent_since: string | null;
+  earlier_since: string | null;
+  /** Clear changes first */
+  metrics: MetricChange[];
+  summary: string;
+}
+
+/** A position in the player's library, imported or bookmarked from a game, puzzle, coach chat or the analysis board */
+export interface SavedPosition {
+  id: number;
+  profile_id: number;
+  fen: string;
+  initial_fen: string;
+  /** UCI moves from `initial_fen` to `fen` */
+  moves: string[];
+  source: 'fen' | 'lichess_url' | 'pgn' | 'puzzle';
+  origin: 'import' | 'game' | 'puzzle' | 'coach' | 'analysis';
+  title: string | null;
+  note: string | null;
+  tags: string[];
+  created_at: string;
+  /** For imported puzzles, the answer from `fen` in UCI with the opponent's replies */
+  solution_line: string[];
+}
+
+/** A change the coach proposed, shown in the chat until the player approves or declines it */
+export interface PendingCoachAction {
+  id: number;
+  profile_id: number;
+  conversation_id: number | null;
+  /** JSON of the change, read back by the backend on approval */
+  change: string;
+  summary: string;
+  status: 'pending' | 'approved' | 'declined';
+  created_at: string;
+  resolved_at: string | null;
+}
+
+/** A delete or reset, undoable with `undo_last_operation` for a minute after it was made */
+export interface OperationRecord {
+  id: number;
+  profile_id: number;
+  kind: 'delete_game' | 'delete_conversation' | 'reset_plan';
+  target_ids: number[];
+  label: string;
+  created_at: string;
+  undone_at: string | null;
+}
+
+/** Where a saved game first left a line of the player's repertoire */
+export interface OpeningDeviation {
+  game_id: number;
+  profile_id: number;
+  opening_key: string;
+  opening_name: string;
+  /** Index of the deviating move in the game */
+  ply: number;
+  /** The deviating move's number, "4." or "4...", counted from the game's start */
+  move_number: string;
+  /** The player left the line, rather than their opponent */
+  by_player: boolean;
+  played: string;
+  expected: string;
+  centipawn_loss: number;
+  played_worse: boolean;
+  /** Rest of the line after the player's deviation, best reply after the opponent's (SAN) */
+  recommended: string[];
+  /** Position before the deviating move */
+  fen: string;
+  created_at: string;
+}
+
+/** An item in the training plan, with the button that starts it */
+export interface TrainingPlanEntry {
+  id: number;
+  profile_id: number;
+  kind: 'training' | 'game_review';
+  title: string;
+  theme: string | null;
+  game_id: number | null;
+  note: string | null;
+  /** YYYY-MM-DD */
+  due_date: string | null;
+  source: 'player' | 'coach';
+  completed_at: string | null;
+  created_at: string;
+  action: CoachAction | null;
+}
+
+export interface Flashcard {
+  id: number;
+  profile_id: number;
+  front: string;
+  back: string;
+  fen: string | null;
+  source: 'player' | 'coach';
+  created_at: string;
+}
+
 export interface Conversation {
   id: number;
   profile_id: number;
@@ -77,14 +268,332 @@ export interface Message {
   created_at: string;
 }
 
-export interface CoachAction {
-  action_type: string;
-  label: string;
-  data: string;
+export interface ConceptLookup {
+  id: string;
+  name: string;
+  category: string;
+  difficulty: string;
+  explanation: string;
+  example_fen: string | null;
+  related_concepts: { id: string; name: string }[];
+}
+
+/** A position from the player's own games for the coach's quiz; carries no answer */
+export interface QuizPosition {
+  game_id: number;
+  ply: number;
+  fen: string;
+  move_number: string;
+  played_at: string;
+  player_color: string;
+  opponent: string;
+  opening: string | null;
+  phase: string;
+}
+
+export interface QuizAnswer {
+  correct: boolean;
+  answer: string;
+  played: string;
+  best: string;
+  centipawn_loss: number;
+  quality: string;
+  theme: string;
+  /** Quiz score so far, when answered in a quiz conversation */
+  answered: number | null;
+  correct_so_far: number | null;
+}
+
+/** The leading question a guided exercise has moved on to */
+export interface GuidedQuestion {
+  step: string;
+  question: string;
+  /** What the position shows, to check the answer against; not for the player */
+  facts: string;
+  /** On the last question, the solution to explain if they still can't find it */
+  solution: string | null;
+}
+
+/** Something the coach told the player in an earlier conversation */
+export interface PastAdvice {
+  conversation_id: number;
+  conversation_title: string | null;
+  /** Matched words are wrapped in [brackets] */
+  excerpt: string;
+  given_at: string;
+  days_ago: number;
+}
+
+/** What a coach conversation is about; stored with the conversation by the backend */
+export type ConversationMode =
+  | { mode: 'general' }
+  | { mode: 'game_review'; game_id: number }
+  | { mode: 'exercise_help'; exercise_id: number }
+  | { mode: 'guided_help'; exercise_id: number }
+  | { mode: 'quiz_me'; answered?: number; correct?: number };
+
+/** Post-game coach summary, sent with the `game-debrief-ready` event */
+export interface GameDebrief {
+  game_id: number;
+  /** Game review conversation holding the debrief */
+  conversation_id: number;
+  result: string;
+  final_fen: string;
+  bullets: string[];
+  puzzle_theme: string;
+  source: 'coach' | 'engine';
+}
+
+export interface QualityCounts {
+  brilliant: number;
+  great: number;
+  good: number;
+  inaccuracy: number;
+  mistake: number;
+  blunder: number;
+}
+
+/** Compact summary of an analysed game for list views, from `get_game_report_card` */
+export interface GameReportCard {
+  game_id: number;
+  result: string;
+  player_color: string;
+  opening_name: string | null;
+  white_accuracy: number;
+  black_accuracy: number;
+  white_counts: QualityCounts;
+  black_counts: QualityCounts;
+  /** Positions before the player's worst moves, worst first */
+  key_moment_fens: string[];
+  performance_rating: number;
+}
+
+/** One half-move of a compared game */
+export interface ComparedMove {
+  san: string;
+  uci: string;
+  by_player: boolean;
+  /** Centipawns after the move from the player's point of view, clamped; null before analysis */
+  eval: number | null;
+  quality: string | null;
+}
+
+/** One game's side of a comparison */
+export interface ComparedGame {
+  game_id: number;
+  result: string;
+  player_color: string;
+  opening_name: string | null;
+  played_at: string;
+  plies: number;
+  /** Plies in common with the closest of the other games */
+  follows_until: number;
+  eval_curve: number[];
+  player_accuracy: number | null;
+  opponent_accuracy: number | null;
+}
+
+/** Games lined up ply by ply, from `compare_games` */
+export interface GameComparison {
+  games: ComparedGame[];
+  shared_plies: number;
+  /** First ply where the games part ways */
+  divergence_ply: number | null;
+  shared_opening: string | null;
+  /** One row per ply; `moves` has an entry per game, null once that game is over */
+  timeline: {
+    ply: number;
+    move_number: string;
+    shared: boolean;
+    moves: (ComparedMove | null)[];
+  }[];
 }
 
+/** Prompts and limits the backend sets for a conversation's mode */
+export interface CoachSetup {
+  /** Stored conversation the setup is for */
+  conversation_id: number | null;
+  mode: ConversationMode;
+  system_prompts: string[];
+  /** Backend command names of the tools the coach may call */
+  allowed_tools: string[];
+  /** Replies must be resolved by the backend before they are shown */
+  solution_locked: boolean;
+  /** The moderation policy can change replies, so they are shown once the backend has checked them */
+  moderated: boolean;
+  /** Reply length cap, from kid mode or the profile's coaching verbosity */
+  max_tokens: number;
+  /** Tool results leave ratings out unless `share_ratings` */
+  privacy: PromptPrivacy;
+}
+
+/** What coach prompts may carry about the student */
+export interface PromptPrivacy {
+  /** Saved games: recent form, game reviews, game quizzes and the game tools */
+  share_games: boolean;
+  /** Ratings in prompts and tool results */
+  share_ratings: boolean;
+  /** Earlier chat messages sent with each request */
+  history_messages: number;
+  /** Replace the profile name with "the student" */
+  redact_name: boolean;
+}
+
+/** How the coach's replies are filtered before they are shown or saved */
+export interface ModerationPolicy {
+  /** Mask profanity */
+  filter_profanity: boolean;
+  /** Replace replies giving financial, medical or legal advice */
+  block_off_topic: boolean;
+  /** Stricter word list and off-topic blocking; follows the kid mode setting */
+  kid_mode: boolean;
+}
+
+/** A coach request as it would leave the app under the current privacy settings */
+export interface PromptPreview {
+  messages: Array<{ role: string; content: string }>;
+  /** Names of the tools the model would be offered */
+  tools: string[];
+}
+
+/** How much the coach says, for every coach feature */
+export type CoachVerbosity = 'terse' | 'normal' | 'deep_dive';
+
+/** How the encouragement policy reads frustration and what it may change */
+export interface EncouragementSettings {
+  enabled: boolean;
+  /** Failed exercises in a row that count as frustration */
+  failure_streak: number;
+  /** Session rating drop that counts as a dip */
+  rating_dip: number;
+  /** Abandoned sessions and early resignations in a day that count as rage-quitting */
+  quits: number;
+  /** Easier exercises when frustrated, harder ones on a streak */
+  adjust_difficulty: boolean;
+}
+
+/** The encouragement policy's current read of the player */
+export interface Encouragement {
+  mood: 'steady' | 'frustrated' | 'on_a_roll';
+  signals: {
+    failed_in_a_row: number;
+    solved_in_a_row: number;
+    rating_change: number | null;
+    recent_quits: number;
+  };
+  reasons: string[];
+  guidance: string | null;
+  difficulty_shift: number;
+}
+
+/** What was sent to the model and what came back for one coach reply (coach transparency) */
+export interface CoachTrace {
+  conversation_id: number | null;
+  model: string;
+  prompt: Array<{ role: string; content: string }>;
+  tool_calls: Array<{ name: string; arguments: string; result: string }>;
+  /** Raw text of each model response, tool-calling rounds included */
+  responses: string[];
+}
+
+/** A button under a coach message; the backend validates these before they reach the UI */
+export type CoachAction = { label: string } & (
+  | { action_type: 'open_settings' }
+  | { action_type: 'play_game' }
+  | { action_type: 'quiz_me' }
+  | { action_type: 'start_training'; theme?: string }
+  | { action_type: 'resume_training'; session_id: number; theme?: string }
+  | { action_type: 'open_concept'; concept_id: string }
+  | { action_type: 'load_game'; game_id: number; ply: number; fen: string }
+  | { action_type: 'setup_position'; fen: string }
+);
+
 export interface ChatMessage {
   role: 'user' | 'assistant';
   content: string;
   actions?: CoachAction[];
 }
+
+/** How far the player has got with one opening line's walkthrough */
+export interface OpeningProgress {
+  opening_key: string;
+  attempts: number;
+  completions: number;
+  /** Fewest wrong answers in a finished walkthrough */
+  best_mistakes: number | null;
+  last_completed_at: string | null;
+}
+
+/** An opening line to walk through, keyed like "c50-italian-game" */
+export interface OpeningLine {
+  key: string;
+  eco: string;
+  name: string;
+  /** SAN with move numbers, e.g. "1.e4 e5 2.Nf3" */
+  moves: string;
+  plies: number;
+  progress: OpeningProgress | null;
+}
+
+export interface WalkthroughStep {
+  ply: number;
+  uci: string;
+  san: string;
+  /** Position before the move */
+  fen: string;
+  explanation: string;
+}
+
+/** A started walkthrough; as Black, `played` already holds White's first move */
+export interface WalkthroughSession {
+  key: string;
+  eco: string;
+  name: string;
+  color: 'white' | 'black';
+  steps: WalkthroughStep[];
+  played: string[];
+  fen: string;
+  progress: OpeningProgress | null;
+}
+
+/** A quiz answer in an opening walkthrough; transposed moves of the line count */
+export interface OpeningQuizResult {
+  verdict: 'mainline' | 'transposition' | 'wrong';
+  /** UCI moves so far, with the answer and the reply when the answer kept to the line */
+  played: string[];
+  fen: string;
+  reply: string | null;
+  /** SAN of the move the line expected, when the answer left it */
+  expected: string | null;
+  completed: boolean;
+  explanations: string[];
+  /** Set once the line is completed */
+  progress: OpeningProgress | null;
+}
+
+export interface StepElaboration {
+  ply: number;
+  text: string;
+  source: 'coach' | 'data';
+}
+
+/** Why a wrong try at an exercise fails, by the engine's reply */
+export interface Refutation {
+  tried: string;
+  /** Null when the try ends the game, e.g. in stalemate */
+  reply: string | null;
+  centipawn_loss: number;
+  summary: string;
+}
+
+/** Card shown after an exercise is solved or failed, stored with its result */
+export interface ExercisePostMortem {
+  result_id: number;
+  solution: string | null;
+  explanation: string;
+  refutations: Refutation[];
+  /** The coach's take; null when it couldn't be reached */
+  elaboration: string | null;
+  source: 'coach' | 'engine';
+  created_at: string;
+}
@@ -0,0 +1,88 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type SnapshotKind = 'game' | 'training' | 'coach_draft';
+
+export interface SessionSnapshot {
+  kind: SnapshotKind;
+  sessionKey: string;
+  payload: unknown;
+}
+
+export interface RecoverableSession {
+  kind: SnapshotKind;
+  session_key: string;
+  payload: any;
+  saved_at: string;
+}
+
+/** Returns the current state to save, or null when there is nothing in flight */
+type SnapshotSource = () => SessionSnapshot | null;
+
+const AUTOSAVE_INTERVAL_MS = 15000;
+
+const sources: SnapshotSource[] = [];
+const lastSaved = new Map<string, string>();
+
+export async function saveSnapshot(snapshot: SessionSnapshot): Promise<void> {
+  const key = `${snapshot.kind}:${snapshot.sessionKey}`;
+  const serialized = JSON.stringify(snapshot.payload);
+  // Skip writes when nothing changed since the last save
+  if (lastSaved.get(key) === serialized) return;
+
+  await invoke('save_session_snapshot', {
+    kind: snapshot.kind,
+    sessionKey: snapshot.sessionKey,
+    payload: snapshot.payload,
+  });
+  lastSaved.set(key, serialized);
+}
+
+export async function discardSnapshot(kind: SnapshotKind, sessionKey: string): Promise<void> {
+  lastSaved.delete(`${kind}:${sessionKey}`);
+  try {
+    await invoke('discard_session_snapshot', { kind, sessionKey });
+  } catch (err) {
+    console.error('Failed to discard session snapshot:', err);
+  }
+}
+
+export async function getRecoverableSessions(): Promise<RecoverableSession[]> {
+  try {
+    return await invoke<RecoverableSession[]>('get_recoverable_sessions');
+  } catch (err) {
+    console.error('Failed to load recoverable sessions:', err);
+    return [];
+  }
+}
+
+export function registerSnapshotSource(source: SnapshotSource): () => void {
+  sources.push(source);
+  return () => {
+    const index = sources.indexOf(source);
+    if (index >= 0) sources.splice(index, 1);
+  };
+}
+
+/** Save every registered source now, e.g. before the window closes */
+export async function flushSnapshots(): Promise<void> {
+  for (const source of sources) {
+    const snapshot = source();
+    if (!snapshot) continue;
+    try {
+      await saveSnapshot(snapshot);
+    } catch (err) {
+      console.error('Autosave failed:', err);
+    }
+  }
+}
+
+/** Start periodic autosave. Returns a function that stops it. */
+export function startAutosave(intervalMs = AUTOSAVE_INTERVAL_MS): () => void {
+  const timer = setInterval(flushSnapshots, intervalMs);
+  const onUnload = () => { flushSnapshots(); };
+  window.addEventListener('beforeunload', onUnload);
+  return () => {
+    clearInterval(timer);
+    window.removeEventListener('beforeunload', onUnload);
+  };
+}
@@ -0,0 +1,85 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** A wrong move in UCI and the position it was played from; `start_fen` when omitted */
+export interface WrongMove {
+  move: string;
+  fen?: string | null;
+}
+
+/** Board on screen, as reported to the coach */
+export interface BoardSnapshot {
+  fen: string;
+  /** Position `moves` were played from; the standard start when omitted */
+  start_fen?: string | null;
+  /** Moves in UCI, oldest first */
+  moves?: string[];
+  exercise_goal?: string | null;
+  /** Exercise on screen; coach chats about the board keep its solution locked */
+  exercise_id?: number | null;
+  /** Side the player is playing */
+  player_color?: 'white' | 'black' | null;
+  /** Side at the bottom of the board as shown; the player's side when omitted */
+  orientation?: 'white' | 'black' | null;
+  /** Wrong moves tried at the exercise on screen */
+  wrong_moves?: WrongMove[];
+  /** When the exercise was started, as from Date.now() */
+  exercise_started_at?: number | null;
+}
+
+/** Side to move in `fen`, which is the side the player solves for in an exercise */
+export function sideToMove(fen: string): 'white' | 'black' {
+  return fen.split(' ')[1] === 'b' ? 'black' : 'white';
+}
+
+let lastSnapshot: BoardSnapshot | null = null;
+let flippedTo: 'white' | 'black' | null = null;
+
+/** Tell the coach which board the player is looking at, or null when it closes */
+export async function reportBoard(snapshot: BoardSnapshot | null): Promise<void> {
+  if (!snapshot) flippedTo = null;
+  lastSnapshot = snapshot && { orientation: flippedTo, ...snapshot };
+  try {
+    await invoke('set_board_context', { snapshot: lastSnapshot });
+  } catch (err) {
+    console.error('Failed to report board context:', err);
+  }
+}
+
+/** The reported board was flipped or reset to a side; later reports keep that orientation until it closes */
+export function reportOrientation(orientation: 'white' | 'black'): void {
+  flippedTo = orientation;
+  if (lastSnapshot) reportBoard({ ...lastSnapshot, orientation });
+}
+
+/** Prompt block describing the reported board, or null when none is on screen */
+export async function getBoardContextPrompt(): Promise<string | null> {
+  try {
+    return await invoke<string | null>('get_board_context_prompt');
+  } catch (err) {
+    console.error('Failed to load board context:', err);
+    return null;
+  }
+}
+
+/** A move named in chat, as the backend resolved it on the reported board */
+export type MoveMention =
+  | { kind: 'legal'; text: string; san: string; uci: string; evaluation: number | null; best: string | null; best_evaluation: number | null; centipawn_loss: number | null }
+  | { kind: 'ambiguous'; text: string; options: string[] }
+  | { kind: 'illegal'; text: string; reason: string };
+
+/**
+ * Prompt block with the moves named in `message` checked on the reported board, or null
+ * when it names none. Keeps the coach from analysing moves that can't be played.
+ */
+export async function getMoveCheckPrompt(message: string, conversationId: number | null): Promise<string | null> {
+  try {
+    const check = await invoke<{ mentions: MoveMention[]; prompt: string } | null>('check_move_mentions', {
+      message,
+      conversationId,
+    });
+    return check?.prompt ?? null;
+  } catch (err) {
+    console.error('Failed to check moves in message:', err);
+    return null;
+  }
+}
@@ -0,0 +1,40 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type CheckStatus = 'ok' | 'warning' | 'failed' | 'skipped';
+
+export interface HealthCheck {
+  /** 'database', 'engine', 'api_key' or 'assets' */
+  name: string;
+  status: CheckStatus;
+  detail: string;
+}
+
+/** What the startup self-test found */
+export interface HealthStatus {
+  checks: HealthCheck[];
+  /** The worst status among the checks */
+  overall: CheckStatus;
+  /** The database could not be used; show the repair screen instead of the app */
+  needs_repair: boolean;
+  checked_at: string;
+}
+
+/** Result of the startup self-test, waiting for it if it is still running */
+export async function getHealthStatus(refresh = false): Promise<HealthStatus | null> {
+  try {
+    return await invoke<HealthStatus>('get_health_status', { refresh });
+  } catch (err) {
+    console.error('Failed to get health status:', err);
+    return null;
+  }
+}
+
+/** The database check alone, or the full result if it is in; returns without waiting for the self-test */
+export async function getQuickHealthStatus(): Promise<HealthStatus | null> {
+  try {
+    return await invoke<HealthStatus>('get_quick_health_status');
+  } catch (err) {
+    console.error('Failed to get health status:', err);
+    return null;
+  }
+}
@@ -0,0 +1,35 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+/** A .pgn or .fen file the OS asked Tacticus to open */
+export interface OpenedFile {
+  path: string;
+  name: string;
+  kind: 'pgn' | 'fen';
+  /** The file's text, ready for import_game or import_position */
+  source: string;
+  /** Why the file couldn't be read; `source` is empty then */
+  error: string | null;
+}
+
+/** Files opened since the last call, oldest first */
+export async function takeOpenedFiles(): Promise<OpenedFile[]> {
+  try {
+    return await invoke<OpenedFile[]>('take_opened_files');
+  } catch (err) {
+    console.error('Failed to get opened files:', err);
+    return [];
+  }
+}
+
+/**
+ * Call `onOpen` with files opened while the app is running, as macOS reports
+ * them. Returns a function that stops listening.
+ */
+export function onFilesOpened(onOpen: (files: OpenedFile[]) => void): () => void {
+  const unlisten = listen('files-opened', async () => {
+    const files = await takeOpenedFiles();
+    if (files.length > 0) onOpen(files);
+  });
+  return () => { unlisten.then((stop) => stop()); };
+}
@@ -0,0 +1,41 @@
+/** Daily workout progress, remembered per day in localStorage */
+
+const ACTIVE_KEY = 'workout-active';
+
+interface ActiveItem {
+  date: string;
+  index: number;
+  exerciseIds: number[];
+}
+
+const doneKey = (date: string) => `workout-${date}`;
+
+/** Indices of the items finished on the workout for `date` */
+export function loadWorkoutDone(date: string): number[] {
+  const saved = localStorage.getItem(doneKey(date));
+  return saved ? JSON.parse(saved) : [];
+}
+
+/** Mark item `index` of the workout for `date` done, or not done again */
+export function setWorkoutItemDone(date: string, index: number, done: boolean): number[] {
+  const current = loadWorkoutDone(date).filter((i) => i !== index);
+  const next = done ? [...current, index] : current;
+  localStorage.setItem(doneKey(date), JSON.stringify(next));
+  return next;
+}
+
+/** Note that item `index` has started on `exerciseIds`; it is done once a session on them finishes */
+export function startWorkoutItem(date: string, index: number, exerciseIds: number[]): void {
+  const active: ActiveItem = { date, index, exerciseIds };
+  localStorage.setItem(ACTIVE_KEY, JSON.stringify(active));
+}
+
+/** Mark the item under way done if the training session just finished was on its exercises */
+export function finishWorkoutExercises(exerciseIds: number[]): void {
+  const saved = localStorage.getItem(ACTIVE_KEY);
+  if (!saved) return;
+  const active: ActiveItem = JSON.parse(saved);
+  if (exerciseIds.length === 0 || !exerciseIds.every((id) => active.exerciseIds.includes(id))) return;
+  setWorkoutItemDone(active.date, active.index, true);
+  localStorage.removeItem(ACTIVE_KEY);
+}
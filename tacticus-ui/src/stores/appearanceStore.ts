@@ -0,0 +1,87 @@
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+
+export interface BoardTheme {
+  name: string;
+  light_square: string;
+  dark_square: string;
+  source: string;
+}
+
+export interface PieceSetInfo {
+  name: string;
+  source: string;
+}
+
+export interface BoardAssets {
+  piece_sets: PieceSetInfo[];
+  board_themes: BoardTheme[];
+  user_dir: string | null;
+}
+
+export interface BoardAppearance {
+  piece_set: string;
+  board_theme: BoardTheme;
+  /** Data URI per piece key ("wK", "bN", ...); empty for the standard set */
+  pieces: Record<string, string>;
+}
+
+/** How moves and boards are written; PGN and the coach's input stay in English SAN */
+export interface NotationPrefs {
+  moves: 'san' | 'long_algebraic';
+  pieces: 'letters' | 'figurine';
+  language: 'en' | 'de' | 'fr' | 'es' | 'it';
+  coordinates: boolean;
+}
+
+interface AppearanceStore {
+  appearance: BoardAppearance | null;
+  assets: BoardAssets | null;
+  notation: NotationPrefs | null;
+
+  loadAppearance: () => Promise<void>;
+  loadAssets: (reload?: boolean) => Promise<void>;
+  setAppearance: (pieceSet: string, boardTheme: string) => Promise<void>;
+  loadNotation: () => Promise<void>;
+  setNotation: (prefs: NotationPrefs) => Promise<void>;
+}
+
+/** Piece set, board theme and notation shared by every board in the app */
+export const useAppearanceStore = create<AppearanceStore>((set) => ({
+  appearance: null,
+  assets: null,
+  notation: null,
+
+  loadAppearance: async () => {
+    try {
+      set({ appearance: await invoke<BoardAppearance>('get_board_appearance') });
+    } catch (error) {
+      console.error('Failed to load board appearance:', error);
+    }
+  },
+
+  loadAssets: async (reload = false) => {
+    try {
+      set({ assets: await invoke<BoardAssets>(reload ? 'reload_board_assets' : 'list_board_assets') });
+    } catch (error) {
+      console.error('Failed to list board assets:', error);
+    }
+  },
+
+  setAppearance: async (pieceSet, boardTheme) => {
+    const appearance = await invoke<BoardAppearance>('set_board_appearance', { pieceSet, boardTheme });
+    set({ appearance });
+  },
+
+  loadNotation: async () => {
+    try {
+      set({ notation: await invoke<NotationPrefs>('get_notation_prefs') });
+    } catch (error) {
+      console.error('Failed to load notation preferences:', error);
+    }
+  },
+
+  setNotation: async (prefs) => {
+    set({ notation: await invoke<NotationPrefs>('set_notation_prefs', { prefs }) });
+  },
+}));
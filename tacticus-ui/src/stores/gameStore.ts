@@ -1,5 +1,7 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import { discardSnapshot, registerSnapshotSource } from '../lib/autosave';
+import { reportBoard } from '../lib/boardContext';
 
 interface GameState {
   fen: string;
@@ -24,6 +26,55 @@ interface EngineMove {
   evaluation: number;
 }
 
+/** How much help casual games give; calibration games get none */
+export type GameAssistance = 'off' | 'hints' | 'retries';
+
+/** A blunder the player may take back and try to improve on */
+export interface RetryDrill {
+  /** Position before the blunder, with the player to move */
+  fen: string;
+  played: string;
+  played_san: string;
+  centipawn_loss: number;
+  exercise_type: string;
+  difficulty: string;
+}
+
+export interface RetryVerdict {
+  accepted: boolean;
+  quality: string;
+  san: string;
+  /** Only revealed once a good move has been found */
+  best_san: string | null;
+}
+
+export interface RetryState {
+  drill: RetryDrill;
+  attempts: number;
+  startedAt: number;
+  verdict: RetryVerdict | null;
+  /** The accepted retry in UCI, playable instead of the blunder */
+  corrected: string | null;
+}
+
+export interface GameSnapshot {
+  fen: string;
+  gameHistory: string[];
+  playerColor: 'white' | 'black';
+  engineElo: number;
+  timeControl?: string | null;
+}
+
+/** A clock as offered in Play, minutes plus increment seconds ("10+0"), in the PGN
+ * form the backend classifies: seconds plus increment ("600+0") */
+export function toPgnTimeControl(clock: string): string | null {
+  const [minutes, increment = '0'] = clock.split('+');
+  const base = Number(minutes);
+  const extra = Number(increment);
+  if (!Number.isFinite(base) || !Number.isFinite(extra)) return null;
+  return `${Math.round(base * 60)}+${extra}`;
+}
+
 interface GameStore {
   gameState: GameState | null;
   selectedSquare: string | null;
@@ -32,17 +83,69 @@ interface GameStore {
   gameHistory: string[];
   playerColor: 'white' | 'black';
   engineElo: number;
+  /** Offer a retry drill after the player blunders */
+  retriesEnabled: boolean;
+  /** The retry drill on screen, holding back the engine's reply */
+  retry: RetryState | null;
+  /** Position the game started from */
+  initialFen: string | null;
+  /** The game's clock in PGN form, e.g. "600+0"; null when untimed */
+  timeControl: string | null;
 
   // Actions
-  startNewGame: (playerColor?: 'white' | 'black') => Promise<void>;
+  startNewGame: (playerColor?: 'white' | 'black', timeControl?: string | null) => Promise<void>;
   selectSquare: (square: string) => void;
   makeMove: (from: string, to: string, promotion?: string) => Promise<boolean>;
   makeEngineMove: () => Promise<void>;
   loadPosition: (fen: string) => Promise<void>;
+  restoreGame: (snapshot: GameSnapshot) => Promise<void>;
   setEngineElo: (elo: number) => void;
+  setRetriesEnabled: (enabled: boolean) => void;
+  retryMove: (from: string, to: string, promotion?: string) => Promise<void>;
+  resumeFromRetry: (line: 'original' | 'corrected') => Promise<void>;
   resetSelection: () => void;
 }
 
+/** Log a finished retry drill as an exercise attempt */
+function recordRetry(retry: RetryState, solved: boolean) {
+  invoke<number>('record_exercise_result', {
+    result: {
+      exercise_type: retry.drill.exercise_type,
+      difficulty: retry.drill.difficulty,
+      position_fen: retry.drill.fen,
+      solved,
+      attempts: retry.attempts,
+      time_seconds: Math.round((Date.now() - retry.startedAt) / 1000),
+      hints_used: 0,
+    },
+  }).catch((err) => console.error('Failed to record retry:', err));
+}
+
+const START_FEN = 'rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1';
+
+/** The game has ended on the board: drop its autosave and store it with its clock,
+ * so stats can tell blitz from classical. The backend works out the result from the moves. */
+function finishGame(state: GameStore) {
+  discardSnapshot('game', 'current');
+  if (!state.gameState) return;
+  invoke<number>('save_game', {
+    game: {
+      initial_fen: state.initialFen ?? START_FEN,
+      final_fen: state.gameState.fen,
+      moves: state.gameHistory,
+      result: '',
+      player_color: state.playerColor,
+      opponent_type: 'engine',
+      opponent_elo: state.engineElo,
+      analysis: null,
+      mistakes: 0,
+      blunders: 0,
+      opening_name: null,
+      time_control: state.timeControl,
+    },
+  }).catch((err) => console.error('Failed to save game:', err));
+}
+
 export const useGameStore = create<GameStore>((set, get) => ({
   gameState: null,
   selectedSquare: null,
@@ -51,8 +154,12 @@ export const useGameStore = create<GameStore>((set, get) => ({
   gameHistory: [],
   playerColor: 'white',
   engineElo: 800,
+  retriesEnabled: false,
+  retry: null,
+  initialFen: null,
+  timeControl: null,
 
-  startNewGame: async (playerColor = 'white') => {
+  startNewGame: async (playerColor = 'white', timeControl = null) => {
     try {
       const gameState = await invoke<GameState>('get_initial_position');
       set({ 
@@ -60,7 +167,10 @@ export const useGameStore = create<GameStore>((set, get) => ({
         selectedSquare: null, 
         legalMovesForSelected: [],
         gameHistory: [],
-        playerColor 
+        playerColor,
+        retry: null,
+        initialFen: gameState.fen,
+        timeControl,
       });
       
       // If player is black, let engine move first
@@ -114,7 +224,20 @@ export const useGameStore = create<GameStore>((set, get) => ({
 
         // If game not over and it's engine's turn, make engine move
         if (!result.new_state.is_checkmate && !result.new_state.is_stalemate) {
+          // A blunder can be retried first; the engine waits until the drill is done
+          const drill = get().retriesEnabled
+            ? await invoke<RetryDrill | null>('check_for_blunder', { fen: gameState.fen, played: uciMove }).catch((err) => {
+              console.error('Failed to check for a blunder:', err);
+              return null;
+            })
+            : null;
+          if (drill) {
+            set({ retry: { drill, attempts: 0, startedAt: Date.now(), verdict: null, corrected: null } });
+            return true;
+          }
           setTimeout(() => get().makeEngineMove(), 500);
+        } else {
+          finishGame(get());
         }
         return true;
       }
@@ -149,6 +272,9 @@ export const useGameStore = create<GameStore>((set, get) => ({
           gameHistory: [...get().gameHistory, engineMove.uci],
           isThinking: false
         });
+        if (result.new_state.is_checkmate || result.new_state.is_stalemate) {
+          finishGame(get());
+        }
       }
     } catch (err) {
       console.error('Engine move failed:', err);
@@ -165,11 +291,94 @@ export const useGameStore = create<GameStore>((set, get) => ({
     }
   },
 
+  restoreGame: async (snapshot: GameSnapshot) => {
+    try {
+      const gameState = await invoke<GameState>('get_position_from_fen', { fen: snapshot.fen });
+      set({
+        gameState,
+        selectedSquare: null,
+        legalMovesForSelected: [],
+        gameHistory: snapshot.gameHistory,
+        playerColor: snapshot.playerColor,
+        engineElo: snapshot.engineElo,
+        retry: null,
+        initialFen: START_FEN,
+        timeControl: snapshot.timeControl ?? null,
+      });
+    } catch (err) {
+      console.error('Failed to restore game:', err);
+    }
+  },
+
   setEngineElo: (elo: number) => {
     set({ engineElo: elo });
   },
 
+  setRetriesEnabled: (enabled: boolean) => {
+    set({ retriesEnabled: enabled });
+  },
+
+  retryMove: async (from: string, to: string, promotion?: string) => {
+    const { retry } = get();
+    if (!retry || retry.verdict?.accepted) return;
+
+    const attempt = from + to + (promotion || '');
+    try {
+      const verdict = await invoke<RetryVerdict>('check_retry_move', { fen: retry.drill.fen, attempt });
+      const updated: RetryState = {
+        ...retry,
+        attempts: retry.attempts + 1,
+        verdict,
+        corrected: verdict.accepted ? attempt : null,
+      };
+      set({ retry: updated });
+      if (verdict.accepted) recordRetry(updated, true);
+    } catch (err) {
+      console.error('Failed to check retry:', err);
+    }
+  },
+
+  resumeFromRetry: async (line: 'original' | 'corrected') => {
+    const { retry, gameHistory } = get();
+    if (!retry) return;
+    // Giving up after trying counts as a missed exercise; skipping outright doesn't count
+    if (!retry.verdict?.accepted && retry.attempts > 0) recordRetry(retry, false);
+    set({ retry: null });
+
+    if (line === 'corrected' && retry.corrected) {
+      try {
+        const result = await invoke<MoveResult>('make_move', { fen: retry.drill.fen, uciMove: retry.corrected });
+        if (result.success && result.new_state) {
+          set({ gameState: result.new_state, gameHistory: [...gameHistory.slice(0, -1), retry.corrected] });
+          if (result.new_state.is_checkmate || result.new_state.is_stalemate) {
+            finishGame(get());
+            return;
+          }
+        }
+      } catch (err) {
+        console.error('Failed to play the corrected move:', err);
+      }
+    }
+    setTimeout(() => get().makeEngineMove(), 500);
+  },
+
   resetSelection: () => {
     set({ selectedSquare: null, legalMovesForSelected: [] });
   },
 }));
+
+// Autosave the game in progress so it can be restored after a crash
+registerSnapshotSource(() => {
+  const { gameState, gameHistory, playerColor, engineElo, timeControl } = useGameStore.getState();
+  if (!gameState || gameState.is_checkmate || gameState.is_stalemate || gameHistory.length === 0) {
+    return null;
+  }
+  const payload: GameSnapshot = { fen: gameState.fen, gameHistory, playerColor, engineElo, timeControl };
+  return { kind: 'game', sessionKey: 'current', payload };
+});
+
+// Keep the coach's view of the board in step with the game
+useGameStore.subscribe((state, prev) => {
+  if (!state.gameState || state.gameState.fen === prev.gameState?.fen) return;
+  reportBoard({ fen: state.gameState.fen, moves: state.gameHistory, player_color: state.playerColor });
+});
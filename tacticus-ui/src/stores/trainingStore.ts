@@ -1,5 +1,9 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import { discardSnapshot, registerSnapshotSource } from '../lib/autosave';
+import { reportBoard, sideToMove, type WrongMove } from '../lib/boardContext';
+import { finishWorkoutExercises } from '../lib/workoutProgress';
+import type { ExercisePostMortem } from '../lib/ai/types';
 
 interface ExerciseData {
   id: number;
@@ -10,6 +14,10 @@ interface ExerciseData {
   fen: string;
   hints: string[];
   solution_moves: string[];
+  /** Opening, Middlegame or Endgame */
+  phase: string;
+  /** More than one for puzzles played out against the opponent's replies */
+  moves_to_find: number;
 }
 
 interface TrainingSession {
@@ -22,13 +30,36 @@ interface ExerciseResult {
   correct: boolean;
   explanation: string;
   correct_move: string | null;
+  /** 'alternative' when the engine accepted a move other than the stored solution */
+  solution_match: 'canonical' | 'alternative' | 'wrong';
+  /** The opponent's answer (UCI) when a multi-move puzzle goes on */
+  reply: string | null;
+  /** Position after `reply`, where the next move is found */
+  fen: string | null;
+}
+
+export interface TrainingSnapshot {
+  session: TrainingSession;
+  currentExerciseIndex: number;
+  score: number;
+  streak: number;
+  sessionId?: number | null;
 }
 
 interface TrainingStore {
   session: TrainingSession | null;
+  /** Database id of the stored training session, grouping its exercise results */
+  sessionId: number | null;
+  exerciseStartedAt: number;
   currentExerciseIndex: number;
   currentExercise: ExerciseData | null;
   exerciseResult: ExerciseResult | null;
+  /** Wrong moves tried at the current exercise, across retries, each with the position it was played from */
+  wrongMoves: WrongMove[];
+  /** Card for the latest attempt at the current exercise, once built */
+  postMortem: ExercisePostMortem | null;
+  /** Where a multi-move puzzle has got to, with the note on the opponent's last reply */
+  linePosition: { fen: string; note: string } | null;
   selectedSquare: string | null;
   score: number;
   streak: number;
@@ -36,53 +67,136 @@ interface TrainingStore {
   loading: boolean;
 
   // Actions
-  startTrainingSession: (count?: number) => Promise<void>;
+  /** `theme` is an exercise type to focus the session on, `phase` a game phase to limit it to */
+  startTrainingSession: (count?: number, theme?: string, phase?: string) => Promise<void>;
+  /** A one-exercise session on a position from the position library */
+  startBookmarkExercise: (positionId: number) => Promise<void>;
+  /** A session on exactly these exercises, e.g. a daily workout item, under `focus` */
+  startExerciseSet: (exerciseIds: number[], focus: string) => Promise<void>;
+  /** Drill a checkmate pattern by concept id, e.g. 'smothered_mate'; every pattern without one */
+  startMatePatternDrill: (pattern?: string) => Promise<void>;
   checkSolution: (move: string) => Promise<boolean>;
   nextExercise: () => void;
   getHint: () => Promise<string | null>;
   selectSquare: (square: string) => void;
   resetExercise: () => void;
   endSession: () => void;
+  restoreSession: (snapshot: TrainingSnapshot) => void;
 }
 
+const finishStoredSession = (sessionId: number | null) => {
+  if (sessionId === null) return;
+  invoke('finish_training_session', { sessionId }).catch((err) =>
+    console.error('Failed to finish training session:', err)
+  );
+};
+
+/** Store `session` in the database and the state that starts it */
+/** Multi-move puzzles are tracked by the backend; a fresh try starts from the first move */
+const restartLine = (exercise: ExerciseData | null | undefined) => {
+  if (!exercise || exercise.moves_to_find <= 1) return;
+  invoke('restart_exercise_line', { exerciseId: exercise.id }).catch((err) =>
+    console.error('Failed to restart puzzle line:', err)
+  );
+};
+
+const openSession = async (session: TrainingSession) => {
+  restartLine(session.exercises[0]);
+  const sessionId = await invoke<number>('start_training_session', {
+    totalExercises: session.total_exercises,
+    focusAreas: session.focus_areas,
+  }).catch((err) => {
+    console.error('Failed to store training session:', err);
+    return null;
+  });
+
+  return {
+    session,
+    sessionId,
+    exerciseStartedAt: Date.now(),
+    currentExerciseIndex: 0,
+    currentExercise: session.exercises[0] || null,
+    exerciseResult: null,
+    wrongMoves: [],
+    postMortem: null,
+    linePosition: null,
+    score: 0,
+    streak: 0,
+    hintsUsed: 0,
+    loading: false,
+  };
+};
+
 export const useTrainingStore = create<TrainingStore>((set, get) => ({
   session: null,
+  sessionId: null,
+  exerciseStartedAt: Date.now(),
   currentExerciseIndex: 0,
   currentExercise: null,
   exerciseResult: null,
+  wrongMoves: [],
+  postMortem: null,
+  linePosition: null,
   selectedSquare: null,
   score: 0,
   streak: 0,
   hintsUsed: 0,
   loading: false,
 
-  startTrainingSession: async (count = 10) => {
+  startTrainingSession: async (count = 10, theme, phase) => {
     set({ loading: true });
     try {
-      const session = await invoke<TrainingSession>('get_training_exercises', {
-        count,
-        userElo: 800, // TODO: Get from user profile
-        weaknesses: [],
-      });
-      
-      set({ 
-        session, 
-        currentExerciseIndex: 0,
-        currentExercise: session.exercises[0] || null,
-        exerciseResult: null,
-        score: 0,
-        streak: 0,
-        hintsUsed: 0,
-        loading: false
-      });
+      // A theme drills the positions the player missed before
+      const session = theme
+        ? await invoke<TrainingSession>('generate_theme_drill', { exerciseType: theme, count, phase })
+        : await invoke<TrainingSession>('get_training_exercises', {
+            count,
+            userElo: 800, // TODO: Get from user profile
+            weaknesses: [],
+            phase,
+          });
+      set(await openSession(session));
     } catch (err) {
       console.error('Failed to start training session:', err);
       set({ loading: false });
     }
   },
 
+  startBookmarkExercise: async (positionId) => {
+    set({ loading: true });
+    try {
+      const session = await invoke<TrainingSession>('start_bookmark_exercise', { positionId });
+      set(await openSession(session));
+    } catch (err) {
+      console.error('Failed to start bookmark exercise:', err);
+      set({ loading: false });
+    }
+  },
+
+  startExerciseSet: async (exerciseIds, focus) => {
+    set({ loading: true });
+    try {
+      const session = await invoke<TrainingSession>('start_exercise_set', { exerciseIds, focus });
+      set(await openSession(session));
+    } catch (err) {
+      console.error('Failed to start exercises:', err);
+      set({ loading: false });
+    }
+  },
+
+  startMatePatternDrill: async (pattern) => {
+    set({ loading: true });
+    try {
+      const session = await invoke<TrainingSession>('start_mate_pattern_drill', { pattern: pattern ?? null });
+      set(await openSession(session));
+    } catch (err) {
+      console.error('Failed to start checkmate pattern drill:', err);
+      set({ loading: false });
+    }
+  },
+
   checkSolution: async (move: string) => {
-    const { currentExercise, streak, score } = get();
+    const { currentExercise, streak, score, session, sessionId, currentExerciseIndex, exerciseStartedAt, hintsUsed, wrongMoves, linePosition } = get();
     if (!currentExercise) return false;
 
     try {
@@ -91,6 +205,12 @@ export const useTrainingStore = create<TrainingStore>((set, get) => ({
         userMove: move,
       });
 
+      // A right move in a longer puzzle: show the reply and wait for the next move
+      if (result.correct && result.fen) {
+        set({ linePosition: { fen: result.fen, note: result.explanation }, selectedSquare: null });
+        return true;
+      }
+
       if (result.correct) {
         const bonus = streak >= 3 ? 50 : 0;
         set({ 
@@ -101,10 +221,46 @@ export const useTrainingStore = create<TrainingStore>((set, get) => ({
       } else {
         set({ 
           exerciseResult: result,
+          wrongMoves: [...wrongMoves, { move, fen: linePosition?.fen ?? currentExercise.fen }],
           streak: 0
         });
       }
 
+      invoke<number>('record_exercise_result', {
+        result: {
+          exercise_type: currentExercise.exercise_type,
+          difficulty: currentExercise.difficulty,
+          position_fen: currentExercise.fen,
+          solved: result.correct,
+          attempts: 1,
+          time_seconds: Math.round((Date.now() - exerciseStartedAt) / 1000),
+          hints_used: hintsUsed,
+          session_id: sessionId,
+          alternative_solution: result.solution_match === 'alternative',
+          exercise_id: currentExercise.id,
+        },
+      })
+        .then((resultId) => {
+          if (session && currentExerciseIndex >= session.exercises.length - 1) {
+            finishStoredSession(sessionId);
+            finishWorkoutExercises(session.exercises.map((e) => e.id));
+          }
+          return invoke<ExercisePostMortem>('build_exercise_postmortem', {
+            resultId,
+            exerciseId: currentExercise.id,
+            solved: result.correct,
+            assisted: hintsUsed > 0,
+            wrongMoves: get().wrongMoves,
+          });
+        })
+        .then((postMortem) => {
+          // Only if the player is still looking at this attempt
+          if (get().currentExercise === currentExercise && get().exerciseResult === result) {
+            set({ postMortem });
+          }
+        })
+        .catch((err) => console.error('Failed to record exercise result:', err));
+
       return result.correct;
     } catch (err) {
       console.error('Failed to check solution:', err);
@@ -118,10 +274,15 @@ export const useTrainingStore = create<TrainingStore>((set, get) => ({
 
     const nextIndex = currentExerciseIndex + 1;
     if (nextIndex < session.exercises.length) {
+      restartLine(session.exercises[nextIndex]);
       set({
         currentExerciseIndex: nextIndex,
         currentExercise: session.exercises[nextIndex],
+        exerciseStartedAt: Date.now(),
         exerciseResult: null,
+        wrongMoves: [],
+        postMortem: null,
+        linePosition: null,
         selectedSquare: null,
         hintsUsed: 0,
       });
@@ -129,13 +290,16 @@ export const useTrainingStore = create<TrainingStore>((set, get) => ({
   },
 
   getHint: async () => {
-    const { currentExercise, hintsUsed } = get();
+    const { currentExercise, hintsUsed, wrongMoves, exerciseStartedAt } = get();
     if (!currentExercise) return null;
 
     try {
+      // Hints speak to the moves already tried, and go further the longer the player is stuck
       const hint = await invoke<string | null>('get_exercise_hint', {
         exerciseId: currentExercise.id,
         hintIndex: hintsUsed,
+        wrongMoves,
+        elapsedSeconds: Math.round((Date.now() - exerciseStartedAt) / 1000),
       });
 
       if (hint) {
@@ -154,20 +318,81 @@ export const useTrainingStore = create<TrainingStore>((set, get) => ({
   },
 
   resetExercise: () => {
+    restartLine(get().currentExercise);
     set({ 
       exerciseResult: null, 
+      postMortem: null,
+      linePosition: null,
       selectedSquare: null,
       hintsUsed: 0
     });
   },
 
   endSession: () => {
+    discardSnapshot('training', 'current');
+    finishStoredSession(get().sessionId);
     set({
       session: null,
+      sessionId: null,
       currentExerciseIndex: 0,
       currentExercise: null,
       exerciseResult: null,
+      wrongMoves: [],
+      postMortem: null,
+      linePosition: null,
       selectedSquare: null,
     });
   },
+
+  restoreSession: (snapshot: TrainingSnapshot) => {
+    set({
+      session: snapshot.session,
+      sessionId: snapshot.sessionId ?? null,
+      exerciseStartedAt: Date.now(),
+      currentExerciseIndex: snapshot.currentExerciseIndex,
+      currentExercise: snapshot.session.exercises[snapshot.currentExerciseIndex] || null,
+      exerciseResult: null,
+      wrongMoves: [],
+      postMortem: null,
+      linePosition: null,
+      selectedSquare: null,
+      score: snapshot.score,
+      streak: snapshot.streak,
+      hintsUsed: 0,
+    });
+  },
 }));
+
+// Autosave training progress so a session survives a crash
+registerSnapshotSource(() => {
+  const { session, sessionId, currentExerciseIndex, score, streak } = useTrainingStore.getState();
+  if (!session) return null;
+  const payload: TrainingSnapshot = { session, currentExerciseIndex, score, streak, sessionId };
+  return { kind: 'training', sessionKey: 'current', payload };
+});
+
+// Let the coach see the exercise on screen, how far into its line the player is, what it
+// asks for and what has been tried
+useTrainingStore.subscribe((state, prev) => {
+  if (
+    state.currentExercise === prev.currentExercise &&
+    state.wrongMoves === prev.wrongMoves &&
+    state.linePosition === prev.linePosition
+  ) {
+    return;
+  }
+  const exercise = state.currentExercise;
+  reportBoard(
+    exercise
+      ? {
+          fen: state.linePosition?.fen ?? exercise.fen,
+          start_fen: exercise.fen,
+          player_color: sideToMove(exercise.fen),
+          exercise_goal: `${exercise.title}: ${exercise.description}`,
+          exercise_id: exercise.id,
+          wrong_moves: state.wrongMoves,
+          exercise_started_at: state.exerciseStartedAt,
+        }
+      : null
+  );
+});
@@ -36,7 +36,7 @@ interface UserStore {
 
   // Actions
   checkOnboarding: () => Promise<boolean>;
-  createProfile: (name: string, level: string) => Promise<void>;
+  finishOnboarding: () => Promise<void>;
   loadProfile: () => Promise<void>;
   loadStats: () => Promise<void>;
   updateElo: (newElo: number, result: string) => Promise<void>;
@@ -63,10 +63,10 @@ export const useUserStore = create<UserStore>((set, get) => ({
     }
   },
 
-  createProfile: async (name: string, level: string) => {
+  finishOnboarding: async () => {
     set({ loading: true, error: null });
     try {
-      const profile = await invoke<UserProfile>('create_user_profile', { name, initialLevel: level });
+      const profile = await invoke<UserProfile>('finish_onboarding');
       set({ profile, hasOnboarded: true, loading: false });
     } catch (err) {
       set({ error: String(err), loading: false });